@@ -0,0 +1,366 @@
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::{quote_location, quote_value, require_element};
+
+/// Candidate H.264 encoders and how they take bitrate and GOP settings
+const VIDEO_ENCODERS: &[VideoEncoder] = &[
+    VideoEncoder {
+        factory: "x264enc",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        gop_property: "key-int-max",
+    },
+    VideoEncoder {
+        factory: "openh264enc",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1000,
+        gop_property: "gop-size",
+    },
+    VideoEncoder {
+        factory: "vah264enc",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        gop_property: "key-int-max",
+    },
+    VideoEncoder {
+        factory: "nvh264enc",
+        bitrate_property: "bitrate",
+        bitrate_scale: 1,
+        gop_property: "gop-size",
+    },
+];
+
+/// AAC encoders; all of them take the bitrate in bit/s
+const AUDIO_ENCODERS: &[&str] = &["fdkaacenc", "avenc_aac", "voaacenc", "faac"];
+
+/// Segment length when none is given
+pub const DEFAULT_SEGMENT_SECONDS: u32 = 6;
+
+/// Audio bitrate shared by all renditions when none is given
+pub const DEFAULT_AUDIO_KBPS: u32 = 128;
+
+/// Frame rate assumed for GOP sizes when the input's is unknown
+const FALLBACK_FRAMERATE: f64 = 30.0;
+
+/// Name of the HLS master playlist written next to the variant directories
+pub const MASTER_PLAYLIST: &str = "master.m3u8";
+
+/// Name of the DASH manifest written by dashsink
+pub const DASH_MANIFEST: &str = "manifest.mpd";
+
+#[derive(Debug, Clone, Copy)]
+pub struct VideoEncoder {
+    pub factory: &'static str,
+    bitrate_property: &'static str,
+    /// Multiplier from kbit/s to the unit of the bitrate property
+    bitrate_scale: u32,
+    gop_property: &'static str,
+}
+
+impl VideoEncoder {
+    fn settings(&self, kbps: u32, gop_frames: u32) -> String {
+        format!(
+            "{} {}={} {}={}",
+            self.factory,
+            self.bitrate_property,
+            kbps * self.bitrate_scale,
+            self.gop_property,
+            gop_frames
+        )
+    }
+}
+
+/// Packaging format of the ladder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AbrFormat {
+    /// One hlssink2 per rendition plus a master playlist
+    #[default]
+    Hls,
+    /// A single dashsink writing every rendition and the MPD
+    Dash,
+}
+
+/// One quality level of the ladder
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct AbrRendition {
+    #[schemars(description = "Directory name of the rendition (default: '<height>p')")]
+    pub name: Option<String>,
+    #[schemars(description = "Output width in pixels")]
+    pub width: u32,
+    #[schemars(description = "Output height in pixels")]
+    pub height: u32,
+    #[schemars(description = "Video bitrate in kbit/s")]
+    pub video_kbps: u32,
+}
+
+impl AbrRendition {
+    fn new(width: u32, height: u32, video_kbps: u32) -> Self {
+        Self {
+            name: None,
+            width,
+            height,
+            video_kbps,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("{}p", self.height))
+    }
+}
+
+/// Ladder used when no renditions are given
+pub fn default_ladder() -> Vec<AbrRendition> {
+    vec![
+        AbrRendition::new(1920, 1080, 5000),
+        AbrRendition::new(1280, 720, 2800),
+        AbrRendition::new(854, 480, 1400),
+        AbrRendition::new(640, 360, 800),
+    ]
+}
+
+/// Everything needed to encode a ladder
+#[derive(Debug, Clone)]
+pub struct AbrLadder {
+    pub format: AbrFormat,
+    pub renditions: Vec<AbrRendition>,
+    pub audio_kbps: Option<u32>,
+    pub segment_seconds: u32,
+    /// Frame rate of the input, if known
+    pub framerate: Option<f64>,
+}
+
+impl AbrLadder {
+    fn gop_frames(&self) -> u32 {
+        let framerate = self.framerate.unwrap_or(FALLBACK_FRAMERATE);
+        ((framerate * self.segment_seconds as f64).round() as u32).max(1)
+    }
+
+    /// Drop renditions larger than the source and check the rest
+    pub fn validate(&mut self, source_height: Option<u32>) -> McpResult<()> {
+        if let Some(source_height) = source_height {
+            let smallest = self.renditions.iter().map(|r| r.height).min();
+            // Keep at least the smallest rendition for low resolution sources
+            self.renditions
+                .retain(|r| r.height <= source_height || Some(r.height) == smallest);
+        }
+        if self.renditions.is_empty() {
+            return Err(GStreamerMcpError::Other(
+                "The ladder needs at least one rendition".to_string(),
+            ));
+        }
+        if self.segment_seconds == 0 {
+            return Err(GStreamerMcpError::Other(
+                "Segment duration must be at least 1 second".to_string(),
+            ));
+        }
+        let mut names: Vec<String> = self.renditions.iter().map(AbrRendition::name).collect();
+        names.sort();
+        if let Some(pair) = names.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(GStreamerMcpError::Other(format!(
+                "Rendition name '{}' is used twice",
+                pair[0]
+            )));
+        }
+        for rendition in &self.renditions {
+            if rendition.width == 0 || rendition.height == 0 || rendition.video_kbps == 0 {
+                return Err(GStreamerMcpError::Other(format!(
+                    "Rendition '{}' needs a non-zero size and bitrate",
+                    rendition.name()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pick the installed encoders for a ladder
+pub fn ladder_encoders(with_audio: bool) -> McpResult<(VideoEncoder, Option<&'static str>)> {
+    let factories: Vec<&'static str> = VIDEO_ENCODERS.iter().map(|e| e.factory).collect();
+    let factory = require_element(&factories, "H.264 encoder")?;
+    let video = VIDEO_ENCODERS
+        .iter()
+        .copied()
+        .find(|e| e.factory == factory)
+        .expect("encoder comes from VIDEO_ENCODERS");
+    let audio = if with_audio {
+        Some(require_element(AUDIO_ENCODERS, "AAC encoder")?)
+    } else {
+        None
+    };
+    Ok((video, audio))
+}
+
+/// gst-launch description decoding `uri` once and encoding every rendition
+/// of `ladder` into `output_dir`. Audio is encoded once and shared;
+/// `video_filters` and `audio_filters`, such as deinterlacing or a downmix,
+/// run on the decoded streams first and end in ` ! `.
+pub fn ladder_description(
+    uri: &str,
+    output_dir: &Path,
+    ladder: &AbrLadder,
+    video_encoder: VideoEncoder,
+    audio_encoder: Option<&str>,
+    video_filters: &str,
+    audio_filters: &str,
+) -> String {
+    let gop = ladder.gop_frames();
+    let mut parts = vec![
+        format!("uridecodebin uri={} name=d", quote_value(uri)),
+        format!(
+            "d. ! video/x-raw ! queue ! {}videoconvert ! tee name=vt",
+            video_filters
+        ),
+    ];
+    let audio = ladder.audio_kbps.zip(audio_encoder);
+    if let Some((kbps, encoder)) = audio {
+        parts.push(format!(
+            "d. ! audio/x-raw ! queue ! {}audioconvert ! audioresample ! {} bitrate={} ! aacparse ! tee name=at",
+            audio_filters,
+            encoder,
+            kbps * 1000
+        ));
+    }
+
+    if ladder.format == AbrFormat::Dash {
+        parts.push(format!(
+            "dashsink name=dash mpd-root-path={} mpd-filename={} target-duration={} muxer=ts",
+            quote_location(output_dir),
+            DASH_MANIFEST,
+            ladder.segment_seconds
+        ));
+    }
+
+    for (i, rendition) in ladder.renditions.iter().enumerate() {
+        let sink = match ladder.format {
+            AbrFormat::Hls => {
+                let dir = output_dir.join(rendition.name());
+                parts.push(format!(
+                    "hlssink2 name=hls{} location={} playlist-location={} target-duration={} max-files=0 playlist-length=0",
+                    i,
+                    quote_location(&dir.join("segment%05d.ts")),
+                    quote_location(&dir.join("playlist.m3u8")),
+                    ladder.segment_seconds
+                ));
+                format!("hls{}.video", i)
+            }
+            AbrFormat::Dash => format!("dash.video_{}", i),
+        };
+        parts.push(format!(
+            "vt. ! queue ! videoscale add-borders=true ! video/x-raw,width={},height={},pixel-aspect-ratio=1/1 ! {} ! h264parse ! {}",
+            rendition.width,
+            rendition.height,
+            video_encoder.settings(rendition.video_kbps, gop),
+            sink
+        ));
+        if audio.is_some() {
+            let sink = match ladder.format {
+                AbrFormat::Hls => format!("hls{}.audio", i),
+                AbrFormat::Dash if i == 0 => "dash.audio_0".to_string(),
+                // DASH shares one audio adaptation set between renditions
+                AbrFormat::Dash => continue,
+            };
+            parts.push(format!("at. ! queue ! {}", sink));
+        }
+    }
+
+    parts.join(" ")
+}
+
+/// HLS master playlist pointing at the playlist of every rendition
+pub fn master_playlist(ladder: &AbrLadder) -> String {
+    let audio_kbps = ladder.audio_kbps.unwrap_or(0);
+    let mut playlist = String::from("#EXTM3U\n#EXT-X-VERSION:3\n");
+    for rendition in &ladder.renditions {
+        playlist.push_str(&format!(
+            "#EXT-X-STREAM-INF:BANDWIDTH={},RESOLUTION={}x{}\n{}/playlist.m3u8\n",
+            (rendition.video_kbps + audio_kbps) * 1000,
+            rendition.width,
+            rendition.height,
+            rendition.name()
+        ));
+    }
+    playlist
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ladder(format: AbrFormat) -> AbrLadder {
+        AbrLadder {
+            format,
+            renditions: vec![
+                AbrRendition::new(1280, 720, 2800),
+                AbrRendition::new(640, 360, 800),
+            ],
+            audio_kbps: Some(128),
+            segment_seconds: 4,
+            framerate: Some(25.0),
+        }
+    }
+
+    #[test]
+    fn test_master_playlist() {
+        assert_eq!(
+            master_playlist(&ladder(AbrFormat::Hls)),
+            "#EXTM3U\n#EXT-X-VERSION:3\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=2928000,RESOLUTION=1280x720\n720p/playlist.m3u8\n\
+             #EXT-X-STREAM-INF:BANDWIDTH=928000,RESOLUTION=640x360\n360p/playlist.m3u8\n"
+        );
+    }
+
+    #[test]
+    fn test_ladder_description() {
+        let description = ladder_description(
+            "file:///in.mp4",
+            Path::new("/out"),
+            &ladder(AbrFormat::Hls),
+            VIDEO_ENCODERS[0],
+            Some("avenc_aac"),
+            "",
+            "",
+        );
+        assert!(description.contains("avenc_aac bitrate=128000 ! aacparse ! tee name=at"));
+        assert!(description.contains(
+            "video/x-raw,width=640,height=360,pixel-aspect-ratio=1/1 ! x264enc bitrate=800 key-int-max=100 ! h264parse ! hls1.video"
+        ));
+        assert!(description.contains("location=\"/out/720p/segment%05d.ts\""));
+        assert!(description.contains("at. ! queue ! hls1.audio"));
+        assert!(!description.contains("deinterlace"));
+
+        let description = ladder_description(
+            "file:///in.mp4",
+            Path::new("/out"),
+            &ladder(AbrFormat::Dash),
+            VIDEO_ENCODERS[1],
+            Some("avenc_aac"),
+            "deinterlace ! ",
+            "audioconvert ! audio/x-raw,channels=2 ! ",
+        );
+        assert!(description
+            .contains("openh264enc bitrate=2800000 gop-size=100 ! h264parse ! dash.video_0"));
+        assert_eq!(description.matches("dash.audio_").count(), 1);
+        assert!(description.contains("queue ! deinterlace ! videoconvert ! tee name=vt"));
+        assert!(description.contains("channels=2 ! audioconvert ! audioresample ! avenc_aac"));
+    }
+
+    #[test]
+    fn test_validate_drops_upscaled_renditions() {
+        let mut ladder = AbrLadder {
+            renditions: default_ladder(),
+            ..ladder(AbrFormat::Hls)
+        };
+        ladder.validate(Some(720)).unwrap();
+        let names: Vec<String> = ladder.renditions.iter().map(AbrRendition::name).collect();
+        assert_eq!(names, ["720p", "480p", "360p"]);
+
+        ladder.validate(Some(240)).unwrap();
+        assert_eq!(ladder.renditions.len(), 1);
+    }
+}
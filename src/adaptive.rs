@@ -0,0 +1,283 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// Properties exposed by hlsdemux/dashdemux (adaptivedemux) and their
+/// adaptivedemux2 counterparts that are worth reporting
+const STAT_PROPERTIES: &[&str] = &[
+    "connection-bitrate",
+    "connection-speed",
+    "current-bandwidth",
+    "bandwidth-target-ratio",
+    "bitrate-limit",
+    "min-bitrate",
+    "max-bitrate",
+    "start-bitrate",
+    "current-level-time-video",
+    "current-level-time-audio",
+    "max-buffering-time",
+    "high-watermark-time",
+    "low-watermark-time",
+    "max-video-width",
+    "max-video-height",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveStats {
+    pub element: String,
+    pub factory: String,
+    /// Negotiated caps on the demuxer source pads, describing the active variant
+    pub current_variant: Vec<String>,
+    /// Estimated download bandwidth in bits per second
+    pub bandwidth_estimate: Option<u64>,
+    /// Buffered video (or audio) data in nanoseconds
+    pub buffer_level_ns: Option<u64>,
+    pub properties: BTreeMap<String, String>,
+}
+
+/// Find all adaptive streaming demuxers (HLS, DASH, MSS) in a pipeline
+pub fn find_adaptive_demuxers(pipeline: &gst::Pipeline) -> Vec<gst::Element> {
+    pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|element| {
+            element
+                .factory()
+                .map(|f| {
+                    let klass = f.klass();
+                    klass.contains("Demuxer") && klass.contains("Adaptive")
+                })
+                .unwrap_or(false)
+        })
+        .collect()
+}
+
+fn select_demuxers(
+    pipeline: &gst::Pipeline,
+    element_name: Option<&str>,
+) -> McpResult<Vec<gst::Element>> {
+    let demuxers = find_adaptive_demuxers(pipeline);
+    let demuxers: Vec<gst::Element> = match element_name {
+        Some(name) => demuxers.into_iter().filter(|e| e.name() == name).collect(),
+        None => demuxers,
+    };
+
+    if demuxers.is_empty() {
+        return Err(GStreamerMcpError::PipelineError(match element_name {
+            Some(name) => format!("No adaptive demuxer named '{}' in pipeline", name),
+            None => "Pipeline does not contain an adaptive demuxer (hlsdemux, dashdemux, ...)"
+                .to_string(),
+        }));
+    }
+
+    Ok(demuxers)
+}
+
+fn read_u64_property(element: &gst::Element, name: &str) -> Option<u64> {
+    let pspec = element.find_property(name)?;
+    if !pspec.flags().contains(gst::glib::ParamFlags::READABLE) {
+        return None;
+    }
+    let value = element.property_value(name);
+    if let Ok(v) = value.get::<u64>() {
+        Some(v)
+    } else if let Ok(v) = value.get::<u32>() {
+        Some(v as u64)
+    } else if let Ok(v) = value.get::<i64>() {
+        u64::try_from(v).ok()
+    } else if let Ok(v) = value.get::<i32>() {
+        u64::try_from(v).ok()
+    } else {
+        None
+    }
+}
+
+fn set_u64_property(element: &gst::Element, name: &str, value: u64) -> McpResult<()> {
+    let pspec = element.find_property(name).ok_or_else(|| {
+        GStreamerMcpError::PropertyError(format!(
+            "Element '{}' has no property '{}'",
+            element.name(),
+            name
+        ))
+    })?;
+
+    let type_ = pspec.value_type();
+    let out_of_range = |reason: String| {
+        GStreamerMcpError::PropertyError(format!(
+            "Value {} is out of range for property '{}': {}",
+            value, name, reason
+        ))
+    };
+
+    let value = if type_ == u32::static_type() {
+        u32::try_from(value)
+            .map_err(|e| out_of_range(e.to_string()))?
+            .to_value()
+    } else if type_ == u64::static_type() {
+        value.to_value()
+    } else if type_ == i32::static_type() {
+        i32::try_from(value)
+            .map_err(|e| out_of_range(e.to_string()))?
+            .to_value()
+    } else if type_ == i64::static_type() {
+        i64::try_from(value)
+            .map_err(|e| out_of_range(e.to_string()))?
+            .to_value()
+    } else {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "Property '{}' has unsupported type {}",
+            name,
+            type_.name()
+        )));
+    };
+    crate::properties::check_value(&pspec, &value).map_err(out_of_range)?;
+    element.set_property_from_value(name, &value);
+
+    Ok(())
+}
+
+/// Download bandwidth in bits/s from what a demuxer reports: adaptivedemux2
+/// measures it, the legacy demuxers only know the configured connection
+/// speed in kbps. Zero means unknown.
+fn bandwidth_estimate(
+    current_bandwidth: Option<u64>,
+    connection_bitrate: Option<u64>,
+    connection_speed_kbps: Option<u64>,
+) -> Option<u64> {
+    current_bandwidth
+        .or(connection_bitrate)
+        .or_else(|| connection_speed_kbps.map(|kbps| kbps.saturating_mul(1000)))
+        .filter(|bps| *bps > 0)
+}
+
+fn collect_stats(element: &gst::Element) -> AdaptiveStats {
+    let mut properties = BTreeMap::new();
+    for name in STAT_PROPERTIES {
+        if let Some(pspec) = element.find_property(name) {
+            if pspec.flags().contains(gst::glib::ParamFlags::READABLE) {
+                let value = element.property_value(name);
+                let text = value
+                    .serialize()
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|_| format!("{:?}", value));
+                properties.insert(name.to_string(), text);
+            }
+        }
+    }
+
+    let current_variant = element
+        .src_pads()
+        .iter()
+        .filter_map(|pad| pad.current_caps())
+        .map(|caps| caps.to_string())
+        .collect();
+
+    let bandwidth_estimate = bandwidth_estimate(
+        read_u64_property(element, "current-bandwidth"),
+        read_u64_property(element, "connection-bitrate"),
+        read_u64_property(element, "connection-speed"),
+    );
+
+    let buffer_level_ns = read_u64_property(element, "current-level-time-video")
+        .or_else(|| read_u64_property(element, "current-level-time-audio"));
+
+    AdaptiveStats {
+        element: element.name().to_string(),
+        factory: element
+            .factory()
+            .map(|f| f.name().to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
+        current_variant,
+        bandwidth_estimate,
+        buffer_level_ns,
+        properties,
+    }
+}
+
+/// Collect statistics from the adaptive demuxers of a pipeline
+pub fn get_adaptive_stats(
+    pipeline: &gst::Pipeline,
+    element_name: Option<&str>,
+) -> McpResult<Vec<AdaptiveStats>> {
+    Ok(select_demuxers(pipeline, element_name)?
+        .iter()
+        .map(collect_stats)
+        .collect())
+}
+
+/// Pin the adaptive demuxers to the variant matching `bitrate` (bits/s).
+/// Passing `None` or `0` restores automatic variant selection.
+pub fn set_variant(
+    pipeline: &gst::Pipeline,
+    element_name: Option<&str>,
+    bitrate: Option<u64>,
+) -> McpResult<Vec<AdaptiveStats>> {
+    let bitrate = bitrate.unwrap_or(0);
+    let demuxers = select_demuxers(pipeline, element_name)?;
+
+    for demux in &demuxers {
+        if demux.find_property("connection-bitrate").is_some() {
+            set_u64_property(demux, "connection-bitrate", bitrate)?;
+        } else if demux.find_property("connection-speed").is_some() {
+            set_u64_property(demux, "connection-speed", bitrate / 1000)?;
+        } else {
+            return Err(GStreamerMcpError::PropertyError(format!(
+                "Element '{}' does not support variant selection",
+                demux.name()
+            )));
+        }
+
+        // Constrain the selection window so the demuxer cannot switch away
+        for name in ["min-bitrate", "max-bitrate"] {
+            if demux.find_property(name).is_some() {
+                set_u64_property(demux, name, bitrate)?;
+            }
+        }
+    }
+
+    Ok(demuxers.iter().map(collect_stats).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bandwidth_estimate() {
+        assert_eq!(
+            bandwidth_estimate(Some(4_000_000), Some(0), None),
+            Some(4_000_000)
+        );
+        assert_eq!(
+            bandwidth_estimate(None, Some(2_500_000), None),
+            Some(2_500_000)
+        );
+        assert_eq!(bandwidth_estimate(None, None, Some(1500)), Some(1_500_000));
+        assert_eq!(bandwidth_estimate(Some(0), None, None), None);
+        assert_eq!(bandwidth_estimate(None, None, Some(0)), None);
+        assert_eq!(bandwidth_estimate(None, None, None), None);
+    }
+
+    #[test]
+    fn test_without_adaptive_demuxer() {
+        gst::init().unwrap();
+        let pipeline = gst::parse::launch("videotestsrc name=src num-buffers=1 ! fakesink")
+            .unwrap()
+            .downcast::<gst::Pipeline>()
+            .unwrap();
+        assert!(find_adaptive_demuxers(&pipeline).is_empty());
+        assert!(get_adaptive_stats(&pipeline, None).is_err());
+        assert!(set_variant(&pipeline, Some("src"), Some(1_000_000)).is_err());
+
+        // Values are checked against the property's own range
+        let src = pipeline.by_name("src").unwrap();
+        assert!(set_u64_property(&src, "num-buffers", u64::MAX).is_err());
+        assert!(set_u64_property(&src, "blocksize", 0).is_err());
+        set_u64_property(&src, "num-buffers", 5).unwrap();
+        assert_eq!(read_u64_property(&src, "num-buffers"), Some(5));
+    }
+}
@@ -0,0 +1,514 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::color::{color_info, ColorInfo};
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::quote_location;
+use crate::suggestions::parse_launch;
+
+/// Keyframe timestamps listed in a GOP analysis; longer files report counts only
+const MAX_LISTED_KEYFRAMES: usize = 500;
+
+/// GOPs longer than this make seeking noticeably slow
+const SLOW_SEEK_GOP_SECONDS: f64 = 5.0;
+
+/// A compressed stream of a file, numbered in the order the demuxer exposed it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScannedStream {
+    pub index: usize,
+    pub caps: String,
+}
+
+impl ScannedStream {
+    pub fn media_type(&self) -> &str {
+        self.caps.split(',').next().unwrap_or_default()
+    }
+
+    pub fn is_video(&self) -> bool {
+        self.media_type().starts_with("video/")
+    }
+}
+
+/// Demux and parse every stream of a file without decoding, passing each
+/// buffer to `on_buffer` from a streaming thread. Blocks until the whole
+/// file has been read and returns the streams found.
+pub fn scan_streams(
+    input: &Path,
+    on_buffer: impl Fn(&ScannedStream, &gst::BufferRef) + Send + Sync + 'static,
+) -> McpResult<Vec<ScannedStream>> {
+    let description = format!(
+        "filesrc location={} ! parsebin name=parse",
+        quote_location(input)
+    );
+    let pipeline = parse_launch(&description, "Failed to build scan pipeline")?
+        .downcast::<gst::Pipeline>()
+        .map_err(|_| GStreamerMcpError::PipelineError("Expected a pipeline".to_string()))?;
+    let parse = pipeline
+        .by_name("parse")
+        .ok_or_else(|| GStreamerMcpError::PipelineError("parsebin not found".to_string()))?;
+
+    let streams = Arc::new(Mutex::new(Vec::<ScannedStream>::new()));
+    let on_buffer = Arc::new(on_buffer);
+    {
+        let streams = streams.clone();
+        let pipeline_weak = pipeline.downgrade();
+        parse.connect_pad_added(move |_, pad| {
+            let Some(pipeline) = pipeline_weak.upgrade() else {
+                return;
+            };
+            let Ok(sink) = gst::ElementFactory::make("fakesink")
+                .property("sync", false)
+                .property("signal-handoffs", true)
+                .build()
+            else {
+                return;
+            };
+            let stream = {
+                let mut streams = streams.lock();
+                let stream = ScannedStream {
+                    index: streams.len(),
+                    caps: pad
+                        .current_caps()
+                        .unwrap_or_else(|| pad.query_caps(None))
+                        .to_string(),
+                };
+                streams.push(stream.clone());
+                stream
+            };
+            let on_buffer = on_buffer.clone();
+            sink.connect("handoff", false, move |values| {
+                let buffer = values.get(1).and_then(|v| v.get::<gst::Buffer>().ok())?;
+                on_buffer(&stream, &buffer);
+                None
+            });
+            if pipeline.add(&sink).is_ok() {
+                let _ = sink.sync_state_with_parent();
+                if let Some(sink_pad) = sink.static_pad("sink") {
+                    let _ = pad.link(&sink_pad);
+                }
+            }
+        });
+    }
+
+    let bus = pipeline
+        .bus()
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Pipeline has no bus".to_string()))?;
+    let result =
+        match pipeline.set_state(gst::State::Playing) {
+            Err(_) => Err(GStreamerMcpError::PipelineError(format!(
+                "Failed to open {}",
+                input.display()
+            ))),
+            Ok(_) => match bus
+                .timed_pop_filtered(
+                    gst::ClockTime::NONE,
+                    &[gst::MessageType::Eos, gst::MessageType::Error],
+                )
+                .as_ref()
+                .map(|msg| msg.view())
+            {
+                Some(gst::MessageView::Error(err)) => Err(GStreamerMcpError::PipelineError(
+                    format!("Failed to read {}: {}", input.display(), err.error()),
+                )),
+                _ => Ok(()),
+            },
+        };
+    let _ = pipeline.set_state(gst::State::Null);
+    result?;
+
+    let streams = streams.lock().clone();
+    Ok(streams)
+}
+
+/// A compressed video frame in decode order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameInfo {
+    pub pts_ns: Option<u64>,
+    pub keyframe: bool,
+}
+
+/// Keyframe structure of a video stream
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GopAnalysis {
+    pub caps: String,
+    pub frames: u64,
+    pub keyframes: u64,
+    /// Presentation timestamps of the keyframes, the first
+    /// [`MAX_LISTED_KEYFRAMES`] only
+    pub keyframe_timestamps_ns: Vec<u64>,
+    /// Number of GOPs of each length in frames
+    pub gop_length_distribution: BTreeMap<u64, u64>,
+    pub min_gop_frames: u64,
+    pub max_gop_frames: u64,
+    pub average_gop_frames: f64,
+    pub min_gop_seconds: f64,
+    pub max_gop_seconds: f64,
+    /// Frames presented before a frame decoded earlier: B-frames
+    pub b_frames: u64,
+    /// Hints about seeking and segmenting
+    pub hints: Vec<String>,
+    /// Colorimetry and HDR metadata of the stream, when its caps carry them
+    #[serde(default)]
+    pub color: Option<ColorInfo>,
+}
+
+/// Analyze the GOPs of a stream from its frames in decode order
+pub fn analyze_gops(caps: &str, frames: &[FrameInfo]) -> GopAnalysis {
+    let mut analysis = GopAnalysis {
+        caps: caps.to_string(),
+        frames: frames.len() as u64,
+        ..Default::default()
+    };
+
+    let mut gop_lengths = Vec::new();
+    let mut keyframe_pts = Vec::new();
+    let mut current: Option<u64> = None;
+    let mut max_pts: Option<u64> = None;
+    for frame in frames {
+        if frame.keyframe {
+            if let Some(length) = current.replace(0) {
+                gop_lengths.push(length);
+            }
+            keyframe_pts.extend(frame.pts_ns);
+        }
+        if let Some(length) = current.as_mut() {
+            *length += 1;
+        }
+        if let Some(pts) = frame.pts_ns {
+            if max_pts.is_some_and(|max| pts < max) {
+                analysis.b_frames += 1;
+            }
+            max_pts = max_pts.max(Some(pts));
+        }
+    }
+    gop_lengths.extend(current);
+
+    analysis.keyframes = frames.iter().filter(|f| f.keyframe).count() as u64;
+    for length in &gop_lengths {
+        *analysis.gop_length_distribution.entry(*length).or_default() += 1;
+    }
+    analysis.min_gop_frames = gop_lengths.iter().copied().min().unwrap_or(0);
+    analysis.max_gop_frames = gop_lengths.iter().copied().max().unwrap_or(0);
+    if !gop_lengths.is_empty() {
+        analysis.average_gop_frames =
+            gop_lengths.iter().sum::<u64>() as f64 / gop_lengths.len() as f64;
+    }
+
+    let gop_seconds: Vec<f64> = keyframe_pts
+        .windows(2)
+        .map(|w| w[1].saturating_sub(w[0]) as f64 / 1e9)
+        .collect();
+    analysis.min_gop_seconds = gop_seconds.iter().copied().fold(f64::INFINITY, f64::min);
+    analysis.max_gop_seconds = gop_seconds.iter().copied().fold(0.0, f64::max);
+    if gop_seconds.is_empty() {
+        analysis.min_gop_seconds = 0.0;
+    }
+
+    if analysis.keyframes == 0 {
+        analysis
+            .hints
+            .push("The stream has no keyframes; it cannot be seeked or segmented".to_string());
+    } else if frames.first().is_some_and(|f| !f.keyframe) {
+        analysis.hints.push(
+            "The stream does not start with a keyframe; the first frames cannot be decoded"
+                .to_string(),
+        );
+    }
+    if analysis.max_gop_seconds > SLOW_SEEK_GOP_SECONDS {
+        analysis.hints.push(format!(
+            "The longest GOP is {:.1} s; an accurate seek may decode that much video",
+            analysis.max_gop_seconds
+        ));
+    }
+    if gop_seconds.len() > 1 && analysis.max_gop_seconds - analysis.min_gop_seconds > 0.1 {
+        analysis.hints.push(format!(
+            "GOP durations vary from {:.2} to {:.2} s; HLS segments cut on keyframes will not have a fixed duration",
+            analysis.min_gop_seconds, analysis.max_gop_seconds
+        ));
+    }
+
+    keyframe_pts.truncate(MAX_LISTED_KEYFRAMES);
+    analysis.keyframe_timestamps_ns = keyframe_pts;
+    analysis
+}
+
+/// Report the keyframe structure of the first video stream of a file. The
+/// stream is parsed, not decoded, so this is fast even for long files.
+pub fn analyze_file_gops(input: &Path) -> McpResult<GopAnalysis> {
+    let frames = Arc::new(Mutex::new(BTreeMap::<usize, Vec<FrameInfo>>::new()));
+    let streams = {
+        let frames = frames.clone();
+        scan_streams(input, move |stream, buffer| {
+            if stream.is_video() {
+                frames
+                    .lock()
+                    .entry(stream.index)
+                    .or_default()
+                    .push(FrameInfo {
+                        pts_ns: buffer.pts().map(|t| t.nseconds()),
+                        keyframe: !buffer.flags().contains(gst::BufferFlags::DELTA_UNIT),
+                    });
+            }
+        })?
+    };
+
+    let stream = streams.iter().find(|s| s.is_video()).ok_or_else(|| {
+        GStreamerMcpError::PipelineError(format!("{} has no video stream", input.display()))
+    })?;
+    let frames = frames.lock().remove(&stream.index).unwrap_or_default();
+    let mut analysis = analyze_gops(&stream.caps, &frames);
+    analysis.color = stream
+        .caps
+        .parse::<gst::Caps>()
+        .ok()
+        .and_then(|caps| color_info(&caps));
+    Ok(analysis)
+}
+
+/// Bitrate over time of one stream of a file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamBitrate {
+    pub index: usize,
+    pub caps: String,
+    pub bytes: u64,
+    pub average_bitrate: u64,
+    pub peak_bitrate: u64,
+    /// Start of the interval with the peak bitrate
+    pub peak_at_ns: u64,
+    /// Bitrate of each interval in bits per second
+    pub samples: Vec<u64>,
+}
+
+/// Bitrate over time of every stream of a file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BitrateProfile {
+    pub interval_ms: u64,
+    pub streams: Vec<StreamBitrate>,
+    /// Combined bitrate of all streams in each interval
+    pub total_samples: Vec<u64>,
+    /// Intervals whose combined bitrate exceeds the target, as start times
+    pub over_target_ns: Vec<u64>,
+    pub recommendations: Vec<String>,
+}
+
+/// Bits per second of each interval, from buffer timestamps and sizes
+pub fn bucket_bitrate(buffers: &[(u64, u64)], interval_ns: u64) -> Vec<u64> {
+    let Some(last) = buffers.iter().map(|(ts, _)| *ts).max() else {
+        return Vec::new();
+    };
+    let mut bytes = vec![0u64; (last / interval_ns) as usize + 1];
+    for (ts, size) in buffers {
+        bytes[(ts / interval_ns) as usize] += size;
+    }
+    bytes
+        .into_iter()
+        .map(|b| (b as f64 * 8.0 * 1e9 / interval_ns as f64) as u64)
+        .collect()
+}
+
+/// Build a bitrate profile from the timestamped buffer sizes of each stream
+pub fn profile_bitrate(
+    streams: &[(ScannedStream, Vec<(u64, u64)>)],
+    interval_ns: u64,
+    target_bitrate: Option<u64>,
+) -> BitrateProfile {
+    let mut profile = BitrateProfile {
+        interval_ms: interval_ns / 1_000_000,
+        ..Default::default()
+    };
+    for (stream, buffers) in streams {
+        let samples = bucket_bitrate(buffers, interval_ns);
+        let bytes: u64 = buffers.iter().map(|(_, size)| size).sum();
+        let (peak_index, peak_bitrate) = samples
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by_key(|(_, bps)| *bps)
+            .unwrap_or_default();
+        let first = buffers.iter().map(|(ts, _)| *ts).min().unwrap_or(0);
+        let last = buffers.iter().map(|(ts, _)| *ts).max().unwrap_or(0);
+        let span = (last - first).max(interval_ns);
+        if profile.total_samples.len() < samples.len() {
+            profile.total_samples.resize(samples.len(), 0);
+        }
+        for (total, bps) in profile.total_samples.iter_mut().zip(&samples) {
+            *total += bps;
+        }
+        profile.streams.push(StreamBitrate {
+            index: stream.index,
+            caps: stream.caps.clone(),
+            bytes,
+            average_bitrate: (bytes as f64 * 8.0 * 1e9 / span as f64) as u64,
+            peak_bitrate,
+            peak_at_ns: peak_index as u64 * interval_ns,
+            samples,
+        });
+    }
+
+    let total_average = profile
+        .streams
+        .iter()
+        .map(|s| s.average_bitrate)
+        .sum::<u64>();
+    let total_peak = profile.total_samples.iter().copied().max().unwrap_or(0);
+    if let Some(target) = target_bitrate {
+        profile.over_target_ns = profile
+            .total_samples
+            .iter()
+            .enumerate()
+            .filter(|(_, bps)| **bps > target)
+            .map(|(i, _)| i as u64 * interval_ns)
+            .collect();
+        if total_average > target {
+            profile.recommendations.push(format!(
+                "The average bitrate ({} kbps) is above the {} kbps target; re-encode at a lower bitrate",
+                total_average / 1000,
+                target / 1000
+            ));
+        } else if !profile.over_target_ns.is_empty() {
+            profile.recommendations.push(format!(
+                "{} interval(s) exceed the {} kbps target although the average ({} kbps) fits; re-encode with a capped maximum bitrate (VBV/HRD buffer) instead of unconstrained VBR",
+                profile.over_target_ns.len(),
+                target / 1000,
+                total_average / 1000
+            ));
+        }
+    }
+    if total_average > 0 && total_peak > total_average * 3 {
+        profile.recommendations.push(format!(
+            "Peaks reach {:.1}x the average bitrate; constrained VBR or CBR keeps streaming buffers from running dry",
+            total_peak as f64 / total_average as f64
+        ));
+    }
+    profile
+}
+
+/// Scan a file and measure the bitrate of each stream over time, from the
+/// timestamps of the compressed buffers
+pub fn bitrate_profile(
+    input: &Path,
+    interval_ns: u64,
+    target_bitrate: Option<u64>,
+) -> McpResult<BitrateProfile> {
+    let buffers = Arc::new(Mutex::new(BTreeMap::<usize, Vec<(u64, u64)>>::new()));
+    let streams = {
+        let buffers = buffers.clone();
+        scan_streams(input, move |stream, buffer| {
+            if let Some(ts) = buffer.pts().or(buffer.dts()) {
+                buffers
+                    .lock()
+                    .entry(stream.index)
+                    .or_default()
+                    .push((ts.nseconds(), buffer.size() as u64));
+            }
+        })?
+    };
+
+    let mut buffers = std::mem::take(&mut *buffers.lock());
+    // Timestamps of some containers, such as MPEG-TS, do not start at zero
+    let start = buffers
+        .values()
+        .flatten()
+        .map(|(ts, _)| *ts)
+        .min()
+        .unwrap_or(0);
+    for (ts, _) in buffers.values_mut().flatten() {
+        *ts -= start;
+    }
+    let streams: Vec<(ScannedStream, Vec<(u64, u64)>)> = streams
+        .into_iter()
+        .map(|stream| {
+            let stream_buffers = buffers.remove(&stream.index).unwrap_or_default();
+            (stream, stream_buffers)
+        })
+        .collect();
+    Ok(profile_bitrate(&streams, interval_ns, target_bitrate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(pts_ms: u64, keyframe: bool) -> FrameInfo {
+        FrameInfo {
+            pts_ns: Some(pts_ms * 1_000_000),
+            keyframe,
+        }
+    }
+
+    #[test]
+    fn test_analyze_gops() {
+        // I P B B, I P B B, I P: decode order with B-frames presented early
+        let frames = vec![
+            frame(0, true),
+            frame(120, false),
+            frame(40, false),
+            frame(80, false),
+            frame(160, true),
+            frame(280, false),
+            frame(200, false),
+            frame(240, false),
+            frame(320, true),
+            frame(360, false),
+        ];
+        let analysis = analyze_gops("video/x-h264", &frames);
+        assert_eq!(analysis.frames, 10);
+        assert_eq!(analysis.keyframes, 3);
+        assert_eq!(
+            analysis.keyframe_timestamps_ns,
+            vec![0, 160_000_000, 320_000_000]
+        );
+        assert_eq!(analysis.b_frames, 4);
+        assert_eq!(analysis.min_gop_frames, 2);
+        assert_eq!(analysis.max_gop_frames, 4);
+        assert_eq!(analysis.gop_length_distribution.get(&4), Some(&2));
+        assert!((analysis.max_gop_seconds - 0.16).abs() < 1e-9);
+        assert!(analysis.hints.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_gops_hints() {
+        let frames = vec![frame(0, false), frame(40, false)];
+        let analysis = analyze_gops("video/x-h264", &frames);
+        assert_eq!(analysis.keyframes, 0);
+        assert_eq!(analysis.min_gop_frames, 0);
+        assert_eq!(analysis.hints.len(), 1);
+
+        let frames = vec![frame(0, true), frame(2000, true), frame(10000, true)];
+        let analysis = analyze_gops("video/x-h264", &frames);
+        assert_eq!(analysis.hints.len(), 2);
+    }
+
+    #[test]
+    fn test_profile_bitrate() {
+        let second = 1_000_000_000;
+        let stream = |index| ScannedStream {
+            index,
+            caps: "video/x-h264".to_string(),
+        };
+        assert_eq!(
+            bucket_bitrate(&[(0, 1000), (second / 2, 1000), (2 * second, 500)], second),
+            vec![16000, 0, 4000]
+        );
+
+        let video = (0..10)
+            .map(|i| (i * second, if i == 5 { 100_000 } else { 10_000 }))
+            .collect();
+        let audio = (0..10).map(|i| (i * second, 1_000)).collect();
+        let profile = profile_bitrate(
+            &[(stream(0), video), (stream(1), audio)],
+            second,
+            Some(200_000),
+        );
+        assert_eq!(profile.streams[0].peak_bitrate, 800_000);
+        assert_eq!(profile.streams[0].peak_at_ns, 5 * second);
+        assert_eq!(profile.total_samples[5], 808_000);
+        assert_eq!(profile.over_target_ns, vec![5 * second]);
+        assert_eq!(profile.recommendations.len(), 2);
+    }
+}
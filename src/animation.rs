@@ -0,0 +1,597 @@
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+use gstreamer_controller as gst_controller;
+use gstreamer_controller::prelude::*;
+use parking_lot::Mutex;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::pipeline::PipelineManager;
+use crate::properties::{resolve_property_path, PropertyTarget};
+
+/// Control points a cubic ramp is sampled to
+const CUBIC_POINTS: u64 = 16;
+
+/// Longest and shortest wait between checks of whether a ramp reached its
+/// end, so pauses and seeks are followed
+const END_CHECK_MAX: gst::ClockTime = gst::ClockTime::from_mseconds(200);
+const END_CHECK_MIN: gst::ClockTime = gst::ClockTime::from_mseconds(10);
+
+/// How a ramp moves from its start value to its end value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    /// At a constant rate
+    #[default]
+    Linear,
+    /// Easing in and out, for fades and moves that look natural
+    Cubic,
+    /// Hold the start value, then jump to the end value
+    Step,
+}
+
+impl Interpolation {
+    /// Value from `from` to `to` at `progress`, from 0 to 1
+    pub fn value(self, from: f64, to: f64, progress: f64) -> f64 {
+        let t = progress.clamp(0.0, 1.0);
+        let eased = match self {
+            Interpolation::Linear => t,
+            Interpolation::Cubic => t * t * (3.0 - 2.0 * t),
+            Interpolation::Step => {
+                if t < 1.0 {
+                    0.0
+                } else {
+                    1.0
+                }
+            }
+        };
+        from + (to - from) * eased
+    }
+
+    /// Control points of a ramp from `from` to `to` over `duration` from
+    /// `start`, and how the control source interpolates between them
+    pub fn control_points(
+        self,
+        from: f64,
+        to: f64,
+        start: gst::ClockTime,
+        duration: gst::ClockTime,
+    ) -> (
+        gst_controller::InterpolationMode,
+        Vec<(gst::ClockTime, f64)>,
+    ) {
+        let end = (start, from, start + duration, to);
+        match self {
+            Interpolation::Linear => (
+                gst_controller::InterpolationMode::Linear,
+                vec![(end.0, end.1), (end.2, end.3)],
+            ),
+            // Without interpolation each point holds until the next one
+            Interpolation::Step => (
+                gst_controller::InterpolationMode::None,
+                vec![(end.0, end.1), (end.2, end.3)],
+            ),
+            // A spline through samples of the eased curve; monotonic so it
+            // does not overshoot them
+            Interpolation::Cubic => (
+                gst_controller::InterpolationMode::CubicMonotonic,
+                (0..=CUBIC_POINTS)
+                    .map(|i| {
+                        let time = start
+                            + gst::ClockTime::from_nseconds(duration.nseconds() / CUBIC_POINTS * i);
+                        (time, self.value(from, to, i as f64 / CUBIC_POINTS as f64))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnimationStatus {
+    Running,
+    Finished,
+    Cancelled,
+}
+
+impl std::fmt::Display for AnimationStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AnimationStatus::Running => "running",
+            AnimationStatus::Finished => "finished",
+            AnimationStatus::Cancelled => "cancelled",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A property ramp in a running pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Animation {
+    pub id: String,
+    pub pipeline_id: String,
+    /// Property path as given, e.g. `mix::sink_1::xpos`
+    pub path: String,
+    /// Properties it resolved to
+    pub targets: Vec<String>,
+    pub from: f64,
+    pub to: f64,
+    pub duration_ms: u64,
+    pub interpolation: Interpolation,
+    pub status: AnimationStatus,
+    /// Stream time the ramp starts at
+    pub start_ns: u64,
+    /// Share of the ramp done, from 0 to 1
+    pub progress: f64,
+    /// Value at the pipeline position
+    pub value: f64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Animation {
+    /// Progress and value of the ramp at stream time `position`
+    fn advance_to(&mut self, position: gst::ClockTime) {
+        let elapsed = position.nseconds().saturating_sub(self.start_ns);
+        self.progress = if self.duration_ms == 0 {
+            1.0
+        } else {
+            (elapsed as f64 / (self.duration_ms as f64 * 1_000_000.0)).min(1.0)
+        };
+        self.value = self.interpolation.value(self.from, self.to, self.progress);
+    }
+}
+
+type Apply = Arc<dyn Fn(f64) + Send + Sync>;
+
+/// Value of a numeric property as a float
+fn value_as_f64(value: &glib::Value) -> Option<f64> {
+    if let Ok(v) = value.get::<f64>() {
+        Some(v)
+    } else if let Ok(v) = value.get::<f32>() {
+        Some(v as f64)
+    } else if let Ok(v) = value.get::<i32>() {
+        Some(v as f64)
+    } else if let Ok(v) = value.get::<u32>() {
+        Some(v as f64)
+    } else if let Ok(v) = value.get::<i64>() {
+        Some(v as f64)
+    } else if let Ok(v) = value.get::<u64>() {
+        Some(v as f64)
+    } else {
+        None
+    }
+}
+
+/// A numeric property a ramp can drive with a control binding
+struct Controlled {
+    object: gst::Object,
+    property: String,
+    path: String,
+    /// Range the property allows
+    min: f64,
+    max: f64,
+    /// Sets the property from a float, rounding for integer properties
+    set: Apply,
+}
+
+/// Check that `target` is a numeric property that can be controlled, and
+/// how to set it directly when a ramp is cancelled at its end value
+fn controlled_property(target: &PropertyTarget) -> McpResult<Controlled> {
+    let pspec = target
+        .object
+        .find_property(&target.property)
+        .expect("resolved properties exist");
+    if !pspec.flags().contains(glib::ParamFlags::WRITABLE)
+        || pspec.flags().contains(glib::ParamFlags::CONSTRUCT_ONLY)
+    {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "{} is not writable",
+            target.path
+        )));
+    }
+    let (min, max) = if let Some(p) = pspec.downcast_ref::<glib::ParamSpecDouble>() {
+        (p.minimum(), p.maximum())
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecFloat>() {
+        (p.minimum() as f64, p.maximum() as f64)
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecInt>() {
+        (p.minimum() as f64, p.maximum() as f64)
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecUInt>() {
+        (p.minimum() as f64, p.maximum() as f64)
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecInt64>() {
+        (p.minimum() as f64, p.maximum() as f64)
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecUInt64>() {
+        (p.minimum() as f64, p.maximum() as f64)
+    } else {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "{} is a {}; only numeric properties can be animated",
+            target.path,
+            pspec.value_type().name()
+        )));
+    };
+    if !pspec.flags().contains(gst::PARAM_FLAG_CONTROLLABLE) {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "{} is not controllable, so it cannot be animated; set it with gst_set_property instead",
+            target.path
+        )));
+    }
+
+    let value_type = pspec.value_type();
+    let object = target
+        .object
+        .clone()
+        .downcast::<gst::Object>()
+        .map_err(|_| {
+            GStreamerMcpError::PropertyError(format!(
+                "{} does not belong to an element or pad",
+                target.path
+            ))
+        })?;
+    let property = target.property.clone();
+    let set: Apply = {
+        let (object, property) = (object.clone(), property.clone());
+        Arc::new(move |value: f64| {
+            let value = value.clamp(min, max);
+            let value = if value_type == f64::static_type() {
+                value.to_value()
+            } else if value_type == f32::static_type() {
+                (value as f32).to_value()
+            } else if value_type == i32::static_type() {
+                (value.round() as i32).to_value()
+            } else if value_type == u32::static_type() {
+                (value.round() as u32).to_value()
+            } else if value_type == i64::static_type() {
+                (value.round() as i64).to_value()
+            } else {
+                (value.round() as u64).to_value()
+            };
+            object.set_property_from_value(&property, &value);
+        })
+    };
+    Ok(Controlled {
+        object,
+        property,
+        path: target.path.clone(),
+        min,
+        max,
+        set,
+    })
+}
+
+/// An animation and the pipeline it belongs to
+struct Entry {
+    animation: Animation,
+    pipeline: glib::WeakRef<gst::Pipeline>,
+}
+
+/// What a running animation needs to be stopped
+struct Running {
+    /// Controlled properties with the bindings driving them
+    bindings: Vec<(gst::Object, gst::ControlBinding)>,
+    apply: Apply,
+}
+
+impl Running {
+    fn unbind(&self) {
+        for (object, binding) in &self.bindings {
+            object.remove_control_binding(binding);
+        }
+    }
+}
+
+/// Ramps of numeric properties in running pipelines, made with GStreamer
+/// control bindings: each property gets an interpolation control source,
+/// and elements set it to its value at the stream time of every buffer they
+/// process. A paused pipeline holds its ramps. When a ramp ends, or is
+/// cancelled, its bindings are removed.
+#[derive(Clone)]
+pub struct Animator {
+    pipeline_manager: Arc<PipelineManager>,
+    animations: Arc<Mutex<HashMap<String, Entry>>>,
+    running: Arc<Mutex<HashMap<String, Running>>>,
+}
+
+impl Animator {
+    pub fn new(pipeline_manager: Arc<PipelineManager>) -> Self {
+        Self {
+            pipeline_manager,
+            animations: Arc::default(),
+            running: Arc::default(),
+        }
+    }
+
+    /// Ramp the properties `path` resolves to, from `from` (default: their
+    /// current value) to `to` over `duration`, starting at stream time
+    /// `start` or at the pipeline's position. Another running ramp of the
+    /// same properties is cancelled. `on_done` gets the animation once it
+    /// finished.
+    #[allow(clippy::too_many_arguments)]
+    pub fn animate(
+        &self,
+        pipeline: &gst::Pipeline,
+        pipeline_id: &str,
+        path: &str,
+        from: Option<f64>,
+        to: f64,
+        duration: gst::ClockTime,
+        interpolation: Interpolation,
+        start: Option<gst::ClockTime>,
+        on_done: impl FnOnce(Animation) + Send + 'static,
+    ) -> McpResult<Animation> {
+        self.prune();
+        let targets = resolve_property_path(pipeline, path)?;
+        let controlled = targets
+            .iter()
+            .map(controlled_property)
+            .collect::<McpResult<Vec<_>>>()?;
+        let from = match from {
+            Some(from) => from,
+            None => value_as_f64(&targets[0].object.property_value(&targets[0].property))
+                .unwrap_or_default(),
+        };
+        let start = start
+            .or_else(|| pipeline.query_position::<gst::ClockTime>())
+            .unwrap_or(gst::ClockTime::ZERO);
+        let target_paths: Vec<String> = targets.iter().map(|t| t.path.clone()).collect();
+
+        for previous in self.list(Some(pipeline_id)) {
+            if previous.status == AnimationStatus::Running
+                && previous.targets.iter().any(|t| target_paths.contains(t))
+            {
+                let _ = self.cancel(&previous.id, false);
+            }
+        }
+
+        let (mode, points) = interpolation.control_points(from, to, start, duration);
+        let mut bindings: Vec<(gst::Object, gst::ControlBinding)> = Vec::new();
+        for target in &controlled {
+            let source = gst_controller::InterpolationControlSource::new();
+            source.set_mode(mode);
+            for (time, value) in &points {
+                source.set(*time, value.clamp(target.min, target.max));
+            }
+            let binding = gst_controller::DirectControlBinding::new_absolute(
+                &target.object,
+                &target.property,
+                &source,
+            )
+            .upcast::<gst::ControlBinding>();
+            if let Err(e) = target.object.add_control_binding(&binding) {
+                for (object, binding) in &bindings {
+                    object.remove_control_binding(binding);
+                }
+                return Err(GStreamerMcpError::PropertyError(format!(
+                    "{} could not be bound to a control source: {}",
+                    target.path, e
+                )));
+            }
+            bindings.push((target.object.clone(), binding));
+        }
+
+        let id = format!("animation-{}", uuid::Uuid::new_v4());
+        let animation = Animation {
+            id: id.clone(),
+            pipeline_id: pipeline_id.to_string(),
+            path: path.to_string(),
+            targets: target_paths,
+            from,
+            to,
+            duration_ms: duration.mseconds(),
+            interpolation,
+            status: AnimationStatus::Running,
+            start_ns: start.nseconds(),
+            progress: 0.0,
+            value: from,
+            created_at: chrono::Utc::now(),
+        };
+        let setters: Vec<Apply> = controlled.into_iter().map(|c| c.set).collect();
+        let apply: Apply = Arc::new(move |value| {
+            for setter in &setters {
+                setter(value);
+            }
+        });
+
+        // Registered before the end is watched for, so a ramp that is over
+        // at once still finds its entries
+        self.animations.lock().insert(
+            id.clone(),
+            Entry {
+                animation: animation.clone(),
+                pipeline: pipeline.downgrade(),
+            },
+        );
+        self.running
+            .lock()
+            .insert(id.clone(), Running { bindings, apply });
+        self.wait_for_end(
+            id,
+            pipeline.downgrade(),
+            start + duration,
+            Box::new(on_done),
+        );
+
+        Ok(animation)
+    }
+
+    /// Finish animation `id` once the position of `pipeline` reaches `end`,
+    /// checking on the system clock so pauses and seeks are followed. The
+    /// wait ends early when the animation is cancelled or the pipeline goes
+    /// away.
+    fn wait_for_end(
+        &self,
+        id: String,
+        pipeline: glib::WeakRef<gst::Pipeline>,
+        end: gst::ClockTime,
+        on_done: Box<dyn FnOnce(Animation) + Send>,
+    ) {
+        if !self.running.lock().contains_key(&id) {
+            return;
+        }
+        let Some(position) = pipeline
+            .upgrade()
+            .map(|pipeline| pipeline.query_position::<gst::ClockTime>())
+        else {
+            return;
+        };
+        let wait = match position {
+            Some(position) if position >= end => return self.finish(&id, on_done),
+            Some(position) => end - position,
+            None => END_CHECK_MAX,
+        };
+
+        let clock = gst::SystemClock::obtain();
+        let clock_id =
+            clock.new_single_shot_id(clock.time() + wait.clamp(END_CHECK_MIN, END_CHECK_MAX));
+        let animator = self.clone();
+        let _ =
+            clock_id.wait_async(move |_, _, _| animator.wait_for_end(id, pipeline, end, on_done));
+    }
+
+    /// End a ramp that reached its end: its bindings are removed and the
+    /// properties left at the end value
+    fn finish(&self, id: &str, on_done: Box<dyn FnOnce(Animation) + Send>) {
+        let done = {
+            let mut animations = self.animations.lock();
+            let Some(entry) = animations
+                .get_mut(id)
+                .filter(|e| e.animation.status == AnimationStatus::Running)
+            else {
+                return;
+            };
+            let animation = &mut entry.animation;
+            animation.status = AnimationStatus::Finished;
+            animation.progress = 1.0;
+            animation.value = animation.to;
+            animation.clone()
+        };
+        if let Some(running) = self.running.lock().remove(id) {
+            running.unbind();
+            (running.apply)(done.to);
+        }
+        on_done(done);
+    }
+
+    /// Forget the animations of pipelines that were removed, or replaced
+    /// by another pipeline under the same ID
+    fn prune(&self) {
+        let entries: Vec<(String, String, Option<gst::Pipeline>)> = self
+            .animations
+            .lock()
+            .iter()
+            .map(|(id, e)| {
+                (
+                    id.clone(),
+                    e.animation.pipeline_id.clone(),
+                    e.pipeline.upgrade(),
+                )
+            })
+            .collect();
+        for (id, pipeline_id, pipeline) in entries {
+            let current = self.pipeline_manager.get_pipeline_handle(&pipeline_id).ok();
+            if pipeline.is_some() && current == pipeline {
+                continue;
+            }
+            self.animations.lock().remove(&id);
+            if let Some(running) = self.running.lock().remove(&id) {
+                running.unbind();
+            }
+        }
+    }
+
+    /// Animations, oldest first, optionally of one pipeline, with the
+    /// progress of running ones at the pipeline position
+    pub fn list(&self, pipeline_id: Option<&str>) -> Vec<Animation> {
+        self.prune();
+        let entries: Vec<(Animation, Option<gst::Pipeline>)> = self
+            .animations
+            .lock()
+            .values()
+            .filter(|e| pipeline_id.is_none_or(|id| e.animation.pipeline_id == id))
+            .map(|e| (e.animation.clone(), e.pipeline.upgrade()))
+            .collect();
+        let mut animations: Vec<Animation> = entries
+            .into_iter()
+            .map(|(mut animation, pipeline)| {
+                if animation.status == AnimationStatus::Running {
+                    if let Some(position) =
+                        pipeline.and_then(|p| p.query_position::<gst::ClockTime>())
+                    {
+                        animation.advance_to(position);
+                    }
+                }
+                animation
+            })
+            .collect();
+        animations.sort_by_key(|a| a.created_at);
+        animations
+    }
+
+    /// Stop a running animation where it is, or with `finish` at its end
+    /// value, and remove its control bindings
+    pub fn cancel(&self, id: &str, finish: bool) -> McpResult<Animation> {
+        let pipeline = self
+            .animations
+            .lock()
+            .get(id)
+            .and_then(|e| e.pipeline.upgrade());
+        let position = pipeline.and_then(|p| p.query_position::<gst::ClockTime>());
+
+        let animation = {
+            let mut animations = self.animations.lock();
+            let animation = &mut animations
+                .get_mut(id)
+                .ok_or_else(|| {
+                    GStreamerMcpError::PipelineError(format!("Animation '{}' not found", id))
+                })?
+                .animation;
+            if animation.status != AnimationStatus::Running {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "Animation '{}' is already {}",
+                    id, animation.status
+                )));
+            }
+            animation.status = AnimationStatus::Cancelled;
+            if let Some(position) = position {
+                animation.advance_to(position);
+            }
+            if finish {
+                animation.value = animation.to;
+            }
+            animation.clone()
+        };
+        if let Some(running) = self.running.lock().remove(id) {
+            running.unbind();
+            if finish {
+                (running.apply)(animation.to);
+            }
+        }
+        Ok(animation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolation() {
+        assert_eq!(Interpolation::Linear.value(1.0, 0.0, 0.25), 0.75);
+        assert_eq!(Interpolation::Linear.value(0.0, 100.0, 2.0), 100.0);
+        assert_eq!(Interpolation::Cubic.value(0.0, 1.0, 0.5), 0.5);
+        assert!(Interpolation::Cubic.value(0.0, 1.0, 0.1) < 0.1);
+        assert_eq!(Interpolation::Step.value(0.0, 640.0, 0.99), 0.0);
+        assert_eq!(Interpolation::Step.value(0.0, 640.0, 1.0), 640.0);
+
+        let second = gst::ClockTime::SECOND;
+        let (_, points) = Interpolation::Linear.control_points(1.0, 0.0, second, 3 * second);
+        assert_eq!(points, vec![(second, 1.0), (4 * second, 0.0)]);
+        let (_, points) = Interpolation::Cubic.control_points(0.0, 1.0, second, 2 * second);
+        assert_eq!(points.len(), CUBIC_POINTS as usize + 1);
+        assert_eq!(points[CUBIC_POINTS as usize / 2], (2 * second, 0.5));
+        assert_eq!(points.last(), Some(&(3 * second, 1.0)));
+    }
+}
@@ -0,0 +1,299 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use crate::discovery::ensure_gstreamer_initialized;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::quote_value;
+
+/// Source element of the Aravis GigE Vision / USB3 Vision library
+pub const ARAVIS_ELEMENTS: &[&str] = &["aravissrc"];
+
+/// Command line tools shipped with Aravis, newest first. The GStreamer
+/// element cannot enumerate cameras itself.
+const ARV_TOOLS: &[&str] = &["arv-tool-0.8", "arv-tool-0.6"];
+
+/// Bayer patterns delivered as `video/x-bayer`
+const BAYER_FORMATS: &[&str] = &["rggb", "bggr", "grbg", "gbrg"];
+
+/// A camera found on the network or USB bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AravisCamera {
+    /// Name to pass as `camera-name`, `<vendor>-<model>-<serial>`
+    pub name: String,
+    /// IP address of GigE Vision cameras, or the USB device
+    pub address: Option<String>,
+    /// Capabilities, when the camera was opened to inspect them
+    pub details: Option<AravisCameraDetails>,
+}
+
+/// Pixel format offered by a camera, with the sizes it supports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AravisPixelFormat {
+    /// `video/x-raw` or `video/x-bayer`
+    pub media_type: String,
+    pub format: Option<String>,
+    pub width: Option<String>,
+    pub height: Option<String>,
+    pub framerate: Option<String>,
+}
+
+/// Current acquisition settings and formats of a camera
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AravisCameraDetails {
+    pub pixel_formats: Vec<AravisPixelFormat>,
+    /// Exposure time in microseconds
+    pub exposure_us: Option<f64>,
+    /// Gain in dB
+    pub gain_db: Option<f64>,
+    pub exposure_auto: Option<String>,
+    pub gain_auto: Option<String>,
+}
+
+/// Automatic exposure/gain mode of aravissrc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AravisAuto {
+    Off,
+    Once,
+    Continuous,
+}
+
+impl AravisAuto {
+    fn nick(self) -> &'static str {
+        match self {
+            AravisAuto::Off => "off",
+            AravisAuto::Once => "once",
+            AravisAuto::Continuous => "on",
+        }
+    }
+}
+
+/// Acquisition settings for a launched camera pipeline
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct AravisSettings {
+    #[schemars(description = "Exposure time in microseconds (disables auto exposure)")]
+    pub exposure_us: Option<f64>,
+    #[schemars(description = "Gain in dB (disables auto gain)")]
+    pub gain_db: Option<f64>,
+    #[schemars(description = "Automatic exposure: 'off', 'once' or 'continuous'")]
+    pub exposure_auto: Option<AravisAuto>,
+    #[schemars(description = "Automatic gain: 'off', 'once' or 'continuous'")]
+    pub gain_auto: Option<AravisAuto>,
+    #[schemars(
+        description = "Pixel format from gst_list_aravis_cameras, e.g. 'GRAY8', 'GRAY16_LE' or a Bayer pattern such as 'rggb' (debayered automatically)"
+    )]
+    pub pixel_format: Option<String>,
+    #[schemars(description = "Frame width in pixels")]
+    pub width: Option<u32>,
+    #[schemars(description = "Frame height in pixels")]
+    pub height: Option<u32>,
+    #[schemars(description = "Frame rate as 'num/den' (e.g., '30/1')")]
+    pub framerate: Option<String>,
+}
+
+/// List the cameras Aravis can see, using its command line tool
+pub fn list_aravis_cameras() -> McpResult<Vec<AravisCamera>> {
+    for tool in ARV_TOOLS {
+        match Command::new(tool).output() {
+            Ok(output) if output.status.success() => {
+                return Ok(parse_camera_list(&String::from_utf8_lossy(&output.stdout)));
+            }
+            Ok(output) => {
+                return Err(GStreamerMcpError::Other(format!(
+                    "{} failed: {}",
+                    tool,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            Err(_) => continue,
+        }
+    }
+    Err(GStreamerMcpError::Other(format!(
+        "Listing cameras needs the Aravis tools ({}) on the PATH",
+        ARV_TOOLS.join(" or ")
+    )))
+}
+
+/// Parse `arv-tool` output: one `<name> (<address>)` line per camera
+fn parse_camera_list(output: &str) -> Vec<AravisCamera> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("No device"))
+        .map(|line| {
+            let (name, address) = match line.rsplit_once(" (") {
+                Some((name, rest)) if rest.ends_with(')') => (
+                    name.to_string(),
+                    Some(rest.trim_end_matches(')').to_string()),
+                ),
+                _ => (line.to_string(), None),
+            };
+            AravisCamera {
+                name,
+                address,
+                details: None,
+            }
+        })
+        .collect()
+}
+
+/// Open a camera to read its pixel formats and current exposure and gain.
+/// Fails while another process is streaming from the camera.
+pub fn inspect_aravis_camera(name: &str) -> McpResult<AravisCameraDetails> {
+    ensure_gstreamer_initialized()?;
+
+    let src = gst::ElementFactory::make("aravissrc")
+        .property("camera-name", name)
+        .build()
+        .map_err(|_| GStreamerMcpError::ElementNotFound("aravissrc is not installed".into()))?;
+    src.set_state(gst::State::Ready).map_err(|_| {
+        GStreamerMcpError::PipelineError(format!("Failed to open camera '{}'", name))
+    })?;
+
+    let caps = src
+        .static_pad("src")
+        .map(|pad| pad.query_caps(None))
+        .unwrap_or_else(gst::Caps::new_empty);
+    let field = |structure: &gst::StructureRef, name: &str| {
+        structure
+            .value(name)
+            .ok()
+            .and_then(|v| v.serialize().ok())
+            .map(|v| v.to_string())
+    };
+    let pixel_formats = caps
+        .iter()
+        .map(|structure| AravisPixelFormat {
+            media_type: structure.name().to_string(),
+            format: field(structure, "format"),
+            width: field(structure, "width"),
+            height: field(structure, "height"),
+            framerate: field(structure, "framerate"),
+        })
+        .collect();
+
+    let double = |property: &str| {
+        src.find_property(property)
+            .filter(|p| p.value_type() == f64::static_type())
+            .map(|_| src.property::<f64>(property))
+    };
+    let nick = |property: &str| {
+        src.find_property(property)
+            .and_then(|_| src.property_value(property).serialize().ok())
+            .map(|v| v.to_string())
+    };
+    let details = AravisCameraDetails {
+        pixel_formats,
+        exposure_us: double("exposure"),
+        gain_db: double("gain"),
+        exposure_auto: nick("exposure-auto"),
+        gain_auto: nick("gain-auto"),
+    };
+
+    let _ = src.set_state(gst::State::Null);
+    Ok(details)
+}
+
+/// gst-launch description streaming camera `name` (or the first camera)
+/// into `sink` with the given acquisition settings
+pub fn aravis_description(name: Option<&str>, settings: &AravisSettings, sink: &str) -> String {
+    let mut src = "aravissrc".to_string();
+    if let Some(name) = name {
+        src.push_str(&format!(" camera-name={}", quote_value(name)));
+    }
+    // A fixed value only sticks when the matching automatic mode is off
+    let exposure_auto = settings
+        .exposure_auto
+        .or(settings.exposure_us.map(|_| AravisAuto::Off));
+    let gain_auto = settings
+        .gain_auto
+        .or(settings.gain_db.map(|_| AravisAuto::Off));
+    if let Some(mode) = exposure_auto {
+        src.push_str(&format!(" exposure-auto={}", mode.nick()));
+    }
+    if let Some(exposure) = settings.exposure_us {
+        src.push_str(&format!(" exposure={}", exposure));
+    }
+    if let Some(mode) = gain_auto {
+        src.push_str(&format!(" gain-auto={}", mode.nick()));
+    }
+    if let Some(gain) = settings.gain_db {
+        src.push_str(&format!(" gain={}", gain));
+    }
+
+    let bayer = settings
+        .pixel_format
+        .as_deref()
+        .filter(|f| BAYER_FORMATS.contains(&f.to_lowercase().as_str()));
+    let mut caps = vec![if bayer.is_some() {
+        "video/x-bayer".to_string()
+    } else {
+        "video/x-raw".to_string()
+    }];
+    if let Some(format) = &settings.pixel_format {
+        caps.push(format!(
+            "format={}",
+            bayer.map_or(format.clone(), str::to_lowercase)
+        ));
+    }
+    if let Some(width) = settings.width {
+        caps.push(format!("width={}", width));
+    }
+    if let Some(height) = settings.height {
+        caps.push(format!("height={}", height));
+    }
+    if let Some(framerate) = &settings.framerate {
+        caps.push(format!("framerate={}", framerate));
+    }
+
+    let mut parts = vec![src];
+    if caps.len() > 1 || bayer.is_some() {
+        parts.push(caps.join(","));
+    }
+    if bayer.is_some() {
+        parts.push("bayer2rgb".to_string());
+    }
+    parts.push(sink.to_string());
+    parts.join(" ! ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_camera_list() {
+        let cameras = parse_camera_list(
+            "Basler-acA1300-60gm-21234567 (192.168.10.20)\nAravis-Fake-GV01 (127.0.0.1)\n",
+        );
+        assert_eq!(cameras.len(), 2);
+        assert_eq!(cameras[0].name, "Basler-acA1300-60gm-21234567");
+        assert_eq!(cameras[0].address.as_deref(), Some("192.168.10.20"));
+        assert!(parse_camera_list("No device found\n").is_empty());
+    }
+
+    #[test]
+    fn test_aravis_description() {
+        let settings = AravisSettings {
+            exposure_us: Some(5000.0),
+            pixel_format: Some("RGGB".to_string()),
+            width: Some(1280),
+            height: Some(1024),
+            ..Default::default()
+        };
+        assert_eq!(
+            aravis_description(Some("Basler-1"), &settings, "videoconvert ! autovideosink"),
+            "aravissrc camera-name=\"Basler-1\" exposure-auto=off exposure=5000 ! \
+             video/x-bayer,format=rggb,width=1280,height=1024 ! bayer2rgb ! \
+             videoconvert ! autovideosink"
+        );
+
+        assert_eq!(
+            aravis_description(None, &AravisSettings::default(), "fakesink"),
+            "aravissrc ! fakesink"
+        );
+    }
+}
@@ -0,0 +1,545 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::confirmation::FILE_WRITING_FACTORIES;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::probe_media;
+use crate::message_filter::glob_match;
+
+/// Properties of file-writing sinks that name the files they produce
+const LOCATION_PROPERTIES: &[&str] = &["location", "playlist-location"];
+
+/// URI scheme under which artifacts are exposed as MCP resources
+pub const ARTIFACT_URI_SCHEME: &str = "artifact://";
+
+/// MCP resource template for artifact reads, including the range parameters
+pub const ARTIFACT_URI_TEMPLATE: &str = "artifact://{id}{?offset,length}";
+
+/// A file produced by a managed pipeline. Artifacts are content-addressed:
+/// identical files written to several locations share one entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Artifact {
+    /// SHA-256 of the file content, hex encoded
+    pub id: String,
+    pub paths: Vec<PathBuf>,
+    pub size_bytes: u64,
+    /// Media duration, when the file is a container GStreamer can inspect
+    pub duration_ns: Option<u64>,
+    /// Pipeline (or job) that produced the file
+    pub pipeline_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Artifact {
+    pub fn uri(&self) -> String {
+        format!("{}{}", ARTIFACT_URI_SCHEME, self.id)
+    }
+
+    /// MIME type guessed from the file extension
+    pub fn mime_type(&self) -> &'static str {
+        let extension = self
+            .paths
+            .first()
+            .and_then(|p| p.extension())
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "webp" => "image/webp",
+            "gif" => "image/gif",
+            "mp4" | "m4v" => "video/mp4",
+            "mov" => "video/quicktime",
+            "mkv" => "video/x-matroska",
+            "webm" => "video/webm",
+            "ts" => "video/mp2t",
+            "m3u8" => "application/vnd.apple.mpegurl",
+            "mpd" => "application/dash+xml",
+            "wav" => "audio/wav",
+            "mp3" => "audio/mpeg",
+            "ogg" | "oga" => "audio/ogg",
+            "flac" => "audio/flac",
+            "m4a" => "audio/mp4",
+            _ => "application/octet-stream",
+        }
+    }
+
+    /// Read `range` of the artifact (the whole file by default), refusing
+    /// reads larger than `max_bytes`
+    pub fn read(&self, range: ByteRange, max_bytes: u64) -> McpResult<Vec<u8>> {
+        let path = self.paths.iter().find(|p| p.is_file()).ok_or_else(|| {
+            GStreamerMcpError::Other(format!("Artifact {} no longer exists on disk", self.id))
+        })?;
+        let mut file = std::fs::File::open(path)?;
+        let size = file.metadata()?.len();
+
+        if range.offset > size {
+            return Err(GStreamerMcpError::Other(format!(
+                "Offset {} is past the end of artifact {} ({} bytes)",
+                range.offset, self.id, size
+            )));
+        }
+        let length = range
+            .length
+            .unwrap_or(size - range.offset)
+            .min(size - range.offset);
+        if length > max_bytes {
+            return Err(GStreamerMcpError::Other(format!(
+                "Reading {} bytes of artifact {} exceeds the {} byte limit; read it in parts with {}{}?offset=<n>&length=<n>",
+                length, self.id, max_bytes, ARTIFACT_URI_SCHEME, self.id
+            )));
+        }
+
+        file.seek(SeekFrom::Start(range.offset))?;
+        let mut data = Vec::with_capacity(length as usize);
+        file.take(length).read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// Part of an artifact to read through a resource URI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ByteRange {
+    pub offset: u64,
+    /// Bytes to read; the rest of the file when unset
+    pub length: Option<u64>,
+}
+
+/// Split an `artifact://<id>?offset=<n>&length=<n>` URI into the artifact
+/// ID and the requested range
+pub fn parse_artifact_uri(uri: &str) -> McpResult<(String, ByteRange)> {
+    let rest = uri
+        .strip_prefix(ARTIFACT_URI_SCHEME)
+        .ok_or_else(|| GStreamerMcpError::Other(format!("Unknown resource URI '{}'", uri)))?;
+    let (id, query) = rest.split_once('?').unwrap_or((rest, ""));
+    if id.is_empty() {
+        return Err(GStreamerMcpError::Other(format!(
+            "Resource URI '{}' has no artifact ID",
+            uri
+        )));
+    }
+
+    let mut range = ByteRange::default();
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = value.parse::<u64>().map_err(|_| {
+            GStreamerMcpError::Other(format!(
+                "Invalid value '{}' for '{}' in '{}'",
+                value, key, uri
+            ))
+        })?;
+        match key {
+            "offset" => range.offset = value,
+            "length" => range.length = Some(value),
+            _ => {
+                return Err(GStreamerMcpError::Other(format!(
+                    "Unknown parameter '{}' in '{}'. Supported: offset, length",
+                    key, uri
+                )))
+            }
+        }
+    }
+
+    Ok((id.to_string(), range))
+}
+
+/// Which artifacts `prune` removes. An artifact is pruned when any of the
+/// limits selects it; unset limits select nothing.
+#[derive(Debug, Clone, Default)]
+pub struct RetentionPolicy {
+    /// Prune artifacts older than this
+    pub max_age: Option<Duration>,
+    /// Keep the newest artifacts whose combined size fits in this many bytes
+    pub max_total_bytes: Option<u64>,
+    /// Keep only this many of the newest artifacts
+    pub keep_latest: Option<usize>,
+    /// Only consider artifacts of this pipeline
+    pub pipeline_id: Option<String>,
+}
+
+/// Registry of the files produced by managed pipelines
+pub struct ArtifactRegistry {
+    artifacts: Mutex<Vec<Artifact>>,
+    state_path: Option<PathBuf>,
+}
+
+impl ArtifactRegistry {
+    /// Create the registry, restoring persisted entries from `state_path`.
+    /// Entries whose files have all disappeared are dropped.
+    pub fn new(state_path: Option<PathBuf>) -> McpResult<Self> {
+        let mut artifacts: Vec<Artifact> = Vec::new();
+        if let Some(path) = state_path.as_ref().filter(|p| p.exists()) {
+            artifacts = serde_json::from_slice(&std::fs::read(path)?)?;
+            for artifact in artifacts.iter_mut() {
+                artifact.paths.retain(|p| p.exists());
+            }
+            artifacts.retain(|a| !a.paths.is_empty());
+        }
+
+        Ok(Self {
+            artifacts: Mutex::new(artifacts),
+            state_path,
+        })
+    }
+
+    fn persist(&self, artifacts: &[Artifact]) {
+        let Some(path) = &self.state_path else {
+            return;
+        };
+        let result = serde_json::to_vec_pretty(artifacts)
+            .map_err(GStreamerMcpError::from)
+            .and_then(|content| {
+                let tmp = path.with_extension("tmp");
+                std::fs::write(&tmp, content)?;
+                std::fs::rename(&tmp, path)?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist artifact registry: {}", e);
+        }
+    }
+
+    /// Hash and record the files written to `locations` by a pipeline. This
+    /// reads every file, so call it from a blocking context.
+    pub fn register(&self, pipeline_id: &str, locations: &[String]) -> Vec<Artifact> {
+        let mut registered = Vec::new();
+        for path in locations.iter().flat_map(|l| expand_location(l)) {
+            match inspect_file(&path, pipeline_id) {
+                Ok(artifact) => registered.push(self.insert(artifact)),
+                Err(e) => tracing::warn!("Failed to register artifact {}: {}", path.display(), e),
+            }
+        }
+        registered
+    }
+
+    fn insert(&self, artifact: Artifact) -> Artifact {
+        let mut artifacts = self.artifacts.lock();
+        let path = artifact.paths[0].clone();
+
+        // The file may have been overwritten since it was last registered
+        for existing in artifacts.iter_mut().filter(|a| a.id != artifact.id) {
+            existing.paths.retain(|p| *p != path);
+        }
+        artifacts.retain(|a| !a.paths.is_empty());
+
+        let entry = match artifacts.iter_mut().find(|a| a.id == artifact.id) {
+            Some(existing) => {
+                if !existing.paths.contains(&path) {
+                    existing.paths.push(path);
+                }
+                existing.pipeline_id = artifact.pipeline_id;
+                existing.created_at = artifact.created_at;
+                existing.clone()
+            }
+            None => {
+                artifacts.push(artifact.clone());
+                artifact
+            }
+        };
+        self.persist(&artifacts);
+        entry
+    }
+
+    pub fn get(&self, id: &str) -> Option<Artifact> {
+        self.artifacts.lock().iter().find(|a| a.id == id).cloned()
+    }
+
+    /// Artifacts, newest first
+    pub fn list(&self, pipeline_id: Option<&str>) -> Vec<Artifact> {
+        let mut artifacts: Vec<Artifact> = self
+            .artifacts
+            .lock()
+            .iter()
+            .filter(|a| pipeline_id.is_none_or(|id| a.pipeline_id == id))
+            .cloned()
+            .collect();
+        artifacts.sort_by_key(|a| std::cmp::Reverse(a.created_at));
+        artifacts
+    }
+
+    /// Remove the artifacts selected by `policy` from the registry, deleting
+    /// their files when `delete_files` is set. With `dry_run` nothing is
+    /// changed. Returns the selected artifacts.
+    pub fn prune(
+        &self,
+        policy: &RetentionPolicy,
+        delete_files: bool,
+        dry_run: bool,
+    ) -> Vec<Artifact> {
+        let mut artifacts = self.artifacts.lock();
+        let selected = select_for_pruning(&artifacts, policy, chrono::Utc::now());
+        if dry_run || selected.is_empty() {
+            return selected;
+        }
+
+        if delete_files {
+            for path in selected.iter().flat_map(|a| &a.paths) {
+                if let Err(e) = std::fs::remove_file(path) {
+                    tracing::warn!("Failed to delete artifact {}: {}", path.display(), e);
+                }
+            }
+        }
+        artifacts.retain(|a| !selected.iter().any(|s| s.id == a.id));
+        self.persist(&artifacts);
+        selected
+    }
+}
+
+/// Artifacts selected by a retention policy, newest first
+pub fn select_for_pruning(
+    artifacts: &[Artifact],
+    policy: &RetentionPolicy,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<Artifact> {
+    let mut candidates: Vec<&Artifact> = artifacts
+        .iter()
+        .filter(|a| {
+            policy
+                .pipeline_id
+                .as_deref()
+                .is_none_or(|id| a.pipeline_id == id)
+        })
+        .collect();
+    candidates.sort_by_key(|a| std::cmp::Reverse(a.created_at));
+
+    let mut total_bytes = 0u64;
+    candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(index, artifact)| {
+            total_bytes = total_bytes.saturating_add(artifact.size_bytes);
+            let age = (now - artifact.created_at).to_std().unwrap_or_default();
+
+            policy.max_age.is_some_and(|max| age > max)
+                || policy.keep_latest.is_some_and(|keep| *index >= keep)
+                || policy.max_total_bytes.is_some_and(|max| total_bytes > max)
+        })
+        .map(|(_, artifact)| artifact.clone())
+        .collect()
+}
+
+/// Output locations configured on the file-writing sinks of a pipeline.
+/// Patterns such as `segment%05d.ts` are returned as configured.
+pub fn output_locations(pipeline: &gst::Pipeline) -> Vec<String> {
+    let mut locations = Vec::new();
+    for element in pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let is_writer = element
+            .factory()
+            .is_some_and(|f| FILE_WRITING_FACTORIES.contains(&f.name().as_str()));
+        if !is_writer {
+            continue;
+        }
+
+        for name in LOCATION_PROPERTIES {
+            let is_string = element
+                .find_property(name)
+                .is_some_and(|pspec| pspec.value_type() == String::static_type());
+            if !is_string {
+                continue;
+            }
+            if let Some(location) = element.property::<Option<String>>(name) {
+                locations.push(location);
+            }
+        }
+    }
+    locations
+}
+
+/// Existing files matching a sink location. printf-style patterns
+/// (`%d`, `%05d`, ...) used by multi-file sinks match any number.
+pub fn expand_location(location: &str) -> Vec<PathBuf> {
+    let path = Path::new(location);
+    let Some(pattern) = location_glob(location) else {
+        return if path.is_file() {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        };
+    };
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let file_pattern = Path::new(&pattern)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|p| p.is_file())
+        .filter(|p| {
+            p.file_name()
+                .is_some_and(|name| glob_match(&file_pattern, &name.to_string_lossy()))
+        })
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Glob equivalent of a printf-style location, or `None` if it has no
+/// conversion specifiers
+fn location_glob(location: &str) -> Option<String> {
+    if !location.contains('%') {
+        return None;
+    }
+
+    let mut glob = String::with_capacity(location.len());
+    let mut chars = location.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            glob.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            glob.push('%');
+            continue;
+        }
+        // Skip flags and width, then the conversion character
+        while chars.next_if(|c| c.is_ascii_digit() || *c == '-').is_some() {}
+        chars.next();
+        glob.push('*');
+    }
+    Some(glob)
+}
+
+fn inspect_file(path: &Path, pipeline_id: &str) -> McpResult<Artifact> {
+    let size_bytes = std::fs::metadata(path)?.len();
+
+    let mut hasher = Sha256::new();
+    std::io::copy(
+        &mut std::io::BufReader::new(std::fs::File::open(path)?),
+        &mut hasher,
+    )?;
+    let id = format!("{:x}", hasher.finalize());
+
+    Ok(Artifact {
+        id,
+        paths: vec![path.canonicalize().unwrap_or_else(|_| path.to_path_buf())],
+        size_bytes,
+        duration_ns: media_duration(path),
+        pipeline_id: pipeline_id.to_string(),
+        created_at: chrono::Utc::now(),
+    })
+}
+
+/// Duration of a media file, if GStreamer can determine one
+fn media_duration(path: &Path) -> Option<u64> {
+    probe_media(path)
+        .ok()?
+        .duration_ns
+        .filter(|duration| *duration > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(id: &str, size_bytes: u64, age_hours: i64, pipeline_id: &str) -> Artifact {
+        Artifact {
+            id: id.to_string(),
+            paths: vec![PathBuf::from(format!("/tmp/{}.mp4", id))],
+            size_bytes,
+            duration_ns: None,
+            pipeline_id: pipeline_id.to_string(),
+            created_at: chrono::Utc::now() - chrono::Duration::hours(age_hours),
+        }
+    }
+
+    fn ids(artifacts: &[Artifact]) -> Vec<&str> {
+        artifacts.iter().map(|a| a.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_select_for_pruning() {
+        let artifacts = vec![
+            artifact("old", 100, 48, "rec"),
+            artifact("mid", 100, 5, "rec"),
+            artifact("new", 100, 1, "thumbs"),
+        ];
+        let now = chrono::Utc::now();
+        let select = |policy: RetentionPolicy| select_for_pruning(&artifacts, &policy, now);
+
+        assert!(select(RetentionPolicy::default()).is_empty());
+        assert_eq!(
+            ids(&select(RetentionPolicy {
+                max_age: Some(Duration::from_secs(24 * 3600)),
+                ..Default::default()
+            })),
+            vec!["old"]
+        );
+        assert_eq!(
+            ids(&select(RetentionPolicy {
+                keep_latest: Some(1),
+                ..Default::default()
+            })),
+            vec!["mid", "old"]
+        );
+        assert_eq!(
+            ids(&select(RetentionPolicy {
+                max_total_bytes: Some(250),
+                ..Default::default()
+            })),
+            vec!["old"]
+        );
+        assert_eq!(
+            ids(&select(RetentionPolicy {
+                keep_latest: Some(1),
+                pipeline_id: Some("rec".to_string()),
+                ..Default::default()
+            })),
+            vec!["old"]
+        );
+    }
+
+    #[test]
+    fn test_parse_artifact_uri() {
+        assert_eq!(
+            parse_artifact_uri("artifact://abc123").unwrap(),
+            ("abc123".to_string(), ByteRange::default())
+        );
+        assert_eq!(
+            parse_artifact_uri("artifact://abc123?offset=1024&length=512").unwrap(),
+            (
+                "abc123".to_string(),
+                ByteRange {
+                    offset: 1024,
+                    length: Some(512)
+                }
+            )
+        );
+        assert!(parse_artifact_uri("file:///tmp/out.mp4").is_err());
+        assert!(parse_artifact_uri("artifact://").is_err());
+        assert!(parse_artifact_uri("artifact://abc123?offset=-1").is_err());
+        assert!(parse_artifact_uri("artifact://abc123?start=0").is_err());
+    }
+
+    #[test]
+    fn test_location_glob() {
+        assert_eq!(location_glob("/rec/out.mp4"), None);
+        assert_eq!(
+            location_glob("/rec/segment%05d.ts").as_deref(),
+            Some("/rec/segment*.ts")
+        );
+        assert_eq!(
+            location_glob("frame-%d-100%%.png").as_deref(),
+            Some("frame-*-100%.png")
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use gstreamer as gst;
+use parking_lot::Mutex;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::error::Result as McpResult;
+use crate::pipeline::attach_tee_branch;
+
+/// Sample peak in dBFS at or above which audio counts as clipping
+pub const DEFAULT_CLIP_LEVEL_DB: f64 = -0.1;
+/// Clipping must last this long before it is reported
+pub const DEFAULT_CLIP_DURATION_MS: u64 = 500;
+/// RMS level in dBFS below which audio counts as silent
+pub const DEFAULT_SILENCE_LEVEL_DB: f64 = -60.0;
+/// Silence must last this long before it is reported
+pub const DEFAULT_SILENCE_DURATION_MS: u64 = 5000;
+
+/// Floor of reported levels; digital silence has no finite level
+const MIN_LEVEL_DB: f64 = -120.0;
+
+/// Alert thresholds of an audio monitor. Unset values use the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct AudioThresholds {
+    #[schemars(description = "Sample peak in dBFS counted as clipping (default: -0.1)")]
+    pub clip_level_db: Option<f64>,
+    #[schemars(
+        description = "How long clipping must continue before a warning, in milliseconds (default: 500)"
+    )]
+    pub clip_duration_ms: Option<u64>,
+    #[schemars(description = "RMS level in dBFS counted as silence (default: -60)")]
+    pub silence_level_db: Option<f64>,
+    #[schemars(
+        description = "How long silence must continue before a warning, in milliseconds (default: 5000)"
+    )]
+    pub silence_duration_ms: Option<u64>,
+}
+
+impl AudioThresholds {
+    pub fn clip_level_db(&self) -> f64 {
+        self.clip_level_db.unwrap_or(DEFAULT_CLIP_LEVEL_DB)
+    }
+
+    pub fn clip_duration_ms(&self) -> u64 {
+        self.clip_duration_ms.unwrap_or(DEFAULT_CLIP_DURATION_MS)
+    }
+
+    pub fn silence_level_db(&self) -> f64 {
+        self.silence_level_db.unwrap_or(DEFAULT_SILENCE_LEVEL_DB)
+    }
+
+    pub fn silence_duration_ms(&self) -> u64 {
+        self.silence_duration_ms
+            .unwrap_or(DEFAULT_SILENCE_DURATION_MS)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioCondition {
+    Clipping,
+    Silence,
+}
+
+impl std::fmt::Display for AudioCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AudioCondition::Clipping => "clipping",
+            AudioCondition::Silence => "silence",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Peak and RMS level of a stretch of audio, in dBFS
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LevelReading {
+    pub peak_db: f64,
+    pub rms_db: f64,
+}
+
+fn to_db(amplitude: f64) -> f64 {
+    if amplitude > 0.0 {
+        (20.0 * amplitude.log10()).max(MIN_LEVEL_DB)
+    } else {
+        MIN_LEVEL_DB
+    }
+}
+
+/// Level of float samples of any channel layout, full scale being 1.0
+pub fn measure(samples: &[f32]) -> LevelReading {
+    let peak = samples
+        .iter()
+        .fold(0.0f64, |peak, s| peak.max(s.abs() as f64));
+    let mean_square = if samples.is_empty() {
+        0.0
+    } else {
+        samples.iter().map(|s| (*s as f64).powi(2)).sum::<f64>() / samples.len() as f64
+    };
+    LevelReading {
+        peak_db: to_db(peak),
+        rms_db: to_db(mean_square.sqrt()),
+    }
+}
+
+/// A condition starting or ending on a monitored pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioAlert {
+    pub pipeline_id: String,
+    pub branch: String,
+    pub condition: AudioCondition,
+    /// True when the condition starts, false when it is over
+    pub active: bool,
+    /// How long the condition has lasted
+    pub duration_ms: u64,
+    pub peak_db: f64,
+    pub rms_db: f64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// Levels of a monitored pipeline and the conditions in progress
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioLevelStatus {
+    pub thresholds: AudioThresholds,
+    pub peak_db: Option<f64>,
+    pub rms_db: Option<f64>,
+    pub clipping: bool,
+    pub silent: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ConditionRun {
+    /// Audio time the condition has held without interruption
+    elapsed_ns: u64,
+    reported: bool,
+}
+
+impl ConditionRun {
+    /// Extend or end the run. Returns the new reported state and the run
+    /// length when it changes.
+    fn update(&mut self, holds: bool, duration_ns: u64, limit_ms: u64) -> Option<(bool, u64)> {
+        if holds {
+            self.elapsed_ns += duration_ns;
+            if !self.reported && self.elapsed_ns >= limit_ms * 1_000_000 {
+                self.reported = true;
+                return Some((true, self.elapsed_ns / 1_000_000));
+            }
+            return None;
+        }
+        let ended = self
+            .reported
+            .then_some((false, self.elapsed_ns / 1_000_000));
+        *self = ConditionRun::default();
+        ended
+    }
+}
+
+/// Watches the levels of successive audio buffers for sustained clipping
+/// and silence. A condition is reported once when it starts and once when
+/// it ends.
+#[derive(Debug, Clone, Default)]
+pub struct LevelMonitor {
+    thresholds: AudioThresholds,
+    last: Option<LevelReading>,
+    clipping: ConditionRun,
+    silence: ConditionRun,
+}
+
+impl LevelMonitor {
+    pub fn new(thresholds: AudioThresholds) -> Self {
+        Self {
+            thresholds,
+            ..Default::default()
+        }
+    }
+
+    /// Replace the thresholds; conditions in progress are measured again
+    pub fn set_thresholds(&mut self, thresholds: AudioThresholds) {
+        *self = LevelMonitor::new(thresholds);
+    }
+
+    /// Account for `duration_ns` of audio at `reading`. Returns the
+    /// conditions that started or ended, with their length in milliseconds.
+    pub fn record(
+        &mut self,
+        reading: LevelReading,
+        duration_ns: u64,
+    ) -> Vec<(AudioCondition, bool, u64)> {
+        self.last = Some(reading);
+        let clipping = self.clipping.update(
+            reading.peak_db >= self.thresholds.clip_level_db(),
+            duration_ns,
+            self.thresholds.clip_duration_ms(),
+        );
+        let silence = self.silence.update(
+            reading.rms_db < self.thresholds.silence_level_db(),
+            duration_ns,
+            self.thresholds.silence_duration_ms(),
+        );
+        [
+            clipping.map(|(active, ms)| (AudioCondition::Clipping, active, ms)),
+            silence.map(|(active, ms)| (AudioCondition::Silence, active, ms)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    pub fn status(&self) -> AudioLevelStatus {
+        AudioLevelStatus {
+            thresholds: self.thresholds.clone(),
+            peak_db: self.last.map(|r| r.peak_db),
+            rms_db: self.last.map(|r| r.rms_db),
+            clipping: self.clipping.reported,
+            silent: self.silence.reported,
+        }
+    }
+}
+
+/// Branch a level meter off a tee of a running pipeline. Each buffer is
+/// measured against `monitor`, and conditions starting or ending are
+/// passed to `on_alert` from a streaming thread. Returns the name of the
+/// added branch.
+pub fn attach_audio_monitor(
+    pipeline: &gst::Pipeline,
+    pipeline_id: &str,
+    tee_name: Option<&str>,
+    monitor: Arc<Mutex<LevelMonitor>>,
+    on_alert: impl Fn(AudioAlert) + Send + Sync + 'static,
+) -> McpResult<String> {
+    // Leaky queue: metering must never stall the main pipeline
+    let description = "queue leaky=downstream max-size-time=1000000000 ! audioconvert ! audio/x-raw,format=F32LE,layout=interleaved ! fakesink name=sink signal-handoffs=true sync=false async=false";
+    let id = pipeline_id.to_string();
+    attach_tee_branch(
+        pipeline,
+        tee_name,
+        "levels",
+        description,
+        move |branch, buffer| {
+            let (Ok(map), Some(duration)) = (buffer.map_readable(), buffer.duration()) else {
+                return;
+            };
+            let samples: Vec<f32> = map
+                .as_slice()
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            let reading = measure(&samples);
+            let changes = monitor.lock().record(reading, duration.nseconds());
+            for (condition, active, duration_ms) in changes {
+                on_alert(AudioAlert {
+                    pipeline_id: id.clone(),
+                    branch: branch.to_string(),
+                    condition,
+                    active,
+                    duration_ms,
+                    peak_db: reading.peak_db,
+                    rms_db: reading.rms_db,
+                    timestamp: chrono::Utc::now(),
+                });
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let reading = measure(&[0.5, -1.0, 0.5, -0.5]);
+        assert!(reading.peak_db.abs() < 1e-9);
+        // RMS of 0.5, 1.0, 0.5, 0.5 is sqrt(0.4375)
+        assert!((reading.rms_db - 20.0 * 0.4375f64.sqrt().log10()).abs() < 1e-9);
+        assert_eq!(measure(&[0.0; 8]).rms_db, MIN_LEVEL_DB);
+        assert_eq!(measure(&[]).peak_db, MIN_LEVEL_DB);
+    }
+
+    #[test]
+    fn test_level_monitor() {
+        let mut monitor = LevelMonitor::new(AudioThresholds {
+            clip_duration_ms: Some(300),
+            silence_duration_ms: Some(1000),
+            ..Default::default()
+        });
+        let clipped = LevelReading {
+            peak_db: 0.0,
+            rms_db: -3.0,
+        };
+        let silent = LevelReading {
+            peak_db: -80.0,
+            rms_db: -90.0,
+        };
+        let buffer_ns = 100_000_000;
+
+        assert!(monitor.record(clipped, buffer_ns).is_empty());
+        assert!(monitor.record(clipped, buffer_ns).is_empty());
+        assert_eq!(
+            monitor.record(clipped, buffer_ns),
+            vec![(AudioCondition::Clipping, true, 300)]
+        );
+        // Reported once
+        assert!(monitor.record(clipped, buffer_ns).is_empty());
+        assert!(monitor.status().clipping);
+
+        assert_eq!(
+            monitor.record(silent, buffer_ns),
+            vec![(AudioCondition::Clipping, false, 400)]
+        );
+        for _ in 0..8 {
+            assert!(monitor.record(silent, buffer_ns).is_empty());
+        }
+        assert_eq!(
+            monitor.record(silent, buffer_ns),
+            vec![(AudioCondition::Silence, true, 1000)]
+        );
+        assert!(monitor.status().silent);
+        assert!(!monitor.status().clipping);
+
+        monitor.set_thresholds(AudioThresholds::default());
+        assert!(!monitor.status().silent);
+    }
+}
@@ -0,0 +1,277 @@
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+
+use crate::discovery::ensure_gstreamer_initialized;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::memory::QueueLeak;
+
+/// Elements that decouple a branch from the others with a thread of its own
+const QUEUE_FACTORIES: &[&str] = &["queue", "queue2", "multiqueue"];
+
+/// A branch of a tee or demuxer that does not start with a queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MissingQueue {
+    /// Tee or demuxer the branch leaves from
+    pub element: String,
+    /// Byte offset in the description of the first element of the branch
+    pub position: usize,
+    /// First element of the branch, as written
+    pub first: String,
+}
+
+impl std::fmt::Display for MissingQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "branch of '{}' at position {} starts with '{}' instead of a queue",
+            self.element, self.position, self.first
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ItemKind {
+    /// Factory and `name` property
+    Element(String, Option<String>),
+    /// `name.` or `name.pad`, standing for the named element
+    Reference(String),
+    Caps,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Item {
+    kind: ItemKind,
+    position: usize,
+    text: String,
+}
+
+/// Split a description into tokens and their byte offsets. `!` is a token
+/// of its own; quoted text and escaped characters stay in their token.
+fn tokenize(description: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in description.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' => {
+                escaped = true;
+                start.get_or_insert(i);
+            }
+            '"' | '\'' if quote == Some(c) => quote = None,
+            '"' | '\'' if quote.is_none() => {
+                quote = Some(c);
+                start.get_or_insert(i);
+            }
+            _ if quote.is_some() => {}
+            '!' => {
+                if let Some(s) = start.take() {
+                    tokens.push((s, &description[s..i]));
+                }
+                tokens.push((i, "!"));
+            }
+            c if c.is_whitespace() => {
+                if let Some(s) = start.take() {
+                    tokens.push((s, &description[s..i]));
+                }
+            }
+            _ => {
+                start.get_or_insert(i);
+            }
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((s, &description[s..]));
+    }
+    tokens
+}
+
+/// Items of a description and the links between them, as pairs of indices
+fn parse_items(description: &str) -> (Vec<Item>, Vec<(usize, usize)>) {
+    let mut items: Vec<Item> = Vec::new();
+    let mut links = Vec::new();
+    let mut linking = false;
+    for (position, token) in tokenize(description) {
+        if token == "!" {
+            linking = true;
+            continue;
+        }
+        let key = token.split('=').next().unwrap_or(token);
+        let kind = if key.contains('/') || key.contains(',') {
+            ItemKind::Caps
+        } else if let Some(value) = token.strip_prefix("name=") {
+            // A property of the element before it
+            if let Some(Item {
+                kind: ItemKind::Element(_, name),
+                ..
+            }) = items.last_mut()
+            {
+                *name = Some(value.trim_matches(|c| c == '"' || c == '\'').to_string());
+            }
+            continue;
+        } else if token.contains('=') {
+            continue;
+        } else if let Some((element, _)) = token.split_once('.') {
+            ItemKind::Reference(element.to_string())
+        } else {
+            ItemKind::Element(token.to_string(), None)
+        };
+        if linking && !items.is_empty() {
+            links.push((items.len() - 1, items.len()));
+        }
+        linking = false;
+        items.push(Item {
+            kind,
+            position,
+            text: token.to_string(),
+        });
+    }
+    (items, links)
+}
+
+/// Branches of tees and demuxers that do not start with a queue, for
+/// elements with more than one branch. Without queues the first branch to
+/// block, such as a sink waiting to preroll, starves the others and the
+/// pipeline deadlocks. Only named elements can be told apart; `is_branching`
+/// decides which factories split the stream.
+pub fn find_missing_queues(
+    description: &str,
+    is_branching: impl Fn(&str) -> bool,
+) -> Vec<MissingQueue> {
+    let (items, links) = parse_items(description);
+    let node = |item: &Item| match &item.kind {
+        ItemKind::Element(_, name) => name.clone(),
+        ItemKind::Reference(name) => Some(name.clone()),
+        ItemKind::Caps => None,
+    };
+    let branching: Vec<String> = items
+        .iter()
+        .filter_map(|item| match &item.kind {
+            ItemKind::Element(factory, Some(name)) if is_branching(factory) => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let mut missing = Vec::new();
+    for element in branching {
+        let branches: Vec<usize> = links
+            .iter()
+            .filter(|(from, _)| node(&items[*from]).as_deref() == Some(element.as_str()))
+            .map(|(_, to)| *to)
+            .collect();
+        if branches.len() < 2 {
+            continue;
+        }
+        for first in branches {
+            // Caps filters are not thread boundaries; look past them
+            let mut current = first;
+            while items[current].kind == ItemKind::Caps {
+                match links.iter().find(|(from, _)| *from == current) {
+                    Some((_, next)) => current = *next,
+                    None => break,
+                }
+            }
+            let queued = matches!(&items[current].kind,
+                ItemKind::Element(factory, _) if QUEUE_FACTORIES.contains(&factory.as_str()));
+            if !queued {
+                missing.push(MissingQueue {
+                    element: element.clone(),
+                    position: items[first].position,
+                    first: items[first].text.clone(),
+                });
+            }
+        }
+    }
+    missing.sort_by_key(|m| m.position);
+    missing
+}
+
+/// Start each branch with `queue`, a queue element in gst-launch syntax
+pub fn insert_queues(description: &str, missing: &[MissingQueue], queue: &str) -> String {
+    let mut positions: Vec<usize> = missing.iter().map(|m| m.position).collect();
+    positions.sort_unstable();
+    positions.dedup();
+    let mut output = description.to_string();
+    for position in positions.into_iter().rev() {
+        output.insert_str(position, &format!("{} ! ", queue));
+    }
+    output
+}
+
+/// Tees and elements the registry classes as demuxers
+pub fn is_branching_factory(factory: &str) -> bool {
+    factory == "tee"
+        || gst::ElementFactory::find(factory)
+            .and_then(|f| f.metadata("klass").map(|k| k.contains("Demuxer")))
+            .unwrap_or(false)
+}
+
+/// Check the branches of a description for queues. With `insert`, missing
+/// queues are added, leaking as `leak` says, and the new description
+/// returned with the branches that needed one; otherwise they are an error
+/// naming their positions.
+pub fn check_branch_queues(
+    description: &str,
+    insert: bool,
+    leak: QueueLeak,
+) -> McpResult<(String, Vec<MissingQueue>)> {
+    ensure_gstreamer_initialized()?;
+    let missing = find_missing_queues(description, is_branching_factory);
+    if missing.is_empty() || insert {
+        return Ok((insert_queues(description, &missing, leak.queue()), missing));
+    }
+    let branches: Vec<String> = missing.iter().map(|m| m.to_string()).collect();
+    Err(GStreamerMcpError::PipelineError(format!(
+        "Branches without a queue would deadlock the pipeline: {}. Add 'queue !' at the start of each branch, or set auto_queue to insert them",
+        branches.join("; ")
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_branching(factory: &str) -> bool {
+        factory == "tee" || factory == "qtdemux"
+    }
+
+    #[test]
+    fn test_find_missing_queues() {
+        let description =
+            "videotestsrc ! tee name=t ! x264enc ! fakesink t. ! video/x-raw ! queue ! autovideosink t. ! fakesink";
+        let missing = find_missing_queues(description, is_branching);
+        assert_eq!(
+            missing,
+            vec![
+                MissingQueue {
+                    element: "t".to_string(),
+                    position: 28,
+                    first: "x264enc".to_string(),
+                },
+                MissingQueue {
+                    element: "t".to_string(),
+                    position: 93,
+                    first: "fakesink".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            insert_queues(description, &missing, "queue"),
+            "videotestsrc ! tee name=t ! queue ! x264enc ! fakesink t. ! video/x-raw ! queue ! autovideosink t. ! queue ! fakesink"
+        );
+
+        // A single branch cannot starve another
+        assert!(
+            find_missing_queues("videotestsrc ! tee name=t ! fakesink", is_branching).is_empty()
+        );
+        // Demuxer pads, and quoted values that look like links
+        let demux = "filesrc location=\"a ! b.mp4\" ! qtdemux name=d d.video_0 ! queue ! fakesink d.audio_0 ! fakesink";
+        let missing = find_missing_queues(demux, is_branching);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(&demux[missing[0].position..], "fakesink");
+    }
+}
@@ -0,0 +1,210 @@
+use parking_lot::RwLock;
+use rmcp::model::LoggingLevel;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::notifications::Notifier;
+use crate::pipeline::{attach_cause, BusMessage, PipelineManager};
+
+/// Bus events a client can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BusEventKind {
+    Error,
+    Warning,
+    Eos,
+    StateChanged,
+}
+
+impl BusEventKind {
+    /// Events delivered when a subscription does not list any
+    pub const DEFAULT: [BusEventKind; 3] = [
+        BusEventKind::Error,
+        BusEventKind::Eos,
+        BusEventKind::StateChanged,
+    ];
+
+    /// Event of a recorded message, from its type
+    pub fn from_message_type(message_type: &str) -> Option<Self> {
+        match message_type {
+            "Error" => Some(BusEventKind::Error),
+            "Warning" => Some(BusEventKind::Warning),
+            "Eos" => Some(BusEventKind::Eos),
+            "StateChanged" => Some(BusEventKind::StateChanged),
+            _ => None,
+        }
+    }
+
+    fn level(self) -> LoggingLevel {
+        match self {
+            BusEventKind::Error => LoggingLevel::Error,
+            BusEventKind::Warning => LoggingLevel::Warning,
+            BusEventKind::Eos | BusEventKind::StateChanged => LoggingLevel::Info,
+        }
+    }
+}
+
+impl std::fmt::Display for BusEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BusEventKind::Error => "error",
+            BusEventKind::Warning => "warning",
+            BusEventKind::Eos => "eos",
+            BusEventKind::StateChanged => "state_changed",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Events of one pipeline, or of every pipeline, pushed to the client
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSubscription {
+    pub id: String,
+    /// Unset for every pipeline, including those launched later
+    pub pipeline_id: Option<String>,
+    pub events: Vec<BusEventKind>,
+}
+
+impl EventSubscription {
+    pub fn matches(&self, pipeline_id: &str, kind: BusEventKind) -> bool {
+        self.pipeline_id
+            .as_deref()
+            .is_none_or(|id| id == pipeline_id)
+            && self.events.contains(&kind)
+    }
+}
+
+/// Subscriptions of the client to bus events
+#[derive(Debug, Clone, Default)]
+pub struct EventSubscriptions {
+    subscriptions: Arc<RwLock<HashMap<String, EventSubscription>>>,
+}
+
+impl EventSubscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a subscription, or replace the one with the same ID. Without
+    /// events, errors, EOS and state changes are delivered.
+    pub fn subscribe(
+        &self,
+        id: Option<String>,
+        pipeline_id: Option<String>,
+        events: Vec<BusEventKind>,
+    ) -> EventSubscription {
+        let subscription = EventSubscription {
+            id: id.unwrap_or_else(|| format!("events-{}", Uuid::new_v4())),
+            pipeline_id,
+            events: if events.is_empty() {
+                BusEventKind::DEFAULT.to_vec()
+            } else {
+                events
+            },
+        };
+        self.subscriptions
+            .write()
+            .insert(subscription.id.clone(), subscription.clone());
+        subscription
+    }
+
+    /// Remove a subscription. Returns it if it existed.
+    pub fn unsubscribe(&self, id: &str) -> Option<EventSubscription> {
+        self.subscriptions.write().remove(id)
+    }
+
+    pub fn list(&self) -> Vec<EventSubscription> {
+        let mut subscriptions: Vec<_> = self.subscriptions.read().values().cloned().collect();
+        subscriptions.sort_by(|a, b| a.id.cmp(&b.id));
+        subscriptions
+    }
+
+    /// IDs of the subscriptions an event is delivered for
+    pub fn matching(&self, pipeline_id: &str, kind: BusEventKind) -> Vec<String> {
+        let mut ids: Vec<String> = self
+            .subscriptions
+            .read()
+            .values()
+            .filter(|s| s.matches(pipeline_id, kind))
+            .map(|s| s.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+}
+
+/// Push the errors, warnings, EOS and state changes recorded for every
+/// pipeline to the client as `bus_event` notifications, for the events it
+/// subscribed to
+pub fn spawn_bus_events(
+    pipeline_manager: &Arc<PipelineManager>,
+    subscriptions: EventSubscriptions,
+    notifier: Notifier,
+) {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<(String, BusMessage)>();
+    pipeline_manager.set_bus_events(tx);
+    tokio::spawn(async move {
+        while let Some((pipeline_id, message)) = rx.recv().await {
+            let Some(kind) = BusEventKind::from_message_type(&message.message_type) else {
+                continue;
+            };
+            let ids = subscriptions.matching(&pipeline_id, kind);
+            if ids.is_empty() {
+                continue;
+            }
+            let mut data = serde_json::json!({
+                "pipeline_id": pipeline_id,
+                "event": kind,
+                "message": message.message,
+                "source": message.source,
+                "timestamp": message.timestamp,
+                "subscriptions": ids,
+            });
+            attach_cause(&mut data, message.cause.as_ref());
+            notifier.notify("bus_event", kind.level(), data).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_subscriptions() {
+        assert_eq!(
+            BusEventKind::from_message_type("StateChanged"),
+            Some(BusEventKind::StateChanged)
+        );
+        assert_eq!(BusEventKind::from_message_type("Tag"), None);
+
+        let subscriptions = EventSubscriptions::new();
+        let all = subscriptions.subscribe(Some("all".to_string()), None, Vec::new());
+        assert_eq!(all.events, BusEventKind::DEFAULT.to_vec());
+        subscriptions.subscribe(
+            Some("cam".to_string()),
+            Some("cam".to_string()),
+            vec![BusEventKind::Warning, BusEventKind::Error],
+        );
+
+        assert_eq!(
+            subscriptions.matching("cam", BusEventKind::Error),
+            vec!["all", "cam"]
+        );
+        assert_eq!(
+            subscriptions.matching("other", BusEventKind::Warning),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            subscriptions.matching("cam", BusEventKind::Warning),
+            vec!["cam"]
+        );
+
+        assert!(subscriptions.unsubscribe("all").is_some());
+        assert!(subscriptions.unsubscribe("all").is_none());
+        assert!(subscriptions.matching("x", BusEventKind::Eos).is_empty());
+    }
+}
@@ -1,12 +1,117 @@
-use futures::prelude::*;
 use gstreamer as gst;
 use gstreamer::prelude::*;
+use parking_lot::{Condvar, Mutex};
+use std::collections::VecDeque;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
+use crate::element_messages::{decode_element_message, element_message_text};
 use crate::pipeline::{BusMessage, PipelineManager};
 
+/// How long the bus is waited on before checking that the watch still runs
+const DRAIN_POLL: gst::ClockTime = gst::ClockTime::from_mseconds(100);
+
+/// Warnings and errors kept until they are collected; the oldest are
+/// dropped first
+const MAX_PENDING_PROBLEMS: usize = 100;
+
+/// EOS and errors kept for the callers waiting on them
+const MAX_RECENT_ENDS: usize = 16;
+
+/// How a pipeline's stream ended, as posted on its bus
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEnd {
+    Eos,
+    Error(String),
+}
+
+#[derive(Debug, Default)]
+struct Drained {
+    /// EOS and errors taken off the bus so far
+    ends_seen: u64,
+    /// The latest of them, numbered from 1
+    ends: VecDeque<(u64, StreamEnd)>,
+    /// EOS taken off the bus and not yet taken by `take_eos`
+    eos: bool,
+    /// Warnings and errors not collected yet, oldest first
+    problems: VecDeque<gst::Message>,
+}
+
+/// Messages the bus handler took off a pipeline's bus that other callers
+/// wait on: EOS and errors, and the warnings and errors not collected yet.
+/// The handler is the only one popping a watched bus, so nothing is left
+/// queued on it.
+#[derive(Debug, Default)]
+pub struct DrainedMessages {
+    state: Mutex<Drained>,
+    posted: Condvar,
+}
+
+impl DrainedMessages {
+    fn keep(&self, msg: &gst::Message) {
+        let end = match msg.view() {
+            gst::MessageView::Eos(_) => Some(StreamEnd::Eos),
+            gst::MessageView::Error(err) => Some(StreamEnd::Error(err.error().to_string())),
+            _ => None,
+        };
+        let problem = matches!(
+            msg.view(),
+            gst::MessageView::Warning(_) | gst::MessageView::Error(_)
+        );
+
+        let mut state = self.state.lock();
+        if problem {
+            if state.problems.len() >= MAX_PENDING_PROBLEMS {
+                state.problems.pop_front();
+            }
+            state.problems.push_back(msg.clone());
+        }
+        if let Some(end) = end {
+            state.eos |= end == StreamEnd::Eos;
+            state.ends_seen += 1;
+            let seen = state.ends_seen;
+            if state.ends.len() >= MAX_RECENT_ENDS {
+                state.ends.pop_front();
+            }
+            state.ends.push_back((seen, end));
+            self.posted.notify_all();
+        }
+    }
+
+    /// Number of EOS and errors taken off the bus so far, to wait for the
+    /// next ones with [`DrainedMessages::wait_end`]
+    pub fn ends_seen(&self) -> u64 {
+        self.state.lock().ends_seen
+    }
+
+    /// Wait up to `timeout` for the first EOS or error after the first
+    /// `after` ones
+    pub fn wait_end(&self, after: u64, timeout: Duration) -> Option<StreamEnd> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock();
+        loop {
+            if let Some((_, end)) = state.ends.iter().find(|(seen, _)| *seen > after) {
+                return Some(end.clone());
+            }
+            if self.posted.wait_until(&mut state, deadline).timed_out() {
+                return None;
+            }
+        }
+    }
+
+    /// Whether EOS was posted since the last call
+    pub fn take_eos(&self) -> bool {
+        std::mem::take(&mut self.state.lock().eos)
+    }
+
+    /// Warnings and errors posted since the last call, oldest first
+    pub fn take_problems(&self) -> Vec<gst::Message> {
+        self.state.lock().problems.drain(..).collect()
+    }
+}
+
 pub struct BusHandler {
     pipeline_manager: Arc<PipelineManager>,
 }
@@ -16,34 +121,51 @@ impl BusHandler {
         Self { pipeline_manager }
     }
 
+    /// Record the messages of a pipeline until `shutdown_rx` is sent to or
+    /// dropped. The bus is drained on a thread of its own, so messages do
+    /// not pile up on it; those other callers wait on are handed to
+    /// `drained`.
     pub async fn watch_pipeline(
         &self,
         pipeline_id: String,
         pipeline: gst::Pipeline,
+        drained: Arc<DrainedMessages>,
         mut shutdown_rx: mpsc::Receiver<()>,
     ) {
         let bus = pipeline.bus().expect("Pipeline should have a bus");
 
-        // Set up bus watch
-        bus.add_signal_watch();
-
-        // Create a stream for bus messages
-        let mut messages = bus.stream();
+        // Stops once the watch is over and the receiver dropped
+        let (tx, mut messages) = mpsc::unbounded_channel();
+        let spawned = std::thread::Builder::new()
+            .name(format!("bus-{}", pipeline_id))
+            .spawn(move || {
+                while !tx.is_closed() {
+                    if let Some(msg) = bus.timed_pop(DRAIN_POLL) {
+                        if tx.send(msg).is_err() {
+                            break;
+                        }
+                    }
+                }
+            });
+        if let Err(e) = spawned {
+            error!("Failed to watch the bus of pipeline {}: {}", pipeline_id, e);
+            return;
+        }
 
         loop {
             tokio::select! {
-                Some(msg) = messages.next() => {
+                Some(msg) = messages.recv() => {
                     self.handle_message(&pipeline_id, &msg).await;
+                    drained.keep(&msg);
                 }
+                // Also ends the watch when the pipeline is removed and the
+                // sender dropped
                 _ = shutdown_rx.recv() => {
                     debug!("Received shutdown signal for pipeline {}", pipeline_id);
                     break;
                 }
             }
         }
-
-        // Cleanup
-        bus.remove_signal_watch();
     }
 
     async fn handle_message(&self, pipeline_id: &str, msg: &gst::Message) {
@@ -55,6 +177,8 @@ impl BusHandler {
                     message_type: "Eos".to_string(),
                     message: "End of stream".to_string(),
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
             gst::MessageView::Error(err) => {
@@ -70,6 +194,8 @@ impl BusHandler {
                     message_type: "Error".to_string(),
                     message: error_msg,
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
             gst::MessageView::Warning(warn) => {
@@ -85,26 +211,27 @@ impl BusHandler {
                     message_type: "Warning".to_string(),
                     message: warning_msg,
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
-            gst::MessageView::StateChanged(state_changed) => {
-                // Only track pipeline state changes, not element state changes
-                if msg.src().map(|s| s.type_().name()) == Some("GstPipeline") {
-                    let message = format!(
-                        "State changed from {:?} to {:?}",
-                        state_changed.old(),
-                        state_changed.current()
-                    );
-                    debug!("Pipeline {} state change: {}", pipeline_id, message);
-                    BusMessage {
-                        timestamp: chrono::Utc::now(),
-                        message_type: "StateChanged".to_string(),
-                        message,
-                        source: msg.src().map(|s| s.path_string().to_string()),
-                    }
-                } else {
-                    // Skip element state changes, only log pipeline state changes
-                    return;
+            // Only track pipeline state changes, not element state changes
+            gst::MessageView::StateChanged(state_changed)
+                if msg.src().map(|s| s.type_().name()) == Some("GstPipeline") =>
+            {
+                let message = format!(
+                    "State changed from {:?} to {:?}",
+                    state_changed.old(),
+                    state_changed.current()
+                );
+                debug!("Pipeline {} state change: {}", pipeline_id, message);
+                BusMessage {
+                    timestamp: chrono::Utc::now(),
+                    message_type: "StateChanged".to_string(),
+                    message,
+                    source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
             gst::MessageView::Buffering(buffering) => {
@@ -115,6 +242,8 @@ impl BusHandler {
                     message_type: "Buffering".to_string(),
                     message: format!("Buffering: {}%", percent),
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
             gst::MessageView::Tag(tag) => {
@@ -125,6 +254,8 @@ impl BusHandler {
                     message_type: "Tag".to_string(),
                     message: format!("Tags: {:?}", tags),
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
             gst::MessageView::StreamStatus(status) => {
@@ -138,6 +269,8 @@ impl BusHandler {
                     message_type: "StreamStatus".to_string(),
                     message: format!("Stream status: {:?}", status.type_()),
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
             gst::MessageView::Application(_app) => {
@@ -147,15 +280,20 @@ impl BusHandler {
                     message_type: "Application".to_string(),
                     message: "Application-specific message".to_string(),
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
             gst::MessageView::Element(_element) => {
                 debug!("Pipeline {} element message", pipeline_id);
+                let details = decode_element_message(msg);
                 BusMessage {
                     timestamp: chrono::Utc::now(),
                     message_type: "Element".to_string(),
-                    message: "Element-specific message".to_string(),
+                    message: element_message_text(msg, details.as_ref()),
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details,
                 }
             }
             gst::MessageView::DurationChanged(_) => {
@@ -165,6 +303,8 @@ impl BusHandler {
                     message_type: "DurationChanged".to_string(),
                     message: "Duration changed".to_string(),
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
             gst::MessageView::Latency(_) => {
@@ -174,6 +314,8 @@ impl BusHandler {
                     message_type: "Latency".to_string(),
                     message: "Latency update".to_string(),
                     source: msg.src().map(|s| s.path_string().to_string()),
+                    cause: None,
+                    details: None,
                 }
             }
             _ => {
@@ -199,57 +341,145 @@ pub fn create_blocking_bus_handler(
 
     let timeout = timeout.unwrap_or(gst::ClockTime::from_seconds(5));
 
-    loop {
-        match bus.timed_pop(timeout) {
-            Some(msg) => {
-                let should_break = matches!(
-                    msg.view(),
-                    gst::MessageView::Eos(_) | gst::MessageView::Error(_)
-                );
+    // Stops at the first timeout with no message
+    while let Some(msg) = bus.timed_pop(timeout) {
+        let should_break = matches!(
+            msg.view(),
+            gst::MessageView::Eos(_) | gst::MessageView::Error(_)
+        );
 
-                // Create and store the message
-                let bus_message = message_to_bus_message(&msg);
-                pipeline_manager.add_bus_message(pipeline_id, bus_message);
+        // Create and store the message
+        let bus_message = message_to_bus_message(&msg);
+        pipeline_manager.add_bus_message(pipeline_id, bus_message);
 
-                if should_break {
-                    break;
-                }
-            }
-            None => {
-                // Timeout - no message received
-                break;
-            }
+        if should_break {
+            break;
         }
     }
 
     Ok(())
 }
 
-fn message_to_bus_message(msg: &gst::Message) -> BusMessage {
+/// Convert a raw bus message into the stored [`BusMessage`] form
+pub fn message_to_bus_message(msg: &gst::Message) -> BusMessage {
     match msg.view() {
         gst::MessageView::Eos(_) => BusMessage {
             timestamp: chrono::Utc::now(),
             message_type: "Eos".to_string(),
             message: "End of stream".to_string(),
             source: msg.src().map(|s| s.path_string().to_string()),
+            cause: None,
+            details: None,
         },
         gst::MessageView::Error(err) => BusMessage {
             timestamp: chrono::Utc::now(),
             message_type: "Error".to_string(),
             message: format!("Error: {} ({:?})", err.error(), err.debug()),
             source: msg.src().map(|s| s.path_string().to_string()),
+            cause: None,
+            details: None,
         },
         gst::MessageView::Warning(warn) => BusMessage {
             timestamp: chrono::Utc::now(),
             message_type: "Warning".to_string(),
             message: format!("Warning: {} ({:?})", warn.error(), warn.debug()),
             source: msg.src().map(|s| s.path_string().to_string()),
+            cause: None,
+            details: None,
         },
+        gst::MessageView::Element(_) => {
+            let details = decode_element_message(msg);
+            BusMessage {
+                timestamp: chrono::Utc::now(),
+                message_type: "Element".to_string(),
+                message: element_message_text(msg, details.as_ref()),
+                source: msg.src().map(|s| s.path_string().to_string()),
+                cause: None,
+                details,
+            }
+        }
         _ => BusMessage {
             timestamp: chrono::Utc::now(),
             message_type: format!("{:?}", msg.type_()),
             message: "Message received".to_string(),
             source: msg.src().map(|s| s.path_string().to_string()),
+            cause: None,
+            details: None,
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(text: &str) -> gst::Message {
+        gst::message::Error::builder(gst::CoreError::Failed, text).build()
+    }
+
+    fn warning(text: &str) -> gst::Message {
+        gst::message::Warning::builder(gst::CoreError::Failed, text).build()
+    }
+
+    #[test]
+    fn test_drained_messages() {
+        gst::init().unwrap();
+        let drained = DrainedMessages::default();
+        assert_eq!(drained.wait_end(0, Duration::from_millis(10)), None);
+
+        drained.keep(&warning("late"));
+        drained.keep(&gst::message::Eos::new());
+        drained.keep(&error("boom"));
+        assert_eq!(drained.ends_seen(), 2);
+        assert_eq!(drained.wait_end(0, Duration::ZERO), Some(StreamEnd::Eos));
+        assert_eq!(
+            drained.wait_end(1, Duration::ZERO),
+            Some(StreamEnd::Error("boom".to_string()))
+        );
+        assert_eq!(drained.wait_end(2, Duration::ZERO), None);
+
+        assert!(drained.take_eos());
+        assert!(!drained.take_eos());
+        let problems = drained.take_problems();
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].type_(), gst::MessageType::Warning);
+        assert!(drained.take_problems().is_empty());
+
+        // Only the latest are kept
+        for i in 0..MAX_PENDING_PROBLEMS + 5 {
+            drained.keep(&warning(&i.to_string()));
+        }
+        for _ in 0..MAX_RECENT_ENDS + 5 {
+            drained.keep(&gst::message::Eos::new());
+        }
+        let problems = drained.take_problems();
+        assert_eq!(problems.len(), MAX_PENDING_PROBLEMS);
+        match problems[0].view() {
+            gst::MessageView::Warning(w) => assert_eq!(w.error().to_string(), "5"),
+            _ => unreachable!(),
+        }
+        assert_eq!(drained.ends_seen(), MAX_RECENT_ENDS as u64 + 7);
+        assert_eq!(drained.state.lock().ends.len(), MAX_RECENT_ENDS);
+        assert_eq!(drained.wait_end(0, Duration::ZERO), Some(StreamEnd::Eos));
+    }
+
+    #[test]
+    fn test_wait_end_wakes_waiter() {
+        gst::init().unwrap();
+        let drained = Arc::new(DrainedMessages::default());
+        let after = drained.ends_seen();
+        let poster = {
+            let drained = drained.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_millis(50));
+                drained.keep(&warning("not an end"));
+                drained.keep(&error("failed"));
+            })
+        };
+        assert_eq!(
+            drained.wait_end(after, Duration::from_secs(5)),
+            Some(StreamEnd::Error("failed".to_string()))
+        );
+        poster.join().unwrap();
+    }
+}
@@ -0,0 +1,208 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::Mutex;
+use rmcp::model::LoggingLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::notifications::Notifier;
+use crate::pipeline::{attach_cause, PipelineManager};
+
+/// Element classes whose output follows the input format: sources,
+/// demuxers, depayloaders and decoders. Watching only these reports a
+/// format change once instead of at every element downstream.
+const WATCHED_CLASSES: &[&str] = &["Source", "Demuxer", "Depayloader", "Decoder"];
+
+/// Key under which the media type is compared alongside the caps fields
+const MEDIA_TYPE_KEY: &str = "media-type";
+
+type OnChange = Arc<dyn Fn(CapsChange) + Send + Sync>;
+
+/// Caps renegotiated on a running pipeline, such as a camera changing
+/// resolution or a broadcast switching to interlaced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapsChange {
+    pub pipeline_id: String,
+    pub element: String,
+    pub pad: String,
+    pub old_caps: String,
+    pub new_caps: String,
+    /// Changed fields as `field: old -> new`
+    pub changes: Vec<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+fn structure_fields(caps: &gst::CapsRef) -> BTreeMap<String, String> {
+    let Some(structure) = caps.structure(0) else {
+        return BTreeMap::new();
+    };
+    let mut fields: BTreeMap<String, String> = structure
+        .iter()
+        .map(|(name, value)| {
+            let value = value
+                .serialize()
+                .map(|s| s.to_string())
+                .unwrap_or_else(|_| format!("{:?}", value));
+            (name.to_string(), value)
+        })
+        .collect();
+    fields.insert(MEDIA_TYPE_KEY.to_string(), structure.name().to_string());
+    fields
+}
+
+/// Fields that differ between two sets of caps fields, as `field: old -> new`
+pub fn field_changes(
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut names: Vec<&String> = old.keys().chain(new.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter(|name| old.get(*name) != new.get(*name))
+        .map(|name| {
+            format!(
+                "{}: {} -> {}",
+                name,
+                old.get(name).map_or("(unset)", |v| v.as_str()),
+                new.get(name).map_or("(unset)", |v| v.as_str())
+            )
+        })
+        .collect()
+}
+
+fn is_watched(element: &gst::Element) -> bool {
+    element.factory().is_some_and(|f| {
+        f.metadata(gst::ELEMENT_METADATA_KLASS)
+            .is_some_and(|klass| WATCHED_CLASSES.iter().any(|c| klass.contains(c)))
+    })
+}
+
+fn watch_pad(pad: &gst::Pad, pipeline_id: &str, on_change: &OnChange) {
+    if pad.direction() != gst::PadDirection::Src {
+        return;
+    }
+    let last = Mutex::new(pad.current_caps());
+    let pipeline_id = pipeline_id.to_string();
+    let on_change = on_change.clone();
+    pad.add_probe(gst::PadProbeType::EVENT_DOWNSTREAM, move |pad, info| {
+        let Some(gst::EventView::Caps(event)) = info.event().map(|e| e.view()) else {
+            return gst::PadProbeReturn::Ok;
+        };
+        let caps = event.caps_owned();
+        let previous = last.lock().replace(caps.clone());
+        if let Some(previous) = previous.filter(|p| *p != caps) {
+            let changes = field_changes(&structure_fields(&previous), &structure_fields(&caps));
+            on_change(CapsChange {
+                pipeline_id: pipeline_id.clone(),
+                element: pad
+                    .parent_element()
+                    .map(|e| e.name().to_string())
+                    .unwrap_or_default(),
+                pad: pad.name().to_string(),
+                old_caps: previous.to_string(),
+                new_caps: caps.to_string(),
+                changes,
+                timestamp: chrono::Utc::now(),
+            });
+        }
+        gst::PadProbeReturn::Ok
+    });
+}
+
+fn watch_element(element: &gst::Element, pipeline_id: &str, on_change: &OnChange) {
+    if !is_watched(element) {
+        return;
+    }
+    for pad in element.src_pads() {
+        watch_pad(&pad, pipeline_id, on_change);
+    }
+    // Demuxers and decodebin-style elements add their pads later
+    let pipeline_id = pipeline_id.to_string();
+    let on_change = on_change.clone();
+    element.connect_pad_added(move |_, pad| watch_pad(pad, &pipeline_id, &on_change));
+}
+
+/// Report caps renegotiations on the output of the sources, demuxers,
+/// depayloaders and decoders of a pipeline, including elements plugged in
+/// later by bins such as decodebin. The first caps of a pad are its initial
+/// negotiation and are not reported. `on_change` runs on streaming threads.
+pub fn watch_caps(
+    pipeline: &gst::Pipeline,
+    pipeline_id: &str,
+    on_change: impl Fn(CapsChange) + Send + Sync + 'static,
+) {
+    let on_change: OnChange = Arc::new(on_change);
+    for element in pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        watch_element(&element, pipeline_id, &on_change);
+    }
+    let pipeline_id = pipeline_id.to_string();
+    pipeline.connect_deep_element_added(move |_, _, element| {
+        watch_element(element, &pipeline_id, &on_change);
+    });
+}
+
+/// Forward caps changes of every pipeline to the client as `caps_changed`
+/// notifications
+pub fn spawn_caps_alerts(pipeline_manager: &Arc<PipelineManager>, notifier: Notifier) {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<CapsChange>();
+    pipeline_manager.set_caps_alerts(tx);
+    let pipeline_manager = pipeline_manager.clone();
+    tokio::spawn(async move {
+        while let Some(change) = rx.recv().await {
+            let mut data = serde_json::to_value(&change).unwrap_or_default();
+            attach_cause(
+                &mut data,
+                pipeline_manager.cause(&change.pipeline_id).as_ref(),
+            );
+            notifier
+                .notify("caps_changed", LoggingLevel::Warning, data)
+                .await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_field_changes() {
+        let old = fields(&[
+            ("media-type", "video/x-raw"),
+            ("width", "1920"),
+            ("height", "1080"),
+            ("interlace-mode", "progressive"),
+        ]);
+        let new = fields(&[
+            ("media-type", "video/x-raw"),
+            ("width", "1280"),
+            ("height", "720"),
+            ("interlace-mode", "interleaved"),
+            ("field-order", "top-field-first"),
+        ]);
+        assert_eq!(
+            field_changes(&old, &new),
+            vec![
+                "field-order: (unset) -> top-field-first",
+                "height: 1080 -> 720",
+                "interlace-mode: progressive -> interleaved",
+                "width: 1920 -> 1280",
+            ]
+        );
+        assert!(field_changes(&old, &old).is_empty());
+    }
+}
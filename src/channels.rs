@@ -0,0 +1,173 @@
+/// Names of the `GstAudioChannelPosition` values, indexed by their bit in a
+/// channel mask
+const POSITIONS: &[&str] = &[
+    "front-left",
+    "front-right",
+    "front-center",
+    "lfe1",
+    "rear-left",
+    "rear-right",
+    "front-left-of-center",
+    "front-right-of-center",
+    "rear-center",
+    "lfe2",
+    "side-left",
+    "side-right",
+    "top-front-left",
+    "top-front-right",
+    "top-front-center",
+    "top-center",
+    "top-rear-left",
+    "top-rear-right",
+    "top-side-left",
+    "top-side-right",
+    "top-rear-center",
+    "bottom-front-center",
+    "bottom-front-left",
+    "bottom-front-right",
+    "wide-left",
+    "wide-right",
+    "surround-left",
+    "surround-right",
+];
+
+/// Gain of -3 dB
+const M3DB: f64 = std::f64::consts::FRAC_1_SQRT_2;
+
+/// Contribution of each position to the left and right output of a stereo
+/// downmix, before normalization. Centers go to both sides at -3 dB and
+/// surrounds to their side at -3 dB (ITU-R BS.775); LFE is dropped.
+const STEREO_GAINS: &[(f64, f64)] = &[
+    (1.0, 0.0),
+    (0.0, 1.0),
+    (M3DB, M3DB),
+    (0.0, 0.0),
+    (M3DB, 0.0),
+    (0.0, M3DB),
+    (1.0, 0.0),
+    (0.0, 1.0),
+    (0.5, 0.5),
+    (0.0, 0.0),
+    (M3DB, 0.0),
+    (0.0, M3DB),
+    (M3DB, 0.0),
+    (0.0, M3DB),
+    (0.5, 0.5),
+    (0.5, 0.5),
+    (0.5, 0.0),
+    (0.0, 0.5),
+    (0.5, 0.0),
+    (0.0, 0.5),
+    (M3DB / 2.0, M3DB / 2.0),
+    (0.5, 0.5),
+    (M3DB, 0.0),
+    (0.0, M3DB),
+    (M3DB, 0.0),
+    (0.0, M3DB),
+    (M3DB, 0.0),
+    (0.0, M3DB),
+];
+
+/// Common layouts and their channel masks
+const LAYOUTS: &[(&str, u64)] = &[
+    ("mono", 0x4),
+    ("stereo", 0x3),
+    ("2.1", 0xb),
+    ("quad", 0x33),
+    ("5.0", 0x37),
+    ("5.1", 0x3f),
+    ("5.1 (side)", 0xc0f),
+    ("7.1", 0xc3f),
+];
+
+/// Positions of the channels of a mask, in channel order. GStreamer orders
+/// the channels of a positioned layout by position.
+pub fn channel_positions(mask: u64) -> Vec<&'static str> {
+    POSITIONS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| mask & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+/// Name of the layout of `channels` channels with `mask`, such as `5.1`
+pub fn layout_name(channels: u32, mask: u64) -> String {
+    if mask == 0 {
+        return match channels {
+            1 => "mono".to_string(),
+            2 => "stereo".to_string(),
+            _ => format!("{} unpositioned channels", channels),
+        };
+    }
+    LAYOUTS
+        .iter()
+        .find(|(_, layout)| *layout == mask)
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_else(|| channel_positions(mask).join(", "))
+}
+
+/// Stereo downmix matrix for a channel mask, one row per output channel and
+/// one column per input channel. Rows are scaled so a full-scale signal on
+/// every input cannot clip. None for masks without positions.
+pub fn stereo_downmix_matrix(mask: u64) -> Option<[Vec<f64>; 2]> {
+    let gains: Vec<(f64, f64)> = STEREO_GAINS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| mask & (1 << bit) != 0)
+        .map(|(_, gains)| *gains)
+        .collect();
+    if gains.is_empty() {
+        return None;
+    }
+    let left: Vec<f64> = gains.iter().map(|(l, _)| *l).collect();
+    let right: Vec<f64> = gains.iter().map(|(_, r)| *r).collect();
+    let loudest = left.iter().sum::<f64>().max(right.iter().sum::<f64>());
+    let scale = if loudest > 1.0 { 1.0 / loudest } else { 1.0 };
+    Some([
+        left.iter().map(|g| g * scale).collect(),
+        right.iter().map(|g| g * scale).collect(),
+    ])
+}
+
+/// `audioconvert` property value for a mix matrix
+pub fn mix_matrix_value(matrix: &[Vec<f64>]) -> String {
+    let rows: Vec<String> = matrix
+        .iter()
+        .map(|row| {
+            let gains: Vec<String> = row.iter().map(|g| format!("(float){:.4}", g)).collect();
+            format!("<{}>", gains.join(", "))
+        })
+        .collect();
+    format!("\"<{}>\"", rows.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_name() {
+        assert_eq!(layout_name(6, 0x3f), "5.1");
+        assert_eq!(layout_name(2, 0), "stereo");
+        assert_eq!(layout_name(3, 0x7), "front-left, front-right, front-center");
+        assert_eq!(layout_name(4, 0), "4 unpositioned channels");
+    }
+
+    #[test]
+    fn test_stereo_downmix_matrix() {
+        let [left, right] = stereo_downmix_matrix(0x3f).unwrap();
+        // FL FR FC LFE RL RR, scaled by 1 / (1 + 2 * -3 dB)
+        let scale = 1.0 / (1.0 + 2.0 * M3DB);
+        let expected_left = [scale, 0.0, M3DB * scale, 0.0, M3DB * scale, 0.0];
+        for (gain, expected) in left.iter().zip(expected_left) {
+            assert!((gain - expected).abs() < 1e-3);
+        }
+        assert_eq!(right[3], 0.0);
+        assert!(stereo_downmix_matrix(0).is_none());
+        assert_eq!(
+            mix_matrix_value(&[vec![1.0, 0.0], vec![0.0, 0.5]]),
+            "\"<<(float)1.0000, (float)0.0000>, <(float)0.0000, (float)0.5000>>\""
+        );
+    }
+}
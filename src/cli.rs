@@ -1,4 +1,4 @@
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -22,6 +22,11 @@ pub struct Cli {
     #[arg(short, long)]
     pub repl: bool,
 
+    /// Run under a service manager: signal readiness and feed the watchdog
+    /// with sd_notify on Linux, run as a Windows service on Windows
+    #[arg(long, conflicts_with = "repl", env = "GSTREAMER_MCP_DAEMON")]
+    pub daemon: bool,
+
     /// Specific tools to enable (comma-separated)
     #[arg(long, value_delimiter = ',', env = "GSTREAMER_MCP_TOOLS")]
     pub tools: Option<Vec<String>>,
@@ -39,6 +44,11 @@ pub struct Cli {
     #[arg(short, long)]
     pub config: Option<PathBuf>,
 
+    /// Maximum number of pipelines that may exist at the same time
+    /// (default: 10)
+    #[arg(long, value_name = "N")]
+    pub max_pipelines: Option<usize>,
+
     /// Verbose output (-v, -vv, -vvv)
     #[arg(short, long, action = clap::ArgAction::Count)]
     pub verbose: u8,
@@ -46,12 +56,55 @@ pub struct Cli {
     /// Disable colored output
     #[arg(long)]
     pub no_color: bool,
+
+    /// Inspect comma-separated elements, print each as a line of JSON and
+    /// exit (used internally to isolate element instantiation from the server)
+    #[arg(long, hide = true, value_name = "ELEMENTS")]
+    pub inspect_helper: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Call one tool, print its result and exit
+    Call {
+        /// Name of the tool, e.g. gst_list_elements
+        tool: String,
+
+        /// Parameters of the tool as a JSON object
+        #[arg(long, default_value = "{}", value_name = "PARAMS")]
+        json: String,
+
+        /// How to print the result
+        #[arg(long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+}
+
+/// How `call` prints a tool result
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The text content of the result
+    Text,
+    /// The structured content of the result, or the whole result without it
+    Json,
+}
+
+/// A single tool call requested on the command line
+#[derive(Debug, Clone)]
+pub struct ToolCall {
+    pub tool: String,
+    pub params: String,
+    pub output: OutputFormat,
 }
 
-#[derive(ValueEnum, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[derive(ValueEnum, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum OperationalMode {
     /// All tools enabled (default)
+    #[default]
     All,
     /// Live operations mode (pipeline control, monitoring)
     Live,
@@ -61,22 +114,20 @@ pub enum OperationalMode {
     Discovery,
 }
 
-impl Default for OperationalMode {
-    fn default() -> Self {
-        OperationalMode::All
-    }
-}
-
 /// Parsed configuration from CLI arguments
 #[derive(Debug, Clone)]
 pub struct ParsedConfig {
     pub mode: OperationalMode,
     pub repl: bool,
+    pub daemon: bool,
     pub included_tools: Option<Vec<String>>,
     pub excluded_tools: Option<Vec<String>>,
     pub config_path: Option<PathBuf>,
+    pub max_pipelines: Option<usize>,
     pub verbose_level: u8,
     pub no_color: bool,
+    pub inspect_helper: Option<String>,
+    pub call: Option<ToolCall>,
 }
 
 impl Cli {
@@ -87,11 +138,21 @@ impl Cli {
         ParsedConfig {
             mode: cli.mode,
             repl: cli.repl,
+            daemon: cli.daemon,
             included_tools: cli.tools,
             excluded_tools: cli.exclude_tools,
             config_path: cli.config,
+            max_pipelines: cli.max_pipelines,
             verbose_level: cli.verbose,
             no_color: cli.no_color,
+            inspect_helper: cli.inspect_helper,
+            call: cli
+                .command
+                .map(|Command::Call { tool, json, output }| ToolCall {
+                    tool,
+                    params: json,
+                    output,
+                }),
         }
     }
 
@@ -0,0 +1,185 @@
+use gstreamer as gst;
+use gstreamer::glib::translate::IntoGlib;
+use gstreamer_video as gst_video;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::media::VideoStream;
+
+/// Elements that tone-map HDR to SDR, most capable first, with the
+/// settings turning tone mapping on
+const TONEMAPPERS: &[(&str, &str)] = &[("vapostproc", "vapostproc hdr-tone-mapping=true")];
+
+/// Used when no tone-mapping element is installed. Converting the transfer
+/// function and primaries keeps mid-tones right but clips highlights.
+const FALLBACK_TONEMAP_CHAIN: &str =
+    "videoconvert gamma-mode=remap primaries-mode=fast ! video/x-raw,colorimetry=bt709";
+
+/// High dynamic range transfer function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HdrFormat {
+    /// SMPTE ST 2084 perceptual quantizer, used by HDR10 and Dolby Vision
+    Pq,
+    /// ARIB STD-B67 hybrid log-gamma, used in broadcast
+    Hlg,
+}
+
+impl std::fmt::Display for HdrFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HdrFormat::Pq => "PQ",
+            HdrFormat::Hlg => "HLG",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Colorimetry and HDR metadata of a video stream, from its caps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorInfo {
+    /// `colorimetry` field of the caps, e.g. `bt709` or `bt2100-pq`
+    pub colorimetry: String,
+    pub primaries: String,
+    pub transfer: String,
+    pub matrix: String,
+    pub range: String,
+    pub hdr: Option<HdrFormat>,
+    /// Mastering display color volume (SMPTE ST 2086), as in the caps
+    pub mastering_display: Option<String>,
+    /// Maximum content and frame-average light levels, as in the caps
+    pub content_light_level: Option<String>,
+}
+
+impl std::fmt::Display for ColorInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (primaries {}, transfer {}, matrix {}, range {})",
+            self.colorimetry, self.primaries, self.transfer, self.matrix, self.range
+        )?;
+        if let Some(hdr) = self.hdr {
+            write!(f, ", HDR {}", hdr)?;
+        }
+        if let Some(mastering) = &self.mastering_display {
+            write!(f, ", mastering display {}", mastering)?;
+        }
+        if let Some(light_level) = &self.content_light_level {
+            write!(f, ", content light level {}", light_level)?;
+        }
+        Ok(())
+    }
+}
+
+/// HDR format of a `GstVideoTransferFunction` value, or None for SDR. The
+/// HDR transfer functions are only named in the bindings for GStreamer
+/// 1.18 and later, so they are matched by value.
+pub fn hdr_format(transfer: i32) -> Option<HdrFormat> {
+    match transfer {
+        gst_video::ffi::GST_VIDEO_TRANSFER_SMPTE2084 => Some(HdrFormat::Pq),
+        gst_video::ffi::GST_VIDEO_TRANSFER_ARIB_STD_B67 => Some(HdrFormat::Hlg),
+        _ => None,
+    }
+}
+
+fn name(value: impl std::fmt::Debug) -> String {
+    format!("{:?}", value).to_lowercase()
+}
+
+fn transfer_name(transfer: gst_video::VideoTransferFunction) -> String {
+    match hdr_format(transfer.into_glib()) {
+        Some(HdrFormat::Pq) => "smpte2084".to_string(),
+        Some(HdrFormat::Hlg) => "arib-std-b67".to_string(),
+        None => name(transfer),
+    }
+}
+
+/// Color information of video caps, or None when they carry no colorimetry
+pub fn color_info(caps: &gst::CapsRef) -> Option<ColorInfo> {
+    let structure = caps.structure(0)?;
+    let colorimetry = structure.get::<String>("colorimetry").ok()?;
+    let parsed = gst_video::VideoColorimetry::from_str(&colorimetry).ok()?;
+    Some(ColorInfo {
+        primaries: name(parsed.primaries()),
+        transfer: transfer_name(parsed.transfer()),
+        matrix: name(parsed.matrix()),
+        range: name(parsed.range()),
+        hdr: hdr_format(parsed.transfer().into_glib()),
+        mastering_display: structure.get::<String>("mastering-display-info").ok(),
+        content_light_level: structure.get::<String>("content-light-level").ok(),
+        colorimetry,
+    })
+}
+
+/// Elements tone-mapping HDR video to SDR BT.709: the first installed
+/// tone mapper, or a plain conversion when there is none
+pub fn tonemap_chain() -> String {
+    TONEMAPPERS
+        .iter()
+        .find(|(factory, _)| gst::ElementFactory::find(factory).is_some())
+        .map_or(FALLBACK_TONEMAP_CHAIN, |(_, chain)| chain)
+        .to_string()
+}
+
+/// HDR to SDR tone mapping of a transcode
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tonemap {
+    /// Elements inserted after the decoder
+    pub chain: String,
+    /// Tone-map every input, not only those detected as HDR
+    pub forced: bool,
+}
+
+impl Tonemap {
+    /// Apply the caller's choice to the video of the inputs: `Some(false)`
+    /// never tone-maps, `Some(true)` tone-maps everything and `None`
+    /// tone-maps HDR inputs, since the output encoders are 8-bit SDR
+    pub fn resolve<'a>(
+        requested: Option<bool>,
+        videos: impl IntoIterator<Item = Option<&'a VideoStream>>,
+    ) -> Option<Tonemap> {
+        let forced = match requested {
+            Some(false) => return None,
+            Some(true) => true,
+            None => false,
+        };
+        if !forced && !videos.into_iter().any(is_hdr) {
+            return None;
+        }
+        Some(Tonemap {
+            chain: tonemap_chain(),
+            forced,
+        })
+    }
+
+    /// Description fragment tone-mapping `video`, ending in ` ! `, or
+    /// nothing when it stays as is
+    pub fn step(&self, video: Option<&VideoStream>) -> String {
+        if self.forced || is_hdr(video) {
+            format!("{} ! ", self.chain)
+        } else {
+            String::new()
+        }
+    }
+}
+
+fn is_hdr(video: Option<&VideoStream>) -> bool {
+    video
+        .and_then(|v| v.color.as_ref())
+        .is_some_and(|c| c.hdr.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hdr_format() {
+        assert_eq!(hdr_format(14), Some(HdrFormat::Pq));
+        assert_eq!(hdr_format(15), Some(HdrFormat::Hlg));
+        assert_eq!(
+            hdr_format(gst_video::VideoTransferFunction::Bt709.into_glib()),
+            None
+        );
+    }
+}
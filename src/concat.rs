@@ -0,0 +1,399 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::conversion::Conversions;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::{
+    media_type, muxer_accepts, output_format, quote_location, require_element, MediaInfo,
+    VideoStream,
+};
+
+/// How `gst_concat_media` joins its inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConcatMode {
+    /// Copy the encoded streams into the new container
+    Remux,
+    /// Decode, conform to the first input's format and encode again
+    Reencode,
+}
+
+/// A ready-to-run concatenation
+#[derive(Debug, Clone, Serialize)]
+pub struct ConcatPlan {
+    pub mode: ConcatMode,
+    pub description: String,
+    /// Why the inputs cannot simply be remuxed, if they cannot
+    pub issues: Vec<String>,
+    /// Sum of the input durations
+    pub duration_ns: Option<u64>,
+    /// Whether video is tone-mapped from HDR to SDR
+    pub tonemapped: bool,
+    /// Whether multichannel audio is downmixed to stereo
+    pub downmixed: bool,
+}
+
+/// `SDR`, or `HDR` with its transfer function
+fn dynamic_range(video: &VideoStream) -> String {
+    match video.color.as_ref().and_then(|c| c.hdr) {
+        Some(hdr) => format!("HDR ({})", hdr),
+        None => "SDR".to_string(),
+    }
+}
+
+/// Differences between the inputs that prevent joining them without
+/// re-encoding. Fails when the inputs cannot be joined at all.
+pub fn compatibility_issues(inputs: &[MediaInfo]) -> McpResult<Vec<String>> {
+    let Some(first) = inputs.first() else {
+        return Err(GStreamerMcpError::Other("No inputs to join".to_string()));
+    };
+
+    for input in inputs {
+        let name = input.path.display();
+        if input.video.is_empty() && input.audio.is_empty() {
+            return Err(GStreamerMcpError::Other(format!(
+                "{} has no audio or video streams",
+                name
+            )));
+        }
+        if input.video.is_empty() != first.video.is_empty()
+            || input.audio.is_empty() != first.audio.is_empty()
+        {
+            return Err(GStreamerMcpError::Other(format!(
+                "{} and {} do not have the same kinds of streams (audio/video)",
+                first.path.display(),
+                name
+            )));
+        }
+    }
+
+    // Only the first stream of each kind is joined
+    let mut issues = Vec::new();
+    for input in &inputs[1..] {
+        let name = input.path.display();
+        if let (Some(a), Some(b)) = (first.video.first(), input.video.first()) {
+            if a.codec != b.codec {
+                issues.push(format!(
+                    "{}: video codec {} differs from {}",
+                    name, b.codec, a.codec
+                ));
+            }
+            if (a.width, a.height) != (b.width, b.height) {
+                issues.push(format!(
+                    "{}: resolution {}x{} differs from {}x{}",
+                    name, b.width, b.height, a.width, a.height
+                ));
+            }
+            if a.framerate != b.framerate {
+                issues.push(format!(
+                    "{}: frame rate {} differs from {}",
+                    name, b.framerate, a.framerate
+                ));
+            }
+            let (range_a, range_b) = (dynamic_range(a), dynamic_range(b));
+            if range_a != range_b {
+                issues.push(format!(
+                    "{}: {} video differs from {}",
+                    name, range_b, range_a
+                ));
+            }
+        }
+        if let (Some(a), Some(b)) = (first.audio.first(), input.audio.first()) {
+            if a.codec != b.codec {
+                issues.push(format!(
+                    "{}: audio codec {} differs from {}",
+                    name, b.codec, a.codec
+                ));
+            }
+            if (a.sample_rate, a.channels) != (b.sample_rate, b.channels) {
+                issues.push(format!(
+                    "{}: audio {} Hz/{} ch differs from {} Hz/{} ch",
+                    name, b.sample_rate, b.channels, a.sample_rate, a.channels
+                ));
+            }
+        }
+    }
+
+    Ok(issues)
+}
+
+/// Decide how to join `inputs` into `output` and build the pipeline.
+/// Inputs are remuxed when they match and the output container can store
+/// their codecs; otherwise they are re-encoded with `conversions`. Forced
+/// tone mapping and downmixing multichannel audio need re-encoding.
+pub fn plan_concat(
+    inputs: &[MediaInfo],
+    output: &Path,
+    reencode: bool,
+    conversions: &Conversions,
+) -> McpResult<ConcatPlan> {
+    if inputs.len() < 2 {
+        return Err(GStreamerMcpError::Other(
+            "At least two inputs are needed to concatenate".to_string(),
+        ));
+    }
+    let format = output_format(output)?;
+    let mut issues = compatibility_issues(inputs)?;
+
+    let first = &inputs[0];
+    if !first.video.is_empty() && format.video_encoders.is_empty() {
+        return Err(GStreamerMcpError::Other(format!(
+            "{} cannot hold video; choose a video container for {}",
+            format.muxer,
+            output.display()
+        )));
+    }
+
+    let stream_caps = first
+        .video
+        .first()
+        .map(|v| &v.caps)
+        .into_iter()
+        .chain(first.audio.first().map(|a| &a.caps));
+    for caps in stream_caps {
+        if !muxer_accepts(format.muxer, caps) {
+            issues.push(format!(
+                "{} cannot store {}",
+                format.muxer,
+                media_type(caps)
+            ));
+        }
+    }
+
+    if conversions.tonemap.as_ref().is_some_and(|t| t.forced) {
+        issues.push("tone mapping was requested".to_string());
+    }
+    let multichannel = inputs
+        .iter()
+        .filter_map(|i| i.audio.first())
+        .any(|a| a.channels > 2);
+    let downmixed = conversions.downmix && multichannel;
+    if downmixed {
+        issues.push("downmixing to stereo was requested".to_string());
+    }
+
+    let mode = if reencode || !issues.is_empty() {
+        ConcatMode::Reencode
+    } else {
+        ConcatMode::Remux
+    };
+    let video_encoder = match (mode, first.video.is_empty()) {
+        (ConcatMode::Reencode, false) => {
+            Some(require_element(format.video_encoders, "video encoder")?)
+        }
+        _ => None,
+    };
+    let audio_encoder = match (mode, first.audio.is_empty()) {
+        (ConcatMode::Reencode, false) => {
+            Some(require_element(format.audio_encoders, "audio encoder")?)
+        }
+        _ => None,
+    };
+
+    let description = concat_description(
+        inputs,
+        output,
+        format.muxer,
+        mode,
+        video_encoder,
+        audio_encoder,
+        conversions,
+    );
+    let duration_ns = inputs.iter().map(|i| i.duration_ns).sum::<Option<u64>>();
+
+    Ok(ConcatPlan {
+        mode,
+        description,
+        issues,
+        duration_ns,
+        tonemapped: mode == ConcatMode::Reencode && conversions.tonemap.is_some(),
+        downmixed,
+    })
+}
+
+/// gst-launch description joining `inputs` with `concat` elements, one per
+/// stream kind. Re-encoding conforms every input to the first one's
+/// resolution, frame rate and audio format, after `conversions`.
+pub fn concat_description(
+    inputs: &[MediaInfo],
+    output: &Path,
+    muxer: &str,
+    mode: ConcatMode,
+    video_encoder: Option<&str>,
+    audio_encoder: Option<&str>,
+    conversions: &Conversions,
+) -> String {
+    let video = inputs.first().and_then(|i| i.video.first());
+    let audio = inputs.first().and_then(|i| i.audio.first());
+
+    let mut parts = vec![format!(
+        "{} name=mux ! filesink location={}",
+        muxer,
+        quote_location(output)
+    )];
+
+    match mode {
+        ConcatMode::Remux => {
+            if video.is_some() {
+                parts.push("concat name=vcat ! queue ! mux.".to_string());
+            }
+            if audio.is_some() {
+                parts.push("concat name=acat ! queue ! mux.".to_string());
+            }
+            for (i, input) in inputs.iter().enumerate() {
+                parts.push(format!(
+                    "filesrc location={} ! parsebin name=in{}",
+                    quote_location(&input.path),
+                    i
+                ));
+                if let Some(video) = video {
+                    parts.push(format!(
+                        "in{}. ! {} ! queue ! vcat.",
+                        i,
+                        media_type(&video.codec)
+                    ));
+                }
+                if let Some(audio) = audio {
+                    parts.push(format!(
+                        "in{}. ! {} ! queue ! acat.",
+                        i,
+                        media_type(&audio.codec)
+                    ));
+                }
+            }
+        }
+        ConcatMode::Reencode => {
+            if let (Some(_), Some(encoder)) = (video, video_encoder) {
+                parts.push(format!(
+                    "concat name=vcat ! queue ! videoconvert ! {} ! queue ! mux.",
+                    encoder
+                ));
+            }
+            if let (Some(_), Some(encoder)) = (audio, audio_encoder) {
+                parts.push(format!(
+                    "concat name=acat ! queue ! audioconvert ! audioresample ! {} ! queue ! mux.",
+                    encoder
+                ));
+            }
+            for (i, input) in inputs.iter().enumerate() {
+                parts.push(format!(
+                    "filesrc location={} ! decodebin name=in{}",
+                    quote_location(&input.path),
+                    i
+                ));
+                if let Some(video) = video {
+                    parts.push(format!(
+                        "in{}. ! video/x-raw ! queue ! {}videoconvert ! videoscale add-borders=true ! videorate ! video/x-raw,width={},height={},framerate={},pixel-aspect-ratio=1/1 ! vcat.",
+                        i,
+                        conversions.video_steps(input.video.first()),
+                        video.width,
+                        video.height,
+                        video.framerate
+                    ));
+                }
+                if let Some(audio) = audio {
+                    parts.push(format!(
+                        "in{}. ! audio/x-raw ! queue ! {}audioconvert ! audioresample ! audio/x-raw,rate={},channels={} ! acat.",
+                        i,
+                        conversions.audio_steps(input.audio.first()),
+                        audio.sample_rate,
+                        conversions.output_channels(audio)
+                    ));
+                }
+            }
+        }
+    }
+
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{ColorInfo, HdrFormat};
+    use crate::media::AudioStream;
+    use std::path::PathBuf;
+
+    fn input(path: &str, width: u32, audio_codec: &str) -> MediaInfo {
+        MediaInfo {
+            path: PathBuf::from(path),
+            container: Some("video/quicktime".to_string()),
+            duration_ns: Some(10_000_000_000),
+            seekable: true,
+            video: vec![VideoStream {
+                codec: "video/x-h264".to_string(),
+                caps: "video/x-h264, stream-format=avc".to_string(),
+                width,
+                height: 720,
+                framerate: "30/1".to_string(),
+                interlaced: false,
+                color: None,
+            }],
+            audio: vec![AudioStream {
+                codec: audio_codec.to_string(),
+                caps: format!("{}, rate=48000", audio_codec),
+                sample_rate: 48000,
+                channels: 2,
+                channel_mask: 0x3,
+                layout: "stereo".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_compatibility_issues() {
+        let aac = "audio/mpeg, mpegversion=4";
+        let matching = [input("a.mp4", 1280, aac), input("b.mp4", 1280, aac)];
+        assert!(compatibility_issues(&matching).unwrap().is_empty());
+
+        let different = [
+            input("a.mp4", 1280, aac),
+            input("b.mp4", 1920, "audio/x-opus"),
+        ];
+        assert_eq!(compatibility_issues(&different).unwrap().len(), 2);
+
+        let mut hdr = input("b.mp4", 1280, aac);
+        hdr.video[0].color = Some(ColorInfo {
+            colorimetry: "bt2100-pq".to_string(),
+            primaries: "bt2020".to_string(),
+            transfer: "smpte2084".to_string(),
+            matrix: "bt2020".to_string(),
+            range: "range16_235".to_string(),
+            hdr: Some(HdrFormat::Pq),
+            mastering_display: None,
+            content_light_level: None,
+        });
+        assert_eq!(
+            compatibility_issues(&[input("a.mp4", 1280, aac), hdr]).unwrap(),
+            vec!["b.mp4: HDR (PQ) video differs from SDR"]
+        );
+
+        let mut audio_only = input("c.m4a", 0, aac);
+        audio_only.video.clear();
+        assert!(compatibility_issues(&[input("a.mp4", 1280, aac), audio_only]).is_err());
+    }
+
+    #[test]
+    fn test_remux_description() {
+        let aac = "audio/mpeg, mpegversion=4";
+        let inputs = [input("a.mp4", 1280, aac), input("b.mp4", 1280, aac)];
+        let description = concat_description(
+            &inputs,
+            Path::new("out.mp4"),
+            "mp4mux",
+            ConcatMode::Remux,
+            None,
+            None,
+            &Conversions::default(),
+        );
+        assert_eq!(
+            description,
+            "mp4mux name=mux ! filesink location=\"out.mp4\" \
+             concat name=vcat ! queue ! mux. concat name=acat ! queue ! mux. \
+             filesrc location=\"a.mp4\" ! parsebin name=in0 \
+             in0. ! video/x-h264 ! queue ! vcat. in0. ! audio/mpeg ! queue ! acat. \
+             filesrc location=\"b.mp4\" ! parsebin name=in1 \
+             in1. ! video/x-h264 ! queue ! vcat. in1. ! audio/mpeg ! queue ! acat."
+        );
+    }
+}
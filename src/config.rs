@@ -1,6 +1,15 @@
 use crate::cli::{OperationalMode, ParsedConfig};
+use crate::locale::DESCRIPTION_LANGUAGE_ENV;
+use crate::memory::{MemoryLimits, QueueLeak};
+use crate::message_filter::MessageRule;
+use crate::ownership::OrphanPolicy;
+use crate::policy::Role;
+use crate::time_display::TimeZoneDisplay;
+use crate::tool_registry::ToolCategory;
+use crate::upload::UploadTarget;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Configuration {
@@ -13,6 +22,119 @@ pub struct Configuration {
     #[serde(default = "default_max_results")]
     pub max_search_results: usize,
 
+    /// Populate the discovery cache in the background at startup
+    #[serde(default)]
+    pub warm_cache_on_start: bool,
+
+    /// Rescan the plugin registry every N seconds and invalidate the cache
+    /// when plugins change (0 disables the watcher)
+    #[serde(default)]
+    pub registry_watch_interval_seconds: u64,
+
+    /// Create elements for gst_inspect_element in a helper process, so a
+    /// crashing plugin cannot take down the server
+    #[serde(default)]
+    pub isolate_inspection: bool,
+
+    /// Tool calls taking longer than this are logged at warn level (0 disables)
+    #[serde(default = "default_slow_call_threshold")]
+    pub slow_call_threshold_ms: u64,
+
+    /// Capture diagnostics of pipelines whose state change is still pending
+    /// after this many seconds (0 disables the stall detector)
+    #[serde(default)]
+    pub stall_timeout_seconds: u64,
+
+    /// Most buffers an appsink keeps for a slow consumer before dropping the
+    /// oldest (0 leaves appsinks unlimited). Pipelines may lower or raise it.
+    #[serde(default)]
+    pub max_appsink_buffers: u32,
+
+    /// What queues the server inserts do when full
+    #[serde(default)]
+    pub queue_leak: QueueLeak,
+
+    /// Ask the operator to approve destructive operations via MCP elicitation
+    #[serde(default)]
+    pub require_confirmation: bool,
+
+    /// Role of callers without a transport identity (e.g. over stdio)
+    #[serde(default)]
+    pub default_role: Role,
+
+    /// Roles bound to transport identities, keyed by auth token
+    #[serde(default)]
+    pub role_tokens: HashMap<String, Role>,
+
+    /// Tool categories allowed per role, replacing the built-in defaults
+    #[serde(default)]
+    pub role_categories: HashMap<Role, Vec<ToolCategory>>,
+
+    /// Directory shared by instances on the same host to publish their
+    /// pipelines to each other; unset keeps pipeline state local. Only
+    /// same-host coordination is supported: the directory must be on a
+    /// local filesystem, not a network share
+    #[serde(default)]
+    pub shared_state_dir: Option<PathBuf>,
+
+    /// Maximum number of pipelines that may exist at the same time,
+    /// including those running jobs
+    #[serde(default = "default_max_pipelines")]
+    pub max_pipelines: usize,
+
+    /// What happens to the pipelines when the client disconnects
+    #[serde(default)]
+    pub orphan_policy: OrphanPolicy,
+
+    /// Remove pipelines that have been in NULL, or failed, for this many
+    /// seconds (0, the default, disables the reaper)
+    #[serde(default)]
+    pub dead_pipeline_ttl_seconds: u64,
+
+    /// Directory each pipeline's bus messages are appended to as JSON
+    /// lines, kept after the pipeline is gone; unset keeps them in memory
+    #[serde(default)]
+    pub pipeline_log_dir: Option<PathBuf>,
+
+    /// Rules dropping known-noisy bus messages, or recording them at
+    /// another severity, before they count towards pipeline health
+    #[serde(default)]
+    pub message_rules: Vec<MessageRule>,
+
+    /// Time zone of the timestamps in text outputs
+    #[serde(default)]
+    pub time_zone: TimeZoneDisplay,
+
+    /// Follow timestamps in text outputs with how long ago they were
+    #[serde(default = "default_relative_times")]
+    pub relative_times: bool,
+
+    /// Language of element descriptions and property blurbs where
+    /// translations exist; unset follows the locale, `en` forces English
+    #[serde(default)]
+    pub description_language: Option<String>,
+
+    /// Maximum number of queued jobs running at the same time
+    #[serde(default = "default_max_concurrent_jobs")]
+    pub max_concurrent_jobs: usize,
+
+    /// File the job queue is persisted to; unset keeps it in memory
+    #[serde(default)]
+    pub jobs_state_path: Option<PathBuf>,
+
+    /// File the artifact registry is persisted to; unset keeps it in memory
+    #[serde(default)]
+    pub artifacts_state_path: Option<PathBuf>,
+
+    /// Largest artifact read served through one MCP resource request;
+    /// bigger files must be read in ranges
+    #[serde(default = "default_max_resource_bytes")]
+    pub max_resource_bytes: u64,
+
+    /// Named storage targets jobs may upload their artifacts to
+    #[serde(default)]
+    pub upload_targets: HashMap<String, UploadTarget>,
+
     #[serde(default)]
     pub operational_mode: OperationalMode,
 
@@ -29,6 +151,31 @@ impl Default for Configuration {
             cache_enabled: default_cache_enabled(),
             cache_ttl_seconds: default_cache_ttl(),
             max_search_results: default_max_results(),
+            warm_cache_on_start: false,
+            registry_watch_interval_seconds: 0,
+            isolate_inspection: false,
+            slow_call_threshold_ms: default_slow_call_threshold(),
+            stall_timeout_seconds: 0,
+            max_appsink_buffers: 0,
+            queue_leak: QueueLeak::default(),
+            require_confirmation: false,
+            default_role: Role::default(),
+            role_tokens: HashMap::new(),
+            role_categories: HashMap::new(),
+            shared_state_dir: None,
+            max_pipelines: default_max_pipelines(),
+            orphan_policy: OrphanPolicy::default(),
+            dead_pipeline_ttl_seconds: 0,
+            pipeline_log_dir: None,
+            message_rules: Vec::new(),
+            time_zone: TimeZoneDisplay::default(),
+            relative_times: default_relative_times(),
+            description_language: None,
+            max_concurrent_jobs: default_max_concurrent_jobs(),
+            jobs_state_path: None,
+            artifacts_state_path: None,
+            max_resource_bytes: default_max_resource_bytes(),
+            upload_targets: HashMap::new(),
             operational_mode: OperationalMode::default(),
             included_tools: None,
             excluded_tools: None,
@@ -40,6 +187,9 @@ impl Configuration {
     pub fn load_from_file(path: impl AsRef<Path>) -> anyhow::Result<Self> {
         let content = std::fs::read_to_string(path)?;
         let config: Configuration = toml::from_str(&content)?;
+        for rule in &config.message_rules {
+            rule.check().map_err(anyhow::Error::msg)?;
+        }
         Ok(config)
     }
 
@@ -61,6 +211,130 @@ impl Configuration {
                 self.max_search_results = max;
             }
         }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_WARM_CACHE") {
+            if let Ok(warm) = val.parse::<bool>() {
+                self.warm_cache_on_start = warm;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_REGISTRY_WATCH_INTERVAL") {
+            if let Ok(interval) = val.parse::<u64>() {
+                self.registry_watch_interval_seconds = interval;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_ISOLATE_INSPECTION") {
+            if let Ok(isolate) = val.parse::<bool>() {
+                self.isolate_inspection = isolate;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_SLOW_CALL_MS") {
+            if let Ok(threshold) = val.parse::<u64>() {
+                self.slow_call_threshold_ms = threshold;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_STALL_TIMEOUT") {
+            if let Ok(timeout) = val.parse::<u64>() {
+                self.stall_timeout_seconds = timeout;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_MAX_APPSINK_BUFFERS") {
+            if let Ok(max) = val.parse::<u32>() {
+                self.max_appsink_buffers = max;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_QUEUE_LEAK") {
+            if let Ok(leak) = val.parse::<QueueLeak>() {
+                self.queue_leak = leak;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_REQUIRE_CONFIRMATION") {
+            if let Ok(required) = val.parse::<bool>() {
+                self.require_confirmation = required;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_DEFAULT_ROLE") {
+            if let Ok(role) = val.parse::<Role>() {
+                self.default_role = role;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_SHARED_STATE_DIR") {
+            self.shared_state_dir = Some(PathBuf::from(val));
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_MAX_PIPELINES") {
+            if let Ok(max) = val.parse::<usize>() {
+                self.max_pipelines = max;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_ORPHAN_POLICY") {
+            if let Ok(policy) = val.parse::<OrphanPolicy>() {
+                self.orphan_policy = policy;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_DEAD_PIPELINE_TTL") {
+            if let Ok(ttl) = val.parse::<u64>() {
+                self.dead_pipeline_ttl_seconds = ttl;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_PIPELINE_LOG_DIR") {
+            self.pipeline_log_dir = Some(PathBuf::from(val));
+        }
+
+        if let Ok(val) = std::env::var(DESCRIPTION_LANGUAGE_ENV) {
+            self.description_language = Some(val);
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_TIME_ZONE") {
+            if let Ok(zone) = val.parse::<TimeZoneDisplay>() {
+                self.time_zone = zone;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_RELATIVE_TIMES") {
+            if let Ok(relative) = val.parse::<bool>() {
+                self.relative_times = relative;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_MAX_CONCURRENT_JOBS") {
+            if let Ok(max) = val.parse::<usize>() {
+                self.max_concurrent_jobs = max;
+            }
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_JOBS_STATE") {
+            self.jobs_state_path = Some(PathBuf::from(val));
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_ARTIFACTS_STATE") {
+            self.artifacts_state_path = Some(PathBuf::from(val));
+        }
+
+        if let Ok(val) = std::env::var("GSTREAMER_MCP_MAX_RESOURCE_BYTES") {
+            if let Ok(max) = val.parse::<u64>() {
+                self.max_resource_bytes = max;
+            }
+        }
+    }
+
+    /// Memory limits applying to pipelines that set none of their own
+    pub fn memory_limits(&self) -> MemoryLimits {
+        MemoryLimits {
+            max_appsink_buffers: Some(self.max_appsink_buffers),
+            queue_leak: Some(self.queue_leak),
+        }
     }
 
     /// Merge CLI arguments into configuration
@@ -77,6 +351,10 @@ impl Configuration {
         if cli_config.excluded_tools.is_some() {
             self.excluded_tools = cli_config.excluded_tools.clone();
         }
+
+        if let Some(max) = cli_config.max_pipelines {
+            self.max_pipelines = max;
+        }
     }
 }
 
@@ -91,3 +369,23 @@ fn default_cache_ttl() -> u64 {
 fn default_max_results() -> usize {
     100
 }
+
+fn default_slow_call_threshold() -> u64 {
+    5000
+}
+
+fn default_max_pipelines() -> usize {
+    10
+}
+
+fn default_relative_times() -> bool {
+    true
+}
+
+fn default_max_concurrent_jobs() -> usize {
+    2
+}
+
+fn default_max_resource_bytes() -> u64 {
+    10 * 1024 * 1024 // 10 MiB
+}
@@ -0,0 +1,62 @@
+use rmcp::model::{CreateElicitationRequestParam, ElicitationAction};
+use rmcp::{Peer, RoleServer};
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// Sink factories that create or overwrite files on disk
+pub const FILE_WRITING_FACTORIES: &[&str] = &[
+    "filesink",
+    "multifilesink",
+    "splitmuxsink",
+    "hlssink",
+    "hlssink2",
+    "hlssink3",
+    "dashsink",
+];
+
+/// Ask the operator behind the MCP client to approve an operation.
+///
+/// Returns `Ok(true)` only when the request was accepted with `confirm`
+/// set. Clients that do not support elicitation cannot confirm, so the
+/// operation is refused with an error rather than silently allowed.
+pub async fn request_confirmation(peer: &Peer<RoleServer>, action: &str) -> McpResult<bool> {
+    let supports_elicitation = peer
+        .peer_info()
+        .is_some_and(|info| info.capabilities.elicitation.is_some());
+    if !supports_elicitation {
+        return Err(GStreamerMcpError::Other(format!(
+            "Confirmation is required to {} but the client does not support elicitation",
+            action
+        )));
+    }
+
+    let requested_schema = serde_json::json!({
+        "type": "object",
+        "properties": {
+            "confirm": {
+                "type": "boolean",
+                "description": "Allow the operation to proceed"
+            }
+        },
+        "required": ["confirm"]
+    })
+    .as_object()
+    .cloned()
+    .unwrap_or_default();
+
+    let result = peer
+        .create_elicitation(CreateElicitationRequestParam {
+            message: format!("The agent wants to {}. Allow?", action),
+            requested_schema,
+        })
+        .await
+        .map_err(|e| GStreamerMcpError::Other(format!("Confirmation request failed: {}", e)))?;
+
+    let confirmed = result
+        .content
+        .as_ref()
+        .and_then(|content| content.get("confirm"))
+        .and_then(|confirm| confirm.as_bool())
+        .unwrap_or(false);
+    Ok(result.action == ElicitationAction::Accept && confirmed)
+}
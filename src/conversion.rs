@@ -0,0 +1,52 @@
+use crate::channels::{mix_matrix_value, stereo_downmix_matrix};
+use crate::color::Tonemap;
+use crate::interlace::deinterlace_step;
+use crate::media::{AudioStream, VideoStream};
+
+/// Format conversions applied to the decoded streams of a transcode
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Conversions {
+    /// HDR to SDR tone mapping, when enabled
+    pub tonemap: Option<Tonemap>,
+    /// Downmix multichannel audio to stereo
+    pub downmix: bool,
+}
+
+impl Conversions {
+    /// Steps for decoded video, ending in ` ! `: deinterlacing of interlaced
+    /// video and tone mapping. `None` stands for a stream that was not
+    /// probed, which is deinterlaced in case it is interlaced.
+    pub fn video_steps(&self, video: Option<&VideoStream>) -> String {
+        let mut steps = deinterlace_step(video.is_none_or(|v| v.interlaced)).to_string();
+        if let Some(tonemap) = &self.tonemap {
+            steps.push_str(&tonemap.step(video));
+        }
+        steps
+    }
+
+    /// Steps for decoded audio, ending in ` ! `: the stereo downmix of
+    /// multichannel audio. Known layouts are mixed with ITU-R BS.775
+    /// coefficients; unpositioned or unprobed channels are left to
+    /// `audioconvert`'s default mix.
+    pub fn audio_steps(&self, audio: Option<&AudioStream>) -> String {
+        if !self.downmix || audio.is_some_and(|a| a.channels <= 2) {
+            return String::new();
+        }
+        match audio.and_then(|a| stereo_downmix_matrix(a.channel_mask)) {
+            Some(matrix) => format!(
+                "audioconvert mix-matrix={} ! audio/x-raw,channels=2,channel-mask=(bitmask)0x3 ! ",
+                mix_matrix_value(&matrix)
+            ),
+            None => "audioconvert ! audio/x-raw,channels=2 ! ".to_string(),
+        }
+    }
+
+    /// Channels of the encoded audio
+    pub fn output_channels(&self, audio: &AudioStream) -> u32 {
+        if self.downmix {
+            audio.channels.min(2)
+        } else {
+            audio.channels
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Service manager integration for `--daemon` mode.
+//!
+//! On Linux this speaks the systemd notification protocol (`sd_notify`)
+//! directly over `$NOTIFY_SOCKET`, so a `Type=notify` unit sees when the
+//! server is ready and, with `WatchdogSec=` set, restarts it if it hangs.
+//! On Windows the server runs under the service control manager: it
+//! reports start, running and stop states and stops when the service is
+//! stopped. `--daemon` is refused on other platforms.
+
+use once_cell::sync::Lazy;
+use std::time::Duration;
+
+/// Whether `--daemon` mode is available on this platform
+pub const SUPPORTED: bool = cfg!(any(target_os = "linux", windows));
+
+/// Signalled when the service manager asks the server to stop
+static STOP: Lazy<tokio::sync::Notify> = Lazy::new(tokio::sync::Notify::new);
+
+/// Run `serve` as a daemon. On Windows this hands the process to the
+/// service control manager, which calls `serve` on its own thread; on Linux
+/// `serve` runs directly.
+pub fn run(serve: impl FnOnce() -> anyhow::Result<()> + Send + 'static) -> anyhow::Result<()> {
+    #[cfg(windows)]
+    return service::run(Box::new(serve));
+    #[cfg(not(windows))]
+    serve()
+}
+
+/// Resolves once the service manager asked the server to stop. systemd
+/// stops a unit with a signal instead, so on Linux it never resolves.
+pub async fn stop_requested() {
+    STOP.notified().await
+}
+
+/// Send a raw `sd_notify` state string (e.g. `READY=1`). Returns whether a
+/// service manager received it.
+#[cfg(target_os = "linux")]
+pub fn notify(state: &str) -> bool {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return false;
+    };
+    let path = path.to_string_lossy().into_owned();
+
+    let addr = match path.strip_prefix('@') {
+        Some(name) => SocketAddr::from_abstract_name(name.as_bytes()),
+        None => SocketAddr::from_pathname(&path),
+    };
+    let sent = UnixDatagram::unbound()
+        .and_then(|socket| addr.and_then(|addr| socket.send_to_addr(state.as_bytes(), &addr)));
+
+    match sent {
+        Ok(_) => true,
+        Err(e) => {
+            tracing::warn!("Failed to notify service manager: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn notify(_state: &str) -> bool {
+    false
+}
+
+pub fn notify_ready() -> bool {
+    #[cfg(windows)]
+    service::set_state(windows_service::service::ServiceState::Running, 0);
+    notify("READY=1")
+}
+
+pub fn notify_stopping() -> bool {
+    #[cfg(windows)]
+    service::set_state(windows_service::service::ServiceState::StopPending, 0);
+    notify("STOPPING=1")
+}
+
+pub fn notify_status(status: &str) -> bool {
+    notify(&format!("STATUS={}", status))
+}
+
+/// Watchdog interval requested by the service manager, if any
+pub fn watchdog_interval() -> Option<Duration> {
+    parse_watchdog(
+        std::env::var("WATCHDOG_USEC").ok().as_deref(),
+        std::env::var("WATCHDOG_PID").ok().as_deref(),
+        std::process::id(),
+    )
+}
+
+fn parse_watchdog(usec: Option<&str>, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    if let Some(pid) = pid {
+        if pid.parse::<u32>().ok()? != own_pid {
+            return None;
+        }
+    }
+    let usec = usec?.parse::<u64>().ok().filter(|usec| *usec > 0)?;
+    Some(Duration::from_micros(usec))
+}
+
+/// Ping the service manager watchdog at half the requested interval. The
+/// pings run on the async runtime, so a stalled runtime stops them and the
+/// service manager restarts the server.
+pub fn spawn_watchdog() -> Option<tokio::task::JoinHandle<()>> {
+    let interval = watchdog_interval()?;
+    tracing::info!("Service manager watchdog enabled ({:?})", interval);
+
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval / 2);
+        loop {
+            ticker.tick().await;
+            notify("WATCHDOG=1");
+        }
+    }))
+}
+
+/// Windows service control manager integration
+#[cfg(windows)]
+mod service {
+    use once_cell::sync::OnceCell;
+    use parking_lot::Mutex;
+    use std::ffi::OsString;
+    use std::time::Duration;
+    use windows_service::service::{
+        ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
+        ServiceType,
+    };
+    use windows_service::service_control_handler::{
+        self, ServiceControlHandlerResult, ServiceStatusHandle,
+    };
+    use windows_service::{define_windows_service, service_dispatcher};
+
+    /// Name the service is registered under, e.g. with
+    /// `sc create gstreamer-mcp binPath= "... --daemon"`
+    pub const SERVICE_NAME: &str = "gstreamer-mcp";
+
+    type Serve = Box<dyn FnOnce() -> anyhow::Result<()> + Send>;
+
+    static SERVE: Mutex<Option<Serve>> = Mutex::new(None);
+    static STATUS: OnceCell<ServiceStatusHandle> = OnceCell::new();
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    /// Start the service dispatcher, which blocks until the service stopped
+    pub fn run(serve: Serve) -> anyhow::Result<()> {
+        *SERVE.lock() = Some(serve);
+        service_dispatcher::start(SERVICE_NAME, ffi_service_main)
+            .map_err(|e| anyhow::anyhow!("Failed to start as a Windows service: {}", e))
+    }
+
+    fn service_main(_arguments: Vec<OsString>) {
+        let handle = service_control_handler::register(SERVICE_NAME, |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                super::STOP.notify_one();
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        });
+        match handle {
+            Ok(handle) => {
+                let _ = STATUS.set(handle);
+            }
+            Err(e) => {
+                tracing::error!("Failed to register the service control handler: {}", e);
+                return;
+            }
+        }
+        set_state(ServiceState::StartPending, 0);
+
+        let result = SERVE.lock().take().map_or(Ok(()), |serve| serve());
+        if let Err(e) = &result {
+            tracing::error!("Service failed: {:#}", e);
+        }
+        set_state(ServiceState::Stopped, u32::from(result.is_err()));
+    }
+
+    /// Report the state of the service. Returns whether the service control
+    /// manager received it.
+    pub fn set_state(state: ServiceState, exit_code: u32) -> bool {
+        let Some(handle) = STATUS.get() else {
+            return false;
+        };
+        let status = ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: state,
+            controls_accepted: if state == ServiceState::Running {
+                ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN
+            } else {
+                ServiceControlAccept::empty()
+            },
+            exit_code: ServiceExitCode::Win32(exit_code),
+            checkpoint: 0,
+            wait_hint: match state {
+                ServiceState::StartPending | ServiceState::StopPending => Duration::from_secs(30),
+                _ => Duration::default(),
+            },
+            process_id: None,
+        };
+        match handle.set_service_status(status) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Failed to report the service state: {}", e);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_watchdog() {
+        assert_eq!(
+            parse_watchdog(Some("30000000"), None, 42),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_watchdog(Some("30000000"), Some("42"), 42),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(parse_watchdog(Some("30000000"), Some("7"), 42), None);
+        assert_eq!(parse_watchdog(Some("0"), None, 42), None);
+        assert_eq!(parse_watchdog(None, None, 42), None);
+    }
+}
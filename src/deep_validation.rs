@@ -0,0 +1,230 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::suggestions::parse_launch;
+
+/// How long a deep validation waits for the pipeline to reach PAUSED
+pub const DEEP_VALIDATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Buffers produced by the test sources feeding unlinked inputs
+const TEST_SOURCE_BUFFERS: i32 = 30;
+
+/// An element created at runtime inside a bin such as decodebin or encodebin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutopluggedElement {
+    pub name: String,
+    pub factory: String,
+    /// Bin the element was plugged into
+    pub parent: String,
+}
+
+/// Outcome of bringing a pipeline to PAUSED
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeepValidation {
+    /// Whether the pipeline reached PAUSED
+    pub prerolled: bool,
+    /// Why it did not, from the first error on the bus when there is one
+    pub error: Option<String>,
+    pub autoplugged: Vec<AutopluggedElement>,
+    /// Sinks swapped for fakesinks and test sources added for the run
+    pub substitutions: Vec<String>,
+}
+
+fn is_sink(element: &gst::Element) -> bool {
+    element.src_pads().is_empty()
+        && element.factory().is_some_and(|f| {
+            f.metadata(gst::ELEMENT_METADATA_KLASS)
+                .is_some_and(|k| k.contains("Sink"))
+        })
+}
+
+/// Replace a sink with a fakesink so validation neither writes files nor
+/// opens windows or devices
+fn replace_sink(sink: &gst::Element) -> Option<String> {
+    let pads = sink.sink_pads();
+    let [pad] = pads.as_slice() else {
+        return None;
+    };
+    let peer = pad.peer()?;
+    let parent = sink.parent()?.downcast::<gst::Bin>().ok()?;
+    let fakesink = gst::ElementFactory::make("fakesink")
+        .property("sync", false)
+        .build()
+        .ok()?;
+    peer.unlink(pad).ok()?;
+    parent.remove(sink).ok()?;
+    parent.add(&fakesink).ok()?;
+    peer.link(&fakesink.static_pad("sink")?).ok()?;
+    Some(format!("{} replaced by fakesink", sink.name()))
+}
+
+/// Test source for raw audio or video input, or None for other media
+fn test_source(caps: &gst::Caps) -> Option<gst::Element> {
+    let factory = if caps.can_intersect(&gst::Caps::builder("video/x-raw").build()) {
+        "videotestsrc"
+    } else if caps.can_intersect(&gst::Caps::builder("audio/x-raw").build()) {
+        "audiotestsrc"
+    } else {
+        return None;
+    };
+    gst::ElementFactory::make(factory)
+        .property("num-buffers", TEST_SOURCE_BUFFERS)
+        .build()
+        .ok()
+}
+
+/// Feed elements that nothing links into, such as a description starting
+/// with encodebin, from test sources. Request pads are asked for once per
+/// raw audio or video template.
+fn feed_unlinked_inputs(pipeline: &gst::Pipeline, elements: &[gst::Element]) -> Vec<String> {
+    let mut substitutions = Vec::new();
+    for element in elements {
+        let templates = element.pad_template_list();
+        let has_inputs = templates
+            .iter()
+            .any(|t| t.direction() == gst::PadDirection::Sink);
+        if !has_inputs || element.sink_pads().iter().any(|p| p.is_linked()) {
+            continue;
+        }
+
+        for template in templates
+            .iter()
+            .filter(|t| t.direction() == gst::PadDirection::Sink)
+        {
+            let pad = match template.presence() {
+                gst::PadPresence::Always => element.static_pad(template.name_template()),
+                gst::PadPresence::Request => element.request_pad(template, None, None),
+                gst::PadPresence::Sometimes => None,
+            };
+            let Some(pad) = pad else {
+                continue;
+            };
+            let Some(source) = test_source(template.caps()) else {
+                if template.presence() == gst::PadPresence::Request {
+                    element.release_request_pad(&pad);
+                }
+                continue;
+            };
+            if pipeline.add(&source).is_err() {
+                continue;
+            }
+            match source.static_pad("src").map(|src| src.link(&pad)) {
+                Some(Ok(_)) => substitutions.push(format!(
+                    "{} feeds {}:{}",
+                    source
+                        .factory()
+                        .map(|f| f.name().to_string())
+                        .unwrap_or_default(),
+                    element.name(),
+                    pad.name()
+                )),
+                _ => {
+                    let _ = pipeline.remove(&source);
+                    if template.presence() == gst::PadPresence::Request {
+                        element.release_request_pad(&pad);
+                    }
+                }
+            }
+        }
+    }
+    substitutions
+}
+
+fn first_error(pipeline: &gst::Pipeline) -> Option<String> {
+    let bus = pipeline.bus()?;
+    let message = bus.pop_filtered(&[gst::MessageType::Error])?;
+    match message.view() {
+        gst::MessageView::Error(err) => Some(format!(
+            "{}: {}",
+            err.src().map(|s| s.name().to_string()).unwrap_or_default(),
+            err.error()
+        )),
+        _ => None,
+    }
+}
+
+/// Bring a pipeline to PAUSED to see what decodebin, encodebin, transcodebin
+/// and similar bins plug in at runtime. Sinks are replaced by fakesinks and
+/// elements without input are fed by test sources; the pipeline is torn down
+/// afterwards.
+pub fn deep_validate_pipeline(description: &str, timeout: Duration) -> McpResult<DeepValidation> {
+    let element = parse_launch(description, "Invalid pipeline description")?;
+    let pipeline = match element.downcast::<gst::Pipeline>() {
+        Ok(pipeline) => pipeline,
+        Err(element) => {
+            let pipeline = gst::Pipeline::new();
+            pipeline.add(&element).map_err(|e| {
+                GStreamerMcpError::PipelineError(format!("Failed to wrap element: {}", e))
+            })?;
+            pipeline
+        }
+    };
+
+    let elements: Vec<gst::Element> = pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .collect();
+    let mut substitutions: Vec<String> = elements
+        .iter()
+        .filter(|e| is_sink(e))
+        .filter_map(replace_sink)
+        .collect();
+    substitutions.extend(feed_unlinked_inputs(&pipeline, &elements));
+
+    let described: HashSet<String> = elements.iter().map(|e| e.name().to_string()).collect();
+
+    let (prerolled, error) = match pipeline.set_state(gst::State::Paused) {
+        Err(_) => (false, first_error(&pipeline)),
+        Ok(gst::StateChangeSuccess::NoPreroll) => (
+            false,
+            Some("Live pipelines do not preroll; auto-plugging happens once PLAYING".to_string()),
+        ),
+        Ok(_) => {
+            let (result, _, _) =
+                pipeline.state(gst::ClockTime::from_nseconds(timeout.as_nanos() as u64));
+            match result {
+                Ok(gst::StateChangeSuccess::Success) => (true, None),
+                Ok(_) => (
+                    false,
+                    Some(format!(
+                        "Did not reach PAUSED within {} ms",
+                        timeout.as_millis()
+                    )),
+                ),
+                Err(_) => (false, first_error(&pipeline)),
+            }
+        }
+    };
+
+    let autoplugged = pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| !described.contains(e.name().as_str()))
+        .filter(|e| {
+            e.parent()
+                .is_some_and(|p| p != *pipeline.upcast_ref::<gst::Object>())
+        })
+        .map(|e| AutopluggedElement {
+            name: e.name().to_string(),
+            factory: e
+                .factory()
+                .map(|f| f.name().to_string())
+                .unwrap_or_default(),
+            parent: e.parent().map(|p| p.name().to_string()).unwrap_or_default(),
+        })
+        .collect();
+
+    let _ = pipeline.set_state(gst::State::Null);
+    Ok(DeepValidation {
+        prerolled,
+        error,
+        autoplugged,
+        substitutions,
+    })
+}
@@ -2,12 +2,31 @@ use crate::error::{GStreamerMcpError, Result};
 use gstreamer as gst;
 use gstreamer::prelude::*;
 use once_cell::sync::OnceCell;
+use rmcp::schemars::{self, JsonSchema};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
 
+/// Hidden command-line flag running the server binary as an inspection helper
+pub const INSPECT_HELPER_ARG: &str = "--inspect-helper";
+
+/// How long an inspection helper may take per element before it is killed
+const INSPECT_HELPER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Elements inspected per helper process, and per step of the search index
+const INSPECT_BATCH: usize = 32;
+
 static GST_INITIALIZED: OnceCell<()> = OnceCell::new();
 
+/// Inspections by element name, with the version of the plugin inspected
+type InspectionCache = HashMap<String, (String, ElementDetailedInfo)>;
+
 pub fn ensure_gstreamer_initialized() -> Result<()> {
     GST_INITIALIZED.get_or_try_init(|| {
         gst::init().map_err(|e| GStreamerMcpError::GStreamerInit(e.to_string()))
@@ -22,6 +41,59 @@ pub struct ElementInfo {
     pub plugin_name: String,
     pub rank: String,
     pub classification: String,
+    #[serde(default)]
+    pub rank_value: i32,
+    #[serde(default)]
+    pub has_src_pads: bool,
+    #[serde(default)]
+    pub has_sink_pads: bool,
+    /// Kinds of media the element handles, from its pad caps and class
+    #[serde(default)]
+    pub media_types: Vec<MediaType>,
+}
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaType {
+    Audio,
+    Video,
+    Text,
+}
+
+impl MediaType {
+    /// Media type of a caps structure name such as "video/x-raw"
+    pub fn from_caps_name(name: &str) -> Option<Self> {
+        match name.split('/').next()? {
+            "audio" => Some(MediaType::Audio),
+            "video" | "image" => Some(MediaType::Video),
+            "text" | "subtitle" | "subpicture" | "closedcaption" => Some(MediaType::Text),
+            "application" if name.contains("subtitle") => Some(MediaType::Text),
+            _ => None,
+        }
+    }
+
+    /// Media type named in an element classification such as "Codec/Decoder/Video"
+    fn from_klass_token(token: &str) -> Option<Self> {
+        match token {
+            "Audio" => Some(MediaType::Audio),
+            "Video" | "Image" => Some(MediaType::Video),
+            "Subtitle" | "Text" => Some(MediaType::Text),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a rank given by name (none, marginal, secondary, primary) or value
+pub fn parse_rank(rank: &str) -> Option<i32> {
+    match rank.trim().to_lowercase().as_str() {
+        "none" => Some(gst::Rank::NONE.into()),
+        "marginal" => Some(gst::Rank::MARGINAL.into()),
+        "secondary" => Some(gst::Rank::SECONDARY.into()),
+        "primary" => Some(gst::Rank::PRIMARY.into()),
+        other => other.parse().ok(),
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +106,15 @@ pub struct ElementDetailedInfo {
     pub properties: Vec<PropertyInfo>,
     pub pad_templates: Vec<PadTemplateInfo>,
     pub signals: Vec<SignalInfo>,
+    /// GObject type of the element and its parent classes, most derived first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub type_hierarchy: Vec<String>,
+    /// Interfaces such as GstURIHandler, GstVideoOverlay or GstChildProxy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub interfaces: Vec<String>,
+    /// Why the element could not be created; properties are then missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instantiation_error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,17 +152,54 @@ pub struct PluginInfo {
     pub elements: Vec<String>,
 }
 
+/// Readiness of the discovery cache
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum CacheStatus {
+    /// Nothing has been discovered yet
+    Cold,
+    /// A background warm-up is in progress
+    Warming,
+    /// Elements and plugins are cached
+    Ready { elements: usize, plugins: usize },
+    /// Only part of the registry is cached (populated lazily)
+    Partial {
+        elements: Option<usize>,
+        plugins: Option<usize>,
+    },
+    /// The last warm-up failed
+    Failed { error: String },
+}
+
+/// Progress of the index of element properties and signals
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum IndexStatus {
+    /// No search has needed it yet
+    #[default]
+    Cold,
+    /// Elements are being inspected in the background
+    Building { indexed: usize, total: usize },
+    /// Every element is inspected
+    Ready { elements: usize },
+}
+
+#[derive(Clone, Default)]
 pub struct DiscoveryCache {
     elements: Arc<RwLock<Option<Vec<ElementInfo>>>>,
     plugins: Arc<RwLock<Option<Vec<PluginInfo>>>>,
+    details: Arc<RwLock<InspectionCache>>,
+    warming: Arc<AtomicBool>,
+    last_error: Arc<RwLock<Option<String>>>,
+    index: Arc<parking_lot::Mutex<IndexStatus>>,
+    /// Bumped by [`DiscoveryCache::clear`] so an index build in progress
+    /// stops filling the emptied cache
+    index_generation: Arc<AtomicU64>,
 }
 
 impl DiscoveryCache {
     pub fn new() -> Self {
-        Self {
-            elements: Arc::new(RwLock::new(None)),
-            plugins: Arc::new(RwLock::new(None)),
-        }
+        Self::default()
     }
 
     pub async fn get_elements(&self) -> Result<Vec<ElementInfo>> {
@@ -100,9 +218,196 @@ impl DiscoveryCache {
         Ok(cache.as_ref().unwrap().clone())
     }
 
+    /// Detailed info of `element_name`. Elements are created once per
+    /// plugin version; later calls are answered from the cache.
+    pub async fn get_element_details(
+        &self,
+        element_name: &str,
+        isolated: bool,
+    ) -> Result<ElementDetailedInfo> {
+        let version = element_plugin_version(element_name)?;
+        if let Some((_, info)) = self
+            .details
+            .read()
+            .await
+            .get(element_name)
+            .filter(|(inspected, _)| *inspected == version)
+        {
+            return Ok(info.clone());
+        }
+
+        let name = element_name.to_string();
+        let info = tokio::task::spawn_blocking(move || {
+            if isolated {
+                inspect_element_isolated(&name)
+            } else {
+                inspect_element(&name)
+            }
+        })
+        .await
+        .map_err(|e| GStreamerMcpError::Other(format!("Inspection task failed: {}", e)))
+        .and_then(|r| r)?;
+
+        self.details
+            .write()
+            .await
+            .insert(element_name.to_string(), (version, info.clone()));
+        Ok(info)
+    }
+
+    /// Detailed info of every element, by name, for searches of properties
+    /// and signals. The first call starts inspecting the elements in a
+    /// background task, a batch at a time; until that finishes the progress
+    /// is returned instead.
+    pub async fn search_index(
+        &self,
+        isolated: bool,
+    ) -> std::result::Result<HashMap<String, ElementDetailedInfo>, IndexStatus> {
+        let status = self.index.lock().clone();
+        match status {
+            IndexStatus::Ready { .. } => Ok(self
+                .details
+                .read()
+                .await
+                .iter()
+                .map(|(name, (_, info))| (name.clone(), info.clone()))
+                .collect()),
+            IndexStatus::Cold => {
+                self.start_index(isolated);
+                Err(self.index.lock().clone())
+            }
+            building => Err(building),
+        }
+    }
+
+    fn start_index(&self, isolated: bool) {
+        {
+            let mut status = self.index.lock();
+            if *status != IndexStatus::Cold {
+                return;
+            }
+            *status = IndexStatus::Building {
+                indexed: 0,
+                total: 0,
+            };
+        }
+
+        let cache = self.clone();
+        let generation = self.index_generation.load(Ordering::SeqCst);
+        tokio::spawn(async move {
+            let current = || cache.index_generation.load(Ordering::SeqCst) == generation;
+            let started = Instant::now();
+            let elements = match cache.get_elements().await {
+                Ok(elements) => elements,
+                Err(e) => {
+                    tracing::warn!("Search index could not list the elements: {}", e);
+                    if current() {
+                        *cache.index.lock() = IndexStatus::Cold;
+                    }
+                    return;
+                }
+            };
+
+            let total = elements.len();
+            for (step, batch) in elements.chunks(INSPECT_BATCH).enumerate() {
+                let names: Vec<String> = {
+                    let details = cache.details.read().await;
+                    batch
+                        .iter()
+                        .filter(|e| !details.contains_key(&e.name))
+                        .map(|e| e.name.clone())
+                        .collect()
+                };
+                let inspected =
+                    tokio::task::spawn_blocking(move || inspect_batch(&names, isolated))
+                        .await
+                        .unwrap_or_default();
+                if !current() {
+                    return;
+                }
+                cache.details.write().await.extend(inspected);
+                *cache.index.lock() = IndexStatus::Building {
+                    indexed: (step * INSPECT_BATCH + batch.len()).min(total),
+                    total,
+                };
+            }
+
+            if current() {
+                tracing::info!(
+                    "Search index of {} elements built in {:?}",
+                    total,
+                    started.elapsed()
+                );
+                *cache.index.lock() = IndexStatus::Ready { elements: total };
+            }
+        });
+    }
+
+    /// Progress of the property and signal search index
+    pub fn index_status(&self) -> IndexStatus {
+        self.index.lock().clone()
+    }
+
     pub async fn clear(&self) {
+        self.index_generation.fetch_add(1, Ordering::SeqCst);
+        *self.index.lock() = IndexStatus::Cold;
         *self.elements.write().await = None;
         *self.plugins.write().await = None;
+        self.details.write().await.clear();
+    }
+
+    /// Populate the element and plugin caches on a blocking thread so the
+    /// first client request does not pay for the registry scan
+    pub async fn warm(&self) -> Result<()> {
+        self.warming.store(true, Ordering::SeqCst);
+        let started = std::time::Instant::now();
+
+        let result = tokio::task::spawn_blocking(|| -> Result<_> {
+            Ok((discover_all_elements()?, discover_all_plugins()?))
+        })
+        .await
+        .map_err(|e| GStreamerMcpError::Other(format!("Cache warm-up task failed: {}", e)))
+        .and_then(|r| r);
+
+        let outcome = match result {
+            Ok((elements, plugins)) => {
+                tracing::info!(
+                    "Discovery cache warmed in {:?}: {} elements, {} plugins",
+                    started.elapsed(),
+                    elements.len(),
+                    plugins.len()
+                );
+                *self.elements.write().await = Some(elements);
+                *self.plugins.write().await = Some(plugins);
+                *self.last_error.write().await = None;
+                Ok(())
+            }
+            Err(e) => {
+                tracing::warn!("Discovery cache warm-up failed: {}", e);
+                *self.last_error.write().await = Some(e.to_string());
+                Err(e)
+            }
+        };
+
+        self.warming.store(false, Ordering::SeqCst);
+        outcome
+    }
+
+    pub async fn status(&self) -> CacheStatus {
+        if self.warming.load(Ordering::SeqCst) {
+            return CacheStatus::Warming;
+        }
+
+        let elements = self.elements.read().await.as_ref().map(|e| e.len());
+        let plugins = self.plugins.read().await.as_ref().map(|p| p.len());
+        match (elements, plugins) {
+            (Some(elements), Some(plugins)) => CacheStatus::Ready { elements, plugins },
+            (None, None) => match self.last_error.read().await.clone() {
+                Some(error) => CacheStatus::Failed { error },
+                None => CacheStatus::Cold,
+            },
+            (elements, plugins) => CacheStatus::Partial { elements, plugins },
+        }
     }
 }
 
@@ -117,12 +422,18 @@ pub fn discover_all_elements() -> Result<Vec<ElementInfo>> {
 
         for feature in registry.features_by_plugin(&plugin_name) {
             if let Ok(factory) = feature.downcast::<gst::ElementFactory>() {
+                let templates = factory.static_pad_templates();
+                let has_pads = |direction| templates.iter().any(|t| t.direction() == direction);
                 elements.push(ElementInfo {
                     name: factory.name().to_string(),
                     description: factory.description().to_string(),
                     plugin_name: plugin_name.clone(),
                     rank: format!("{:?}", factory.rank()),
                     classification: factory.klass().to_string(),
+                    rank_value: factory.rank().into(),
+                    has_src_pads: has_pads(gst::PadDirection::Src),
+                    has_sink_pads: has_pads(gst::PadDirection::Sink),
+                    media_types: factory_media_types(&factory),
                 });
             }
         }
@@ -132,6 +443,28 @@ pub fn discover_all_elements() -> Result<Vec<ElementInfo>> {
     Ok(elements)
 }
 
+fn factory_media_types(factory: &gst::ElementFactory) -> Vec<MediaType> {
+    let mut types: Vec<MediaType> = factory
+        .static_pad_templates()
+        .iter()
+        .flat_map(|t| {
+            let caps = t.caps();
+            caps.iter()
+                .filter_map(|s| MediaType::from_caps_name(s.name()))
+                .collect::<Vec<_>>()
+        })
+        .chain(
+            factory
+                .klass()
+                .split('/')
+                .filter_map(MediaType::from_klass_token),
+        )
+        .collect();
+    types.sort();
+    types.dedup();
+    types
+}
+
 pub fn discover_all_plugins() -> Result<Vec<PluginInfo>> {
     ensure_gstreamer_initialized()?;
 
@@ -166,56 +499,240 @@ pub fn discover_all_plugins() -> Result<Vec<PluginInfo>> {
 pub fn inspect_element(element_name: &str) -> Result<ElementDetailedInfo> {
     ensure_gstreamer_initialized()?;
 
-    let registry = gst::Registry::get();
-    let factory = registry
-        .find_feature(element_name, gst::ElementFactory::static_type())
-        .and_then(|f| f.downcast::<gst::ElementFactory>().ok())
-        .ok_or_else(|| GStreamerMcpError::ElementNotFound(element_name.to_string()))?;
+    let factory = find_element_factory(element_name)?;
+    let mut info = factory_metadata(&factory);
+
+    match factory.create().name(element_name).build() {
+        Ok(element) => {
+            info.properties = get_element_properties(&element)?;
+            info.signals = get_element_signals(element.type_());
+        }
+        // Describe the factory even when its element cannot be created
+        Err(e) => info.instantiation_error = Some(e.to_string()),
+    }
 
-    let element = factory
-        .create()
-        .name(element_name)
-        .build()
-        .map_err(|e| GStreamerMcpError::ElementNotFound(format!("{}: {}", element_name, e)))?;
+    // Known once the plugin is loaded, even if creating the element failed
+    let element_type = factory.element_type();
+    if element_type.is_valid() {
+        info.type_hierarchy = type_hierarchy(element_type);
+        info.interfaces = type_interfaces(element_type);
+    }
+
+    Ok(info)
+}
+
+/// `element_type` followed by its parent classes, e.g. GstX264Enc,
+/// GstVideoEncoder, GstElement, GstObject, GInitiallyUnowned, GObject
+fn type_hierarchy(element_type: gst::glib::Type) -> Vec<String> {
+    std::iter::successors(Some(element_type), |t| t.parent())
+        .map(|t| t.name().to_string())
+        .collect()
+}
+
+/// Interfaces implemented by `element_type` or its parent classes
+fn type_interfaces(element_type: gst::glib::Type) -> Vec<String> {
+    let mut interfaces: Vec<String> = std::iter::successors(Some(element_type), |t| t.parent())
+        .flat_map(|t| {
+            t.interfaces()
+                .iter()
+                .map(|i| i.name().to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    interfaces.sort();
+    interfaces.dedup();
+    interfaces
+}
 
-    // Get properties
-    let properties = get_element_properties(&element)?;
+/// Inspect `element_name` in a short-lived helper process, so a plugin that
+/// crashes while its element is created takes down the helper rather than
+/// the server. A crashed or hung helper falls back to the factory metadata.
+/// Results, including failures, are cached by [`DiscoveryCache`] like other
+/// inspections.
+pub fn inspect_element_isolated(element_name: &str) -> Result<ElementDetailedInfo> {
+    ensure_gstreamer_initialized()?;
 
-    // Get pad templates
-    let pad_templates = get_pad_templates(&factory);
+    find_element_factory(element_name)?;
+    inspect_elements_isolated(&[element_name.to_string()])
+        .pop()
+        .ok_or_else(|| GStreamerMcpError::ElementNotFound(element_name.to_string()))
+}
 
-    // Get signals (basic implementation - GStreamer signals are complex)
-    let signals = Vec::new(); // TODO: Implement signal discovery if needed
+/// Inspect `element_names` in helper processes, one per batch of
+/// [`INSPECT_BATCH`] elements. An element that crashes or hangs its helper
+/// falls back to the factory metadata, and a new helper carries on with the
+/// elements after it.
+pub fn inspect_elements_isolated(element_names: &[String]) -> Vec<ElementDetailedInfo> {
+    let mut inspected = Vec::new();
+    let mut rest = element_names;
+    while !rest.is_empty() {
+        let batch = &rest[..rest.len().min(INSPECT_BATCH)];
+        let (infos, failure) = run_inspect_helper(batch);
+        let done = infos.len().min(batch.len());
+        inspected.extend(infos);
+        rest = &rest[done..];
 
-    // Try to find the plugin that provides this element
-    let plugin_name = registry
-        .plugins()
+        if done < batch.len() {
+            let reason = failure.unwrap_or_else(|| "helper stopped early".to_string());
+            tracing::warn!("Isolated inspection of {} failed: {}", rest[0], reason);
+            if let Ok(info) = element_metadata(&rest[0]) {
+                inspected.push(ElementDetailedInfo {
+                    instantiation_error: Some(reason),
+                    ..info
+                });
+            }
+            rest = &rest[1..];
+        }
+    }
+    inspected
+}
+
+/// Inspections of `element_names` keyed for the [`InspectionCache`];
+/// elements that are not found are left out
+fn inspect_batch(
+    element_names: &[String],
+    isolated: bool,
+) -> Vec<(String, (String, ElementDetailedInfo))> {
+    let infos = if isolated {
+        inspect_elements_isolated(element_names)
+    } else {
+        element_names
+            .iter()
+            .filter_map(|name| inspect_element(name).ok())
+            .collect()
+    };
+    infos
         .into_iter()
-        .find(|plugin| {
-            registry
-                .features_by_plugin(&plugin.plugin_name())
-                .into_iter()
-                .any(|feature| {
-                    if let Ok(f) = feature.downcast::<gst::ElementFactory>() {
-                        f.name() == element_name
-                    } else {
-                        false
-                    }
-                })
+        .filter_map(|info| {
+            let version = element_plugin_version(&info.name).ok()?;
+            Some((info.name.clone(), (version, info)))
         })
-        .map(|p| p.plugin_name().to_string())
-        .unwrap_or_else(|| "unknown".to_string());
+        .collect()
+}
+
+/// Version of the plugin providing `element_name`, which invalidates cached
+/// inspections of the element when the plugin is upgraded
+pub fn element_plugin_version(element_name: &str) -> Result<String> {
+    ensure_gstreamer_initialized()?;
+    Ok(plugin_version(&find_element_factory(element_name)?))
+}
+
+fn plugin_version(factory: &gst::ElementFactory) -> String {
+    factory
+        .plugin()
+        .map(|p| p.version().to_string())
+        .unwrap_or_default()
+}
+
+/// Entry point of the helper process started by [`inspect_elements_isolated`]:
+/// writes the inspection of each of the comma-separated `element_names` to
+/// stdout as a line of JSON, in order, and stops at the first failure
+pub fn inspect_helper_main(element_names: &str) -> Result<()> {
+    crate::locale::set_description_language(
+        std::env::var(crate::locale::DESCRIPTION_LANGUAGE_ENV)
+            .ok()
+            .as_deref(),
+    );
+    for element_name in element_names.split(',') {
+        let info = inspect_element(element_name)?;
+        println!("{}", serde_json::to_string(&info)?);
+    }
+    Ok(())
+}
+
+/// Run a helper inspecting `element_names` in order. Returns the
+/// inspections it wrote and, if it did not finish, why; the element after
+/// the last inspection is the one it failed on.
+fn run_inspect_helper(element_names: &[String]) -> (Vec<ElementDetailedInfo>, Option<String>) {
+    let mut inspected = Vec::new();
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => return (inspected, Some(format!("helper not found: {}", e))),
+    };
+    let spawned = Command::new(exe)
+        .arg(INSPECT_HELPER_ARG)
+        .arg(element_names.join(","))
+        .envs(
+            crate::locale::configured_description_language()
+                .map(|language| (crate::locale::DESCRIPTION_LANGUAGE_ENV, language)),
+        )
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) => return (inspected, Some(format!("helper did not start: {}", e))),
+    };
+
+    // Read stdout on a thread so each element can be given its own timeout
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let (lines_tx, lines) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if lines_tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let failure = loop {
+        match lines.recv_timeout(INSPECT_HELPER_TIMEOUT) {
+            Ok(Ok(line)) => match serde_json::from_str(&line) {
+                Ok(info) => inspected.push(info),
+                Err(e) => break Some(format!("helper output is invalid: {}", e)),
+            },
+            Ok(Err(e)) => break Some(format!("helper output could not be read: {}", e)),
+            Err(RecvTimeoutError::Timeout) => {
+                break Some(format!(
+                    "helper timed out after {}s",
+                    INSPECT_HELPER_TIMEOUT.as_secs()
+                ))
+            }
+            // The helper closed its output
+            Err(RecvTimeoutError::Disconnected) => break None,
+        }
+    };
+    if failure.is_some() {
+        let _ = child.kill();
+        let _ = child.wait();
+        return (inspected, failure);
+    }
+
+    let failure = match child.wait() {
+        Ok(status) if status.success() => None,
+        Ok(status) => Some(format!("helper failed ({})", status)),
+        Err(e) => Some(format!("helper could not be waited for: {}", e)),
+    };
+    (inspected, failure)
+}
+
+fn find_element_factory(element_name: &str) -> Result<gst::ElementFactory> {
+    gst::Registry::get()
+        .find_feature(element_name, gst::ElementFactory::static_type())
+        .and_then(|f| f.downcast::<gst::ElementFactory>().ok())
+        .ok_or_else(|| GStreamerMcpError::ElementNotFound(element_name.to_string()))
+}
 
-    Ok(ElementDetailedInfo {
-        name: element_name.to_string(),
+/// Everything known about an element without creating one
+fn factory_metadata(factory: &gst::ElementFactory) -> ElementDetailedInfo {
+    ElementDetailedInfo {
+        name: factory.name().to_string(),
         description: factory.longname().to_string(),
-        plugin_name,
+        plugin_name: factory
+            .plugin_name()
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
         rank: format!("{:?}", factory.rank()),
         classification: factory.klass().to_string(),
-        properties,
-        pad_templates,
-        signals,
-    })
+        properties: Vec::new(),
+        pad_templates: get_pad_templates(factory),
+        // Signals are read from the element type, loaded with the element
+        signals: Vec::new(),
+        type_hierarchy: Vec::new(),
+        interfaces: Vec::new(),
+        instantiation_error: None,
+    }
 }
 
 fn get_element_properties(element: &gst::Element) -> Result<Vec<PropertyInfo>> {
@@ -262,6 +779,41 @@ fn get_element_properties(element: &gst::Element) -> Result<Vec<PropertyInfo>> {
     Ok(prop_infos)
 }
 
+/// Signals of `element_type` and its parent classes up to GstElement
+fn get_element_signals(element_type: gst::glib::Type) -> Vec<SignalInfo> {
+    use gst::glib::translate::{FromGlib, IntoGlib};
+
+    let mut signals = Vec::new();
+    let mut type_ = Some(element_type);
+    while let Some(t) = type_.filter(|t| t.is_a(gst::Element::static_type())) {
+        let mut n_ids = 0;
+        // SAFETY: g_signal_list_ids returns an array of n_ids signal IDs
+        // owned by the caller
+        unsafe {
+            let ids = gst::glib::gobject_ffi::g_signal_list_ids(t.into_glib(), &mut n_ids);
+            if !ids.is_null() {
+                for id in std::slice::from_raw_parts(ids, n_ids as usize) {
+                    let query = gst::glib::subclass::SignalId::from_glib(*id).query();
+                    signals.push(SignalInfo {
+                        name: query.signal_name().to_string(),
+                        return_type: query.return_type().type_().name().to_string(),
+                        parameters: query
+                            .param_types()
+                            .iter()
+                            .map(|p| p.type_().name().to_string())
+                            .collect(),
+                    });
+                }
+                gst::glib::ffi::g_free(ids as *mut _);
+            }
+        }
+        type_ = t.parent();
+    }
+
+    signals.sort_by(|a, b| a.name.cmp(&b.name));
+    signals
+}
+
 fn get_pad_templates(factory: &gst::ElementFactory) -> Vec<PadTemplateInfo> {
     let mut templates = Vec::new();
 
@@ -280,8 +832,142 @@ fn get_pad_templates(factory: &gst::ElementFactory) -> Vec<PadTemplateInfo> {
     templates
 }
 
+/// Factory metadata of `element_name` (description, rank, pad templates),
+/// without creating the element
+pub fn element_metadata(element_name: &str) -> Result<ElementDetailedInfo> {
+    ensure_gstreamer_initialized()?;
+    Ok(factory_metadata(&find_element_factory(element_name)?))
+}
+
+/// Parts of an element a search query is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchField {
+    /// Element names
+    Names,
+    /// Descriptions and classifications
+    Descriptions,
+    /// Property names and descriptions
+    Properties,
+    /// Signal names
+    Signals,
+    /// Pad template caps
+    Caps,
+}
+
+impl SearchField {
+    /// Fields searched when none are given
+    pub const DEFAULT: &'static [SearchField] = &[SearchField::Names, SearchField::Descriptions];
+
+    /// Whether matching this field needs the elements created, which the
+    /// search index of [`DiscoveryCache::search_index`] does once
+    pub fn needs_instance(self) -> bool {
+        matches!(self, SearchField::Properties | SearchField::Signals)
+    }
+}
+
+/// An element found by [`rank_detailed_elements`] and what matched the query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchMatch {
+    pub element: ElementInfo,
+    /// Properties, signals and caps fields that matched, e.g. "property key-int-max"
+    pub matched: Vec<String>,
+}
+
+/// Score and rank elements against a search query in the given fields.
+/// `details` holds the detailed info of the elements, by name, needed to
+/// search properties, signals and caps.
+pub fn rank_detailed_elements(
+    all_elements: Vec<ElementInfo>,
+    details: &HashMap<String, ElementDetailedInfo>,
+    query: &str,
+    fields: &[SearchField],
+    max_results: usize,
+) -> Vec<SearchMatch> {
+    let query_lower = query.to_lowercase();
+    let term = |text: &str| -> i32 {
+        let text = text.to_lowercase();
+        if text == query_lower {
+            2
+        } else if text.contains(&query_lower) {
+            1
+        } else {
+            0
+        }
+    };
+
+    let mut matches: Vec<(SearchMatch, i32)> = all_elements
+        .into_iter()
+        .filter_map(|element| {
+            let mut score = 0;
+            let mut matched = Vec::new();
+
+            if fields.contains(&SearchField::Names) {
+                score += term(&element.name) * 50;
+            }
+            if fields.contains(&SearchField::Descriptions) {
+                score += term(&element.description).min(1) * 20;
+                score += term(&element.classification).min(1) * 10;
+            }
+
+            if let Some(info) = details.get(&element.name) {
+                if fields.contains(&SearchField::Properties) {
+                    for prop in &info.properties {
+                        let name_score = term(&prop.name);
+                        if name_score > 0 || term(&prop.description) > 0 {
+                            score += name_score * 30 + 5;
+                            matched.push(format!("property {}", prop.name));
+                        }
+                    }
+                }
+                if fields.contains(&SearchField::Signals) {
+                    for signal in info.signals.iter().filter(|s| term(&s.name) > 0) {
+                        score += term(&signal.name) * 30;
+                        matched.push(format!("signal {}", signal.name));
+                    }
+                }
+                if fields.contains(&SearchField::Caps) {
+                    for pad in info.pad_templates.iter().filter(|p| term(&p.caps) > 0) {
+                        score += 15;
+                        matched.push(format!("{} caps", pad.name));
+                    }
+                }
+            }
+
+            (score > 0).then_some((SearchMatch { element, matched }, score))
+        })
+        .collect();
+
+    // Sort by score (highest first)
+    matches.sort_by_key(|(_, score)| std::cmp::Reverse(*score));
+
+    matches
+        .into_iter()
+        .take(max_results)
+        .map(|(m, _)| m)
+        .collect()
+}
+
+/// Factory metadata of every element in `element_names`, by name, for
+/// searches of caps; no element is created
+pub fn metadata_index(element_names: &[String]) -> HashMap<String, ElementDetailedInfo> {
+    element_names
+        .iter()
+        .filter_map(|name| element_metadata(name).ok().map(|info| (name.clone(), info)))
+        .collect()
+}
+
 pub fn search_elements(query: &str, max_results: usize) -> Result<Vec<ElementInfo>> {
     let all_elements = discover_all_elements()?;
+    Ok(rank_elements(all_elements, query, max_results))
+}
+
+/// Score and rank already discovered elements against a search query
+pub fn rank_elements(
+    all_elements: Vec<ElementInfo>,
+    query: &str,
+    max_results: usize,
+) -> Vec<ElementInfo> {
     let query_lower = query.to_lowercase();
 
     let mut matches: Vec<(ElementInfo, i32)> = all_elements
@@ -321,12 +1007,182 @@ pub fn search_elements(query: &str, max_results: usize) -> Result<Vec<ElementInf
         .collect();
 
     // Sort by score (highest first)
-    matches.sort_by(|a, b| b.1.cmp(&a.1));
+    matches.sort_by_key(|m| std::cmp::Reverse(m.1));
 
     // Take only the requested number of results
-    Ok(matches
+    matches
         .into_iter()
         .take(max_results)
         .map(|(element, _)| element)
-        .collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(name: &str, description: &str) -> ElementInfo {
+        ElementInfo {
+            name: name.to_string(),
+            description: description.to_string(),
+            plugin_name: "test".to_string(),
+            rank: "Primary".to_string(),
+            classification: "Codec/Encoder/Video".to_string(),
+            rank_value: 256,
+            has_src_pads: true,
+            has_sink_pads: true,
+            media_types: vec![MediaType::Video],
+        }
+    }
+
+    fn property(name: &str) -> PropertyInfo {
+        PropertyInfo {
+            name: name.to_string(),
+            type_name: "guint".to_string(),
+            description: String::new(),
+            flags: Vec::new(),
+            default_value: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_rank() {
+        assert_eq!(parse_rank("Primary"), Some(256));
+        assert_eq!(parse_rank("marginal"), Some(64));
+        assert_eq!(parse_rank("300"), Some(300));
+        assert_eq!(parse_rank("best"), None);
+    }
+
+    #[test]
+    fn test_media_type_from_caps_name() {
+        assert_eq!(
+            MediaType::from_caps_name("video/x-h264"),
+            Some(MediaType::Video)
+        );
+        assert_eq!(
+            MediaType::from_caps_name("audio/x-raw"),
+            Some(MediaType::Audio)
+        );
+        assert_eq!(
+            MediaType::from_caps_name("application/x-subtitle-vtt"),
+            Some(MediaType::Text)
+        );
+        assert_eq!(MediaType::from_caps_name("application/x-rtp"), None);
+    }
+
+    #[test]
+    fn test_rank_detailed_elements() {
+        let elements = vec![
+            element("x264enc", "H264 Encoder"),
+            element("vp8enc", "On2 VP8 Encoder"),
+        ];
+        let mut details = HashMap::new();
+        details.insert(
+            "x264enc".to_string(),
+            ElementDetailedInfo {
+                name: "x264enc".to_string(),
+                description: "H264 Encoder".to_string(),
+                plugin_name: "test".to_string(),
+                rank: "Primary".to_string(),
+                classification: "Codec/Encoder/Video".to_string(),
+                properties: vec![property("bitrate"), property("key-int-max")],
+                pad_templates: Vec::new(),
+                signals: Vec::new(),
+                type_hierarchy: Vec::new(),
+                interfaces: Vec::new(),
+                instantiation_error: None,
+            },
+        );
+
+        let results = rank_detailed_elements(
+            elements.clone(),
+            &details,
+            "key-int-max",
+            &[SearchField::Properties],
+            10,
+        );
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].element.name, "x264enc");
+        assert_eq!(results[0].matched, vec!["property key-int-max"]);
+
+        // Properties are not searched by default
+        let results =
+            rank_detailed_elements(elements, &details, "bitrate", SearchField::DEFAULT, 10);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_inspect_element_isolated_fallback() {
+        let info = inspect_element("videotestsrc").unwrap();
+        assert!(info.properties.iter().any(|p| p.name == "pattern"));
+        assert!(info.instantiation_error.is_none());
+
+        // The test binary is no inspection helper, so the helper fails and
+        // the factory metadata is returned instead
+        let fallback = inspect_element_isolated("videotestsrc").unwrap();
+        assert_eq!(fallback.name, "videotestsrc");
+        assert_eq!(fallback.classification, info.classification);
+        assert_eq!(fallback.pad_templates.len(), info.pad_templates.len());
+        assert!(fallback.properties.is_empty());
+        assert!(fallback.instantiation_error.is_some());
+
+        assert!(matches!(
+            inspect_element_isolated("nosuchelement"),
+            Err(GStreamerMcpError::ElementNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_element_details_cache() {
+        let cache = DiscoveryCache::new();
+        let info = cache
+            .get_element_details("videotestsrc", false)
+            .await
+            .unwrap();
+        let version = element_plugin_version("videotestsrc").unwrap();
+        {
+            let details = cache.details.read().await;
+            let (cached_version, cached) = &details["videotestsrc"];
+            assert_eq!(cached_version, &version);
+            assert_eq!(cached.properties.len(), info.properties.len());
+        }
+
+        // A cached inspection is returned as is while the plugin version
+        // matches, and replaced once it does not
+        cache.details.write().await.insert(
+            "videotestsrc".to_string(),
+            (
+                version.clone(),
+                ElementDetailedInfo {
+                    properties: Vec::new(),
+                    ..info.clone()
+                },
+            ),
+        );
+        let cached = cache
+            .get_element_details("videotestsrc", false)
+            .await
+            .unwrap();
+        assert!(cached.properties.is_empty());
+
+        cache.details.write().await.insert(
+            "videotestsrc".to_string(),
+            (
+                "0.0.0".to_string(),
+                ElementDetailedInfo {
+                    properties: Vec::new(),
+                    ..info.clone()
+                },
+            ),
+        );
+        let refreshed = cache
+            .get_element_details("videotestsrc", false)
+            .await
+            .unwrap();
+        assert_eq!(refreshed.properties.len(), info.properties.len());
+        assert_eq!(cache.details.read().await["videotestsrc"].0, version);
+
+        cache.clear().await;
+        assert!(cache.details.read().await.is_empty());
+    }
 }
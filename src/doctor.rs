@@ -0,0 +1,456 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::discovery::ensure_gstreamer_initialized;
+
+/// Elements every pipeline tool relies on, and the package providing them
+const ESSENTIAL_ELEMENTS: &[(&str, &str)] = &[
+    ("queue", "GStreamer core"),
+    ("tee", "GStreamer core"),
+    ("filesrc", "GStreamer core"),
+    ("filesink", "GStreamer core"),
+    ("fakesink", "GStreamer core"),
+    ("typefind", "GStreamer core"),
+    ("decodebin", "gst-plugins-base"),
+    ("uridecodebin", "gst-plugins-base"),
+    ("videoconvert", "gst-plugins-base"),
+    ("audioconvert", "gst-plugins-base"),
+    ("audioresample", "gst-plugins-base"),
+    ("videotestsrc", "gst-plugins-base"),
+    ("audiotestsrc", "gst-plugins-base"),
+];
+
+/// Elements of common formats, and the package providing them
+const COMMON_ELEMENTS: &[(&str, &str)] = &[
+    ("mp4mux", "gst-plugins-good"),
+    ("matroskamux", "gst-plugins-good"),
+    ("h264parse", "gst-plugins-bad"),
+    ("x264enc", "gst-plugins-ugly"),
+    ("avdec_h264", "gst-libav"),
+];
+
+/// Hardware codec elements by API
+const HARDWARE_CODECS: &[(&str, &[&str])] = &[
+    (
+        "VA-API",
+        &["vah264dec", "vah264enc", "vaapih264dec", "vaapih264enc"],
+    ),
+    ("NVIDIA", &["nvh264dec", "nvh264enc", "nvv4l2decoder"]),
+    (
+        "Intel Quick Sync",
+        &["qsvh264dec", "qsvh264enc", "msdkh264dec", "msdkh264enc"],
+    ),
+    ("V4L2", &["v4l2h264dec", "v4l2h264enc"]),
+    ("VideoToolbox", &["vtdec", "vtenc_h264"]),
+    ("Direct3D", &["d3d11h264dec", "d3d12h264dec", "mfh264enc"]),
+];
+
+/// Outcome of one check, most severe last so sorting puts errors first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Error,
+    Warning,
+    Ok,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a failed check
+    pub fix: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn problem(
+        name: &str,
+        status: CheckStatus,
+        detail: impl Into<String>,
+        fix: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorReport {
+    /// Checks with errors first, then warnings, then passed checks
+    pub checks: Vec<DoctorCheck>,
+    /// Fixes in the order to apply them
+    pub fixes: Vec<String>,
+}
+
+impl DoctorReport {
+    fn new(mut checks: Vec<DoctorCheck>) -> Self {
+        checks.sort_by_key(|c| c.status);
+        let mut fixes: Vec<String> = Vec::new();
+        for fix in checks.iter().filter_map(|c| c.fix.clone()) {
+            if !fixes.contains(&fix) {
+                fixes.push(fix);
+            }
+        }
+        Self { checks, fixes }
+    }
+
+    pub fn count(&self, status: CheckStatus) -> usize {
+        self.checks.iter().filter(|c| c.status == status).count()
+    }
+}
+
+fn missing_elements<'a>(elements: &'a [(&'a str, &'a str)]) -> Vec<(&'a str, &'a str)> {
+    elements
+        .iter()
+        .filter(|(name, _)| gst::ElementFactory::find(name).is_none())
+        .copied()
+        .collect()
+}
+
+fn package_list(missing: &[(&str, &str)]) -> String {
+    let mut packages: Vec<&str> = missing.iter().map(|(_, package)| *package).collect();
+    packages.dedup();
+    packages.join(", ")
+}
+
+fn check_elements() -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+    let missing = missing_elements(ESSENTIAL_ELEMENTS);
+    checks.push(if missing.is_empty() {
+        DoctorCheck::ok("essential_plugins", "Core and base elements are installed")
+    } else {
+        DoctorCheck::problem(
+            "essential_plugins",
+            CheckStatus::Error,
+            format!(
+                "Missing elements: {}",
+                missing
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            format!("Install {}", package_list(&missing)),
+        )
+    });
+
+    let missing = missing_elements(COMMON_ELEMENTS);
+    checks.push(if missing.is_empty() {
+        DoctorCheck::ok(
+            "common_formats",
+            "Common muxers, parsers and codecs are installed",
+        )
+    } else {
+        DoctorCheck::problem(
+            "common_formats",
+            CheckStatus::Warning,
+            format!(
+                "Missing elements: {}",
+                missing
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            format!("Install {} for MP4/MKV and H.264", package_list(&missing)),
+        )
+    });
+    checks
+}
+
+/// Whether `factory` can be created and opened, as a device sink must
+fn sink_usable(factory: &str) -> Result<(), String> {
+    let sink = gst::ElementFactory::make(factory)
+        .build()
+        .map_err(|_| format!("{} is not installed", factory))?;
+    let result = sink
+        .set_state(gst::State::Ready)
+        .map(|_| ())
+        .map_err(|_| format!("{} could not open an output device", factory));
+    let _ = sink.set_state(gst::State::Null);
+    result
+}
+
+fn check_sinks(headless: bool) -> Vec<DoctorCheck> {
+    let audio = match sink_usable("autoaudiosink") {
+        Ok(()) => DoctorCheck::ok("audio_output", "autoaudiosink opened an audio device"),
+        Err(detail) => DoctorCheck::problem(
+            "audio_output",
+            CheckStatus::Warning,
+            detail,
+            "No usable audio device: render audio to a file or use fakesink, or start PulseAudio/PipeWire for this user",
+        ),
+    };
+    let video = match (sink_usable("autovideosink"), headless) {
+        (Ok(()), false) => DoctorCheck::ok("video_output", "autovideosink opened a display"),
+        (_, true) => DoctorCheck::problem(
+            "video_output",
+            CheckStatus::Warning,
+            "No display server (DISPLAY and WAYLAND_DISPLAY are unset); windowed video sinks will fail",
+            "Running headless: encode video to a file or stream, or use fakesink, instead of autovideosink",
+        ),
+        (Err(detail), false) => DoctorCheck::problem(
+            "video_output",
+            CheckStatus::Warning,
+            detail,
+            "Install a video sink plugin (e.g. gst-plugins-good for ximagesink/glimagesink) or use fakesink",
+        ),
+    };
+    vec![audio, video]
+}
+
+fn check_hardware() -> DoctorCheck {
+    let available: Vec<String> = HARDWARE_CODECS
+        .iter()
+        .filter_map(|(api, factories)| {
+            let found: Vec<&str> = factories
+                .iter()
+                .copied()
+                .filter(|f| gst::ElementFactory::find(f).is_some())
+                .collect();
+            (!found.is_empty()).then(|| format!("{} ({})", api, found.join(", ")))
+        })
+        .collect();
+    if available.is_empty() {
+        DoctorCheck::problem(
+            "hardware_acceleration",
+            CheckStatus::Warning,
+            "No hardware video codecs found; encoding and decoding run on the CPU",
+            "For hardware codecs install the va plugin (gst-plugins-bad) with a VA-API driver, or nvcodec with the NVIDIA driver",
+        )
+    } else {
+        DoctorCheck::ok("hardware_acceleration", available.join("; "))
+    }
+}
+
+fn check_blacklist() -> DoctorCheck {
+    let blacklisted: Vec<String> = gst::Registry::get()
+        .plugins()
+        .iter()
+        .filter(|p| p.plugin_flags().contains(gst::PluginFlags::BLACKLISTED))
+        .map(|p| p.plugin_name().to_string())
+        .collect();
+    if blacklisted.is_empty() {
+        DoctorCheck::ok("blacklisted_plugins", "No plugin failed to load")
+    } else {
+        DoctorCheck::problem(
+            "blacklisted_plugins",
+            CheckStatus::Warning,
+            format!("Plugins that failed to load: {}", blacklisted.join(", ")),
+            "Run 'gst-inspect-1.0 -b' to see why plugins fail to load, install their missing libraries, then delete the registry cache to rescan",
+        )
+    }
+}
+
+/// Directory the registry cache is written to
+fn registry_dir(var: &dyn Fn(&str) -> Option<String>) -> PathBuf {
+    match var("GST_REGISTRY_1_0").or_else(|| var("GST_REGISTRY")) {
+        Some(path) => Path::new(&path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default(),
+        None => gst::glib::user_cache_dir().join("gstreamer-1.0"),
+    }
+}
+
+fn check_registry(var: &dyn Fn(&str) -> Option<String>) -> DoctorCheck {
+    let dir = registry_dir(var);
+    let probe = dir.join(format!(".gstreamer-mcp-doctor-{}", std::process::id()));
+    let writable = std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&probe, b""))
+        .is_ok();
+    let _ = std::fs::remove_file(&probe);
+    if writable {
+        DoctorCheck::ok(
+            "registry_writable",
+            format!("{} is writable", dir.display()),
+        )
+    } else {
+        DoctorCheck::problem(
+            "registry_writable",
+            CheckStatus::Warning,
+            format!("Cannot write the registry cache in {}", dir.display()),
+            "Make the registry cache directory writable (or point GST_REGISTRY to a writable file); otherwise every start rescans all plugins",
+        )
+    }
+}
+
+/// Checks of the GStreamer environment variables, reading them via `var`
+pub fn check_environment(var: &dyn Fn(&str) -> Option<String>) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+
+    for name in ["GST_PLUGIN_PATH", "GST_PLUGIN_PATH_1_0"] {
+        let Some(value) = var(name) else { continue };
+        let missing: Vec<&str> = std::env::split_paths(&value)
+            .zip(value.split(if cfg!(windows) { ';' } else { ':' }))
+            .filter(|(path, _)| !path.as_os_str().is_empty() && !path.exists())
+            .map(|(_, entry)| entry)
+            .collect();
+        if !missing.is_empty() {
+            checks.push(DoctorCheck::problem(
+                "plugin_path",
+                CheckStatus::Warning,
+                format!("{} lists missing directories: {}", name, missing.join(", ")),
+                format!("Remove stale entries from {}", name),
+            ));
+        }
+    }
+
+    if let Some(value) = var("GST_PLUGIN_SYSTEM_PATH").or_else(|| var("GST_PLUGIN_SYSTEM_PATH_1_0"))
+    {
+        checks.push(DoctorCheck::problem(
+            "plugin_system_path",
+            CheckStatus::Warning,
+            format!("GST_PLUGIN_SYSTEM_PATH replaces the system plugin directories with '{}'", value),
+            "Unset GST_PLUGIN_SYSTEM_PATH unless the system plugins should be hidden; use GST_PLUGIN_PATH to add directories",
+        ));
+    }
+
+    if let Some(scanner) = var("GST_PLUGIN_SCANNER").or_else(|| var("GST_PLUGIN_SCANNER_1_0")) {
+        if !Path::new(&scanner).is_file() {
+            checks.push(DoctorCheck::problem(
+                "plugin_scanner",
+                CheckStatus::Error,
+                format!("GST_PLUGIN_SCANNER points to missing '{}'", scanner),
+                "Fix or unset GST_PLUGIN_SCANNER; without the scanner new plugins are loaded in-process or not at all",
+            ));
+        }
+    }
+
+    if let Some(debug) = var("GST_DEBUG") {
+        // Levels 5 and above log per buffer
+        let verbose = debug.split(',').any(|entry| {
+            let level = entry.rsplit(':').next().unwrap_or(entry).trim();
+            level.parse::<u32>().is_ok_and(|l| l >= 5)
+                || ["debug", "log", "trace", "memdump"]
+                    .iter()
+                    .any(|name| level.eq_ignore_ascii_case(name))
+        });
+        if verbose {
+            checks.push(DoctorCheck::problem(
+                "debug_logging",
+                CheckStatus::Warning,
+                format!(
+                    "GST_DEBUG={} logs at debug level or above and slows pipelines down",
+                    debug
+                ),
+                "Lower GST_DEBUG to 2-4 (or unset it) outside of debugging sessions",
+            ));
+        }
+    }
+
+    if checks.is_empty() {
+        checks.push(DoctorCheck::ok(
+            "environment",
+            "No problematic GStreamer environment variables",
+        ));
+    }
+    checks
+}
+
+/// Whether no display server is reachable, on platforms that need one
+pub fn is_headless(var: &dyn Fn(&str) -> Option<String>) -> bool {
+    cfg!(all(unix, not(target_os = "macos")))
+        && var("DISPLAY").is_none()
+        && var("WAYLAND_DISPLAY").is_none()
+}
+
+/// Run every check against the running process and environment
+pub fn run_doctor() -> DoctorReport {
+    let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+    let mut checks = Vec::new();
+    if let Err(e) = ensure_gstreamer_initialized() {
+        checks.push(DoctorCheck::problem(
+            "initialization",
+            CheckStatus::Error,
+            e.to_string(),
+            "Install the GStreamer runtime (1.16 or later) and make sure its libraries are on the library search path",
+        ));
+        checks.extend(check_environment(&var));
+        return DoctorReport::new(checks);
+    }
+
+    let (major, minor, micro, _) = gst::version();
+    checks.push(if (major, minor) >= (1, 16) {
+        DoctorCheck::ok(
+            "initialization",
+            format!("GStreamer {}.{}.{} initialized", major, minor, micro),
+        )
+    } else {
+        DoctorCheck::problem(
+            "initialization",
+            CheckStatus::Warning,
+            format!("GStreamer {}.{}.{} is older than 1.16", major, minor, micro),
+            "Upgrade GStreamer to 1.16 or later",
+        )
+    });
+    checks.extend(check_elements());
+    checks.extend(check_sinks(is_headless(&var)));
+    checks.push(check_hardware());
+    checks.push(check_blacklist());
+    checks.push(check_registry(&var));
+    checks.extend(check_environment(&var));
+    DoctorReport::new(checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_check_environment() {
+        let checks = check_environment(&env(&[]));
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, CheckStatus::Ok);
+
+        let checks = check_environment(&env(&[
+            ("GST_DEBUG", "2,rtpjitterbuffer:6"),
+            ("GST_PLUGIN_SCANNER", "/nonexistent/gst-plugin-scanner"),
+        ]));
+        let names: Vec<&str> = checks.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["plugin_scanner", "debug_logging"]);
+
+        assert!(check_environment(&env(&[("GST_DEBUG", "3")]))[0]
+            .fix
+            .is_none());
+    }
+
+    #[test]
+    fn test_report_prioritizes_errors() {
+        let report = DoctorReport::new(vec![
+            DoctorCheck::ok("a", "fine"),
+            DoctorCheck::problem("b", CheckStatus::Warning, "meh", "fix b"),
+            DoctorCheck::problem("c", CheckStatus::Error, "bad", "fix c"),
+            DoctorCheck::problem("d", CheckStatus::Warning, "meh", "fix b"),
+        ]);
+        assert_eq!(report.checks[0].name, "c");
+        assert_eq!(report.fixes, vec!["fix c", "fix b"]);
+        assert_eq!(report.count(CheckStatus::Warning), 2);
+    }
+}
@@ -0,0 +1,269 @@
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+use gstreamer_pbutils as gst_pbutils;
+use serde::{Deserialize, Serialize};
+
+/// Fields of the element messages clients commonly act on, decoded from
+/// their structures
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ElementMessage {
+    /// Audio levels from `level`, in dB per channel
+    Level {
+        running_time_ns: Option<u64>,
+        rms_db: Vec<f64>,
+        peak_db: Vec<f64>,
+        decay_db: Vec<f64>,
+    },
+    /// Magnitudes from `spectrum`, in dB per band, of the first channel
+    /// when the element posts one per channel
+    Spectrum {
+        running_time_ns: Option<u64>,
+        magnitude_db: Vec<f64>,
+    },
+    /// `multifilesink` finished a file
+    FileWritten {
+        filename: String,
+        index: Option<i64>,
+        running_time_ns: Option<u64>,
+        duration_ns: Option<u64>,
+    },
+    /// `splitmuxsink` started a fragment
+    FragmentOpened {
+        location: String,
+        running_time_ns: Option<u64>,
+    },
+    /// `splitmuxsink` finished a fragment
+    FragmentClosed {
+        location: String,
+        running_time_ns: Option<u64>,
+        duration_ns: Option<u64>,
+    },
+    /// Progress from `progressreport`
+    Progress {
+        percent: Option<f64>,
+        current: Option<i64>,
+        total: Option<i64>,
+        format: Option<String>,
+    },
+    /// A plugin needed by the pipeline is not installed
+    MissingPlugin {
+        plugin_type: Option<String>,
+        description: String,
+        installer_detail: String,
+    },
+}
+
+impl ElementMessage {
+    /// One line description, used as the text of the bus message
+    pub fn summary(&self) -> String {
+        match self {
+            ElementMessage::Level {
+                rms_db, peak_db, ..
+            } => format!(
+                "Level: rms {} dB, peak {} dB",
+                format_db(rms_db),
+                format_db(peak_db)
+            ),
+            ElementMessage::Spectrum { magnitude_db, .. } => {
+                let loudest = magnitude_db
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1.total_cmp(b.1));
+                match loudest {
+                    Some((band, db)) => format!(
+                        "Spectrum: {} bands, loudest band {} at {:.1} dB",
+                        magnitude_db.len(),
+                        band,
+                        db
+                    ),
+                    None => "Spectrum: no bands".to_string(),
+                }
+            }
+            ElementMessage::FileWritten { filename, .. } => format!("File written: {}", filename),
+            ElementMessage::FragmentOpened { location, .. } => {
+                format!("Fragment opened: {}", location)
+            }
+            ElementMessage::FragmentClosed { location, .. } => {
+                format!("Fragment closed: {}", location)
+            }
+            ElementMessage::Progress {
+                percent,
+                current,
+                total,
+                format,
+            } => {
+                let mut text = match percent {
+                    Some(percent) => format!("Progress: {:.1}%", percent),
+                    None => "Progress".to_string(),
+                };
+                if let (Some(current), Some(total)) = (current, total) {
+                    text.push_str(&format!(" ({} of {}", current, total));
+                    if let Some(format) = format {
+                        text.push_str(&format!(" {}", format));
+                    }
+                    text.push(')');
+                }
+                text
+            }
+            ElementMessage::MissingPlugin { description, .. } => {
+                format!("Missing plugin: {}", description)
+            }
+        }
+    }
+}
+
+fn format_db(values: &[f64]) -> String {
+    values
+        .iter()
+        .map(|db| format!("{:.1}", db))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Decode the element message `msg` if its structure is a well-known one
+pub fn decode_element_message(msg: &gst::MessageRef) -> Option<ElementMessage> {
+    if gst_pbutils::MissingPluginMessage::is(msg) {
+        let missing = gst_pbutils::MissingPluginMessage::parse(msg).ok()?;
+        return Some(ElementMessage::MissingPlugin {
+            plugin_type: msg.structure().and_then(|s| s.get::<String>("type").ok()),
+            description: missing.description().to_string(),
+            installer_detail: missing.installer_detail().to_string(),
+        });
+    }
+    decode_structure(msg.structure()?)
+}
+
+/// Text of the element message `msg`: the summary of `details` when it was
+/// decoded, otherwise the name of its structure
+pub fn element_message_text(msg: &gst::MessageRef, details: Option<&ElementMessage>) -> String {
+    match details {
+        Some(details) => details.summary(),
+        None => match msg.structure() {
+            Some(s) => format!("Element message: {}", s.name()),
+            None => "Element message".to_string(),
+        },
+    }
+}
+
+fn decode_structure(s: &gst::StructureRef) -> Option<ElementMessage> {
+    let running_time_ns = u64_field(s, "running-time");
+    match s.name().as_str() {
+        "level" => Some(ElementMessage::Level {
+            running_time_ns,
+            rms_db: f64_values(s, "rms"),
+            peak_db: f64_values(s, "peak"),
+            decay_db: f64_values(s, "decay"),
+        }),
+        "spectrum" => Some(ElementMessage::Spectrum {
+            running_time_ns,
+            magnitude_db: f64_values(s, "magnitude"),
+        }),
+        "GstMultiFileSink" => Some(ElementMessage::FileWritten {
+            filename: s.get::<String>("filename").ok()?,
+            index: s.get::<i32>("index").ok().map(i64::from),
+            running_time_ns,
+            duration_ns: u64_field(s, "duration"),
+        }),
+        "splitmuxsink-fragment-opened" => Some(ElementMessage::FragmentOpened {
+            location: s.get::<String>("location").ok()?,
+            running_time_ns,
+        }),
+        "splitmuxsink-fragment-closed" => Some(ElementMessage::FragmentClosed {
+            location: s.get::<String>("location").ok()?,
+            running_time_ns,
+            duration_ns: u64_field(s, "fragment-duration"),
+        }),
+        "progress" => Some(ElementMessage::Progress {
+            percent: s
+                .get::<f64>("percent-double")
+                .ok()
+                .or_else(|| s.get::<i32>("percent").ok().map(f64::from)),
+            current: i64_field(s, "current"),
+            total: i64_field(s, "total"),
+            format: s.get::<String>("format").ok(),
+        }),
+        _ => None,
+    }
+}
+
+fn u64_field(s: &gst::StructureRef, name: &str) -> Option<u64> {
+    s.get::<u64>(name)
+        .ok()
+        .or_else(|| s.get::<gst::ClockTime>(name).ok().map(|t| t.nseconds()))
+}
+
+fn i64_field(s: &gst::StructureRef, name: &str) -> Option<i64> {
+    s.get::<i64>(name)
+        .ok()
+        .or_else(|| s.get::<i32>(name).ok().map(i64::from))
+}
+
+fn value_to_f64(value: &glib::Value) -> Option<f64> {
+    value
+        .get::<f64>()
+        .ok()
+        .or_else(|| value.get::<f32>().ok().map(f64::from))
+}
+
+/// Numbers of a list field; `level` posts a GValueArray, `spectrum` a list,
+/// or a list of lists with `multi-channel`
+fn f64_values(s: &gst::StructureRef, name: &str) -> Vec<f64> {
+    let Ok(value) = s.value(name) else {
+        return Vec::new();
+    };
+    let values: Vec<glib::Value> = if let Ok(array) = value.get::<glib::ValueArray>() {
+        array.iter().cloned().collect()
+    } else if let Ok(list) = value.get::<gst::List>() {
+        list.iter().map(|v| v.to_value()).collect()
+    } else if let Ok(array) = value.get::<gst::Array>() {
+        array.iter().map(|v| v.to_value()).collect()
+    } else {
+        return Vec::new();
+    };
+    if let Some(first) = values.first() {
+        if let Ok(channel) = first.get::<gst::Array>() {
+            return channel.iter().filter_map(|v| value_to_f64(v)).collect();
+        }
+    }
+    values.iter().filter_map(value_to_f64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_element_message_summary() {
+        let level = ElementMessage::Level {
+            running_time_ns: Some(1_000_000_000),
+            rms_db: vec![-20.0, -21.04],
+            peak_db: vec![-3.0, -3.5],
+            decay_db: vec![-3.0, -3.5],
+        };
+        assert_eq!(
+            level.summary(),
+            "Level: rms -20.0/-21.0 dB, peak -3.0/-3.5 dB"
+        );
+        let json = serde_json::to_value(&level).unwrap();
+        assert_eq!(json["kind"], "level");
+
+        let spectrum = ElementMessage::Spectrum {
+            running_time_ns: None,
+            magnitude_db: vec![-60.0, -12.5, -40.0],
+        };
+        assert_eq!(
+            spectrum.summary(),
+            "Spectrum: 3 bands, loudest band 1 at -12.5 dB"
+        );
+
+        let progress = ElementMessage::Progress {
+            percent: Some(42.0),
+            current: Some(21),
+            total: Some(50),
+            format: Some("seconds".to_string()),
+        };
+        assert_eq!(progress.summary(), "Progress: 42.0% (21 of 50 seconds)");
+    }
+}
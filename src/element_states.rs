@@ -0,0 +1,195 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::properties::changed_properties;
+
+/// Flow state of one pad of an element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PadState {
+    pub name: String,
+    /// `src` or `sink`
+    pub direction: String,
+    /// `element.pad` this pad is linked to
+    pub peer: Option<String>,
+    /// Data is refused, as in elements below PAUSED or during a flushing seek
+    pub flushing: bool,
+    /// Held by a blocking probe
+    pub blocked: bool,
+    /// EOS went through the pad
+    pub eos: bool,
+    pub caps: Option<String>,
+}
+
+/// State of one element of a pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElementState {
+    pub name: String,
+    pub factory: Option<String>,
+    /// Bin the element is in; the pipeline for top-level elements
+    pub parent: Option<String>,
+    pub current: String,
+    /// State the element is still changing to, if any
+    pub pending: Option<String>,
+    /// Result of the last state change: `success`, `async`, `no_preroll` or
+    /// `failure`
+    pub state_return: String,
+    pub pads: Vec<PadState>,
+}
+
+impl ElementState {
+    fn is_changing(&self) -> bool {
+        self.pending.is_some() || self.state_return == "async"
+    }
+}
+
+fn state_name(state: gst::State) -> String {
+    format!("{:?}", state)
+}
+
+fn pad_state(pad: &gst::Pad) -> PadState {
+    let flags = pad.pad_flags();
+    PadState {
+        name: pad.name().to_string(),
+        direction: match pad.direction() {
+            gst::PadDirection::Src => "src",
+            gst::PadDirection::Sink => "sink",
+            _ => "unknown",
+        }
+        .to_string(),
+        peer: pad.peer().map(|peer| {
+            let element = peer
+                .parent_element()
+                .map(|e| e.name().to_string())
+                .unwrap_or_default();
+            format!("{}.{}", element, peer.name())
+        }),
+        flushing: flags.contains(gst::PadFlags::FLUSHING),
+        blocked: flags.contains(gst::PadFlags::BLOCKED),
+        eos: flags.contains(gst::PadFlags::EOS),
+        caps: pad.current_caps().map(|caps| caps.to_string()),
+    }
+}
+
+fn element_state(element: &gst::Element) -> ElementState {
+    let (result, current, pending) = element.state(gst::ClockTime::ZERO);
+    let state_return = match result {
+        Ok(gst::StateChangeSuccess::Success) => "success",
+        Ok(gst::StateChangeSuccess::Async) => "async",
+        Ok(gst::StateChangeSuccess::NoPreroll) => "no_preroll",
+        Err(gst::StateChangeError) => "failure",
+    };
+    ElementState {
+        name: element.name().to_string(),
+        factory: element.factory().map(|f| f.name().to_string()),
+        parent: element.parent().map(|p| p.name().to_string()),
+        current: state_name(current),
+        pending: (pending != gst::State::VoidPending).then(|| state_name(pending)),
+        state_return: state_return.to_string(),
+        pads: element.pads().iter().map(pad_state).collect(),
+    }
+}
+
+/// A pipeline followed by every element in it, including those nested in
+/// bins, in launch order
+fn pipeline_elements(pipeline: &gst::Pipeline) -> Vec<gst::Element> {
+    let mut elements: Vec<gst::Element> = pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .collect();
+    // Bins iterate the most recently added elements first
+    elements.reverse();
+    elements.insert(0, pipeline.clone().upcast());
+    elements
+}
+
+/// State of a pipeline followed by every element in it, including those
+/// nested in bins, in launch order. Reading the states does not wait for
+/// changes in progress.
+pub fn element_states(pipeline: &gst::Pipeline) -> Vec<ElementState> {
+    pipeline_elements(pipeline)
+        .iter()
+        .map(element_state)
+        .collect()
+}
+
+/// State of an element with the properties it was configured with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ElementDetails {
+    #[serde(flatten)]
+    pub state: ElementState,
+    /// Settable properties whose value differs from their default
+    pub properties: BTreeMap<String, String>,
+}
+
+/// [`element_states`] with the properties each element was configured with
+pub fn element_details(pipeline: &gst::Pipeline) -> Vec<ElementDetails> {
+    pipeline_elements(pipeline)
+        .iter()
+        .map(|element| ElementDetails {
+            state: element_state(element),
+            properties: changed_properties(element.upcast_ref()),
+        })
+        .collect()
+}
+
+/// Elements holding up a pipeline: those still changing state that contain
+/// no element that is also changing (a bin waits on its children), and
+/// those with blocked pads
+pub fn stuck_elements(states: &[ElementState]) -> Vec<String> {
+    states
+        .iter()
+        .filter(|state| {
+            let waits_on_child = states.iter().any(|child| {
+                child.parent.as_deref() == Some(state.name.as_str()) && child.is_changing()
+            });
+            (state.is_changing() && !waits_on_child) || state.pads.iter().any(|p| p.blocked)
+        })
+        .map(|state| state.name.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn element(name: &str, parent: &str, pending: Option<&str>, blocked: bool) -> ElementState {
+        ElementState {
+            name: name.to_string(),
+            factory: None,
+            parent: Some(parent.to_string()),
+            current: "Paused".to_string(),
+            pending: pending.map(|p| p.to_string()),
+            state_return: if pending.is_some() {
+                "async"
+            } else {
+                "success"
+            }
+            .to_string(),
+            pads: vec![PadState {
+                name: "src".to_string(),
+                direction: "src".to_string(),
+                peer: None,
+                flushing: false,
+                blocked,
+                eos: false,
+                caps: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_stuck_elements() {
+        let states = vec![
+            element("src", "pipeline0", None, false),
+            element("decode", "pipeline0", Some("Playing"), false),
+            element("decoder", "decode", Some("Playing"), false),
+            element("valve", "pipeline0", None, true),
+            element("sink", "pipeline0", None, false),
+        ];
+        assert_eq!(stuck_elements(&states), vec!["decoder", "valve"]);
+        assert!(stuck_elements(&[element("sink", "pipeline0", None, false)]).is_empty());
+    }
+}
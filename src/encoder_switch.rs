@@ -0,0 +1,311 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_video::UpstreamForceKeyUnitEvent;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::pipeline::make_element;
+
+/// Outcome of migrating a live stream to another encoder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncoderSwitch {
+    pub old_encoder: String,
+    /// Name of the bin holding the replacement encoder
+    pub new_encoder: String,
+    /// Timestamp of the first buffer output by the new encoder
+    pub switch_pts_ns: Option<u64>,
+    /// Running time of the pipeline when the new encoder took over
+    pub switch_running_time_ns: Option<u64>,
+    /// Time from the request to the switch, warm-up included
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// Both encoders run, only the old one's output goes downstream
+    WarmingUp,
+    /// The next keyframe of the new encoder takes over
+    Armed,
+    Switched,
+    Abandoned,
+}
+
+type SwitchPoint = (Option<u64>, Option<u64>);
+
+fn static_pad(element: &impl IsA<gst::Element>, name: &str) -> McpResult<gst::Pad> {
+    element.static_pad(name).ok_or_else(|| {
+        GStreamerMcpError::PipelineError(format!(
+            "{} has no '{}' pad",
+            element.as_ref().name(),
+            name
+        ))
+    })
+}
+
+fn switch_error(e: impl std::fmt::Display) -> GStreamerMcpError {
+    GStreamerMcpError::PipelineError(format!("Encoder switch failed: {}", e))
+}
+
+/// Stop and remove elements that are no longer linked to the stream
+fn discard(pipeline: &gst::Pipeline, elements: &[gst::Element]) {
+    for element in elements {
+        let _ = element.set_state(gst::State::Null);
+        let _ = pipeline.remove(element);
+    }
+}
+
+/// Detach a tee branch once no buffer is passing through its pad, then
+/// remove its elements off the streaming thread
+fn remove_branch(
+    pipeline: &gst::Pipeline,
+    tee: &gst::Element,
+    pad: &gst::Pad,
+    elements: Vec<gst::Element>,
+) {
+    let pipeline = pipeline.clone();
+    let tee = tee.clone();
+    pad.add_probe(gst::PadProbeType::IDLE, move |pad, _| {
+        let tee = tee.clone();
+        let pad = pad.clone();
+        let elements = elements.clone();
+        pipeline.call_async(move |pipeline| {
+            tee.release_request_pad(&pad);
+            discard(pipeline, &elements);
+        });
+        gst::PadProbeReturn::Remove
+    });
+}
+
+/// Migrate the output of `encoder` to a new encoder built from
+/// `replacement` without interrupting the stream. A tee feeds both encoders
+/// for `warmup`, then downstream is relinked to the new encoder on its
+/// first keyframe and the old encoder removed. Downstream must accept the
+/// new encoder's caps mid-stream, e.g. a parser and a streaming muxer.
+pub async fn switch_encoder(
+    pipeline: &gst::Pipeline,
+    encoder: &str,
+    replacement: &str,
+    warmup: Duration,
+    timeout: Duration,
+) -> McpResult<EncoderSwitch> {
+    let started = Instant::now();
+    let old = pipeline.by_name(encoder).ok_or_else(|| {
+        GStreamerMcpError::ElementNotFound(format!("No element named '{}' in pipeline", encoder))
+    })?;
+    let old_sink = static_pad(&old, "sink")?;
+    let old_src = static_pad(&old, "src")?;
+    let (Some(upstream), Some(downstream)) = (old_sink.peer(), old_src.peer()) else {
+        return Err(GStreamerMcpError::PipelineError(format!(
+            "'{}' must be linked on both sides to be switched",
+            encoder
+        )));
+    };
+
+    let new_name = (0..)
+        .map(|i| format!("{}_next{}", encoder, i))
+        .find(|name| pipeline.by_name(name).is_none())
+        .expect("unbounded range");
+    let new_encoder = gst::parse::bin_from_description(replacement, true)
+        .map_err(|e| switch_error(format!("invalid replacement encoder: {}", e)))?;
+    new_encoder.set_property("name", &new_name);
+    let new_src = static_pad(&new_encoder, "src")?;
+    let tee = make_element("tee", format!("{}_tee", new_name))?;
+    let old_queue = make_element("queue", format!("{}_queue_old", new_name))?;
+    let new_queue = make_element("queue", format!("{}_queue", new_name))?;
+    let added = vec![
+        tee.clone(),
+        old_queue.clone(),
+        new_queue.clone(),
+        new_encoder.clone().upcast(),
+    ];
+
+    // Everything but the splice into the running stream is prepared idle
+    pipeline.add_many(&added).map_err(switch_error)?;
+    let tee_old = tee.request_pad_simple("src_%u");
+    let tee_new = tee.request_pad_simple("src_%u");
+    let prepared = (|| -> McpResult<(gst::Pad, gst::Pad)> {
+        let (Some(tee_old), Some(tee_new)) = (tee_old, tee_new) else {
+            return Err(switch_error("tee refused a new src pad"));
+        };
+        tee_old
+            .link(&static_pad(&old_queue, "sink")?)
+            .map_err(switch_error)?;
+        tee_new
+            .link(&static_pad(&new_queue, "sink")?)
+            .map_err(switch_error)?;
+        new_queue.link(&new_encoder).map_err(switch_error)?;
+        for element in &added {
+            element.sync_state_with_parent().map_err(switch_error)?;
+        }
+        Ok((tee_old, tee_new))
+    })();
+    let (tee_old, tee_new) = match prepared {
+        Ok(pads) => pads,
+        Err(e) => {
+            discard(pipeline, &added);
+            return Err(e);
+        }
+    };
+
+    let phase = Arc::new(Mutex::new(Phase::WarmingUp));
+
+    // The old encoder's output stops the instant the new one takes over
+    {
+        let phase = phase.clone();
+        old_src.add_probe(
+            gst::PadProbeType::BUFFER
+                | gst::PadProbeType::BUFFER_LIST
+                | gst::PadProbeType::EVENT_DOWNSTREAM,
+            move |_pad, _info| {
+                if *phase.lock() == Phase::Switched {
+                    gst::PadProbeReturn::Drop
+                } else {
+                    gst::PadProbeReturn::Ok
+                }
+            },
+        );
+    }
+
+    // New encoder output is discarded until its first keyframe once armed,
+    // which relinks downstream to it and goes on
+    let (switch_tx, mut switch_rx) = tokio::sync::oneshot::channel::<Result<SwitchPoint, String>>();
+    {
+        let switch_tx = Mutex::new(Some(switch_tx));
+        let phase = phase.clone();
+        let old_src = old_src.clone();
+        let downstream = downstream.clone();
+        let pipeline = pipeline.downgrade();
+        new_src.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+            let mut phase = phase.lock();
+            let keyframe = info
+                .buffer()
+                .is_some_and(|b| !b.flags().contains(gst::BufferFlags::DELTA_UNIT));
+            if *phase != Phase::Armed || !keyframe {
+                return gst::PadProbeReturn::Drop;
+            }
+            let _ = old_src.unlink(&downstream);
+            let result = match pad.link(&downstream) {
+                Ok(_) => {
+                    *phase = Phase::Switched;
+                    Ok((
+                        info.buffer().and_then(|b| b.pts()).map(|t| t.nseconds()),
+                        pipeline
+                            .upgrade()
+                            .and_then(|p| p.current_running_time())
+                            .map(|t| t.nseconds()),
+                    ))
+                }
+                Err(e) => {
+                    let _ = old_src.link(&downstream);
+                    *phase = Phase::Abandoned;
+                    Err(format!("downstream refused the new encoder: {}", e))
+                }
+            };
+            let switched = result.is_ok();
+            if let Some(tx) = switch_tx.lock().take() {
+                let _ = tx.send(result);
+            }
+            if switched {
+                gst::PadProbeReturn::Remove
+            } else {
+                gst::PadProbeReturn::Drop
+            }
+        });
+    }
+
+    // Splice the tee in between upstream and the old encoder
+    let (splice_tx, splice_rx) = tokio::sync::oneshot::channel::<Result<(), String>>();
+    {
+        let splice_tx = Mutex::new(Some(splice_tx));
+        let phase = phase.clone();
+        let old_sink = old_sink.clone();
+        let tee_sink = static_pad(&tee, "sink")?;
+        let old_queue_src = static_pad(&old_queue, "src")?;
+        upstream.add_probe(gst::PadProbeType::IDLE, move |pad, _| {
+            let phase = phase.lock();
+            if *phase == Phase::Abandoned {
+                return gst::PadProbeReturn::Remove;
+            }
+            let result = (|| -> Result<(), String> {
+                pad.unlink(&old_sink).map_err(|e| e.to_string())?;
+                pad.link(&tee_sink).map_err(|e| e.to_string())?;
+                old_queue_src.link(&old_sink).map_err(|e| e.to_string())?;
+                Ok(())
+            })();
+            if result.is_err() {
+                // Put the original link back
+                let _ = pad.unlink(&tee_sink);
+                let _ = old_queue_src.unlink(&old_sink);
+                let _ = pad.link(&old_sink);
+            }
+            if let Some(tx) = splice_tx.lock().take() {
+                let _ = tx.send(result);
+            }
+            gst::PadProbeReturn::Remove
+        });
+    }
+
+    let abandon = |reason: String| {
+        *phase.lock() = Phase::Abandoned;
+        switch_error(reason)
+    };
+    match tokio::time::timeout(timeout, splice_rx).await {
+        Ok(Ok(Ok(()))) => {}
+        result => {
+            let error = match result {
+                Ok(Ok(Err(e))) => abandon(e),
+                _ => abandon("the stream into the encoder never went idle".to_string()),
+            };
+            discard(pipeline, &added);
+            return Err(error);
+        }
+    }
+
+    tokio::time::sleep(warmup).await;
+    *phase.lock() = Phase::Armed;
+    // Ask for a keyframe rather than waiting for the end of the GOP
+    new_src.send_event(
+        UpstreamForceKeyUnitEvent::builder()
+            .all_headers(true)
+            .build(),
+    );
+
+    let result = match tokio::time::timeout(timeout, &mut switch_rx).await {
+        Ok(Ok(result)) => result,
+        _ => {
+            let mut phase = phase.lock();
+            if *phase == Phase::Switched {
+                // Switched while timing out
+                switch_rx.try_recv().unwrap_or(Ok((None, None)))
+            } else {
+                *phase = Phase::Abandoned;
+                Err("the new encoder produced no keyframe in time".to_string())
+            }
+        }
+    };
+
+    match result {
+        Ok((switch_pts_ns, switch_running_time_ns)) => {
+            remove_branch(pipeline, &tee, &tee_old, vec![old_queue, old]);
+            Ok(EncoderSwitch {
+                old_encoder: encoder.to_string(),
+                new_encoder: new_name,
+                switch_pts_ns,
+                switch_running_time_ns,
+                elapsed_ms: started.elapsed().as_millis() as u64,
+            })
+        }
+        Err(e) => {
+            remove_branch(
+                pipeline,
+                &tee,
+                &tee_new,
+                vec![new_queue, new_encoder.upcast()],
+            );
+            Err(switch_error(e))
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_pbutils as gst_pbutils;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::discovery::ensure_gstreamer_initialized;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// Containers by short name: caps and file extension
+const CONTAINERS: &[(&str, &str, &str)] = &[
+    ("mp4", "video/quicktime,variant=iso", "mp4"),
+    ("mov", "video/quicktime", "mov"),
+    ("mkv", "video/x-matroska", "mkv"),
+    ("matroska", "video/x-matroska", "mkv"),
+    ("webm", "video/webm", "webm"),
+    ("ogg", "application/ogg", "ogg"),
+    ("ts", "video/mpegts,systemstream=true", "ts"),
+    ("mpegts", "video/mpegts,systemstream=true", "ts"),
+    ("flv", "video/x-flv", "flv"),
+];
+
+/// Video codecs by short name: caps
+const VIDEO_CODECS: &[(&str, &str)] = &[
+    ("h264", "video/x-h264"),
+    ("h265", "video/x-h265"),
+    ("hevc", "video/x-h265"),
+    ("vp8", "video/x-vp8"),
+    ("vp9", "video/x-vp9"),
+    ("av1", "video/x-av1"),
+    ("mpeg2", "video/mpeg,mpegversion=2"),
+    ("mpeg4", "video/mpeg,mpegversion=4"),
+    ("theora", "video/x-theora"),
+];
+
+/// Audio codecs by short name: caps and the extension of a bare stream
+const AUDIO_CODECS: &[(&str, &str, &str)] = &[
+    ("aac", "audio/mpeg,mpegversion=4", "aac"),
+    ("mp3", "audio/mpeg,mpegversion=1,layer=3", "mp3"),
+    ("opus", "audio/x-opus", "opus"),
+    ("vorbis", "audio/x-vorbis", "ogg"),
+    ("flac", "audio/x-flac", "flac"),
+    ("ac3", "audio/x-ac3", "ac3"),
+];
+
+/// High-level description of an encoding profile. Codecs and containers
+/// are short names (h264, aac, mp4, ...) or caps strings.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct EncodingProfileSpec {
+    #[schemars(
+        description = "Container: mp4, mov, mkv, webm, ogg, ts, flv, or caps. Omit for a bare stream of a single codec"
+    )]
+    pub container: Option<String>,
+    #[schemars(
+        description = "Video codec: h264, h265, vp8, vp9, av1, mpeg2, mpeg4, theora, or caps"
+    )]
+    pub video_codec: Option<String>,
+    #[schemars(description = "Codec profile (e.g., 'high', 'main', 'baseline')")]
+    pub video_profile: Option<String>,
+    #[schemars(description = "Output width in pixels")]
+    pub width: Option<u32>,
+    #[schemars(description = "Output height in pixels")]
+    pub height: Option<u32>,
+    #[schemars(
+        description = "Output frame rate as a fraction or integer (e.g., '30000/1001', '25')"
+    )]
+    pub framerate: Option<String>,
+    #[schemars(description = "Audio codec: aac, mp3, opus, vorbis, flac, ac3, or caps")]
+    pub audio_codec: Option<String>,
+    #[schemars(description = "Output audio channels")]
+    pub channels: Option<u32>,
+    #[schemars(description = "Output audio sample rate in Hz")]
+    pub sample_rate: Option<u32>,
+}
+
+/// A profile ready for encodebin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncodingProfileResult {
+    /// Serialized profile, the value of encodebin's `profile` property
+    pub profile: String,
+    pub file_extension: Option<String>,
+    /// Example launch description writing the encoded output to a file
+    pub launch_example: String,
+    /// Parts of the profile no installed element can produce
+    pub warnings: Vec<String>,
+}
+
+fn lookup<'a>(table: &'a [(&str, &'a str)], name: &'a str) -> &'a str {
+    table
+        .iter()
+        .find(|(short, _)| short.eq_ignore_ascii_case(name.trim()))
+        .map_or(name.trim(), |(_, caps)| caps)
+}
+
+fn check_known(kind: &str, name: &str, known: &[&str]) -> McpResult<()> {
+    if name.contains('/') || known.iter().any(|k| k.eq_ignore_ascii_case(name.trim())) {
+        Ok(())
+    } else {
+        Err(GStreamerMcpError::PipelineError(format!(
+            "Unknown {} '{}': use one of {} or a caps string",
+            kind,
+            name,
+            known.join(", ")
+        )))
+    }
+}
+
+fn parse_framerate(framerate: &str) -> McpResult<String> {
+    let invalid = || {
+        GStreamerMcpError::PipelineError(format!(
+            "Invalid framerate '{}': use a fraction such as 30000/1001 or an integer",
+            framerate
+        ))
+    };
+    let (num, denom) = framerate
+        .trim()
+        .split_once('/')
+        .unwrap_or((framerate.trim(), "1"));
+    let num: u32 = num.trim().parse().map_err(|_| invalid())?;
+    let denom: u32 = denom.trim().parse().map_err(|_| invalid())?;
+    if num == 0 || denom == 0 {
+        return Err(invalid());
+    }
+    Ok(format!("{}/{}", num, denom))
+}
+
+/// Caps of the video stream and its restriction caps
+fn video_caps(spec: &EncodingProfileSpec) -> McpResult<Option<(String, Option<String>)>> {
+    let Some(codec) = &spec.video_codec else {
+        return Ok(None);
+    };
+    let known: Vec<&str> = VIDEO_CODECS.iter().map(|(name, _)| *name).collect();
+    check_known("video codec", codec, &known)?;
+    let mut caps = lookup(VIDEO_CODECS, codec).to_string();
+    if let Some(profile) = &spec.video_profile {
+        caps.push_str(&format!(",profile={}", profile));
+    }
+
+    let mut restriction = Vec::new();
+    if let Some(width) = spec.width {
+        restriction.push(format!("width={}", width));
+    }
+    if let Some(height) = spec.height {
+        restriction.push(format!("height={}", height));
+    }
+    if let Some(framerate) = &spec.framerate {
+        restriction.push(format!("framerate={}", parse_framerate(framerate)?));
+    }
+    let restriction =
+        (!restriction.is_empty()).then(|| format!("video/x-raw,{}", restriction.join(",")));
+    Ok(Some((caps, restriction)))
+}
+
+/// Caps of the audio stream and its restriction caps
+fn audio_caps(spec: &EncodingProfileSpec) -> McpResult<Option<(String, Option<String>)>> {
+    let Some(codec) = &spec.audio_codec else {
+        return Ok(None);
+    };
+    let table: Vec<(&str, &str)> = AUDIO_CODECS
+        .iter()
+        .map(|(name, caps, _)| (*name, *caps))
+        .collect();
+    let known: Vec<&str> = table.iter().map(|(name, _)| *name).collect();
+    check_known("audio codec", codec, &known)?;
+    let caps = lookup(&table, codec).to_string();
+
+    let mut restriction = Vec::new();
+    if let Some(channels) = spec.channels {
+        restriction.push(format!("channels={}", channels));
+    }
+    if let Some(rate) = spec.sample_rate {
+        restriction.push(format!("rate={}", rate));
+    }
+    let restriction =
+        (!restriction.is_empty()).then(|| format!("audio/x-raw,{}", restriction.join(",")));
+    Ok(Some((caps, restriction)))
+}
+
+fn container_caps(spec: &EncodingProfileSpec) -> McpResult<Option<(String, Option<String>)>> {
+    let Some(container) = &spec.container else {
+        return Ok(None);
+    };
+    let known: Vec<&str> = CONTAINERS.iter().map(|(name, _, _)| *name).collect();
+    check_known("container", container, &known)?;
+    Ok(Some(
+        CONTAINERS
+            .iter()
+            .find(|(name, _, _)| name.eq_ignore_ascii_case(container.trim()))
+            .map_or((container.trim().to_string(), None), |(_, caps, ext)| {
+                (caps.to_string(), Some(ext.to_string()))
+            }),
+    ))
+}
+
+fn stream_string((caps, restriction): &(String, Option<String>)) -> String {
+    match restriction {
+        Some(restriction) => format!("{}->{}", restriction, caps),
+        None => caps.clone(),
+    }
+}
+
+/// Serialize a profile in the format encodebin's `profile` property
+/// accepts: `container:video:audio`, each stream optionally prefixed by its
+/// restriction caps and `->`. Returns the profile and a file extension.
+pub fn profile_string(spec: &EncodingProfileSpec) -> McpResult<(String, Option<String>)> {
+    let video = video_caps(spec)?;
+    let audio = audio_caps(spec)?;
+    let streams: Vec<String> = video
+        .iter()
+        .chain(audio.iter())
+        .map(stream_string)
+        .collect();
+    if streams.is_empty() {
+        return Err(GStreamerMcpError::PipelineError(
+            "An encoding profile needs a video or an audio codec".to_string(),
+        ));
+    }
+
+    match container_caps(spec)? {
+        Some((caps, ext)) => Ok((format!("{}:{}", caps, streams.join(":")), ext)),
+        None if streams.len() == 1 => {
+            let ext = spec.audio_codec.as_ref().and_then(|codec| {
+                AUDIO_CODECS
+                    .iter()
+                    .find(|(name, _, _)| name.eq_ignore_ascii_case(codec.trim()))
+                    .map(|(_, _, ext)| ext.to_string())
+            });
+            Ok((streams.concat(), ext))
+        }
+        None => Err(GStreamerMcpError::PipelineError(
+            "Video and audio together need a container".to_string(),
+        )),
+    }
+}
+
+fn parse_caps(caps: &str) -> McpResult<gst::Caps> {
+    gst::Caps::from_str(caps)
+        .map_err(|_| GStreamerMcpError::PipelineError(format!("Invalid caps '{}'", caps)))
+}
+
+fn factories_producing(kind: gst::ElementFactoryType, caps: &gst::Caps) -> Vec<String> {
+    gst::ElementFactory::factories_with_type(kind, gst::Rank::MARGINAL)
+        .iter()
+        .filter(|f| f.can_src_any_caps(caps))
+        .map(|f| f.name().to_string())
+        .collect()
+}
+
+/// Check that encoders exist for a stream, returning its parsed caps
+fn check_stream(
+    (caps, restriction): &(String, Option<String>),
+    warnings: &mut Vec<String>,
+) -> McpResult<gst::Caps> {
+    let format = parse_caps(caps)?;
+    if let Some(restriction) = restriction {
+        parse_caps(restriction)?;
+    }
+    if factories_producing(gst::ElementFactoryType::ENCODER, &format).is_empty() {
+        warnings.push(format!("No installed encoder produces {}", caps));
+    }
+    Ok(format)
+}
+
+/// Build an encoding profile from `spec`. The serialized profile is parsed
+/// into a GstEncodingProfile the way gst-launch does for encodebin, and
+/// installed encoders and muxers are checked to be able to produce it.
+pub fn build_encoding_profile(spec: &EncodingProfileSpec) -> McpResult<EncodingProfileResult> {
+    ensure_gstreamer_initialized()?;
+    let (profile, file_extension) = profile_string(spec)?;
+    gst::glib::Value::deserialize(&profile, gst_pbutils::EncodingProfile::static_type()).map_err(
+        |_| {
+            GStreamerMcpError::PipelineError(format!(
+                "GStreamer cannot parse the encoding profile '{}'",
+                profile
+            ))
+        },
+    )?;
+
+    let mut warnings = Vec::new();
+    let mut streams = Vec::new();
+    for stream in video_caps(spec)?.iter().chain(audio_caps(spec)?.iter()) {
+        streams.push(check_stream(stream, &mut warnings)?);
+    }
+
+    if let Some((caps, _)) = container_caps(spec)? {
+        let format = parse_caps(&caps)?;
+        let muxers: Vec<gst::ElementFactory> = gst::ElementFactory::factories_with_type(
+            gst::ElementFactoryType::MUXER,
+            gst::Rank::MARGINAL,
+        )
+        .into_iter()
+        .filter(|f| f.can_src_any_caps(&format))
+        .collect();
+        if muxers.is_empty() {
+            warnings.push(format!("No installed muxer produces {}", caps));
+        } else {
+            for stream in &streams {
+                if !muxers.iter().any(|m| m.can_sink_any_caps(stream)) {
+                    warnings.push(format!("{} cannot be muxed into {}", stream, caps));
+                }
+            }
+        }
+    }
+
+    let location = format!("output.{}", file_extension.as_deref().unwrap_or("bin"));
+    let launch_example = format!(
+        "<source> ! encodebin profile=\"{}\" ! filesink location={}",
+        profile, location
+    );
+    Ok(EncodingProfileResult {
+        profile,
+        file_extension,
+        launch_example,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_string() {
+        let spec = EncodingProfileSpec {
+            container: Some("mp4".to_string()),
+            video_codec: Some("h264".to_string()),
+            video_profile: Some("high".to_string()),
+            width: Some(1280),
+            height: Some(720),
+            framerate: Some("30".to_string()),
+            audio_codec: Some("aac".to_string()),
+            sample_rate: Some(48000),
+            ..Default::default()
+        };
+        let (profile, ext) = profile_string(&spec).unwrap();
+        assert_eq!(
+            profile,
+            "video/quicktime,variant=iso:\
+             video/x-raw,width=1280,height=720,framerate=30/1->video/x-h264,profile=high:\
+             audio/x-raw,rate=48000->audio/mpeg,mpegversion=4"
+        );
+        assert_eq!(ext.as_deref(), Some("mp4"));
+    }
+
+    #[test]
+    fn test_profile_string_errors() {
+        let audio_only = EncodingProfileSpec {
+            audio_codec: Some("flac".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            profile_string(&audio_only).unwrap(),
+            ("audio/x-flac".to_string(), Some("flac".to_string()))
+        );
+
+        let no_container = EncodingProfileSpec {
+            video_codec: Some("vp9".to_string()),
+            audio_codec: Some("opus".to_string()),
+            ..Default::default()
+        };
+        assert!(profile_string(&no_container).is_err());
+        assert!(profile_string(&EncodingProfileSpec::default()).is_err());
+
+        let unknown = EncodingProfileSpec {
+            video_codec: Some("h266".to_string()),
+            ..Default::default()
+        };
+        assert!(profile_string(&unknown).is_err());
+    }
+}
@@ -24,6 +24,22 @@ pub enum GStreamerMcpError {
     #[error("Pipeline error: {0}")]
     PipelineError(String),
 
+    #[error("Property inspection failed: {message}")]
+    InvalidEnumValue {
+        message: String,
+        /// Nicknames the enum or flags type accepts
+        valid_values: Vec<String>,
+        /// Closest valid nickname for each one that is not
+        closest: std::collections::BTreeMap<String, String>,
+    },
+
+    #[error("Pipeline error: {message}")]
+    UnknownElements {
+        message: String,
+        /// Closest registry matches for each element that was not found
+        suggestions: std::collections::BTreeMap<String, Vec<String>>,
+    },
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -45,14 +61,31 @@ impl From<GStreamerMcpError> for McpError {
             GStreamerMcpError::GStreamerInit(_) => -32004,
             GStreamerMcpError::RegistryError(_) => -32005,
             GStreamerMcpError::PropertyError(_) => -32006,
+            GStreamerMcpError::InvalidEnumValue { .. } => -32006,
             GStreamerMcpError::PipelineError(_) => -32007,
+            GStreamerMcpError::UnknownElements { .. } => -32007,
             _ => -32000,
         };
 
+        let data = match &err {
+            GStreamerMcpError::UnknownElements { suggestions, .. } => {
+                Some(serde_json::json!({ "suggestions": suggestions }))
+            }
+            GStreamerMcpError::InvalidEnumValue {
+                valid_values,
+                closest,
+                ..
+            } => Some(serde_json::json!({
+                "valid_values": valid_values,
+                "closest": closest,
+            })),
+            _ => None,
+        };
+
         McpError {
             code: rmcp::model::ErrorCode(code),
             message: err.to_string().into(),
-            data: None,
+            data,
         }
     }
 }
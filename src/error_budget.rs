@@ -0,0 +1,281 @@
+use rmcp::model::LoggingLevel;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::notifications::Notifier;
+use crate::pipeline::{attach_cause, BusMessage, PipelineHealth, PipelineManager};
+
+/// Window the budget limits are counted over
+const BUDGET_WINDOW: Duration = Duration::from_secs(60);
+
+/// Alert thresholds of a pipeline. Unset limits are not enforced.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+pub struct ErrorBudget {
+    #[schemars(description = "Errors allowed per minute before the pipeline is Failed")]
+    pub max_errors_per_minute: Option<u32>,
+    #[schemars(description = "Warnings allowed per minute before the pipeline is Degraded")]
+    pub max_warnings_per_minute: Option<u32>,
+    #[schemars(
+        description = "Times the pipeline may start buffering per minute before it is Degraded"
+    )]
+    pub max_buffering_events_per_minute: Option<u32>,
+}
+
+impl ErrorBudget {
+    pub fn is_empty(&self) -> bool {
+        self == &ErrorBudget::default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetMetric {
+    Errors,
+    Warnings,
+    BufferingEvents,
+}
+
+impl BudgetMetric {
+    /// Health of a pipeline over budget on this metric
+    pub fn health(self) -> PipelineHealth {
+        match self {
+            BudgetMetric::Errors => PipelineHealth::Failed,
+            BudgetMetric::Warnings | BudgetMetric::BufferingEvents => PipelineHealth::Degraded,
+        }
+    }
+}
+
+impl std::fmt::Display for BudgetMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BudgetMetric::Errors => "errors",
+            BudgetMetric::Warnings => "warnings",
+            BudgetMetric::BufferingEvents => "buffering events",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A budget limit exceeded by a pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetBreach {
+    pub pipeline_id: String,
+    pub metric: BudgetMetric,
+    /// Events in the last minute
+    pub count: u32,
+    pub limit: u32,
+    pub health: PipelineHealth,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// Budget of a pipeline and its usage over the last minute
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub budget: ErrorBudget,
+    pub errors_last_minute: u32,
+    pub warnings_last_minute: u32,
+    pub buffering_events_last_minute: u32,
+    /// Limits exceeded since the budget was set
+    pub breached: Vec<BudgetMetric>,
+}
+
+/// Counts the bus messages of a pipeline against its budget. A limit alerts
+/// once; setting the budget again re-arms it.
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    budget: ErrorBudget,
+    errors: VecDeque<Instant>,
+    warnings: VecDeque<Instant>,
+    buffering_events: VecDeque<Instant>,
+    buffering: bool,
+    breached: Vec<BudgetMetric>,
+}
+
+fn count_recent(events: &VecDeque<Instant>, now: Instant) -> u32 {
+    events
+        .iter()
+        .filter(|t| now.duration_since(**t) < BUDGET_WINDOW)
+        .count() as u32
+}
+
+impl BudgetTracker {
+    pub fn new(budget: ErrorBudget) -> Self {
+        Self {
+            budget,
+            errors: VecDeque::new(),
+            warnings: VecDeque::new(),
+            buffering_events: VecDeque::new(),
+            buffering: false,
+            breached: Vec::new(),
+        }
+    }
+
+    /// Count `message` received at `now`. Returns the metric and its count
+    /// when this message takes it over budget for the first time.
+    pub fn record(&mut self, message: &BusMessage, now: Instant) -> Option<(BudgetMetric, u32)> {
+        let (metric, events, limit) = match message.message_type.as_str() {
+            "Error" => (
+                BudgetMetric::Errors,
+                &mut self.errors,
+                self.budget.max_errors_per_minute,
+            ),
+            "Warning" => (
+                BudgetMetric::Warnings,
+                &mut self.warnings,
+                self.budget.max_warnings_per_minute,
+            ),
+            "Buffering" => {
+                // Messages report progress; an event is a drop below 100%
+                let percent = message
+                    .message
+                    .trim_start_matches("Buffering: ")
+                    .trim_end_matches('%')
+                    .parse::<u32>()
+                    .unwrap_or(100);
+                let started = percent < 100 && !self.buffering;
+                self.buffering = percent < 100;
+                if !started {
+                    return None;
+                }
+                (
+                    BudgetMetric::BufferingEvents,
+                    &mut self.buffering_events,
+                    self.budget.max_buffering_events_per_minute,
+                )
+            }
+            _ => return None,
+        };
+        events.push_back(now);
+        while events
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= BUDGET_WINDOW)
+        {
+            events.pop_front();
+        }
+        let count = events.len() as u32;
+        match limit {
+            Some(limit) if count > limit && !self.breached.contains(&metric) => {
+                self.breached.push(metric);
+                Some((metric, count))
+            }
+            _ => None,
+        }
+    }
+
+    /// Worst health of the exceeded limits
+    pub fn health(&self) -> PipelineHealth {
+        self.breached
+            .iter()
+            .map(|m| m.health())
+            .max_by_key(|h| match h {
+                PipelineHealth::Healthy => 0,
+                PipelineHealth::Degraded => 1,
+                PipelineHealth::Failed => 2,
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn limit(&self, metric: BudgetMetric) -> Option<u32> {
+        match metric {
+            BudgetMetric::Errors => self.budget.max_errors_per_minute,
+            BudgetMetric::Warnings => self.budget.max_warnings_per_minute,
+            BudgetMetric::BufferingEvents => self.budget.max_buffering_events_per_minute,
+        }
+    }
+
+    pub fn status(&self, now: Instant) -> BudgetStatus {
+        BudgetStatus {
+            budget: self.budget.clone(),
+            errors_last_minute: count_recent(&self.errors, now),
+            warnings_last_minute: count_recent(&self.warnings, now),
+            buffering_events_last_minute: count_recent(&self.buffering_events, now),
+            breached: self.breached.clone(),
+        }
+    }
+}
+
+/// Forward budget breaches of every pipeline to the client as
+/// `error_budget` notifications
+pub fn spawn_budget_alerts(pipeline_manager: &Arc<PipelineManager>, notifier: Notifier) {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<BudgetBreach>();
+    pipeline_manager.set_budget_alerts(tx);
+    let pipeline_manager = pipeline_manager.clone();
+    tokio::spawn(async move {
+        while let Some(breach) = rx.recv().await {
+            let level = match breach.health {
+                PipelineHealth::Failed => LoggingLevel::Error,
+                _ => LoggingLevel::Warning,
+            };
+            let mut data = serde_json::to_value(&breach).unwrap_or_default();
+            attach_cause(
+                &mut data,
+                pipeline_manager.cause(&breach.pipeline_id).as_ref(),
+            );
+            notifier.notify("error_budget", level, data).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(message_type: &str, message: &str) -> BusMessage {
+        BusMessage {
+            timestamp: chrono::Utc::now(),
+            message_type: message_type.to_string(),
+            message: message.to_string(),
+            source: None,
+            cause: None,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_budget_breach() {
+        let mut tracker = BudgetTracker::new(ErrorBudget {
+            max_warnings_per_minute: Some(2),
+            ..Default::default()
+        });
+        let start = Instant::now();
+        let warning = message("Warning", "late buffer");
+        assert_eq!(tracker.record(&warning, start), None);
+        assert_eq!(tracker.record(&warning, start), None);
+        assert_eq!(
+            tracker.record(&warning, start),
+            Some((BudgetMetric::Warnings, 3))
+        );
+        // Alerts once
+        assert_eq!(tracker.record(&warning, start), None);
+        assert_eq!(tracker.health(), PipelineHealth::Degraded);
+        // No limit on errors
+        assert_eq!(tracker.record(&message("Error", "boom"), start), None);
+
+        let later = start + Duration::from_secs(61);
+        assert_eq!(tracker.status(later).warnings_last_minute, 0);
+    }
+
+    #[test]
+    fn test_buffering_events() {
+        let mut tracker = BudgetTracker::new(ErrorBudget {
+            max_buffering_events_per_minute: Some(1),
+            ..Default::default()
+        });
+        let now = Instant::now();
+        for percent in ["0", "50", "100", "10", "100"] {
+            let result = tracker.record(
+                &message("Buffering", &format!("Buffering: {}%", percent)),
+                now,
+            );
+            if percent == "10" {
+                assert_eq!(result, Some((BudgetMetric::BufferingEvents, 2)));
+            } else {
+                assert_eq!(result, None);
+            }
+        }
+        assert_eq!(tracker.status(now).buffering_events_last_minute, 2);
+    }
+}
@@ -0,0 +1,282 @@
+use cpu_time::ProcessTime;
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::confirmation::FILE_WRITING_FACTORIES;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::probe_media;
+use crate::suggestions::parse_launch;
+
+/// Longest wall time a sample encode may take, whatever the sample length
+const MAX_SAMPLE_WALL: Duration = Duration::from_secs(120);
+
+/// Predicted cost of running a transcode to completion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobEstimate {
+    pub input: Option<PathBuf>,
+    pub input_duration_ns: Option<u64>,
+    /// Media time encoded by the sample
+    pub sample_media_ms: u64,
+    pub sample_wall_ms: u64,
+    /// Media seconds processed per second; above 1 is faster than real time
+    pub speed: f64,
+    /// CPU used while encoding, in percent of one core
+    pub cpu_percent: f64,
+    pub cpu_cores: usize,
+    pub sample_output_bytes: Option<u64>,
+    pub output_bitrate_kbps: Option<f64>,
+    pub estimated_duration_ms: Option<u64>,
+    pub estimated_output_bytes: Option<u64>,
+}
+
+/// What a sample run measured
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleMeasurement {
+    pub media: Duration,
+    pub wall: Duration,
+    pub cpu: Duration,
+    pub output_bytes: Option<u64>,
+}
+
+/// Extrapolate a sample run to an input of `total` media time
+pub fn extrapolate(
+    input: Option<PathBuf>,
+    total: Option<Duration>,
+    sample: SampleMeasurement,
+) -> JobEstimate {
+    let media = sample.media.as_secs_f64();
+    let wall = sample.wall.as_secs_f64().max(f64::EPSILON);
+    let speed = media / wall;
+    let bytes_per_second = sample
+        .output_bytes
+        .filter(|_| media > 0.0)
+        .map(|bytes| bytes as f64 / media);
+    JobEstimate {
+        input,
+        input_duration_ns: total.map(|t| t.as_nanos() as u64),
+        sample_media_ms: sample.media.as_millis() as u64,
+        sample_wall_ms: sample.wall.as_millis() as u64,
+        speed,
+        cpu_percent: sample.cpu.as_secs_f64() / wall * 100.0,
+        cpu_cores: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        sample_output_bytes: sample.output_bytes,
+        output_bitrate_kbps: bytes_per_second.map(|bps| bps * 8.0 / 1000.0),
+        estimated_duration_ms: total
+            .filter(|_| speed > 0.0)
+            .map(|t| (t.as_secs_f64() / speed * 1000.0) as u64),
+        estimated_output_bytes: total
+            .zip(bytes_per_second)
+            .map(|(t, bps)| (t.as_secs_f64() * bps) as u64),
+    }
+}
+
+fn input_file(pipeline: &gst::Bin) -> Option<PathBuf> {
+    pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .find_map(|element| {
+            let factory = element.factory()?.name();
+            match factory.as_str() {
+                "filesrc" => element
+                    .property::<Option<String>>("location")
+                    .map(PathBuf::from),
+                "uridecodebin" | "uridecodebin3" | "urisourcebin" => {
+                    let uri = element.property::<Option<String>>("uri")?;
+                    gst::glib::filename_from_uri(&uri)
+                        .ok()
+                        .map(|(path, _)| path)
+                }
+                _ => None,
+            }
+        })
+}
+
+/// Point every file writer of the pipeline into `dir`, keeping file names
+/// (and their patterns) so the sample never touches the real outputs
+fn redirect_outputs(pipeline: &gst::Bin, dir: &Path) -> bool {
+    let mut redirected = false;
+    for element in pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let writes_files = element
+            .factory()
+            .is_some_and(|f| FILE_WRITING_FACTORIES.contains(&f.name().as_str()));
+        if !writes_files || element.find_property("location").is_none() {
+            continue;
+        }
+        let name = element
+            .property::<Option<String>>("location")
+            .and_then(|l| Path::new(&l).file_name().map(|n| n.to_os_string()))
+            .unwrap_or_else(|| element.name().as_str().into());
+        element.set_property("location", dir.join(name).to_string_lossy().as_ref());
+        redirected = true;
+    }
+    redirected
+}
+
+fn directory_size(dir: &Path) -> u64 {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| e.metadata().ok())
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// Encode `sample` of media from the middle of the input of a transcode
+/// pipeline and extrapolate its duration, CPU use and output size. File
+/// outputs are written to a temporary directory. Blocks for the length of
+/// the sample encode.
+pub fn estimate_job(description: &str, sample: Duration) -> McpResult<JobEstimate> {
+    let element = parse_launch(description, "Failed to build pipeline")?;
+    let pipeline = element
+        .downcast::<gst::Bin>()
+        .map_err(|_| GStreamerMcpError::PipelineError("Expected a pipeline".to_string()))?;
+    let bus = pipeline
+        .bus()
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Pipeline has no bus".to_string()))?;
+
+    let input = input_file(&pipeline);
+    let total = input
+        .as_deref()
+        .and_then(|path| probe_media(path).ok())
+        .and_then(|info| info.duration_ns)
+        .map(Duration::from_nanos);
+
+    let scratch =
+        std::env::temp_dir().join(format!("gstreamer-mcp-estimate-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&scratch)?;
+    let writes_files = redirect_outputs(&pipeline, &scratch);
+
+    let result = (|| {
+        let fail = |what: &str| {
+            GStreamerMcpError::PipelineError(format!("Sample encode failed: {}", what))
+        };
+        pipeline
+            .set_state(gst::State::Paused)
+            .map_err(|_| fail("the pipeline did not start"))?;
+        let (prerolled, _, _) = pipeline.state(gst::ClockTime::from_seconds(10));
+        prerolled.map_err(|_| fail("the pipeline did not preroll"))?;
+
+        // Sample the middle of long inputs, past intros and fades
+        let start = match total {
+            Some(total) if total > sample * 3 => (total - sample) / 2,
+            _ => Duration::ZERO,
+        };
+        let start = gst::ClockTime::from_nseconds(start.as_nanos() as u64);
+        let stop = start + gst::ClockTime::from_nseconds(sample.as_nanos() as u64);
+        let bounded = pipeline
+            .seek(
+                1.0,
+                gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+                gst::SeekType::Set,
+                start,
+                gst::SeekType::Set,
+                stop,
+            )
+            .is_ok();
+        let (start, stop) = if bounded {
+            (start, stop)
+        } else {
+            (gst::ClockTime::ZERO, stop - start)
+        };
+
+        let wall = Instant::now();
+        let cpu = ProcessTime::now();
+        pipeline
+            .set_state(gst::State::Playing)
+            .map_err(|_| fail("the pipeline did not play"))?;
+        let deadline = (sample * 10).clamp(Duration::from_secs(30), MAX_SAMPLE_WALL);
+        let mut position = start;
+        loop {
+            let message = bus.timed_pop_filtered(
+                gst::ClockTime::from_mseconds(100),
+                &[gst::MessageType::Eos, gst::MessageType::Error],
+            );
+            if let Some(position_now) = pipeline.query_position::<gst::ClockTime>() {
+                position = position.max(position_now);
+            }
+            match message.as_ref().map(|m| m.view()) {
+                Some(gst::MessageView::Eos(_)) => break,
+                Some(gst::MessageView::Error(err)) => {
+                    return Err(fail(&err.error().to_string()));
+                }
+                _ => {}
+            }
+            // Unbounded pipelines are stopped once the sample is encoded
+            if position >= stop || wall.elapsed() >= deadline {
+                break;
+            }
+        }
+        let measured = SampleMeasurement {
+            media: Duration::from_nanos(position.min(stop).saturating_sub(start).nseconds()),
+            wall: wall.elapsed(),
+            cpu: cpu.elapsed(),
+            output_bytes: None,
+        };
+        if measured.media.is_zero() {
+            return Err(fail("no media was processed"));
+        }
+        Ok(measured)
+    })();
+    let _ = pipeline.set_state(gst::State::Null);
+
+    let output_bytes = writes_files.then(|| directory_size(&scratch));
+    let _ = std::fs::remove_dir_all(&scratch);
+    let measured = result?;
+    Ok(extrapolate(
+        input,
+        total,
+        SampleMeasurement {
+            output_bytes,
+            ..measured
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extrapolate() {
+        let estimate = extrapolate(
+            None,
+            Some(Duration::from_secs(3600)),
+            SampleMeasurement {
+                media: Duration::from_secs(10),
+                wall: Duration::from_secs(5),
+                cpu: Duration::from_secs(15),
+                output_bytes: Some(2_500_000),
+            },
+        );
+        assert_eq!(estimate.speed, 2.0);
+        assert_eq!(estimate.cpu_percent, 300.0);
+        assert_eq!(estimate.estimated_duration_ms, Some(1_800_000));
+        assert_eq!(estimate.estimated_output_bytes, Some(900_000_000));
+        assert_eq!(estimate.output_bitrate_kbps, Some(2000.0));
+
+        let unknown = extrapolate(
+            None,
+            None,
+            SampleMeasurement {
+                media: Duration::from_secs(10),
+                wall: Duration::from_secs(5),
+                cpu: Duration::ZERO,
+                output_bytes: None,
+            },
+        );
+        assert_eq!(unknown.estimated_duration_ms, None);
+        assert_eq!(unknown.estimated_output_bytes, None);
+    }
+}
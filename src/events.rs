@@ -0,0 +1,249 @@
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// Which way a custom event travels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomDirection {
+    /// From the sinks towards the sources, as `splitmuxsink` split requests
+    Upstream,
+    /// With the data, after the buffers queued before it
+    Downstream,
+    /// Downstream, ahead of any queued data
+    DownstreamOob,
+    /// Both ways
+    Both,
+}
+
+/// An event to send into a pipeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum PipelineEvent {
+    /// Discard the data in flight and refuse more until a flush stop
+    FlushStart,
+    /// End a flush; `reset_time` restarts the running time at zero
+    FlushStop { reset_time: bool },
+    /// With a value, tell the elements the latency to configure; without,
+    /// have the bin query and redistribute it
+    Latency { latency_ns: Option<u64> },
+    /// Application-defined event carrying a structure
+    Custom {
+        direction: CustomDirection,
+        name: String,
+        fields: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl PipelineEvent {
+    /// Name of the event, for reports
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PipelineEvent::FlushStart => "flush_start",
+            PipelineEvent::FlushStop { .. } => "flush_stop",
+            PipelineEvent::Latency { latency_ns: None } => "latency recalculation",
+            PipelineEvent::Latency { .. } => "latency",
+            PipelineEvent::Custom { direction, .. } => match direction {
+                CustomDirection::Upstream => "custom_upstream",
+                CustomDirection::Downstream => "custom_downstream",
+                CustomDirection::DownstreamOob => "custom_downstream_oob",
+                CustomDirection::Both => "custom_both",
+            },
+        }
+    }
+
+    /// Direction of a custom event from its name, or None for the other
+    /// events
+    pub fn parse_kind(kind: &str) -> Option<CustomDirection> {
+        match kind {
+            "custom_upstream" => Some(CustomDirection::Upstream),
+            "custom_downstream" => Some(CustomDirection::Downstream),
+            "custom_downstream_oob" => Some(CustomDirection::DownstreamOob),
+            "custom_both" => Some(CustomDirection::Both),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of [`send_event`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventReport {
+    pub pipeline_id: String,
+    /// Element the event was sent to, or the pipeline
+    pub target: String,
+    pub event: String,
+    /// The structure of a custom event, in GStreamer's string form
+    pub structure: Option<String>,
+    /// Whether an element handled the event
+    pub handled: bool,
+}
+
+/// Whether `name` can name a structure: a letter followed by letters,
+/// digits and `/-_.:+`
+pub fn is_valid_structure_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/-_.:+".contains(c))
+}
+
+/// Structure field for a JSON value. Whole numbers become `gint` when they
+/// fit, as most elements read them; objects become nested structures named
+/// by their `name` key.
+fn json_to_value(field: &str, value: &serde_json::Value) -> McpResult<glib::SendValue> {
+    use serde_json::Value;
+    Ok(match value {
+        Value::Bool(b) => b.to_send_value(),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                match i32::try_from(i) {
+                    Ok(i) => i.to_send_value(),
+                    Err(_) => i.to_send_value(),
+                }
+            } else if let Some(u) = n.as_u64() {
+                u.to_send_value()
+            } else {
+                n.as_f64().unwrap_or_default().to_send_value()
+            }
+        }
+        Value::String(s) => s.to_send_value(),
+        Value::Array(items) => {
+            let values = items
+                .iter()
+                .map(|item| json_to_value(field, item))
+                .collect::<McpResult<Vec<_>>>()?;
+            gst::Array::from_values(values).to_send_value()
+        }
+        Value::Object(object) => {
+            let name = object.get("name").and_then(|n| n.as_str()).ok_or_else(|| {
+                GStreamerMcpError::PipelineError(format!(
+                    "Field '{}' holds an object without a string 'name' for its structure",
+                    field
+                ))
+            })?;
+            let mut fields = object.clone();
+            fields.remove("name");
+            json_to_structure(name, &fields)?.to_send_value()
+        }
+        Value::Null => {
+            return Err(GStreamerMcpError::PipelineError(format!(
+                "Field '{}' is null; leave it out instead",
+                field
+            )))
+        }
+    })
+}
+
+/// Build a structure from its name and JSON-encoded fields
+pub fn json_to_structure(
+    name: &str,
+    fields: &serde_json::Map<String, serde_json::Value>,
+) -> McpResult<gst::Structure> {
+    if !is_valid_structure_name(name) {
+        return Err(GStreamerMcpError::PipelineError(format!(
+            "'{}' is not a valid structure name: start with a letter and use letters, digits and /-_.:+",
+            name
+        )));
+    }
+    let mut structure = gst::Structure::new_empty(name);
+    for (field, value) in fields {
+        structure.set_value(field, json_to_value(field, value)?);
+    }
+    Ok(structure)
+}
+
+/// Send an event to the element named `element`, or to the pipeline, which
+/// hands upstream events to its sinks and downstream events to its
+/// sources. Returns the report with `pipeline_id` unset.
+pub fn send_event(
+    pipeline: &gst::Pipeline,
+    element: Option<&str>,
+    event: &PipelineEvent,
+) -> McpResult<EventReport> {
+    let target: gst::Element = match element {
+        Some(name) => pipeline.by_name(name).ok_or_else(|| {
+            GStreamerMcpError::ElementNotFound(format!("No element named '{}' in pipeline", name))
+        })?,
+        None => pipeline.clone().upcast(),
+    };
+
+    let mut structure = None;
+    let handled = match event {
+        PipelineEvent::FlushStart => target.send_event(gst::event::FlushStart::new()),
+        PipelineEvent::FlushStop { reset_time } => {
+            target.send_event(gst::event::FlushStop::new(*reset_time))
+        }
+        PipelineEvent::Latency {
+            latency_ns: Some(latency),
+        } => target.send_event(gst::event::Latency::new(gst::ClockTime::from_nseconds(
+            *latency,
+        ))),
+        PipelineEvent::Latency { latency_ns: None } => {
+            let bin = target.downcast_ref::<gst::Bin>().ok_or_else(|| {
+                GStreamerMcpError::PipelineError(format!(
+                    "'{}' is not a bin and cannot recalculate its latency; set latency_ns to send it a latency event",
+                    target.name()
+                ))
+            })?;
+            bin.recalculate_latency().is_ok()
+        }
+        PipelineEvent::Custom {
+            direction,
+            name,
+            fields,
+        } => {
+            let custom = json_to_structure(name, fields)?;
+            structure = Some(custom.to_string());
+            target.send_event(match direction {
+                CustomDirection::Upstream => gst::event::CustomUpstream::new(custom),
+                CustomDirection::Downstream => gst::event::CustomDownstream::new(custom),
+                CustomDirection::DownstreamOob => gst::event::CustomDownstreamOob::new(custom),
+                CustomDirection::Both => gst::event::CustomBoth::new(custom),
+            })
+        }
+    };
+
+    Ok(EventReport {
+        pipeline_id: String::new(),
+        target: target.name().to_string(),
+        event: event.kind().to_string(),
+        structure,
+        handled,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_kinds() {
+        assert!(is_valid_structure_name("split-now"));
+        assert!(is_valid_structure_name("GstForceKeyUnit"));
+        assert!(is_valid_structure_name("application/x-marker"));
+        assert!(!is_valid_structure_name("2pass"));
+        assert!(!is_valid_structure_name("has space"));
+        assert!(!is_valid_structure_name(""));
+
+        assert_eq!(
+            PipelineEvent::parse_kind("custom_downstream_oob"),
+            Some(CustomDirection::DownstreamOob)
+        );
+        assert_eq!(PipelineEvent::parse_kind("flush_start"), None);
+        let event = PipelineEvent::Custom {
+            direction: CustomDirection::Upstream,
+            name: "split-now".to_string(),
+            fields: serde_json::Map::new(),
+        };
+        assert_eq!(
+            PipelineEvent::parse_kind(event.kind()),
+            Some(CustomDirection::Upstream)
+        );
+        assert_eq!(
+            PipelineEvent::Latency { latency_ns: None }.kind(),
+            "latency recalculation"
+        );
+    }
+}
@@ -0,0 +1,543 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_video as gst_video;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::quote_location;
+use crate::probes::find_pad;
+use crate::suggestions::parse_launch;
+
+/// How long to wait for each preroll and for the image conversion
+const EXTRACT_TIMEOUT_SECONDS: u64 = 10;
+
+/// Image formats by file extension: caps of the encoded image
+const IMAGE_FORMATS: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+];
+
+/// Longest audio segment that can be extracted
+pub const MAX_AUDIO_SEGMENT: Duration = Duration::from_secs(30);
+
+/// Most bytes of audio kept for a segment, whatever its duration
+pub const MAX_AUDIO_SEGMENT_BYTES: usize = 16 * 1024 * 1024;
+
+/// File extensions of encoded audio written as-is, by media type
+const ENCODED_EXTENSIONS: &[(&str, &str)] = &[
+    ("audio/mpeg", "mp3"),
+    ("audio/x-opus", "opus"),
+    ("audio/x-vorbis", "ogg"),
+    ("audio/x-flac", "flac"),
+    ("audio/x-ac3", "ac3"),
+];
+
+/// A frame written by [`extract_frame_at`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtractedFrame {
+    pub output: PathBuf,
+    pub requested_ns: u64,
+    /// Presentation timestamp of the exported frame
+    pub pts_ns: Option<u64>,
+    pub duration_ns: Option<u64>,
+    pub width: u32,
+    pub height: u32,
+    pub accurate: bool,
+}
+
+/// Media type of the image format selected by the output extension
+fn image_media_type(output: &Path) -> McpResult<&'static str> {
+    let extension = output
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    IMAGE_FORMATS
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, media_type)| *media_type)
+        .ok_or_else(|| {
+            GStreamerMcpError::Other(format!(
+                "Unsupported image format '{}': use one of {}",
+                output.display(),
+                IMAGE_FORMATS
+                    .iter()
+                    .map(|(ext, _)| *ext)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+}
+
+/// Wait for a pending preroll, failing with the error on the bus if any
+fn wait_preroll(pipeline: &gst::Element, what: &str) -> McpResult<()> {
+    let (result, _, _) = pipeline.state(gst::ClockTime::from_seconds(EXTRACT_TIMEOUT_SECONDS));
+    match result {
+        Ok(gst::StateChangeSuccess::Success) => Ok(()),
+        Ok(_) => Err(GStreamerMcpError::PipelineError(format!(
+            "Timed out while {}",
+            what
+        ))),
+        Err(_) => {
+            let detail = pipeline
+                .bus()
+                .and_then(|bus| bus.pop_filtered(&[gst::MessageType::Error]))
+                .and_then(|msg| match msg.view() {
+                    gst::MessageView::Error(err) => Some(err.error().to_string()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| "pipeline error".to_string());
+            Err(GStreamerMcpError::PipelineError(format!(
+                "Failed while {}: {}",
+                what, detail
+            )))
+        }
+    }
+}
+
+/// Seek a file to `position` and write the frame displayed at that time
+/// as an image, its format chosen by the output extension. With
+/// `accurate` the decoder runs from the preceding keyframe up to the exact
+/// frame; without it the nearest keyframe at or before `position` is
+/// exported, which is faster but only exact on keyframes.
+pub fn extract_frame_at(
+    input: &Path,
+    output: &Path,
+    position: gst::ClockTime,
+    accurate: bool,
+) -> McpResult<ExtractedFrame> {
+    let media_type = image_media_type(output)?;
+    let description = format!(
+        "filesrc location={} ! decodebin ! videoconvert ! fakesink name=sink sync=false enable-last-sample=true",
+        quote_location(input)
+    );
+    let pipeline = parse_launch(&description, "Failed to build extraction pipeline")?;
+    let image_caps = gst::Caps::builder(media_type).build();
+
+    let result = (|| {
+        pipeline.set_state(gst::State::Paused).map_err(|_| {
+            GStreamerMcpError::PipelineError(format!("Failed to open {}", input.display()))
+        })?;
+        wait_preroll(&pipeline, "opening the input")?;
+
+        let flags = if accurate {
+            gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE
+        } else {
+            gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT | gst::SeekFlags::SNAP_BEFORE
+        };
+        pipeline.seek_simple(flags, position).map_err(|_| {
+            GStreamerMcpError::PipelineError(format!(
+                "{} cannot be seeked to {}",
+                input.display(),
+                position
+            ))
+        })?;
+        wait_preroll(&pipeline, "seeking")?;
+
+        let sink = pipeline
+            .downcast_ref::<gst::Bin>()
+            .and_then(|bin| bin.by_name("sink"))
+            .ok_or_else(|| GStreamerMcpError::PipelineError("Sink not found".to_string()))?;
+        let sample = sink
+            .property::<Option<gst::Sample>>("last-sample")
+            .ok_or_else(|| {
+                GStreamerMcpError::PipelineError(format!(
+                    "No video frame at {} in {}",
+                    position,
+                    input.display()
+                ))
+            })?;
+        let buffer = sample
+            .buffer()
+            .ok_or_else(|| GStreamerMcpError::PipelineError("Empty frame".to_string()))?;
+        let (pts_ns, duration_ns) = (
+            buffer.pts().map(|t| t.nseconds()),
+            buffer.duration().map(|t| t.nseconds()),
+        );
+        let info = sample
+            .caps()
+            .and_then(|caps| gst_video::VideoInfo::from_caps(caps).ok())
+            .ok_or_else(|| {
+                GStreamerMcpError::PipelineError(format!("{} has no video", input.display()))
+            })?;
+
+        let image = gst_video::convert_sample(
+            &sample,
+            &image_caps,
+            gst::ClockTime::from_seconds(EXTRACT_TIMEOUT_SECONDS),
+        )
+        .map_err(|e| GStreamerMcpError::PipelineError(format!("Failed to encode frame: {}", e)))?;
+        let map = image
+            .buffer()
+            .and_then(|b| b.map_readable().ok())
+            .ok_or_else(|| GStreamerMcpError::PipelineError("Empty image".to_string()))?;
+        std::fs::write(output, map.as_slice())?;
+
+        Ok(ExtractedFrame {
+            output: output.to_path_buf(),
+            requested_ns: position.nseconds(),
+            pts_ns,
+            duration_ns,
+            width: info.width(),
+            height: info.height(),
+            accurate,
+        })
+    })();
+
+    let _ = pipeline.set_state(gst::State::Null);
+    result
+}
+
+/// Layout of interleaved little-endian PCM, as needed for a WAV header
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PcmFormat {
+    pub rate: u32,
+    pub channels: u16,
+    pub bits: u16,
+    pub float: bool,
+}
+
+/// Audio collected by [`extract_audio_segment`] or [`tap_audio_segment`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioSegment {
+    pub caps: String,
+    /// Set for raw audio, which is written as WAV
+    pub pcm: Option<PcmFormat>,
+    /// Timestamp of the first buffer
+    pub start_ns: Option<u64>,
+    pub duration_ns: u64,
+    /// Whether collection stopped at [`MAX_AUDIO_SEGMENT_BYTES`]
+    pub truncated: bool,
+    #[serde(skip)]
+    pub data: Vec<u8>,
+}
+
+/// Bits per sample and whether samples are floats, for the raw audio
+/// formats a WAV file can hold
+fn pcm_layout(format: &str) -> Option<(u16, bool)> {
+    match format {
+        "U8" => Some((8, false)),
+        "S16LE" => Some((16, false)),
+        "S24LE" => Some((24, false)),
+        "S32LE" => Some((32, false)),
+        "F32LE" => Some((32, true)),
+        "F64LE" => Some((64, true)),
+        _ => None,
+    }
+}
+
+fn pcm_format(caps: &gst::CapsRef) -> McpResult<Option<PcmFormat>> {
+    let structure = caps
+        .structure(0)
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Audio has empty caps".to_string()))?;
+    if !structure.name().starts_with("audio/") {
+        return Err(GStreamerMcpError::PipelineError(format!(
+            "Not audio: {}",
+            caps
+        )));
+    }
+    if structure.name() != "audio/x-raw" {
+        return Ok(None);
+    }
+    let format = structure.get::<&str>("format").unwrap_or_default();
+    let layout = structure.get::<&str>("layout").unwrap_or("interleaved");
+    let (bits, float) = pcm_layout(format)
+        .filter(|_| layout == "interleaved")
+        .ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!(
+                "Raw audio in {} {} cannot be written as WAV; tap a pad after audioconvert",
+                layout, format
+            ))
+        })?;
+    Ok(Some(PcmFormat {
+        rate: structure.get::<i32>("rate").unwrap_or_default() as u32,
+        channels: structure.get::<i32>("channels").unwrap_or(1) as u16,
+        bits,
+        float,
+    }))
+}
+
+/// Header of a WAV file holding `data_len` bytes of `format`
+fn wav_header(format: &PcmFormat, data_len: u32) -> Vec<u8> {
+    let block_align = format.channels * format.bits / 8;
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(36 + data_len).to_le_bytes());
+    header.extend_from_slice(b"WAVEfmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&(if format.float { 3u16 } else { 1u16 }).to_le_bytes());
+    header.extend_from_slice(&format.channels.to_le_bytes());
+    header.extend_from_slice(&format.rate.to_le_bytes());
+    header.extend_from_slice(&(format.rate * block_align as u32).to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&format.bits.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_len.to_le_bytes());
+    header
+}
+
+/// Extension of the file a segment is written to when no output is given
+pub fn segment_extension(segment: &AudioSegment) -> &'static str {
+    if segment.pcm.is_some() {
+        return "wav";
+    }
+    let media_type = segment.caps.split(',').next().unwrap_or_default();
+    ENCODED_EXTENSIONS
+        .iter()
+        .find(|(name, _)| *name == media_type)
+        .map_or("bin", |(_, ext)| ext)
+}
+
+/// Write a segment to `output`: raw audio as WAV, encoded audio as-is
+pub fn write_segment(segment: &AudioSegment, output: &Path) -> McpResult<()> {
+    let mut bytes = match &segment.pcm {
+        Some(format) => wav_header(format, segment.data.len() as u32),
+        None => Vec::new(),
+    };
+    bytes.extend_from_slice(&segment.data);
+    std::fs::write(output, bytes)?;
+    Ok(())
+}
+
+/// Appends buffers to a segment until it is full
+#[derive(Default)]
+struct SegmentCollector {
+    data: Vec<u8>,
+    start_ns: Option<u64>,
+    truncated: bool,
+}
+
+impl SegmentCollector {
+    fn push(&mut self, buffer: &gst::BufferRef) {
+        if self.truncated {
+            return;
+        }
+        let Ok(map) = buffer.map_readable() else {
+            return;
+        };
+        if self.start_ns.is_none() {
+            self.start_ns = buffer.pts().map(|t| t.nseconds());
+        }
+        let room = MAX_AUDIO_SEGMENT_BYTES - self.data.len();
+        if map.len() > room {
+            self.truncated = true;
+        }
+        self.data.extend_from_slice(&map[..map.len().min(room)]);
+    }
+}
+
+fn finish_segment(
+    collector: SegmentCollector,
+    caps: Option<gst::Caps>,
+    duration: Duration,
+) -> McpResult<AudioSegment> {
+    let caps = caps.ok_or_else(|| {
+        GStreamerMcpError::PipelineError("No audio flowed during the segment".to_string())
+    })?;
+    let pcm = pcm_format(&caps)?;
+    // The duration of raw audio is known from its size
+    let duration_ns = match &pcm {
+        Some(f) if f.rate > 0 && f.channels > 0 => {
+            let frames = collector.data.len() as u64 / (f.channels as u64 * f.bits as u64 / 8);
+            frames * 1_000_000_000 / f.rate as u64
+        }
+        _ => duration.as_nanos() as u64,
+    };
+    Ok(AudioSegment {
+        caps: caps.to_string(),
+        pcm,
+        start_ns: collector.start_ns,
+        duration_ns,
+        truncated: collector.truncated,
+        data: collector.data,
+    })
+}
+
+/// Decode `duration` of audio from a file starting at `start`, converted to
+/// 16-bit PCM with the given rate and channel count when set
+pub fn extract_audio_segment(
+    input: &Path,
+    start: gst::ClockTime,
+    duration: Duration,
+    rate: Option<u32>,
+    channels: Option<u32>,
+) -> McpResult<AudioSegment> {
+    let mut caps = "audio/x-raw,format=S16LE,layout=interleaved".to_string();
+    if let Some(rate) = rate {
+        caps.push_str(&format!(",rate={}", rate));
+    }
+    if let Some(channels) = channels {
+        caps.push_str(&format!(",channels={}", channels));
+    }
+    let description = format!(
+        "filesrc location={} ! decodebin ! audioconvert ! audioresample ! {} ! fakesink name=sink sync=false signal-handoffs=true",
+        quote_location(input),
+        caps
+    );
+    let pipeline = parse_launch(&description, "Failed to build extraction pipeline")?;
+    let sink = pipeline
+        .downcast_ref::<gst::Bin>()
+        .and_then(|bin| bin.by_name("sink"))
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Sink not found".to_string()))?;
+    let collector = Arc::new(Mutex::new(SegmentCollector::default()));
+    {
+        let collector = collector.clone();
+        sink.connect("handoff", false, move |values| {
+            let buffer = values.get(1).and_then(|v| v.get::<gst::Buffer>().ok())?;
+            collector.lock().push(&buffer);
+            None
+        });
+    }
+
+    let result = (|| {
+        pipeline.set_state(gst::State::Paused).map_err(|_| {
+            GStreamerMcpError::PipelineError(format!("Failed to open {}", input.display()))
+        })?;
+        wait_preroll(&pipeline, "opening the input")?;
+
+        let stop = start + gst::ClockTime::from_nseconds(duration.as_nanos() as u64);
+        pipeline
+            .seek(
+                1.0,
+                gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+                gst::SeekType::Set,
+                start,
+                gst::SeekType::Set,
+                stop,
+            )
+            .map_err(|_| {
+                GStreamerMcpError::PipelineError(format!(
+                    "{} cannot be seeked to {}",
+                    input.display(),
+                    start
+                ))
+            })?;
+        // fakesink only hands off buffers it renders, so nothing from the
+        // initial preroll is collected
+        wait_preroll(&pipeline, "seeking")?;
+
+        let caps = sink.static_pad("sink").and_then(|pad| pad.current_caps());
+        pipeline.set_state(gst::State::Playing).map_err(|_| {
+            GStreamerMcpError::PipelineError("Failed to start extraction".to_string())
+        })?;
+        let bus = pipeline
+            .bus()
+            .ok_or_else(|| GStreamerMcpError::PipelineError("Pipeline has no bus".to_string()))?;
+        let timeout = gst::ClockTime::from_seconds(EXTRACT_TIMEOUT_SECONDS)
+            + gst::ClockTime::from_nseconds(duration.as_nanos() as u64);
+        match bus
+            .timed_pop_filtered(timeout, &[gst::MessageType::Eos, gst::MessageType::Error])
+            .as_ref()
+            .map(|msg| msg.view())
+        {
+            Some(gst::MessageView::Error(err)) => {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "Decoding failed: {}",
+                    err.error()
+                )))
+            }
+            Some(_) => {}
+            None => {
+                return Err(GStreamerMcpError::PipelineError(
+                    "Timed out while decoding".to_string(),
+                ))
+            }
+        }
+        Ok(caps)
+    })();
+
+    let _ = pipeline.set_state(gst::State::Null);
+    let caps = result?;
+    let collector = std::mem::take(&mut *collector.lock());
+    finish_segment(collector, caps, duration)
+}
+
+/// Collect what flows through `pad_spec` of a running pipeline for
+/// `duration`. Raw audio must be interleaved little-endian; encoded audio
+/// is kept as it flows, which suits byte-stream formats such as ADTS AAC.
+pub async fn tap_audio_segment(
+    pipeline: &gst::Pipeline,
+    pad_spec: &str,
+    duration: Duration,
+) -> McpResult<AudioSegment> {
+    let pad = find_pad(pipeline, pad_spec)?;
+    if let Some(caps) = pad.current_caps() {
+        pcm_format(&caps)?;
+    }
+
+    let collector = Arc::new(Mutex::new(SegmentCollector::default()));
+    let probe_id = {
+        let collector = collector.clone();
+        pad.add_probe(
+            gst::PadProbeType::BUFFER | gst::PadProbeType::BUFFER_LIST,
+            move |_pad, info| {
+                if let Some(buffer) = info.buffer() {
+                    collector.lock().push(buffer);
+                } else if let Some(list) = info.buffer_list() {
+                    let mut collector = collector.lock();
+                    for buffer in list.iter() {
+                        collector.push(buffer);
+                    }
+                }
+                gst::PadProbeReturn::Ok
+            },
+        )
+    }
+    .ok_or_else(|| {
+        GStreamerMcpError::PipelineError(format!("Failed to install probe on '{}'", pad_spec))
+    })?;
+
+    tokio::time::sleep(duration).await;
+    pad.remove_probe(probe_id);
+
+    let collector = std::mem::take(&mut *collector.lock());
+    finish_segment(collector, pad.current_caps(), duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_media_type() {
+        assert_eq!(
+            image_media_type(Path::new("frame.JPG")).unwrap(),
+            "image/jpeg"
+        );
+        assert_eq!(
+            image_media_type(Path::new("out/frame.png")).unwrap(),
+            "image/png"
+        );
+        assert!(image_media_type(Path::new("frame.gif")).is_err());
+        assert!(image_media_type(Path::new("frame")).is_err());
+    }
+
+    #[test]
+    fn test_wav_header() {
+        let format = PcmFormat {
+            rate: 44100,
+            channels: 2,
+            bits: 16,
+            float: false,
+        };
+        let header = wav_header(&format, 1000);
+        assert_eq!(header.len(), 44);
+        assert_eq!(&header[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(header[4..8].try_into().unwrap()), 1036);
+        assert_eq!(u16::from_le_bytes(header[20..22].try_into().unwrap()), 1);
+        assert_eq!(
+            u32::from_le_bytes(header[28..32].try_into().unwrap()),
+            176400
+        );
+        assert_eq!(u16::from_le_bytes(header[32..34].try_into().unwrap()), 4);
+        assert_eq!(u32::from_le_bytes(header[40..44].try_into().unwrap()), 1000);
+
+        assert_eq!(pcm_layout("F32LE"), Some((32, true)));
+        assert_eq!(pcm_layout("S16BE"), None);
+    }
+}
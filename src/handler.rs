@@ -1,20 +1,110 @@
+use crate::abr::{
+    default_ladder, ladder_description, ladder_encoders, master_playlist, AbrFormat, AbrLadder,
+    AbrRendition, DASH_MANIFEST, DEFAULT_AUDIO_KBPS, DEFAULT_SEGMENT_SECONDS, MASTER_PLAYLIST,
+};
+use crate::adaptive::{get_adaptive_stats, set_variant, AdaptiveStats};
+use crate::analysis::{analyze_file_gops, bitrate_profile};
+use crate::animation::{Animation, Animator, Interpolation};
+use crate::aravis::{
+    aravis_description, inspect_aravis_camera, list_aravis_cameras, AravisSettings,
+};
+use crate::artifacts::{
+    output_locations, parse_artifact_uri, Artifact, ArtifactRegistry, RetentionPolicy,
+    ARTIFACT_URI_TEMPLATE,
+};
+use crate::audio_monitor::{attach_audio_monitor, AudioAlert, AudioThresholds, LevelMonitor};
+use crate::auto_queue::{check_branch_queues, MissingQueue};
+use crate::bus_events::{spawn_bus_events, BusEventKind, EventSubscription, EventSubscriptions};
+use crate::caps_watch::spawn_caps_alerts;
+use crate::color::Tonemap;
+use crate::concat::{plan_concat, ConcatMode};
 use crate::config::Configuration;
+use crate::confirmation::{request_confirmation, FILE_WRITING_FACTORIES};
+use crate::conversion::Conversions;
+use crate::deep_validation::{deep_validate_pipeline, DEEP_VALIDATION_TIMEOUT};
 use crate::discovery::{
-    discover_all_elements, discover_all_plugins, inspect_element, search_elements, DiscoveryCache,
+    discover_all_elements, discover_all_plugins, inspect_element, inspect_element_isolated,
+    metadata_index, parse_rank, rank_detailed_elements, rank_elements, search_elements,
+    CacheStatus, DiscoveryCache, IndexStatus, MediaType, SearchField,
+};
+use crate::doctor::{run_doctor, CheckStatus};
+use crate::element_states::{element_details, element_states, stuck_elements};
+use crate::encoder_switch::switch_encoder;
+use crate::encoding_profile::{build_encoding_profile, EncodingProfileSpec};
+use crate::error_budget::{spawn_budget_alerts, ErrorBudget};
+use crate::estimate::estimate_job;
+use crate::events::PipelineEvent;
+use crate::extract::{
+    extract_audio_segment, extract_frame_at, segment_extension, tap_audio_segment, write_segment,
+    MAX_AUDIO_SEGMENT,
 };
-use crate::pipeline::{validate_pipeline_description, PipelineManager};
-use crate::tool_registry::ToolRegistry;
+use crate::inference::{attach_detector, detector_chain, DetectionEvent, DetectionFilter};
+use crate::interlace::{detect_interlacing, DEFAULT_SAMPLE_FRAMES};
+use crate::jobs::{Job, JobQueue, JobStatus};
+use crate::latency::query_latency;
+use crate::limits::{spawn_limits_publisher, ServerLimits, LIMITS_URI};
+use crate::looping::LoopSettings;
+use crate::loudness::{
+    measure_loudness, normalization_gain_db, normalize_description, LoudnessMeasurement,
+};
+use crate::media::{check_input_file, probe_media, run_to_completion};
+use crate::memory::{cap_appsinks, MemoryLimits};
+use crate::message_filter::{MessageFilter, MessageSeverity};
+use crate::message_history::HistoryQuery;
+use crate::message_wait::MessageQuery;
+use crate::meta_tap::{tap_metadata, FrameMeta, MetadataEvent};
+use crate::metrics::{ToolMetrics, ToolStats};
+use crate::modify::{PipelineModification, MODIFY_TIMEOUT};
+use crate::ndi::{
+    default_video_chain, list_ndi_sources, ndi_description, NdiBandwidth, NdiDirection,
+    NdiEndpoints,
+};
+use crate::netsync::{NetSync, SyncRequest, DEFAULT_CLOCK_PORT, DEFAULT_SYNC_LATENCY_MS};
+use crate::network::{apply_network_options, list_network_interfaces, NetworkOptions};
+use crate::notifications::Notifier;
+use crate::pipeline::{
+    matches_labels, pipeline_deadline, rate_segment, spawn_deadline_enforcer, spawn_watchdog,
+    validate_pipeline_description, BusMessage, Cause, EosOutcome, OnError, PipelineManager,
+    SeekPosition, WatchdogPolicy, DEFAULT_PREROLL_TIMEOUT, SEEK_TIMEOUT,
+};
+use crate::pipeline_log::{tail_log, LogRecord};
+use crate::policy::{CallerIdentity, RolePolicy};
+use crate::prerecord::set_prerecord;
+use crate::probes::{measure_bitrate, ProbeStats};
+use crate::properties::{get_properties, set_properties};
+use crate::reaper::spawn_reaper;
+use crate::registry_watcher::spawn_registry_watcher;
+use crate::routing::{route_audio, AudioRoute};
+use crate::schedule::{parse_trigger, ScheduledAction, ScheduledEntry, Scheduler};
+use crate::shared_state::{spawn_heartbeat, SharedState};
+use crate::speech::{attach_transcriber, speech_description, TranscriptSegment, SYNTHESIZERS};
+use crate::speed::plan_speed_change;
+use crate::stall::spawn_stall_detector;
+use crate::suggestions::{correct_element_typos, ElementCorrection};
+use crate::time_display::TimeDisplay;
+use crate::timecode::{apply_timecode, current_timecode, read_timecode, TimecodeOptions};
+use crate::tool_registry::{ToolCategory, ToolRegistry};
+use crate::upload::UploadRequest;
+use crate::verify::verify_file;
+use crate::watermark::{apply_watermark, WatermarkOptions};
+use base64::Engine as _;
 use gstreamer as gst;
 use rmcp::{
-    handler::server::{router::tool::ToolRouter, tool::Parameters},
+    handler::server::tool::ToolCallContext,
+    handler::server::{
+        router::tool::ToolRouter,
+        tool::{Parameters, RequestId as CallId},
+    },
     model::{ErrorCode, *},
     schemars,
     schemars::JsonSchema,
-    tool, tool_handler, tool_router, ErrorData as McpError, ServerHandler,
+    service::{NotificationContext, RequestContext, RoleServer},
+    tool, tool_router, ErrorData as McpError, Peer, ServerHandler,
 };
 use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -27,6 +117,18 @@ pub struct ListElementsParams {
         description = "Optional category to filter elements by classification (e.g., 'Source', 'Sink', 'Filter', 'Codec')"
     )]
     pub category: Option<String>,
+    #[schemars(description = "Only elements of this plugin (e.g., 'coreelements', 'x264')")]
+    pub plugin: Option<String>,
+    #[schemars(
+        description = "Only elements of at least this rank: none, marginal, secondary, primary, or a number"
+    )]
+    pub min_rank: Option<String>,
+    #[schemars(description = "Only elements with (true) or without (false) source pads")]
+    pub has_src_pads: Option<bool>,
+    #[schemars(description = "Only elements with (true) or without (false) sink pads")]
+    pub has_sink_pads: Option<bool>,
+    #[schemars(description = "Only elements handling this media: audio, video, or text")]
+    pub media_type: Option<MediaType>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -35,6 +137,14 @@ pub struct InspectElementParams {
         description = "Name of the GStreamer element to inspect (e.g., 'videotestsrc', 'x264enc', 'filesink')"
     )]
     pub element_name: String,
+    #[schemars(
+        description = "Create the element in a helper process so a crashing plugin cannot take down the server (default: the isolate_inspection setting)"
+    )]
+    pub isolated: Option<bool>,
+    #[schemars(
+        description = "Inspect the element again instead of answering from the cache (default: false)"
+    )]
+    pub bypass_cache: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -51,6 +161,10 @@ pub struct SearchElementsParams {
         description = "Search query to match against element names, descriptions, and classifications (e.g., 'encoder', 'mp4', 'audio')"
     )]
     pub query: String,
+    #[schemars(
+        description = "Fields to search: names, descriptions, properties, signals, caps (default: names and descriptions). Properties and signals create each element once, so the first such search is slow"
+    )]
+    pub search_in: Option<Vec<SearchField>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -67,6 +181,76 @@ pub struct LaunchPipelineParams {
         description = "Optional custom pipeline ID. If not provided, a UUID will be generated"
     )]
     pub pipeline_id: Option<String>,
+    #[schemars(
+        description = "Optional socket options (multicast interface, TTL, buffer size) applied to udpsrc/udpsink elements"
+    )]
+    pub network: Option<NetworkOptions>,
+    #[schemars(
+        description = "Replace unknown element names with their closest registry match, e.g. 'x264env' → 'x264enc' (default: false)"
+    )]
+    pub auto_correct: Option<bool>,
+    #[schemars(
+        description = "Optional logo overlay (image path, corner, opacity) drawn in front of every video encoder and video sink"
+    )]
+    pub watermark: Option<WatermarkOptions>,
+    #[schemars(
+        description = "Optional SMPTE timecode (source, first timecode, drop-frame, burn-in) stamped on the video in front of every video encoder and video sink"
+    )]
+    pub timecode: Option<TimecodeOptions>,
+    #[schemars(
+        description = "Take the pipeline to PAUSED and hold it prerolled instead of playing it, so it goes live instantly when set to 'playing' (default: false). Not possible with live sources"
+    )]
+    pub preroll_only: Option<bool>,
+    #[schemars(
+        description = "Start tee and demuxer branches that lack one with a queue (default: false). Otherwise such branches are rejected with their positions, as they deadlock the pipeline"
+    )]
+    pub auto_queue: Option<bool>,
+    #[schemars(
+        description = "Optional memory guardrails: appsink queue depth and leakiness of inserted queues. Unset values use the server configuration"
+    )]
+    pub memory: Option<MemoryLimits>,
+    #[schemars(
+        description = "Optional recovery policy: restart, stop or ignore the pipeline when it posts an error, with max retries and backoff"
+    )]
+    pub watchdog: Option<WatchdogPolicy>,
+    #[schemars(
+        description = "Stop and remove the pipeline after it has existed this many seconds"
+    )]
+    pub max_duration_seconds: Option<u64>,
+    #[schemars(
+        description = "Stop and remove the pipeline at this time, in RFC 3339 (e.g. '2025-06-01T18:00:00Z'). With max_duration_seconds, the earlier applies"
+    )]
+    pub stop_at: Option<String>,
+    #[schemars(
+        description = "Optional JSON object kept with the pipeline and returned by list and status, e.g. {\"request_id\": \"r-42\"}"
+    )]
+    pub metadata: Option<serde_json::Map<String, serde_json::Value>>,
+    #[schemars(
+        description = "Optional idempotency key: while a pipeline launched with the same key exists, it is returned instead of launching another. Protects against retries creating duplicates"
+    )]
+    pub dedupe_key: Option<String>,
+    #[schemars(
+        description = "Optional labels for grouping and filtering, e.g. {\"camera\": \"front\", \"purpose\": \"record\"}"
+    )]
+    pub labels: Option<std::collections::BTreeMap<String, String>>,
+    #[schemars(
+        description = "Who supervises the pipeline, e.g. an agent or operator name (default: the client's name)"
+    )]
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct TransferPipelineParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(description = "New owner of the pipeline; omit to leave it without one")]
+    pub owner: Option<String>,
+    #[schemars(
+        description = "Only transfer if the pipeline still belongs to this owner, so concurrent takeovers cannot both succeed"
+    )]
+    pub expected_owner: Option<String>,
+    #[schemars(description = "Replace the pipeline's labels with these")]
+    pub labels: Option<std::collections::BTreeMap<String, String>>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
@@ -80,526 +264,7235 @@ pub struct SetPipelineStateParams {
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
-pub struct GetPipelineStatusParams {
+pub struct SeekParams {
     #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
     pub pipeline_id: String,
     #[schemars(
-        description = "Include recent bus messages (errors, warnings, info) in the response (default: false)"
+        description = "Target position: nanoseconds (83500000000) or [[H:]M:]S[.fraction] text ('1:23.5')"
     )]
-    pub include_messages: Option<bool>,
+    pub position: SeekPosition,
+    #[schemars(
+        description = "Flush queued data so the new position shows at once (default: true)"
+    )]
+    pub flush: Option<bool>,
+    #[schemars(description = "Land on the nearest keyframe, which is faster (default: false)")]
+    pub key_unit: Option<bool>,
+    #[schemars(
+        description = "Land exactly on the position, decoding from the previous keyframe (default: false)"
+    )]
+    pub accurate: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
-pub struct StopPipelineParams {
+pub struct SetPlaybackRateParams {
     #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
     pub pipeline_id: String,
     #[schemars(
-        description = "Force termination even if pipeline is processing (default: false). Use with caution"
+        description = "Playback rate: 1.0 is normal, 0.5 slow motion, 2.0 fast-forward, negative rates play in reverse"
     )]
-    pub force: Option<bool>,
+    pub rate: f64,
+    #[schemars(
+        description = "Start of the played segment (default: current position; the beginning for reverse playback)"
+    )]
+    pub start: Option<SeekPosition>,
+    #[schemars(
+        description = "End of the played segment (default: the end; the current position for reverse playback)"
+    )]
+    pub stop: Option<SeekPosition>,
+    #[schemars(description = "Flush queued data so the rate applies at once (default: true)")]
+    pub flush: Option<bool>,
+    #[schemars(
+        description = "Trick mode: decode only keyframes, for fast-forward and reverse at high rates (default: false)"
+    )]
+    pub key_units_only: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
-pub struct ListGstPipelinesParams {
+pub struct SetLoopParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
     #[schemars(
-        description = "Include detailed information about each pipeline (state, duration, messages) (default: false)"
+        description = "Start of the looped segment, in nanoseconds or as '1:23.5' (default: the beginning)"
     )]
-    pub include_details: Option<bool>,
+    pub start: Option<SeekPosition>,
+    #[schemars(description = "End of the looped segment (default: the end of the media)")]
+    pub stop: Option<SeekPosition>,
+    #[schemars(
+        description = "Times the segment plays in all, the first included (default: until looping is turned off)"
+    )]
+    pub count: Option<u32>,
+    #[schemars(
+        description = "Set to false to turn looping off; the current play is then the last (default: true)"
+    )]
+    pub enabled: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
-pub struct ValidatePipelineParams {
+pub struct AddElementParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
     #[schemars(
-        description = "Pipeline description in gst-launch syntax to validate (e.g., 'filesrc location=video.mp4 ! decodebin ! autovideosink')"
+        description = "Element in gst-launch syntax (e.g., 'videoscale name=scale', 'capsfilter caps=video/x-raw,width=640'), or a chain joined with '!'"
     )]
-    pub pipeline_description: String,
+    pub element: String,
+    #[schemars(
+        description = "Upstream element to link from, as 'element' or 'element.pad' (e.g., 'tee0' for a new tee branch)"
+    )]
+    pub after: Option<String>,
+    #[schemars(
+        description = "Downstream element to link to, as 'element' or 'element.pad'. When after and before are linked, the new element is spliced between them"
+    )]
+    pub before: Option<String>,
 }
 
-#[derive(Clone)]
-pub struct GStreamerHandler {
-    pub config: Arc<RwLock<Configuration>>,
-    pub cache: Arc<DiscoveryCache>,
-    pub pipeline_manager: Arc<PipelineManager>,
-    pub tool_registry: Arc<ToolRegistry>,
-    pub enabled_tools: Arc<RwLock<std::collections::HashSet<String>>>,
-    tool_router: ToolRouter<GStreamerHandler>,
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RemoveElementParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(description = "Name of the element to remove")]
+    pub element: String,
+    #[schemars(
+        description = "Link the element's upstream and downstream neighbours to each other (default: true)"
+    )]
+    pub relink: Option<bool>,
 }
 
-#[tool_router]
-impl GStreamerHandler {
-    pub async fn new() -> crate::Result<Self> {
-        let config = Configuration::default();
-        let cache = DiscoveryCache::new();
-        let pipeline_manager = PipelineManager::new(10); // Max 10 concurrent pipelines
-        let tool_registry = Arc::new(ToolRegistry::new());
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct LinkElementsParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Source as 'element' or 'element.pad' (e.g., 'tee0.src_1'). A linked pad is moved off its current peer"
+    )]
+    pub src: String,
+    #[schemars(description = "Sink as 'element' or 'element.pad'; the pad must be unlinked")]
+    pub sink: String,
+}
 
-        // Get enabled tools based on default configuration
-        let enabled_tools = tool_registry.filter_tools(
-            &config.operational_mode,
-            config.included_tools.as_deref(),
-            config.excluded_tools.as_deref(),
-        );
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetElementStatesParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(description = "Include the pads of each element (default: true)")]
+    pub include_pads: Option<bool>,
+}
 
-        Ok(Self {
-            config: Arc::new(RwLock::new(config)),
-            cache: Arc::new(cache),
-            pipeline_manager: Arc::new(pipeline_manager),
-            tool_registry,
-            enabled_tools: Arc::new(RwLock::new(enabled_tools)),
-            tool_router: Self::tool_router(),
-        })
-    }
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ListPipelineElementsParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(description = "Include the pads of each element and their caps (default: true)")]
+    pub include_pads: Option<bool>,
+    #[schemars(
+        description = "Include the properties set away from their defaults (default: true)"
+    )]
+    pub include_properties: Option<bool>,
+}
 
-    pub async fn with_config(config: Configuration) -> crate::Result<Self> {
-        let cache = DiscoveryCache::new();
-        let pipeline_manager = PipelineManager::new(10); // Max 10 concurrent pipelines
-        let tool_registry = Arc::new(ToolRegistry::new());
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetPipelineDiagnosticsParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Include the DOT graph and element states of each report (default: true)"
+    )]
+    pub include_details: Option<bool>,
+}
 
-        // Get enabled tools based on configuration
-        let enabled_tools = tool_registry.filter_tools(
-            &config.operational_mode,
-            config.included_tools.as_deref(),
-            config.excluded_tools.as_deref(),
-        );
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetPipelineLatencyParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+}
 
-        Ok(Self {
-            config: Arc::new(RwLock::new(config)),
-            cache: Arc::new(cache),
-            pipeline_manager: Arc::new(pipeline_manager),
-            tool_registry,
-            enabled_tools: Arc::new(RwLock::new(enabled_tools)),
-            tool_router: Self::tool_router(),
-        })
-    }
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetPipelineStatusParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Include recent bus messages (errors, warnings, info) in the response (default: false)"
+    )]
+    pub include_messages: Option<bool>,
+    #[schemars(
+        description = "Subscriber whose message filter is applied to included messages (default: 'default'). See gst_set_message_filter"
+    )]
+    pub subscriber: Option<String>,
+}
 
-    /// Check if a tool is enabled based on current configuration
-    async fn is_tool_enabled(&self, tool_name: &str) -> bool {
-        self.enabled_tools.read().await.contains(tool_name)
-    }
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct StopPipelineParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Force termination even if pipeline is processing (default: false). Skips the EOS drain and abandons a hanging teardown. Use with caution"
+    )]
+    pub force: Option<bool>,
+    #[schemars(
+        description = "Milliseconds to wait for the EOS drain, or for the forced teardown (default: 5000)"
+    )]
+    pub timeout_ms: Option<u64>,
+}
 
-    #[tool(
-        description = "Lists all available GStreamer elements with optional filtering. Accepts name filter and category filter (both optional). Returns element names, descriptions, plugin sources, and rank values."
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct StopAllPipelinesParams {
+    #[schemars(description = "Skip the EOS drain and abandon hanging teardowns (default: false)")]
+    pub force: Option<bool>,
+    #[schemars(
+        description = "Milliseconds to wait for each pipeline's EOS drain or forced teardown; pipelines stop in parallel (default: 5000)"
     )]
-    async fn gst_list_elements(
-        &self,
-        Parameters(params): Parameters<ListElementsParams>,
-    ) -> Result<CallToolResult, McpError> {
-        // Check if tool is enabled
-        if !self.is_tool_enabled("gst_list_elements").await {
-            return Err(McpError::new(
-                ErrorCode::METHOD_NOT_FOUND,
-                format!("Tool 'gst_list_elements' is not available in the current mode"),
-                None::<serde_json::Value>,
-            ));
-        }
+    pub timeout_ms: Option<u64>,
+    #[schemars(
+        description = "Only stop pipelines with all of these labels, e.g. {\"purpose\": \"preview\"}"
+    )]
+    pub labels: Option<std::collections::BTreeMap<String, String>>,
+    #[schemars(description = "Only stop pipelines belonging to this owner")]
+    pub owner: Option<String>,
+    #[schemars(
+        description = "Also stop the pipelines of running jobs, failing those jobs (default: false; use gst_cancel_job instead)"
+    )]
+    pub include_jobs: Option<bool>,
+}
 
-        let elements = if self.config.read().await.cache_enabled {
-            self.cache.get_elements().await
-        } else {
-            discover_all_elements()
-        }
-        .map_err(Into::<McpError>::into)?;
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct WaitForStateParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(description = "State to wait for: 'null', 'ready', 'paused' or 'playing'")]
+    pub state: String,
+    #[schemars(description = "Milliseconds to wait at most (default: 5000)")]
+    pub timeout_ms: Option<u64>,
+}
 
-        let mut filtered_elements = elements;
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct WaitForMessageParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Message types to wait for, any of them ends the wait: eos, error, warning, info, element, application, state_changed, async_done, stream_start, buffering, tag, latency, duration_changed, segment_done, qos, clock_lost (default: [\"eos\", \"error\"])"
+    )]
+    pub message_types: Option<Vec<String>>,
+    #[schemars(
+        description = "Glob matched against the structure name of element and application messages, e.g. 'level' or 'GstMultiFileSink'"
+    )]
+    pub structure_name: Option<String>,
+    #[schemars(
+        description = "Glob matched against the posting element name, or its full object path if the pattern contains '/' (default: any element; state changes of the pipeline only)"
+    )]
+    pub source: Option<String>,
+    #[schemars(description = "Milliseconds to wait at most (default: 30000)")]
+    pub timeout_ms: Option<u64>,
+    #[schemars(
+        description = "Also match the messages recorded since the last launch, seek or state change, so EOS or an error posted before the call is not missed (default: true)"
+    )]
+    pub include_recorded: Option<bool>,
+}
 
-        // Apply name filter if provided
-        if let Some(filter) = params.filter {
-            let filter_lower = filter.to_lowercase();
-            filtered_elements.retain(|e| e.name.to_lowercase().contains(&filter_lower));
-        }
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RestartPipelineParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Resume a seekable pipeline at its last known position (default: true)"
+    )]
+    pub restore_position: Option<bool>,
+    #[schemars(
+        description = "State to restart in: 'ready', 'paused' or 'playing' (default: the state it was in, or 'playing' if stopped)"
+    )]
+    pub state: Option<String>,
+    #[schemars(
+        description = "Milliseconds to wait for the new pipeline to preroll before seeking (default: 10000)"
+    )]
+    pub timeout_ms: Option<u64>,
+}
 
-        // Apply category filter if provided
-        if let Some(category) = params.category {
-            let category_lower = category.to_lowercase();
-            filtered_elements.retain(|e| e.classification.to_lowercase().contains(&category_lower));
-        }
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SendEosParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Wait for EOS to reach the sinks, so recordings are finalized (default: true)"
+    )]
+    pub wait: Option<bool>,
+    #[schemars(description = "Milliseconds to wait for EOS (default: 10000)")]
+    pub timeout_ms: Option<u64>,
+}
 
-        let output = if filtered_elements.is_empty() {
-            "No elements found matching the criteria.".to_string()
-        } else {
-            let mut output = format!("Found {} elements:\n\n", filtered_elements.len());
-            for element in filtered_elements {
-                output.push_str(&format!(
-                    "- {} ({})\n  Plugin: {}, Rank: {}\n",
-                    element.name, element.description, element.plugin_name, element.rank
-                ));
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SendEventParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Event to send: 'flush_start', 'flush_stop', 'latency', 'custom_upstream', 'custom_downstream', 'custom_downstream_oob' or 'custom_both'"
+    )]
+    pub event: String,
+    #[schemars(
+        description = "Name of the element to send the event to (default: the pipeline, which passes upstream events to its sinks and downstream events to its sources)"
+    )]
+    pub element: Option<String>,
+    #[schemars(description = "For flush_stop: restart the running time at zero (default: true)")]
+    pub reset_time: Option<bool>,
+    #[schemars(
+        description = "For latency: latency in nanoseconds to configure; without it the pipeline or bin recalculates its latency"
+    )]
+    pub latency_ns: Option<u64>,
+    #[schemars(
+        description = "For custom events: name of the structure the event carries (e.g., 'split-now')"
+    )]
+    pub structure_name: Option<String>,
+    #[schemars(
+        description = "For custom events: fields of the structure as a JSON object. Whole numbers become int, arrays GstArray, and objects with a 'name' nested structures"
+    )]
+    pub fields: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ListGstPipelinesParams {
+    #[schemars(
+        description = "Include detailed information about each pipeline (state, duration, messages) (default: false)"
+    )]
+    pub include_details: Option<bool>,
+    #[schemars(
+        description = "Also list pipelines of other gstreamer-mcp instances sharing the state directory (read-only) (default: false)"
+    )]
+    pub include_remote: Option<bool>,
+    #[schemars(
+        description = "Only list pipelines in this state: 'playing', 'paused', 'prerolled' (held ready by preroll_only), 'ready' or 'null'"
+    )]
+    pub state: Option<String>,
+    #[schemars(
+        description = "Only list pipelines that have all these labels with these values, e.g. {\"camera\": \"front\"}"
+    )]
+    pub labels: Option<std::collections::BTreeMap<String, String>>,
+    #[schemars(description = "Only list pipelines belonging to this owner")]
+    pub owner: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SetPipelineMetadataParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "JSON object merged into the pipeline's metadata; keys set to null are removed"
+    )]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    #[schemars(
+        description = "Replace all metadata with this object instead of merging (default: false)"
+    )]
+    pub replace: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ValidatePipelineParams {
+    #[schemars(
+        description = "Pipeline description in gst-launch syntax to validate (e.g., 'filesrc location=video.mp4 ! decodebin ! autovideosink')"
+    )]
+    pub pipeline_description: String,
+    #[schemars(
+        description = "Validate with unknown element names replaced by their closest registry match (default: false)"
+    )]
+    pub auto_correct: Option<bool>,
+    #[schemars(
+        description = "Also bring the pipeline to PAUSED, with sinks replaced by fakesinks and test sources on unlinked inputs, and report the elements decodebin, encodebin and similar bins plug in (default: false)"
+    )]
+    pub deep: Option<bool>,
+    #[schemars(
+        description = "Validate with queues inserted at the start of tee and demuxer branches that lack one (default: false)"
+    )]
+    pub auto_queue: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct BuildEncodingProfileParams {
+    #[serde(flatten)]
+    pub profile: EncodingProfileSpec,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetAdaptiveStatsParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Optional name of the adaptive demuxer element (e.g., 'hlsdemux0'). Defaults to all adaptive demuxers in the pipeline"
+    )]
+    pub element_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SetVariantParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Optional name of the adaptive demuxer element (e.g., 'hlsdemux0'). Defaults to all adaptive demuxers in the pipeline"
+    )]
+    pub element_name: Option<String>,
+    #[schemars(
+        description = "Bandwidth of the variant to pin in bits per second (e.g., 2500000). Omit or use 0 to restore automatic selection"
+    )]
+    pub bitrate: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetPropertyParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Property path: element::property, or through child proxies and object properties (e.g., 'playbin0::video-sink::sync', 'mix::sink_*::alpha'). Element and child names may contain * and ? wildcards"
+    )]
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SetPropertyParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Property path: element::property, or through child proxies and object properties (e.g., 'playbin0::video-sink::sync', 'mix::sink_*::alpha'). With wildcards the value is applied to every match"
+    )]
+    pub path: String,
+    #[schemars(
+        description = "New value in gst-launch syntax (e.g., 'false', '0.5', 'ultrafast'), or with units converted to the property's own (e.g., '2Mbps', '500ms', '48kHz', '4MiB', '50%', '29.97', '1920x1080' for caps)"
+    )]
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RouteAudioParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Optional name of the audiomixmatrix element (e.g., 'router'). Defaults to the first one in the pipeline"
+    )]
+    pub element_name: Option<String>,
+    #[schemars(
+        description = "Routes from input to output channels, e.g. [{\"input\": 2, \"output\": 0}, {\"input\": 3, \"output\": 1, \"gain_db\": -3}]. Outputs without a route are silent"
+    )]
+    pub routes: Vec<AudioRoute>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct MeasureBitrateParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Pad to measure as 'element.pad' (e.g., 'x264enc0.src', 'udpsink0.sink'). A bare element name measures its 'src' pad"
+    )]
+    pub pad: String,
+    #[schemars(description = "Measurement window in milliseconds (default: 5000, max: 60000)")]
+    pub window_ms: Option<u64>,
+    #[schemars(description = "Sampling interval in milliseconds (default: 1000)")]
+    pub interval_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct AddProbeParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Pad to watch as 'element.pad' (e.g., 'tee0.src_1', 'mux.video_0'). A bare element name watches its 'src' pad"
+    )]
+    pub pad: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetProbeStatsParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(description = "Pad of the probe as 'element.pad' (default: all probes)")]
+    pub pad: Option<String>,
+    #[schemars(description = "Remove the probes after reading them (default: false)")]
+    pub remove: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct TapMetadataParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Pad to tap as 'element.pad' (e.g., 'detector.src', 'tsdemux0.private_0_0042'). A bare element name taps its 'src' pad"
+    )]
+    pub pad: String,
+    #[schemars(description = "Tap duration in milliseconds (default: 5000, max: 60000)")]
+    pub window_ms: Option<u64>,
+    #[schemars(description = "Maximum number of events collected (default: 100)")]
+    pub max_events: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ReadTimecodeParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Pad to read from as 'element.pad' (e.g., 'timecode0.src'). When omitted, reads the last frame rendered by the sinks"
+    )]
+    pub pad: Option<String>,
+    #[schemars(
+        description = "How long to wait for a timecoded frame on the pad in milliseconds (default: 2000, max: 30000)"
+    )]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ScheduleActionParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Action to run: {\"type\": \"switch_input\", \"selector\": \"sel\", \"pad\": \"sink_1\"}, {\"type\": \"start_recording\", \"valve\": \"rec\"}, {\"type\": \"stop_recording\", \"valve\": \"rec\"} or {\"type\": \"set_property\", \"element\": \"...\", \"property\": \"...\", \"value\": \"...\"}"
+    )]
+    pub action: ScheduledAction,
+    #[schemars(description = "Running time in milliseconds at which to run the action")]
+    pub running_time_ms: Option<u64>,
+    #[schemars(
+        description = "SMPTE timecode 'HH:MM:SS:FF' at which to run the action, for timecoded video (instead of running_time_ms)"
+    )]
+    pub timecode: Option<String>,
+    #[schemars(
+        description = "Pad whose buffers trigger the action, as 'element.pad'. Defaults to the active input of the selector, or the input of the valve or element"
+    )]
+    pub pad: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ListScheduledActionsParams {
+    #[schemars(description = "Only list the actions of this pipeline")]
+    pub pipeline_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct CancelScheduledActionParams {
+    #[schemars(description = "Scheduled action identifier returned by gst_schedule_action")]
+    pub action_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SetPrerecordParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Name of the valve in front of the recording branch; a queue must feed it"
+    )]
+    pub valve: String,
+    #[schemars(
+        description = "Milliseconds of data from before its start that a recording started at the valve includes; 0 disarms the buffer"
+    )]
+    pub pre_record_ms: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct AnimatePropertyParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Numeric property to ramp, as 'element::property' or 'element::pad::property' (e.g., 'vol::volume', 'mix::sink_1::xpos'); wildcards ramp every match"
+    )]
+    pub path: String,
+    #[schemars(description = "Value to start from (default: the current value)")]
+    pub from: Option<f64>,
+    #[schemars(description = "Value to end at")]
+    pub to: f64,
+    #[schemars(description = "Length of the ramp in milliseconds")]
+    pub duration_ms: u64,
+    #[schemars(
+        description = "How the value moves: 'linear' (default), 'cubic' (easing in and out) or 'step' (jump at the end)"
+    )]
+    pub interpolation: Option<Interpolation>,
+    #[schemars(
+        description = "Stream time in milliseconds to start the ramp at (default: the current position)"
+    )]
+    pub start_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct CancelAnimationParams {
+    #[schemars(description = "Animation identifier returned by gst_animate_property")]
+    pub animation_id: String,
+    #[schemars(
+        description = "Set the end value instead of leaving the property where the ramp stopped (default: false)"
+    )]
+    pub finish: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetBusMessagesParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Message types to return (e.g., ['Error', 'Eos', 'StateChanged']). Omit for all types"
+    )]
+    pub types: Option<Vec<String>>,
+    #[schemars(description = "Minimum severity: 'debug', 'info', 'warning', or 'error'")]
+    pub min_severity: Option<MessageSeverity>,
+    #[schemars(
+        description = "Glob matched against the source element name (e.g., 'udpsrc*'), or the full object path if it contains '/'"
+    )]
+    pub source: Option<String>,
+    #[schemars(description = "Only messages at or after this RFC 3339 time")]
+    pub since: Option<String>,
+    #[schemars(description = "Only messages before this RFC 3339 time")]
+    pub until: Option<String>,
+    #[schemars(
+        description = "Only messages after this cursor, the next_cursor of a previous call (default: from the oldest message kept)"
+    )]
+    pub cursor: Option<u64>,
+    #[schemars(description = "Messages per page (default: 50, max: 500)")]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetPipelineLogParams {
+    #[schemars(description = "Pipeline identifier; its log is kept after the pipeline is stopped")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Number of records from the end of the log (default: 100, max: 1000)"
+    )]
+    pub lines: Option<usize>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SetMessageFilterParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Subscriber the filter belongs to (default: 'default'). Each subscriber has at most one filter per pipeline"
+    )]
+    pub subscriber: Option<String>,
+    #[schemars(
+        description = "Message types to keep (e.g., ['Error', 'Eos', 'StateChanged']). Omit to keep all types"
+    )]
+    pub types: Option<Vec<String>>,
+    #[schemars(description = "Minimum severity to keep: 'debug', 'info', 'warning', or 'error'")]
+    pub min_severity: Option<MessageSeverity>,
+    #[schemars(
+        description = "Glob matched against the source element name (e.g., 'udpsrc*'), or the full object path if it contains '/'"
+    )]
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RemoveMessageFilterParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(description = "Subscriber whose filter should be removed (default: 'default')")]
+    pub subscriber: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SubscribeEventsParams {
+    #[schemars(
+        description = "Only push events of this pipeline; omit for every pipeline, including those launched later"
+    )]
+    pub pipeline_id: Option<String>,
+    #[schemars(
+        description = "Events to push: 'error', 'warning', 'eos', 'state_changed' (default: error, eos, state_changed)"
+    )]
+    pub events: Option<Vec<BusEventKind>>,
+    #[schemars(
+        description = "Subscription identifier, to replace an existing subscription (default: generated)"
+    )]
+    pub subscription_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct UnsubscribeEventsParams {
+    #[schemars(description = "Subscription identifier returned by gst_subscribe_events")]
+    pub subscription_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetToolMetricsParams {
+    #[schemars(description = "Only report metrics for this tool (e.g., 'gst_launch_pipeline')")]
+    pub tool: Option<String>,
+    #[schemars(description = "Clear all collected metrics after reporting (default: false)")]
+    pub reset: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SubmitJobParams {
+    #[schemars(
+        description = "Pipeline description in gst-launch syntax that ends with EOS (e.g., 'filesrc location=in.mp4 ! decodebin ! x264enc ! mp4mux ! filesink location=out.mp4')"
+    )]
+    pub pipeline_description: String,
+    #[schemars(
+        description = "Priority; higher runs first, equal priorities run in submission order (default: 0)"
+    )]
+    pub priority: Option<i32>,
+    #[schemars(description = "Optional custom job ID. If not provided, a UUID will be generated")]
+    pub job_id: Option<String>,
+    #[schemars(
+        description = "Upload the job's output files to a configured target (S3 or HTTP PUT) once it completes"
+    )]
+    pub upload: Option<UploadRequest>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ListJobsParams {
+    #[schemars(
+        description = "Only list jobs with this status (queued, running, completed, failed, cancelled)"
+    )]
+    pub status: Option<JobStatus>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct CancelJobParams {
+    #[schemars(description = "Job identifier returned by gst_submit_job")]
+    pub job_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ListArtifactsParams {
+    #[schemars(description = "Only list artifacts produced by this pipeline or job")]
+    pub pipeline_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct PruneArtifactsParams {
+    #[schemars(description = "Prune artifacts older than this many hours")]
+    pub max_age_hours: Option<u64>,
+    #[schemars(
+        description = "Keep the newest artifacts whose combined size fits in this many bytes and prune the rest"
+    )]
+    pub max_total_bytes: Option<u64>,
+    #[schemars(description = "Keep only this many of the newest artifacts")]
+    pub keep_latest: Option<usize>,
+    #[schemars(description = "Only consider artifacts produced by this pipeline or job")]
+    pub pipeline_id: Option<String>,
+    #[schemars(
+        description = "Delete the files from disk, not just the registry entries (default: false)"
+    )]
+    pub delete_files: Option<bool>,
+    #[schemars(
+        description = "Report what would be pruned without changing anything (default: false)"
+    )]
+    pub dry_run: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ConcatMediaParams {
+    #[schemars(description = "Paths of the files to join, in order (at least two)")]
+    pub inputs: Vec<String>,
+    #[schemars(
+        description = "Path of the joined file; its extension selects the container (mp4, mov, mkv, webm, ts, m4a, ogg)"
+    )]
+    pub output: String,
+    #[schemars(
+        description = "Re-encode even when the inputs could be joined without it (default: false)"
+    )]
+    pub reencode: Option<bool>,
+    #[schemars(
+        description = "Tone-map HDR (PQ/HLG) video to SDR BT.709: true for every input, false never (default: inputs detected as HDR, since the output encoders are 8-bit SDR)"
+    )]
+    pub tonemap: Option<bool>,
+    #[schemars(
+        description = "Downmix multichannel audio (e.g. 5.1) to stereo with ITU-R BS.775 coefficients instead of keeping every channel (default: false)"
+    )]
+    pub downmix: Option<bool>,
+    #[schemars(description = "Job priority; higher runs first (default: 0)")]
+    pub priority: Option<i32>,
+    #[schemars(description = "Optional custom job ID. If not provided, a UUID will be generated")]
+    pub job_id: Option<String>,
+    #[schemars(description = "Upload the joined file to a configured target once it is written")]
+    pub upload: Option<UploadRequest>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct NormalizeAudioParams {
+    #[schemars(description = "Path of the file to normalize")]
+    pub input: String,
+    #[schemars(
+        description = "Path of the normalized file; its extension selects the container (e.g., m4a, ogg, mp4, mkv)"
+    )]
+    pub output: String,
+    #[schemars(
+        description = "Target integrated loudness in LUFS (default: -23, EBU R128; streaming services often use -14 or -16)"
+    )]
+    pub target_lufs: Option<f64>,
+    #[schemars(
+        description = "Maximum true peak after normalization in dBTP (default: -1). Limits the gain for very dynamic material"
+    )]
+    pub true_peak_limit: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ExtractFrameAtParams {
+    #[schemars(description = "Path of the video file")]
+    pub input: String,
+    #[schemars(description = "Path of the image; its extension selects the format (png, jpg)")]
+    pub output: String,
+    #[schemars(description = "Presentation timestamp of the frame in nanoseconds")]
+    pub position_ns: u64,
+    #[schemars(
+        description = "Decode up to the exact frame at the timestamp instead of exporting the keyframe at or before it (default: true)"
+    )]
+    pub accurate: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ExtractAudioSegmentParams {
+    #[schemars(description = "Path of a media file to decode the segment from")]
+    pub input: Option<String>,
+    #[schemars(description = "Running pipeline to record the segment from, instead of a file")]
+    pub pipeline_id: Option<String>,
+    #[schemars(
+        description = "Pad of the running pipeline as 'element.pad' (e.g., 'audioconvert0.src', 'aacparse0.src'). Raw audio is written as WAV, encoded audio as-is"
+    )]
+    pub pad: Option<String>,
+    #[schemars(description = "Start of the segment in the file in nanoseconds (default: 0)")]
+    pub start_ns: Option<u64>,
+    #[schemars(description = "Segment length in milliseconds (default: 10000, max: 30000)")]
+    pub duration_ms: Option<u64>,
+    #[schemars(description = "Sample rate of the decoded file audio (default: the file's)")]
+    pub sample_rate: Option<u32>,
+    #[schemars(description = "Channel count of the decoded file audio (default: the file's)")]
+    pub channels: Option<u32>,
+    #[schemars(
+        description = "Path to write the segment to (default: a file in the temporary directory)"
+    )]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct AnalyzeGopParams {
+    #[schemars(description = "Path of the video file")]
+    pub input: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct BitrateProfileParams {
+    #[schemars(description = "Path of the media file")]
+    pub input: String,
+    #[schemars(description = "Sample interval in milliseconds (default: 1000)")]
+    pub interval_ms: Option<u64>,
+    #[schemars(
+        description = "Streaming target in bits per second; intervals above it are reported with re-encoding recommendations"
+    )]
+    pub target_bitrate: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct VerifyFileParams {
+    #[schemars(description = "Path of the media file to check")]
+    pub input: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct DetectInterlacingParams {
+    #[schemars(
+        description = "Input file path or URI (e.g., '/videos/broadcast.ts', 'srt://encoder:9000')"
+    )]
+    pub input: String,
+    #[schemars(description = "Number of video frames to sample (default: 100)")]
+    pub frames: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ChangeSpeedParams {
+    #[schemars(description = "Path of the file to retime")]
+    pub input: String,
+    #[schemars(
+        description = "Path of the new file; its extension selects the container (e.g., mp4, mkv, m4a, ogg)"
+    )]
+    pub output: String,
+    #[schemars(
+        description = "Speed factor between 0.25 and 4.0 (e.g., 2.0 plays twice as fast, 0.5 at half speed)"
+    )]
+    pub rate: f64,
+    #[schemars(
+        description = "Keep the original audio pitch (default: true). When false, audio pitch changes with the speed"
+    )]
+    pub preserve_pitch: Option<bool>,
+    #[schemars(
+        description = "Tone-map HDR (PQ/HLG) video to SDR BT.709: true for every input, false never (default: inputs detected as HDR, since the output encoders are 8-bit SDR)"
+    )]
+    pub tonemap: Option<bool>,
+    #[schemars(
+        description = "Downmix multichannel audio (e.g. 5.1) to stereo with ITU-R BS.775 coefficients instead of keeping every channel (default: false)"
+    )]
+    pub downmix: Option<bool>,
+    #[schemars(description = "Job priority; higher runs first (default: 0)")]
+    pub priority: Option<i32>,
+    #[schemars(description = "Optional custom job ID. If not provided, a UUID will be generated")]
+    pub job_id: Option<String>,
+    #[schemars(description = "Upload the new file to a configured target once it is written")]
+    pub upload: Option<UploadRequest>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct CreateAbrLadderParams {
+    #[schemars(
+        description = "Input file path or URI (e.g., '/videos/talk.mp4', 'rtsp://camera/stream')"
+    )]
+    pub input: String,
+    #[schemars(description = "Directory receiving the playlists/manifest and segments")]
+    pub output_dir: String,
+    #[schemars(description = "Packaging format: 'hls' (default) or 'dash'")]
+    pub format: Option<AbrFormat>,
+    #[schemars(
+        description = "Quality levels to encode (default: 1080p 5000k, 720p 2800k, 480p 1400k, 360p 800k). Renditions larger than a file input are skipped"
+    )]
+    pub renditions: Option<Vec<AbrRendition>>,
+    #[schemars(description = "Audio bitrate in kbit/s shared by all renditions (default: 128)")]
+    pub audio_kbps: Option<u32>,
+    #[schemars(
+        description = "Include audio (default: true). Detected automatically for file inputs; set to false for URIs without audio"
+    )]
+    pub audio: Option<bool>,
+    #[schemars(
+        description = "Tone-map HDR (PQ/HLG) video to SDR BT.709 (default: when a file input is HDR). Set to true for HDR URIs, which are not probed"
+    )]
+    pub tonemap: Option<bool>,
+    #[schemars(
+        description = "Downmix multichannel audio (e.g. 5.1) to stereo with ITU-R BS.775 coefficients instead of keeping every channel (default: false)"
+    )]
+    pub downmix: Option<bool>,
+    #[schemars(description = "Target segment duration in seconds (default: 6)")]
+    pub segment_duration: Option<u32>,
+    #[schemars(
+        description = "Optional custom pipeline ID. If not provided, a UUID will be generated"
+    )]
+    pub pipeline_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ListNdiSourcesParams {
+    #[schemars(
+        description = "How long to listen for source announcements in milliseconds (default: 2000)"
+    )]
+    pub wait_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct LaunchNdiParams {
+    #[schemars(
+        description = "'receive' to play an NDI source, 'send' to publish local sources over NDI"
+    )]
+    pub direction: NdiDirection,
+    #[schemars(
+        description = "NDI source name: the source to receive (see gst_list_ndi_sources) or the name to publish under"
+    )]
+    pub ndi_name: String,
+    #[schemars(
+        description = "Video chain in gst-launch syntax: sinks when receiving (default: 'videoconvert ! autovideosink'), a source when sending (default: 'videotestsrc is-live=true'). Empty string to leave out video when receiving"
+    )]
+    pub video: Option<String>,
+    #[schemars(
+        description = "Audio chain in gst-launch syntax: sinks when receiving (e.g., 'audioconvert ! autoaudiosink'), a source when sending (e.g., 'audiotestsrc is-live=true'). Default: no audio"
+    )]
+    pub audio: Option<String>,
+    #[schemars(description = "Sender address (IP:port) to connect to directly when receiving")]
+    pub url_address: Option<String>,
+    #[schemars(
+        description = "Receive bandwidth: 'highest' (default), 'lowest', 'audio-only' or 'metadata-only'"
+    )]
+    pub bandwidth: Option<NdiBandwidth>,
+    #[schemars(description = "Whether to start the pipeline immediately (default: true)")]
+    pub auto_play: Option<bool>,
+    #[schemars(
+        description = "Optional custom pipeline ID. If not provided, a UUID will be generated"
+    )]
+    pub pipeline_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct ListAravisCamerasParams {
+    #[schemars(
+        description = "Open each camera to report pixel formats, sizes, exposure and gain (default: false). Fails for cameras streaming elsewhere"
+    )]
+    pub details: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct LaunchAravisParams {
+    #[schemars(
+        description = "Camera name from gst_list_aravis_cameras (e.g., 'Basler-acA1300-60gm-21234567'). Defaults to the first camera found"
+    )]
+    pub camera_name: Option<String>,
+    #[serde(flatten)]
+    pub settings: AravisSettings,
+    #[schemars(
+        description = "Downstream chain in gst-launch syntax (default: 'videoconvert ! autovideosink')"
+    )]
+    pub sink: Option<String>,
+    #[schemars(description = "Whether to start the pipeline immediately (default: true)")]
+    pub auto_play: Option<bool>,
+    #[schemars(
+        description = "Optional custom pipeline ID. If not provided, a UUID will be generated"
+    )]
+    pub pipeline_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct AttachTranscriberParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Name of the tee carrying raw audio to transcribe. Optional when the pipeline has a single tee"
+    )]
+    pub tee_name: Option<String>,
+    #[schemars(
+        description = "Speech-to-text element to use (e.g., 'vosktranscriber'). Defaults to the first installed of whisper, vosktranscriber, speechmaticstranscriber, awstranscriber"
+    )]
+    pub transcriber: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct AttachDetectorParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Name of the tee carrying raw video to analyse. Optional when the pipeline has a single tee"
+    )]
+    pub tee_name: Option<String>,
+    #[schemars(
+        description = "Detector element (e.g., 'gvadetect') or a gst-launch chain attaching region of interest metadata. Defaults to the first installed of gvadetect, facedetect"
+    )]
+    pub detector: Option<String>,
+    #[schemars(description = "Model file loaded by the detector element")]
+    pub model: Option<String>,
+    #[schemars(
+        description = "Only report these labels (e.g., ['person']). Reports every label when omitted"
+    )]
+    pub labels: Option<Vec<String>>,
+    #[schemars(description = "Minimum confidence (0.0-1.0) of reported detections")]
+    pub min_confidence: Option<f64>,
+    #[schemars(
+        description = "Minimum interval between detection notifications in milliseconds (default: 1000)"
+    )]
+    pub interval_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SpeakParams {
+    #[schemars(description = "Text to speak")]
+    pub text: String,
+    #[schemars(
+        description = "Optional WAV file to write the speech to. Plays on the default audio device when omitted"
+    )]
+    pub output: Option<String>,
+    #[schemars(
+        description = "Optional custom pipeline ID. If not provided, a UUID will be generated"
+    )]
+    pub pipeline_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct EstimateJobParams {
+    #[schemars(
+        description = "Transcode pipeline in gst-launch syntax, as it would be passed to gst_submit_job"
+    )]
+    pub pipeline_description: String,
+    #[schemars(description = "Seconds of media to encode as a sample (default: 10)")]
+    pub sample_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SetErrorBudgetParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[serde(flatten)]
+    pub budget: ErrorBudget,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct MonitorAudioLevelsParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Name of the tee carrying the audio to meter. Optional when the pipeline has a single tee"
+    )]
+    pub tee_name: Option<String>,
+    #[serde(flatten)]
+    pub thresholds: AudioThresholds,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SwitchEncoderParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(description = "Name of the running encoder element to replace")]
+    pub encoder: String,
+    #[schemars(
+        description = "Replacement encoder in gst-launch syntax, e.g. 'nvh264enc bitrate=4000' or 'videoconvert ! vaapih264enc'"
+    )]
+    pub new_encoder: String,
+    #[schemars(
+        description = "How long both encoders run side by side before switching, in milliseconds (default: 1000)"
+    )]
+    pub warmup_ms: Option<u64>,
+    #[schemars(
+        description = "How long to wait for the splice and for a keyframe of the new encoder, in milliseconds (default: 10000)"
+    )]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct StartClockServerParams {
+    #[schemars(description = "UDP port to serve the clock on (default: 8555)")]
+    pub port: Option<u16>,
+    #[schemars(description = "Address to bind to (default: all interfaces)")]
+    pub address: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct SyncPipelineParams {
+    #[schemars(description = "Pipeline identifier (UUID or custom ID provided during launch)")]
+    pub pipeline_id: String,
+    #[schemars(
+        description = "Host running the clock server. Omit on the host running gst_start_clock_server"
+    )]
+    pub clock_address: Option<String>,
+    #[schemars(description = "Port of the clock server (default: 8555)")]
+    pub clock_port: Option<u16>,
+    #[schemars(
+        description = "Shared base time in nanoseconds of the server clock; pass the same value to every pipeline. Defaults to a whole second just after the latency from now, to reuse on the other pipelines"
+    )]
+    pub base_time_ns: Option<u64>,
+    #[schemars(
+        description = "Latency in milliseconds, identical on every pipeline; must cover network and decoding delays (default: 500)"
+    )]
+    pub latency_ms: Option<u64>,
+    #[schemars(
+        description = "How long to wait for the clock to sync, in milliseconds (default: 5000)"
+    )]
+    pub sync_timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct GetSyncReportParams {
+    #[schemars(description = "Only report this pipeline")]
+    pub pipeline_id: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct GStreamerHandler {
+    pub config: Arc<RwLock<Configuration>>,
+    pub cache: Arc<DiscoveryCache>,
+    pub pipeline_manager: Arc<PipelineManager>,
+    pub tool_registry: Arc<ToolRegistry>,
+    pub enabled_tools: Arc<RwLock<std::collections::HashSet<String>>>,
+    pub notifier: Notifier,
+    pub metrics: ToolMetrics,
+    pub policy: Arc<RolePolicy>,
+    pub shared_state: Option<Arc<SharedState>>,
+    pub jobs: Arc<JobQueue>,
+    pub artifacts: Arc<ArtifactRegistry>,
+    pub scheduler: Scheduler,
+    pub animator: Animator,
+    pub netsync: NetSync,
+    /// Bus events the client subscribed to with `gst_subscribe_events`
+    pub events: EventSubscriptions,
+    tool_router: ToolRouter<GStreamerHandler>,
+}
+
+#[tool_router]
+impl GStreamerHandler {
+    pub async fn new() -> crate::Result<Self> {
+        Self::with_config(Configuration::default()).await
+    }
+
+    pub async fn with_config(config: Configuration) -> crate::Result<Self> {
+        let cache = Arc::new(DiscoveryCache::new());
+        if config.cache_enabled && config.warm_cache_on_start {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                let _ = cache.warm().await;
+            });
+        }
+
+        let notifier = Notifier::new();
+        if config.registry_watch_interval_seconds > 0 {
+            spawn_registry_watcher(
+                cache.clone(),
+                notifier.clone(),
+                Duration::from_secs(config.registry_watch_interval_seconds),
+            );
+        }
+
+        let metrics = ToolMetrics::new(Duration::from_millis(config.slow_call_threshold_ms));
+        let policy = RolePolicy::new(
+            config.default_role,
+            config.role_tokens.clone(),
+            config.role_categories.clone(),
+        );
+        let shared_state = match &config.shared_state_dir {
+            Some(dir) => {
+                let shared = Arc::new(SharedState::new(dir)?);
+                tracing::info!(
+                    "Sharing pipeline state in {} as {}",
+                    dir.display(),
+                    shared.instance_id()
+                );
+                Some(shared)
+            }
+            None => None,
+        };
+        let pipeline_manager = Arc::new(
+            PipelineManager::new(config.max_pipelines)
+                .with_message_rules(config.message_rules.clone())
+                .with_log_dir(config.pipeline_log_dir.clone())
+                .with_shared_state(shared_state.clone()),
+        );
+        pipeline_manager.watch_buses();
+        spawn_budget_alerts(&pipeline_manager, notifier.clone());
+        spawn_caps_alerts(&pipeline_manager, notifier.clone());
+        let events = EventSubscriptions::new();
+        spawn_bus_events(&pipeline_manager, events.clone(), notifier.clone());
+        spawn_watchdog(
+            pipeline_manager.clone(),
+            notifier.clone(),
+            DEFAULT_PREROLL_TIMEOUT,
+        );
+        spawn_deadline_enforcer(pipeline_manager.clone(), notifier.clone());
+        if config.stall_timeout_seconds > 0 {
+            spawn_stall_detector(
+                pipeline_manager.clone(),
+                notifier.clone(),
+                Duration::from_secs(config.stall_timeout_seconds),
+            );
+        }
+        let tool_registry = Arc::new(ToolRegistry::new());
+
+        if let Some(shared) = &shared_state {
+            spawn_heartbeat(shared.clone(), pipeline_manager.clone());
+        }
+
+        let artifacts = Arc::new(ArtifactRegistry::new(config.artifacts_state_path.clone())?);
+        let jobs = Arc::new(
+            JobQueue::new(
+                pipeline_manager.clone(),
+                artifacts.clone(),
+                config.max_concurrent_jobs,
+                config.jobs_state_path.clone(),
+            )?
+            .with_upload_targets(config.upload_targets.clone()),
+        );
+        jobs.spawn_scheduler();
+        if config.dead_pipeline_ttl_seconds > 0 {
+            spawn_reaper(
+                pipeline_manager.clone(),
+                jobs.clone(),
+                notifier.clone(),
+                Duration::from_secs(config.dead_pipeline_ttl_seconds),
+            );
+        }
+
+        // Get enabled tools based on configuration
+        let enabled_tools = tool_registry.filter_tools(
+            &config.operational_mode,
+            config.included_tools.as_deref(),
+            config.excluded_tools.as_deref(),
+        );
+
+        let config = Arc::new(RwLock::new(config));
+        spawn_limits_publisher(
+            pipeline_manager.clone(),
+            jobs.clone(),
+            cache.clone(),
+            config.clone(),
+            notifier.clone(),
+        );
+
+        Ok(Self {
+            config,
+            cache,
+            animator: Animator::new(pipeline_manager.clone()),
+            pipeline_manager,
+            tool_registry,
+            enabled_tools: Arc::new(RwLock::new(enabled_tools)),
+            notifier,
+            metrics,
+            policy: Arc::new(policy),
+            shared_state,
+            jobs,
+            artifacts,
+            scheduler: Scheduler::new(),
+            events,
+            netsync: NetSync::new(),
+            tool_router: Self::tool_router(),
+        })
+    }
+
+    /// Check if a tool is enabled based on current configuration
+    async fn is_tool_enabled(&self, tool_name: &str) -> bool {
+        self.enabled_tools.read().await.contains(tool_name)
+    }
+
+    /// How text outputs show timestamps, from the configuration
+    async fn time_display(&self) -> TimeDisplay {
+        let config = self.config.read().await;
+        TimeDisplay::new(config.time_zone, config.relative_times)
+    }
+
+    /// Apply a modification off the async runtime and report its links
+    async fn modify_pipeline(
+        &self,
+        pipeline_id: String,
+        modification: PipelineModification,
+    ) -> Result<CallToolResult, McpError> {
+        let pipeline_manager = self.pipeline_manager.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            pipeline_manager.modify_pipeline(&pipeline_id, &modification, MODIFY_TIMEOUT)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Modification task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!("Pipeline '{}': {}", result.pipeline_id, result.operation);
+        if let Some(element) = &result.element {
+            output.push_str(&format!(" '{}'", element));
+        }
+        output.push('\n');
+        for link in &result.linked {
+            output.push_str(&format!("  linked {}\n", link));
+        }
+        for link in &result.unlinked {
+            output.push_str(&format!("  unlinked {}\n", link));
+        }
+        let json = serde_json::to_value(&result).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    /// Publish local pipelines to other instances right away instead of
+    /// waiting for the next heartbeat
+    fn publish_shared_state(&self) {
+        if let Some(shared) = &self.shared_state {
+            if let Err(e) = shared.publish(self.pipeline_manager.list_pipelines()) {
+                tracing::warn!("Failed to publish shared pipeline state: {}", e);
+            }
+        }
+    }
+
+    /// Artifact resources are readable by roles that may call the
+    /// artifact tools
+    fn check_artifact_access(&self, context: &RequestContext<RoleServer>) -> Result<(), McpError> {
+        let role = self
+            .policy
+            .resolve(context.extensions.get::<CallerIdentity>());
+        if self.policy.allows(role, &ToolCategory::Pipeline) {
+            Ok(())
+        } else {
+            Err(McpError::new(
+                ErrorCode::INVALID_REQUEST,
+                format!("Role '{}' is not permitted to read artifacts", role),
+                None::<serde_json::Value>,
+            ))
+        }
+    }
+
+    /// Ask the operator to approve a destructive action when
+    /// `require_confirmation` is set; otherwise allow it
+    async fn confirm(&self, peer: &Peer<RoleServer>, action: &str) -> Result<(), McpError> {
+        if !self.config.read().await.require_confirmation {
+            return Ok(());
+        }
+
+        match request_confirmation(peer, action).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(McpError::new(
+                ErrorCode::INVALID_REQUEST,
+                format!("Operator did not approve the request to {}", action),
+                None::<serde_json::Value>,
+            )),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// gst_search_elements over properties, signals or caps, which need the
+    /// detailed info of every element
+    async fn search_element_details(
+        &self,
+        query: &str,
+        fields: &[SearchField],
+        max_results: usize,
+        cache_enabled: bool,
+    ) -> Result<CallToolResult, McpError> {
+        let isolated = self.config.read().await.isolate_inspection;
+        let cached = if cache_enabled {
+            Some(
+                self.cache
+                    .get_elements()
+                    .await
+                    .map_err(Into::<McpError>::into)?,
+            )
+        } else {
+            None
+        };
+        // Caps come from the factories' pad templates, without creating elements
+        let (elements, mut details) = tokio::task::spawn_blocking(move || {
+            let elements = match cached {
+                Some(elements) => elements,
+                None => discover_all_elements()?,
+            };
+            let names: Vec<String> = elements.iter().map(|e| e.name.clone()).collect();
+            let details = metadata_index(&names);
+            Ok((elements, details))
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Search task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        // Properties and signals come from the index built in the background
+        let mut index_status = None;
+        if fields.iter().any(|f| f.needs_instance()) {
+            match self.cache.search_index(isolated).await {
+                Ok(index) => details.extend(index),
+                Err(status) => index_status = Some(status),
+            }
+        }
+
+        let results = rank_detailed_elements(elements, &details, query, fields, max_results);
+
+        let mut output = if results.is_empty() {
+            format!("No elements found matching '{}'", query)
+        } else {
+            let mut output = format!("Found {} elements matching '{}':\n\n", results.len(), query);
+            for result in &results {
+                let element = &result.element;
+                output.push_str(&format!(
+                    "- {} ({})\n  Plugin: {}, Classification: {}\n",
+                    element.name, element.description, element.plugin_name, element.classification
+                ));
+                if !result.matched.is_empty() {
+                    output.push_str(&format!("  Matched: {}\n", result.matched.join(", ")));
+                }
+            }
+            output
+        };
+        if let Some(IndexStatus::Building { indexed, total }) = &index_status {
+            output.push_str(&format!(
+                "\nProperties and signals are still being indexed ({} of {} elements), so only names, descriptions and caps were searched. Try again shortly.\n",
+                indexed, total
+            ));
+        }
+
+        let mut json =
+            serde_json::json!({ "query": query, "search_in": fields, "results": results });
+        if let Some(status) = index_status {
+            json["index"] = serde_json::to_value(status).unwrap_or_default();
+        }
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Lists all available GStreamer elements with optional filtering. Accepts name, category, plugin, min_rank, has_src_pads/has_sink_pads, and media_type (audio/video/text) filters (all optional). Returns element names, descriptions, plugin sources, and rank values."
+    )]
+    async fn gst_list_elements(
+        &self,
+        Parameters(params): Parameters<ListElementsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        // Check if tool is enabled
+        if !self.is_tool_enabled("gst_list_elements").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_list_elements' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let elements = if self.config.read().await.cache_enabled {
+            self.cache.get_elements().await
+        } else {
+            discover_all_elements()
+        }
+        .map_err(Into::<McpError>::into)?;
+
+        let mut filtered_elements = elements;
+
+        // Apply name filter if provided
+        if let Some(filter) = params.filter {
+            let filter_lower = filter.to_lowercase();
+            filtered_elements.retain(|e| e.name.to_lowercase().contains(&filter_lower));
+        }
+
+        // Apply category filter if provided
+        if let Some(category) = params.category {
+            let category_lower = category.to_lowercase();
+            filtered_elements.retain(|e| e.classification.to_lowercase().contains(&category_lower));
+        }
+
+        if let Some(plugin) = &params.plugin {
+            filtered_elements.retain(|e| e.plugin_name.eq_ignore_ascii_case(plugin));
+        }
+
+        if let Some(min_rank) = &params.min_rank {
+            let min_rank = parse_rank(min_rank).ok_or_else(|| {
+                McpError::invalid_params(
+                    format!(
+                        "Invalid min_rank '{}': use none, marginal, secondary, primary, or a number",
+                        min_rank
+                    ),
+                    None,
+                )
+            })?;
+            filtered_elements.retain(|e| e.rank_value >= min_rank);
+        }
+
+        if let Some(has_src_pads) = params.has_src_pads {
+            filtered_elements.retain(|e| e.has_src_pads == has_src_pads);
+        }
+
+        if let Some(has_sink_pads) = params.has_sink_pads {
+            filtered_elements.retain(|e| e.has_sink_pads == has_sink_pads);
+        }
+
+        if let Some(media_type) = params.media_type {
+            filtered_elements.retain(|e| e.media_types.contains(&media_type));
+        }
+
+        let output = if filtered_elements.is_empty() {
+            "No elements found matching the criteria.".to_string()
+        } else {
+            let mut output = format!("Found {} elements:\n\n", filtered_elements.len());
+            for element in filtered_elements {
+                output.push_str(&format!(
+                    "- {} ({})\n  Plugin: {}, Rank: {}\n",
+                    element.name, element.description, element.plugin_name, element.rank
+                ));
+            }
+            output
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Retrieves detailed information about a specific GStreamer element. Accepts element name (required). Returns properties with types/defaults, pad templates, signals, and classification."
+    )]
+    async fn gst_inspect_element(
+        &self,
+        Parameters(params): Parameters<InspectElementParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let (isolated, cache_enabled) = {
+            let config = self.config.read().await;
+            (
+                params.isolated.unwrap_or(config.isolate_inspection),
+                config.cache_enabled,
+            )
+        };
+        let info = if cache_enabled && !params.bypass_cache.unwrap_or(false) {
+            self.cache
+                .get_element_details(&params.element_name, isolated)
+                .await
+        } else if isolated {
+            let element_name = params.element_name.clone();
+            tokio::task::spawn_blocking(move || inspect_element_isolated(&element_name))
+                .await
+                .map_err(|e| {
+                    crate::GStreamerMcpError::Other(format!("Inspection task failed: {}", e))
+                })
+                .and_then(|r| r)
+        } else {
+            inspect_element(&params.element_name)
+        }
+        .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!("Element: {}\n", info.name);
+        output.push_str(&format!("Description: {}\n", info.description));
+        output.push_str(&format!("Plugin: {}\n", info.plugin_name));
+        output.push_str(&format!("Rank: {}\n", info.rank));
+        output.push_str(&format!("Classification: {}\n", info.classification));
+        if !info.type_hierarchy.is_empty() {
+            output.push_str(&format!(
+                "Type hierarchy: {}\n",
+                info.type_hierarchy.join(" -> ")
+            ));
+        }
+        if !info.interfaces.is_empty() {
+            output.push_str(&format!("Interfaces: {}\n", info.interfaces.join(", ")));
+        }
+        output.push('\n');
+
+        if let Some(error) = &info.instantiation_error {
+            output.push_str(&format!(
+                "Note: the element could not be created ({}); properties are unavailable\n\n",
+                error
+            ));
+        }
+
+        // Properties section
+        if !info.properties.is_empty() {
+            output.push_str("Properties:\n");
+            for prop in &info.properties {
+                output.push_str(&format!(
+                    "  {} ({}) [{}]\n    {}\n",
+                    prop.name,
+                    prop.type_name,
+                    prop.flags.join(", "),
+                    prop.description
+                ));
+                if let Some(default) = &prop.default_value {
+                    output.push_str(&format!("    Default: {}\n", default));
+                }
+            }
+            output.push('\n');
+        }
+
+        // Pad templates section
+        if !info.pad_templates.is_empty() {
+            output.push_str("Pad Templates:\n");
+            for pad in &info.pad_templates {
+                output.push_str(&format!(
+                    "  {} ({}, {})\n    Caps: {}\n",
+                    pad.name, pad.direction, pad.presence, pad.caps
+                ));
+            }
+            output.push('\n');
+        }
+
+        // Signals section
+        if !info.signals.is_empty() {
+            output.push_str("Signals:\n");
+            for signal in &info.signals {
+                output.push_str(&format!(
+                    "  {} -> {}\n    Parameters: {}\n",
+                    signal.name,
+                    signal.return_type,
+                    signal.parameters.join(", ")
+                ));
+            }
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Lists all available GStreamer plugins. Accepts name filter (optional). Returns plugin names, versions, descriptions, licenses, and contained elements."
+    )]
+    async fn gst_list_plugins(
+        &self,
+        Parameters(params): Parameters<ListPluginsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let plugins = if self.config.read().await.cache_enabled {
+            self.cache.get_plugins().await
+        } else {
+            discover_all_plugins()
+        }
+        .map_err(Into::<McpError>::into)?;
+
+        let mut filtered_plugins = plugins;
+
+        // Apply filter if provided
+        if let Some(filter) = params.filter {
+            let filter_lower = filter.to_lowercase();
+            filtered_plugins.retain(|p| p.name.to_lowercase().contains(&filter_lower));
+        }
+
+        let output = if filtered_plugins.is_empty() {
+            "No plugins found matching the criteria.".to_string()
+        } else {
+            let mut output = format!("Found {} plugins:\n\n", filtered_plugins.len());
+            for plugin in filtered_plugins {
+                output.push_str(&format!(
+                    "- {} (v{}) - {}\n  License: {}, Source: {}\n",
+                    plugin.name, plugin.version, plugin.description, plugin.license, plugin.source
+                ));
+                if !plugin.elements.is_empty() {
+                    output.push_str(&format!("  Elements: {}\n", plugin.elements.join(", ")));
+                }
+                output.push('\n');
+            }
+            output
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Searches for GStreamer elements by keyword. Accepts search query (required) and search_in (optional: names, descriptions, properties, signals, caps). Returns relevance-ranked results and, for properties/signals/caps, what matched."
+    )]
+    async fn gst_search_elements(
+        &self,
+        Parameters(params): Parameters<SearchElementsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let (max_results, cache_enabled) = {
+            let config = self.config.read().await;
+            (config.max_search_results, config.cache_enabled)
+        };
+
+        let fields = params
+            .search_in
+            .clone()
+            .filter(|fields| !fields.is_empty())
+            .unwrap_or_else(|| SearchField::DEFAULT.to_vec());
+        if fields != SearchField::DEFAULT {
+            return self
+                .search_element_details(&params.query, &fields, max_results, cache_enabled)
+                .await;
+        }
+
+        let results = if cache_enabled {
+            let elements = self
+                .cache
+                .get_elements()
+                .await
+                .map_err(Into::<McpError>::into)?;
+            rank_elements(elements, &params.query, max_results)
+        } else {
+            search_elements(&params.query, max_results).map_err(Into::<McpError>::into)?
+        };
+
+        let output = if results.is_empty() {
+            format!("No elements found matching '{}'", params.query)
+        } else {
+            let mut output = format!(
+                "Found {} elements matching '{}':\n\n",
+                results.len(),
+                params.query
+            );
+            for element in results {
+                output.push_str(&format!(
+                    "- {} ({})\n  Plugin: {}, Classification: {}\n",
+                    element.name, element.description, element.plugin_name, element.classification
+                ));
+            }
+            output
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Creates and launches a GStreamer pipeline from description. Accepts gst-launch syntax, auto-play flag (default: true), custom ID (optional), auto_correct to fix element name typos (optional), and preroll_only to hold it prerolled in PAUSED (optional). Returns pipeline ID and current state; unknown elements are reported with the closest registry matches."
+    )]
+    async fn gst_launch_pipeline(
+        &self,
+        Parameters(params): Parameters<LaunchPipelineParams>,
+        peer: Peer<RoleServer>,
+        CallId(call_id): CallId,
+    ) -> Result<CallToolResult, McpError> {
+        // Check if tool is enabled
+        if !self.is_tool_enabled("gst_launch_pipeline").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_launch_pipeline' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let (description, corrections) = if params.auto_correct.unwrap_or(false) {
+            correct_element_typos(&params.pipeline_description).map_err(Into::<McpError>::into)?
+        } else {
+            (params.pipeline_description.clone(), Vec::new())
+        };
+        let limits = params.memory.clone().unwrap_or_default().or(&self
+            .config
+            .read()
+            .await
+            .memory_limits());
+        let (description, queued) = check_branch_queues(
+            &description,
+            params.auto_queue.unwrap_or(false),
+            limits.queue_leak(),
+        )
+        .map_err(Into::<McpError>::into)?;
+
+        let stop_at = match params.stop_at.as_deref() {
+            Some(text) => Some(
+                chrono::DateTime::parse_from_rfc3339(text)
+                    .map_err(|e| {
+                        McpError::invalid_params(
+                            format!("stop_at '{}' is not an RFC 3339 time: {}", text, e),
+                            None,
+                        )
+                    })?
+                    .with_timezone(&chrono::Utc),
+            ),
+            None => None,
+        };
+        let deadline = pipeline_deadline(chrono::Utc::now(), params.max_duration_seconds, stop_at)
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+
+        // Create the pipeline, or find the one launched with the same key
+        let pipeline_id = match params.dedupe_key.as_deref() {
+            Some(key) => {
+                let (pipeline_id, created) = self
+                    .pipeline_manager
+                    .create_pipeline_once(&description, params.pipeline_id, key)
+                    .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+                if !created {
+                    let state = self
+                        .pipeline_manager
+                        .get_pipeline_status(&pipeline_id)
+                        .map(|status| status.state)
+                        .unwrap_or_default();
+                    let output = format!(
+                        "Pipeline '{}' was already launched with dedupe key '{}'; no new pipeline was created.\nState: {}\nDescription: {}",
+                        pipeline_id, key, state, description
+                    );
+                    let json = serde_json::json!({
+                        "pipeline_id": pipeline_id,
+                        "state": state,
+                        "description": description,
+                        "dedupe_key": key,
+                        "deduplicated": true,
+                    });
+                    return Ok(success_with_json(output, json));
+                }
+                pipeline_id
+            }
+            None => self
+                .pipeline_manager
+                .create_pipeline(&description, params.pipeline_id)
+                .map_err(Into::<McpError>::into)?,
+        };
+        self.publish_shared_state();
+
+        // From here on, a failed launch leaves no half-configured pipeline
+        let discard = |e: McpError| {
+            let _ = self.pipeline_manager.remove_pipeline(&pipeline_id);
+            e
+        };
+
+        // Launch options applied, for the text output
+        let mut extra_summary = String::new();
+
+        let capped_appsinks = self
+            .pipeline_manager
+            .get_pipeline_handle(&pipeline_id)
+            .map(|pipeline| cap_appsinks(&pipeline, limits.max_appsink_buffers()))
+            .unwrap_or_default();
+        if !capped_appsinks.is_empty() {
+            extra_summary.push_str(&format!(
+                "\nAppsinks capped at {} buffers: {}",
+                limits.max_appsink_buffers(),
+                capped_appsinks.join(", ")
+            ));
+        }
+
+        // Apply socket options to UDP elements before the pipeline starts
+        let mut network_applied = Vec::new();
+        if let Some(network) = params.network.filter(|n| !n.is_empty()) {
+            let applied = self
+                .pipeline_manager
+                .get_pipeline_handle(&pipeline_id)
+                .and_then(|pipeline| apply_network_options(&pipeline, &network));
+            match applied {
+                Ok(applied) if applied.is_empty() => {
+                    extra_summary.push_str("\nNetwork options: no UDP elements to configure");
+                }
+                Ok(applied) => {
+                    extra_summary.push_str("\nNetwork options applied to:");
+                    for element in &applied {
+                        extra_summary.push_str(&format!("\n  - {}", element));
+                    }
+                    network_applied = applied;
+                }
+                Err(e) => return Err(discard(e.into())),
+            }
+        }
+
+        // Insert the watermark overlays before the pipeline starts
+        let mut watermarked = Vec::new();
+        if let Some(watermark) = &params.watermark {
+            let applied = self
+                .pipeline_manager
+                .get_pipeline_handle(&pipeline_id)
+                .and_then(|pipeline| apply_watermark(&pipeline, watermark));
+            match applied {
+                Ok(applied) => {
+                    extra_summary.push_str(&format!(
+                        "\nWatermark inserted before: {}",
+                        applied.join(", ")
+                    ));
+                    watermarked = applied;
+                }
+                Err(e) => return Err(discard(e.into())),
+            }
+        }
+
+        // Timecode after the watermark, so a burnt-in timecode stays readable
+        let mut timecoded = Vec::new();
+        if let Some(timecode) = &params.timecode {
+            let applied = self
+                .pipeline_manager
+                .get_pipeline_handle(&pipeline_id)
+                .and_then(|pipeline| apply_timecode(&pipeline, timecode));
+            match applied {
+                Ok(applied) => {
+                    extra_summary.push_str(&format!(
+                        "\nTimecode inserted before: {}",
+                        applied.join(", ")
+                    ));
+                    timecoded = applied;
+                }
+                Err(e) => return Err(discard(e.into())),
+            }
+        }
+
+        let elements = self
+            .pipeline_manager
+            .pipeline_elements(&pipeline_id)
+            .map_err(|e| discard(e.into()))?;
+
+        let file_writers: Vec<&str> = elements
+            .iter()
+            .filter(|e| {
+                e.factory
+                    .as_deref()
+                    .is_some_and(|f| FILE_WRITING_FACTORIES.contains(&f))
+            })
+            .map(|e| e.name.as_str())
+            .collect();
+        if !file_writers.is_empty() {
+            let action = format!(
+                "launch pipeline '{}' which writes files ({})",
+                pipeline_id,
+                file_writers.join(", ")
+            );
+            self.confirm(&peer, &action).await.map_err(discard)?;
+        }
+
+        if let Some(policy) = params.watchdog.clone() {
+            self.pipeline_manager
+                .set_watchdog(&pipeline_id, policy)
+                .map_err(|e| discard(e.into()))?;
+        }
+        if let Some(deadline) = deadline {
+            self.pipeline_manager
+                .set_deadline(&pipeline_id, deadline)
+                .map_err(|e| discard(e.into()))?;
+        }
+        let owner = params.owner.clone().or_else(|| {
+            peer.peer_info()
+                .map(|info| info.client_info.name.clone())
+                .filter(|name| !name.is_empty())
+        });
+        self.pipeline_manager
+            .set_owner(&pipeline_id, owner.clone())
+            .map_err(|e| discard(e.into()))?;
+        self.pipeline_manager
+            .set_cause(&pipeline_id, Cause::new(&call_id, "gst_launch_pipeline"));
+        if let Some(labels) = params.labels.clone() {
+            self.pipeline_manager
+                .set_labels(&pipeline_id, labels)
+                .map_err(|e| discard(McpError::invalid_params(e.to_string(), None)))?;
+        }
+        if let Some(metadata) = params.metadata.clone() {
+            self.pipeline_manager
+                .set_metadata(&pipeline_id, metadata, true)
+                .map_err(|e| discard(McpError::invalid_params(e.to_string(), None)))?;
+        }
+
+        // Auto-play if requested (default is true)
+        let auto_play = params.auto_play.unwrap_or(true);
+        let (state, mut output) = if params.preroll_only.unwrap_or(false) {
+            self.pipeline_manager
+                .preroll_pipeline(&pipeline_id, DEFAULT_PREROLL_TIMEOUT)
+                .map_err(|e| discard(e.into()))?;
+            let output = format!(
+                "Pipeline '{}' prerolled and held in PAUSED. Set it to 'playing' to go live.\nDescription: {}{}",
+                pipeline_id, description, extra_summary
+            );
+            (gst::State::Paused, output)
+        } else if auto_play {
+            let state = self
+                .pipeline_manager
+                .set_pipeline_state(&pipeline_id, gst::State::Playing)
+                .map_err(|e| discard(e.into()))?;
+
+            let output = format!(
+                "Pipeline '{}' launched successfully.\nState: {:?}\nDescription: {}{}",
+                pipeline_id, state, description, extra_summary
+            );
+            (state, output)
+        } else {
+            let output = format!(
+                "Pipeline '{}' created successfully in NULL state.\nDescription: {}{}",
+                pipeline_id, description, extra_summary
+            );
+            (gst::State::Null, output)
+        };
+
+        let problems = self
+            .pipeline_manager
+            .collect_pending_problems(&pipeline_id)
+            .unwrap_or_default();
+        for problem in &problems {
+            output.push_str(&format!("\n{}", problem.message));
+        }
+        if let Some(deadline) = deadline {
+            output.push_str(&format!(
+                "\nStops at: {}",
+                self.time_display().await.format(deadline)
+            ));
+        }
+        output.push_str(&format_corrections(&corrections));
+        output.push_str(&format_queued(&queued));
+
+        let json = serde_json::json!({
+            "pipeline_id": pipeline_id,
+            "state": format!("{:?}", state),
+            "prerolled": params.preroll_only.unwrap_or(false),
+            "description": description,
+            "corrections": corrections,
+            "queued": queued,
+            "capped_appsinks": capped_appsinks,
+            "elements": elements,
+            "warnings": problems,
+            "network": network_applied,
+            "watermarked": watermarked,
+            "timecoded": timecoded,
+            "watchdog": params.watchdog,
+            "stop_at": deadline,
+            "metadata": params.metadata,
+            "dedupe_key": params.dedupe_key,
+            "deduplicated": false,
+            "labels": params.labels,
+            "owner": owner,
+        });
+
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Changes the state of an active pipeline. Accepts pipeline ID and target state (null/ready/paused/playing). Returns new state and transition success status."
+    )]
+    async fn gst_set_pipeline_state(
+        &self,
+        Parameters(params): Parameters<SetPipelineStateParams>,
+        CallId(call_id): CallId,
+    ) -> Result<CallToolResult, McpError> {
+        // Check if tool is enabled
+        if !self.is_tool_enabled("gst_set_pipeline_state").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_set_pipeline_state' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let state = match params.state.to_lowercase().as_str() {
+            "null" => gst::State::Null,
+            "ready" => gst::State::Ready,
+            "paused" => gst::State::Paused,
+            "playing" => gst::State::Playing,
+            _ => {
+                return Err(McpError {
+                    code: rmcp::model::ErrorCode(-32003),
+                    message: format!(
+                        "Invalid state '{}'. Must be one of: null, ready, paused, playing",
+                        params.state
+                    )
+                    .into(),
+                    data: None,
+                })
+            }
+        };
+
+        self.pipeline_manager.set_cause(
+            &params.pipeline_id,
+            Cause::new(&call_id, "gst_set_pipeline_state"),
+        );
+        let current_state = self
+            .pipeline_manager
+            .set_pipeline_state(&params.pipeline_id, state)
+            .map_err(Into::<McpError>::into)?;
+
+        let output = format!(
+            "Pipeline '{}' state changed to {:?}",
+            params.pipeline_id, current_state
+        );
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "requested_state": format!("{:?}", state),
+            "state": format!("{:?}", current_state),
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Seeks a paused or playing pipeline. Accepts pipeline ID, position (nanoseconds or '1:23.5' style text) and flush (default: true), key_unit and accurate flags. Returns the requested and resulting position and the duration."
+    )]
+    async fn gst_seek(
+        &self,
+        Parameters(params): Parameters<SeekParams>,
+        CallId(call_id): CallId,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_seek").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_seek' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let position = params
+            .position
+            .to_clock_time()
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        let mut flags = gst::SeekFlags::empty();
+        if params.flush.unwrap_or(true) {
+            flags |= gst::SeekFlags::FLUSH;
+        }
+        if params.key_unit.unwrap_or(false) {
+            flags |= gst::SeekFlags::KEY_UNIT;
+        }
+        if params.accurate.unwrap_or(false) {
+            flags |= gst::SeekFlags::ACCURATE;
+        }
+
+        self.pipeline_manager
+            .set_cause(&params.pipeline_id, Cause::new(&call_id, "gst_seek"));
+        let pipeline_manager = self.pipeline_manager.clone();
+        let pipeline_id = params.pipeline_id.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            pipeline_manager.seek(&pipeline_id, position, flags, SEEK_TIMEOUT)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Seek task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let seconds = |ns: Option<u64>| {
+            ns.map(|ns| format!("{:.3} s", ns as f64 / 1e9))
+                .unwrap_or_else(|| "unknown".to_string())
+        };
+        let output = format!(
+            "Pipeline '{}' seeked to {} (requested {}, duration {})",
+            result.pipeline_id,
+            seconds(result.position_ns),
+            seconds(Some(result.requested_ns)),
+            seconds(result.duration_ns)
+        );
+        let json = serde_json::to_value(&result).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Changes the playback rate of a paused or playing pipeline with a seek, for slow motion, fast-forward and reverse playback. Accepts pipeline ID, rate (negative for reverse), start and stop bounds, flush (default: true) and key_units_only flags (optional). Returns whether the pipeline accepted the rate, the applied rate and the segment."
+    )]
+    async fn gst_set_playback_rate(
+        &self,
+        Parameters(params): Parameters<SetPlaybackRateParams>,
+        CallId(call_id): CallId,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_set_playback_rate").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_set_playback_rate' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let bound = |position: &Option<SeekPosition>| {
+            position
+                .as_ref()
+                .map(|p| p.to_clock_time())
+                .transpose()
+                .map_err(|e| McpError::invalid_params(e.to_string(), None))
+        };
+        let start = bound(&params.start)?;
+        let stop = bound(&params.stop)?;
+        // Bounds defaulting to the current position are checked once it is known
+        let unbounded = Some(gst::ClockTime::MAX);
+        rate_segment(
+            params.rate,
+            start.or(Some(gst::ClockTime::ZERO)),
+            stop,
+            unbounded,
+        )
+        .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        let mut flags = gst::SeekFlags::empty();
+        if params.flush.unwrap_or(true) {
+            flags |= gst::SeekFlags::FLUSH;
+        }
+        if params.key_units_only.unwrap_or(false) {
+            flags |= gst::SeekFlags::TRICKMODE
+                | gst::SeekFlags::TRICKMODE_KEY_UNITS
+                | gst::SeekFlags::TRICKMODE_NO_AUDIO;
+        }
+
+        self.pipeline_manager.set_cause(
+            &params.pipeline_id,
+            Cause::new(&call_id, "gst_set_playback_rate"),
+        );
+        let pipeline_manager = self.pipeline_manager.clone();
+        let pipeline_id = params.pipeline_id.clone();
+        let rate = params.rate;
+        let result = tokio::task::spawn_blocking(move || {
+            pipeline_manager.set_playback_rate(&pipeline_id, rate, start, stop, flags, SEEK_TIMEOUT)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Rate change task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let seconds = |ns: Option<u64>| {
+            ns.map(|ns| format!("{:.3} s", ns as f64 / 1e9))
+                .unwrap_or_else(|| "the end".to_string())
+        };
+        let mut output = if result.accepted {
+            format!(
+                "Pipeline '{}' plays at rate {} from {} to {}",
+                result.pipeline_id,
+                result.rate.unwrap_or(result.requested_rate),
+                seconds(Some(result.start_ns)),
+                seconds(result.stop_ns)
+            )
+        } else {
+            format!(
+                "Pipeline '{}' did not accept rate {}",
+                result.pipeline_id, result.requested_rate
+            )
+        };
+        if let Some(rate) = result.rate.filter(|_| !result.accepted) {
+            output.push_str(&format!("; it still plays at rate {}", rate));
+        }
+        let json = serde_json::to_value(&result).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Loops a segment of a paused or playing pipeline, or the whole media, by answering SEGMENT_DONE and EOS with seeks back to its start. Accepts pipeline ID, start/stop bounds, count and enabled flag (optional). Returns the loop with the plays completed and remaining, also shown in the pipeline status."
+    )]
+    async fn gst_set_loop(
+        &self,
+        Parameters(params): Parameters<SetLoopParams>,
+        CallId(call_id): CallId,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_set_loop").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_set_loop' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let settings = if params.enabled.unwrap_or(true) {
+            let bound = |position: &Option<SeekPosition>| {
+                position
+                    .as_ref()
+                    .map(|p| p.to_clock_time().map(|t| t.nseconds()))
+                    .transpose()
+                    .map_err(|e| McpError::invalid_params(e.to_string(), None))
+            };
+            Some(LoopSettings {
+                start_ns: bound(&params.start)?.unwrap_or(0),
+                stop_ns: bound(&params.stop)?,
+                count: params.count,
+            })
+        } else {
+            None
+        };
+
+        self.pipeline_manager
+            .set_cause(&params.pipeline_id, Cause::new(&call_id, "gst_set_loop"));
+        let pipeline_manager = self.pipeline_manager.clone();
+        let pipeline_id = params.pipeline_id.clone();
+        let status =
+            tokio::task::spawn_blocking(move || pipeline_manager.set_loop(&pipeline_id, settings))
+                .await
+                .map_err(|e| crate::GStreamerMcpError::Other(format!("Loop task failed: {}", e)))
+                .and_then(|r| r)
+                .map_err(Into::<McpError>::into)?;
+
+        let output = match &status {
+            Some(status) if status.active => format!(
+                "Pipeline '{}' loops from {:.3} s to {}, {}",
+                params.pipeline_id,
+                status.settings.start_ns as f64 / 1e9,
+                status
+                    .settings
+                    .stop_ns
+                    .map(|ns| format!("{:.3} s", ns as f64 / 1e9))
+                    .unwrap_or_else(|| "the end".to_string()),
+                match status.settings.count {
+                    Some(count) => format!("{} times", count),
+                    None => "until turned off".to_string(),
+                }
+            ),
+            Some(status) => format!(
+                "Pipeline '{}' stops looping after the current play ({} completed)",
+                params.pipeline_id, status.completed
+            ),
+            None => format!("Pipeline '{}' was not looping", params.pipeline_id),
+        };
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "loop": status,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Adds an element to a pipeline, which may be playing. Accepts pipeline ID, element description in gst-launch syntax, and the after/before elements to link it to (optional). Between two linked elements it is spliced in while the upstream pad is blocked. Returns the links made and broken."
+    )]
+    async fn gst_add_element(
+        &self,
+        Parameters(params): Parameters<AddElementParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_add_element").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_add_element' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let modification = PipelineModification::AddElement {
+            description: params.element,
+            after: params.after,
+            before: params.before,
+        };
+        self.modify_pipeline(params.pipeline_id, modification).await
+    }
+
+    #[tool(
+        description = "Removes an element from a pipeline, which may be playing. Accepts pipeline ID, element name and relink flag (optional, default: true). With relink the element is drained and its neighbours linked to each other while the upstream pad is blocked. Returns the links made and broken."
+    )]
+    async fn gst_remove_element(
+        &self,
+        Parameters(params): Parameters<RemoveElementParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_remove_element").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_remove_element' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let modification = PipelineModification::RemoveElement {
+            name: params.element,
+            relink: params.relink.unwrap_or(true),
+        };
+        self.modify_pipeline(params.pipeline_id, modification).await
+    }
+
+    #[tool(
+        description = "Links two elements of a pipeline, which may be playing. Accepts pipeline ID, src and sink as 'element' or 'element.pad'. A src pad that is already linked is moved to the new sink while blocked. Returns the links made and broken."
+    )]
+    async fn gst_link_elements(
+        &self,
+        Parameters(params): Parameters<LinkElementsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_link_elements").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_link_elements' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let modification = PipelineModification::LinkElements {
+            src: params.src,
+            sink: params.sink,
+        };
+        self.modify_pipeline(params.pipeline_id, modification).await
+    }
+
+    #[tool(
+        description = "Lists the state of every element of a pipeline to find the one holding it back. Accepts pipeline ID and include_pads flag (optional). Returns each element's current and pending state, its pads' links and flushing/blocked/EOS flags, and the elements likely stuck."
+    )]
+    async fn gst_get_element_states(
+        &self,
+        Parameters(params): Parameters<GetElementStatesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_element_states").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_element_states' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let mut states = element_states(&pipeline);
+        let stuck = stuck_elements(&states);
+        if !params.include_pads.unwrap_or(true) {
+            for state in &mut states {
+                state.pads.clear();
+            }
+        }
+
+        let mut output = format!("Pipeline '{}':\n", params.pipeline_id);
+        for state in &states {
+            output.push_str(&format!(
+                "  {} ({}): {}{} [{}]\n",
+                state.name,
+                state.factory.as_deref().unwrap_or("bin"),
+                state.current,
+                state
+                    .pending
+                    .as_ref()
+                    .map(|pending| format!(" -> {}", pending))
+                    .unwrap_or_default(),
+                state.state_return
+            ));
+            for pad in &state.pads {
+                let mut flags = Vec::new();
+                if pad.flushing {
+                    flags.push("flushing");
+                }
+                if pad.blocked {
+                    flags.push("blocked");
+                }
+                if pad.eos {
+                    flags.push("eos");
+                }
+                output.push_str(&format!(
+                    "    {} ({}) -> {}{}\n",
+                    pad.name,
+                    pad.direction,
+                    pad.peer.as_deref().unwrap_or("unlinked"),
+                    if flags.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" [{}]", flags.join(", "))
+                    }
+                ));
+            }
+        }
+        if !stuck.is_empty() {
+            output.push_str(&format!("Likely stuck: {}\n", stuck.join(", ")));
+        }
+
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "elements": states,
+            "stuck": stuck,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Lists the elements of a pipeline, including those inside bins. Accepts pipeline ID, include_pads and include_properties flags (optional). Returns each element's name, factory and state, its pads with their links and negotiated caps, and the properties set away from their defaults."
+    )]
+    async fn gst_list_pipeline_elements(
+        &self,
+        Parameters(params): Parameters<ListPipelineElementsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_list_pipeline_elements").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_list_pipeline_elements' is not available in the current mode"
+                    .to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let mut elements = element_details(&pipeline);
+        for element in &mut elements {
+            if !params.include_pads.unwrap_or(true) {
+                element.state.pads.clear();
+            }
+            if !params.include_properties.unwrap_or(true) {
+                element.properties.clear();
+            }
+        }
+
+        let mut output = format!(
+            "Pipeline '{}' has {} elements:\n",
+            params.pipeline_id,
+            elements.len()
+        );
+        for element in &elements {
+            let state = &element.state;
+            output.push_str(&format!(
+                "  {} ({}): {}{}\n",
+                state.name,
+                state.factory.as_deref().unwrap_or("bin"),
+                state.current,
+                state
+                    .pending
+                    .as_ref()
+                    .map(|pending| format!(" -> {}", pending))
+                    .unwrap_or_default()
+            ));
+            for (name, value) in &element.properties {
+                output.push_str(&format!("    {}={}\n", name, value));
+            }
+            for pad in &state.pads {
+                output.push_str(&format!(
+                    "    {} ({}) -> {}: {}\n",
+                    pad.name,
+                    pad.direction,
+                    pad.peer.as_deref().unwrap_or("unlinked"),
+                    pad.caps.as_deref().unwrap_or("not negotiated")
+                ));
+            }
+        }
+
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "elements": elements,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Returns the stall reports of a pipeline, captured when a state change stays pending longer than the configured stall timeout. Accepts pipeline ID and include_details flag (optional). Each report has the states, the elements likely stuck, a per-element state snapshot and a DOT graph of the pipeline."
+    )]
+    async fn gst_get_pipeline_diagnostics(
+        &self,
+        Parameters(params): Parameters<GetPipelineDiagnosticsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_pipeline_diagnostics").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_pipeline_diagnostics' is not available in the current mode"
+                    .to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let mut reports = self
+            .pipeline_manager
+            .stall_reports(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let timeout = self.config.read().await.stall_timeout_seconds;
+
+        let mut output = if timeout == 0 {
+            format!(
+                "Stall detection is off; set stall_timeout_seconds to capture diagnostics.\nPipeline '{}' has {} stall reports.\n",
+                params.pipeline_id,
+                reports.len()
+            )
+        } else {
+            format!(
+                "Pipeline '{}' has {} stall reports (timeout {} s).\n",
+                params.pipeline_id,
+                reports.len(),
+                timeout
+            )
+        };
+        for report in &reports {
+            output.push_str(&format!(
+                "{}: stuck changing from {} to {} for {} ms; suspects: {}; DOT graph {} bytes\n",
+                report.detected_at,
+                report.current_state,
+                report.pending_state,
+                report.stalled_ms,
+                if report.stuck.is_empty() {
+                    "none".to_string()
+                } else {
+                    report.stuck.join(", ")
+                },
+                report.dot.len()
+            ));
+        }
+        if !params.include_details.unwrap_or(true) {
+            for report in &mut reports {
+                report.elements.clear();
+                report.dot.clear();
+            }
+        }
+
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "stall_timeout_seconds": timeout,
+            "reports": reports,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Queries the latency of a pipeline and reads its clock, to diagnose A/V sync and live streaming delay. Accepts pipeline ID. Returns min/max latency, whether the pipeline is live, the configured latency, the clock type, base time and running time."
+    )]
+    async fn gst_get_pipeline_latency(
+        &self,
+        Parameters(params): Parameters<GetPipelineLatencyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_pipeline_latency").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_pipeline_latency' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let mut report = query_latency(&pipeline);
+        report.pipeline_id = params.pipeline_id.clone();
+
+        let ms = |ns: u64| format!("{:.1} ms", ns as f64 / 1_000_000.0);
+        let mut output = format!(
+            "Latency of pipeline '{}':
+",
+            params.pipeline_id
+        );
+        match report.min_latency_ns {
+            Some(min) => output.push_str(&format!(
+                "Live: {}\nMin latency: {}\nMax latency: {}\n",
+                if report.live { "yes" } else { "no" },
+                ms(min),
+                report
+                    .max_latency_ns
+                    .map(ms)
+                    .unwrap_or_else(|| "unlimited".to_string())
+            )),
+            None => output.push_str("Latency: unknown\n"),
+        }
+        if let Some(configured) = report.configured_latency_ns {
+            output.push_str(&format!("Configured latency: {}\n", ms(configured)));
+        }
+        if let Some(clock) = &report.clock {
+            output.push_str(&format!(
+                "Clock: {} ({}, {:?})\n",
+                clock.name, clock.type_name, clock.kind
+            ));
+        }
+        if let Some(base_time) = report.base_time_ns {
+            output.push_str(&format!("Base time: {} ns\n", base_time));
+        }
+        if let Some(running_time) = report.running_time_ns {
+            output.push_str(&format!("Running time: {} ns\n", running_time));
+        }
+        for warning in &report.warnings {
+            output.push_str(&format!("Warning: {}\n", warning));
+        }
+
+        let json = serde_json::to_value(&report).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Retrieves current status of a pipeline. Accepts pipeline ID and include_messages flag (optional). Returns state, position, duration, and recent bus messages."
+    )]
+    async fn gst_get_pipeline_status(
+        &self,
+        Parameters(params): Parameters<GetPipelineStatusParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let status = self
+            .pipeline_manager
+            .get_pipeline_status(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let time = self.time_display().await;
+        // Timestamps in the structured result stay ISO-8601 in UTC
+        let mut json = serde_json::to_value(&status).unwrap_or_default();
+
+        let mut output = format!(
+            "Pipeline: {}\nDescription: {}\nState: {}{}\n",
+            status.id,
+            status.description,
+            status.state,
+            if status.prerolled {
+                " (prerolled, goes live instantly when played)"
+            } else {
+                ""
+            }
+        );
+
+        if let Some(pending) = status.pending_state {
+            output.push_str(&format!("Pending State: {}\n", pending));
+        }
+
+        if status.position >= 0 {
+            output.push_str(&format!("Position: {} ns\n", status.position));
+        }
+        if status.duration >= 0 {
+            output.push_str(&format!("Duration: {} ns\n", status.duration));
+        }
+        if let Some(progress) = &status.progress {
+            let seconds = |ns: u64| format!("{:.1} s", ns as f64 / 1e9);
+            output.push_str(&format!(
+                "Progress: {}{} at rate {}{}\n",
+                seconds(progress.monotonic_position_ns),
+                progress
+                    .fraction
+                    .map(|f| format!(" ({:.1}%)", f * 100.0))
+                    .unwrap_or_default(),
+                progress.segment.rate,
+                progress
+                    .remaining_ns
+                    .map(|ns| format!(", {} left", seconds(ns)))
+                    .unwrap_or_default()
+            ));
+        }
+        if let Some(timecode) = &status.timecode {
+            output.push_str(&format!("Timecode: {}\n", timecode));
+        }
+        if let Some(levels) = &status.audio_levels {
+            let db =
+                |value: Option<f64>| value.map_or("n/a".to_string(), |v| format!("{:.1} dBFS", v));
+            let mut conditions = Vec::new();
+            if levels.clipping {
+                conditions.push("clipping");
+            }
+            if levels.silent {
+                conditions.push("silent");
+            }
+            output.push_str(&format!(
+                "Audio: peak {}, RMS {}{}\n",
+                db(levels.peak_db),
+                db(levels.rms_db),
+                if conditions.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", conditions.join(", "))
+                }
+            ));
+        }
+        if status.restart_count > 0 {
+            output.push_str(&format!("Restarts: {}\n", status.restart_count));
+        }
+        if !status.metadata.is_empty() {
+            output.push_str(&format!(
+                "Metadata: {}\n",
+                serde_json::Value::Object(status.metadata.clone())
+            ));
+        }
+        if let Some(looping) = status.loop_status.filter(|l| l.active) {
+            output.push_str(&format!(
+                "Loop: {} plays completed, {}\n",
+                looping.completed,
+                match looping.remaining {
+                    Some(remaining) => format!("{} remaining", remaining),
+                    None => "until turned off".to_string(),
+                }
+            ));
+        }
+        if let (Some(stop_at), Some(remaining)) = (status.stop_at, status.remaining_ms) {
+            output.push_str(&format!(
+                "Stops at: {} ({:.0} s left)\n",
+                time.format(stop_at),
+                remaining as f64 / 1000.0
+            ));
+        }
+        if let Some(watchdog) = &status.watchdog {
+            if watchdog.policy.on_error != OnError::Ignore {
+                output.push_str(&format!(
+                    "Watchdog: {} on error, {}/{} retries used{}\n",
+                    format!("{:?}", watchdog.policy.on_error).to_lowercase(),
+                    watchdog.retries,
+                    watchdog.policy.max_retries(),
+                    if watchdog.gave_up {
+                        ", gave up"
+                    } else if watchdog.restart_pending {
+                        ", restart pending"
+                    } else {
+                        ""
+                    }
+                ));
+            }
+        }
+        if let Some(budget) = &status.error_budget {
+            output.push_str(&format!(
+                "Last minute: {} errors, {} warnings, {} buffering events{}\n",
+                budget.errors_last_minute,
+                budget.warnings_last_minute,
+                budget.buffering_events_last_minute,
+                if budget.breached.is_empty() {
+                    String::new()
+                } else {
+                    format!(
+                        " (over budget: {})",
+                        budget
+                            .breached
+                            .iter()
+                            .map(|m| m.to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }
+            ));
+        }
+
+        output.push_str(&format!(
+            "Errors: {}, Warnings: {}\n",
+            status.error_count, status.warning_count
+        ));
+        if status.suppressed_messages > 0 {
+            output.push_str(&format!(
+                "Suppressed messages: {}\n",
+                status.suppressed_messages
+            ));
+        }
+        output.push_str(&format!(
+            "Created: {}\nLast State Change: {}\n",
+            time.format(status.created_at),
+            time.format(status.last_state_change)
+        ));
+
+        // Include messages if requested
+        if params.include_messages.unwrap_or(false) {
+            let subscriber = params.subscriber.as_deref().unwrap_or("default");
+            let messages = self.pipeline_manager.get_filtered_bus_messages(
+                &params.pipeline_id,
+                subscriber,
+                10,
+            );
+            json["messages"] = serde_json::json!(messages);
+            if !messages.is_empty() {
+                output.push_str("\nRecent Messages:\n");
+                for msg in messages {
+                    output.push_str(&format!(
+                        "  [{}] {}: {}\n",
+                        time.format(msg.timestamp),
+                        msg.message_type,
+                        msg.message
+                    ));
+                }
+            }
+        }
+
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Tears a pipeline down and relaunches the same description under the same ID, e.g. to recover from an error. Accepts pipeline ID, restore_position flag, state and timeout_ms (optional). Returns the restart count, new state and the position resumed at for seekable pipelines."
+    )]
+    async fn gst_restart_pipeline(
+        &self,
+        Parameters(params): Parameters<RestartPipelineParams>,
+        CallId(call_id): CallId,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_restart_pipeline").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_restart_pipeline' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let target = match params.state.as_deref().map(str::to_lowercase).as_deref() {
+            None => None,
+            Some("ready") => Some(gst::State::Ready),
+            Some("paused") => Some(gst::State::Paused),
+            Some("playing") => Some(gst::State::Playing),
+            Some(state) => {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "Invalid state '{}'. Must be one of: ready, paused, playing",
+                        state
+                    ),
+                    None,
+                ))
+            }
+        };
+        let restore_position = params.restore_position.unwrap_or(true);
+        let timeout = Duration::from_millis(params.timeout_ms.unwrap_or(10000));
+        self.pipeline_manager.set_cause(
+            &params.pipeline_id,
+            Cause::new(&call_id, "gst_restart_pipeline"),
+        );
+        let pipeline_manager = self.pipeline_manager.clone();
+        let pipeline_id = params.pipeline_id.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            pipeline_manager.restart_pipeline(&pipeline_id, target, restore_position, timeout)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Restart task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+        self.publish_shared_state();
+
+        let mut output = format!(
+            "Pipeline '{}' restarted (restart {}) and is {}",
+            report.pipeline_id, report.restart_count, report.state
+        );
+        if let Some(position) = report.restored_position_ns {
+            output.push_str(&format!(
+                "\nResumed at {}",
+                gst::ClockTime::from_nseconds(position)
+            ));
+        }
+        if let Some(detail) = &report.detail {
+            output.push_str(&format!("\nNote: {}", detail));
+        }
+        let json = serde_json::to_value(&report).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Stops and releases resources for a pipeline. Accepts pipeline ID, force flag and timeout_ms (optional). By default sends EOS and waits for it to drain; force skips draining and abandons a hanging teardown. Returns cleanup status and which path was taken."
+    )]
+    async fn gst_stop_pipeline(
+        &self,
+        Parameters(params): Parameters<StopPipelineParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.confirm(&peer, &format!("stop pipeline '{}'", params.pipeline_id))
+            .await?;
+
+        let timeout = Duration::from_millis(params.timeout_ms.unwrap_or(5000));
+        let force = params.force.unwrap_or(false);
+
+        let manager = self.pipeline_manager.clone();
+        let artifacts = self.artifacts.clone();
+        let pipeline_id = params.pipeline_id.clone();
+        let (report, produced) = tokio::task::spawn_blocking(move || {
+            // Read the sink locations first; the pipeline is gone once stopped
+            let locations = manager
+                .get_pipeline_handle(&pipeline_id)
+                .map(|pipeline| output_locations(&pipeline))
+                .unwrap_or_default();
+            let report = if force {
+                manager.force_stop_pipeline(&pipeline_id, timeout)
+            } else {
+                manager.stop_pipeline_draining(&pipeline_id, timeout)
+            }?;
+            let produced = artifacts.register(&pipeline_id, &locations);
+            Ok((report, produced))
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Stop task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+        self.publish_shared_state();
+
+        let mut output = format!(
+            "Pipeline '{}' stopped and removed successfully ({}, {} ms)",
+            report.pipeline_id, report.path, report.elapsed_ms
+        );
+        if let Some(detail) = &report.detail {
+            output.push_str(&format!("\nNote: {}", detail));
+        }
+        if !produced.is_empty() {
+            output.push_str("\nArtifacts:");
+            for artifact in &produced {
+                output.push_str(&format!("\n  - {}", format_artifact(artifact)));
+            }
+        }
+        let mut json = serde_json::to_value(&report).unwrap_or_default();
+        json["artifacts"] = serde_json::json!(produced);
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Stops and releases every pipeline, or those matching labels or an owner, in parallel. Accepts force flag, timeout_ms, labels, owner and include_jobs (optional). Pipelines of running jobs are skipped unless include_jobs is set. Returns the stop report of each pipeline and the ones that failed to stop."
+    )]
+    async fn gst_stop_all_pipelines(
+        &self,
+        Parameters(params): Parameters<StopAllPipelinesParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_stop_all_pipelines").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_stop_all_pipelines' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let mut pipelines = self.pipeline_manager.list_pipelines();
+        if let Some(selector) = &params.labels {
+            pipelines.retain(|p| matches_labels(&p.labels, selector));
+        }
+        if let Some(owner) = &params.owner {
+            pipelines.retain(|p| p.owner.as_ref() == Some(owner));
+        }
+        let mut skipped = Vec::new();
+        if !params.include_jobs.unwrap_or(false) {
+            let running: Vec<String> = self
+                .jobs
+                .list(Some(JobStatus::Running))
+                .into_iter()
+                .map(|job| job.id)
+                .collect();
+            pipelines.retain(|p| {
+                let job = running.contains(&p.id);
+                if job {
+                    skipped.push(p.id.clone());
+                }
+                !job
+            });
+        }
+        let ids: Vec<String> = pipelines.into_iter().map(|p| p.id).collect();
+        if ids.is_empty() {
+            let json = serde_json::json!({
+                "stopped": [],
+                "failed": [],
+                "skipped_jobs": skipped,
+            });
+            return Ok(success_with_json("No pipelines to stop".to_string(), json));
+        }
+        self.confirm(&peer, &format!("stop {} pipelines", ids.len()))
+            .await?;
+
+        let timeout = Duration::from_millis(params.timeout_ms.unwrap_or(5000));
+        let force = params.force.unwrap_or(false);
+        let manager = self.pipeline_manager.clone();
+        let artifacts = self.artifacts.clone();
+        let results = tokio::task::spawn_blocking(move || {
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = ids
+                    .iter()
+                    .map(|id| {
+                        let (manager, artifacts) = (&manager, &artifacts);
+                        scope.spawn(move || {
+                            let locations = manager
+                                .get_pipeline_handle(id)
+                                .map(|pipeline| output_locations(&pipeline))
+                                .unwrap_or_default();
+                            let report = if force {
+                                manager.force_stop_pipeline(id, timeout)
+                            } else {
+                                manager.stop_pipeline_draining(id, timeout)
+                            }?;
+                            let produced = artifacts.register(id, &locations);
+                            Ok((report, produced))
+                        })
+                    })
+                    .collect();
+                ids.iter()
+                    .zip(handles)
+                    .map(|(id, handle)| {
+                        let result = handle.join().unwrap_or_else(|_| {
+                            Err(crate::GStreamerMcpError::Other(
+                                "Stop thread panicked".to_string(),
+                            ))
+                        });
+                        (id.clone(), result)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        })
+        .await
+        .map_err(|e| McpError::internal_error(format!("Stop task failed: {}", e), None))?;
+        self.publish_shared_state();
+
+        let mut stopped = Vec::new();
+        let mut failed = Vec::new();
+        let mut output = String::new();
+        for (id, result) in results {
+            match result {
+                Ok((report, produced)) => {
+                    output.push_str(&format!(
+                        "Stopped '{}' ({}, {} ms)\n",
+                        id, report.path, report.elapsed_ms
+                    ));
+                    let mut json = serde_json::to_value(&report).unwrap_or_default();
+                    json["artifacts"] = serde_json::json!(produced);
+                    stopped.push(json);
+                }
+                Err(e) => {
+                    output.push_str(&format!("Failed to stop '{}': {}\n", id, e));
+                    failed.push(serde_json::json!({"pipeline_id": id, "error": e.to_string()}));
+                }
+            }
+        }
+        if !skipped.is_empty() {
+            output.push_str(&format!(
+                "Skipped pipelines of running jobs: {}\n",
+                skipped.join(", ")
+            ));
+        }
+        output.insert_str(
+            0,
+            &format!(
+                "Stopped {} of {} pipelines\n",
+                stopped.len(),
+                stopped.len() + failed.len()
+            ),
+        );
+        let json = serde_json::json!({
+            "stopped": stopped,
+            "failed": failed,
+            "skipped_jobs": skipped,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Waits for a pipeline to actually reach a state after an asynchronous state change. Accepts pipeline ID, target state (null/ready/paused/playing) and timeout_ms (optional, default: 5000). Returns whether it was reached, the current and pending state, the last state change result and the warnings and errors posted meanwhile."
+    )]
+    async fn gst_wait_for_state(
+        &self,
+        Parameters(params): Parameters<WaitForStateParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_wait_for_state").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_wait_for_state' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let target = match params.state.to_lowercase().as_str() {
+            "null" => gst::State::Null,
+            "ready" => gst::State::Ready,
+            "paused" => gst::State::Paused,
+            "playing" => gst::State::Playing,
+            _ => {
+                return Err(McpError::invalid_params(
+                    format!(
+                        "Invalid state '{}'. Must be one of: null, ready, paused, playing",
+                        params.state
+                    ),
+                    None,
+                ))
+            }
+        };
+        let timeout = Duration::from_millis(params.timeout_ms.unwrap_or(5000));
+        let pipeline_manager = self.pipeline_manager.clone();
+        let pipeline_id = params.pipeline_id.clone();
+        let wait = tokio::task::spawn_blocking(move || {
+            pipeline_manager.wait_for_state(&pipeline_id, target, timeout)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("State wait task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let mut output = if wait.reached {
+            format!(
+                "Pipeline '{}' reached {} after {} ms",
+                wait.pipeline_id, wait.current, wait.elapsed_ms
+            )
+        } else {
+            format!(
+                "Pipeline '{}' did not reach {} within {} ms: it is {}{} [{}]",
+                wait.pipeline_id,
+                wait.target,
+                wait.elapsed_ms,
+                wait.current,
+                wait.pending
+                    .as_ref()
+                    .map(|pending| format!(" -> {}", pending))
+                    .unwrap_or_default(),
+                wait.state_return
+            )
+        };
+        for message in &wait.messages {
+            output.push_str(&format!("\n{}", message.message));
+        }
+        let json = serde_json::to_value(&wait).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Blocks until a pipeline posts a bus message: EOS, an error, an element message with a given structure name, a state change and so on, making launches awaitable. Accepts pipeline ID, message_types (default: eos and error), structure_name, source, timeout_ms (default: 30000) and include_recorded (default: true). Returns whether a message came within the timeout and its type, source, text and structure fields."
+    )]
+    async fn gst_wait_for_message(
+        &self,
+        Parameters(params): Parameters<WaitForMessageParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_wait_for_message").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_wait_for_message' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let query = MessageQuery::new(
+            params.message_types.unwrap_or_default(),
+            params.structure_name,
+            params.source,
+        )
+        .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        let timeout = Duration::from_millis(params.timeout_ms.unwrap_or(30000));
+        let include_recorded = params.include_recorded.unwrap_or(true);
+        let pipeline_manager = self.pipeline_manager.clone();
+        let pipeline_id = params.pipeline_id.clone();
+        let wait = tokio::task::spawn_blocking(move || {
+            pipeline_manager.wait_for_message(&pipeline_id, &query, timeout, include_recorded)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Message wait task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let output = match &wait.message {
+            Some(message) => {
+                let mut output = format!(
+                    "Pipeline '{}' posted {}{} after {} ms{}",
+                    wait.pipeline_id,
+                    message.message_type,
+                    message
+                        .source
+                        .as_ref()
+                        .map(|source| format!(" from {}", source))
+                        .unwrap_or_default(),
+                    wait.elapsed_ms,
+                    if message.recorded {
+                        " (recorded before the wait)"
+                    } else {
+                        ""
+                    }
+                );
+                if let Some(text) = &message.text {
+                    output.push_str(&format!("\n{}", text));
+                }
+                if let Some(name) = &message.structure_name {
+                    output.push_str(&format!("\nStructure: {}", name));
+                    for (field, value) in &message.fields {
+                        output.push_str(&format!("\n  {}: {}", field, value));
+                    }
+                }
+                output
+            }
+            None => format!(
+                "Pipeline '{}' posted no {} message within {} ms",
+                wait.pipeline_id,
+                wait.query.types.join("/"),
+                wait.elapsed_ms
+            ),
+        };
+        let json = serde_json::to_value(&wait).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Sends EOS to a paused or playing pipeline, e.g. to finalize an mp4mux recording without corrupting it. Accepts pipeline ID, wait flag (default: true) and timeout_ms (optional). Returns whether EOS reached the sinks, timed out or failed; the pipeline is not stopped."
+    )]
+    async fn gst_send_eos(
+        &self,
+        Parameters(params): Parameters<SendEosParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_send_eos").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_send_eos' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let wait = params
+            .wait
+            .unwrap_or(true)
+            .then(|| Duration::from_millis(params.timeout_ms.unwrap_or(10000)));
+        let pipeline_manager = self.pipeline_manager.clone();
+        let pipeline_id = params.pipeline_id.clone();
+        let report =
+            tokio::task::spawn_blocking(move || pipeline_manager.send_eos(&pipeline_id, wait))
+                .await
+                .map_err(|e| crate::GStreamerMcpError::Other(format!("EOS task failed: {}", e)))
+                .and_then(|r| r)
+                .map_err(Into::<McpError>::into)?;
+
+        let mut output = match report.outcome {
+            EosOutcome::Sent => format!("EOS sent to pipeline '{}'", report.pipeline_id),
+            EosOutcome::Completed => format!(
+                "EOS reached the sinks of pipeline '{}' after {} ms; outputs are finalized",
+                report.pipeline_id, report.elapsed_ms
+            ),
+            _ => format!(
+                "EOS sent to pipeline '{}' but it {}",
+                report.pipeline_id, report.outcome
+            ),
+        };
+        if let Some(detail) = &report.detail {
+            output.push_str(&format!("\nNote: {}", detail));
+        }
+        let json = serde_json::to_value(&report).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Sends an event to a pipeline or one of its elements: flush start/stop, latency (a value, or recalculation), or an application-defined custom event carrying a structure given as JSON, such as upstream requests to splitmuxsink. Accepts pipeline ID, event, element, reset_time, latency_ns, structure_name and fields (optional). Returns whether the event was handled."
+    )]
+    async fn gst_send_event(
+        &self,
+        Parameters(params): Parameters<SendEventParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_send_event").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_send_event' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let event = match params.event.as_str() {
+            "flush_start" => PipelineEvent::FlushStart,
+            "flush_stop" => PipelineEvent::FlushStop {
+                reset_time: params.reset_time.unwrap_or(true),
+            },
+            "latency" => PipelineEvent::Latency {
+                latency_ns: params.latency_ns,
+            },
+            kind => {
+                let direction = PipelineEvent::parse_kind(kind).ok_or_else(|| {
+                    McpError::invalid_params(
+                        format!(
+                            "Unknown event '{}'. Must be one of: flush_start, flush_stop, latency, custom_upstream, custom_downstream, custom_downstream_oob, custom_both",
+                            kind
+                        ),
+                        None,
+                    )
+                })?;
+                let name = params.structure_name.ok_or_else(|| {
+                    McpError::invalid_params(
+                        format!("Event '{}' needs a structure_name", kind),
+                        None,
+                    )
+                })?;
+                PipelineEvent::Custom {
+                    direction,
+                    name,
+                    fields: params.fields.unwrap_or_default(),
+                }
+            }
+        };
+
+        let pipeline_manager = self.pipeline_manager.clone();
+        let pipeline_id = params.pipeline_id.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            pipeline_manager.send_event(&pipeline_id, params.element.as_deref(), &event)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Event task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!(
+            "Sent {} to '{}' in pipeline '{}': {}",
+            report.event,
+            report.target,
+            report.pipeline_id,
+            if report.handled {
+                "handled"
+            } else {
+                "not handled by any element"
+            }
+        );
+        if let Some(structure) = &report.structure {
+            output.push_str(&format!("\nStructure: {}", structure));
+        }
+        let json = serde_json::to_value(&report).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Lists all currently active pipelines. Accepts include_details flag, state filter including 'prerolled', and label filter (optional). Returns pipeline IDs, descriptions, states, health, labels, and creation times as text plus structured JSON."
+    )]
+    async fn gst_list_pipelines(
+        &self,
+        Parameters(params): Parameters<ListGstPipelinesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut pipelines = self.pipeline_manager.list_pipelines();
+        let in_use = pipelines.len();
+        let time = self.time_display().await;
+        let max_pipelines = self.pipeline_manager.max_pipelines();
+        if let Some(state) = &params.state {
+            pipelines.retain(|p| p.state.eq_ignore_ascii_case(state));
+        }
+        if let Some(selector) = &params.labels {
+            pipelines.retain(|p| matches_labels(&p.labels, selector));
+        }
+        if let Some(owner) = &params.owner {
+            pipelines.retain(|p| p.owner.as_ref() == Some(owner));
+        }
+
+        let remote = match (&self.shared_state, params.include_remote.unwrap_or(false)) {
+            (Some(shared), true) => shared.other_instances().map_err(Into::<McpError>::into)?,
+            _ => Vec::new(),
+        };
+        let remote_count: usize = remote.iter().map(|i| i.pipelines.len()).sum();
+
+        let mut json = serde_json::json!({
+            "count": pipelines.len(),
+            "in_use": in_use,
+            "max_pipelines": max_pipelines,
+            "pipelines": pipelines,
+        });
+        if params.include_remote.unwrap_or(false) {
+            json["remote"] = serde_json::json!(remote);
+        }
+
+        let mut remote_output = String::new();
+        for instance in &remote {
+            remote_output.push_str(&format!(
+                "\nInstance {} (pid {}, read-only):\n",
+                instance.instance_id, instance.pid
+            ));
+            for pipeline in &instance.pipelines {
+                remote_output.push_str(&format!(
+                    "- {} ({}, {})\n",
+                    pipeline.id, pipeline.state, pipeline.health
+                ));
+            }
+        }
+
+        if pipelines.is_empty() && remote_count == 0 {
+            return Ok(success_with_json(
+                format!("No active pipelines ({}/{} in use)", in_use, max_pipelines),
+                json,
+            ));
+        }
+
+        let mut output = format!(
+            "Active pipelines: {} ({}/{} in use)\n\n",
+            pipelines.len(),
+            in_use,
+            max_pipelines
+        );
+
+        for pipeline in &pipelines {
+            if params.include_details.unwrap_or(false) {
+                output.push_str(&format!(
+                    "ID: {}\n  Description: {}\n  State: {}\n  Health: {}\n  Created: {}\n  Errors: {}, Warnings: {}\n  Restarts: {}\n",
+                    pipeline.id, pipeline.description, pipeline.state, pipeline.health,
+                    time.format(pipeline.created_at), pipeline.error_count, pipeline.warning_count,
+                    pipeline.restart_count
+                ));
+                if let Some(owner) = &pipeline.owner {
+                    output.push_str(&format!("  Owner: {}\n", owner));
+                }
+                if !pipeline.labels.is_empty() {
+                    output.push_str(&format!("  Labels: {}\n", format_labels(&pipeline.labels)));
+                }
+                if !pipeline.metadata.is_empty() {
+                    output.push_str(&format!(
+                        "  Metadata: {}\n",
+                        serde_json::Value::Object(pipeline.metadata.clone())
+                    ));
+                }
+                output.push('\n');
+            } else {
+                output.push_str(&format!(
+                    "- {} ({}, {})\n",
+                    pipeline.id, pipeline.state, pipeline.health
+                ));
+            }
+        }
+        output.push_str(&remote_output);
+
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Attaches client metadata to a pipeline. Accepts pipeline ID, a JSON object merged into the existing metadata (null values remove keys) and replace flag (optional). Returns the metadata now stored, which list and status also return."
+    )]
+    async fn gst_set_pipeline_metadata(
+        &self,
+        Parameters(params): Parameters<SetPipelineMetadataParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_set_pipeline_metadata").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_set_pipeline_metadata' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        if self
+            .pipeline_manager
+            .get_pipeline(&params.pipeline_id)
+            .is_none()
+        {
+            return Err(crate::GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' not found",
+                params.pipeline_id
+            ))
+            .into());
+        }
+        let metadata = self
+            .pipeline_manager
+            .set_metadata(
+                &params.pipeline_id,
+                params.metadata,
+                params.replace.unwrap_or(false),
+            )
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        self.publish_shared_state();
+
+        let output = format!(
+            "Metadata of pipeline '{}': {}",
+            params.pipeline_id,
+            serde_json::Value::Object(metadata.clone())
+        );
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "metadata": metadata,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Hands a pipeline to another owner so a different agent or person can supervise it. Accepts pipeline ID, new owner, expected current owner and replacement labels (all optional except the pipeline ID). Returns the previous and new owner and the labels."
+    )]
+    async fn gst_transfer_pipeline(
+        &self,
+        Parameters(params): Parameters<TransferPipelineParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_transfer_pipeline").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_transfer_pipeline' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let report = self
+            .pipeline_manager
+            .transfer_pipeline(
+                &params.pipeline_id,
+                params.owner,
+                params.expected_owner.as_deref(),
+                params.labels,
+            )
+            .map_err(Into::<McpError>::into)?;
+        self.publish_shared_state();
+
+        let owner = |owner: &Option<String>| owner.clone().unwrap_or_else(|| "no one".to_string());
+        let mut output = format!(
+            "Pipeline '{}' handed from {} to {}",
+            report.pipeline_id,
+            owner(&report.previous_owner),
+            owner(&report.owner)
+        );
+        if !report.labels.is_empty() {
+            output.push_str(&format!("\nLabels: {}", format_labels(&report.labels)));
+        }
+        let json = serde_json::to_value(&report).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Validates pipeline description syntax without launching. Accepts gst-launch syntax description, auto_correct flag (optional) and deep flag (optional) that prerolls the pipeline to report elements auto-plugged inside decodebin, encodebin and similar bins. Returns validation status and list of elements that would be created, or the closest registry matches for unknown elements."
+    )]
+    async fn gst_validate_pipeline(
+        &self,
+        Parameters(params): Parameters<ValidatePipelineParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let (description, corrections) = if params.auto_correct.unwrap_or(false) {
+            match correct_element_typos(&params.pipeline_description) {
+                Ok(corrected) => corrected,
+                Err(e) => {
+                    let output = format!("Pipeline validation failed:\n{}", e);
+                    return Ok(CallToolResult::success(vec![Content::text(output)]));
+                }
+            }
+        } else {
+            (params.pipeline_description.clone(), Vec::new())
+        };
+        let leak = self.config.read().await.queue_leak;
+        let (description, queued) =
+            match check_branch_queues(&description, params.auto_queue.unwrap_or(false), leak) {
+                Ok(queued) => queued,
+                Err(e) => {
+                    let output = format!("Pipeline validation failed:\n{}", e);
+                    return Ok(CallToolResult::success(vec![Content::text(output)]));
+                }
+            };
+
+        match validate_pipeline_description(&description) {
+            Ok(elements) => {
+                let mut output = format!(
+                    "Pipeline description is valid!\n\nElements that would be created ({}):\n",
+                    elements.len()
+                );
+                for element in elements {
+                    output.push_str(&format!("- {}\n", element));
+                }
+                if !corrections.is_empty() || !queued.is_empty() {
+                    output.push_str(&format!(
+                        "{}{}\nCorrected description: {}\n",
+                        format_corrections(&corrections),
+                        format_queued(&queued),
+                        description
+                    ));
+                }
+                if params.deep.unwrap_or(false) {
+                    let deep = tokio::task::spawn_blocking(move || {
+                        deep_validate_pipeline(&description, DEEP_VALIDATION_TIMEOUT)
+                    })
+                    .await
+                    .map_err(|e| {
+                        crate::GStreamerMcpError::Other(format!(
+                            "Deep validation task failed: {}",
+                            e
+                        ))
+                    })
+                    .and_then(|r| r)
+                    .map_err(Into::<McpError>::into)?;
+
+                    if deep.prerolled {
+                        output.push_str("\nDeep validation: the pipeline reached PAUSED\n");
+                    } else {
+                        output.push_str(&format!(
+                            "\nDeep validation: the pipeline did not reach PAUSED: {}\n",
+                            deep.error.as_deref().unwrap_or("unknown error")
+                        ));
+                    }
+                    if !deep.autoplugged.is_empty() {
+                        output.push_str(&format!(
+                            "\nAuto-plugged elements ({}):\n",
+                            deep.autoplugged.len()
+                        ));
+                        for element in &deep.autoplugged {
+                            output.push_str(&format!(
+                                "- {} ({}) in {}\n",
+                                element.name, element.factory, element.parent
+                            ));
+                        }
+                    }
+                    if !deep.substitutions.is_empty() {
+                        output.push_str("\nFor this run:\n");
+                        for substitution in &deep.substitutions {
+                            output.push_str(&format!("- {}\n", substitution));
+                        }
+                    }
+                }
+                Ok(CallToolResult::success(vec![Content::text(output)]))
+            }
+            Err(e) => {
+                let output = format!("Pipeline validation failed:\n{}", e);
+                Ok(CallToolResult::success(vec![Content::text(output)]))
+            }
+        }
+    }
+
+    #[tool(
+        description = "Builds an encodebin encoding profile from high-level parameters. Accepts container, video_codec, video_profile, width, height, framerate, audio_codec, channels and sample_rate (all optional, at least one codec). Returns the serialized profile for encodebin's profile property, a file extension, an example launch description and warnings for streams no installed encoder or muxer can produce."
+    )]
+    async fn gst_build_encoding_profile(
+        &self,
+        Parameters(params): Parameters<BuildEncodingProfileParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_build_encoding_profile").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_build_encoding_profile' is not available in the current mode"
+                    .to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let result = tokio::task::spawn_blocking(move || build_encoding_profile(&params.profile))
+            .await
+            .map_err(|e| {
+                crate::GStreamerMcpError::Other(format!("Encoding profile task failed: {}", e))
+            })
+            .and_then(|r| r)
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!("Encoding profile:\n{}\n", result.profile);
+        if let Some(ext) = &result.file_extension {
+            output.push_str(&format!("\nFile extension: .{}\n", ext));
+        }
+        output.push_str(&format!("\nExample:\n{}\n", result.launch_example));
+        if !result.warnings.is_empty() {
+            output.push_str("\nWarnings:\n");
+            for warning in &result.warnings {
+                output.push_str(&format!("- {}\n", warning));
+            }
+        }
+
+        let json = serde_json::to_value(&result).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Reports adaptive streaming (HLS/DASH) statistics for a pipeline. Accepts pipeline ID and demuxer element name (optional). Returns current variant caps, bandwidth estimate, buffer level, and demuxer tuning properties."
+    )]
+    async fn gst_get_adaptive_stats(
+        &self,
+        Parameters(params): Parameters<GetAdaptiveStatsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_adaptive_stats").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_adaptive_stats' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let stats = get_adaptive_stats(&pipeline, params.element_name.as_deref())
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!("Adaptive streaming stats for '{}':\n", params.pipeline_id);
+        for demux in stats {
+            output.push_str(&format_adaptive_stats(&demux));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Pins the quality level of adaptive streaming (HLS/DASH) demuxers. Accepts pipeline ID, demuxer element name (optional), and variant bitrate in bits/s (omit or 0 for automatic). Returns the updated demuxer stats."
+    )]
+    async fn gst_set_variant(
+        &self,
+        Parameters(params): Parameters<SetVariantParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_set_variant").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_set_variant' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        self.confirm(
+            &peer,
+            &format!(
+                "change the adaptive streaming variant of pipeline '{}'",
+                params.pipeline_id
+            ),
+        )
+        .await?;
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let stats = set_variant(&pipeline, params.element_name.as_deref(), params.bitrate)
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = match params.bitrate {
+            Some(bitrate) if bitrate > 0 => format!(
+                "Pipeline '{}' pinned to variant at {} bps\n",
+                params.pipeline_id, bitrate
+            ),
+            _ => format!(
+                "Pipeline '{}' restored to automatic variant selection\n",
+                params.pipeline_id
+            ),
+        };
+        for demux in stats {
+            output.push_str(&format_adaptive_stats(&demux));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Reads element properties of a running pipeline. Accepts pipeline ID and property path (element::property, child-proxy paths like playbin0::video-sink::sync, and * wildcards). Returns the value and type of every matching property."
+    )]
+    async fn gst_get_property(
+        &self,
+        Parameters(params): Parameters<GetPropertyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_property").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_property' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let values = get_properties(&pipeline, &params.path).map_err(Into::<McpError>::into)?;
+
+        let mut output = String::new();
+        for value in &values {
+            output.push_str(&format!(
+                "{} = {} ({})\n",
+                value.path, value.value, value.type_name
+            ));
+        }
+
+        let json = serde_json::json!({ "pipeline_id": params.pipeline_id, "properties": values });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Sets element properties of a running pipeline. Accepts pipeline ID, property path (element::property, child-proxy paths like playbin0::video-sink::sync, and * wildcards applied to every match), and value in gst-launch syntax. Returns the new values."
+    )]
+    async fn gst_set_property(
+        &self,
+        Parameters(params): Parameters<SetPropertyParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_set_property").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_set_property' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        self.confirm(
+            &peer,
+            &format!(
+                "set {} to '{}' in pipeline '{}'",
+                params.path, params.value, params.pipeline_id
+            ),
+        )
+        .await?;
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let values = set_properties(&pipeline, &params.path, &params.value)
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!(
+            "Set {} propert{} in pipeline '{}':\n",
+            values.len(),
+            if values.len() == 1 { "y" } else { "ies" },
+            params.pipeline_id
+        );
+        for value in &values {
+            output.push_str(&format!("  {} = {}", value.path, value.value));
+            if let Some(converted) = &value.converted {
+                output.push_str(&format!(
+                    " (from '{}', in {})",
+                    converted.input, converted.unit
+                ));
+            }
+            output.push('\n');
+        }
+
+        let json = serde_json::json!({ "pipeline_id": params.pipeline_id, "properties": values });
+        Ok(success_with_json(output, json))
+    }
+    #[tool(
+        description = "Routes audio channels through an audiomixmatrix element of a running pipeline. Accepts pipeline ID, element name (optional), and a list of routes (input channel, output channel, gain in dB). Replaces the whole matrix; unrouted outputs are silent. Returns the applied gain matrix."
+    )]
+    async fn gst_route_audio(
+        &self,
+        Parameters(params): Parameters<RouteAudioParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_route_audio").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_route_audio' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let routing = route_audio(&pipeline, params.element_name.as_deref(), &params.routes)
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!(
+            "Routing of '{}' in pipeline '{}' ({} in → {} out):\n",
+            routing.element, params.pipeline_id, routing.in_channels, routing.out_channels
+        );
+        for (out, row) in routing.matrix.iter().enumerate() {
+            let sources: Vec<String> = row
+                .iter()
+                .enumerate()
+                .filter(|(_, gain)| **gain != 0.0)
+                .map(|(input, gain)| format!("in {} ({:+.1} dB)", input, 20.0 * gain.log10()))
+                .collect();
+            output.push_str(&format!(
+                "  out {} ← {}\n",
+                out,
+                if sources.is_empty() {
+                    "silent".to_string()
+                } else {
+                    sources.join(", ")
+                }
+            ));
+        }
+
+        let json = serde_json::to_value(&routing).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Attaches a speech-to-text branch to a tee of a running pipeline. Accepts pipeline ID, tee name and transcriber element (optional). Transcripts are sent as 'transcript' notifications and recorded in the pipeline's messages. Returns the name of the added branch."
+    )]
+    async fn gst_attach_transcriber(
+        &self,
+        Parameters(params): Parameters<AttachTranscriberParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_attach_transcriber").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_attach_transcriber' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+
+        // Segments arrive on streaming threads; forward them from a task
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<TranscriptSegment>();
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            while let Some(segment) = rx.recv().await {
+                let data = serde_json::to_value(&segment).unwrap_or_default();
+                notifier
+                    .notify("transcript", LoggingLevel::Info, data)
+                    .await;
+            }
+        });
+
+        let manager = self.pipeline_manager.clone();
+        let branch = attach_transcriber(
+            &pipeline,
+            &params.pipeline_id,
+            params.tee_name.as_deref(),
+            params.transcriber.as_deref(),
+            move |segment| {
+                manager.add_bus_message(
+                    &segment.pipeline_id,
+                    BusMessage {
+                        timestamp: chrono::Utc::now(),
+                        message_type: "Transcript".to_string(),
+                        message: segment.text.clone(),
+                        source: Some(segment.branch.clone()),
+                        cause: None,
+                        details: None,
+                    },
+                );
+                let _ = tx.send(segment);
+            },
+        )
+        .map_err(Into::<McpError>::into)?;
+
+        let output = format!(
+            "Transcriber '{}' attached to pipeline '{}'.\nTranscripts are sent as 'transcript' notifications and appear as Transcript messages in gst_get_pipeline_status.",
+            branch, params.pipeline_id
+        );
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "branch": branch,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Attaches an object detection branch to a tee of a running pipeline. Accepts pipeline ID, tee name, detector element or chain, model, labels, minimum confidence and notification interval (all optional except the pipeline ID). Detections are sent as 'detection' notifications and recorded in the pipeline's messages. Returns the name of the added branch."
+    )]
+    async fn gst_attach_detector(
+        &self,
+        Parameters(params): Parameters<AttachDetectorParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_attach_detector").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_attach_detector' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let detector = match params.detector.as_deref() {
+            // A full chain is used as given
+            Some(chain) if chain.contains(' ') || chain.contains('!') => {
+                if params.model.is_some() {
+                    return Err(McpError::invalid_params(
+                        "Set the model inside the detector chain".to_string(),
+                        None,
+                    ));
+                }
+                chain.to_string()
+            }
+            factory => {
+                detector_chain(factory, params.model.as_deref()).map_err(Into::<McpError>::into)?
+            }
+        };
+        let filter = DetectionFilter {
+            labels: params.labels.clone().unwrap_or_default(),
+            min_confidence: params.min_confidence,
+        };
+        let interval = Duration::from_millis(params.interval_ms.unwrap_or(1000));
+
+        // Detections arrive on streaming threads; forward them from a task
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<DetectionEvent>();
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let data = serde_json::to_value(&event).unwrap_or_default();
+                notifier.notify("detection", LoggingLevel::Info, data).await;
+            }
+        });
+
+        let manager = self.pipeline_manager.clone();
+        let last_sent = parking_lot::Mutex::new(None::<std::time::Instant>);
+        let branch = attach_detector(
+            &pipeline,
+            &params.pipeline_id,
+            params.tee_name.as_deref(),
+            &detector,
+            filter,
+            move |event| {
+                let mut last_sent = last_sent.lock();
+                if last_sent.is_some_and(|t| t.elapsed() < interval) {
+                    return;
+                }
+                *last_sent = Some(std::time::Instant::now());
+                let summary: Vec<String> = event
+                    .objects
+                    .iter()
+                    .map(|o| match o.confidence {
+                        Some(confidence) => format!("{} ({:.2})", o.label, confidence),
+                        None => o.label.clone(),
+                    })
+                    .collect();
+                manager.add_bus_message(
+                    &event.pipeline_id,
+                    BusMessage {
+                        timestamp: chrono::Utc::now(),
+                        message_type: "Detection".to_string(),
+                        message: summary.join(", "),
+                        source: Some(event.branch.clone()),
+                        cause: None,
+                        details: None,
+                    },
+                );
+                let _ = tx.send(event);
+            },
+        )
+        .map_err(Into::<McpError>::into)?;
+
+        let output = format!(
+            "Detector '{}' ({}) attached to pipeline '{}'.\nDetections are sent as 'detection' notifications and appear as Detection messages in gst_get_pipeline_status.",
+            branch, detector, params.pipeline_id
+        );
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "branch": branch,
+            "detector": detector,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Speaks text with a text-to-speech element (festival). Accepts text, output WAV path and pipeline ID (optional). Plays on the default audio device unless an output file is given. Returns the pipeline ID of the speech pipeline."
+    )]
+    async fn gst_speak(
+        &self,
+        Parameters(params): Parameters<SpeakParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_speak").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_speak' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        if params.text.trim().is_empty() {
+            return Err(McpError::invalid_params(
+                "There is no text to speak".to_string(),
+                None,
+            ));
+        }
+        let synthesizer = crate::media::require_element(SYNTHESIZERS, "text-to-speech element")
+            .map_err(Into::<McpError>::into)?;
+        let output = params.output.as_ref().map(std::path::PathBuf::from);
+        if let Some(output) = &output {
+            self.confirm(&peer, &format!("write {}", output.display()))
+                .await?;
+        }
+
+        // The synthesizer reads text from a file
+        let text_file =
+            std::env::temp_dir().join(format!("gstreamer-mcp-speech-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&text_file, &params.text)
+            .map_err(|e| crate::GStreamerMcpError::Other(format!("Failed to write text: {}", e)))
+            .map_err(Into::<McpError>::into)?;
+        let description = speech_description(synthesizer, &text_file, output.as_deref());
+
+        let pipeline_id = self
+            .pipeline_manager
+            .create_pipeline(&description, params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        self.publish_shared_state();
+        let state = self
+            .pipeline_manager
+            .set_pipeline_state(&pipeline_id, gst::State::Playing)
+            .map_err(Into::<McpError>::into)?;
+
+        let text = format!(
+            "Pipeline '{}' speaking {} characters {}.\nState: {:?}",
+            pipeline_id,
+            params.text.chars().count(),
+            match &output {
+                Some(path) => format!("into {}", path.display()),
+                None => "on the default audio device".to_string(),
+            },
+            state
+        );
+        let json = serde_json::json!({
+            "pipeline_id": pipeline_id,
+            "state": format!("{:?}", state),
+            "output": output,
+            "description": description,
+        });
+        Ok(success_with_json(text, json))
+    }
+
+    #[tool(
+        description = "Lists the network interfaces of the host. Accepts no parameters. Returns interface names, indexes, MAC addresses, IP addresses, and loopback flags. Use to pick the multicast interface for UDP pipelines."
+    )]
+    async fn gst_list_network_interfaces(&self) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_list_network_interfaces").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_list_network_interfaces' is not available in the current mode"
+                    .to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let interfaces = list_network_interfaces().map_err(Into::<McpError>::into)?;
+
+        let output = if interfaces.is_empty() {
+            "No network interfaces found.".to_string()
+        } else {
+            let mut output = format!("Found {} network interfaces:\n\n", interfaces.len());
+            for iface in interfaces {
+                output.push_str(&format!("- {} (index {})", iface.name, iface.index));
+                if iface.loopback {
+                    output.push_str(" [loopback]");
+                }
+                output.push('\n');
+                if let Some(mac) = &iface.mac_address {
+                    output.push_str(&format!("  MAC: {}\n", mac));
+                }
+                if !iface.addresses.is_empty() {
+                    output.push_str(&format!("  Addresses: {}\n", iface.addresses.join(", ")));
+                }
+            }
+            output
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+    #[tool(
+        description = "Lists NDI sources announced on the network. Accepts wait time in milliseconds (optional, default: 2000). Returns source names and sender addresses. Only offered when the NDI plugin is installed."
+    )]
+    async fn gst_list_ndi_sources(
+        &self,
+        Parameters(params): Parameters<ListNdiSourcesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_list_ndi_sources").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_list_ndi_sources' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let wait = Duration::from_millis(params.wait_ms.unwrap_or(2000));
+        let sources = tokio::task::spawn_blocking(move || list_ndi_sources(wait))
+            .await
+            .map_err(|e| crate::GStreamerMcpError::Other(format!("NDI discovery failed: {}", e)))
+            .and_then(|r| r)
+            .map_err(Into::<McpError>::into)?;
+
+        let output = if sources.is_empty() {
+            format!(
+                "No NDI sources found within {} ms. Senders on other subnets need url_address or an NDI discovery server.",
+                wait.as_millis()
+            )
+        } else {
+            let mut output = format!("Found {} NDI sources:\n\n", sources.len());
+            for source in &sources {
+                output.push_str(&format!("- {}", source.name));
+                if let Some(url) = &source.url_address {
+                    output.push_str(&format!(" ({})", url));
+                }
+                output.push('\n');
+            }
+            output
+        };
+
+        let json = serde_json::json!({ "sources": sources });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Launches a pipeline receiving or sending NDI. Accepts direction (receive/send), NDI name, video and audio chains, sender address, receive bandwidth, auto-play flag and pipeline ID (optional). Builds the ndisrc/ndisrcdemux or ndisinkcombiner/ndisink wiring. Returns the pipeline ID, state and generated description."
+    )]
+    async fn gst_launch_ndi(
+        &self,
+        Parameters(params): Parameters<LaunchNdiParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_launch_ndi").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_launch_ndi' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let video = match params.video.as_deref() {
+            None => Some(default_video_chain(params.direction)),
+            Some(chain) if chain.trim().is_empty() => None,
+            Some(chain) => Some(chain),
+        };
+        let endpoints = NdiEndpoints {
+            video,
+            audio: params.audio.as_deref().filter(|a| !a.trim().is_empty()),
+            url_address: params.url_address.as_deref(),
+            bandwidth: params.bandwidth,
+        };
+        let description = ndi_description(params.direction, &params.ndi_name, &endpoints)
+            .map_err(Into::<McpError>::into)?;
+
+        let pipeline_id = self
+            .pipeline_manager
+            .create_pipeline(&description, params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        self.publish_shared_state();
+
+        let state = if params.auto_play.unwrap_or(true) {
+            self.pipeline_manager
+                .set_pipeline_state(&pipeline_id, gst::State::Playing)
+                .map_err(Into::<McpError>::into)?
+        } else {
+            gst::State::Null
+        };
+
+        let output = format!(
+            "Pipeline '{}' {} NDI source '{}'.\nState: {:?}\nDescription: {}",
+            pipeline_id,
+            match params.direction {
+                NdiDirection::Receive => "receiving",
+                NdiDirection::Send => "publishing",
+            },
+            params.ndi_name,
+            state,
+            description
+        );
+        let json = serde_json::json!({
+            "pipeline_id": pipeline_id,
+            "direction": params.direction,
+            "ndi_name": params.ndi_name,
+            "state": format!("{:?}", state),
+            "description": description,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Lists GigE Vision / USB3 Vision cameras visible to Aravis. Accepts details flag (optional) to open each camera and report pixel formats, sizes, exposure and gain. Only offered when aravissrc is installed."
+    )]
+    async fn gst_list_aravis_cameras(
+        &self,
+        Parameters(params): Parameters<ListAravisCamerasParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_list_aravis_cameras").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_list_aravis_cameras' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let details = params.details.unwrap_or(false);
+        let cameras = tokio::task::spawn_blocking(move || {
+            let mut cameras = list_aravis_cameras()?;
+            if details {
+                for camera in &mut cameras {
+                    match inspect_aravis_camera(&camera.name) {
+                        Ok(info) => camera.details = Some(info),
+                        Err(e) => tracing::warn!("Failed to inspect {}: {}", camera.name, e),
+                    }
+                }
+            }
+            Ok(cameras)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Camera discovery failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let output = if cameras.is_empty() {
+            "No cameras found. GigE Vision cameras must be on a subnet of this host.".to_string()
+        } else {
+            let mut output = format!("Found {} cameras:\n\n", cameras.len());
+            for camera in &cameras {
+                output.push_str(&format!("- {}", camera.name));
+                if let Some(address) = &camera.address {
+                    output.push_str(&format!(" ({})", address));
+                }
+                output.push('\n');
+                let Some(info) = &camera.details else {
+                    continue;
+                };
+                for format in &info.pixel_formats {
+                    output.push_str(&format!(
+                        "  {} {} {}x{}\n",
+                        format.media_type,
+                        format.format.as_deref().unwrap_or("-"),
+                        format.width.as_deref().unwrap_or("?"),
+                        format.height.as_deref().unwrap_or("?")
+                    ));
+                }
+                if let Some(exposure) = info.exposure_us {
+                    output.push_str(&format!(
+                        "  Exposure: {} µs (auto: {})\n",
+                        exposure,
+                        info.exposure_auto.as_deref().unwrap_or("unknown")
+                    ));
+                }
+                if let Some(gain) = info.gain_db {
+                    output.push_str(&format!(
+                        "  Gain: {} dB (auto: {})\n",
+                        gain,
+                        info.gain_auto.as_deref().unwrap_or("unknown")
+                    ));
+                }
+            }
+            output
+        };
+
+        let json = serde_json::json!({ "cameras": cameras });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Launches a pipeline streaming from an industrial camera with aravissrc. Accepts camera name, exposure (µs), gain (dB), auto exposure/gain modes, pixel format, size, frame rate, downstream chain, auto-play flag and pipeline ID (optional). Bayer formats are debayered automatically. Returns the pipeline ID, state and generated description."
+    )]
+    async fn gst_launch_aravis(
+        &self,
+        Parameters(params): Parameters<LaunchAravisParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_launch_aravis").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_launch_aravis' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let description = aravis_description(
+            params.camera_name.as_deref(),
+            &params.settings,
+            params
+                .sink
+                .as_deref()
+                .unwrap_or("videoconvert ! autovideosink"),
+        );
+
+        let pipeline_id = self
+            .pipeline_manager
+            .create_pipeline(&description, params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        self.publish_shared_state();
+
+        let state = if params.auto_play.unwrap_or(true) {
+            self.pipeline_manager
+                .set_pipeline_state(&pipeline_id, gst::State::Playing)
+                .map_err(Into::<McpError>::into)?
+        } else {
+            gst::State::Null
+        };
+
+        let output = format!(
+            "Pipeline '{}' streaming from camera '{}'.\nState: {:?}\nDescription: {}",
+            pipeline_id,
+            params.camera_name.as_deref().unwrap_or("first available"),
+            state,
+            description
+        );
+        let json = serde_json::json!({
+            "pipeline_id": pipeline_id,
+            "camera_name": params.camera_name,
+            "state": format!("{:?}", state),
+            "description": description,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Installs a lightweight probe on a pad of a running pipeline that keeps counting what flows through it, to check later with gst_get_probe_stats. Accepts pipeline ID and pad as 'element.pad'. A probe already on the pad is replaced and its statistics start over."
+    )]
+    async fn gst_add_probe(
+        &self,
+        Parameters(params): Parameters<AddProbeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_add_probe").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_add_probe' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pad = self
+            .pipeline_manager
+            .add_probe(&params.pipeline_id, &params.pad)
+            .map_err(Into::<McpError>::into)?;
+
+        let output = format!(
+            "Probe added on '{}' in pipeline '{}'. Read it with gst_get_probe_stats",
+            pad, params.pipeline_id
+        );
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "pad": pad,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Returns what the probes added with gst_add_probe have seen, to verify data flows through each branch. Accepts pipeline ID, pad and remove flag (optional). Returns per pad the buffer and byte counts, average buffer size and byte rate, first and last PTS, PTS going backwards, time since the last buffer, and the caps seen."
+    )]
+    async fn gst_get_probe_stats(
+        &self,
+        Parameters(params): Parameters<GetProbeStatsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_probe_stats").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_probe_stats' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let stats: Vec<ProbeStats> = self
+            .pipeline_manager
+            .probe_stats(
+                &params.pipeline_id,
+                params.pad.as_deref(),
+                params.remove.unwrap_or(false),
+            )
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = if stats.is_empty() {
+            format!(
+                "No probes on pipeline '{}'; add one with gst_add_probe\n",
+                params.pipeline_id
+            )
+        } else {
+            format!("Probes on pipeline '{}':\n", params.pipeline_id)
+        };
+        for probe in &stats {
+            output.push_str(&format!(
+                "  {}: {} buffers, {} bytes in {} ms (avg {} bytes, {} bytes/s)\n",
+                probe.pad,
+                probe.buffers,
+                probe.bytes,
+                probe.elapsed_ms,
+                probe.average_buffer_size,
+                probe.bytes_per_second
+            ));
+            match probe.idle_ms {
+                Some(idle) => output.push_str(&format!("    last buffer {} ms ago\n", idle)),
+                None => output.push_str("    no data has flowed yet\n"),
+            }
+            if let (Some(first), Some(last)) = (probe.first_pts_ns, probe.last_pts_ns) {
+                output.push_str(&format!(
+                    "    PTS {:.3}s -> {:.3}s{}\n",
+                    first as f64 / 1e9,
+                    last as f64 / 1e9,
+                    if probe.pts_backwards > 0 {
+                        format!(", went backwards {} times", probe.pts_backwards)
+                    } else {
+                        String::new()
+                    }
+                ));
+            }
+            if let Some(caps) = probe.caps.last() {
+                output.push_str(&format!("    caps: {}\n", caps));
+            }
+            if probe.caps.len() > 1 {
+                output.push_str(&format!(
+                    "    renegotiated {} times\n",
+                    probe.caps.len() - 1
+                ));
+            }
+        }
+        if params.remove.unwrap_or(false) && !stats.is_empty() {
+            output.push_str("Probes removed\n");
+        }
+
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "probes": stats,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Measures the data rate flowing through a pad of a running pipeline. Accepts pipeline ID, pad as 'element.pad', window and interval in milliseconds (optional). Returns buffer/byte counts and average, min, max, and per-interval bitrates."
+    )]
+    async fn gst_measure_bitrate(
+        &self,
+        Parameters(params): Parameters<MeasureBitrateParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_measure_bitrate").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_measure_bitrate' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let window = Duration::from_millis(params.window_ms.unwrap_or(5000).clamp(100, 60_000));
+        let interval = Duration::from_millis(params.interval_ms.unwrap_or(1000));
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let measurement = measure_bitrate(&pipeline, &params.pad, window, interval)
+            .await
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!(
+            "Bitrate on '{}' over {} ms:\n",
+            measurement.pad, measurement.window_ms
+        );
+        output.push_str(&format!(
+            "  Buffers: {}, Bytes: {}\n",
+            measurement.buffers, measurement.bytes
+        ));
+        output.push_str(&format!(
+            "  Average: {} bps\n  Min: {} bps\n  Max: {} bps\n",
+            measurement.average_bitrate, measurement.min_bitrate, measurement.max_bitrate
+        ));
+        if measurement.buffers == 0 {
+            output.push_str("  No data flowed through the pad during the window\n");
+        }
+        let samples: Vec<String> = measurement.samples.iter().map(|s| s.to_string()).collect();
+        output.push_str(&format!(
+            "  Samples ({} ms): {}\n",
+            measurement.interval_ms,
+            samples.join(", ")
+        ));
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+    #[tool(
+        description = "Extracts the metadata attached to buffers passing a pad of a running pipeline for a limited time. Accepts pipeline ID, pad as 'element.pad', window in milliseconds and maximum events (optional). Returns one event per buffer with regions of interest, timecodes, reference timestamps, KLV items and other metas; events are also sent as 'metadata' notifications."
+    )]
+    async fn gst_tap_metadata(
+        &self,
+        Parameters(params): Parameters<TapMetadataParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_tap_metadata").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_tap_metadata' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let window = Duration::from_millis(params.window_ms.unwrap_or(5000).clamp(100, 60_000));
+        let max_events = params.max_events.unwrap_or(100);
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+
+        // Events arrive on streaming threads; forward them from a task
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<MetadataEvent>();
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                let data = serde_json::to_value(&event).unwrap_or_default();
+                notifier.notify("metadata", LoggingLevel::Info, data).await;
+            }
+        });
+
+        let tap = tap_metadata(&pipeline, &params.pad, window, max_events, move |event| {
+            let _ = tx.send(event.clone());
+        })
+        .await
+        .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!(
+            "Metadata on '{}' over {} ms: {} buffers, {} with metadata{}\n",
+            tap.pad,
+            tap.window_ms,
+            tap.buffers,
+            tap.events.len(),
+            if tap.truncated {
+                format!(" (stopped collecting after {})", max_events)
+            } else {
+                String::new()
+            }
+        );
+        if tap.buffers == 0 {
+            output.push_str("  No data flowed through the pad during the window\n");
+        }
+        let mut kinds: std::collections::BTreeMap<String, usize> = Default::default();
+        for meta in tap.events.iter().flat_map(|e| &e.metas) {
+            let kind = match meta {
+                FrameMeta::RegionOfInterest { label, .. } => {
+                    format!("region of interest '{}'", label)
+                }
+                FrameMeta::Timecode { .. } => "timecode".to_string(),
+                FrameMeta::ReferenceTimestamp { .. } => "reference timestamp".to_string(),
+                FrameMeta::Klv { .. } => "KLV item".to_string(),
+                FrameMeta::Other { api, .. } => api.clone(),
+            };
+            *kinds.entry(kind).or_default() += 1;
+        }
+        for (kind, count) in &kinds {
+            output.push_str(&format!("  {}: {}\n", kind, count));
+        }
+
+        let json = serde_json::to_value(&tap).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Reads the SMPTE timecode of a running pipeline. Accepts pipeline ID, pad as 'element.pad' and timeout in milliseconds (optional). Without a pad, reads the last frame rendered by the sinks. Returns the timecode, where it was read and the frame's timestamp."
+    )]
+    async fn gst_read_timecode(
+        &self,
+        Parameters(params): Parameters<ReadTimecodeParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_read_timecode").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_read_timecode' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let reading = match &params.pad {
+            Some(pad) => {
+                let timeout =
+                    Duration::from_millis(params.timeout_ms.unwrap_or(2000).clamp(10, 30_000));
+                read_timecode(&pipeline, pad, timeout)
+                    .await
+                    .map_err(Into::<McpError>::into)?
+            }
+            None => current_timecode(&pipeline),
+        };
+
+        let output = match &reading {
+            Some(reading) => format!(
+                "Timecode {} on '{}' (pts: {})",
+                reading.timecode,
+                reading.source,
+                reading
+                    .pts_ns
+                    .map_or("none".to_string(), |pts| format!("{} ns", pts))
+            ),
+            None => format!(
+                "No timecode found in pipeline '{}'. Stamp one with the timecode option of gst_launch_pipeline or a timecodestamper element",
+                params.pipeline_id
+            ),
+        };
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "reading": reading,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Schedules an action (switch input, start/stop recording, set property) at a running time or SMPTE timecode of a running pipeline. Accepts pipeline ID, action, running time in milliseconds or timecode, and trigger pad (optional). The action runs from a pad probe on the first buffer at or after that time. Returns the scheduled action ID."
+    )]
+    async fn gst_schedule_action(
+        &self,
+        Parameters(params): Parameters<ScheduleActionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_schedule_action").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_schedule_action' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let trigger = parse_trigger(params.running_time_ms, params.timecode.as_deref())
+            .map_err(|e| McpError::invalid_params(e.to_string(), None))?;
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+
+        // Actions run on streaming threads; report them from a task
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<ScheduledEntry>();
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            if let Some(entry) = rx.recv().await {
+                let data = serde_json::to_value(&entry).unwrap_or_default();
+                notifier
+                    .notify("scheduled_action", LoggingLevel::Info, data)
+                    .await;
+            }
+        });
+
+        let manager = self.pipeline_manager.clone();
+        let entry = self
+            .scheduler
+            .schedule(
+                &pipeline,
+                &params.pipeline_id,
+                params.action,
+                trigger,
+                params.pad.as_deref(),
+                move |entry| {
+                    manager.add_bus_message(
+                        &entry.pipeline_id,
+                        BusMessage {
+                            timestamp: chrono::Utc::now(),
+                            message_type: "ScheduledAction".to_string(),
+                            message: match &entry.error {
+                                Some(error) => format!("{} failed: {}", entry.action, error),
+                                None => format!("{} ({})", entry.action, entry.status),
+                            },
+                            source: Some(entry.trigger_pad.clone()),
+                            cause: None,
+                            details: None,
+                        },
+                    );
+                    let _ = tx.send(entry);
+                },
+            )
+            .map_err(Into::<McpError>::into)?;
+
+        let output = format!(
+            "Scheduled '{}' ({}) at {} on '{}' in pipeline '{}'.\nA 'scheduled_action' notification is sent when it runs.",
+            entry.id,
+            entry.action,
+            match (&entry.timecode, entry.running_time_ms) {
+                (Some(timecode), _) => format!("timecode {}", timecode),
+                (None, Some(ms)) => format!("running time {} ms", ms),
+                (None, None) => "an unknown time".to_string(),
+            },
+            entry.trigger_pad,
+            entry.pipeline_id
+        );
+        let json = serde_json::to_value(&entry).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Lists scheduled actions. Accepts pipeline ID (optional). Returns each action with its trigger, status (pending/fired/failed/cancelled) and the running time it ran at."
+    )]
+    async fn gst_list_scheduled_actions(
+        &self,
+        Parameters(params): Parameters<ListScheduledActionsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_list_scheduled_actions").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_list_scheduled_actions' is not available in the current mode"
+                    .to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let entries = self.scheduler.list(params.pipeline_id.as_deref());
+        let output = if entries.is_empty() {
+            "No scheduled actions".to_string()
+        } else {
+            let mut output = format!("{} scheduled actions:\n", entries.len());
+            for entry in &entries {
+                output.push_str(&format!(
+                    "- {} [{}] {} in '{}'",
+                    entry.id, entry.status, entry.action, entry.pipeline_id
+                ));
+                if let Some(fired) = entry.fired_at_ns {
+                    output.push_str(&format!(" at {} ns", fired));
+                }
+                if let Some(error) = &entry.error {
+                    output.push_str(&format!(": {}", error));
+                }
+                output.push('\n');
+            }
+            output
+        };
+        let json = serde_json::json!({ "actions": entries });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Cancels a pending scheduled action. Accepts the action ID. Returns the cancelled action."
+    )]
+    async fn gst_cancel_scheduled_action(
+        &self,
+        Parameters(params): Parameters<CancelScheduledActionParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_cancel_scheduled_action").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_cancel_scheduled_action' is not available in the current mode"
+                    .to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let entry = self
+            .scheduler
+            .cancel(&params.action_id)
+            .map_err(Into::<McpError>::into)?;
+        let output = format!("Cancelled '{}' ({})", entry.id, entry.action);
+        let json = serde_json::to_value(&entry).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Keeps a rolling pre-record buffer in front of a recording valve, so a start_recording action includes the moments before its time, as DVRs and security cameras do. The queue feeding the valve holds back pre_record_ms of data, which the closed valve drops once it is older. Accepts pipeline ID, valve name and pre_record_ms (0 disarms). Returns the queue and its limits."
+    )]
+    async fn gst_set_prerecord(
+        &self,
+        Parameters(params): Parameters<SetPrerecordParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_set_prerecord").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_set_prerecord' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let prerecord = set_prerecord(
+            &pipeline,
+            &params.pipeline_id,
+            &params.valve,
+            gst::ClockTime::from_mseconds(params.pre_record_ms),
+        )
+        .map_err(Into::<McpError>::into)?;
+
+        let output = if prerecord.pre_record_ms == 0 {
+            format!(
+                "Pre-record buffer of '{}' disarmed; '{}' is back to its default limits",
+                prerecord.valve, prerecord.queue
+            )
+        } else {
+            format!(
+                "'{}' holds back {} ms in front of '{}' (max-size-time {} ms).\nRecordings started there with start_recording include the {} ms before their time, from the first keyframe.",
+                prerecord.queue,
+                prerecord.pre_record_ms,
+                prerecord.valve,
+                prerecord.max_size_time_ms,
+                prerecord.pre_record_ms
+            )
+        };
+        let json = serde_json::to_value(&prerecord).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Ramps a numeric property of a running pipeline over time, e.g. fading volume from 1.0 to 0.0 over 3 s or moving a compositor input's xpos. Accepts pipeline ID, property path, from (optional), to, duration_ms, interpolation (linear/cubic/step) and start_ms (optional). The property gets a GStreamer control binding, so the value follows the stream time of the buffers. Returns the animation ID."
+    )]
+    async fn gst_animate_property(
+        &self,
+        Parameters(params): Parameters<AnimatePropertyParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_animate_property").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_animate_property' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+
+        // Ramps finish on a clock thread; report them from a task
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Animation>();
+        let notifier = self.notifier.clone();
+        tokio::spawn(async move {
+            if let Some(animation) = rx.recv().await {
+                let data = serde_json::to_value(&animation).unwrap_or_default();
+                notifier.notify("animation", LoggingLevel::Info, data).await;
+            }
+        });
+
+        let manager = self.pipeline_manager.clone();
+        let animation = self
+            .animator
+            .animate(
+                &pipeline,
+                &params.pipeline_id,
+                &params.path,
+                params.from,
+                params.to,
+                gst::ClockTime::from_mseconds(params.duration_ms),
+                params.interpolation.unwrap_or_default(),
+                params.start_ms.map(gst::ClockTime::from_mseconds),
+                move |animation| {
+                    manager.add_bus_message(
+                        &animation.pipeline_id,
+                        BusMessage {
+                            timestamp: chrono::Utc::now(),
+                            message_type: "Animation".to_string(),
+                            message: format!(
+                                "{} reached {} ({})",
+                                animation.path, animation.value, animation.status
+                            ),
+                            source: animation.targets.first().cloned(),
+                            cause: None,
+                            details: None,
+                        },
+                    );
+                    let _ = tx.send(animation);
+                },
+            )
+            .map_err(Into::<McpError>::into)?;
+
+        let output = format!(
+            "Animating {} from {} to {} over {} ms ({:?}) as '{}', from stream time {} ms.\nAn 'animation' notification is sent when it finishes.",
+            animation.targets.join(", "),
+            animation.from,
+            animation.to,
+            animation.duration_ms,
+            animation.interpolation,
+            animation.id,
+            animation.start_ns / 1_000_000
+        );
+        let json = serde_json::to_value(&animation).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Cancels a running property animation. Accepts the animation ID and finish flag (optional) to jump to the end value. Returns the animation with the value it stopped at."
+    )]
+    async fn gst_cancel_animation(
+        &self,
+        Parameters(params): Parameters<CancelAnimationParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_cancel_animation").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_cancel_animation' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let animation = self
+            .animator
+            .cancel(&params.animation_id, params.finish.unwrap_or(false))
+            .map_err(Into::<McpError>::into)?;
+        let output = format!(
+            "Cancelled '{}' ({}) at {}",
+            animation.id, animation.path, animation.value
+        );
+        let json = serde_json::to_value(&animation).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Estimates the cost of a transcode before running it. Accepts the job's pipeline description and sample length in seconds (optional). Encodes a short sample from the middle of the input, writing file outputs to a temporary directory. Returns the predicted duration, CPU usage and output size."
+    )]
+    async fn gst_estimate_job(
+        &self,
+        Parameters(params): Parameters<EstimateJobParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_estimate_job").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_estimate_job' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let sample = Duration::from_secs(params.sample_seconds.unwrap_or(10).max(1));
+        let description = params.pipeline_description.clone();
+        let estimate = tokio::task::spawn_blocking(move || estimate_job(&description, sample))
+            .await
+            .map_err(|e| crate::GStreamerMcpError::Other(format!("Estimate task failed: {}", e)))
+            .and_then(|r| r)
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!(
+            "Sample: {:.1} s of media in {:.1} s ({:.1}x real time), CPU {:.0}% of one core ({} cores available)\n",
+            estimate.sample_media_ms as f64 / 1000.0,
+            estimate.sample_wall_ms as f64 / 1000.0,
+            estimate.speed,
+            estimate.cpu_percent,
+            estimate.cpu_cores
+        );
+        if let Some(kbps) = estimate.output_bitrate_kbps {
+            output.push_str(&format!("Output bitrate: {:.0} kbps\n", kbps));
+        }
+        match estimate.estimated_duration_ms {
+            Some(ms) => {
+                output.push_str(&format!(
+                    "Estimated duration: {}\n",
+                    gst::ClockTime::from_mseconds(ms)
+                ));
+                if ms > 3_600_000 {
+                    output.push_str("Warning: this job would run for over an hour\n");
+                }
+            }
+            None => output
+                .push_str("Estimated duration: unknown (no file input with a known duration)\n"),
+        }
+        if let Some(bytes) = estimate.estimated_output_bytes {
+            output.push_str(&format!(
+                "Estimated output size: {:.1} MB\n",
+                bytes as f64 / 1_000_000.0
+            ));
+        }
+        output.push_str("Estimates assume the whole input encodes like the sample");
+        let json = serde_json::to_value(&estimate).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Sets alert thresholds of a pipeline: errors per minute, warnings per minute and buffering events per minute (all optional; none removes the budget). Breaching one flips the pipeline health to Degraded (warnings, buffering) or Failed (errors) and sends an 'error_budget' notification. Returns the budget."
+    )]
+    async fn gst_set_error_budget(
+        &self,
+        Parameters(params): Parameters<SetErrorBudgetParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_set_error_budget").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_set_error_budget' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        self.pipeline_manager
+            .set_error_budget(&params.pipeline_id, params.budget.clone())
+            .map_err(Into::<McpError>::into)?;
+
+        let budget = &params.budget;
+        let output = if budget.is_empty() {
+            format!(
+                "Removed the error budget of pipeline '{}'; any error fails it again",
+                params.pipeline_id
+            )
+        } else {
+            let limit =
+                |value: Option<u32>| value.map_or("unlimited".to_string(), |v| v.to_string());
+            format!(
+                "Error budget of pipeline '{}' per minute: errors {}, warnings {}, buffering events {}. Health reset to Healthy",
+                params.pipeline_id,
+                limit(budget.max_errors_per_minute),
+                limit(budget.max_warnings_per_minute),
+                limit(budget.max_buffering_events_per_minute)
+            )
+        };
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "budget": budget,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Meters the audio of a running pipeline and warns about sustained clipping or silence. Accepts pipeline ID, tee name, clip level and duration, silence level and duration (all optional except the pipeline ID). The first call attaches a level meter to a tee; later calls replace the thresholds. Conditions are sent as 'audio_alert' notifications and recorded in the pipeline's messages. Returns the branch and thresholds."
+    )]
+    async fn gst_monitor_audio_levels(
+        &self,
+        Parameters(params): Parameters<MonitorAudioLevelsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_monitor_audio_levels").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_monitor_audio_levels' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let thresholds = params.thresholds.clone();
+        let existing = self
+            .pipeline_manager
+            .audio_monitor(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let branch = match existing {
+            Some(monitor) => {
+                monitor.lock().set_thresholds(thresholds.clone());
+                None
+            }
+            None => {
+                let pipeline = self
+                    .pipeline_manager
+                    .get_pipeline_handle(&params.pipeline_id)
+                    .map_err(Into::<McpError>::into)?;
+
+                // Alerts arrive on streaming threads; forward them from a task
+                let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<AudioAlert>();
+                let notifier = self.notifier.clone();
+                tokio::spawn(async move {
+                    while let Some(alert) = rx.recv().await {
+                        let level = if alert.active {
+                            LoggingLevel::Warning
+                        } else {
+                            LoggingLevel::Info
+                        };
+                        let data = serde_json::to_value(&alert).unwrap_or_default();
+                        notifier.notify("audio_alert", level, data).await;
+                    }
+                });
+
+                let monitor = Arc::new(parking_lot::Mutex::new(LevelMonitor::new(
+                    thresholds.clone(),
+                )));
+                let manager = self.pipeline_manager.clone();
+                let branch = attach_audio_monitor(
+                    &pipeline,
+                    &params.pipeline_id,
+                    params.tee_name.as_deref(),
+                    monitor.clone(),
+                    move |alert| {
+                        let message = if alert.active {
+                            format!(
+                                "Sustained {} for {} ms (peak {:.1} dBFS, RMS {:.1} dBFS)",
+                                alert.condition, alert.duration_ms, alert.peak_db, alert.rms_db
+                            )
+                        } else {
+                            format!("{} ended after {} ms", alert.condition, alert.duration_ms)
+                        };
+                        manager.add_bus_message(
+                            &alert.pipeline_id,
+                            BusMessage {
+                                timestamp: alert.timestamp,
+                                message_type: "AudioAlert".to_string(),
+                                message,
+                                source: Some(alert.branch.clone()),
+                                cause: None,
+                                details: None,
+                            },
+                        );
+                        let _ = tx.send(alert);
+                    },
+                )
+                .map_err(Into::<McpError>::into)?;
+                self.pipeline_manager
+                    .set_audio_monitor(&params.pipeline_id, monitor)
+                    .map_err(Into::<McpError>::into)?;
+                Some(branch)
+            }
+        };
+
+        let mut output = match &branch {
+            Some(branch) => format!(
+                "Audio monitor '{}' attached to pipeline '{}'.",
+                branch, params.pipeline_id
+            ),
+            None => format!(
+                "Updated the audio thresholds of pipeline '{}'.",
+                params.pipeline_id
+            ),
+        };
+        output.push_str(&format!(
+            "\nClipping: peak >= {} dBFS for {} ms\nSilence: RMS < {} dBFS for {} ms\nConditions are sent as 'audio_alert' notifications and appear as AudioAlert messages in gst_get_pipeline_status.",
+            thresholds.clip_level_db(),
+            thresholds.clip_duration_ms(),
+            thresholds.silence_level_db(),
+            thresholds.silence_duration_ms()
+        ));
+        let json = serde_json::json!({
+            "pipeline_id": params.pipeline_id,
+            "branch": branch,
+            "clip_level_db": thresholds.clip_level_db(),
+            "clip_duration_ms": thresholds.clip_duration_ms(),
+            "silence_level_db": thresholds.silence_level_db(),
+            "silence_duration_ms": thresholds.silence_duration_ms(),
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Migrates a live stream from one encoder to another (e.g. x264enc to nvh264enc) without interrupting the output. Accepts pipeline ID, encoder element name, replacement encoder description, warm-up and timeout (optional). Both encoders run from a tee during warm-up, then downstream switches to the new one on its first keyframe. Returns the switch timestamp."
+    )]
+    async fn gst_switch_encoder(
+        &self,
+        Parameters(params): Parameters<SwitchEncoderParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_switch_encoder").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_switch_encoder' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let switch = switch_encoder(
+            &pipeline,
+            &params.encoder,
+            &params.new_encoder,
+            Duration::from_millis(params.warmup_ms.unwrap_or(1000)),
+            Duration::from_millis(params.timeout_ms.unwrap_or(10000)),
+        )
+        .await
+        .map_err(Into::<McpError>::into)?;
+
+        let at = match (switch.switch_pts_ns, switch.switch_running_time_ns) {
+            (Some(pts), Some(running)) => format!("pts {} ns, running time {} ns", pts, running),
+            (Some(pts), None) => format!("pts {} ns", pts),
+            (None, Some(running)) => format!("running time {} ns", running),
+            (None, None) => "unknown time".to_string(),
+        };
+        self.pipeline_manager.add_bus_message(
+            &params.pipeline_id,
+            BusMessage {
+                timestamp: chrono::Utc::now(),
+                message_type: "EncoderSwitch".to_string(),
+                message: format!(
+                    "Switched from '{}' to '{}' at {}",
+                    switch.old_encoder, switch.new_encoder, at
+                ),
+                source: Some(switch.new_encoder.clone()),
+                cause: None,
+                details: None,
+            },
+        );
+
+        let output = format!(
+            "Switched pipeline '{}' from '{}' to '{}' at {} after {} ms",
+            params.pipeline_id, switch.old_encoder, switch.new_encoder, at, switch.elapsed_ms
+        );
+        let json = serde_json::to_value(&switch).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Publishes this host's clock on the network for synchronized playback across hosts. Accepts UDP port and bind address (both optional). Returns the port and the current clock time to derive a shared base time from."
+    )]
+    async fn gst_start_clock_server(
+        &self,
+        Parameters(params): Parameters<StartClockServerParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_start_clock_server").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_start_clock_server' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let server = self
+            .netsync
+            .start_clock_server(
+                params.address.as_deref(),
+                params.port.unwrap_or(DEFAULT_CLOCK_PORT),
+            )
+            .map_err(Into::<McpError>::into)?;
+        let output = format!(
+            "Clock server on {}:{} (clock time: {} ns).\nSync pipelines on other hosts with gst_sync_pipeline and clock_address set to this host",
+            server.address.as_deref().unwrap_or("0.0.0.0"),
+            server.port,
+            server.clock_time_ns
+        );
+        let json = serde_json::to_value(&server).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Slaves a pipeline to a shared network clock with a fixed base time and latency, then plays it, so pipelines on several hosts render in lockstep (multi-room audio, video walls). Accepts pipeline ID, clock server address and port, base time, latency and sync timeout (optional). Returns the sync settings to reuse on the other pipelines."
+    )]
+    async fn gst_sync_pipeline(
+        &self,
+        Parameters(params): Parameters<SyncPipelineParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_sync_pipeline").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_sync_pipeline' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let pipeline = self
+            .pipeline_manager
+            .get_pipeline_handle(&params.pipeline_id)
+            .map_err(Into::<McpError>::into)?;
+        let report = self
+            .netsync
+            .sync_pipeline(
+                &params.pipeline_id,
+                &pipeline,
+                &SyncRequest {
+                    clock_address: params.clock_address.clone(),
+                    clock_port: params.clock_port.unwrap_or(DEFAULT_CLOCK_PORT),
+                    base_time: params.base_time_ns.map(gst::ClockTime::from_nseconds),
+                    latency: Duration::from_millis(
+                        params.latency_ms.unwrap_or(DEFAULT_SYNC_LATENCY_MS),
+                    ),
+                },
+                Duration::from_millis(params.sync_timeout_ms.unwrap_or(5000)),
+            )
+            .map_err(Into::<McpError>::into)?;
+        self.pipeline_manager
+            .set_pipeline_state(&params.pipeline_id, gst::State::Playing)
+            .map_err(Into::<McpError>::into)?;
+
+        let output = format!(
+            "Pipeline '{}' synced to {}:{} with base time {} ns and latency {} ms.\nUse the same base_time_ns and latency_ms on the other pipelines",
+            params.pipeline_id,
+            report.settings.clock_address.as_deref().unwrap_or("local clock"),
+            report.settings.clock_port,
+            report.settings.base_time_ns,
+            report.settings.latency_ms
+        );
+        let json = serde_json::to_value(&report).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Reports the clock synchronization of synced pipelines. Accepts pipeline ID (optional). Returns whether each clock is locked, its drift against the server in ppm, and the shared running time."
+    )]
+    async fn gst_get_sync_report(
+        &self,
+        Parameters(params): Parameters<GetSyncReportParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_sync_report").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_sync_report' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let reports = match &params.pipeline_id {
+            Some(id) => vec![self.netsync.report(id).map_err(Into::<McpError>::into)?],
+            None => self.netsync.reports(),
+        };
+        let servers = self.netsync.servers();
+        let mut output = String::new();
+        for server in &servers {
+            output.push_str(&format!("Serving clock on port {}\n", server.port));
+        }
+        if reports.is_empty() {
+            output.push_str("No synchronized pipelines");
+        }
+        for report in &reports {
+            output.push_str(&format!(
+                "- {}: {}, drift {}, running time {}\n",
+                report.pipeline_id,
+                if report.synced {
+                    "synced"
+                } else {
+                    "NOT synced"
+                },
+                report
+                    .drift_ppm
+                    .map_or("unknown".to_string(), |ppm| format!("{:+.1} ppm", ppm)),
+                report
+                    .running_time_ns
+                    .map_or("not started".to_string(), |ns| format!("{} ns", ns))
+            ));
+        }
+        let json = serde_json::json!({
+            "servers": servers,
+            "pipelines": reports,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Pages through the recorded bus message history of a pipeline, oldest first. Accepts pipeline ID, message types, minimum severity, source element glob, since/until times, cursor and limit (all optional but the ID). Returns the messages with their sequence numbers and the cursor of the next page."
+    )]
+    async fn gst_get_bus_messages(
+        &self,
+        Parameters(params): Parameters<GetBusMessagesParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_bus_messages").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_bus_messages' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let query = HistoryQuery {
+            filter: MessageFilter {
+                types: params.types,
+                min_severity: params.min_severity,
+                source: params.source,
+            },
+            since: parse_time_param("since", params.since.as_deref())?,
+            until: parse_time_param("until", params.until.as_deref())?,
+            cursor: params.cursor,
+            limit: params.limit.unwrap_or(50).clamp(1, 500),
+        };
+        let page = self
+            .pipeline_manager
+            .query_bus_messages(&params.pipeline_id, &query)
+            .map_err(Into::<McpError>::into)?;
+
+        let time = self.time_display().await;
+        let mut output = format!(
+            "{} message(s) of pipeline '{}' ({} kept)\n",
+            page.messages.len(),
+            params.pipeline_id,
+            page.retained
+        );
+        if page.dropped > 0 {
+            output.push_str(&format!(
+                "{} message(s) after the cursor were dropped from the history\n",
+                page.dropped
+            ));
+        }
+        for entry in &page.messages {
+            output.push_str(&format!(
+                "  #{} [{}] {}: {}{}\n",
+                entry.seq,
+                time.format(entry.message.timestamp),
+                entry.message.message_type,
+                entry.message.message,
+                entry
+                    .message
+                    .source
+                    .as_ref()
+                    .map(|source| format!(" ({})", source))
+                    .unwrap_or_default()
+            ));
+        }
+        output.push_str(&format!(
+            "Next cursor: {}{}",
+            page.next_cursor,
+            if page.has_more {
+                " (more messages)"
+            } else {
+                ""
+            }
+        ));
+        let json = serde_json::to_value(&page).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Tails the on-disk log of a pipeline, which holds its launches, bus messages and removal as JSON lines and outlives both the in-memory history and the pipeline. Accepts pipeline ID and number of lines (optional). Needs pipeline_log_dir to be configured. Returns the last records."
+    )]
+    async fn gst_get_pipeline_log(
+        &self,
+        Parameters(params): Parameters<GetPipelineLogParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_pipeline_log").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_pipeline_log' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let Some(dir) = self.pipeline_manager.log_dir().map(|dir| dir.to_path_buf()) else {
+            return Err(McpError::invalid_params(
+                "Pipeline logs are off; set pipeline_log_dir to keep them".to_string(),
+                None,
+            ));
+        };
+        let lines = params.lines.unwrap_or(100).clamp(1, 1000);
+        let pipeline_id = params.pipeline_id.clone();
+        let tail = tokio::task::spawn_blocking(move || tail_log(&dir, &pipeline_id, lines))
+            .await
+            .map_err(|e| crate::GStreamerMcpError::Other(format!("Log task failed: {}", e)))
+            .and_then(|r| r)
+            .map_err(Into::<McpError>::into)?;
+
+        let time = self.time_display().await;
+        let mut output = format!(
+            "Last {} record(s) of {} ({} bytes)\n",
+            tail.records.len(),
+            tail.path.display(),
+            tail.size_bytes
+        );
+        for record in &tail.records {
+            let line = match record {
+                LogRecord::Created { description, .. } => format!("Created: {}", description),
+                LogRecord::Message(entry) => format!(
+                    "#{} {}: {}",
+                    entry.seq, entry.message.message_type, entry.message.message
+                ),
+                LogRecord::Removed { .. } => "Removed".to_string(),
+            };
+            output.push_str(&format!(
+                "  [{}] {}\n",
+                time.format(record.timestamp()),
+                line
+            ));
+        }
+        if tail.skipped_lines > 0 {
+            output.push_str(&format!(
+                "{} unreadable line(s) skipped\n",
+                tail.skipped_lines
+            ));
+        }
+        let json = serde_json::to_value(&tail).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Registers a bus message filter for a pipeline subscriber. Accepts pipeline ID, subscriber name (optional), message types, minimum severity, and source element glob (all optional). Returns the active filters for the pipeline."
+    )]
+    async fn gst_set_message_filter(
+        &self,
+        Parameters(params): Parameters<SetMessageFilterParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_set_message_filter").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_set_message_filter' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let subscriber = params.subscriber.as_deref().unwrap_or("default");
+        let filter = MessageFilter {
+            types: params.types,
+            min_severity: params.min_severity,
+            source: params.source,
+        };
+        self.pipeline_manager
+            .set_message_filter(&params.pipeline_id, subscriber, filter)
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!(
+            "Message filter for subscriber '{}' set on pipeline '{}'\n\nActive filters:\n",
+            subscriber, params.pipeline_id
+        );
+        for (name, filter) in self
+            .pipeline_manager
+            .list_message_filters(&params.pipeline_id)
+        {
+            output.push_str(&format_message_filter(&name, &filter));
+        }
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Removes the bus message filter of a pipeline subscriber. Accepts pipeline ID and subscriber name (optional). Returns whether a filter was removed."
+    )]
+    async fn gst_remove_message_filter(
+        &self,
+        Parameters(params): Parameters<RemoveMessageFilterParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_remove_message_filter").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_remove_message_filter' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let subscriber = params.subscriber.as_deref().unwrap_or("default");
+        let output = if self
+            .pipeline_manager
+            .remove_message_filter(&params.pipeline_id, subscriber)
+        {
+            format!(
+                "Message filter for subscriber '{}' removed from pipeline '{}'",
+                subscriber, params.pipeline_id
+            )
+        } else {
+            format!(
+                "No message filter registered for subscriber '{}' on pipeline '{}'",
+                subscriber, params.pipeline_id
+            )
+        };
+
+        Ok(CallToolResult::success(vec![Content::text(output)]))
+    }
+
+    #[tool(
+        description = "Subscribes to bus events so they are pushed as 'bus_event' notifications as they happen, instead of polling gst_get_pipeline_status. Accepts pipeline ID, events (error, warning, eos, state_changed) and subscription ID (all optional). Returns the subscription ID and the active subscriptions."
+    )]
+    async fn gst_subscribe_events(
+        &self,
+        Parameters(params): Parameters<SubscribeEventsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_subscribe_events").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_subscribe_events' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        if let Some(pipeline_id) = &params.pipeline_id {
+            self.pipeline_manager
+                .get_pipeline_handle(pipeline_id)
+                .map_err(Into::<McpError>::into)?;
+        }
+        let subscription = self.events.subscribe(
+            params.subscription_id,
+            params.pipeline_id,
+            params.events.unwrap_or_default(),
+        );
+
+        let mut output = format!(
+            "Subscribed '{}' to {} of {}\n",
+            subscription.id,
+            format_event_kinds(&subscription.events),
+            subscription
+                .pipeline_id
+                .as_ref()
+                .map(|id| format!("pipeline '{}'", id))
+                .unwrap_or_else(|| "every pipeline".to_string())
+        );
+        if !self.notifier.is_connected().await {
+            output.push_str("Warning: no client session is attached yet; events are not queued\n");
+        }
+        let subscriptions = self.events.list();
+        output.push_str(&format_event_subscriptions(&subscriptions));
+
+        let json = serde_json::json!({
+            "subscription": subscription,
+            "subscriptions": subscriptions,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Removes a bus event subscription made with gst_subscribe_events. Accepts subscription ID. Returns whether it was removed and the remaining subscriptions."
+    )]
+    async fn gst_unsubscribe_events(
+        &self,
+        Parameters(params): Parameters<UnsubscribeEventsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_unsubscribe_events").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_unsubscribe_events' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let removed = self.events.unsubscribe(&params.subscription_id);
+        let mut output = match &removed {
+            Some(_) => format!("Subscription '{}' removed\n", params.subscription_id),
+            None => format!("No subscription '{}'\n", params.subscription_id),
+        };
+        let subscriptions = self.events.list();
+        output.push_str(&format_event_subscriptions(&subscriptions));
+
+        let json = serde_json::json!({
+            "removed": removed.is_some(),
+            "subscriptions": subscriptions,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Reports server health and readiness. Accepts no parameters. Returns GStreamer version, discovery cache status (cold/warming/ready), active pipeline count, and operational mode."
+    )]
+    async fn gst_health(&self) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_health").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_health' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let gstreamer_ok = crate::discovery::ensure_gstreamer_initialized().is_ok();
+        let version = if gstreamer_ok {
+            gst::version_string().to_string()
+        } else {
+            "unavailable".to_string()
+        };
+        let cache_status = self.cache.status().await;
+        let (mode, cache_enabled) = {
+            let config = self.config.read().await;
+            (config.operational_mode.clone(), config.cache_enabled)
+        };
+        let pipelines = self.pipeline_manager.list_pipelines().len();
+        let max_pipelines = self.pipeline_manager.max_pipelines();
+        let enabled_tools = self.enabled_tools.read().await.len();
+        let description_language = crate::locale::description_language();
+
+        let cache_text = if !cache_enabled {
+            "disabled".to_string()
+        } else {
+            match &cache_status {
+                CacheStatus::Cold => "cold (populated on first request)".to_string(),
+                CacheStatus::Warming => "warming".to_string(),
+                CacheStatus::Ready { elements, plugins } => {
+                    format!("ready ({} elements, {} plugins)", elements, plugins)
+                }
+                CacheStatus::Partial { elements, plugins } => format!(
+                    "partial (elements: {}, plugins: {})",
+                    elements.map_or("not cached".to_string(), |n| n.to_string()),
+                    plugins.map_or("not cached".to_string(), |n| n.to_string())
+                ),
+                CacheStatus::Failed { error } => format!("failed ({})", error),
+            }
+        };
+
+        let output = format!(
+            "Status: {}\nGStreamer: {}\nDiscovery cache: {}\nPipelines: {}/{}\nMode: {:?}\nEnabled tools: {}\nDescriptions: {}\n",
+            if gstreamer_ok { "ok" } else { "error" },
+            version,
+            cache_text,
+            pipelines,
+            max_pipelines,
+            mode,
+            enabled_tools,
+            description_language.as_deref().unwrap_or("English")
+        );
+
+        let json = serde_json::json!({
+            "status": if gstreamer_ok { "ok" } else { "error" },
+            "gstreamer_version": version,
+            "cache_enabled": cache_enabled,
+            "cache": cache_status,
+            "pipelines": pipelines,
+            "max_pipelines": max_pipelines,
+            "mode": mode,
+            "enabled_tools": enabled_tools,
+            "description_language": description_language,
+        });
+
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Diagnoses the GStreamer installation. Accepts no parameters. Checks initialization, base plugins, audio/video sinks (headless or not), hardware codecs, registry writability and environment variables. Returns each check and a prioritized fix list."
+    )]
+    async fn gst_doctor(&self) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_doctor").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_doctor' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let report = tokio::task::spawn_blocking(run_doctor).await.map_err(|e| {
+            Into::<McpError>::into(crate::GStreamerMcpError::Other(format!(
+                "Doctor task failed: {}",
+                e
+            )))
+        })?;
+
+        let mut output = format!(
+            "Doctor: {} error(s), {} warning(s), {} passed\n",
+            report.count(CheckStatus::Error),
+            report.count(CheckStatus::Warning),
+            report.count(CheckStatus::Ok)
+        );
+        if !report.fixes.is_empty() {
+            output.push_str("\nFixes, most important first:\n");
+            for (i, fix) in report.fixes.iter().enumerate() {
+                output.push_str(&format!("  {}. {}\n", i + 1, fix));
+            }
+        }
+        output.push_str("\nChecks:\n");
+        for check in &report.checks {
+            let status = match check.status {
+                CheckStatus::Ok => "ok",
+                CheckStatus::Warning => "warning",
+                CheckStatus::Error => "error",
+            };
+            output.push_str(&format!(
+                "  [{}] {}: {}\n",
+                status, check.name, check.detail
+            ));
+        }
+
+        let json = serde_json::to_value(&report).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Reports per-tool execution metrics collected since startup. Optional: tool (filter to one tool), reset (clear metrics after reporting). Returns call counts, success/failure counters, slow calls, and average/max/last duration in milliseconds."
+    )]
+    async fn gst_get_tool_metrics(
+        &self,
+        Parameters(params): Parameters<GetToolMetricsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_get_tool_metrics").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_get_tool_metrics' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let mut stats = self.metrics.snapshot().await;
+        if let Some(tool) = &params.tool {
+            stats.retain(|s| &s.tool == tool);
+        }
+        if params.reset.unwrap_or(false) {
+            self.metrics.reset().await;
+        }
+
+        let threshold_ms = self.metrics.slow_call_threshold().as_millis() as u64;
+        let mut output = format!(
+            "Tool metrics ({} tools, slow-call threshold {} ms):\n\n",
+            stats.len(),
+            threshold_ms
+        );
+        if stats.is_empty() {
+            output.push_str("No tool calls recorded.\n");
+        }
+        for stat in &stats {
+            output.push_str(&format_tool_stats(stat));
+        }
+
+        let json = serde_json::json!({
+            "slow_call_threshold_ms": threshold_ms,
+            "tools": stats,
+        });
+
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Queues a batch pipeline (e.g., a transcode) that runs until EOS. Accepts gst-launch syntax, priority and custom job ID (optional). At most max_concurrent_jobs run at once; the rest wait in priority/FIFO order. Returns the job ID and queue position."
+    )]
+    async fn gst_submit_job(
+        &self,
+        Parameters(params): Parameters<SubmitJobParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_submit_job").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_submit_job' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let job = self
+            .jobs
+            .submit(
+                &params.pipeline_description,
+                params.priority,
+                params.job_id,
+                params.upload,
+            )
+            .map_err(Into::<McpError>::into)?;
+        let queued = self.jobs.list(Some(JobStatus::Queued));
+        let position = queued.iter().position(|j| j.id == job.id);
+
+        let output = match position {
+            Some(position) => format!(
+                "Job '{}' queued (priority {}, position {} of {})",
+                job.id,
+                job.priority,
+                position + 1,
+                queued.len()
+            ),
+            None => format!("Job '{}' submitted", job.id),
+        };
+        let json = serde_json::json!({
+            "job": job,
+            "queue_position": position.map(|p| p + 1),
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Lists batch jobs in queue order. Accepts status filter (optional: queued, running, completed, failed, cancelled). Returns job IDs, status, priority, timestamps, and errors."
+    )]
+    async fn gst_list_jobs(
+        &self,
+        Parameters(params): Parameters<ListJobsParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_list_jobs").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_list_jobs' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let jobs = self.jobs.list(params.status);
+        let mut output = format!(
+            "Jobs: {} (max {} running at once)\n\n",
+            jobs.len(),
+            self.jobs.max_concurrent()
+        );
+        let time = self.time_display().await;
+        for job in &jobs {
+            output.push_str(&format_job(job, &time));
+        }
+
+        let json = serde_json::json!({
+            "count": jobs.len(),
+            "max_concurrent": self.jobs.max_concurrent(),
+            "jobs": jobs,
+        });
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Cancels a batch job. Accepts job ID. Queued jobs are cancelled immediately; running jobs have their pipeline stopped. Returns the job status."
+    )]
+    async fn gst_cancel_job(
+        &self,
+        Parameters(params): Parameters<CancelJobParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_cancel_job").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_cancel_job' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let job = self
+            .jobs
+            .cancel(&params.job_id)
+            .map_err(Into::<McpError>::into)?;
+        let output = match job.status {
+            JobStatus::Running => format!("Job '{}' is stopping", job.id),
+            _ => format!("Job '{}' cancelled", job.id),
+        };
+        let json = serde_json::to_value(&job).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Joins media files into one output as a batch job. Accepts input paths (in order), output path, reencode flag, tonemap flag (default: HDR inputs), downmix flag (default: false), priority, job ID and upload target (optional). Checks codec compatibility up front: matching inputs are remuxed with concat, others are re-encoded to the first input's format. Returns the job ID, mode, and compatibility issues; track progress with gst_list_jobs."
+    )]
+    async fn gst_concat_media(
+        &self,
+        Parameters(params): Parameters<ConcatMediaParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_concat_media").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_concat_media' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let inputs = params
+            .inputs
+            .iter()
+            .map(|input| check_input_file(input))
+            .collect::<crate::Result<Vec<_>>>()
+            .map_err(Into::<McpError>::into)?;
+        let output = std::path::PathBuf::from(&params.output);
+        if inputs.contains(&output) {
+            return Err(McpError::invalid_params(
+                "The output must not be one of the inputs".to_string(),
+                None,
+            ));
+        }
+
+        let reencode = params.reencode.unwrap_or(false);
+        let tonemap = params.tonemap;
+        let downmix = params.downmix.unwrap_or(false);
+        let plan_output = output.clone();
+        let (infos, plan) = tokio::task::spawn_blocking(move || {
+            let infos = inputs
+                .iter()
+                .map(|input| probe_media(input))
+                .collect::<crate::Result<Vec<_>>>()?;
+            let conversions = Conversions {
+                tonemap: Tonemap::resolve(tonemap, infos.iter().map(|i| i.video.first())),
+                downmix,
+            };
+            let plan = plan_concat(&infos, &plan_output, reencode, &conversions)?;
+            Ok((infos, plan))
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Probe task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        self.confirm(&peer, &format!("write {}", output.display()))
+            .await?;
+
+        let job = self
+            .jobs
+            .submit(
+                &plan.description,
+                params.priority,
+                params.job_id,
+                params.upload,
+            )
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output_text = format!(
+            "Job '{}' queued to join {} files into {} ({})",
+            job.id,
+            infos.len(),
+            output.display(),
+            match plan.mode {
+                ConcatMode::Remux => "remux, no re-encoding",
+                ConcatMode::Reencode => "re-encode",
             }
-            output
-        };
+        );
+        if let Some(duration) = plan.duration_ns {
+            output_text.push_str(&format!(
+                "\nTotal duration: {}",
+                gst::ClockTime::from_nseconds(duration)
+            ));
+        }
+        for issue in &plan.issues {
+            output_text.push_str(&format!("\nRe-encoding because {}", issue));
+        }
+        if plan.tonemapped {
+            output_text.push_str("\nHDR video is tone-mapped to SDR");
+        }
+        if plan.downmixed {
+            output_text.push_str("\nMultichannel audio is downmixed to stereo");
+        }
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+        let json = serde_json::json!({
+            "job": job,
+            "mode": plan.mode,
+            "issues": plan.issues,
+            "duration_ns": plan.duration_ns,
+            "tonemapped": plan.tonemapped,
+            "downmixed": plan.downmixed,
+            "inputs": infos,
+            "description": plan.description,
+        });
+        Ok(success_with_json(output_text, json))
     }
 
     #[tool(
-        description = "Retrieves detailed information about a specific GStreamer element. Accepts element name (required). Returns properties with types/defaults, pad templates, signals, and classification."
+        description = "Normalizes the loudness of a file to a target LUFS (EBU R128). Accepts input path, output path, target_lufs (default: -23) and true_peak_limit in dBTP (default: -1). Measures the input, applies the gain to the audio (video is copied), then measures the output. Returns before/after loudness, true peak, and the applied gain."
     )]
-    async fn gst_inspect_element(
+    async fn gst_normalize_audio(
         &self,
-        Parameters(params): Parameters<InspectElementParams>,
+        Parameters(params): Parameters<NormalizeAudioParams>,
+        peer: Peer<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        let info = inspect_element(&params.element_name).map_err(Into::<McpError>::into)?;
+        if !self.is_tool_enabled("gst_normalize_audio").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_normalize_audio' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
 
-        let mut output = format!("Element: {}\n", info.name);
-        output.push_str(&format!("Description: {}\n", info.description));
-        output.push_str(&format!("Plugin: {}\n", info.plugin_name));
-        output.push_str(&format!("Rank: {}\n", info.rank));
-        output.push_str(&format!("Classification: {}\n\n", info.classification));
+        let input = check_input_file(&params.input).map_err(Into::<McpError>::into)?;
+        let output = std::path::PathBuf::from(&params.output);
+        if input == output {
+            return Err(McpError::invalid_params(
+                "The output must differ from the input".to_string(),
+                None,
+            ));
+        }
+        let target_lufs = params.target_lufs.unwrap_or(-23.0);
+        let peak_limit = params.true_peak_limit.unwrap_or(-1.0);
 
-        // Properties section
-        if !info.properties.is_empty() {
-            output.push_str("Properties:\n");
-            for prop in &info.properties {
-                output.push_str(&format!(
-                    "  {} ({}) [{}]\n    {}\n",
-                    prop.name,
-                    prop.type_name,
-                    prop.flags.join(", "),
-                    prop.description
-                ));
-                if let Some(default) = &prop.default_value {
-                    output.push_str(&format!("    Default: {}\n", default));
-                }
-            }
-            output.push('\n');
+        // Measure and plan first so nothing is written if the input is unusable
+        let plan_output = output.clone();
+        let (before, gain_db, description) = tokio::task::spawn_blocking(move || {
+            let info = probe_media(&input)?;
+            let before = measure_loudness(&input)?;
+            let gain_db = normalization_gain_db(&before, target_lufs, peak_limit);
+            let description = normalize_description(&info, &plan_output, gain_db)?;
+            Ok((before, gain_db, description))
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Analysis task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        self.confirm(&peer, &format!("write {}", output.display()))
+            .await?;
+
+        let render_output = output.clone();
+        let after = tokio::task::spawn_blocking(move || {
+            run_to_completion(&description, |_| {})?;
+            measure_loudness(&render_output)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Render task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let artifacts = self.artifacts.clone();
+        let location = output.to_string_lossy().into_owned();
+        let produced = tokio::task::spawn_blocking(move || {
+            artifacts.register("gst_normalize_audio", &[location])
+        })
+        .await
+        .unwrap_or_default();
+
+        let text = format!(
+            "Normalized {} to {} LUFS\nBefore: {}\nAfter:  {}\nGain applied: {:+.2} dB",
+            params.input,
+            target_lufs,
+            format_loudness(&before),
+            format_loudness(&after),
+            gain_db
+        );
+        let json = serde_json::json!({
+            "output": output,
+            "target_lufs": target_lufs,
+            "true_peak_limit": peak_limit,
+            "gain_db": gain_db,
+            "before": before,
+            "after": after,
+            "artifacts": produced,
+        });
+        Ok(success_with_json(text, json))
+    }
+
+    #[tool(
+        description = "Exports the frame of a video file at a given timestamp as an image. Accepts input path, output path (png or jpg), position_ns and accurate flag (default: true; false exports the keyframe at or before the timestamp, which is faster). Returns the timestamp and size of the exported frame."
+    )]
+    async fn gst_extract_frame_at(
+        &self,
+        Parameters(params): Parameters<ExtractFrameAtParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_extract_frame_at").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_extract_frame_at' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
         }
 
-        // Pad templates section
-        if !info.pad_templates.is_empty() {
-            output.push_str("Pad Templates:\n");
-            for pad in &info.pad_templates {
-                output.push_str(&format!(
-                    "  {} ({}, {})\n    Caps: {}\n",
-                    pad.name, pad.direction, pad.presence, pad.caps
-                ));
-            }
-            output.push('\n');
+        let input = check_input_file(&params.input).map_err(Into::<McpError>::into)?;
+        let output = std::path::PathBuf::from(&params.output);
+        if input == output {
+            return Err(McpError::invalid_params(
+                "The output must differ from the input".to_string(),
+                None,
+            ));
         }
+        let accurate = params.accurate.unwrap_or(true);
 
-        // Signals section
-        if !info.signals.is_empty() {
-            output.push_str("Signals:\n");
-            for signal in &info.signals {
-                output.push_str(&format!(
-                    "  {} -> {}\n    Parameters: {}\n",
-                    signal.name,
-                    signal.return_type,
-                    signal.parameters.join(", ")
-                ));
+        self.confirm(&peer, &format!("write {}", output.display()))
+            .await?;
+
+        let position = gst::ClockTime::from_nseconds(params.position_ns);
+        let frame = tokio::task::spawn_blocking(move || {
+            extract_frame_at(&input, &output, position, accurate)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Extraction task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let artifacts = self.artifacts.clone();
+        let location = frame.output.to_string_lossy().into_owned();
+        let produced = tokio::task::spawn_blocking(move || {
+            artifacts.register("gst_extract_frame_at", &[location])
+        })
+        .await
+        .unwrap_or_default();
+
+        let pts = frame
+            .pts_ns
+            .map(|pts| gst::ClockTime::from_nseconds(pts).to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let text = format!(
+            "Wrote {}x{} frame at {} to {} ({} seek, requested {})",
+            frame.width,
+            frame.height,
+            pts,
+            frame.output.display(),
+            if frame.accurate {
+                "accurate"
+            } else {
+                "keyframe"
+            },
+            position
+        );
+        let json = serde_json::json!({
+            "frame": frame,
+            "artifacts": produced,
+        });
+        Ok(success_with_json(text, json))
+    }
+
+    #[tool(
+        description = "Extracts a short audio segment for fingerprinting or identification. Accepts input file path with start_ns, sample_rate and channels, or pipeline_id and pad of a running pipeline; duration_ms (default: 10000, max: 30000) and output path (optional). File audio is decoded to 16-bit PCM WAV; pipeline audio is recorded from the pad, raw as WAV and encoded as-is. Returns the file as an artifact resource with its format."
+    )]
+    async fn gst_extract_audio_segment(
+        &self,
+        Parameters(params): Parameters<ExtractAudioSegmentParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_extract_audio_segment").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_extract_audio_segment' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let duration = Duration::from_millis(params.duration_ms.unwrap_or(10_000).max(100))
+            .min(MAX_AUDIO_SEGMENT);
+        let segment = match (&params.input, &params.pipeline_id, &params.pad) {
+            (Some(input), None, None) => {
+                let input = check_input_file(input).map_err(Into::<McpError>::into)?;
+                let start = gst::ClockTime::from_nseconds(params.start_ns.unwrap_or(0));
+                let (rate, channels) = (params.sample_rate, params.channels);
+                tokio::task::spawn_blocking(move || {
+                    extract_audio_segment(&input, start, duration, rate, channels)
+                })
+                .await
+                .map_err(|e| {
+                    crate::GStreamerMcpError::Other(format!("Extraction task failed: {}", e))
+                })
+                .and_then(|r| r)
+                .map_err(Into::<McpError>::into)?
             }
+            (None, Some(pipeline_id), Some(pad)) => {
+                let pipeline = self
+                    .pipeline_manager
+                    .get_pipeline_handle(pipeline_id)
+                    .map_err(Into::<McpError>::into)?;
+                tap_audio_segment(&pipeline, pad, duration)
+                    .await
+                    .map_err(Into::<McpError>::into)?
+            }
+            _ => {
+                return Err(McpError::invalid_params(
+                    "Give either input, or pipeline_id and pad".to_string(),
+                    None,
+                ))
+            }
+        };
+
+        let output = match &params.output {
+            Some(output) => std::path::PathBuf::from(output),
+            None => std::env::temp_dir().join(format!(
+                "gstreamer-mcp-segment-{}.{}",
+                uuid::Uuid::new_v4(),
+                segment_extension(&segment)
+            )),
+        };
+        let artifacts = self.artifacts.clone();
+        let (write_segment_data, write_output) = (segment.clone(), output.clone());
+        let produced = tokio::task::spawn_blocking(move || {
+            write_segment(&write_segment_data, &write_output)?;
+            Ok(artifacts.register(
+                "gst_extract_audio_segment",
+                &[write_output.to_string_lossy().into_owned()],
+            ))
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Write task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        let mut text = format!(
+            "Wrote {} ms of audio ({} bytes) to {}\nFormat: {}\n",
+            segment.duration_ns / 1_000_000,
+            segment.data.len(),
+            output.display(),
+            segment.caps
+        );
+        if segment.truncated {
+            text.push_str("Stopped early at the segment size limit\n");
         }
+        for artifact in &produced {
+            text.push_str(&format!("Resource: {}\n", artifact.uri()));
+        }
+        let json = serde_json::json!({
+            "output": output,
+            "segment": segment,
+            "artifacts": produced,
+        });
+        Ok(success_with_json(text, json))
+    }
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+    #[tool(
+        description = "Analyzes the keyframe/GOP structure of the first video stream of a file without decoding it. Accepts input path. Returns GOP length distribution in frames and seconds, keyframe timestamps, B-frame usage, colorimetry/HDR metadata and hints about seeking and HLS segmenting."
+    )]
+    async fn gst_analyze_gop(
+        &self,
+        Parameters(params): Parameters<AnalyzeGopParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_analyze_gop").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_analyze_gop' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let input = check_input_file(&params.input).map_err(Into::<McpError>::into)?;
+        let analysis = tokio::task::spawn_blocking(move || analyze_file_gops(&input))
+            .await
+            .map_err(|e| crate::GStreamerMcpError::Other(format!("Analysis task failed: {}", e)))
+            .and_then(|r| r)
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = format!(
+            "GOP structure of {} ({}):\n  Frames: {}, keyframes: {}, B-frames: {}\n",
+            params.input, analysis.caps, analysis.frames, analysis.keyframes, analysis.b_frames
+        );
+        output.push_str(&format!(
+            "  GOP length: {}-{} frames (average {:.1}), {:.2}-{:.2} s\n",
+            analysis.min_gop_frames,
+            analysis.max_gop_frames,
+            analysis.average_gop_frames,
+            analysis.min_gop_seconds,
+            analysis.max_gop_seconds
+        ));
+        output.push_str("  Distribution:\n");
+        for (length, count) in &analysis.gop_length_distribution {
+            output.push_str(&format!("    {} frames: {} GOP(s)\n", length, count));
+        }
+        let listed: Vec<String> = analysis
+            .keyframe_timestamps_ns
+            .iter()
+            .take(20)
+            .map(|pts| format!("{:.3}", *pts as f64 / 1e9))
+            .collect();
+        output.push_str(&format!("  Keyframes (s): {}", listed.join(", ")));
+        if analysis.keyframes > listed.len() as u64 {
+            output.push_str(", ...");
+        }
+        output.push('\n');
+        if let Some(color) = &analysis.color {
+            output.push_str(&format!("  Color: {}\n", color));
+        }
+        for hint in &analysis.hints {
+            output.push_str(&format!("  Note: {}\n", hint));
+        }
+
+        let json = serde_json::to_value(&analysis).unwrap_or_default();
+        Ok(success_with_json(output, json))
     }
 
     #[tool(
-        description = "Lists all available GStreamer plugins. Accepts name filter (optional). Returns plugin names, versions, descriptions, licenses, and contained elements."
+        description = "Scans a media file and returns its bitrate over time, per stream and combined. Accepts input path, interval_ms (default: 1000) and target_bitrate in bps (optional). Returns per-interval samples, average and peak bitrates, intervals over the target, and re-encoding recommendations."
     )]
-    async fn gst_list_plugins(
+    async fn gst_bitrate_profile(
         &self,
-        Parameters(params): Parameters<ListPluginsParams>,
+        Parameters(params): Parameters<BitrateProfileParams>,
     ) -> Result<CallToolResult, McpError> {
-        let plugins = if self.config.read().await.cache_enabled {
-            self.cache.get_plugins().await
-        } else {
-            discover_all_plugins()
+        if !self.is_tool_enabled("gst_bitrate_profile").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_bitrate_profile' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
         }
+
+        let input = check_input_file(&params.input).map_err(Into::<McpError>::into)?;
+        let interval = Duration::from_millis(params.interval_ms.unwrap_or(1000).max(100));
+        let target = params.target_bitrate;
+        let profile = tokio::task::spawn_blocking(move || {
+            bitrate_profile(&input, interval.as_nanos() as u64, target)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Scan task failed: {}", e)))
+        .and_then(|r| r)
         .map_err(Into::<McpError>::into)?;
 
-        let mut filtered_plugins = plugins;
+        let mut output = format!(
+            "Bitrate of {} per {} ms:\n",
+            params.input, profile.interval_ms
+        );
+        for stream in &profile.streams {
+            output.push_str(&format!(
+                "  Stream {} ({}): average {} kbps, peak {} kbps at {:.1} s\n",
+                stream.index,
+                stream.caps.split(',').next().unwrap_or_default(),
+                stream.average_bitrate / 1000,
+                stream.peak_bitrate / 1000,
+                stream.peak_at_ns as f64 / 1e9
+            ));
+        }
+        if let Some(target) = target {
+            output.push_str(&format!(
+                "  {} interval(s) above the {} kbps target\n",
+                profile.over_target_ns.len(),
+                target / 1000
+            ));
+        }
+        for recommendation in &profile.recommendations {
+            output.push_str(&format!("  Recommendation: {}\n", recommendation));
+        }
+        output.push_str("  Per-interval samples are in the structured result\n");
 
-        // Apply filter if provided
-        if let Some(filter) = params.filter {
-            let filter_lower = filter.to_lowercase();
-            filtered_plugins.retain(|p| p.name.to_lowercase().contains(&filter_lower));
+        let json = serde_json::to_value(&profile).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Checks whether a media file is OK by decoding it entirely into fakesinks. Accepts input path. Decoders tolerate corrupt frames so the whole file is covered. Returns decode errors and warnings with positions, truncation against the declared duration, audio/video end mismatch and a verdict."
+    )]
+    async fn gst_verify_file(
+        &self,
+        Parameters(params): Parameters<VerifyFileParams>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_verify_file").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_verify_file' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
         }
 
-        let output = if filtered_plugins.is_empty() {
-            "No plugins found matching the criteria.".to_string()
+        let input = check_input_file(&params.input).map_err(Into::<McpError>::into)?;
+        let report = tokio::task::spawn_blocking(move || verify_file(&input))
+            .await
+            .map_err(|e| {
+                crate::GStreamerMcpError::Other(format!("Verification task failed: {}", e))
+            })
+            .and_then(|r| r)
+            .map_err(Into::<McpError>::into)?;
+
+        let mut output = if report.ok {
+            format!("{} decoded without problems\n", params.input)
         } else {
-            let mut output = format!("Found {} plugins:\n\n", filtered_plugins.len());
-            for plugin in filtered_plugins {
+            format!("{} has problems:\n", params.input)
+        };
+        for problem in &report.problems {
+            output.push_str(&format!("  - {}\n", problem));
+        }
+        if let Some(duration) = report.declared_duration_ns {
+            output.push_str(&format!(
+                "\nDeclared duration: {:.2} s\n",
+                duration as f64 / 1e9
+            ));
+        }
+        for stream in &report.streams {
+            output.push_str(&format!(
+                "Stream {} ({}): {} buffers, {:.2}-{:.2} s\n",
+                stream.index,
+                stream.media_type,
+                stream.buffers,
+                stream.start_ns.unwrap_or(0) as f64 / 1e9,
+                stream.end_ns.unwrap_or(0) as f64 / 1e9
+            ));
+        }
+        if !report.issues.is_empty() {
+            output.push_str("\nIssues:\n");
+            for issue in &report.issues {
+                let position = issue
+                    .position_ns
+                    .map(|p| format!("{:.2} s", p as f64 / 1e9))
+                    .unwrap_or_else(|| "?".to_string());
                 output.push_str(&format!(
-                    "- {} (v{}) - {}\n  License: {}, Source: {}\n",
-                    plugin.name, plugin.version, plugin.description, plugin.license, plugin.source
+                    "  [{}] {} {}: {}\n",
+                    position,
+                    if issue.fatal { "error" } else { "warning" },
+                    issue.source,
+                    issue.message
                 ));
-                if !plugin.elements.is_empty() {
-                    output.push_str(&format!("  Elements: {}\n", plugin.elements.join(", ")));
-                }
-                output.push('\n');
             }
-            output
-        };
+        }
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+        let json = serde_json::to_value(&report).unwrap_or_default();
+        Ok(success_with_json(output, json))
     }
 
     #[tool(
-        description = "Searches for GStreamer elements by keyword. Accepts search query (required). Returns relevance-ranked results matching element names, descriptions, and classifications."
+        description = "Samples the video of a file or URI and reports whether it is progressive, interlaced, mixed or telecined. Accepts input path or URI and number of frames to sample (default: 100). Returns the scan type, field order, buffer flag counts and the deinterlace or ivtc element to insert after the decoder. gst_change_speed, gst_concat_media and gst_create_abr_ladder deinterlace interlaced inputs automatically."
     )]
-    async fn gst_search_elements(
+    async fn gst_detect_interlacing(
         &self,
-        Parameters(params): Parameters<SearchElementsParams>,
+        Parameters(params): Parameters<DetectInterlacingParams>,
     ) -> Result<CallToolResult, McpError> {
-        let config = self.config.read().await;
-        let max_results = config.max_search_results;
+        if !self.is_tool_enabled("gst_detect_interlacing").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_detect_interlacing' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
 
-        let results =
-            search_elements(&params.query, max_results).map_err(Into::<McpError>::into)?;
+        let frames = params.frames.unwrap_or(DEFAULT_SAMPLE_FRAMES);
+        if frames == 0 {
+            return Err(McpError::invalid_params(
+                "frames must be at least 1".to_string(),
+                None,
+            ));
+        }
+        let input = params.input.clone();
+        let report = tokio::task::spawn_blocking(move || {
+            let uri = if input.contains("://") {
+                input
+            } else {
+                let path = check_input_file(&input)?.canonicalize()?;
+                gst::glib::filename_to_uri(&path, None)
+                    .map_err(|e| {
+                        crate::GStreamerMcpError::Other(format!("Invalid path {}: {}", input, e))
+                    })?
+                    .to_string()
+            };
+            detect_interlacing(&uri, frames)
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Sampling task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
 
-        let output = if results.is_empty() {
-            format!("No elements found matching '{}'", params.query)
-        } else {
-            let mut output = format!(
-                "Found {} elements matching '{}':\n\n",
-                results.len(),
-                params.query
-            );
-            for element in results {
-                output.push_str(&format!(
-                    "- {} ({})\n  Plugin: {}, Classification: {}\n",
-                    element.name, element.description, element.plugin_name, element.classification
-                ));
+        let counts = &report.counts;
+        let mut output = format!(
+            "{} is {} (interlace-mode {})\n  Frames sampled: {}, interlaced: {}, top field first: {}, repeat first field: {}, single field: {}\n",
+            params.input,
+            report.scan_type,
+            report.interlace_mode,
+            counts.frames,
+            counts.interlaced,
+            counts.top_field_first,
+            counts.repeat_first_field,
+            counts.single_field
+        );
+        if let Some(order) = &report.field_order {
+            output.push_str(&format!("  Field order: {}\n", order));
+        }
+        if let (Some(recommendation), Some(element)) =
+            (&report.recommendation, &report.suggested_element)
+        {
+            output.push_str(&format!(
+                "\n{}: insert '{}' after the decoder\n",
+                recommendation, element
+            ));
+        }
+
+        let json = serde_json::to_value(&report).unwrap_or_default();
+        Ok(success_with_json(output, json))
+    }
+
+    #[tool(
+        description = "Writes a copy of a file played at a different speed, as a batch job. Accepts input path, output path, rate (0.25-4.0), preserve_pitch (default: true), tonemap flag (default: HDR inputs), downmix flag (default: false), priority, job ID and upload target (optional). Video frames are retimed to keep the frame rate; audio tempo is changed with or without pitch change. Returns the job ID and expected output duration; track progress with gst_list_jobs."
+    )]
+    async fn gst_change_speed(
+        &self,
+        Parameters(params): Parameters<ChangeSpeedParams>,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.is_tool_enabled("gst_change_speed").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_change_speed' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
+
+        let input = check_input_file(&params.input).map_err(Into::<McpError>::into)?;
+        let output = std::path::PathBuf::from(&params.output);
+        if input == output {
+            return Err(McpError::invalid_params(
+                "The output must differ from the input".to_string(),
+                None,
+            ));
+        }
+        let rate = params.rate;
+        let preserve_pitch = params.preserve_pitch.unwrap_or(true);
+        let tonemap = params.tonemap;
+        let downmix = params.downmix.unwrap_or(false);
+
+        let plan_output = output.clone();
+        let (info, conversions, description) = tokio::task::spawn_blocking(move || {
+            let info = probe_media(&input)?;
+            let conversions = Conversions {
+                tonemap: Tonemap::resolve(tonemap, [info.video.first()]),
+                downmix,
+            };
+            let description =
+                plan_speed_change(&info, &plan_output, rate, preserve_pitch, &conversions)?;
+            Ok((info, conversions, description))
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Probe task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        self.confirm(&peer, &format!("write {}", output.display()))
+            .await?;
+
+        let job = self
+            .jobs
+            .submit(&description, params.priority, params.job_id, params.upload)
+            .map_err(Into::<McpError>::into)?;
+
+        let expected_ns = info
+            .duration_ns
+            .map(|duration| (duration as f64 / rate) as u64);
+        let mut text = format!(
+            "Job '{}' queued to write {} at {}x speed{}",
+            job.id,
+            output.display(),
+            rate,
+            if info.audio.is_empty() {
+                ""
+            } else if preserve_pitch {
+                " (pitch preserved)"
+            } else {
+                " (pitch follows speed)"
             }
-            output
-        };
+        );
+        if let (Some(before), Some(after)) = (info.duration_ns, expected_ns) {
+            text.push_str(&format!(
+                "\nDuration: {} → {}",
+                gst::ClockTime::from_nseconds(before),
+                gst::ClockTime::from_nseconds(after)
+            ));
+        }
+        let tonemapped = !info.video.is_empty() && conversions.tonemap.is_some();
+        if tonemapped {
+            text.push_str("\nVideo is tone-mapped to SDR");
+        }
+        let downmixed = info.audio.first().filter(|a| downmix && a.channels > 2);
+        if let Some(audio) = downmixed {
+            text.push_str(&format!(
+                "\nAudio is downmixed from {} to stereo",
+                audio.layout
+            ));
+        }
+        let downmixed = downmixed.is_some();
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+        let json = serde_json::json!({
+            "job": job,
+            "rate": rate,
+            "preserve_pitch": preserve_pitch,
+            "input_duration_ns": info.duration_ns,
+            "expected_duration_ns": expected_ns,
+            "tonemapped": tonemapped,
+            "downmixed": downmixed,
+            "description": description,
+        });
+        Ok(success_with_json(text, json))
     }
 
     #[tool(
-        description = "Creates and launches a GStreamer pipeline from description. Accepts gst-launch syntax, auto-play flag (default: true), and custom ID (optional). Returns pipeline ID and current state."
+        description = "Encodes an input into a multi-bitrate HLS or DASH ladder. Accepts input path or URI, output directory, format (hls/dash), renditions (width, height, video kbit/s), audio bitrate, tonemap and downmix flags, segment duration and pipeline ID (optional). Decodes once, tees into one H.264 encoder per rendition, and writes the HLS master playlist. Returns the pipeline ID, manifest path and renditions; stop it with gst_stop_pipeline for live inputs."
     )]
-    async fn gst_launch_pipeline(
+    async fn gst_create_abr_ladder(
         &self,
-        Parameters(params): Parameters<LaunchPipelineParams>,
+        Parameters(params): Parameters<CreateAbrLadderParams>,
+        peer: Peer<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        // Check if tool is enabled
-        if !self.is_tool_enabled("gst_launch_pipeline").await {
+        if !self.is_tool_enabled("gst_create_abr_ladder").await {
             return Err(McpError::new(
                 ErrorCode::METHOD_NOT_FOUND,
-                format!("Tool 'gst_launch_pipeline' is not available in the current mode"),
+                "Tool 'gst_create_abr_ladder' is not available in the current mode".to_string(),
                 None::<serde_json::Value>,
             ));
         }
 
-        // Create the pipeline
+        let format = params.format.unwrap_or_default();
+        let mut ladder = AbrLadder {
+            format,
+            renditions: params.renditions.clone().unwrap_or_else(default_ladder),
+            audio_kbps: None,
+            segment_seconds: params.segment_duration.unwrap_or(DEFAULT_SEGMENT_SECONDS),
+            framerate: None,
+        };
+        let output_dir = std::path::PathBuf::from(&params.output_dir);
+        let input = params.input.clone();
+        let with_audio = params.audio.unwrap_or(true);
+        let audio_kbps = params.audio_kbps.unwrap_or(DEFAULT_AUDIO_KBPS);
+        let tonemap = params.tonemap;
+        let downmix = params.downmix.unwrap_or(false);
+
+        // Files are probed so the ladder fits the source; URIs are taken as is
+        let plan_dir = output_dir.clone();
+        let (ladder, description) = tokio::task::spawn_blocking(move || {
+            let (uri, info) = if input.contains("://") {
+                (input, None)
+            } else {
+                let path = check_input_file(&input)?.canonicalize()?;
+                let uri = gst::glib::filename_to_uri(&path, None).map_err(|e| {
+                    crate::GStreamerMcpError::Other(format!("Invalid path {}: {}", input, e))
+                })?;
+                (uri.to_string(), Some(probe_media(&path)?))
+            };
+            let video = info.as_ref().and_then(|i| i.video.first());
+            if info.is_some() && video.is_none() {
+                return Err(crate::GStreamerMcpError::Other(format!(
+                    "{} has no video stream",
+                    uri
+                )));
+            }
+            ladder.framerate = video.and_then(|v| {
+                let (num, den) = v.framerate.split_once('/')?;
+                let (num, den) = (num.parse::<f64>().ok()?, den.parse::<f64>().ok()?);
+                (num > 0.0 && den > 0.0).then(|| num / den)
+            });
+            ladder.validate(video.map(|v| v.height))?;
+            let with_audio = info.as_ref().map_or(with_audio, |i| !i.audio.is_empty());
+            ladder.audio_kbps = with_audio.then_some(audio_kbps);
+
+            let (video_encoder, audio_encoder) = ladder_encoders(with_audio)?;
+            // Unprobed streams are deinterlaced in case they are; it passes
+            // progressive frames through
+            let conversions = Conversions {
+                tonemap: Tonemap::resolve(tonemap, [video]),
+                downmix,
+            };
+            let audio = info.as_ref().and_then(|i| i.audio.first());
+            let description = ladder_description(
+                &uri,
+                &plan_dir,
+                &ladder,
+                video_encoder,
+                audio_encoder,
+                &conversions.video_steps(video),
+                &conversions.audio_steps(audio),
+            );
+            Ok((ladder, description))
+        })
+        .await
+        .map_err(|e| crate::GStreamerMcpError::Other(format!("Probe task failed: {}", e)))
+        .and_then(|r| r)
+        .map_err(Into::<McpError>::into)?;
+
+        self.confirm(&peer, &format!("write {}", output_dir.display()))
+            .await?;
+
+        let manifest = match format {
+            AbrFormat::Hls => output_dir.join(MASTER_PLAYLIST),
+            AbrFormat::Dash => output_dir.join(DASH_MANIFEST),
+        };
+        let prepared: std::io::Result<()> = (|| {
+            std::fs::create_dir_all(&output_dir)?;
+            if format == AbrFormat::Hls {
+                for rendition in &ladder.renditions {
+                    std::fs::create_dir_all(output_dir.join(rendition.name()))?;
+                }
+                std::fs::write(&manifest, master_playlist(&ladder))?;
+            }
+            Ok(())
+        })();
+        prepared
+            .map_err(|e| {
+                crate::GStreamerMcpError::Other(format!(
+                    "Failed to prepare {}: {}",
+                    output_dir.display(),
+                    e
+                ))
+            })
+            .map_err(Into::<McpError>::into)?;
+
         let pipeline_id = self
             .pipeline_manager
-            .create_pipeline(&params.pipeline_description, params.pipeline_id)
+            .create_pipeline(&description, params.pipeline_id)
             .map_err(Into::<McpError>::into)?;
+        self.publish_shared_state();
+        if let Err(e) = self
+            .pipeline_manager
+            .set_pipeline_state(&pipeline_id, gst::State::Playing)
+        {
+            let _ = self.pipeline_manager.remove_pipeline(&pipeline_id);
+            self.publish_shared_state();
+            return Err(e.into());
+        }
 
-        // Auto-play if requested (default is true)
-        let auto_play = params.auto_play.unwrap_or(true);
-        if auto_play {
-            let state = self
-                .pipeline_manager
-                .set_pipeline_state(&pipeline_id, gst::State::Playing)
-                .map_err(Into::<McpError>::into)?;
-
-            let output = format!(
-                "Pipeline '{}' launched successfully.\nState: {:?}\nDescription: {}",
-                pipeline_id, state, params.pipeline_description
-            );
-            Ok(CallToolResult::success(vec![Content::text(output)]))
-        } else {
-            let output = format!(
-                "Pipeline '{}' created successfully in NULL state.\nDescription: {}",
-                pipeline_id, params.pipeline_description
-            );
-            Ok(CallToolResult::success(vec![Content::text(output)]))
+        let mut text = format!(
+            "Pipeline '{}' encoding {} {} renditions into {}\nManifest: {}",
+            pipeline_id,
+            ladder.renditions.len(),
+            match format {
+                AbrFormat::Hls => "HLS",
+                AbrFormat::Dash => "DASH",
+            },
+            output_dir.display(),
+            manifest.display()
+        );
+        for rendition in &ladder.renditions {
+            text.push_str(&format!(
+                "\n  - {}: {}x{} @ {} kbit/s",
+                rendition.name(),
+                rendition.width,
+                rendition.height,
+                rendition.video_kbps
+            ));
         }
+        if let Some(kbps) = ladder.audio_kbps {
+            text.push_str(&format!("\nAudio: AAC @ {} kbit/s", kbps));
+        }
+
+        let json = serde_json::json!({
+            "pipeline_id": pipeline_id,
+            "format": format,
+            "manifest": manifest,
+            "renditions": ladder.renditions,
+            "audio_kbps": ladder.audio_kbps,
+            "segment_duration": ladder.segment_seconds,
+            "description": description,
+        });
+        Ok(success_with_json(text, json))
     }
 
     #[tool(
-        description = "Changes the state of an active pipeline. Accepts pipeline ID and target state (null/ready/paused/playing). Returns new state and transition success status."
+        description = "Lists files produced by managed pipelines and jobs (recordings, thumbnails, transcodes). Accepts pipeline ID filter (optional). Returns paths, sizes, durations, SHA-256 hashes, and creation times, newest first."
     )]
-    async fn gst_set_pipeline_state(
+    async fn gst_list_artifacts(
         &self,
-        Parameters(params): Parameters<SetPipelineStateParams>,
+        Parameters(params): Parameters<ListArtifactsParams>,
     ) -> Result<CallToolResult, McpError> {
-        // Check if tool is enabled
-        if !self.is_tool_enabled("gst_set_pipeline_state").await {
+        if !self.is_tool_enabled("gst_list_artifacts").await {
             return Err(McpError::new(
                 ErrorCode::METHOD_NOT_FOUND,
-                format!("Tool 'gst_set_pipeline_state' is not available in the current mode"),
+                "Tool 'gst_list_artifacts' is not available in the current mode".to_string(),
                 None::<serde_json::Value>,
             ));
         }
 
-        let state = match params.state.to_lowercase().as_str() {
-            "null" => gst::State::Null,
-            "ready" => gst::State::Ready,
-            "paused" => gst::State::Paused,
-            "playing" => gst::State::Playing,
-            _ => {
-                return Err(McpError {
-                    code: rmcp::model::ErrorCode(-32003),
-                    message: format!(
-                        "Invalid state '{}'. Must be one of: null, ready, paused, playing",
-                        params.state
-                    )
-                    .into(),
-                    data: None,
-                })
-            }
-        };
+        let artifacts = self.artifacts.list(params.pipeline_id.as_deref());
+        let total_bytes: u64 = artifacts.iter().map(|a| a.size_bytes).sum();
 
-        let current_state = self
-            .pipeline_manager
-            .set_pipeline_state(&params.pipeline_id, state)
-            .map_err(Into::<McpError>::into)?;
+        let mut output = format!("Artifacts: {} ({} bytes)\n", artifacts.len(), total_bytes);
+        for artifact in &artifacts {
+            output.push_str(&format!("- {}\n", format_artifact(artifact)));
+        }
 
-        let output = format!(
-            "Pipeline '{}' state changed to {:?}",
-            params.pipeline_id, current_state
-        );
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+        let json = serde_json::json!({
+            "count": artifacts.len(),
+            "total_bytes": total_bytes,
+            "artifacts": artifacts,
+        });
+        Ok(success_with_json(output, json))
     }
 
     #[tool(
-        description = "Retrieves current status of a pipeline. Accepts pipeline ID and include_messages flag (optional). Returns state, position, duration, and recent bus messages."
+        description = "Removes artifacts selected by a retention policy. Accepts max_age_hours, max_total_bytes, keep_latest, pipeline ID filter, delete_files and dry_run (all optional). An artifact is pruned when any limit selects it. Returns the pruned artifacts and reclaimed bytes."
     )]
-    async fn gst_get_pipeline_status(
+    async fn gst_prune_artifacts(
         &self,
-        Parameters(params): Parameters<GetPipelineStatusParams>,
+        Parameters(params): Parameters<PruneArtifactsParams>,
+        peer: Peer<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        let status = self
-            .pipeline_manager
-            .get_pipeline_status(&params.pipeline_id)
-            .map_err(Into::<McpError>::into)?;
+        if !self.is_tool_enabled("gst_prune_artifacts").await {
+            return Err(McpError::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                "Tool 'gst_prune_artifacts' is not available in the current mode".to_string(),
+                None::<serde_json::Value>,
+            ));
+        }
 
-        let mut output = format!(
-            "Pipeline: {}\nDescription: {}\nState: {}\n",
-            status.id, status.description, status.state
-        );
+        let policy = RetentionPolicy {
+            max_age: params
+                .max_age_hours
+                .map(|hours| Duration::from_secs(hours * 3600)),
+            max_total_bytes: params.max_total_bytes,
+            keep_latest: params.keep_latest,
+            pipeline_id: params.pipeline_id,
+        };
+        let delete_files = params.delete_files.unwrap_or(false);
+        let dry_run = params.dry_run.unwrap_or(false);
 
-        if let Some(pending) = status.pending_state {
-            output.push_str(&format!("Pending State: {}\n", pending));
+        if delete_files && !dry_run {
+            self.confirm(
+                &peer,
+                "delete artifact files selected by the retention policy",
+            )
+            .await?;
         }
 
-        if status.position >= 0 {
-            output.push_str(&format!("Position: {} ns\n", status.position));
-        }
-        if status.duration >= 0 {
-            output.push_str(&format!("Duration: {} ns\n", status.duration));
+        let pruned = self.artifacts.prune(&policy, delete_files, dry_run);
+        let reclaimed: u64 = pruned.iter().map(|a| a.size_bytes).sum();
+
+        let mut output = match (dry_run, delete_files) {
+            (true, _) => format!(
+                "Would prune {} artifacts ({} bytes)\n",
+                pruned.len(),
+                reclaimed
+            ),
+            (false, true) => format!("Deleted {} artifacts ({} bytes)\n", pruned.len(), reclaimed),
+            (false, false) => format!(
+                "Removed {} artifacts from the registry ({} bytes, files kept)\n",
+                pruned.len(),
+                reclaimed
+            ),
+        };
+        for artifact in &pruned {
+            output.push_str(&format!("- {}\n", format_artifact(artifact)));
         }
 
+        let json = serde_json::json!({
+            "dry_run": dry_run,
+            "files_deleted": delete_files && !dry_run,
+            "count": pruned.len(),
+            "reclaimed_bytes": reclaimed,
+            "artifacts": pruned,
+        });
+        Ok(success_with_json(output, json))
+    }
+}
+
+/// Build a successful result carrying both a human readable text block and
+/// the same data as structured JSON for programmatic clients
+fn success_with_json(text: String, json: serde_json::Value) -> CallToolResult {
+    CallToolResult {
+        content: vec![Content::text(text)],
+        structured_content: Some(json),
+        is_error: Some(false),
+    }
+}
+
+fn format_corrections(corrections: &[ElementCorrection]) -> String {
+    let mut output = String::new();
+    for correction in corrections {
         output.push_str(&format!(
-            "Errors: {}, Warnings: {}\n",
-            status.error_count, status.warning_count
+            "\nAuto-corrected: {} → {}",
+            correction.from, correction.to
         ));
+    }
+    output
+}
+
+fn format_queued(queued: &[MissingQueue]) -> String {
+    let mut output = String::new();
+    for branch in queued {
         output.push_str(&format!(
-            "Created: {}\nLast State Change: {}\n",
-            status.created_at, status.last_state_change
+            "\nQueue inserted before '{}' at position {} (branch of '{}')",
+            branch.first, branch.position, branch.element
         ));
+    }
+    output
+}
 
-        // Include messages if requested
-        if params.include_messages.unwrap_or(false) {
-            let messages = self
-                .pipeline_manager
-                .get_bus_messages(&params.pipeline_id, 10);
-            if !messages.is_empty() {
-                output.push_str("\nRecent Messages:\n");
-                for msg in messages {
-                    output.push_str(&format!(
-                        "  [{}] {}: {}\n",
-                        msg.timestamp, msg.message_type, msg.message
-                    ));
-                }
-            }
-        }
+fn format_event_kinds(events: &[BusEventKind]) -> String {
+    events
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+fn format_event_subscriptions(subscriptions: &[EventSubscription]) -> String {
+    if subscriptions.is_empty() {
+        return "No active subscriptions\n".to_string();
+    }
+    let mut output = String::from("\nActive subscriptions:\n");
+    for subscription in subscriptions {
+        output.push_str(&format!(
+            "- {}: {} ({})\n",
+            subscription.id,
+            subscription
+                .pipeline_id
+                .as_deref()
+                .unwrap_or("all pipelines"),
+            format_event_kinds(&subscription.events)
+        ));
     }
+    output
+}
 
-    #[tool(
-        description = "Stops and releases resources for a pipeline. Accepts pipeline ID and force flag (optional). Returns cleanup status."
-    )]
-    async fn gst_stop_pipeline(
-        &self,
-        Parameters(params): Parameters<StopPipelineParams>,
-    ) -> Result<CallToolResult, McpError> {
-        // First set to NULL state
-        let _ = self
-            .pipeline_manager
-            .set_pipeline_state(&params.pipeline_id, gst::State::Null);
+fn format_job(job: &Job, time: &TimeDisplay) -> String {
+    let mut output = format!(
+        "- {} [{}] priority {}: {}\n",
+        job.id, job.status, job.priority, job.description
+    );
+    output.push_str(&format!("  Submitted: {}", time.format(job.submitted_at)));
+    if let Some(started_at) = job.started_at {
+        output.push_str(&format!(", started: {}", time.format(started_at)));
+    }
+    if let Some(finished_at) = job.finished_at {
+        output.push_str(&format!(", finished: {}", time.format(finished_at)));
+    }
+    output.push('\n');
+    if let (JobStatus::Running, Some(progress)) = (job.status, job.progress_percent) {
+        output.push_str(&format!("  Progress: {}%\n", progress));
+    }
+    if let Some(error) = &job.error {
+        output.push_str(&format!("  Error: {}\n", error));
+    }
+    if let Some(upload) = &job.upload_progress {
+        output.push_str(&format!(
+            "  Upload to {}: {:?}, {}/{} bytes\n",
+            upload.target, upload.state, upload.bytes_uploaded, upload.bytes_total
+        ));
+        for url in &upload.urls {
+            output.push_str(&format!("  Uploaded: {}\n", url));
+        }
+    }
+    output
+}
 
-        // Then remove the pipeline
-        self.pipeline_manager
-            .remove_pipeline(&params.pipeline_id)
-            .map_err(Into::<McpError>::into)?;
+fn format_artifact(artifact: &Artifact) -> String {
+    let paths: Vec<String> = artifact
+        .paths
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect();
+    let mut output = format!(
+        "{} ({} bytes, sha256 {}",
+        paths.join(", "),
+        artifact.size_bytes,
+        &artifact.id[..12.min(artifact.id.len())]
+    );
+    if let Some(duration) = artifact.duration_ns {
+        output.push_str(&format!(", {}", gst::ClockTime::from_nseconds(duration)));
+    }
+    output.push_str(&format!(", from {})", artifact.pipeline_id));
+    output
+}
 
-        let output = format!(
-            "Pipeline '{}' stopped and removed successfully",
-            params.pipeline_id
-        );
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+fn format_loudness(measurement: &LoudnessMeasurement) -> String {
+    let mut output = format!("{:.1} LUFS", measurement.integrated_lufs);
+    if let Some(range) = measurement.loudness_range_lu {
+        output.push_str(&format!(", range {:.1} LU", range));
     }
+    if let Some(peak) = measurement.true_peak_dbtp {
+        output.push_str(&format!(", true peak {:.1} dBTP", peak));
+    }
+    output
+}
 
-    #[tool(
-        description = "Lists all currently active pipelines. Accepts include_details flag (optional). Returns pipeline IDs, descriptions, states, and creation times."
-    )]
-    async fn gst_list_pipelines(
-        &self,
-        Parameters(params): Parameters<ListGstPipelinesParams>,
-    ) -> Result<CallToolResult, McpError> {
-        let pipelines = self.pipeline_manager.list_pipelines();
+fn format_tool_stats(stats: &ToolStats) -> String {
+    format!(
+        "{}: {} calls ({} ok, {} failed, {} slow), avg {} ms, max {} ms, last {} ms\n",
+        stats.tool,
+        stats.calls,
+        stats.successes,
+        stats.failures,
+        stats.slow_calls,
+        stats.average_ms,
+        stats.max_ms,
+        stats.last_ms
+    )
+}
+
+fn format_labels(labels: &std::collections::BTreeMap<String, String>) -> String {
+    labels
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Parse an optional RFC 3339 time parameter
+fn parse_time_param(
+    name: &str,
+    text: Option<&str>,
+) -> Result<Option<chrono::DateTime<chrono::Utc>>, McpError> {
+    text.map(|text| {
+        chrono::DateTime::parse_from_rfc3339(text)
+            .map(|time| time.with_timezone(&chrono::Utc))
+            .map_err(|e| {
+                McpError::invalid_params(
+                    format!("{} '{}' is not an RFC 3339 time: {}", name, text, e),
+                    None,
+                )
+            })
+    })
+    .transpose()
+}
+
+fn format_message_filter(subscriber: &str, filter: &MessageFilter) -> String {
+    let types = filter
+        .types
+        .as_ref()
+        .map(|t| t.join(", "))
+        .unwrap_or_else(|| "all".to_string());
+    let severity = filter
+        .min_severity
+        .map(|s| format!("{:?}", s))
+        .unwrap_or_else(|| "any".to_string());
+    let source = filter.source.as_deref().unwrap_or("*");
+    format!(
+        "- {}: types: {}, min severity: {}, source: {}\n",
+        subscriber, types, severity, source
+    )
+}
 
-        if pipelines.is_empty() {
-            return Ok(CallToolResult::success(vec![Content::text(
-                "No active pipelines".to_string(),
-            )]));
+fn format_adaptive_stats(stats: &AdaptiveStats) -> String {
+    let mut output = format!("\n{} ({})\n", stats.element, stats.factory);
+    match stats.bandwidth_estimate {
+        Some(bps) => output.push_str(&format!("  Bandwidth estimate: {} bps\n", bps)),
+        None => output.push_str("  Bandwidth estimate: unknown\n"),
+    }
+    if let Some(level) = stats.buffer_level_ns {
+        output.push_str(&format!("  Buffer level: {} ns\n", level));
+    }
+    if stats.current_variant.is_empty() {
+        output.push_str("  Current variant: not negotiated yet\n");
+    } else {
+        for caps in &stats.current_variant {
+            output.push_str(&format!("  Current variant: {}\n", caps));
+        }
+    }
+    if !stats.properties.is_empty() {
+        output.push_str("  Properties:\n");
+        for (name, value) in &stats.properties {
+            output.push_str(&format!("    {} = {}\n", name, value));
         }
+    }
+    output
+}
 
-        let mut output = format!("Active pipelines: {}\n\n", pipelines.len());
+impl ServerHandler for GStreamerHandler {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        let tool = request.name.to_string();
 
-        for pipeline in pipelines {
-            if params.include_details.unwrap_or(false) {
-                output.push_str(&format!(
-                    "ID: {}\n  Description: {}\n  State: {}\n  Created: {}\n  Errors: {}, Warnings: {}\n\n",
-                    pipeline.id, pipeline.description, pipeline.state, 
-                    pipeline.created_at, pipeline.error_count, pipeline.warning_count
+        let role = self
+            .policy
+            .resolve(context.extensions.get::<CallerIdentity>());
+        if let Some(metadata) = self.tool_registry.get_tool(&tool) {
+            if !self.policy.allows(role, &metadata.category) {
+                return Err(McpError::new(
+                    ErrorCode::INVALID_REQUEST,
+                    format!("Role '{}' is not permitted to call tool '{}'", role, tool),
+                    None::<serde_json::Value>,
+                ));
+            }
+            let missing = metadata.missing_elements();
+            if !missing.is_empty() {
+                return Err(McpError::new(
+                    ErrorCode::METHOD_NOT_FOUND,
+                    format!(
+                        "Tool '{}' needs elements that are not installed: {}",
+                        tool,
+                        missing.join(", ")
+                    ),
+                    None::<serde_json::Value>,
                 ));
-            } else {
-                output.push_str(&format!("- {} ({})\n", pipeline.id, pipeline.state));
             }
         }
 
-        Ok(CallToolResult::success(vec![Content::text(output)]))
+        let started = std::time::Instant::now();
+
+        let tcc = ToolCallContext::new(self, request, context);
+        let result = self.tool_router.call(tcc).await;
+
+        let success = matches!(&result, Ok(r) if r.is_error != Some(true));
+        self.metrics.record(&tool, started.elapsed(), success).await;
+        result
     }
 
-    #[tool(
-        description = "Validates pipeline description syntax without launching. Accepts gst-launch syntax description. Returns validation status and list of elements that would be created."
-    )]
-    async fn gst_validate_pipeline(
+    async fn list_tools(
         &self,
-        Parameters(params): Parameters<ValidatePipelineParams>,
-    ) -> Result<CallToolResult, McpError> {
-        match validate_pipeline_description(&params.pipeline_description) {
-            Ok(elements) => {
-                let mut output = format!(
-                    "Pipeline description is valid!\n\nElements that would be created ({}):\n",
-                    elements.len()
-                );
-                for element in elements {
-                    output.push_str(&format!("- {}\n", element));
-                }
-                Ok(CallToolResult::success(vec![Content::text(output)]))
-            }
-            Err(e) => {
-                let output = format!("Pipeline validation failed:\n{}", e);
-                Ok(CallToolResult::success(vec![Content::text(output)]))
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        let role = self
+            .policy
+            .resolve(context.extensions.get::<CallerIdentity>());
+        let tools = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .filter(|tool| {
+                self.tool_registry
+                    .get_tool(&tool.name)
+                    .is_none_or(|metadata| {
+                        self.policy.allows(role, &metadata.category)
+                            && metadata.missing_elements().is_empty()
+                    })
+            })
+            .collect();
+        Ok(ListToolsResult::with_all_items(tools))
+    }
+
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        let mut resources = vec![RawResource {
+            uri: LIMITS_URI.to_string(),
+            name: "server-limits".to_string(),
+            description: Some(
+                "Pipeline slots in use, job queue depth and discovery cache status. Subscribe to be told when they change"
+                    .to_string(),
+            ),
+            mime_type: Some("application/json".to_string()),
+            size: None,
+        }
+        .no_annotation()];
+        if self.check_artifact_access(&context).is_err() {
+            return Ok(ListResourcesResult::with_all_items(resources));
+        }
+
+        let artifacts = self.artifacts.list(None).into_iter().map(|artifact| {
+            let name = artifact
+                .paths
+                .first()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| artifact.id.clone());
+            RawResource {
+                uri: artifact.uri(),
+                name,
+                description: Some(format!(
+                    "Produced by '{}' at {}",
+                    artifact.pipeline_id, artifact.created_at
+                )),
+                mime_type: Some(artifact.mime_type().to_string()),
+                size: u32::try_from(artifact.size_bytes).ok(),
             }
+            .no_annotation()
+        });
+        resources.extend(artifacts);
+        Ok(ListResourcesResult::with_all_items(resources))
+    }
+
+    async fn list_resource_templates(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ListResourceTemplatesResult, McpError> {
+        self.check_artifact_access(&context)?;
+
+        let template = RawResourceTemplate {
+            uri_template: ARTIFACT_URI_TEMPLATE.to_string(),
+            name: "artifact".to_string(),
+            description: Some(
+                "File produced by a pipeline or job, by SHA-256. Use offset and length to read large files in parts"
+                    .to_string(),
+            ),
+            mime_type: None,
         }
+        .no_annotation();
+        Ok(ListResourceTemplatesResult::with_all_items(vec![template]))
+    }
+
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        if request.uri == LIMITS_URI {
+            let cache_enabled = self.config.read().await.cache_enabled;
+            let limits = ServerLimits::sample(
+                &self.pipeline_manager,
+                &self.jobs,
+                &self.cache,
+                cache_enabled,
+            )
+            .await;
+            return Ok(ReadResourceResult {
+                contents: vec![ResourceContents::TextResourceContents {
+                    uri: request.uri,
+                    mime_type: Some("application/json".to_string()),
+                    text: serde_json::to_string_pretty(&limits).unwrap_or_default(),
+                }],
+            });
+        }
+        self.check_artifact_access(&context)?;
+
+        let (id, range) = parse_artifact_uri(&request.uri).map_err(|e| {
+            McpError::new(
+                ErrorCode::INVALID_PARAMS,
+                e.to_string(),
+                None::<serde_json::Value>,
+            )
+        })?;
+        let artifact = self.artifacts.get(&id).ok_or_else(|| {
+            McpError::resource_not_found(
+                format!("Artifact '{}' not found", id),
+                None::<serde_json::Value>,
+            )
+        })?;
+
+        let max_bytes = self.config.read().await.max_resource_bytes;
+        let mime_type = artifact.mime_type().to_string();
+        let data = tokio::task::spawn_blocking(move || artifact.read(range, max_bytes))
+            .await
+            .map_err(|e| crate::GStreamerMcpError::Other(format!("Read task failed: {}", e)))
+            .and_then(|r| r)
+            .map_err(Into::<McpError>::into)?;
+
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents::BlobResourceContents {
+                uri: request.uri,
+                mime_type: Some(mime_type),
+                blob: base64::engine::general_purpose::STANDARD.encode(data),
+            }],
+        })
     }
-}
 
-#[tool_handler]
-impl ServerHandler for GStreamerHandler {
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::V_2024_11_05,
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .enable_resources_subscribe()
+                .enable_logging()
+                .build(),
             server_info: Implementation {
                 name: "gstreamer-mcp".to_string(),
                 version: env!("CARGO_PKG_VERSION").to_string(),
@@ -612,4 +7505,145 @@ impl ServerHandler for GStreamerHandler {
             ),
         }
     }
+
+    async fn subscribe(
+        &self,
+        request: SubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        if request.uri != LIMITS_URI {
+            return Err(McpError::resource_not_found(
+                format!(
+                    "Resource '{}' does not change; only {} can be subscribed to",
+                    request.uri, LIMITS_URI
+                ),
+                None::<serde_json::Value>,
+            ));
+        }
+        self.notifier.subscribe(request.uri).await;
+        Ok(())
+    }
+
+    async fn unsubscribe(
+        &self,
+        request: UnsubscribeRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.notifier.unsubscribe(&request.uri).await;
+        Ok(())
+    }
+
+    async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        tracing::info!("client initialized");
+        self.notifier.set_peer(context.peer).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Configuration;
+    use crate::oneshot::Session;
+    use serde_json::json;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_list_pipelines_json() {
+        let mut session = Session::connect(Configuration::default()).await.unwrap();
+        session
+            .call(
+                "gst_launch_pipeline",
+                json!({
+                    "pipeline_description": "videotestsrc ! fakesink",
+                    "pipeline_id": "listed",
+                    "auto_play": false,
+                    "labels": {"camera": "front"},
+                }),
+            )
+            .await
+            .unwrap();
+
+        let result = session
+            .call("gst_list_pipelines", json!({"labels": {"camera": "front"}}))
+            .await
+            .unwrap();
+        let json = result.structured_content.unwrap();
+        assert_eq!(json["count"], 1);
+        let pipeline = &json["pipelines"][0];
+        assert_eq!(pipeline["id"], "listed");
+        assert_eq!(pipeline["state"], "Null");
+        assert_eq!(pipeline["health"], "healthy");
+        assert_eq!(pipeline["labels"], json!({"camera": "front"}));
+        assert_eq!(pipeline["restart_count"], 0);
+
+        let result = session
+            .call("gst_list_pipelines", json!({"labels": {"camera": "back"}}))
+            .await
+            .unwrap();
+        assert_eq!(result.structured_content.unwrap()["count"], 0);
+        session.close().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_launch_set_state_stop_json() {
+        let mut session = Session::connect(Configuration::default()).await.unwrap();
+        let launched = session
+            .call(
+                "gst_launch_pipeline",
+                json!({
+                    "pipeline_description": "videotestsrc name=src ! fakesink name=sink",
+                    "pipeline_id": "structured",
+                }),
+            )
+            .await
+            .unwrap()
+            .structured_content
+            .unwrap();
+        assert_eq!(launched["pipeline_id"], "structured");
+        assert_eq!(launched["state"], "Playing");
+        assert_eq!(launched["warnings"], json!([]));
+        let elements: Vec<(String, String)> = launched["elements"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| {
+                (
+                    e["name"].as_str().unwrap().to_string(),
+                    e["factory"].as_str().unwrap().to_string(),
+                )
+            })
+            .collect();
+        assert!(elements.contains(&("src".to_string(), "videotestsrc".to_string())));
+        assert!(elements.contains(&("sink".to_string(), "fakesink".to_string())));
+
+        let paused = session
+            .call(
+                "gst_set_pipeline_state",
+                json!({"pipeline_id": "structured", "state": "paused"}),
+            )
+            .await
+            .unwrap()
+            .structured_content
+            .unwrap();
+        assert_eq!(paused["pipeline_id"], "structured");
+        assert_eq!(paused["requested_state"], "Paused");
+        assert_eq!(paused["state"], "Paused");
+
+        // A paused pipeline has nothing to drain
+        let stopped = session
+            .call("gst_stop_pipeline", json!({"pipeline_id": "structured"}))
+            .await
+            .unwrap()
+            .structured_content
+            .unwrap();
+        assert_eq!(stopped["pipeline_id"], "structured");
+        assert_eq!(stopped["path"], "immediate");
+        assert_eq!(stopped["teardown_completed"], true);
+        assert!(session
+            .call(
+                "gst_get_pipeline_status",
+                json!({"pipeline_id": "structured"})
+            )
+            .await
+            .is_err());
+        session.close().await;
+    }
 }
@@ -0,0 +1,214 @@
+use gstreamer as gst;
+use gstreamer_video::VideoRegionOfInterestMeta;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::quote_value;
+use crate::pipeline::attach_tee_branch;
+
+/// Detector elements attaching `GstVideoRegionOfInterestMeta` to the frames
+/// they analyse, and the property taking their model
+pub const DETECTORS: &[Detector] = &[
+    Detector {
+        factory: "gvadetect",
+        model_property: Some("model"),
+    },
+    Detector {
+        factory: "facedetect",
+        model_property: Some("profile"),
+    },
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct Detector {
+    pub factory: &'static str,
+    model_property: Option<&'static str>,
+}
+
+/// One object found in a frame
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Detection {
+    pub label: String,
+    pub confidence: Option<f64>,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Objects found in one frame by a detector branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionEvent {
+    pub pipeline_id: String,
+    pub branch: String,
+    /// Running time of the frame in the pipeline
+    pub pts_ns: Option<u64>,
+    pub objects: Vec<Detection>,
+}
+
+/// Which detections are reported
+#[derive(Debug, Clone, Default)]
+pub struct DetectionFilter {
+    /// Labels to report, case-insensitive; all when empty
+    pub labels: Vec<String>,
+    pub min_confidence: Option<f64>,
+}
+
+impl DetectionFilter {
+    pub fn accepts(&self, detection: &Detection) -> bool {
+        let label_ok = self.labels.is_empty()
+            || self
+                .labels
+                .iter()
+                .any(|l| l.eq_ignore_ascii_case(&detection.label));
+        // Detections without a score pass a confidence threshold
+        let confidence_ok = match (self.min_confidence, detection.confidence) {
+            (Some(min), Some(confidence)) => confidence >= min,
+            _ => true,
+        };
+        label_ok && confidence_ok
+    }
+}
+
+/// Detector chain for `factory` (or the first installed detector) loading
+/// `model`
+pub fn detector_chain(factory: Option<&str>, model: Option<&str>) -> McpResult<String> {
+    let (factory, model_property) = match factory {
+        Some(name) => (
+            name,
+            DETECTORS
+                .iter()
+                .find(|d| d.factory == name)
+                .and_then(|d| d.model_property),
+        ),
+        None => {
+            let detector = DETECTORS
+                .iter()
+                .find(|d| gst::ElementFactory::find(d.factory).is_some())
+                .ok_or_else(|| {
+                    GStreamerMcpError::ElementNotFound(format!(
+                        "No object detector installed (tried {}). Pass a detector chain that attaches region of interest metadata",
+                        DETECTORS
+                            .iter()
+                            .map(|d| d.factory)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                })?;
+            (detector.factory, detector.model_property)
+        }
+    };
+    match (model, model_property) {
+        (Some(model), Some(property)) => {
+            Ok(format!("{} {}={}", factory, property, quote_value(model)))
+        }
+        (Some(_), None) => Err(GStreamerMcpError::PropertyError(format!(
+            "Don't know how to pass a model to {}; set the model property in the detector chain",
+            factory
+        ))),
+        (None, _) => Ok(factory.to_string()),
+    }
+}
+
+/// Read the detections attached to a frame as region of interest metas
+pub fn frame_detections(buffer: &gst::BufferRef) -> Vec<Detection> {
+    buffer
+        .iter_meta::<VideoRegionOfInterestMeta>()
+        .map(|meta| {
+            let (x, y, width, height) = meta.rect();
+            let params: Vec<&gst::StructureRef> = meta.params().collect();
+            let confidence = params.iter().find_map(|p| p.get::<f64>("confidence").ok());
+            // Detectors put the class in the ROI type or in a label param
+            let label = params
+                .iter()
+                .find_map(|p| p.get::<String>("label").ok())
+                .unwrap_or_else(|| meta.roi_type().to_string());
+            Detection {
+                label,
+                confidence,
+                x,
+                y,
+                width,
+                height,
+            }
+        })
+        .collect()
+}
+
+/// Branch a detector off a tee of a running pipeline. Frames with at least
+/// one detection passing `filter` are passed to `on_detection` from a
+/// streaming thread. Returns the name of the added branch.
+pub fn attach_detector(
+    pipeline: &gst::Pipeline,
+    pipeline_id: &str,
+    tee_name: Option<&str>,
+    detector: &str,
+    filter: DetectionFilter,
+    on_detection: impl Fn(DetectionEvent) + Send + Sync + 'static,
+) -> McpResult<String> {
+    // Leaky queue: inference falls behind rather than stalling the pipeline
+    let description = format!(
+        "queue leaky=downstream max-size-buffers=2 ! videoconvert ! {} ! fakesink name=sink signal-handoffs=true sync=false async=false",
+        detector
+    );
+    let id = pipeline_id.to_string();
+    attach_tee_branch(
+        pipeline,
+        tee_name,
+        "detector",
+        &description,
+        move |branch, buffer| {
+            let objects: Vec<Detection> = frame_detections(buffer)
+                .into_iter()
+                .filter(|d| filter.accepts(d))
+                .collect();
+            if !objects.is_empty() {
+                on_detection(DetectionEvent {
+                    pipeline_id: id.clone(),
+                    branch: branch.to_string(),
+                    pts_ns: buffer.pts().map(|t| t.nseconds()),
+                    objects,
+                });
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detection(label: &str, confidence: Option<f64>) -> Detection {
+        Detection {
+            label: label.to_string(),
+            confidence,
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+        }
+    }
+
+    #[test]
+    fn test_detection_filter() {
+        let filter = DetectionFilter {
+            labels: vec!["Person".to_string()],
+            min_confidence: Some(0.5),
+        };
+        assert!(filter.accepts(&detection("person", Some(0.8))));
+        assert!(filter.accepts(&detection("person", None)));
+        assert!(!filter.accepts(&detection("person", Some(0.3))));
+        assert!(!filter.accepts(&detection("car", Some(0.9))));
+        assert!(DetectionFilter::default().accepts(&detection("car", None)));
+    }
+
+    #[test]
+    fn test_detector_chain() {
+        assert_eq!(
+            detector_chain(Some("gvadetect"), Some("/models/person.xml")).unwrap(),
+            "gvadetect model=\"/models/person.xml\""
+        );
+        assert!(detector_chain(Some("hailonet"), Some("yolo.hef")).is_err());
+        assert_eq!(detector_chain(Some("hailonet"), None).unwrap(), "hailonet");
+    }
+}
@@ -0,0 +1,270 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_video as gst_video;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::quote_value;
+use crate::suggestions::parse_launch;
+
+/// Frames sampled when no count is given
+pub const DEFAULT_SAMPLE_FRAMES: u32 = 100;
+
+/// Give up on sources that stop delivering frames, such as a stalled stream
+const SAMPLE_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Step deinterlacing the raw video of transcodes. In its default mode the
+/// element only touches frames flagged as interlaced.
+const DEINTERLACE_STEP: &str = "deinterlace ! ";
+
+/// The deinterlace step when `interlaced`, otherwise nothing
+pub fn deinterlace_step(interlaced: bool) -> &'static str {
+    if interlaced {
+        DEINTERLACE_STEP
+    } else {
+        ""
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanType {
+    Progressive,
+    Interlaced,
+    /// Progressive and interlaced frames alternate
+    Mixed,
+    /// Progressive frames with repeat-field flags, such as 24 fps film
+    /// carried as 30 fps video
+    Telecine,
+}
+
+impl std::fmt::Display for ScanType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ScanType::Progressive => "progressive",
+            ScanType::Interlaced => "interlaced",
+            ScanType::Mixed => "mixed",
+            ScanType::Telecine => "telecined",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Buffer flags counted over the sampled frames
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldCounts {
+    pub frames: u64,
+    pub interlaced: u64,
+    pub top_field_first: u64,
+    pub repeat_first_field: u64,
+    pub single_field: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InterlaceReport {
+    pub scan_type: ScanType,
+    /// `interlace-mode` of the decoded caps
+    pub interlace_mode: String,
+    /// `top-field-first` or `bottom-field-first` for interlaced content
+    pub field_order: Option<String>,
+    pub counts: FieldCounts,
+    pub recommendation: Option<String>,
+    /// Element to insert after the decoder
+    pub suggested_element: Option<String>,
+}
+
+/// Judge the scan type from the caps and the buffer flags of the sampled
+/// frames. Caps may say `mixed` for content that is entirely progressive
+/// or entirely interlaced, so the flags decide in that case.
+pub fn classify(
+    interlace_mode: &str,
+    caps_field_order: Option<&str>,
+    counts: FieldCounts,
+) -> InterlaceReport {
+    let scan_type = if counts.repeat_first_field > 0 {
+        ScanType::Telecine
+    } else {
+        match interlace_mode {
+            "interleaved" | "alternate" => ScanType::Interlaced,
+            "mixed" if counts.interlaced == 0 => ScanType::Progressive,
+            "mixed" if counts.interlaced == counts.frames => ScanType::Interlaced,
+            "mixed" => ScanType::Mixed,
+            _ => ScanType::Progressive,
+        }
+    };
+
+    let field_order = match caps_field_order.filter(|o| *o != "unknown") {
+        Some(order) => Some(order.to_string()),
+        None if counts.interlaced > 0 => Some(
+            if counts.top_field_first * 2 >= counts.interlaced {
+                "top-field-first"
+            } else {
+                "bottom-field-first"
+            }
+            .to_string(),
+        ),
+        None => None,
+    }
+    .filter(|_| matches!(scan_type, ScanType::Interlaced | ScanType::Mixed));
+
+    let tff = match field_order.as_deref() {
+        Some("top-field-first") => " tff=tff",
+        Some("bottom-field-first") => " tff=bff",
+        _ => "",
+    };
+    let (recommendation, suggested_element) = match scan_type {
+        ScanType::Progressive => (None, None),
+        ScanType::Interlaced => (
+            Some("Deinterlace before scaling, encoding for the web or running detectors; interlaced frames show combing".to_string()),
+            Some(format!("deinterlace{}", tff)),
+        ),
+        ScanType::Mixed => (
+            Some("Deinterlace in auto mode so only the interlaced frames are processed".to_string()),
+            Some(format!("deinterlace mode=auto{}", tff)),
+        ),
+        ScanType::Telecine => (
+            Some("Recover the original progressive frames with inverse telecine (ivtc) instead of deinterlacing".to_string()),
+            Some("ivtc".to_string()),
+        ),
+    };
+
+    InterlaceReport {
+        scan_type,
+        interlace_mode: interlace_mode.to_string(),
+        field_order,
+        counts,
+        recommendation,
+        suggested_element,
+    }
+}
+
+/// Decode up to `frames` video frames from a URI and report whether the
+/// video is progressive, interlaced, mixed or telecined, with its field
+/// order.
+pub fn detect_interlacing(uri: &str, frames: u32) -> McpResult<InterlaceReport> {
+    let description = format!(
+        "uridecodebin uri={} ! video/x-raw ! fakesink name=sample sync=false signal-handoffs=true",
+        quote_value(uri)
+    );
+    let pipeline = parse_launch(&description, "Failed to build sampling pipeline")?
+        .downcast::<gst::Pipeline>()
+        .map_err(|_| GStreamerMcpError::PipelineError("Expected a pipeline".to_string()))?;
+    let sink = pipeline
+        .by_name("sample")
+        .ok_or_else(|| GStreamerMcpError::PipelineError("fakesink not found".to_string()))?;
+
+    let counts = Arc::new(Mutex::new(FieldCounts::default()));
+    {
+        let counts = counts.clone();
+        sink.connect("handoff", false, move |values| {
+            let buffer = values.get(1).and_then(|v| v.get::<gst::Buffer>().ok())?;
+            let flags = gst_video::VideoBufferFlags::from_bits_truncate(buffer.flags().bits());
+            let mut counts = counts.lock();
+            counts.frames += 1;
+            if flags.contains(gst_video::VideoBufferFlags::INTERLACED) {
+                counts.interlaced += 1;
+                if flags.contains(gst_video::VideoBufferFlags::TFF) {
+                    counts.top_field_first += 1;
+                }
+            }
+            if flags.contains(gst_video::VideoBufferFlags::RFF) {
+                counts.repeat_first_field += 1;
+            }
+            if flags.contains(gst_video::VideoBufferFlags::ONEFIELD) {
+                counts.single_field += 1;
+            }
+            None
+        });
+    }
+
+    let bus = pipeline
+        .bus()
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Pipeline has no bus".to_string()))?;
+    pipeline
+        .set_state(gst::State::Playing)
+        .map_err(|_| GStreamerMcpError::PipelineError(format!("Failed to open {}", uri)))?;
+
+    let started = Instant::now();
+    let mut error = None;
+    while counts.lock().frames < u64::from(frames) && started.elapsed() < SAMPLE_TIMEOUT {
+        let Some(message) = bus.timed_pop_filtered(
+            gst::ClockTime::from_mseconds(100),
+            &[gst::MessageType::Eos, gst::MessageType::Error],
+        ) else {
+            continue;
+        };
+        if let gst::MessageView::Error(err) = message.view() {
+            error = Some(err.error().to_string());
+        }
+        break;
+    }
+
+    let caps = sink.static_pad("sink").and_then(|pad| pad.current_caps());
+    let _ = pipeline.set_state(gst::State::Null);
+
+    let counts = *counts.lock();
+    if counts.frames == 0 {
+        return Err(GStreamerMcpError::PipelineError(match error {
+            Some(error) => format!("No video decoded from {}: {}", uri, error),
+            None => format!("No video decoded from {}", uri),
+        }));
+    }
+    let structure = caps.as_ref().and_then(|c| c.structure(0));
+    let interlace_mode = structure
+        .and_then(|s| s.get::<String>("interlace-mode").ok())
+        .unwrap_or_else(|| "progressive".to_string());
+    let field_order = structure.and_then(|s| s.get::<String>("field-order").ok());
+    Ok(classify(&interlace_mode, field_order.as_deref(), counts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(frames: u64, interlaced: u64, tff: u64, rff: u64) -> FieldCounts {
+        FieldCounts {
+            frames,
+            interlaced,
+            top_field_first: tff,
+            repeat_first_field: rff,
+            single_field: 0,
+        }
+    }
+
+    #[test]
+    fn test_classify() {
+        let report = classify("progressive", None, counts(100, 0, 0, 0));
+        assert_eq!(report.scan_type, ScanType::Progressive);
+        assert!(report.suggested_element.is_none());
+
+        let report = classify(
+            "interleaved",
+            Some("bottom-field-first"),
+            counts(100, 0, 0, 0),
+        );
+        assert_eq!(report.scan_type, ScanType::Interlaced);
+        assert_eq!(
+            report.suggested_element.as_deref(),
+            Some("deinterlace tff=bff")
+        );
+
+        let report = classify("mixed", None, counts(100, 100, 100, 0));
+        assert_eq!(report.scan_type, ScanType::Interlaced);
+        assert_eq!(report.field_order.as_deref(), Some("top-field-first"));
+
+        let report = classify("mixed", None, counts(100, 40, 0, 0));
+        assert_eq!(report.scan_type, ScanType::Mixed);
+        assert_eq!(
+            report.suggested_element.as_deref(),
+            Some("deinterlace mode=auto tff=bff")
+        );
+
+        let report = classify("mixed", None, counts(100, 0, 0, 40));
+        assert_eq!(report.scan_type, ScanType::Telecine);
+        assert!(report.field_order.is_none());
+        assert_eq!(report.suggested_element.as_deref(), Some("ivtc"));
+    }
+}
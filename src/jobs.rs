@@ -0,0 +1,545 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::Mutex;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+
+use crate::artifacts::{output_locations, Artifact, ArtifactRegistry};
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::pipeline::{validate_pipeline_description, EosOutcome, PipelineManager};
+use crate::upload::{upload_file, UploadProgress, UploadRequest, UploadState, UploadTarget};
+
+/// How often a running job checks for cancellation while waiting on its bus
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl std::fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A pipeline run to completion (EOS) by the job queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub description: String,
+    /// Higher priorities start first; equal priorities run in submission order
+    pub priority: i32,
+    pub status: JobStatus,
+    /// Submission order, used to keep the queue FIFO within a priority
+    pub seq: u64,
+    pub submitted_at: chrono::DateTime<chrono::Utc>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Pipeline running this job while it is running
+    pub pipeline_id: Option<String>,
+    pub error: Option<String>,
+    /// Share of the input processed so far, while running
+    #[serde(default)]
+    pub progress_percent: Option<f64>,
+    /// Where to upload the job's artifacts once it completes
+    #[serde(default)]
+    pub upload: Option<UploadRequest>,
+    #[serde(default)]
+    pub upload_progress: Option<UploadProgress>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    jobs: Vec<Job>,
+    next_seq: u64,
+    cancel_flags: HashMap<String, Arc<AtomicBool>>,
+}
+
+impl QueueState {
+    fn get_mut(&mut self, id: &str) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    fn running(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Running)
+            .count()
+    }
+
+    /// Next job to start: highest priority, then oldest submission
+    fn next_queued(&self) -> Option<String> {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Queued)
+            .max_by(|a, b| a.priority.cmp(&b.priority).then(b.seq.cmp(&a.seq)))
+            .map(|job| job.id.clone())
+    }
+}
+
+/// Priority queue of batch pipelines executed with bounded concurrency
+pub struct JobQueue {
+    state: Mutex<QueueState>,
+    wake: Notify,
+    max_concurrent: usize,
+    state_path: Option<PathBuf>,
+    pipeline_manager: Arc<PipelineManager>,
+    artifacts: Arc<ArtifactRegistry>,
+    upload_targets: HashMap<String, UploadTarget>,
+}
+
+impl JobQueue {
+    /// Create the queue, restoring persisted jobs from `state_path`. Jobs
+    /// that were running when the previous process exited are queued again.
+    pub fn new(
+        pipeline_manager: Arc<PipelineManager>,
+        artifacts: Arc<ArtifactRegistry>,
+        max_concurrent: usize,
+        state_path: Option<PathBuf>,
+    ) -> McpResult<Self> {
+        let mut state = QueueState::default();
+        if let Some(path) = state_path.as_ref().filter(|p| p.exists()) {
+            state.jobs = serde_json::from_slice(&std::fs::read(path)?)?;
+            for job in state.jobs.iter_mut() {
+                if job.status == JobStatus::Running {
+                    job.status = JobStatus::Queued;
+                    job.started_at = None;
+                    job.pipeline_id = None;
+                    job.progress_percent = None;
+                }
+            }
+            state.next_seq = state.jobs.iter().map(|j| j.seq + 1).max().unwrap_or(0);
+        }
+
+        Ok(Self {
+            state: Mutex::new(state),
+            wake: Notify::new(),
+            max_concurrent: max_concurrent.max(1),
+            state_path,
+            pipeline_manager,
+            artifacts,
+            upload_targets: HashMap::new(),
+        })
+    }
+
+    /// Targets that `upload` requests may name
+    pub fn with_upload_targets(mut self, targets: HashMap<String, UploadTarget>) -> Self {
+        self.upload_targets = targets;
+        self
+    }
+
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+
+    fn persist(&self, state: &QueueState) {
+        let Some(path) = &self.state_path else {
+            return;
+        };
+        let result = serde_json::to_vec_pretty(&state.jobs)
+            .map_err(GStreamerMcpError::from)
+            .and_then(|content| {
+                let tmp = path.with_extension("tmp");
+                std::fs::write(&tmp, content)?;
+                std::fs::rename(&tmp, path)?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist job queue: {}", e);
+        }
+    }
+
+    pub fn submit(
+        &self,
+        description: &str,
+        priority: Option<i32>,
+        custom_id: Option<String>,
+        upload: Option<UploadRequest>,
+    ) -> McpResult<Job> {
+        validate_pipeline_description(description)?;
+        if let Some(upload) = &upload {
+            if !self.upload_targets.contains_key(&upload.target) {
+                let mut known: Vec<&str> = self.upload_targets.keys().map(String::as_str).collect();
+                known.sort();
+                return Err(GStreamerMcpError::Other(format!(
+                    "Unknown upload target '{}'. Configured targets: {}",
+                    upload.target,
+                    if known.is_empty() {
+                        "none".to_string()
+                    } else {
+                        known.join(", ")
+                    }
+                )));
+            }
+        }
+
+        let mut state = self.state.lock();
+        let id = custom_id.unwrap_or_else(|| format!("job-{}", uuid::Uuid::new_v4()));
+        if state.jobs.iter().any(|job| job.id == id) {
+            return Err(GStreamerMcpError::PipelineError(format!(
+                "Job '{}' already exists",
+                id
+            )));
+        }
+
+        let job = Job {
+            id,
+            description: description.to_string(),
+            priority: priority.unwrap_or(0),
+            status: JobStatus::Queued,
+            seq: state.next_seq,
+            submitted_at: chrono::Utc::now(),
+            started_at: None,
+            finished_at: None,
+            pipeline_id: None,
+            error: None,
+            progress_percent: None,
+            upload_progress: upload.as_ref().map(|u| UploadProgress::new(&u.target)),
+            upload,
+        };
+        state.next_seq += 1;
+        state.jobs.push(job.clone());
+        self.persist(&state);
+        drop(state);
+
+        self.wake.notify_one();
+        Ok(job)
+    }
+
+    /// Number of jobs with `status`
+    pub fn count(&self, status: JobStatus) -> usize {
+        self.state
+            .lock()
+            .jobs
+            .iter()
+            .filter(|job| job.status == status)
+            .count()
+    }
+
+    /// Jobs in queue order (running, then queued by priority, then finished)
+    pub fn list(&self, status: Option<JobStatus>) -> Vec<Job> {
+        let mut jobs: Vec<Job> = self
+            .state
+            .lock()
+            .jobs
+            .iter()
+            .filter(|job| status.is_none_or(|s| job.status == s))
+            .cloned()
+            .collect();
+        jobs.sort_by(|a, b| {
+            let rank = |job: &Job| match job.status {
+                JobStatus::Running => 0,
+                JobStatus::Queued => 1,
+                _ => 2,
+            };
+            rank(a)
+                .cmp(&rank(b))
+                .then(b.priority.cmp(&a.priority))
+                .then(a.seq.cmp(&b.seq))
+        });
+        jobs
+    }
+
+    /// Cancel a queued job immediately, or ask a running job to stop
+    pub fn cancel(&self, id: &str) -> McpResult<Job> {
+        let mut state = self.state.lock();
+        let flag = state.cancel_flags.get(id).cloned();
+        let job = state
+            .get_mut(id)
+            .ok_or_else(|| GStreamerMcpError::PipelineError(format!("Job '{}' not found", id)))?;
+
+        match job.status {
+            JobStatus::Queued => {
+                job.status = JobStatus::Cancelled;
+                job.finished_at = Some(chrono::Utc::now());
+            }
+            JobStatus::Running => {
+                if let Some(flag) = flag {
+                    flag.store(true, Ordering::SeqCst);
+                }
+            }
+            status => {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "Job '{}' is already {}",
+                    id, status
+                )));
+            }
+        }
+
+        let job = job.clone();
+        self.persist(&state);
+        Ok(job)
+    }
+
+    /// Run the scheduler until the process exits
+    pub fn spawn_scheduler(self: &Arc<Self>) -> tokio::task::JoinHandle<()> {
+        let queue = self.clone();
+        tokio::spawn(async move {
+            loop {
+                while let Some(id) = queue.claim_next() {
+                    queue.start_job(id);
+                }
+                queue.wake.notified().await;
+            }
+        })
+    }
+
+    /// Mark the next queued job as running if a slot is free
+    fn claim_next(&self) -> Option<String> {
+        let mut state = self.state.lock();
+        if state.running() >= self.max_concurrent {
+            return None;
+        }
+        let id = state.next_queued()?;
+        state
+            .cancel_flags
+            .insert(id.clone(), Arc::new(AtomicBool::new(false)));
+        let job = state.get_mut(&id)?;
+        job.status = JobStatus::Running;
+        job.started_at = Some(chrono::Utc::now());
+        job.pipeline_id = Some(id.clone());
+        self.persist(&state);
+        Some(id)
+    }
+
+    fn start_job(self: &Arc<Self>, id: String) {
+        let (description, cancel) = {
+            let mut state = self.state.lock();
+            let cancel = state.cancel_flags.get(&id).cloned().unwrap_or_default();
+            match state.get_mut(&id) {
+                Some(job) => (job.description.clone(), cancel),
+                None => return,
+            }
+        };
+
+        let started = self
+            .pipeline_manager
+            .create_pipeline(&description, Some(id.clone()))
+            .and_then(|pipeline_id| {
+                self.pipeline_manager
+                    .set_pipeline_state(&pipeline_id, gst::State::Playing)?;
+                self.pipeline_manager.get_pipeline_handle(&pipeline_id)
+            });
+
+        let pipeline = match started {
+            Ok(pipeline) => pipeline,
+            Err(e) => {
+                let _ = self.pipeline_manager.remove_pipeline(&id);
+                self.finish(&id, JobStatus::Failed, Some(e.to_string()));
+                return;
+            }
+        };
+
+        let queue = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let (mut status, mut error) = queue.wait_for_completion(&id, &pipeline, &cancel);
+            let _ = queue.pipeline_manager.stop_pipeline(&id);
+            // Cancelled and failed jobs may still have written partial output
+            let produced = queue.artifacts.register(&id, &output_locations(&pipeline));
+            if status == JobStatus::Completed {
+                if let Err(e) = queue.upload_artifacts(&id, &produced) {
+                    status = JobStatus::Failed;
+                    error = Some(e);
+                }
+            }
+            queue.finish(&id, status, error);
+        });
+    }
+
+    /// Block until the job's pipeline reaches EOS, fails, is cancelled or
+    /// disappears because it was stopped directly
+    fn wait_for_completion(
+        &self,
+        id: &str,
+        pipeline: &gst::Pipeline,
+        cancel: &AtomicBool,
+    ) -> (JobStatus, Option<String>) {
+        let watch = match self.pipeline_manager.end_watch(id) {
+            Ok(watch) => watch.since_launch(),
+            Err(e) => return (JobStatus::Failed, Some(e.to_string())),
+        };
+
+        loop {
+            if cancel.load(Ordering::SeqCst) {
+                return (JobStatus::Cancelled, None);
+            }
+            if self.pipeline_manager.get_pipeline(id).is_none() {
+                return (
+                    JobStatus::Cancelled,
+                    Some("Pipeline was stopped outside the job queue".to_string()),
+                );
+            }
+
+            match watch.wait_for_eos(POLL_INTERVAL) {
+                (EosOutcome::Completed, _) => return (JobStatus::Completed, None),
+                (EosOutcome::Failed, error) => return (JobStatus::Failed, error),
+                _ => {}
+            }
+
+            if let Some(progress) = pipeline_progress(pipeline) {
+                if let Some(job) = self.state.lock().get_mut(id) {
+                    job.progress_percent = Some(progress);
+                }
+            }
+        }
+    }
+
+    /// Upload a completed job's artifacts if it asked for it, keeping the
+    /// job's upload progress current
+    fn upload_artifacts(&self, id: &str, artifacts: &[Artifact]) -> Result<(), String> {
+        let Some(request) = self.state.lock().get_mut(id).and_then(|j| j.upload.clone()) else {
+            return Ok(());
+        };
+        let update = |f: &dyn Fn(&mut UploadProgress)| {
+            if let Some(progress) = self
+                .state
+                .lock()
+                .get_mut(id)
+                .and_then(|j| j.upload_progress.as_mut())
+            {
+                f(progress);
+            }
+        };
+
+        let result = match self.upload_targets.get(&request.target) {
+            None => Err(format!(
+                "Upload target '{}' is no longer configured",
+                request.target
+            )),
+            Some(target) => {
+                let total: u64 = artifacts.iter().map(|a| a.size_bytes).sum();
+                update(&|p| {
+                    p.state = UploadState::Uploading;
+                    p.bytes_total = total;
+                });
+
+                let mut uploaded = 0;
+                artifacts.iter().try_for_each(|artifact| {
+                    let path = artifact.paths.first().ok_or("Artifact has no path")?;
+                    let url = upload_file(target, path, request.prefix.as_deref(), |sent| {
+                        update(&|p| p.bytes_uploaded = uploaded + sent)
+                    })
+                    .map_err(|e| e.to_string())?;
+                    uploaded += artifact.size_bytes;
+                    update(&|p| {
+                        p.bytes_uploaded = uploaded;
+                        p.urls.push(url.clone());
+                    });
+                    Ok::<(), String>(())
+                })
+            }
+        };
+
+        match &result {
+            Ok(()) => update(&|p| p.state = UploadState::Completed),
+            Err(e) => update(&|p| {
+                p.state = UploadState::Failed;
+                p.error = Some(e.clone());
+            }),
+        }
+        result
+    }
+
+    fn finish(&self, id: &str, status: JobStatus, error: Option<String>) {
+        let mut state = self.state.lock();
+        state.cancel_flags.remove(id);
+        if let Some(job) = state.get_mut(id) {
+            if status == JobStatus::Completed {
+                job.progress_percent = Some(100.0);
+            }
+            job.status = status;
+            job.error = error;
+            job.finished_at = Some(chrono::Utc::now());
+            job.pipeline_id = None;
+        }
+        self.persist(&state);
+        drop(state);
+
+        tracing::info!("Job '{}' {}", id, status);
+        self.wake.notify_one();
+    }
+}
+
+/// Progress of a file-processing pipeline in percent: bytes read by its
+/// file sources out of their total size, or the playback position out of
+/// the duration when it reads no files
+fn pipeline_progress(pipeline: &gst::Pipeline) -> Option<f64> {
+    let (read, total) = pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.factory().is_some_and(|f| f.name() == "filesrc"))
+        .filter_map(|src| {
+            let read = src.query_position::<gst::format::Bytes>()?;
+            let total = src.query_duration::<gst::format::Bytes>()?;
+            Some((*read, *total))
+        })
+        .fold((0u64, 0u64), |(r, t), (read, total)| (r + read, t + total));
+
+    let (position, duration) = if total > 0 {
+        (read, total)
+    } else {
+        let position = pipeline.query_position::<gst::ClockTime>()?;
+        let duration = pipeline.query_duration::<gst::ClockTime>()?;
+        (position.nseconds(), duration.nseconds())
+    };
+    (duration > 0).then(|| (position.min(duration) as f64 / duration as f64 * 100.0).round())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, priority: i32, seq: u64) -> Job {
+        Job {
+            id: id.to_string(),
+            description: "videotestsrc num-buffers=10 ! fakesink".to_string(),
+            priority,
+            status: JobStatus::Queued,
+            seq,
+            submitted_at: chrono::Utc::now(),
+            started_at: None,
+            finished_at: None,
+            pipeline_id: None,
+            error: None,
+            progress_percent: None,
+            upload: None,
+            upload_progress: None,
+        }
+    }
+
+    #[test]
+    fn test_next_queued_priority_then_fifo() {
+        let mut state = QueueState {
+            jobs: vec![job("a", 0, 0), job("b", 5, 1), job("c", 5, 2)],
+            ..Default::default()
+        };
+        assert_eq!(state.next_queued().as_deref(), Some("b"));
+
+        state.get_mut("b").unwrap().status = JobStatus::Running;
+        assert_eq!(state.next_queued().as_deref(), Some("c"));
+        assert_eq!(state.running(), 1);
+
+        state.get_mut("c").unwrap().status = JobStatus::Cancelled;
+        assert_eq!(state.next_queued().as_deref(), Some("a"));
+    }
+}
@@ -0,0 +1,164 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Where the clock of a pipeline gets its time from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockKind {
+    /// The monotonic system clock, picked when no element provides one
+    System,
+    /// An audio device, so playback follows the sound card
+    Audio,
+    /// A clock following a server on the network (NTP or GStreamer's own)
+    Network,
+    /// A PTP grandmaster
+    Ptp,
+    Other,
+}
+
+impl ClockKind {
+    /// Kind of a clock from its GType name
+    pub fn from_type_name(type_name: &str) -> Self {
+        match type_name {
+            "GstSystemClock" => ClockKind::System,
+            "GstNetClientClock" | "GstNtpClock" => ClockKind::Network,
+            "GstPtpClock" => ClockKind::Ptp,
+            name if name.contains("Audio") => ClockKind::Audio,
+            _ => ClockKind::Other,
+        }
+    }
+}
+
+/// Clock a pipeline synchronizes to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockInfo {
+    pub name: String,
+    pub type_name: String,
+    pub kind: ClockKind,
+    pub time_ns: u64,
+}
+
+/// Latency and clock of a pipeline, for diagnosing A/V sync and delay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyReport {
+    pub pipeline_id: String,
+    /// Whether an element answered the latency query; pipelines answer once
+    /// prerolled
+    pub answered: bool,
+    /// Whether the pipeline has live sources
+    pub live: bool,
+    /// Lowest latency the pipeline can run with
+    pub min_latency_ns: Option<u64>,
+    /// Highest latency the pipeline can buffer; unset when unlimited
+    pub max_latency_ns: Option<u64>,
+    /// Latency configured on the pipeline in place of the queried minimum
+    pub configured_latency_ns: Option<u64>,
+    /// Unset until the pipeline first goes to PLAYING
+    pub clock: Option<ClockInfo>,
+    pub base_time_ns: Option<u64>,
+    /// Clock time minus base time
+    pub running_time_ns: Option<u64>,
+    pub warnings: Vec<String>,
+}
+
+/// Problems with a latency query result. A pipeline whose minimum latency
+/// exceeds the maximum cannot buffer enough and drops data.
+pub fn latency_warnings(live: bool, min_ns: u64, max_ns: Option<u64>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(max) = max_ns {
+        if min_ns > max {
+            warnings.push(format!(
+                "Minimum latency {} ms exceeds the maximum {} ms; add a queue or raise buffering so the pipeline can cover it",
+                min_ns / 1_000_000,
+                max / 1_000_000
+            ));
+        }
+    }
+    if live && min_ns == 0 {
+        warnings.push(
+            "The pipeline is live but reports no latency; sinks may render late and drop frames"
+                .to_string(),
+        );
+    }
+    warnings
+}
+
+/// Query the latency of a pipeline and read its clock. Returns the report
+/// with `pipeline_id` unset.
+pub fn query_latency(pipeline: &gst::Pipeline) -> LatencyReport {
+    let mut query = gst::query::Latency::new();
+    let answered = pipeline.query(&mut query);
+    let (live, min, max) = query.result();
+
+    let clock = pipeline.clock().map(|clock| {
+        let type_name = clock.type_().name().to_string();
+        ClockInfo {
+            name: clock.name().to_string(),
+            kind: ClockKind::from_type_name(&type_name),
+            type_name,
+            time_ns: clock.time().nseconds(),
+        }
+    });
+    let base_time = pipeline.base_time().map(|t| t.nseconds());
+    let running_time = clock
+        .as_ref()
+        .zip(base_time)
+        .and_then(|(clock, base)| clock.time_ns.checked_sub(base));
+
+    let mut warnings = if answered {
+        latency_warnings(live, min.nseconds(), max.map(|m| m.nseconds()))
+    } else {
+        vec!["No element answered the latency query; pause or play the pipeline first".to_string()]
+    };
+    if clock.is_none() {
+        warnings.push("No clock selected yet; the pipeline picks one when it plays".to_string());
+    }
+
+    LatencyReport {
+        pipeline_id: String::new(),
+        answered,
+        live: answered && live,
+        min_latency_ns: answered.then(|| min.nseconds()),
+        max_latency_ns: if answered {
+            max.map(|m| m.nseconds())
+        } else {
+            None
+        },
+        configured_latency_ns: pipeline.latency().map(|l| l.nseconds()),
+        clock,
+        base_time_ns: base_time,
+        running_time_ns: running_time,
+        warnings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_000_000;
+
+    #[test]
+    fn test_latency_warnings() {
+        assert!(latency_warnings(true, 20 * MS, Some(200 * MS)).is_empty());
+        assert!(latency_warnings(true, 20 * MS, None).is_empty());
+        assert!(latency_warnings(false, 0, None).is_empty());
+        assert!(latency_warnings(true, 300 * MS, Some(200 * MS))[0].contains("300 ms"));
+        assert_eq!(latency_warnings(true, 0, None).len(), 1);
+
+        assert_eq!(
+            ClockKind::from_type_name("GstSystemClock"),
+            ClockKind::System
+        );
+        assert_eq!(
+            ClockKind::from_type_name("GstPulseSinkClock"),
+            ClockKind::Other
+        );
+        assert_eq!(ClockKind::from_type_name("GstAudioClock"), ClockKind::Audio);
+        assert_eq!(
+            ClockKind::from_type_name("GstNetClientClock"),
+            ClockKind::Network
+        );
+    }
+}
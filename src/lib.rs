@@ -1,12 +1,81 @@
+pub mod abr;
+pub mod adaptive;
+pub mod analysis;
+pub mod animation;
+pub mod aravis;
+pub mod artifacts;
+pub mod audio_monitor;
+pub mod auto_queue;
+pub mod bus_events;
 pub mod bus_handler;
+pub mod caps_watch;
+pub mod channels;
 pub mod cli;
+pub mod color;
+pub mod concat;
 pub mod config;
+pub mod confirmation;
+pub mod conversion;
+pub mod daemon;
+pub mod deep_validation;
 pub mod discovery;
+pub mod doctor;
+pub mod element_messages;
+pub mod element_states;
+pub mod encoder_switch;
+pub mod encoding_profile;
 pub mod error;
+pub mod error_budget;
+pub mod estimate;
+pub mod events;
+pub mod extract;
 pub mod handler;
+pub mod inference;
+pub mod interlace;
+pub mod jobs;
+pub mod latency;
+pub mod limits;
+pub mod locale;
+pub mod looping;
+pub mod loudness;
+pub mod media;
+pub mod memory;
+pub mod message_filter;
+pub mod message_history;
+pub mod message_wait;
+pub mod meta_tap;
+pub mod metrics;
+pub mod modify;
+pub mod ndi;
+pub mod netsync;
+pub mod network;
+pub mod notifications;
+pub mod oneshot;
+pub mod ownership;
 pub mod pipeline;
+pub mod pipeline_log;
+pub mod policy;
+pub mod prerecord;
+pub mod probes;
+pub mod progress;
+pub mod properties;
+pub mod reaper;
+pub mod registry_watcher;
 pub mod repl;
+pub mod routing;
+pub mod schedule;
+pub mod shared_state;
+pub mod speech;
+pub mod speed;
+pub mod stall;
+pub mod suggestions;
+pub mod time_display;
+pub mod timecode;
 pub mod tool_registry;
+pub mod units;
+pub mod upload;
+pub mod verify;
+pub mod watermark;
 
 pub use error::{GStreamerMcpError, Result};
 pub use handler::GStreamerHandler;
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+use crate::config::Configuration;
+use crate::discovery::{CacheStatus, DiscoveryCache};
+use crate::jobs::{JobQueue, JobStatus};
+use crate::notifications::Notifier;
+use crate::pipeline::PipelineManager;
+
+/// Resource with the capacity of the server and how much of it is used
+pub const LIMITS_URI: &str = "gst://server/limits";
+
+/// How often the limits are sampled for changes
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pipeline slots of the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PipelineUsage {
+    pub max: usize,
+    pub in_use: usize,
+    /// Pipelines that can still be launched
+    pub available: usize,
+}
+
+/// Batch jobs of the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobUsage {
+    pub max_concurrent: usize,
+    pub running: usize,
+    /// Jobs waiting for a slot
+    pub queued: usize,
+}
+
+/// Content of the `gst://server/limits` resource
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServerLimits {
+    pub pipelines: PipelineUsage,
+    pub jobs: JobUsage,
+    pub cache_enabled: bool,
+    pub cache: CacheStatus,
+}
+
+impl PipelineUsage {
+    pub fn new(max: usize, in_use: usize) -> Self {
+        Self {
+            max,
+            in_use,
+            available: max.saturating_sub(in_use),
+        }
+    }
+}
+
+impl ServerLimits {
+    /// Current limits and usage
+    pub async fn sample(
+        pipeline_manager: &PipelineManager,
+        jobs: &JobQueue,
+        cache: &DiscoveryCache,
+        cache_enabled: bool,
+    ) -> Self {
+        Self {
+            pipelines: PipelineUsage::new(
+                pipeline_manager.max_pipelines(),
+                pipeline_manager.list_pipelines().len(),
+            ),
+            jobs: JobUsage {
+                max_concurrent: jobs.max_concurrent(),
+                running: jobs.count(JobStatus::Running),
+                queued: jobs.count(JobStatus::Queued),
+            },
+            cache_enabled,
+            cache: cache.status().await,
+        }
+    }
+}
+
+/// Sample the limits every second and tell subscribed clients when they
+/// change
+pub fn spawn_limits_publisher(
+    pipeline_manager: Arc<PipelineManager>,
+    jobs: Arc<JobQueue>,
+    cache: Arc<DiscoveryCache>,
+    config: Arc<RwLock<Configuration>>,
+    notifier: Notifier,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut last: Option<ServerLimits> = None;
+        loop {
+            tokio::time::sleep(SAMPLE_INTERVAL).await;
+
+            let cache_enabled = config.read().await.cache_enabled;
+            let limits =
+                ServerLimits::sample(&pipeline_manager, &jobs, &cache, cache_enabled).await;
+            if last.as_ref() != Some(&limits) {
+                if last.is_some() {
+                    notifier.notify_resource_updated(LIMITS_URI).await;
+                }
+                last = Some(limits);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_usage() {
+        assert_eq!(
+            PipelineUsage::new(10, 3),
+            PipelineUsage {
+                max: 10,
+                in_use: 3,
+                available: 7,
+            }
+        );
+        // More pipelines than slots leaves none available
+        assert_eq!(PipelineUsage::new(2, 5).available, 0);
+    }
+}
@@ -0,0 +1,139 @@
+use once_cell::sync::OnceCell;
+use std::ffi::{CStr, CString};
+
+/// Passes the description language on to inspection helpers
+pub const DESCRIPTION_LANGUAGE_ENV: &str = "GSTREAMER_MCP_DESCRIPTION_LANGUAGE";
+
+static DESCRIPTION_LANGUAGE: OnceCell<Option<String>> = OnceCell::new();
+/// Language the descriptions ended up in, resolved once
+static RESOLVED_LANGUAGE: OnceCell<Option<String>> = OnceCell::new();
+
+/// Whether `language` asks for the untranslated English descriptions
+pub fn is_english(language: &str) -> bool {
+    let language = language.trim().to_lowercase();
+    language == "c"
+        || language == "posix"
+        || language == "english"
+        || language == "en"
+        || language.starts_with("en_")
+        || language.starts_with("en-")
+}
+
+/// Choose the language of element descriptions, property blurbs and
+/// GStreamer error messages, before GStreamer is initialized. Unset follows
+/// LANG/LC_MESSAGES/LANGUAGE; `en` forces English so automated clients see
+/// the same text everywhere; other values are gettext languages such as
+/// `de` or `pt_BR`. Strings are only translated where GStreamer and the
+/// plugins ship translations.
+///
+/// This changes the environment and the C locale, which is only sound while
+/// the process has a single thread: call it from `main` before the async
+/// runtime or anything else starts threads.
+pub fn set_description_language(language: Option<&str>) {
+    let language = language
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(str::to_string);
+    if DESCRIPTION_LANGUAGE.set(language.clone()).is_err() {
+        return;
+    }
+
+    match language.as_deref() {
+        Some(language) if is_english(language) => {
+            set_messages_locale("C");
+            // Without a messages locale only LANGUAGE picks the catalogs
+            #[cfg(not(unix))]
+            std::env::set_var("LANGUAGE", "en");
+        }
+        Some(language) => {
+            // gettext takes LANGUAGE over the locale, unless the locale is C
+            std::env::set_var("LANGUAGE", language);
+            if set_messages_locale("").as_deref() == Some("C") {
+                set_messages_locale("C.UTF-8");
+            }
+        }
+        None => {
+            set_messages_locale("");
+        }
+    }
+    let resolved = RESOLVED_LANGUAGE.get_or_init(resolve_description_language);
+    tracing::debug!(
+        "Element descriptions in {}",
+        resolved.as_deref().unwrap_or("C")
+    );
+}
+
+/// Language set by `set_description_language`, if any
+pub fn configured_description_language() -> Option<String> {
+    DESCRIPTION_LANGUAGE.get().cloned().flatten()
+}
+
+/// Language descriptions are translated to, from LANGUAGE and the messages
+/// locale as `set_description_language` left them; None when they are
+/// untranslated English
+pub fn description_language() -> Option<String> {
+    RESOLVED_LANGUAGE
+        .get_or_init(resolve_description_language)
+        .clone()
+}
+
+fn resolve_description_language() -> Option<String> {
+    let locale = query_messages_locale();
+    if matches!(locale.as_deref(), Some("C" | "POSIX")) {
+        return None;
+    }
+    std::env::var("LANGUAGE")
+        .ok()
+        .and_then(|l| l.split(':').next().map(str::to_string))
+        .filter(|l| !l.is_empty())
+        .or(locale)
+        .filter(|l| !is_english(l))
+}
+
+/// Set the messages locale; "" takes it from the environment. Returns the
+/// locale in effect, or None if it is not available.
+#[cfg(unix)]
+fn set_messages_locale(locale: &str) -> Option<String> {
+    let locale = CString::new(locale).ok()?;
+    // SAFETY: setlocale is given a valid C string and returns a string
+    // owned by the C library, copied before any other locale call
+    unsafe {
+        let result = libc::setlocale(libc::LC_MESSAGES, locale.as_ptr());
+        (!result.is_null()).then(|| CStr::from_ptr(result).to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(unix)]
+fn query_messages_locale() -> Option<String> {
+    // SAFETY: a null locale only queries the current one
+    unsafe {
+        let result = libc::setlocale(libc::LC_MESSAGES, std::ptr::null());
+        (!result.is_null()).then(|| CStr::from_ptr(result).to_string_lossy().into_owned())
+    }
+}
+
+/// Windows has no LC_MESSAGES; gettext goes by LANGUAGE there
+#[cfg(not(unix))]
+fn set_messages_locale(_locale: &str) -> Option<String> {
+    None
+}
+
+#[cfg(not(unix))]
+fn query_messages_locale() -> Option<String> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_english() {
+        assert!(is_english("en"));
+        assert!(is_english("en_US.UTF-8"));
+        assert!(is_english("C"));
+        assert!(is_english(" English "));
+        assert!(!is_english("de"));
+        assert!(!is_english("pt_BR"));
+    }
+}
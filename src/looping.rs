@@ -0,0 +1,272 @@
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::progress::query_segment;
+
+/// Segment a pipeline loops over, and how often
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoopSettings {
+    pub start_ns: u64,
+    /// Unset to loop to the end of the media
+    pub stop_ns: Option<u64>,
+    /// Times the segment plays in all; unset to loop until turned off
+    pub count: Option<u32>,
+}
+
+/// Loop progress, as reported by the pipeline status
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoopStatus {
+    #[serde(flatten)]
+    pub settings: LoopSettings,
+    /// Plays of the segment completed
+    pub completed: u32,
+    /// Plays left, the current one included; unset when looping forever
+    pub remaining: Option<u32>,
+    /// Whether the segment is still repeated; false once the plays are used
+    /// up or looping was turned off
+    pub active: bool,
+}
+
+/// What to do when the looped segment has played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopAction {
+    /// Seek back to the start. With `segment`, the next play ends with
+    /// SEGMENT_DONE, so it can be repeated without a flush; the last play
+    /// ends with EOS instead.
+    Repeat { segment: bool },
+    /// Let the pipeline reach EOS
+    Finish,
+}
+
+/// Counts the plays of a looped segment
+#[derive(Debug, Clone)]
+pub struct LoopState {
+    settings: LoopSettings,
+    completed: u32,
+    active: bool,
+}
+
+impl LoopState {
+    pub fn new(settings: LoopSettings) -> Self {
+        Self {
+            settings,
+            completed: 0,
+            active: true,
+        }
+    }
+
+    /// Whether the play about to start is followed by another
+    pub fn repeats(&self) -> bool {
+        self.active
+            && self
+                .settings
+                .count
+                .is_none_or(|count| self.completed + 1 < count)
+    }
+
+    /// Count a finished play and decide whether to play again
+    pub fn on_end(&mut self) -> LoopAction {
+        if !self.active {
+            return LoopAction::Finish;
+        }
+        self.completed += 1;
+        match self.settings.count {
+            Some(count) if self.completed >= count => {
+                self.active = false;
+                LoopAction::Finish
+            }
+            _ => LoopAction::Repeat {
+                segment: self.repeats(),
+            },
+        }
+    }
+
+    /// Stop repeating; the current play is the last
+    pub fn stop(&mut self) {
+        self.active = false;
+    }
+
+    pub fn status(&self) -> LoopStatus {
+        LoopStatus {
+            settings: self.settings,
+            completed: self.completed,
+            remaining: self
+                .settings
+                .count
+                .map(|count| count.saturating_sub(self.completed)),
+            active: self.active,
+        }
+    }
+}
+
+/// Playback rate of the current segment of `pipeline`
+fn current_rate(pipeline: &gst::Pipeline) -> f64 {
+    query_segment(pipeline)
+        .map(|segment| segment.rate)
+        .filter(|rate| *rate != 0.0)
+        .unwrap_or(1.0)
+}
+
+/// Seek `pipeline` to the start of the looped segment, keeping its rate
+fn seek_to_start(
+    pipeline: &gst::Pipeline,
+    settings: &LoopSettings,
+    flush: bool,
+    segment: bool,
+) -> bool {
+    let mut flags = gst::SeekFlags::ACCURATE;
+    if flush {
+        flags |= gst::SeekFlags::FLUSH;
+    }
+    if segment {
+        flags |= gst::SeekFlags::SEGMENT;
+    }
+    let rate = current_rate(pipeline);
+    let stop = settings.stop_ns.map(gst::ClockTime::from_nseconds);
+    pipeline
+        .seek(
+            rate,
+            flags,
+            gst::SeekType::Set,
+            gst::ClockTime::from_nseconds(settings.start_ns),
+            if stop.is_some() {
+                gst::SeekType::Set
+            } else {
+                gst::SeekType::None
+            },
+            stop,
+        )
+        .is_ok()
+}
+
+/// Loops a pipeline by answering SEGMENT_DONE and EOS with seeks back to
+/// the start of the segment. Segment seeks repeat it without a gap; EOS,
+/// from elements that do not support them, is answered with a flushing
+/// seek. Looping ends when the looper is dropped.
+#[derive(Debug)]
+pub struct Looper {
+    bus: gst::Bus,
+    handler: Option<glib::SignalHandlerId>,
+    state: Arc<Mutex<LoopState>>,
+}
+
+impl Looper {
+    /// Start looping `pipeline`, which must be paused or playing, with a
+    /// flushing seek to the start of the segment
+    pub fn start(pipeline: &gst::Pipeline, settings: LoopSettings) -> Option<Self> {
+        let bus = pipeline.bus()?;
+        let state = Arc::new(Mutex::new(LoopState::new(settings)));
+
+        // Messages are read in the streaming thread that posts them without
+        // taking them off the bus; seeks are made from another thread
+        bus.enable_sync_message_emission();
+        let weak = pipeline.downgrade();
+        let loop_state = state.clone();
+        let handler = bus.connect_sync_message(None, move |_, message| {
+            let Some(pipeline) = weak.upgrade() else {
+                return;
+            };
+            if message.src() != Some(pipeline.upcast_ref()) {
+                return;
+            }
+            let (eos, done_at) = match message.view() {
+                gst::MessageView::SegmentDone(done) => (false, done.get().value()),
+                gst::MessageView::Eos(_) => (true, 0),
+                _ => return,
+            };
+            let action = loop_state.lock().on_end();
+            pipeline.call_async(move |pipeline| match action {
+                LoopAction::Repeat { segment } => {
+                    seek_to_start(pipeline, &settings, eos, segment);
+                }
+                // After SEGMENT_DONE the sources wait for a seek; an empty
+                // segment makes them send EOS
+                LoopAction::Finish if !eos => {
+                    let _ = pipeline.seek(
+                        current_rate(pipeline),
+                        gst::SeekFlags::empty(),
+                        gst::SeekType::Set,
+                        gst::ClockTime::from_nseconds(done_at.max(0) as u64),
+                        gst::SeekType::Set,
+                        gst::ClockTime::from_nseconds(done_at.max(0) as u64),
+                    );
+                }
+                LoopAction::Finish => {}
+            });
+        });
+
+        let looper = Self {
+            bus,
+            handler: Some(handler),
+            state,
+        };
+        let segment = looper.state.lock().repeats();
+        seek_to_start(pipeline, &settings, true, segment).then_some(looper)
+    }
+
+    pub fn status(&self) -> LoopStatus {
+        self.state.lock().status()
+    }
+
+    /// Let the current play be the last
+    pub fn stop(&self) {
+        self.state.lock().stop();
+    }
+}
+
+impl Drop for Looper {
+    fn drop(&mut self) {
+        if let Some(handler) = self.handler.take() {
+            self.bus.disconnect(handler);
+            self.bus.disable_sync_message_emission();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loop_state() {
+        let settings = LoopSettings {
+            start_ns: 0,
+            stop_ns: Some(5_000_000_000),
+            count: Some(3),
+        };
+        let mut state = LoopState::new(settings);
+        assert!(state.repeats());
+        assert_eq!(state.on_end(), LoopAction::Repeat { segment: true });
+        // The last play ends with EOS
+        assert_eq!(state.on_end(), LoopAction::Repeat { segment: false });
+        assert_eq!(state.status().remaining, Some(1));
+        assert_eq!(state.on_end(), LoopAction::Finish);
+        let status = state.status();
+        assert_eq!((status.completed, status.remaining), (3, Some(0)));
+        assert!(!status.active);
+
+        // A single play is not repeated at all
+        assert!(!LoopState::new(LoopSettings {
+            count: Some(1),
+            ..settings
+        })
+        .repeats());
+
+        // Looping forever until turned off
+        let mut state = LoopState::new(LoopSettings {
+            count: None,
+            ..settings
+        });
+        for _ in 0..5 {
+            assert_eq!(state.on_end(), LoopAction::Repeat { segment: true });
+        }
+        state.stop();
+        assert_eq!(state.on_end(), LoopAction::Finish);
+        assert_eq!(state.status().completed, 5);
+        assert_eq!(state.status().remaining, None);
+    }
+}
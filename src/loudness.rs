@@ -0,0 +1,174 @@
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::{
+    media_type, muxer_accepts, output_format, quote_location, require_element, run_to_completion,
+    MediaInfo,
+};
+
+/// EBU R128 meter from gst-plugins-rs (audiofx)
+const LOUDNESS_METER: &str = "ebur128level";
+
+/// Element message posted by the meter
+const METER_MESSAGE: &str = "ebur128-level";
+
+/// Interval between meter messages; the last one before EOS carries the
+/// integrated loudness of (almost) the whole file
+const METER_INTERVAL_MS: u64 = 100;
+
+/// Loudness of a file according to EBU R128
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoudnessMeasurement {
+    /// Integrated loudness in LUFS
+    pub integrated_lufs: f64,
+    /// Loudness range in LU
+    pub loudness_range_lu: Option<f64>,
+    /// Highest true peak over all channels in dBTP
+    pub true_peak_dbtp: Option<f64>,
+}
+
+/// Gain that brings `measured` to `target_lufs` without pushing the true
+/// peak above `peak_limit_dbtp`
+pub fn normalization_gain_db(
+    measured: &LoudnessMeasurement,
+    target_lufs: f64,
+    peak_limit_dbtp: f64,
+) -> f64 {
+    let gain = target_lufs - measured.integrated_lufs;
+    match measured.true_peak_dbtp {
+        Some(peak) => gain.min(peak_limit_dbtp - peak),
+        None => gain,
+    }
+}
+
+fn db_to_linear(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+fn linear_to_db(linear: f64) -> f64 {
+    20.0 * linear.log10()
+}
+
+/// Measure the loudness of a file's first audio stream. Decodes the whole
+/// file, so call it from a blocking context.
+pub fn measure_loudness(path: &Path) -> McpResult<LoudnessMeasurement> {
+    let meter = require_element(&[LOUDNESS_METER], "loudness meter (gst-plugins-rs audiofx)")?;
+    let description = format!(
+        "filesrc location={} ! decodebin name=d d. ! audio/x-raw ! queue ! audioconvert ! {} post-messages=true interval={} ! fakesink sync=false",
+        quote_location(path),
+        meter,
+        METER_INTERVAL_MS * gst::ClockTime::MSECOND.nseconds()
+    );
+
+    let mut last: Option<LoudnessMeasurement> = None;
+    run_to_completion(&description, |structure| {
+        if structure.name() != METER_MESSAGE {
+            return;
+        }
+        let Ok(integrated_lufs) = structure.get::<f64>("global-loudness") else {
+            return;
+        };
+        let true_peak_dbtp = structure
+            .get::<gst::Array>("true-peak")
+            .ok()
+            .and_then(|peaks| {
+                peaks
+                    .iter()
+                    .filter_map(|v| v.get::<f64>().ok())
+                    .reduce(f64::max)
+            })
+            .filter(|peak| *peak > 0.0)
+            .map(linear_to_db);
+        last = Some(LoudnessMeasurement {
+            integrated_lufs,
+            loudness_range_lu: structure.get::<f64>("loudness-range").ok(),
+            true_peak_dbtp,
+        });
+    })?;
+
+    last.filter(|m| m.integrated_lufs.is_finite())
+        .ok_or_else(|| {
+            GStreamerMcpError::Other(format!(
+                "No loudness could be measured for {} (silent or no audio stream)",
+                path.display()
+            ))
+        })
+}
+
+/// gst-launch description applying `gain_db` to the audio of `input` and
+/// writing `output`. The audio is re-encoded; video is copied unchanged.
+pub fn normalize_description(input: &MediaInfo, output: &Path, gain_db: f64) -> McpResult<String> {
+    if input.audio.is_empty() {
+        return Err(GStreamerMcpError::Other(format!(
+            "{} has no audio stream",
+            input.path.display()
+        )));
+    }
+
+    let format = output_format(output)?;
+    let encoder = require_element(format.audio_encoders, "audio encoder")?;
+
+    let mut parts = vec![
+        format!(
+            "{} name=mux ! filesink location={}",
+            format.muxer,
+            quote_location(output)
+        ),
+        format!(
+            "filesrc location={} ! parsebin name=p",
+            quote_location(&input.path)
+        ),
+        format!(
+            "p. ! {} ! queue ! decodebin ! audioconvert ! volume volume={:.6} ! audioconvert ! audioresample ! {} ! queue ! mux.",
+            media_type(&input.audio[0].codec),
+            db_to_linear(gain_db),
+            encoder
+        ),
+    ];
+
+    if let Some(video) = input.video.first() {
+        if !muxer_accepts(format.muxer, &video.caps) {
+            return Err(GStreamerMcpError::Other(format!(
+                "{} cannot store the {} video of {}; choose another output container",
+                format.muxer,
+                media_type(&video.codec),
+                input.path.display()
+            )));
+        }
+        parts.push(format!("p. ! {} ! queue ! mux.", media_type(&video.codec)));
+    }
+
+    Ok(parts.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization_gain() {
+        let quiet = LoudnessMeasurement {
+            integrated_lufs: -30.0,
+            loudness_range_lu: None,
+            true_peak_dbtp: Some(-12.0),
+        };
+        assert_eq!(normalization_gain_db(&quiet, -23.0, -1.0), 7.0);
+        // Limited by the true peak
+        assert_eq!(normalization_gain_db(&quiet, -14.0, -1.0), 11.0);
+
+        let loud = LoudnessMeasurement {
+            integrated_lufs: -9.0,
+            loudness_range_lu: None,
+            true_peak_dbtp: None,
+        };
+        assert_eq!(normalization_gain_db(&loud, -14.0, -1.0), -5.0);
+    }
+
+    #[test]
+    fn test_db_conversion() {
+        assert!((db_to_linear(-6.0) - 0.501187).abs() < 1e-6);
+        assert!((linear_to_db(db_to_linear(-3.5)) + 3.5).abs() < 1e-9);
+    }
+}
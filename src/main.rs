@@ -1,13 +1,24 @@
 use anyhow::Result;
-use gstreamer_mcp::{cli::Cli, config::Configuration, handler::GStreamerHandler, repl};
+use gstreamer_mcp::{
+    cli::{Cli, ParsedConfig},
+    config::Configuration,
+    daemon, discovery,
+    handler::GStreamerHandler,
+    locale, oneshot, ownership, repl,
+};
 use rmcp::{transport::stdio, ServiceExt};
 use tracing_subscriber::{self, EnvFilter};
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     // Parse CLI arguments BEFORE stdio takeover
     let cli_config = Cli::parse_with_env();
 
+    // Helper process of isolated element inspection
+    if let Some(ref elements) = cli_config.inspect_helper {
+        discovery::inspect_helper_main(elements)?;
+        return Ok(());
+    }
+
     // Configure logging based on verbosity level
     let log_level = match cli_config.verbose_level {
         0 => tracing::Level::INFO,
@@ -23,6 +34,12 @@ async fn main() -> Result<()> {
         .init();
 
     tracing::info!("Starting gstreamer-mcp server");
+
+    if cli_config.daemon && !daemon::SUPPORTED {
+        return Err(anyhow::anyhow!(
+            "--daemon is only supported on Linux (systemd) and Windows (service)"
+        ));
+    }
     tracing::debug!("CLI config: {:?}", cli_config);
 
     // Load configuration from file
@@ -42,20 +59,46 @@ async fn main() -> Result<()> {
     // Merge CLI arguments (highest priority)
     config.merge_cli_args(&cli_config);
 
+    // Descriptions are translated from the time GStreamer is initialized.
+    // This sets the locale, so it runs before the runtime starts threads.
+    locale::set_description_language(config.description_language.as_deref());
+
     tracing::info!(
         "Configuration loaded, mode: {:?}, cache: {}",
         config.operational_mode,
         config.cache_enabled
     );
 
-    // Check if running in REPL mode
-    if cli_config.repl {
+    let daemon = cli_config.daemon;
+    let serve = move || {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?
+            .block_on(run(cli_config, config))
+    };
+    if daemon {
+        daemon::run(serve)
+    } else {
+        serve()
+    }
+}
+
+async fn run(cli_config: ParsedConfig, config: Configuration) -> Result<()> {
+    // Run a single tool call, or check if running in REPL mode
+    if let Some(call) = cli_config.call {
+        let success = oneshot::call_tool(config, &call.tool, &call.params, call.output).await?;
+        if !success {
+            std::process::exit(1);
+        }
+    } else if cli_config.repl {
         tracing::info!("Starting REPL mode");
-        repl::run_repl(config).await?;
+        repl::run_repl(config, cli_config.no_color).await?;
     } else {
         // Normal MCP server mode
         // Create handler with configuration
+        let orphan_policy = config.orphan_policy;
         let handler = GStreamerHandler::with_config(config).await?;
+        let pipeline_manager = handler.pipeline_manager.clone();
 
         tracing::info!("GStreamer initialized, starting MCP server on stdio");
 
@@ -64,10 +107,34 @@ async fn main() -> Result<()> {
             tracing::error!("Server error: {:?}", e);
         })?;
 
+        if cli_config.daemon {
+            daemon::notify_ready();
+            daemon::notify_status("Serving MCP on stdio");
+            daemon::spawn_watchdog();
+
+            // The service manager may stop the server before its client
+            // leaves
+            let cancel = service.cancellation_token();
+            tokio::spawn(async move {
+                daemon::stop_requested().await;
+                tracing::info!("Stop requested by the service manager");
+                cancel.cancel();
+            });
+        }
+
         // Wait for the service to complete
         service.waiting().await?;
 
+        // Pipelines outlive their client only as the orphan policy allows
+        if cli_config.daemon {
+            daemon::notify_status("Client disconnected, handling orphaned pipelines");
+        }
+        ownership::handle_orphans(pipeline_manager, orphan_policy).await;
+
         tracing::info!("Server shutting down");
+        if cli_config.daemon {
+            daemon::notify_stopping();
+        }
     }
 
     Ok(())
@@ -0,0 +1,333 @@
+use gstreamer as gst;
+use gstreamer_pbutils as gst_pbutils;
+use gstreamer_pbutils::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::channels::layout_name;
+use crate::color::{color_info, ColorInfo};
+use crate::discovery::ensure_gstreamer_initialized;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::suggestions::parse_launch;
+
+/// How long inspecting a single file may take
+const PROBE_TIMEOUT_SECONDS: u64 = 10;
+
+/// Caps fields that identify a codec variant beyond the media type
+const CODEC_FIELDS: &[&str] = &["mpegversion", "layer"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VideoStream {
+    /// Media type of the stream, e.g. `video/x-h264`
+    pub codec: String,
+    pub caps: String,
+    pub width: u32,
+    pub height: u32,
+    /// Frame rate as `num/den`
+    pub framerate: String,
+    pub interlaced: bool,
+    /// Colorimetry and HDR metadata, when the caps carry them
+    #[serde(default)]
+    pub color: Option<ColorInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioStream {
+    /// Media type of the stream, e.g. `audio/mpeg, mpegversion=4`
+    pub codec: String,
+    pub caps: String,
+    pub sample_rate: u32,
+    pub channels: u32,
+    /// Channel positions as a `GstAudioChannelPosition` bitmask; 0 when
+    /// the channels are unpositioned
+    #[serde(default)]
+    pub channel_mask: u64,
+    /// Layout name such as `stereo` or `5.1`
+    #[serde(default)]
+    pub layout: String,
+}
+
+/// Streams and duration of a media file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaInfo {
+    pub path: PathBuf,
+    /// Media type of the container, e.g. `video/quicktime`
+    pub container: Option<String>,
+    pub duration_ns: Option<u64>,
+    pub seekable: bool,
+    pub video: Vec<VideoStream>,
+    pub audio: Vec<AudioStream>,
+}
+
+/// Container and encoders used when a tool writes a file of a given type
+#[derive(Debug)]
+pub struct OutputFormat {
+    pub extensions: &'static [&'static str],
+    pub muxer: &'static str,
+    /// Candidate video encoders, in order of preference
+    pub video_encoders: &'static [&'static str],
+    /// Candidate audio encoders, in order of preference
+    pub audio_encoders: &'static [&'static str],
+}
+
+const H264_ENCODERS: &[&str] = &["x264enc", "openh264enc", "vah264enc", "nvh264enc"];
+const AAC_ENCODERS: &[&str] = &["fdkaacenc", "avenc_aac", "voaacenc", "faac"];
+const OPEN_AUDIO_ENCODERS: &[&str] = &["opusenc", "vorbisenc"];
+
+pub const OUTPUT_FORMATS: &[OutputFormat] = &[
+    OutputFormat {
+        extensions: &["mp4", "m4v"],
+        muxer: "mp4mux",
+        video_encoders: H264_ENCODERS,
+        audio_encoders: AAC_ENCODERS,
+    },
+    OutputFormat {
+        extensions: &["m4a"],
+        muxer: "mp4mux",
+        video_encoders: &[],
+        audio_encoders: AAC_ENCODERS,
+    },
+    OutputFormat {
+        extensions: &["mov"],
+        muxer: "qtmux",
+        video_encoders: H264_ENCODERS,
+        audio_encoders: AAC_ENCODERS,
+    },
+    OutputFormat {
+        extensions: &["mkv", "mka"],
+        muxer: "matroskamux",
+        video_encoders: H264_ENCODERS,
+        audio_encoders: OPEN_AUDIO_ENCODERS,
+    },
+    OutputFormat {
+        extensions: &["webm"],
+        muxer: "webmmux",
+        video_encoders: &["vp8enc", "vp9enc"],
+        audio_encoders: OPEN_AUDIO_ENCODERS,
+    },
+    OutputFormat {
+        extensions: &["ts"],
+        muxer: "mpegtsmux",
+        video_encoders: H264_ENCODERS,
+        audio_encoders: AAC_ENCODERS,
+    },
+    OutputFormat {
+        extensions: &["ogg", "oga", "opus"],
+        muxer: "oggmux",
+        video_encoders: &[],
+        audio_encoders: OPEN_AUDIO_ENCODERS,
+    },
+];
+
+/// Output format for a file name, chosen by its extension
+pub fn output_format(path: &Path) -> McpResult<&'static OutputFormat> {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    OUTPUT_FORMATS
+        .iter()
+        .find(|format| format.extensions.contains(&extension.as_str()))
+        .ok_or_else(|| {
+            let supported: Vec<&str> = OUTPUT_FORMATS
+                .iter()
+                .flat_map(|f| f.extensions.iter().copied())
+                .collect();
+            GStreamerMcpError::Other(format!(
+                "Unsupported output type '{}'. Supported extensions: {}",
+                path.display(),
+                supported.join(", ")
+            ))
+        })
+}
+
+/// First of `candidates` that is installed
+pub fn first_available(candidates: &[&'static str]) -> Option<&'static str> {
+    candidates
+        .iter()
+        .copied()
+        .find(|name| gst::ElementFactory::find(name).is_some())
+}
+
+/// Like `first_available`, but fails naming the candidates
+pub fn require_element(candidates: &[&'static str], purpose: &str) -> McpResult<&'static str> {
+    first_available(candidates).ok_or_else(|| {
+        GStreamerMcpError::ElementNotFound(format!(
+            "No {} is installed (tried {})",
+            purpose,
+            candidates.join(", ")
+        ))
+    })
+}
+
+/// Quote a string for use as a property value in gst-launch syntax
+pub fn quote_value(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Quote a path for use as a property value in gst-launch syntax
+pub fn quote_location(path: &Path) -> String {
+    quote_value(&path.to_string_lossy())
+}
+
+/// Fail unless `path` names an existing regular file
+pub fn check_input_file(path: &str) -> McpResult<PathBuf> {
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(GStreamerMcpError::Other(format!(
+            "Input file '{}' does not exist",
+            path.display()
+        )));
+    }
+    Ok(path)
+}
+
+/// Inspect a media file with the GStreamer discoverer. Blocks until the
+/// file has been analyzed.
+pub fn probe_media(path: &Path) -> McpResult<MediaInfo> {
+    ensure_gstreamer_initialized()?;
+
+    let absolute = path.canonicalize()?;
+    let uri = gst::glib::filename_to_uri(&absolute, None)
+        .map_err(|e| GStreamerMcpError::Other(format!("Invalid path {}: {}", path.display(), e)))?;
+    let discoverer =
+        gst_pbutils::Discoverer::new(gst::ClockTime::from_seconds(PROBE_TIMEOUT_SECONDS))
+            .map_err(|e| GStreamerMcpError::Other(format!("Failed to create discoverer: {}", e)))?;
+    let info = discoverer.discover_uri(&uri).map_err(|e| {
+        GStreamerMcpError::PipelineError(format!("Failed to inspect {}: {}", path.display(), e))
+    })?;
+
+    let container = info
+        .container_streams()
+        .first()
+        .and_then(|c| c.caps())
+        .and_then(|caps| caps.structure(0).map(|s| s.name().to_string()));
+
+    let video = info
+        .video_streams()
+        .iter()
+        .filter(|v| !v.is_image())
+        .map(|v| {
+            let caps = v.caps();
+            let framerate = v.framerate();
+            VideoStream {
+                codec: caps.as_ref().map(codec_name).unwrap_or_default(),
+                caps: caps.as_ref().map(|c| c.to_string()).unwrap_or_default(),
+                width: v.width(),
+                height: v.height(),
+                framerate: format!("{}/{}", framerate.numer(), framerate.denom()),
+                interlaced: v.is_interlaced(),
+                color: caps.as_ref().and_then(|c| color_info(c)),
+            }
+        })
+        .collect();
+
+    let audio = info
+        .audio_streams()
+        .iter()
+        .map(|a| {
+            let caps = a.caps();
+            AudioStream {
+                codec: caps.as_ref().map(codec_name).unwrap_or_default(),
+                caps: caps.map(|c| c.to_string()).unwrap_or_default(),
+                sample_rate: a.sample_rate(),
+                channels: a.channels(),
+                channel_mask: a.channel_mask(),
+                layout: layout_name(a.channels(), a.channel_mask()),
+            }
+        })
+        .collect();
+
+    Ok(MediaInfo {
+        path: path.to_path_buf(),
+        container,
+        duration_ns: info.duration().map(|d| d.nseconds()),
+        seekable: info.is_seekable(),
+        video,
+        audio,
+    })
+}
+
+/// Media type of the caps plus the fields telling codec variants apart
+fn codec_name(caps: &gst::Caps) -> String {
+    let Some(structure) = caps.structure(0) else {
+        return String::new();
+    };
+    let mut name = structure.name().to_string();
+    for field in CODEC_FIELDS {
+        if let Ok(value) = structure.get::<i32>(*field) {
+            name.push_str(&format!(", {}={}", field, value));
+        }
+    }
+    name
+}
+
+/// Whether a muxer has a sink pad template accepting `caps`
+pub fn muxer_accepts(muxer: &str, caps: &str) -> bool {
+    let (Some(factory), Ok(caps)) = (gst::ElementFactory::find(muxer), caps.parse::<gst::Caps>())
+    else {
+        return false;
+    };
+    factory
+        .static_pad_templates()
+        .iter()
+        .filter(|template| template.direction() == gst::PadDirection::Sink)
+        .any(|template| template.caps().can_intersect(&caps))
+}
+
+/// Media type alone (`audio/mpeg` for `audio/mpeg, mpegversion=4`), usable
+/// as a caps filter in gst-launch syntax
+pub fn media_type(codec: &str) -> &str {
+    codec.split(',').next().unwrap_or(codec).trim()
+}
+
+/// Run a pipeline description as fast as possible until EOS, passing the
+/// structure of every element message to `on_element_message`. Blocks until
+/// the pipeline finishes and fails on the first error.
+pub fn run_to_completion(
+    description: &str,
+    mut on_element_message: impl FnMut(&gst::StructureRef),
+) -> McpResult<()> {
+    let pipeline = parse_launch(description, "Failed to build pipeline")?;
+    let bus = pipeline
+        .bus()
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Pipeline has no bus".to_string()))?;
+
+    pipeline.set_state(gst::State::Playing).map_err(|e| {
+        GStreamerMcpError::PipelineError(format!("Failed to start pipeline: {:?}", e))
+    })?;
+
+    let result = loop {
+        let Some(msg) = bus.timed_pop_filtered(
+            gst::ClockTime::NONE,
+            &[
+                gst::MessageType::Eos,
+                gst::MessageType::Error,
+                gst::MessageType::Element,
+            ],
+        ) else {
+            break Ok(());
+        };
+        match msg.view() {
+            gst::MessageView::Eos(_) => break Ok(()),
+            gst::MessageView::Error(err) => {
+                break Err(GStreamerMcpError::PipelineError(format!(
+                    "{} (from {})",
+                    err.error(),
+                    msg.src()
+                        .map(|s| s.path_string().to_string())
+                        .unwrap_or_default()
+                )));
+            }
+            gst::MessageView::Element(element) => {
+                if let Some(structure) = element.structure() {
+                    on_element_message(structure);
+                }
+            }
+            _ => {}
+        }
+    };
+
+    let _ = pipeline.set_state(gst::State::Null);
+    result
+}
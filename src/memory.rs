@@ -0,0 +1,125 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+/// What a queue the server inserts does when it is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueLeak {
+    /// Block upstream until there is room, as GStreamer queues do
+    #[default]
+    No,
+    /// Drop the incoming buffers
+    Upstream,
+    /// Drop the oldest queued buffers
+    Downstream,
+}
+
+impl QueueLeak {
+    /// The queue element in gst-launch syntax
+    pub fn queue(&self) -> &'static str {
+        match self {
+            QueueLeak::No => "queue",
+            QueueLeak::Upstream => "queue leaky=upstream",
+            QueueLeak::Downstream => "queue leaky=downstream",
+        }
+    }
+}
+
+impl std::str::FromStr for QueueLeak {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "no" | "none" => Ok(QueueLeak::No),
+            "upstream" => Ok(QueueLeak::Upstream),
+            "downstream" => Ok(QueueLeak::Downstream),
+            _ => Err(format!(
+                "Invalid queue leak '{}'. Must be one of: no, upstream, downstream",
+                s
+            )),
+        }
+    }
+}
+
+/// Memory guardrails of a pipeline. Unset values use the server
+/// configuration.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct MemoryLimits {
+    #[schemars(
+        description = "Most buffers an appsink keeps for a slow consumer; older ones are dropped (0: unlimited)"
+    )]
+    pub max_appsink_buffers: Option<u32>,
+    #[schemars(
+        description = "What queues inserted by the server do when full: 'no' (block), 'upstream' (drop new buffers) or 'downstream' (drop old buffers)"
+    )]
+    pub queue_leak: Option<QueueLeak>,
+}
+
+impl MemoryLimits {
+    /// These limits, with unset values taken from `defaults`
+    pub fn or(&self, defaults: &MemoryLimits) -> MemoryLimits {
+        MemoryLimits {
+            max_appsink_buffers: self.max_appsink_buffers.or(defaults.max_appsink_buffers),
+            queue_leak: self.queue_leak.or(defaults.queue_leak),
+        }
+    }
+
+    pub fn max_appsink_buffers(&self) -> u32 {
+        self.max_appsink_buffers.unwrap_or(0)
+    }
+
+    pub fn queue_leak(&self) -> QueueLeak {
+        self.queue_leak.unwrap_or_default()
+    }
+}
+
+/// Cap the queue of every appsink that may hold more than `max_buffers`,
+/// so a consumer that stops pulling loses the oldest buffers instead of
+/// growing the server's memory. Returns the appsinks changed.
+pub fn cap_appsinks(pipeline: &gst::Pipeline, max_buffers: u32) -> Vec<String> {
+    if max_buffers == 0 {
+        return Vec::new();
+    }
+    pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.factory().is_some_and(|f| f.name() == "appsink"))
+        .filter(|sink| {
+            let current = sink.property::<u32>("max-buffers");
+            current == 0 || current > max_buffers
+        })
+        .map(|sink| {
+            sink.set_property("max-buffers", max_buffers);
+            sink.set_property("drop", true);
+            sink.name().to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_limits() {
+        let defaults = MemoryLimits {
+            max_appsink_buffers: Some(100),
+            queue_leak: Some(QueueLeak::Downstream),
+        };
+        let limits = MemoryLimits {
+            max_appsink_buffers: Some(5),
+            queue_leak: None,
+        }
+        .or(&defaults);
+        assert_eq!(limits.max_appsink_buffers(), 5);
+        assert_eq!(limits.queue_leak().queue(), "queue leaky=downstream");
+
+        assert_eq!(MemoryLimits::default().max_appsink_buffers(), 0);
+        assert_eq!(MemoryLimits::default().queue_leak(), QueueLeak::No);
+        assert_eq!("Upstream".parse::<QueueLeak>(), Ok(QueueLeak::Upstream));
+        assert!("sideways".parse::<QueueLeak>().is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::pipeline::BusMessage;
+
+/// Severity of a bus message, ordered from least to most important
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageSeverity {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl MessageSeverity {
+    /// Message type recorded for messages mapped to this severity
+    pub fn message_type(&self) -> &'static str {
+        match self {
+            MessageSeverity::Debug => "Debug",
+            MessageSeverity::Info => "Info",
+            MessageSeverity::Warning => "Warning",
+            MessageSeverity::Error => "Error",
+        }
+    }
+
+    /// Default severity of a bus message type as recorded in [`BusMessage::message_type`]
+    pub fn for_message_type(message_type: &str) -> Self {
+        match message_type {
+            "Error" => MessageSeverity::Error,
+            "Warning" => MessageSeverity::Warning,
+            "Eos" | "StateChanged" | "Buffering" | "Element" | "Application" | "Info" => {
+                MessageSeverity::Info
+            }
+            _ => MessageSeverity::Debug,
+        }
+    }
+}
+
+/// Per-subscriber filter deciding which bus messages are delivered
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessageFilter {
+    /// Message types to keep (e.g. "Error", "Eos"); `None` keeps all types
+    pub types: Option<Vec<String>>,
+    /// Minimum severity to keep
+    pub min_severity: Option<MessageSeverity>,
+    /// Glob matched against the source element name, or against the full
+    /// object path when the pattern contains '/'
+    pub source: Option<String>,
+}
+
+impl MessageFilter {
+    pub fn matches(&self, message: &BusMessage) -> bool {
+        if let Some(types) = &self.types {
+            if !types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&message.message_type))
+            {
+                return false;
+            }
+        }
+
+        if let Some(min) = self.min_severity {
+            if MessageSeverity::for_message_type(&message.message_type) < min {
+                return false;
+            }
+        }
+
+        if let Some(pattern) = &self.source {
+            if !source_matches(pattern, message.source.as_deref()) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Configured rule that drops known-noisy bus messages, or records them at
+/// another severity, before they are counted towards health
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageRule {
+    /// Glob matched against the source element name, or against the full
+    /// object path when the pattern contains '/'
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Message type to match, e.g. "Warning"
+    #[serde(default)]
+    pub message_type: Option<String>,
+    /// Text the message must contain, ignoring case
+    #[serde(default)]
+    pub contains: Option<String>,
+    /// Severity to record matching messages at; unset drops them
+    #[serde(default)]
+    pub severity: Option<MessageSeverity>,
+}
+
+impl MessageRule {
+    /// Rules must match on something, so one cannot silence every message
+    pub fn check(&self) -> Result<(), String> {
+        if self.source.is_none() && self.message_type.is_none() && self.contains.is_none() {
+            return Err(
+                "A message rule needs at least one of source, message_type or contains".to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, message: &BusMessage) -> bool {
+        self.message_type
+            .as_ref()
+            .is_none_or(|t| t.eq_ignore_ascii_case(&message.message_type))
+            && self
+                .source
+                .as_ref()
+                .is_none_or(|pattern| source_matches(pattern, message.source.as_deref()))
+            && self.contains.as_ref().is_none_or(|text| {
+                message
+                    .message
+                    .to_lowercase()
+                    .contains(&text.to_lowercase())
+            })
+    }
+}
+
+/// Apply the first of `rules` matching `message`. Returns None when the
+/// message is dropped.
+pub fn apply_message_rules(rules: &[MessageRule], mut message: BusMessage) -> Option<BusMessage> {
+    let Some(rule) = rules.iter().find(|rule| rule.matches(&message)) else {
+        return Some(message);
+    };
+    let severity = rule.severity?;
+    message.message_type = severity.message_type().to_string();
+    Some(message)
+}
+
+/// Whether the object path `source` matches `pattern`, a glob matched
+/// against the element name, or against the full path when it contains '/'
+fn source_matches(pattern: &str, source: Option<&str>) -> bool {
+    let Some(path) = source else {
+        return false;
+    };
+    let subject = if pattern.contains('/') {
+        path
+    } else {
+        source_element_name(path)
+    };
+    glob_match(pattern, subject)
+}
+
+/// Extract the element name from an object path such as
+/// `/GstPipeline:pipeline0/GstVideoTestSrc:videotestsrc0`
+pub fn source_element_name(path: &str) -> &str {
+    let last = path.rsplit('/').next().unwrap_or(path);
+    last.rsplit(':').next().unwrap_or(last)
+}
+
+/// Minimal glob matching supporting `*` and `?`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(message_type: &str, source: Option<&str>) -> BusMessage {
+        BusMessage {
+            timestamp: chrono::Utc::now(),
+            message_type: message_type.to_string(),
+            message: String::new(),
+            source: source.map(str::to_string),
+            cause: None,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("v4l2src*", "v4l2src0"));
+        assert!(glob_match("*sink?", "autovideosink0"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("udpsrc*", "videotestsrc0"));
+        assert!(!glob_match("src?", "src"));
+    }
+
+    #[test]
+    fn test_filter_by_severity_and_source() {
+        let filter = MessageFilter {
+            types: None,
+            min_severity: Some(MessageSeverity::Warning),
+            source: Some("v4l2src*".to_string()),
+        };
+
+        let path = "/GstPipeline:pipeline0/GstV4l2Src:v4l2src0";
+        assert!(filter.matches(&message("Error", Some(path))));
+        assert!(filter.matches(&message("Warning", Some(path))));
+        assert!(!filter.matches(&message("StreamStatus", Some(path))));
+        assert!(!filter.matches(&message("Error", Some("/GstPipeline:pipeline0"))));
+        assert!(!filter.matches(&message("Error", None)));
+    }
+
+    #[test]
+    fn test_message_rules() {
+        let rules = vec![
+            MessageRule {
+                source: Some("v4l2src*".to_string()),
+                message_type: Some("Warning".to_string()),
+                contains: Some("timestamp".to_string()),
+                severity: None,
+            },
+            MessageRule {
+                source: Some("v4l2src*".to_string()),
+                severity: Some(MessageSeverity::Info),
+                ..Default::default()
+            },
+        ];
+        let path = "/GstPipeline:pipeline0/GstV4l2Src:v4l2src0";
+        let mut noisy = message("Warning", Some(path));
+        noisy.message = "Warning: Timestamps are unreliable".to_string();
+        assert!(apply_message_rules(&rules, noisy).is_none());
+
+        // Other warnings of the element are kept at the mapped severity
+        let mapped = apply_message_rules(&rules, message("Warning", Some(path))).unwrap();
+        assert_eq!(mapped.message_type, "Info");
+        let other = message("Warning", Some("/GstPipeline:pipeline0/GstQueue:queue0"));
+        assert_eq!(
+            apply_message_rules(&rules, other).unwrap().message_type,
+            "Warning"
+        );
+
+        assert!(MessageRule::default().check().is_err());
+        assert!(rules[1].check().is_ok());
+    }
+
+    #[test]
+    fn test_filter_by_type() {
+        let filter = MessageFilter {
+            types: Some(vec!["eos".to_string(), "Error".to_string()]),
+            ..Default::default()
+        };
+        assert!(filter.matches(&message("Eos", None)));
+        assert!(filter.matches(&message("Error", None)));
+        assert!(!filter.matches(&message("Tag", None)));
+    }
+}
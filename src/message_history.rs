@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::message_filter::MessageFilter;
+use crate::pipeline::BusMessage;
+use crate::pipeline_log::{LogRecord, PipelineLog};
+
+/// Bus messages kept per pipeline; the oldest are dropped first
+pub const HISTORY_CAPACITY: usize = 1000;
+
+/// A recorded bus message with its sequence number, which keeps growing
+/// over the life of the pipeline and serves as pagination cursor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub message: BusMessage,
+}
+
+/// Ring buffer of the bus messages of a pipeline
+#[derive(Debug, Clone)]
+pub struct MessageHistory {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+    next_seq: u64,
+    /// File every message is also appended to, if pipeline logs are on
+    log: Option<PipelineLog>,
+}
+
+impl Default for MessageHistory {
+    fn default() -> Self {
+        Self::new(HISTORY_CAPACITY)
+    }
+}
+
+/// Which recorded messages to return, and from where
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    /// Types, minimum severity and source of the messages
+    pub filter: MessageFilter,
+    /// Only messages at or after this time
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only messages before this time
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only messages after this sequence number, as returned in
+    /// `next_cursor`; unset starts at the oldest message kept
+    pub cursor: Option<u64>,
+    pub limit: usize,
+}
+
+impl HistoryQuery {
+    fn matches(&self, entry: &HistoryEntry) -> bool {
+        self.cursor.is_none_or(|cursor| entry.seq > cursor)
+            && self
+                .since
+                .is_none_or(|since| entry.message.timestamp >= since)
+            && self
+                .until
+                .is_none_or(|until| entry.message.timestamp < until)
+            && self.filter.matches(&entry.message)
+    }
+}
+
+/// One page of recorded messages, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub messages: Vec<HistoryEntry>,
+    /// Cursor for the next page; once `has_more` is false, polling with it
+    /// returns the messages posted since
+    pub next_cursor: u64,
+    /// Whether more matching messages are kept past this page
+    pub has_more: bool,
+    /// Messages after the cursor that were dropped from the buffer before
+    /// they could be read
+    pub dropped: u64,
+    /// Messages currently kept
+    pub retained: usize,
+}
+
+impl MessageHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+            next_seq: 1,
+            log: None,
+        }
+    }
+
+    /// Also append every message to `log`
+    pub fn with_log(mut self, log: Option<PipelineLog>) -> Self {
+        self.log = log;
+        self
+    }
+
+    pub fn log(&self) -> Option<&PipelineLog> {
+        self.log.as_ref()
+    }
+
+    /// Keep a message, dropping the oldest when full. Returns its sequence
+    /// number.
+    pub fn push(&mut self, message: BusMessage) -> u64 {
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let entry = HistoryEntry { seq, message };
+        if let Some(log) = &self.log {
+            log.append(&LogRecord::Message(Box::new(entry.clone())));
+        }
+        self.entries.push_back(entry);
+        seq
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Messages kept, oldest first
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &BusMessage> {
+        self.entries.iter().map(|entry| &entry.message)
+    }
+
+    /// The last `limit` messages, oldest first
+    pub fn latest(&self, limit: usize) -> Vec<BusMessage> {
+        let start = self.entries.len().saturating_sub(limit);
+        self.iter().skip(start).cloned().collect()
+    }
+
+    /// Page through the messages matching `query`
+    pub fn query(&self, query: &HistoryQuery) -> HistoryPage {
+        let oldest = self
+            .entries
+            .front()
+            .map_or(self.next_seq, |entry| entry.seq);
+        let dropped = query
+            .cursor
+            .map_or(0, |cursor| oldest.saturating_sub(cursor + 1));
+
+        let mut matching = self.entries.iter().filter(|entry| query.matches(entry));
+        let messages: Vec<HistoryEntry> = matching.by_ref().take(query.limit).cloned().collect();
+        let has_more = matching.next().is_some();
+        let next_cursor = match messages.last() {
+            Some(last) if has_more => last.seq,
+            _ => self.next_seq - 1,
+        };
+        HistoryPage {
+            messages,
+            next_cursor: next_cursor.max(query.cursor.unwrap_or(0)),
+            has_more,
+            dropped,
+            retained: self.entries.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(message_type: &str, source: &str) -> BusMessage {
+        BusMessage {
+            timestamp: chrono::Utc::now(),
+            message_type: message_type.to_string(),
+            message: format!("{} from {}", message_type, source),
+            source: Some(format!("/GstPipeline:pipeline0/GstElement:{}", source)),
+            cause: None,
+            details: None,
+        }
+    }
+
+    #[test]
+    fn test_message_history() {
+        let mut history = MessageHistory::new(4);
+        for i in 0..6 {
+            let kind = if i % 2 == 0 {
+                "Warning"
+            } else {
+                "StateChanged"
+            };
+            history.push(message(kind, &format!("src{}", i)));
+        }
+        // Sequence numbers 3 to 6 are kept
+        assert_eq!(history.len(), 4);
+        assert_eq!(history.latest(1)[0].message, "StateChanged from src5");
+
+        let mut query = HistoryQuery {
+            limit: 1,
+            ..Default::default()
+        };
+        let page = history.query(&query);
+        assert_eq!(page.messages[0].seq, 3);
+        assert!(page.has_more);
+        assert_eq!(page.next_cursor, 3);
+
+        query.cursor = Some(page.next_cursor);
+        query.limit = 10;
+        query.filter.types = Some(vec!["warning".to_string()]);
+        let page = history.query(&query);
+        assert_eq!(page.messages.len(), 1);
+        assert_eq!(page.messages[0].seq, 5);
+        assert!(!page.has_more);
+        assert_eq!(page.next_cursor, 6);
+
+        // A cursor behind the buffer reports what was missed
+        query.cursor = Some(1);
+        assert_eq!(history.query(&query).dropped, 1);
+        query.cursor = Some(6);
+        let page = history.query(&query);
+        assert!(page.messages.is_empty());
+        assert_eq!(page.next_cursor, 6);
+    }
+}
@@ -0,0 +1,354 @@
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::message_filter::{glob_match, source_element_name};
+use crate::pipeline::BusMessage;
+use crate::properties::format_value;
+
+/// Message types that can be waited for
+pub const MESSAGE_TYPES: &[&str] = &[
+    "eos",
+    "error",
+    "warning",
+    "info",
+    "element",
+    "application",
+    "state_changed",
+    "async_done",
+    "stream_start",
+    "buffering",
+    "tag",
+    "latency",
+    "duration_changed",
+    "segment_done",
+    "qos",
+    "clock_lost",
+];
+
+/// Waited for when no type is given
+pub const DEFAULT_MESSAGE_TYPES: [&str; 2] = ["eos", "error"];
+
+/// Name of the type of a bus message, as in [`MESSAGE_TYPES`]
+pub fn message_type_name(message: &gst::MessageRef) -> &'static str {
+    match message.view() {
+        gst::MessageView::Eos(_) => "eos",
+        gst::MessageView::Error(_) => "error",
+        gst::MessageView::Warning(_) => "warning",
+        gst::MessageView::Info(_) => "info",
+        gst::MessageView::Element(_) => "element",
+        gst::MessageView::Application(_) => "application",
+        gst::MessageView::StateChanged(_) => "state_changed",
+        gst::MessageView::AsyncDone(_) => "async_done",
+        gst::MessageView::StreamStart(_) => "stream_start",
+        gst::MessageView::Buffering(_) => "buffering",
+        gst::MessageView::Tag(_) => "tag",
+        gst::MessageView::Latency(_) => "latency",
+        gst::MessageView::DurationChanged(_) => "duration_changed",
+        gst::MessageView::SegmentDone(_) => "segment_done",
+        gst::MessageView::Qos(_) => "qos",
+        gst::MessageView::ClockLost(_) => "clock_lost",
+        _ => "other",
+    }
+}
+
+/// Type recorded in [`BusMessage::message_type`] for a type name, e.g.
+/// `StateChanged` for `state_changed`
+pub fn recorded_type(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// The bus message a wait is for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageQuery {
+    /// Any of these types, from [`MESSAGE_TYPES`]
+    pub types: Vec<String>,
+    /// Glob matched against the structure name of element and application
+    /// messages, e.g. `level` or `GstMultiFileSink`
+    pub structure_name: Option<String>,
+    /// Glob matched against the source element name, or against the full
+    /// object path when the pattern contains '/'. Without it, state changes
+    /// only match those of the pipeline itself.
+    pub source: Option<String>,
+}
+
+impl MessageQuery {
+    /// Check the types, lowercased; without any, wait for EOS or an error
+    pub fn new(
+        types: Vec<String>,
+        structure_name: Option<String>,
+        source: Option<String>,
+    ) -> McpResult<Self> {
+        let mut types: Vec<String> = types.iter().map(|t| t.trim().to_lowercase()).collect();
+        if let Some(unknown) = types.iter().find(|t| !MESSAGE_TYPES.contains(&t.as_str())) {
+            return Err(GStreamerMcpError::PipelineError(format!(
+                "Unknown message type '{}'. Must be one of: {}",
+                unknown,
+                MESSAGE_TYPES.join(", ")
+            )));
+        }
+        if types.is_empty() {
+            types = DEFAULT_MESSAGE_TYPES
+                .iter()
+                .map(|t| t.to_string())
+                .collect();
+        }
+        Ok(Self {
+            types,
+            structure_name,
+            source,
+        })
+    }
+
+    /// Whether a message of type `message_type` from the object at `source`
+    /// carrying a structure named `structure_name` is the one waited for
+    pub fn matches(
+        &self,
+        message_type: &str,
+        source: Option<&str>,
+        structure_name: Option<&str>,
+    ) -> bool {
+        if !self.types.iter().any(|t| t == message_type) {
+            return false;
+        }
+        if let Some(pattern) = &self.structure_name {
+            let carries_name = matches!(message_type, "element" | "application");
+            if carries_name && !structure_name.is_some_and(|name| glob_match(pattern, name)) {
+                return false;
+            }
+        }
+        match (&self.source, source) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(pattern), Some(path)) if pattern.contains('/') => glob_match(pattern, path),
+            (Some(pattern), Some(path)) => glob_match(pattern, source_element_name(path)),
+        }
+    }
+
+    /// Whether a recorded message matches. Recorded messages carry no
+    /// structure, so none matches once a structure name is asked for.
+    pub fn matches_recorded(&self, message: &BusMessage) -> bool {
+        self.structure_name.is_none()
+            && self.types.iter().any(|t| {
+                recorded_type(t) == message.message_type
+                    && self.matches(t, message.source.as_deref(), None)
+            })
+    }
+}
+
+/// A bus message a wait ended with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitedMessage {
+    pub message_type: String,
+    /// Object path of the element that posted it
+    pub source: Option<String>,
+    /// Readable summary: the error text, the state change, ...
+    pub text: Option<String>,
+    /// Name of the structure the message carries, if any
+    pub structure_name: Option<String>,
+    /// Fields of the structure; numbers and booleans stay JSON values,
+    /// nested structures become objects and anything else its string form
+    pub fields: serde_json::Map<String, serde_json::Value>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Taken from the messages recorded since the last launch, seek or
+    /// state change, having been posted before the wait started
+    pub recorded: bool,
+}
+
+impl WaitedMessage {
+    fn from_message(message: &gst::MessageRef) -> Self {
+        let text = match message.view() {
+            gst::MessageView::Error(err) => Some(with_debug(err.error().to_string(), err.debug())),
+            gst::MessageView::Warning(w) => Some(with_debug(w.error().to_string(), w.debug())),
+            gst::MessageView::Info(i) => Some(with_debug(i.error().to_string(), i.debug())),
+            gst::MessageView::StateChanged(s) => {
+                Some(format!("{:?} -> {:?}", s.old(), s.current()))
+            }
+            gst::MessageView::Buffering(b) => Some(format!("{}%", b.percent())),
+            gst::MessageView::Eos(_) => Some("End of stream".to_string()),
+            _ => None,
+        };
+        // Errors carry their GError in the structure, already in `text`
+        let structure = message.structure().filter(|_| {
+            !matches!(
+                message.view(),
+                gst::MessageView::Error(_)
+                    | gst::MessageView::Warning(_)
+                    | gst::MessageView::Info(_)
+            )
+        });
+        Self {
+            message_type: message_type_name(message).to_string(),
+            source: message.src().map(|s| s.path_string().to_string()),
+            text,
+            structure_name: structure.map(|s| s.name().to_string()),
+            fields: structure.map(structure_to_json).unwrap_or_default(),
+            timestamp: chrono::Utc::now(),
+            recorded: false,
+        }
+    }
+
+    fn from_recorded(message: &BusMessage) -> Self {
+        Self {
+            message_type: message.message_type.clone(),
+            source: message.source.clone(),
+            text: Some(message.message.clone()),
+            structure_name: None,
+            fields: serde_json::Map::new(),
+            timestamp: message.timestamp,
+            recorded: true,
+        }
+    }
+}
+
+fn with_debug(text: String, debug: Option<glib::GString>) -> String {
+    match debug {
+        Some(debug) => format!("{} ({})", text, debug),
+        None => text,
+    }
+}
+
+/// Fields of a structure as JSON
+pub fn structure_to_json(
+    structure: &gst::StructureRef,
+) -> serde_json::Map<String, serde_json::Value> {
+    structure
+        .iter()
+        .map(|(name, value)| (name.to_string(), value_to_json(value)))
+        .collect()
+}
+
+fn value_to_json(value: &glib::Value) -> serde_json::Value {
+    use serde_json::Value;
+    if let Ok(b) = value.get::<bool>() {
+        Value::from(b)
+    } else if let Ok(i) = value.get::<i32>() {
+        Value::from(i)
+    } else if let Ok(u) = value.get::<u32>() {
+        Value::from(u)
+    } else if let Ok(i) = value.get::<i64>() {
+        Value::from(i)
+    } else if let Ok(u) = value.get::<u64>() {
+        Value::from(u)
+    } else if let Ok(f) = value.get::<f64>() {
+        Value::from(f)
+    } else if let Ok(f) = value.get::<f32>() {
+        Value::from(f)
+    } else if let Ok(s) = value.get::<String>() {
+        Value::from(s)
+    } else if let Ok(s) = value.get::<gst::Structure>() {
+        Value::Object(structure_to_json(&s))
+    } else if let Ok(list) = value.get::<gst::List>() {
+        Value::Array(list.iter().map(|v| value_to_json(v)).collect())
+    } else if let Ok(array) = value.get::<gst::Array>() {
+        Value::Array(array.iter().map(|v| value_to_json(v)).collect())
+    } else {
+        Value::from(format_value(value))
+    }
+}
+
+/// Outcome of waiting for a bus message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageWait {
+    pub pipeline_id: String,
+    pub query: MessageQuery,
+    pub matched: bool,
+    pub elapsed_ms: u64,
+    /// The matching message, if one came
+    pub message: Option<WaitedMessage>,
+}
+
+/// Wait up to `timeout` for the pipeline to post a message matching
+/// `query`, looking first at `recorded`, the messages recorded since the
+/// last launch, seek or state change. Messages are seen as they are posted
+/// and left on the bus for the other consumers.
+pub fn wait_for_message(
+    pipeline: &gst::Pipeline,
+    query: &MessageQuery,
+    recorded: &[BusMessage],
+    timeout: Duration,
+) -> McpResult<Option<WaitedMessage>> {
+    if let Some(message) = recorded.iter().find(|m| query.matches_recorded(m)) {
+        return Ok(Some(WaitedMessage::from_recorded(message)));
+    }
+
+    let bus = pipeline
+        .bus()
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Pipeline has no bus".to_string()))?;
+    let started = Instant::now();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let own_state_only = query.source.is_none();
+    let pipeline_object = pipeline.clone().upcast::<gst::Object>();
+    let matcher = query.clone();
+    bus.enable_sync_message_emission();
+    let handler = bus.connect_sync_message(None, move |_, msg| {
+        let message_type = message_type_name(msg);
+        if own_state_only && message_type == "state_changed" && msg.src() != Some(&pipeline_object)
+        {
+            return;
+        }
+        let source = msg.src().map(|s| s.path_string());
+        let structure_name = msg.structure().map(|s| s.name().to_string());
+        if matcher.matches(message_type, source.as_deref(), structure_name.as_deref()) {
+            let _ = tx.send(WaitedMessage::from_message(msg));
+        }
+    });
+
+    let received = rx
+        .recv_timeout(timeout.saturating_sub(started.elapsed()))
+        .ok();
+    bus.disconnect(handler);
+    bus.disable_sync_message_emission();
+    Ok(received)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_query() {
+        assert_eq!(recorded_type("state_changed"), "StateChanged");
+        assert_eq!(recorded_type("eos"), "Eos");
+
+        let query = MessageQuery::new(Vec::new(), None, None).unwrap();
+        assert_eq!(query.types, vec!["eos", "error"]);
+        assert!(MessageQuery::new(vec!["eof".to_string()], None, None).is_err());
+
+        let query = MessageQuery::new(
+            vec!["Element".to_string()],
+            Some("level".to_string()),
+            Some("lev*".to_string()),
+        )
+        .unwrap();
+        let path = "/GstPipeline:pipeline0/GstLevel:level0";
+        assert!(query.matches("element", Some(path), Some("level")));
+        assert!(!query.matches("element", Some(path), Some("GstMultiFileSink")));
+        assert!(!query.matches("element", None, Some("level")));
+        assert!(!query.matches("eos", Some(path), None));
+
+        let recorded = BusMessage {
+            timestamp: chrono::Utc::now(),
+            message_type: "StateChanged".to_string(),
+            message: "State changed from Paused to Playing".to_string(),
+            source: None,
+            cause: None,
+            details: None,
+        };
+        let query = MessageQuery::new(vec!["state_changed".to_string()], None, None).unwrap();
+        assert!(query.matches_recorded(&recorded));
+        let query = MessageQuery::new(vec!["element".to_string()], None, None).unwrap();
+        assert!(!query.matches_recorded(&recorded));
+    }
+}
@@ -0,0 +1,244 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_video::{VideoRegionOfInterestMeta, VideoTimeCodeMeta};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::probes::find_pad;
+
+/// Caps of streams carrying KLV (SMPTE 336M) packets as buffer payload
+const KLV_CAPS: &str = "meta/x-klv";
+
+/// Bytes of a KLV value included in events
+const KLV_VALUE_PREVIEW: usize = 32;
+
+/// Metadata found on a buffer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FrameMeta {
+    RegionOfInterest {
+        label: String,
+        id: i32,
+        parent_id: i32,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        /// Parameter structures in GStreamer serialization
+        params: Vec<String>,
+    },
+    Timecode {
+        timecode: String,
+    },
+    ReferenceTimestamp {
+        reference: String,
+        timestamp_ns: u64,
+        duration_ns: Option<u64>,
+    },
+    Klv {
+        /// Universal label of the item, in hex
+        key: String,
+        length: usize,
+        /// Start of the value, in hex
+        value: String,
+    },
+    /// Any other meta, identified by its API type
+    Other {
+        api: String,
+        tags: Vec<String>,
+    },
+}
+
+/// Metadata of one buffer passing the tapped pad
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataEvent {
+    pub pad: String,
+    pub pts_ns: Option<u64>,
+    pub metas: Vec<FrameMeta>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetadataTap {
+    pub pad: String,
+    pub window_ms: u64,
+    pub buffers: u64,
+    /// Events in arrival order, at most `max_events` of them
+    pub events: Vec<MetadataEvent>,
+    /// Whether buffers with metadata were dropped after `max_events`
+    pub truncated: bool,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Split a KLV payload into its items. A truncated trailing item ends
+/// parsing.
+pub fn parse_klv(mut data: &[u8]) -> Vec<FrameMeta> {
+    let mut items = Vec::new();
+    while data.len() > 16 {
+        let (key, rest) = data.split_at(16);
+        // BER length: short form below 0x80, else the count of length bytes
+        let (length, header) = match rest[0] {
+            short if short < 0x80 => (short as usize, 1),
+            long => {
+                let count = (long & 0x7f) as usize;
+                if count == 0 || count > 8 || rest.len() < 1 + count {
+                    break;
+                }
+                let length = rest[1..=count]
+                    .iter()
+                    .fold(0usize, |acc, b| (acc << 8) | *b as usize);
+                (length, 1 + count)
+            }
+        };
+        let Some(value) = rest.get(header..header + length) else {
+            break;
+        };
+        items.push(FrameMeta::Klv {
+            key: hex(key),
+            length,
+            value: hex(&value[..length.min(KLV_VALUE_PREVIEW)]),
+        });
+        data = &rest[header + length..];
+    }
+    items
+}
+
+/// Decode the metas attached to `buffer`, and its KLV payload when `klv`
+pub fn buffer_metas(buffer: &gst::BufferRef, klv: bool) -> Vec<FrameMeta> {
+    let mut metas: Vec<FrameMeta> = buffer
+        .iter_meta::<gst::Meta>()
+        .map(|meta| {
+            if let Some(roi) = meta.downcast_ref::<VideoRegionOfInterestMeta>() {
+                let (x, y, width, height) = roi.rect();
+                FrameMeta::RegionOfInterest {
+                    label: roi.roi_type().to_string(),
+                    id: roi.id(),
+                    parent_id: roi.parent_id(),
+                    x,
+                    y,
+                    width,
+                    height,
+                    params: roi.params().map(|p| p.to_string()).collect(),
+                }
+            } else if let Some(tc) = meta.downcast_ref::<VideoTimeCodeMeta>() {
+                FrameMeta::Timecode {
+                    timecode: tc.tc().to_string(),
+                }
+            } else if let Some(reference) = meta.downcast_ref::<gst::ReferenceTimestampMeta>() {
+                FrameMeta::ReferenceTimestamp {
+                    reference: reference.reference().to_string(),
+                    timestamp_ns: reference.timestamp().nseconds(),
+                    duration_ns: reference.duration().map(|d| d.nseconds()),
+                }
+            } else {
+                FrameMeta::Other {
+                    api: meta.api().name().to_string(),
+                    tags: meta.tags().iter().map(|t| t.to_string()).collect(),
+                }
+            }
+        })
+        .collect();
+    if klv {
+        if let Ok(map) = buffer.map_readable() {
+            metas.extend(parse_klv(map.as_slice()));
+        }
+    }
+    metas
+}
+
+/// Install a temporary buffer probe on `pad_spec` and collect the metadata
+/// of buffers passing it over `window`. Each event is also passed to
+/// `on_event` as it happens, up to `max_events`.
+pub async fn tap_metadata(
+    pipeline: &gst::Pipeline,
+    pad_spec: &str,
+    window: Duration,
+    max_events: usize,
+    on_event: impl Fn(&MetadataEvent) + Send + Sync + 'static,
+) -> McpResult<MetadataTap> {
+    let pad = find_pad(pipeline, pad_spec)?;
+    let klv = pad
+        .current_caps()
+        .and_then(|caps| caps.structure(0).map(|s| s.name() == KLV_CAPS))
+        .unwrap_or(false);
+
+    let tap = Arc::new(Mutex::new(MetadataTap {
+        pad: pad_spec.to_string(),
+        window_ms: window.as_millis() as u64,
+        buffers: 0,
+        events: Vec::new(),
+        truncated: false,
+    }));
+    let probe_id = {
+        let tap = tap.clone();
+        pad.add_probe(gst::PadProbeType::BUFFER, move |_pad, info| {
+            let Some(buffer) = info.buffer() else {
+                return gst::PadProbeReturn::Ok;
+            };
+            let metas = buffer_metas(buffer, klv);
+            let mut tap = tap.lock();
+            tap.buffers += 1;
+            if metas.is_empty() {
+                return gst::PadProbeReturn::Ok;
+            }
+            if tap.events.len() >= max_events {
+                tap.truncated = true;
+                return gst::PadProbeReturn::Ok;
+            }
+            let event = MetadataEvent {
+                pad: tap.pad.clone(),
+                pts_ns: buffer.pts().map(|t| t.nseconds()),
+                metas,
+            };
+            on_event(&event);
+            tap.events.push(event);
+            gst::PadProbeReturn::Ok
+        })
+    }
+    .ok_or_else(|| {
+        GStreamerMcpError::PipelineError(format!("Failed to install probe on '{}'", pad_spec))
+    })?;
+
+    tokio::time::sleep(window).await;
+    pad.remove_probe(probe_id);
+
+    let tap = tap.lock().clone();
+    Ok(tap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_klv() {
+        let key = [
+            0x06, 0x0e, 0x2b, 0x34, 2, 0x0b, 1, 1, 0x0e, 1, 3, 1, 1, 0, 0, 0,
+        ];
+        let mut data = key.to_vec();
+        data.extend([0x03, 0xaa, 0xbb, 0xcc]);
+        // Long form length, 0x81 0x02
+        data.extend(key);
+        data.extend([0x81, 0x02, 0x01, 0x02]);
+        // Truncated item
+        data.extend(key);
+        data.extend([0x10, 0x00]);
+
+        let items = parse_klv(&data);
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0],
+            FrameMeta::Klv {
+                key: "060e2b34020b01010e01030101000000".to_string(),
+                length: 3,
+                value: "aabbcc".to_string(),
+            }
+        );
+        assert!(matches!(&items[1], FrameMeta::Klv { length: 2, value, .. } if value == "0102"));
+    }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Aggregated execution statistics for a single tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolStats {
+    pub tool: String,
+    pub calls: u64,
+    pub successes: u64,
+    pub failures: u64,
+    /// Calls that took longer than the slow-call threshold
+    pub slow_calls: u64,
+    pub total_ms: u64,
+    pub average_ms: u64,
+    pub max_ms: u64,
+    pub last_ms: u64,
+}
+
+impl ToolStats {
+    fn record(&mut self, elapsed_ms: u64, success: bool, slow: bool) {
+        self.calls += 1;
+        if success {
+            self.successes += 1;
+        } else {
+            self.failures += 1;
+        }
+        if slow {
+            self.slow_calls += 1;
+        }
+        self.total_ms += elapsed_ms;
+        self.average_ms = self.total_ms / self.calls;
+        self.max_ms = self.max_ms.max(elapsed_ms);
+        self.last_ms = elapsed_ms;
+    }
+}
+
+/// Per-tool timing and success/failure counters collected by the server
+#[derive(Debug, Clone, Default)]
+pub struct ToolMetrics {
+    stats: Arc<RwLock<HashMap<String, ToolStats>>>,
+    slow_call_threshold: Duration,
+}
+
+impl ToolMetrics {
+    pub fn new(slow_call_threshold: Duration) -> Self {
+        Self {
+            stats: Arc::default(),
+            slow_call_threshold,
+        }
+    }
+
+    pub fn slow_call_threshold(&self) -> Duration {
+        self.slow_call_threshold
+    }
+
+    /// Record a finished tool call, logging a warning if it was slow
+    pub async fn record(&self, tool: &str, elapsed: Duration, success: bool) {
+        let slow = !self.slow_call_threshold.is_zero() && elapsed > self.slow_call_threshold;
+        if slow {
+            tracing::warn!(
+                "Slow tool call: '{}' took {:?} (threshold {:?})",
+                tool,
+                elapsed,
+                self.slow_call_threshold
+            );
+        }
+
+        let mut stats = self.stats.write().await;
+        stats
+            .entry(tool.to_string())
+            .or_insert_with(|| ToolStats {
+                tool: tool.to_string(),
+                ..Default::default()
+            })
+            .record(elapsed.as_millis() as u64, success, slow);
+    }
+
+    /// Statistics of every tool called so far, most expensive first
+    pub async fn snapshot(&self) -> Vec<ToolStats> {
+        let mut stats: Vec<ToolStats> = self.stats.read().await.values().cloned().collect();
+        stats.sort_by(|a, b| b.total_ms.cmp(&a.total_ms).then(a.tool.cmp(&b.tool)));
+        stats
+    }
+
+    pub async fn reset(&self) {
+        self.stats.write().await.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_record_and_snapshot() {
+        let metrics = ToolMetrics::new(Duration::from_millis(100));
+        metrics
+            .record("gst_list_elements", Duration::from_millis(40), true)
+            .await;
+        metrics
+            .record("gst_list_elements", Duration::from_millis(200), false)
+            .await;
+        metrics
+            .record("gst_health", Duration::from_millis(1), true)
+            .await;
+
+        let stats = metrics.snapshot().await;
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].tool, "gst_list_elements");
+        assert_eq!(stats[0].calls, 2);
+        assert_eq!(stats[0].failures, 1);
+        assert_eq!(stats[0].slow_calls, 1);
+        assert_eq!(stats[0].average_ms, 120);
+        assert_eq!(stats[0].max_ms, 200);
+
+        metrics.reset().await;
+        assert!(metrics.snapshot().await.is_empty());
+    }
+}
@@ -0,0 +1,543 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// How long a modification waits for a pad to go idle or an element to
+/// drain
+pub const MODIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A change to the elements of a pipeline, which may be playing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum PipelineModification {
+    /// Add an element from a gst-launch description. When `after` and
+    /// `before` are linked to each other it is spliced between them,
+    /// otherwise it is linked to whichever of them is given.
+    AddElement {
+        description: String,
+        after: Option<String>,
+        before: Option<String>,
+    },
+    /// Remove an element. With `relink` its upstream and downstream
+    /// neighbours are linked to each other in its place.
+    RemoveElement { name: String, relink: bool },
+    /// Link a src pad to a sink pad, moving the src pad off its current peer
+    LinkElements { src: String, sink: String },
+}
+
+/// What a modification changed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModificationResult {
+    pub pipeline_id: String,
+    pub operation: String,
+    /// Element added or removed
+    pub element: Option<String>,
+    /// Links made, as `element.pad -> element.pad`
+    pub linked: Vec<String>,
+    /// Links broken
+    pub unlinked: Vec<String>,
+}
+
+impl ModificationResult {
+    fn new(operation: &str, element: Option<String>) -> Self {
+        Self {
+            operation: operation.to_string(),
+            element,
+            ..Default::default()
+        }
+    }
+
+    /// One line description, such as `add_element scale: linked a.src -> scale.sink`
+    pub fn summary(&self) -> String {
+        let mut summary = self.operation.clone();
+        if let Some(element) = &self.element {
+            summary.push_str(&format!(" {}", element));
+        }
+        let mut changes = Vec::new();
+        if !self.linked.is_empty() {
+            changes.push(format!("linked {}", self.linked.join(", ")));
+        }
+        if !self.unlinked.is_empty() {
+            changes.push(format!("unlinked {}", self.unlinked.join(", ")));
+        }
+        if !changes.is_empty() {
+            summary.push_str(&format!(": {}", changes.join("; ")));
+        }
+        summary
+    }
+}
+
+/// Split `element.pad` into the element and pad names. A bare element name
+/// leaves the pad to be chosen.
+pub fn split_pad_spec(spec: &str) -> (&str, Option<&str>) {
+    match spec.rsplit_once('.') {
+        Some((element, pad)) if !element.is_empty() && !pad.is_empty() => (element, Some(pad)),
+        _ => (spec, None),
+    }
+}
+
+fn modify_error(e: impl std::fmt::Display) -> GStreamerMcpError {
+    GStreamerMcpError::PipelineError(format!("Pipeline modification failed: {}", e))
+}
+
+fn pad_label(pad: &gst::Pad) -> String {
+    let element = pad
+        .parent_element()
+        .map(|e| e.name().to_string())
+        .unwrap_or_default();
+    format!("{}.{}", element, pad.name())
+}
+
+fn link_label(src: &gst::Pad, sink: &gst::Pad) -> String {
+    format!("{} -> {}", pad_label(src), pad_label(sink))
+}
+
+fn element_by_name(pipeline: &gst::Pipeline, name: &str) -> McpResult<gst::Element> {
+    pipeline.by_name(name).ok_or_else(|| {
+        GStreamerMcpError::ElementNotFound(format!("No element named '{}' in pipeline", name))
+    })
+}
+
+fn is_request_pad(pad: &gst::Pad) -> bool {
+    pad.pad_template()
+        .is_some_and(|t| t.presence() == gst::PadPresence::Request)
+}
+
+/// Give back a request pad that ended up without a peer
+fn release_if_unused(pad: &gst::Pad) {
+    if is_request_pad(pad) && !pad.is_linked() {
+        if let Some(element) = pad.parent_element() {
+            element.release_request_pad(pad);
+        }
+    }
+}
+
+/// Pad `name` of `element`, requested from a template if it does not exist
+/// yet. Without a name: the `src` or `sink` pad, else an unlinked pad of
+/// that direction, else a newly requested one.
+fn resolve_pad(
+    element: &gst::Element,
+    name: Option<&str>,
+    direction: gst::PadDirection,
+) -> McpResult<gst::Pad> {
+    if let Some(name) = name {
+        return element
+            .static_pad(name)
+            .or_else(|| element.pads().into_iter().find(|p| p.name() == name))
+            .or_else(|| element.request_pad_simple(name))
+            .ok_or_else(|| {
+                let available: Vec<String> = element
+                    .pads()
+                    .iter()
+                    .map(|p| p.name().to_string())
+                    .collect();
+                GStreamerMcpError::PipelineError(format!(
+                    "Pad '{}' not found on element '{}'. Available pads: {}",
+                    name,
+                    element.name(),
+                    available.join(", ")
+                ))
+            });
+    }
+    let default = if direction == gst::PadDirection::Src {
+        "src"
+    } else {
+        "sink"
+    };
+    element
+        .static_pad(default)
+        .or_else(|| {
+            element
+                .pads()
+                .into_iter()
+                .find(|p| p.direction() == direction && !p.is_linked())
+        })
+        .or_else(|| {
+            element
+                .pad_template_list()
+                .into_iter()
+                .find(|t| t.direction() == direction && t.presence() == gst::PadPresence::Request)
+                .and_then(|t| element.request_pad(&t, None, None))
+        })
+        .ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!(
+                "'{}' has no free {} pad; name one as {}.<pad>",
+                element.name(),
+                default,
+                element.name()
+            ))
+        })
+}
+
+/// Block `pad` as soon as no data is passing through it. It stays blocked
+/// until the returned probe is removed.
+fn block_idle(pad: &gst::Pad, timeout: Duration) -> McpResult<gst::PadProbeId> {
+    let (tx, rx) = mpsc::channel();
+    let probe = pad
+        .add_probe(gst::PadProbeType::IDLE, move |_, _| {
+            let _ = tx.send(());
+            gst::PadProbeReturn::Ok
+        })
+        .ok_or_else(|| modify_error(format!("cannot block {}", pad_label(pad))))?;
+    if rx.recv_timeout(timeout).is_err() {
+        pad.remove_probe(probe);
+        return Err(modify_error(format!(
+            "{} stayed busy for {} ms; a paused sink may be holding the stream, set the pipeline to PLAYING first",
+            pad_label(pad),
+            timeout.as_millis()
+        )));
+    }
+    Ok(probe)
+}
+
+/// Push EOS into `sink` and wait until it leaves through `src`, where it is
+/// dropped, so nothing is left queued in the element between them. Returns
+/// false when the EOS did not come out in time.
+fn drain(sink: &gst::Pad, src: &gst::Pad, timeout: Duration) -> bool {
+    let (tx, rx) = mpsc::channel();
+    let probe = src.add_probe(
+        gst::PadProbeType::EVENT_DOWNSTREAM,
+        move |_, info| match info.event() {
+            Some(event) if event.type_() == gst::EventType::Eos => {
+                let _ = tx.send(());
+                gst::PadProbeReturn::Drop
+            }
+            _ => gst::PadProbeReturn::Pass,
+        },
+    );
+    sink.send_event(gst::event::Eos::new());
+    let drained = rx.recv_timeout(timeout).is_ok();
+    if let Some(probe) = probe {
+        src.remove_probe(probe);
+    }
+    drained
+}
+
+/// Link `src` to `sink`. A linked `src` is moved over while blocked, and
+/// goes back to its old peer if `sink` refuses it.
+fn relink(
+    src: &gst::Pad,
+    sink: &gst::Pad,
+    timeout: Duration,
+    result: &mut ModificationResult,
+) -> McpResult<()> {
+    if let Some(peer) = sink.peer() {
+        return Err(modify_error(format!(
+            "{} is already linked to {}",
+            pad_label(sink),
+            pad_label(&peer)
+        )));
+    }
+    let Some(old) = src.peer() else {
+        src.link(sink)
+            .map_err(|e| modify_error(format!("cannot link {}: {}", link_label(src, sink), e)))?;
+        result.linked.push(link_label(src, sink));
+        return Ok(());
+    };
+
+    let probe = block_idle(src, timeout)?;
+    let _ = src.unlink(&old);
+    let linked = src.link(sink);
+    if linked.is_err() {
+        let _ = src.link(&old);
+    }
+    src.remove_probe(probe);
+    linked.map_err(|e| modify_error(format!("cannot link {}: {}", link_label(src, sink), e)))?;
+    result.unlinked.push(link_label(src, &old));
+    result.linked.push(link_label(src, sink));
+    Ok(())
+}
+
+/// An element, or a bin with ghost pads for a chain of elements
+fn parse_element(description: &str) -> McpResult<gst::Element> {
+    let element = gst::parse::launch(description)
+        .map_err(|e| modify_error(format!("invalid element description: {}", e)))?;
+    if element.is::<gst::Pipeline>() {
+        return gst::parse::bin_from_description(description, true)
+            .map(|bin| bin.upcast())
+            .map_err(|e| modify_error(format!("invalid element description: {}", e)));
+    }
+    Ok(element)
+}
+
+/// Element and optional pad name of a pad specification
+fn neighbour<'a>(
+    pipeline: &gst::Pipeline,
+    spec: Option<&'a str>,
+) -> McpResult<Option<(gst::Element, Option<&'a str>)>> {
+    spec.map(|spec| {
+        let (element_name, pad) = split_pad_spec(spec);
+        element_by_name(pipeline, element_name).map(|e| (e, pad))
+    })
+    .transpose()
+}
+
+fn add_element(
+    pipeline: &gst::Pipeline,
+    description: &str,
+    after: Option<&str>,
+    before: Option<&str>,
+    timeout: Duration,
+) -> McpResult<ModificationResult> {
+    let element = parse_element(description)?;
+    let name = element.name().to_string();
+    if pipeline.by_name(&name).is_some() {
+        return Err(modify_error(format!(
+            "the pipeline already has an element named '{}'; set name= in the description",
+            name
+        )));
+    }
+    let upstream = neighbour(pipeline, after)?;
+    let downstream = neighbour(pipeline, before)?;
+
+    pipeline.add(&element).map_err(modify_error)?;
+    let mut result = ModificationResult::new("add_element", Some(name.clone()));
+    let mut pads = Vec::new();
+    let linked = (|| -> McpResult<()> {
+        let src = upstream
+            .as_ref()
+            .map(|(e, pad)| resolve_pad(e, *pad, gst::PadDirection::Src))
+            .transpose()?;
+        let sink = downstream
+            .as_ref()
+            .map(|(e, pad)| resolve_pad(e, *pad, gst::PadDirection::Sink))
+            .transpose()?;
+        pads.extend(src.iter().chain(sink.iter()).cloned());
+        let new_sink = src
+            .as_ref()
+            .map(|_| resolve_pad(&element, None, gst::PadDirection::Sink))
+            .transpose()?;
+        let new_src = sink
+            .as_ref()
+            .map(|_| resolve_pad(&element, None, gst::PadDirection::Src))
+            .transpose()?;
+        // Up to the pipeline's state before any data reaches it
+        element.sync_state_with_parent().map_err(modify_error)?;
+
+        match (src, sink, new_sink, new_src) {
+            (Some(src), Some(sink), Some(new_sink), Some(new_src))
+                if src.peer().as_ref() == Some(&sink) =>
+            {
+                let probe = block_idle(&src, timeout)?;
+                let _ = src.unlink(&sink);
+                let spliced = new_src.link(&sink).and_then(|_| src.link(&new_sink));
+                if spliced.is_err() {
+                    let _ = new_src.unlink(&sink);
+                    let _ = src.link(&sink);
+                }
+                src.remove_probe(probe);
+                spliced.map_err(|e| {
+                    modify_error(format!(
+                        "'{}' does not fit between {} and {}: {}",
+                        name,
+                        pad_label(&src),
+                        pad_label(&sink),
+                        e
+                    ))
+                })?;
+                result.unlinked.push(link_label(&src, &sink));
+                result.linked.push(link_label(&src, &new_sink));
+                result.linked.push(link_label(&new_src, &sink));
+            }
+            (src, sink, new_sink, new_src) => {
+                // Downstream first, so data never reaches an unlinked pad
+                if let (Some(new_src), Some(sink)) = (new_src, sink) {
+                    relink(&new_src, &sink, timeout, &mut result)?;
+                }
+                if let (Some(src), Some(new_sink)) = (src, new_sink) {
+                    relink(&src, &new_sink, timeout, &mut result)?;
+                }
+            }
+        }
+        Ok(())
+    })();
+    if let Err(e) = linked {
+        for pad in &pads {
+            release_if_unused(pad);
+        }
+        let _ = element.set_state(gst::State::Null);
+        let _ = pipeline.remove(&element);
+        return Err(e);
+    }
+    Ok(result)
+}
+
+fn remove_element(
+    pipeline: &gst::Pipeline,
+    name: &str,
+    relink: bool,
+    timeout: Duration,
+) -> McpResult<ModificationResult> {
+    let element = element_by_name(pipeline, name)?;
+    let parent = element
+        .parent()
+        .and_then(|p| p.downcast::<gst::Bin>().ok())
+        .ok_or_else(|| modify_error(format!("'{}' is not in a bin", name)))?;
+    let upstream: Vec<(gst::Pad, gst::Pad)> = element
+        .sink_pads()
+        .into_iter()
+        .filter_map(|pad| pad.peer().map(|peer| (peer, pad)))
+        .collect();
+    let downstream: Vec<(gst::Pad, gst::Pad)> = element
+        .src_pads()
+        .into_iter()
+        .filter_map(|pad| pad.peer().map(|peer| (pad, peer)))
+        .collect();
+    let mut result = ModificationResult::new("remove_element", Some(name.to_string()));
+
+    if relink {
+        let ([(up_src, sink)], [(src, down_sink)]) = (upstream.as_slice(), downstream.as_slice())
+        else {
+            return Err(modify_error(format!(
+                "'{}' has {} linked inputs and {} linked outputs; relinking needs one of each",
+                name,
+                upstream.len(),
+                downstream.len()
+            )));
+        };
+        if !up_src.can_link(down_sink) {
+            return Err(modify_error(format!(
+                "{} cannot link to {}",
+                pad_label(up_src),
+                pad_label(down_sink)
+            )));
+        }
+        let probe = block_idle(up_src, timeout)?;
+        if !drain(sink, src, timeout) {
+            tracing::warn!("'{}' did not drain before removal", name);
+        }
+        let _ = up_src.unlink(sink);
+        let _ = src.unlink(down_sink);
+        let linked = up_src.link(down_sink);
+        if linked.is_err() {
+            let _ = up_src.link(sink);
+            let _ = src.link(down_sink);
+        }
+        up_src.remove_probe(probe);
+        linked.map_err(|e| {
+            modify_error(format!(
+                "cannot link {}: {}",
+                link_label(up_src, down_sink),
+                e
+            ))
+        })?;
+        result.unlinked.push(link_label(up_src, sink));
+        result.unlinked.push(link_label(src, down_sink));
+        result.linked.push(link_label(up_src, down_sink));
+    } else {
+        // A static pad left without a peer fails the stream feeding it
+        if let Some((up_src, _)) = upstream.iter().find(|(up_src, _)| !is_request_pad(up_src)) {
+            return Err(modify_error(format!(
+                "removing '{}' would leave {} unlinked; relink its neighbours or remove the elements upstream",
+                name,
+                pad_label(up_src)
+            )));
+        }
+        for (up_src, sink) in &upstream {
+            let probe = block_idle(up_src, timeout)?;
+            let _ = up_src.unlink(sink);
+            up_src.remove_probe(probe);
+            result.unlinked.push(link_label(up_src, sink));
+            release_if_unused(up_src);
+        }
+    }
+
+    // Stopped before its outputs are unlinked, so its own threads push nothing
+    let _ = element.set_state(gst::State::Null);
+    for (src, down_sink) in &downstream {
+        if src.unlink(down_sink).is_ok() {
+            result.unlinked.push(link_label(src, down_sink));
+            release_if_unused(down_sink);
+        }
+    }
+    parent.remove(&element).map_err(modify_error)?;
+    Ok(result)
+}
+
+fn link_elements(
+    pipeline: &gst::Pipeline,
+    src_spec: &str,
+    sink_spec: &str,
+    timeout: Duration,
+) -> McpResult<ModificationResult> {
+    let (src_name, src_pad) = split_pad_spec(src_spec);
+    let (sink_name, sink_pad) = split_pad_spec(sink_spec);
+    let src_element = element_by_name(pipeline, src_name)?;
+    let sink_element = element_by_name(pipeline, sink_name)?;
+    let src = resolve_pad(&src_element, src_pad, gst::PadDirection::Src)?;
+    let sink = match resolve_pad(&sink_element, sink_pad, gst::PadDirection::Sink) {
+        Ok(sink) => sink,
+        Err(e) => {
+            release_if_unused(&src);
+            return Err(e);
+        }
+    };
+
+    let mut result = ModificationResult::new("link_elements", None);
+    // Ready for data before it is linked, then its source may start
+    let linked = sink_element
+        .sync_state_with_parent()
+        .map_err(modify_error)
+        .and_then(|_| relink(&src, &sink, timeout, &mut result));
+    if let Err(e) = linked {
+        release_if_unused(&src);
+        release_if_unused(&sink);
+        return Err(e);
+    }
+    let _ = src_element.sync_state_with_parent();
+    Ok(result)
+}
+
+/// Apply a modification to a pipeline in any state. Links that carry data
+/// are changed while the pad feeding them is blocked, so a playing pipeline
+/// keeps running; waits for idle pads and drains give up after `timeout`.
+pub fn apply_modification(
+    pipeline: &gst::Pipeline,
+    modification: &PipelineModification,
+    timeout: Duration,
+) -> McpResult<ModificationResult> {
+    match modification {
+        PipelineModification::AddElement {
+            description,
+            after,
+            before,
+        } => add_element(
+            pipeline,
+            description,
+            after.as_deref(),
+            before.as_deref(),
+            timeout,
+        ),
+        PipelineModification::RemoveElement { name, relink } => {
+            remove_element(pipeline, name, *relink, timeout)
+        }
+        PipelineModification::LinkElements { src, sink } => {
+            link_elements(pipeline, src, sink, timeout)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_pad_spec() {
+        assert_eq!(split_pad_spec("tee.src_0"), ("tee", Some("src_0")));
+        assert_eq!(split_pad_spec("scale"), ("scale", None));
+        assert_eq!(split_pad_spec("scale."), ("scale.", None));
+
+        let mut result = ModificationResult::new("add_element", Some("scale".to_string()));
+        assert_eq!(result.summary(), "add_element scale");
+        result.linked.push("src.src -> scale.sink".to_string());
+        result.unlinked.push("src.src -> sink.sink".to_string());
+        assert_eq!(
+            result.summary(),
+            "add_element scale: linked src.src -> scale.sink; unlinked src.src -> sink.sink"
+        );
+    }
+}
@@ -0,0 +1,212 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::discovery::ensure_gstreamer_initialized;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::quote_value;
+
+/// Device provider of the NDI plugin (gst-plugins-rs)
+const NDI_DEVICE_PROVIDER: &str = "ndideviceprovider";
+
+/// Elements the NDI tools depend on
+pub const NDI_ELEMENTS: &[&str] = &["ndisrc", "ndisink"];
+
+/// An NDI source announced on the network
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NdiSource {
+    /// Name to pass as `ndi-name`, e.g. `STUDIO-PC (OBS)`
+    pub name: String,
+    /// Address of the sender, usable as `url-address` to skip discovery
+    pub url_address: Option<String>,
+}
+
+/// Listen for NDI sources for `wait`. Sources announce themselves
+/// asynchronously, so a longer wait finds more of them on busy networks.
+pub fn list_ndi_sources(wait: Duration) -> McpResult<Vec<NdiSource>> {
+    ensure_gstreamer_initialized()?;
+
+    let provider = gst::DeviceProviderFactory::by_name(NDI_DEVICE_PROVIDER).ok_or_else(|| {
+        GStreamerMcpError::ElementNotFound(format!(
+            "{} is not installed (NDI plugin from gst-plugins-rs)",
+            NDI_DEVICE_PROVIDER
+        ))
+    })?;
+    provider
+        .start()
+        .map_err(|e| GStreamerMcpError::Other(format!("Failed to start NDI discovery: {}", e)))?;
+    std::thread::sleep(wait);
+    let devices = provider.devices();
+    provider.stop();
+
+    let mut sources: Vec<NdiSource> = devices
+        .iter()
+        .map(|device| {
+            let properties = device.properties();
+            let field = |name: &str| properties.as_ref().and_then(|p| p.get::<String>(name).ok());
+            NdiSource {
+                name: field("ndi-name").unwrap_or_else(|| device.display_name().to_string()),
+                url_address: field("url-address"),
+            }
+        })
+        .collect();
+    sources.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(sources)
+}
+
+/// Whether a launched NDI pipeline receives or sends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum NdiDirection {
+    /// Play an NDI source into local sinks
+    Receive,
+    /// Publish local sources as an NDI source
+    Send,
+}
+
+/// Receive bandwidth requested from the sender
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum NdiBandwidth {
+    Highest,
+    Lowest,
+    AudioOnly,
+    MetadataOnly,
+}
+
+impl NdiBandwidth {
+    fn nick(self) -> &'static str {
+        match self {
+            NdiBandwidth::Highest => "highest",
+            NdiBandwidth::Lowest => "lowest",
+            NdiBandwidth::AudioOnly => "audio-only",
+            NdiBandwidth::MetadataOnly => "metadata-only",
+        }
+    }
+}
+
+/// What an NDI pipeline connects to on the local side
+#[derive(Debug, Clone, Default)]
+pub struct NdiEndpoints<'a> {
+    /// Video chain: sinks when receiving, a source when sending
+    pub video: Option<&'a str>,
+    /// Audio chain: sinks when receiving, a source when sending
+    pub audio: Option<&'a str>,
+    pub url_address: Option<&'a str>,
+    pub bandwidth: Option<NdiBandwidth>,
+}
+
+/// Video chain used when none is given
+pub fn default_video_chain(direction: NdiDirection) -> &'static str {
+    match direction {
+        NdiDirection::Receive => "videoconvert ! autovideosink",
+        NdiDirection::Send => "videotestsrc is-live=true",
+    }
+}
+
+/// gst-launch description receiving the NDI source `name` or publishing
+/// one under `name`. Receiving goes through ndisrcdemux; sending combines
+/// audio and video with ndisinkcombiner, which ndisink requires.
+pub fn ndi_description(
+    direction: NdiDirection,
+    name: &str,
+    endpoints: &NdiEndpoints,
+) -> McpResult<String> {
+    if endpoints.video.is_none() && endpoints.audio.is_none() {
+        return Err(GStreamerMcpError::Other(
+            "An NDI pipeline needs a video or audio chain".to_string(),
+        ));
+    }
+
+    let mut parts = Vec::new();
+    match direction {
+        NdiDirection::Receive => {
+            let mut src = format!("ndisrc ndi-name={}", quote_value(name));
+            if let Some(url) = endpoints.url_address {
+                src.push_str(&format!(" url-address={}", quote_value(url)));
+            }
+            if let Some(bandwidth) = endpoints.bandwidth {
+                src.push_str(&format!(" bandwidth={}", bandwidth.nick()));
+            }
+            parts.push(format!("{} ! ndisrcdemux name=demux", src));
+            if let Some(video) = endpoints.video {
+                parts.push(format!("demux.video ! queue ! {}", video));
+            }
+            if let Some(audio) = endpoints.audio {
+                parts.push(format!("demux.audio ! queue ! {}", audio));
+            }
+        }
+        NdiDirection::Send => {
+            if endpoints.url_address.is_some() || endpoints.bandwidth.is_some() {
+                return Err(GStreamerMcpError::Other(
+                    "url_address and bandwidth only apply when receiving".to_string(),
+                ));
+            }
+            let Some(video) = endpoints.video else {
+                return Err(GStreamerMcpError::Other(
+                    "Sending NDI needs a video source; ndisinkcombiner requires video".to_string(),
+                ));
+            };
+            parts.push(format!(
+                "ndisinkcombiner name=combiner ! ndisink ndi-name={}",
+                quote_value(name)
+            ));
+            parts.push(format!("{} ! videoconvert ! queue ! combiner.video", video));
+            if let Some(audio) = endpoints.audio {
+                parts.push(format!(
+                    "{} ! audioconvert ! audioresample ! queue ! combiner.audio",
+                    audio
+                ));
+            }
+        }
+    }
+
+    Ok(parts.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ndi_description() {
+        let receive = ndi_description(
+            NdiDirection::Receive,
+            "STUDIO (Camera 1)",
+            &NdiEndpoints {
+                video: Some(default_video_chain(NdiDirection::Receive)),
+                bandwidth: Some(NdiBandwidth::Lowest),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            receive,
+            "ndisrc ndi-name=\"STUDIO (Camera 1)\" bandwidth=lowest ! ndisrcdemux name=demux \
+             demux.video ! queue ! videoconvert ! autovideosink"
+        );
+
+        let send = ndi_description(
+            NdiDirection::Send,
+            "Test Pattern",
+            &NdiEndpoints {
+                video: Some("videotestsrc is-live=true"),
+                audio: Some("audiotestsrc is-live=true"),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(
+            send.starts_with("ndisinkcombiner name=combiner ! ndisink ndi-name=\"Test Pattern\"")
+        );
+        assert!(send.contains("audiotestsrc is-live=true ! audioconvert"));
+
+        let audio_only = NdiEndpoints {
+            audio: Some("audiotestsrc is-live=true"),
+            ..Default::default()
+        };
+        assert!(ndi_description(NdiDirection::Send, "x", &audio_only).is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+use gstreamer_net as gst_net;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::net::UdpSocket;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::discovery::ensure_gstreamer_initialized;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// Port the clock server listens on when none is given
+pub const DEFAULT_CLOCK_PORT: u16 = 8555;
+
+/// Pipeline latency used when none is given; every host renders this long
+/// after the shared base time, which absorbs network and decoding delays
+pub const DEFAULT_SYNC_LATENCY_MS: u64 = 500;
+
+/// A clock published on the network for other hosts to follow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockServerInfo {
+    pub address: Option<String>,
+    pub port: u16,
+    /// Current time of the published clock
+    pub clock_time_ns: u64,
+}
+
+/// How a pipeline is slaved to a shared clock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSettings {
+    /// Clock server to follow; the local system clock when absent, for
+    /// pipelines on the host running the server
+    pub clock_address: Option<String>,
+    pub clock_port: u16,
+    pub base_time_ns: u64,
+    pub latency_ms: u64,
+}
+
+/// Synchronization state of a pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub pipeline_id: String,
+    pub settings: SyncSettings,
+    /// Whether the network clock has locked to the server
+    pub synced: bool,
+    /// Rate of the local clock against the server's in parts per million;
+    /// positive when the local clock runs fast
+    pub drift_ppm: Option<f64>,
+    /// Current time of the shared clock
+    pub clock_time_ns: Option<u64>,
+    /// Time the pipeline is at, identical on every host when in sync
+    pub running_time_ns: Option<u64>,
+}
+
+/// Clock and timing a pipeline is asked to follow
+#[derive(Debug, Clone)]
+pub struct SyncRequest {
+    /// Clock server host; the local system clock when absent
+    pub clock_address: Option<String>,
+    pub clock_port: u16,
+    /// Shared base time; picked just ahead of the clock when absent
+    pub base_time: Option<gst::ClockTime>,
+    pub latency: Duration,
+}
+
+struct ClockServer {
+    // Serves the clock for as long as it is alive
+    _provider: gst_net::NetTimeProvider,
+    address: Option<String>,
+}
+
+struct SyncedPipeline {
+    pipeline: glib::WeakRef<gst::Pipeline>,
+    clock: gst::Clock,
+    settings: SyncSettings,
+}
+
+/// Drift in ppm from a clock's calibration rate
+pub fn drift_ppm(rate_num: u64, rate_denom: u64) -> Option<f64> {
+    (rate_num > 0 && rate_denom > 0).then(|| (rate_num as f64 / rate_denom as f64 - 1.0) * 1e6)
+}
+
+/// Base time `delay` after the clock's current time, rounded up to a whole
+/// second so it is easy to pass to other hosts
+pub fn future_base_time(clock_time: gst::ClockTime, delay: Duration) -> gst::ClockTime {
+    let target = clock_time.nseconds() + delay.as_nanos() as u64;
+    gst::ClockTime::from_nseconds(target.div_ceil(1_000_000_000) * 1_000_000_000)
+}
+
+/// Clock servers and pipelines slaved to shared clocks, for multi-room
+/// audio and video walls
+#[derive(Clone, Default)]
+pub struct NetSync {
+    servers: Arc<Mutex<HashMap<u16, ClockServer>>>,
+    pipelines: Arc<Mutex<HashMap<String, SyncedPipeline>>>,
+}
+
+impl NetSync {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish the system clock on `port`. Starting a server on a port
+    /// already served returns the running one.
+    pub fn start_clock_server(
+        &self,
+        address: Option<&str>,
+        port: u16,
+    ) -> McpResult<ClockServerInfo> {
+        ensure_gstreamer_initialized()?;
+        let clock = gst::SystemClock::obtain();
+        let mut servers = self.servers.lock();
+        if let Entry::Vacant(entry) = servers.entry(port) {
+            // The provider cannot report bind failures; check the port first
+            let bind = format!("{}:{}", address.unwrap_or("0.0.0.0"), port);
+            UdpSocket::bind(&bind).map_err(|e| {
+                GStreamerMcpError::Other(format!("Cannot serve the clock on {}: {}", bind, e))
+            })?;
+            entry.insert(ClockServer {
+                _provider: gst_net::NetTimeProvider::new(&clock, address, port as i32),
+                address: address.map(str::to_string),
+            });
+        }
+        Ok(ClockServerInfo {
+            address: servers[&port].address.clone(),
+            port,
+            clock_time_ns: clock.time().nseconds(),
+        })
+    }
+
+    /// Slave `pipeline` to a shared clock so it renders in lockstep with
+    /// every other pipeline using the same clock, base time and latency.
+    /// Waits up to `sync_timeout` for a network clock to lock.
+    pub fn sync_pipeline(
+        &self,
+        pipeline_id: &str,
+        pipeline: &gst::Pipeline,
+        request: &SyncRequest,
+        sync_timeout: Duration,
+    ) -> McpResult<SyncReport> {
+        let clock_address = request.clock_address.as_deref();
+        let clock_port = request.clock_port;
+        let latency = request.latency;
+        let clock: gst::Clock = match clock_address {
+            Some(address) => {
+                let clock = gst_net::NetClientClock::new(
+                    Some(&format!("netclock-{}", pipeline_id)),
+                    address,
+                    clock_port as i32,
+                    gst::ClockTime::ZERO,
+                );
+                clock.upcast()
+            }
+            None => gst::SystemClock::obtain(),
+        };
+        if clock
+            .wait_for_sync(gst::ClockTime::from_nseconds(sync_timeout.as_nanos() as u64))
+            .is_err()
+        {
+            return Err(GStreamerMcpError::Other(format!(
+                "Clock {}:{} did not sync within {} ms; is a clock server running there?",
+                clock_address.unwrap_or("localhost"),
+                clock_port,
+                sync_timeout.as_millis()
+            )));
+        }
+
+        let base_time = request
+            .base_time
+            .unwrap_or_else(|| future_base_time(clock.time(), latency + Duration::from_secs(1)));
+        pipeline.use_clock(Some(&clock));
+        // A fixed base time instead of one picked when going to PLAYING
+        pipeline.set_start_time(gst::ClockTime::NONE);
+        pipeline.set_base_time(base_time);
+        pipeline.set_latency(gst::ClockTime::from_nseconds(latency.as_nanos() as u64));
+
+        let settings = SyncSettings {
+            clock_address: clock_address.map(str::to_string),
+            clock_port,
+            base_time_ns: base_time.nseconds(),
+            latency_ms: latency.as_millis() as u64,
+        };
+        self.pipelines.lock().insert(
+            pipeline_id.to_string(),
+            SyncedPipeline {
+                pipeline: pipeline.downgrade(),
+                clock,
+                settings,
+            },
+        );
+        self.report(pipeline_id)
+    }
+
+    fn report_for(&self, pipeline_id: &str, synced: &SyncedPipeline) -> SyncReport {
+        let (_, _, rate_num, rate_denom) = synced.clock.calibration();
+        let clock_time = synced.clock.time();
+        let base_time = gst::ClockTime::from_nseconds(synced.settings.base_time_ns);
+        SyncReport {
+            pipeline_id: pipeline_id.to_string(),
+            settings: synced.settings.clone(),
+            synced: synced.clock.is_synced(),
+            drift_ppm: drift_ppm(rate_num, rate_denom),
+            clock_time_ns: Some(clock_time.nseconds()),
+            running_time_ns: synced
+                .pipeline
+                .upgrade()
+                .and_then(|_| clock_time.checked_sub(base_time))
+                .map(|t| t.nseconds()),
+        }
+    }
+
+    /// Synchronization state of a synced pipeline
+    pub fn report(&self, pipeline_id: &str) -> McpResult<SyncReport> {
+        let pipelines = self.pipelines.lock();
+        let synced = pipelines.get(pipeline_id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' is not synchronized to a shared clock",
+                pipeline_id
+            ))
+        })?;
+        Ok(self.report_for(pipeline_id, synced))
+    }
+
+    /// Synchronization state of every synced pipeline still running
+    pub fn reports(&self) -> Vec<SyncReport> {
+        let mut pipelines = self.pipelines.lock();
+        pipelines.retain(|_, synced| synced.pipeline.upgrade().is_some());
+        let mut reports: Vec<SyncReport> = pipelines
+            .iter()
+            .map(|(id, synced)| self.report_for(id, synced))
+            .collect();
+        reports.sort_by(|a, b| a.pipeline_id.cmp(&b.pipeline_id));
+        reports
+    }
+
+    /// Clock servers running in this process
+    pub fn servers(&self) -> Vec<ClockServerInfo> {
+        let clock = gst::SystemClock::obtain();
+        let mut servers: Vec<ClockServerInfo> = self
+            .servers
+            .lock()
+            .iter()
+            .map(|(port, server)| ClockServerInfo {
+                address: server.address.clone(),
+                port: *port,
+                clock_time_ns: clock.time().nseconds(),
+            })
+            .collect();
+        servers.sort_by_key(|s| s.port);
+        servers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drift_ppm() {
+        assert_eq!(drift_ppm(1, 1), Some(0.0));
+        let drift = drift_ppm(1_000_050, 1_000_000).unwrap();
+        assert!((drift - 50.0).abs() < 1e-6);
+        assert_eq!(drift_ppm(0, 0), None);
+    }
+
+    #[test]
+    fn test_future_base_time() {
+        let now = gst::ClockTime::from_mseconds(12_300);
+        assert_eq!(
+            future_base_time(now, Duration::from_millis(1500)),
+            gst::ClockTime::from_seconds(14)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use network_interface::{Addr, NetworkInterface, NetworkInterfaceConfig};
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// Element factories whose socket options are managed by [`NetworkOptions`]
+const UDP_FACTORIES: &[&str] = &["udpsrc", "udpsink", "multiudpsink", "dynudpsink"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkInterfaceInfo {
+    pub name: String,
+    pub index: u32,
+    pub mac_address: Option<String>,
+    pub addresses: Vec<String>,
+    pub loopback: bool,
+}
+
+/// Socket options applied to the UDP elements of a pipeline
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct NetworkOptions {
+    #[schemars(
+        description = "Network interface(s) used to join multicast groups, comma-separated (e.g., 'eth1'). See gst_list_network_interfaces"
+    )]
+    pub multicast_iface: Option<String>,
+    #[schemars(description = "Time-to-live for outgoing unicast and multicast packets")]
+    pub ttl: Option<u32>,
+    #[schemars(description = "Kernel socket buffer size in bytes (e.g., 2097152)")]
+    pub buffer_size: Option<u32>,
+}
+
+impl NetworkOptions {
+    pub fn is_empty(&self) -> bool {
+        self.multicast_iface.is_none() && self.ttl.is_none() && self.buffer_size.is_none()
+    }
+}
+
+/// List the network interfaces of the host, merging per-address entries
+pub fn list_network_interfaces() -> McpResult<Vec<NetworkInterfaceInfo>> {
+    let interfaces = NetworkInterface::show().map_err(|e| {
+        GStreamerMcpError::Other(format!("Failed to list network interfaces: {}", e))
+    })?;
+
+    let mut merged: BTreeMap<String, NetworkInterfaceInfo> = BTreeMap::new();
+    for iface in interfaces {
+        let entry = merged
+            .entry(iface.name.clone())
+            .or_insert_with(|| NetworkInterfaceInfo {
+                name: iface.name.clone(),
+                index: iface.index,
+                mac_address: None,
+                addresses: Vec::new(),
+                loopback: false,
+            });
+
+        if entry.mac_address.is_none() {
+            entry.mac_address = iface.mac_addr.clone();
+        }
+
+        for addr in &iface.addr {
+            let (ip, loopback) = match addr {
+                Addr::V4(v4) => (v4.ip.to_string(), v4.ip.is_loopback()),
+                Addr::V6(v6) => (v6.ip.to_string(), v6.ip.is_loopback()),
+            };
+            entry.loopback |= loopback;
+            if !entry.addresses.contains(&ip) {
+                entry.addresses.push(ip);
+            }
+        }
+    }
+
+    Ok(merged.into_values().collect())
+}
+
+/// Check `value` against the range `min..=max` a property accepts, e.g.
+/// 0 to 255 for the `ttl` of udpsink
+fn check_int_range(name: &str, value: u32, (min, max): (i64, i64)) -> McpResult<()> {
+    if !(min..=max).contains(&i64::from(value)) {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "Value {} is out of range for property '{}' ({} to {})",
+            value, name, min, max
+        )));
+    }
+    Ok(())
+}
+
+fn set_int_property(element: &gst::Element, name: &str, value: u32) -> McpResult<bool> {
+    let Some(pspec) = element.find_property(name) else {
+        return Ok(false);
+    };
+
+    if let Some(p) = pspec.downcast_ref::<gst::glib::ParamSpecInt>() {
+        check_int_range(name, value, (p.minimum().into(), p.maximum().into()))?;
+        element.set_property(name, value as i32);
+    } else if let Some(p) = pspec.downcast_ref::<gst::glib::ParamSpecUInt>() {
+        check_int_range(name, value, (p.minimum().into(), p.maximum().into()))?;
+        element.set_property(name, value);
+    } else {
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+/// Apply network options to every UDP element of a pipeline.
+/// Returns a human readable summary per configured element.
+pub fn apply_network_options(
+    pipeline: &gst::Pipeline,
+    options: &NetworkOptions,
+) -> McpResult<Vec<String>> {
+    if let Some(ifaces) = &options.multicast_iface {
+        let known = list_network_interfaces()?;
+        for name in ifaces.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            if !known.iter().any(|iface| iface.name == name) {
+                let available: Vec<&str> = known.iter().map(|i| i.name.as_str()).collect();
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "Unknown network interface '{}'. Available interfaces: {}",
+                    name,
+                    available.join(", ")
+                )));
+            }
+        }
+    }
+
+    let mut applied = Vec::new();
+    for element in pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let Some(factory) = element.factory() else {
+            continue;
+        };
+        if !UDP_FACTORIES.contains(&factory.name().as_str()) {
+            continue;
+        }
+
+        let mut settings = Vec::new();
+        if let Some(iface) = &options.multicast_iface {
+            if element.find_property("multicast-iface").is_some() {
+                element.set_property("multicast-iface", iface);
+                settings.push(format!("multicast-iface={}", iface));
+            }
+        }
+        if let Some(ttl) = options.ttl {
+            for name in ["ttl", "ttl-mc"] {
+                if set_int_property(&element, name, ttl)? {
+                    settings.push(format!("{}={}", name, ttl));
+                }
+            }
+        }
+        if let Some(size) = options.buffer_size {
+            if set_int_property(&element, "buffer-size", size)? {
+                settings.push(format!("buffer-size={}", size));
+            }
+        }
+
+        if !settings.is_empty() {
+            applied.push(format!("{} ({})", element.name(), settings.join(", ")));
+        }
+    }
+
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_int_range() {
+        assert!(check_int_range("ttl", 64, (0, 255)).is_ok());
+        assert!(check_int_range("ttl", 255, (0, 255)).is_ok());
+        assert!(matches!(
+            check_int_range("ttl", 1000, (0, 255)),
+            Err(GStreamerMcpError::PropertyError(_))
+        ));
+        assert!(check_int_range("buffer-size", 0, (1, i32::MAX.into())).is_err());
+        assert!(check_int_range("buffer-size", u32::MAX, (0, i32::MAX.into())).is_err());
+        assert!(check_int_range("buffer-size", u32::MAX, (0, u32::MAX.into())).is_ok());
+    }
+}
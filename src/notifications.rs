@@ -0,0 +1,90 @@
+use rmcp::model::{
+    LoggingLevel, LoggingMessageNotificationParam, ResourceUpdatedNotificationParam,
+};
+use rmcp::{Peer, RoleServer};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Delivers server-initiated events to the connected MCP client.
+///
+/// Events are sent as logging notifications whose `logger` is the event kind
+/// (e.g. `registry_changed`) and whose `data` carries the event payload.
+/// Changes to resources the client subscribed to are sent as resource
+/// updated notifications.
+#[derive(Clone, Default)]
+pub struct Notifier {
+    peer: Arc<RwLock<Option<Peer<RoleServer>>>>,
+    subscriptions: Arc<RwLock<HashSet<String>>>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach the client peer once the MCP session has been initialized
+    pub async fn set_peer(&self, peer: Peer<RoleServer>) {
+        *self.peer.write().await = Some(peer);
+    }
+
+    pub async fn is_connected(&self) -> bool {
+        self.peer.read().await.is_some()
+    }
+
+    pub async fn subscribe(&self, uri: String) {
+        self.subscriptions.write().await.insert(uri);
+    }
+
+    pub async fn unsubscribe(&self, uri: &str) {
+        self.subscriptions.write().await.remove(uri);
+    }
+
+    /// Tell the client that the resource at `uri` changed, if it subscribed
+    /// to it. Returns whether a notification was sent.
+    pub async fn notify_resource_updated(&self, uri: &str) -> bool {
+        if !self.subscriptions.read().await.contains(uri) {
+            return false;
+        }
+        let Some(peer) = self.peer.read().await.clone() else {
+            return false;
+        };
+
+        match peer
+            .notify_resource_updated(ResourceUpdatedNotificationParam {
+                uri: uri.to_string(),
+            })
+            .await
+        {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Failed to send update of resource '{}': {}", uri, e);
+                false
+            }
+        }
+    }
+
+    /// Send an event to the client. Returns false when no client is attached
+    /// or delivery failed; events are never queued.
+    pub async fn notify(&self, kind: &str, level: LoggingLevel, data: serde_json::Value) -> bool {
+        let Some(peer) = self.peer.read().await.clone() else {
+            tracing::debug!("No client attached, dropping '{}' notification", kind);
+            return false;
+        };
+
+        match peer
+            .notify_logging_message(LoggingMessageNotificationParam {
+                level,
+                logger: Some(kind.to_string()),
+                data,
+            })
+            .await
+        {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!("Failed to send '{}' notification: {}", kind, e);
+                false
+            }
+        }
+    }
+}
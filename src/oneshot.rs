@@ -0,0 +1,207 @@
+use crate::{cli::OutputFormat, config::Configuration, handler::GStreamerHandler};
+use anyhow::{anyhow, bail, Context, Result};
+use rmcp::model::{CallToolResult, ProtocolVersion};
+use rmcp::ServiceExt;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, DuplexStream, Lines};
+
+/// Room in each direction of the in-process transport
+const TRANSPORT_BUFFER: usize = 1 << 20;
+
+/// Client end of the in-process transport, speaking line-delimited JSON-RPC
+struct Client {
+    lines: Lines<BufReader<tokio::io::ReadHalf<DuplexStream>>>,
+    writer: tokio::io::WriteHalf<DuplexStream>,
+}
+
+impl Client {
+    async fn send(&mut self, message: Value) -> Result<()> {
+        let mut line = serde_json::to_vec(&message)?;
+        line.push(b'\n');
+        self.writer.write_all(&line).await?;
+        Ok(())
+    }
+
+    /// Wait for the response to request `id`. Notifications from the server
+    /// are skipped and its requests, such as elicitation, refused: there is
+    /// no one to answer them.
+    async fn response(&mut self, id: u64) -> Result<Value> {
+        while let Some(line) = self.lines.next_line().await? {
+            let message: Value = serde_json::from_str(&line)
+                .with_context(|| format!("Invalid message from the server: {}", line))?;
+            if message.get("method").is_some() {
+                if let Some(request) = message.get("id") {
+                    self.send(json!({
+                        "jsonrpc": "2.0",
+                        "id": request,
+                        "error": {"code": -32601, "message": "Not supported by a one-shot call"},
+                    }))
+                    .await?;
+                }
+                continue;
+            }
+            if message.get("id") != Some(&json!(id)) {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                let text = error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("unknown error");
+                bail!("{}", text);
+            }
+            return message
+                .get("result")
+                .cloned()
+                .ok_or_else(|| anyhow!("Response {} has no result", id));
+        }
+        bail!("The server closed the connection before responding")
+    }
+}
+
+/// Text of a tool result for `format`
+pub fn render_result(result: &CallToolResult, format: OutputFormat) -> Result<String> {
+    Ok(match format {
+        OutputFormat::Text => result
+            .content
+            .iter()
+            .filter_map(|content| content.as_text().map(|text| text.text.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        OutputFormat::Json => match &result.structured_content {
+            Some(structured) => serde_json::to_string_pretty(structured)?,
+            None => serde_json::to_string_pretty(result)?,
+        },
+    })
+}
+
+/// A client connected to a handler over the in-process transport
+pub struct Session {
+    client: Client,
+    server: tokio::task::JoinHandle<()>,
+    next_id: u64,
+}
+
+impl Session {
+    /// Initialize a handler with `config` and connect to it as an MCP
+    /// client, so modes, policy and metrics apply to the calls made
+    pub async fn connect(config: Configuration) -> Result<Self> {
+        let handler = GStreamerHandler::with_config(config).await?;
+        let (client, server) = tokio::io::duplex(TRANSPORT_BUFFER);
+        let server = tokio::spawn(async move {
+            match handler.serve(server).await {
+                Ok(service) => {
+                    let _ = service.waiting().await;
+                }
+                Err(e) => tracing::error!("Server error: {:?}", e),
+            }
+        });
+
+        let (reader, writer) = tokio::io::split(client);
+        let mut client = Client {
+            lines: BufReader::new(reader).lines(),
+            writer,
+        };
+        client
+            .send(json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": ProtocolVersion::LATEST,
+                    "capabilities": {},
+                    "clientInfo": {"name": "gstreamer-mcp-call", "version": env!("CARGO_PKG_VERSION")},
+                },
+            }))
+            .await?;
+        client.response(1).await.context("Initialization failed")?;
+        client
+            .send(json!({"jsonrpc": "2.0", "method": "notifications/initialized"}))
+            .await?;
+        Ok(Self {
+            client,
+            server,
+            next_id: 2,
+        })
+    }
+
+    /// Call `tool` with the JSON object `arguments`
+    pub async fn call(&mut self, tool: &str, arguments: Value) -> Result<CallToolResult> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.client
+            .send(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "tools/call",
+                "params": {"name": tool, "arguments": arguments},
+            }))
+            .await?;
+        Ok(serde_json::from_value(self.client.response(id).await?)?)
+    }
+
+    /// Disconnect and wait for the server to finish
+    pub async fn close(self) {
+        drop(self.client);
+        let _ = self.server.await;
+    }
+}
+
+/// Initialize a handler with `config`, call `tool` with the JSON object
+/// `params` through the MCP protocol, so modes, policy and metrics apply as
+/// for a client, and print the result. Returns whether the tool succeeded.
+pub async fn call_tool(
+    config: Configuration,
+    tool: &str,
+    params: &str,
+    format: OutputFormat,
+) -> Result<bool> {
+    let arguments: Value =
+        serde_json::from_str(params).context("Tool parameters are not valid JSON")?;
+    if !arguments.is_object() {
+        bail!("Tool parameters must be a JSON object, e.g. '{{\"name\": \"videotestsrc\"}}'");
+    }
+
+    let mut session = Session::connect(config).await?;
+    let result = session.call(tool, arguments).await;
+    session.close().await;
+
+    let result = result?;
+    let output = render_result(&result, format)?;
+    let success = result.is_error != Some(true);
+    if success {
+        println!("{}", output);
+    } else {
+        eprintln!("{}", output);
+    }
+    Ok(success)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+
+    #[test]
+    fn test_render_result() {
+        let result = CallToolResult {
+            content: vec![Content::text("first"), Content::text("second")],
+            structured_content: Some(json!({"count": 2})),
+            is_error: Some(false),
+        };
+        assert_eq!(
+            render_result(&result, OutputFormat::Text).unwrap(),
+            "first\nsecond"
+        );
+        assert_eq!(
+            render_result(&result, OutputFormat::Json).unwrap(),
+            "{\n  \"count\": 2\n}"
+        );
+
+        // Without structured content the whole result is printed
+        let plain = CallToolResult::success(vec![Content::text("done")]);
+        let rendered: Value =
+            serde_json::from_str(&render_result(&plain, OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(rendered["content"][0]["text"], "done");
+    }
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::pipeline::PipelineManager;
+
+/// How long each pipeline may drain when the client leaves
+const ORPHAN_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often kept orphans are checked for having finished
+const ORPHAN_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What happens to the pipelines when the client disconnects
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrphanPolicy {
+    /// Drain and stop every pipeline, then exit
+    #[default]
+    Stop,
+    /// Keep the pipelines running without an owner, and exit once they
+    /// have all ended or been stopped
+    Keep,
+}
+
+impl std::str::FromStr for OrphanPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "stop" => Ok(OrphanPolicy::Stop),
+            "keep" => Ok(OrphanPolicy::Keep),
+            _ => Err(format!(
+                "Invalid orphan policy '{}'. Must be one of: stop, keep",
+                s
+            )),
+        }
+    }
+}
+
+/// Outcome of handing a pipeline to another owner
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferReport {
+    pub pipeline_id: String,
+    pub previous_owner: Option<String>,
+    pub owner: Option<String>,
+    pub labels: BTreeMap<String, String>,
+}
+
+/// Apply `policy` to the pipelines left behind by a client that
+/// disconnected. Returns once none of them is left running.
+pub async fn handle_orphans(pipeline_manager: Arc<PipelineManager>, policy: OrphanPolicy) {
+    let count = pipeline_manager.list_pipelines().len();
+    if count == 0 {
+        return;
+    }
+    match policy {
+        OrphanPolicy::Stop => {
+            tracing::info!("Client disconnected; stopping {} pipelines", count);
+            let manager = pipeline_manager.clone();
+            let _ = tokio::task::spawn_blocking(move || {
+                for info in manager.list_pipelines() {
+                    match manager.stop_pipeline_draining(&info.id, ORPHAN_DRAIN_TIMEOUT) {
+                        Ok(report) => {
+                            tracing::info!("Stopped pipeline {} ({})", info.id, report.path)
+                        }
+                        Err(e) => tracing::warn!("Failed to stop pipeline {}: {}", info.id, e),
+                    }
+                }
+            })
+            .await;
+        }
+        OrphanPolicy::Keep => {
+            let orphaned = pipeline_manager.orphan_pipelines();
+            tracing::info!(
+                "Client disconnected; keeping {} pipelines until they end",
+                orphaned.len()
+            );
+            loop {
+                tokio::time::sleep(ORPHAN_CHECK_INTERVAL).await;
+                let manager = pipeline_manager.clone();
+                if let Ok(stopped) =
+                    tokio::task::spawn_blocking(move || manager.stop_finished_pipelines()).await
+                {
+                    for id in stopped {
+                        tracing::info!("Orphaned pipeline {} ended and was removed", id);
+                    }
+                }
+                if pipeline_manager.list_pipelines().is_empty() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orphan_policy() {
+        assert_eq!(OrphanPolicy::default(), OrphanPolicy::Stop);
+        assert_eq!("Keep".parse::<OrphanPolicy>(), Ok(OrphanPolicy::Keep));
+        assert!("adopt".parse::<OrphanPolicy>().is_err());
+        assert_eq!(
+            serde_json::to_string(&OrphanPolicy::Keep).unwrap(),
+            "\"keep\""
+        );
+    }
+}
@@ -1,13 +1,48 @@
 use gstreamer as gst;
+use gstreamer::glib;
 use gstreamer::prelude::*;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use rmcp::model::LoggingLevel;
+use rmcp::schemars::{self, JsonSchema};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedSender};
 use uuid::Uuid;
 
+use crate::audio_monitor::{AudioLevelStatus, LevelMonitor};
+use crate::bus_events::BusEventKind;
+use crate::bus_handler::{BusHandler, DrainedMessages, StreamEnd};
+use crate::caps_watch::{watch_caps, CapsChange};
 use crate::discovery::ensure_gstreamer_initialized;
+use crate::element_messages::ElementMessage;
 use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::error_budget::{BudgetBreach, BudgetStatus, BudgetTracker, ErrorBudget};
+use crate::events::{send_event, EventReport, PipelineEvent};
+use crate::looping::{LoopSettings, LoopStatus, Looper};
+use crate::message_filter::{apply_message_rules, MessageFilter, MessageRule};
+use crate::message_history::{HistoryPage, HistoryQuery, MessageHistory};
+use crate::message_wait::{self, MessageQuery, MessageWait};
+use crate::modify::{apply_modification, ModificationResult, PipelineModification};
+use crate::notifications::Notifier;
+use crate::ownership::TransferReport;
+use crate::pipeline_log::{LogRecord, PipelineLog};
+use crate::probes::{add_stats_probe, find_pad, pad_name, ProbeStats, StatsProbe};
+use crate::progress::{query_progress, Progress, ProgressTracker};
+use crate::shared_state::SharedState;
+use crate::stall::{StallReport, MAX_STALL_REPORTS};
+use crate::suggestions::parse_launch;
+use crate::timecode::current_timecode;
+
+/// State listed for pipelines held in PAUSED by a preroll-only launch
+pub const PREROLLED_STATE: &str = "Prerolled";
+
+/// How long a preroll-only launch waits for the pipeline to preroll
+pub const DEFAULT_PREROLL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a flushing seek waits for the pipeline to settle at the new position
+pub const SEEK_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PipelineInfo {
@@ -18,13 +53,661 @@ pub struct PipelineInfo {
     pub last_state_change: chrono::DateTime<chrono::Utc>,
     pub error_count: u32,
     pub warning_count: u32,
+    pub health: PipelineHealth,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+    #[serde(default)]
+    pub restart_count: u32,
+    /// Client data kept with the pipeline, such as correlation IDs
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    /// Key that makes repeated launches return this pipeline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dedupe_key: Option<String>,
+    /// Client or person supervising the pipeline; unset once orphaned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+}
+
+/// Whether a pipeline's labels have every label of `selector`, with the
+/// same value
+pub fn matches_labels(
+    labels: &BTreeMap<String, String>,
+    selector: &BTreeMap<String, String>,
+) -> bool {
+    selector
+        .iter()
+        .all(|(key, value)| labels.get(key) == Some(value))
+}
+
+fn check_labels(labels: &BTreeMap<String, String>) -> McpResult<()> {
+    if labels.keys().any(|key| key.trim().is_empty()) {
+        return Err(GStreamerMcpError::PipelineError(
+            "Label names cannot be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Largest pipeline metadata, in bytes of JSON
+pub const MAX_METADATA_BYTES: usize = 64 * 1024;
+
+/// Apply `update` to pipeline metadata: keys set to null are removed, others
+/// replace the value they had. Nested objects are replaced, not merged.
+pub fn merge_metadata(
+    metadata: &mut serde_json::Map<String, serde_json::Value>,
+    update: serde_json::Map<String, serde_json::Value>,
+) {
+    for (key, value) in update {
+        if value.is_null() {
+            metadata.remove(&key);
+        } else {
+            metadata.insert(key, value);
+        }
+    }
+}
+
+/// How often the watchdog checks the pipelines that have a policy
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest wait between automatic restarts
+const MAX_WATCHDOG_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A pipeline running this long without errors after an automatic restart
+/// gets its retries back
+const WATCHDOG_STABLE_PERIOD: Duration = Duration::from_secs(60);
+
+/// How often pipelines are checked for a passed stop time
+const DEADLINE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Time to stop a pipeline launched now with a maximum duration and/or a
+/// stop time: the earlier of the two. A stop time that has passed is an
+/// error.
+pub fn pipeline_deadline(
+    now: chrono::DateTime<chrono::Utc>,
+    max_duration_seconds: Option<u64>,
+    stop_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> McpResult<Option<chrono::DateTime<chrono::Utc>>> {
+    if let Some(stop_at) = stop_at.filter(|at| *at <= now) {
+        return Err(GStreamerMcpError::PipelineError(format!(
+            "stop_at {} has already passed",
+            stop_at.to_rfc3339()
+        )));
+    }
+    let by_duration = match max_duration_seconds {
+        Some(seconds) => Some(
+            i64::try_from(seconds)
+                .ok()
+                .and_then(chrono::Duration::try_seconds)
+                .and_then(|duration| now.checked_add_signed(duration))
+                .ok_or_else(|| {
+                    GStreamerMcpError::PipelineError(format!(
+                        "max_duration_seconds {} is too large",
+                        seconds
+                    ))
+                })?,
+        ),
+        None => None,
+    };
+    Ok(match (by_duration, stop_at) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    })
+}
+
+/// Tool call behind the last launch, seek or state change of a pipeline.
+/// Messages posted afterwards are tagged with it, so clients can tell which
+/// of their calls an error followed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cause {
+    /// JSON-RPC ID of the tool call
+    pub request_id: String,
+    pub tool: String,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Cause {
+    pub fn new(request_id: impl ToString, tool: &str) -> Self {
+        Self {
+            request_id: request_id.to_string(),
+            tool: tool.to_string(),
+            at: chrono::Utc::now(),
+        }
+    }
+}
+
+/// Add `cause` to the data of a notification about a pipeline
+pub fn attach_cause(data: &mut serde_json::Value, cause: Option<&Cause>) {
+    if let (Some(object), Some(cause)) = (data.as_object_mut(), cause) {
+        object.insert(
+            "cause".to_string(),
+            serde_json::to_value(cause).unwrap_or_default(),
+        );
+    }
+}
+
+/// A pipeline stopped because its time ran out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpiredPipeline {
+    pub pipeline_id: String,
+    pub deadline: chrono::DateTime<chrono::Utc>,
+    /// Why the pipeline did not stop cleanly, if it did not
+    pub error: Option<String>,
+}
+
+/// What the watchdog does when a pipeline posts an error
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OnError {
+    /// Relaunch the pipeline after a backoff, up to `max_retries` times
+    Restart,
+    /// Stop and remove the pipeline
+    Stop,
+    /// Only record the error
+    #[default]
+    Ignore,
+}
+
+/// Recovery policy of a pipeline, applied by the watchdog
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct WatchdogPolicy {
+    #[schemars(
+        description = "What to do when the pipeline posts an error: 'restart', 'stop' or 'ignore' (default: ignore)"
+    )]
+    #[serde(default)]
+    pub on_error: OnError,
+    #[schemars(
+        description = "Automatic restarts before giving up; retries come back after a minute without errors (default: 3)"
+    )]
+    pub max_retries: Option<u32>,
+    #[schemars(
+        description = "Milliseconds to wait before the first restart, doubling with each retry up to a minute (default: 1000)"
+    )]
+    pub backoff_ms: Option<u64>,
+}
+
+impl WatchdogPolicy {
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries.unwrap_or(3)
+    }
+
+    /// Wait before the restart following `retries` earlier ones
+    pub fn backoff(&self, retries: u32) -> Duration {
+        let first = Duration::from_millis(self.backoff_ms.unwrap_or(1000));
+        first
+            .checked_mul(2u32.saturating_pow(retries))
+            .unwrap_or(MAX_WATCHDOG_BACKOFF)
+            .min(MAX_WATCHDOG_BACKOFF)
+    }
+}
+
+/// Decision of a watchdog on one check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogAction {
+    None,
+    /// New errors; a restart is due after this long
+    Scheduled(Duration),
+    Restart,
+    Stop,
+    /// New errors with no retries left
+    GiveUp,
+}
+
+/// Applies a [`WatchdogPolicy`] to the errors of one pipeline
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    policy: WatchdogPolicy,
+    errors_seen: u32,
+    retries: u32,
+    restart_due: Option<Instant>,
+    last_restart: Option<Instant>,
+    gave_up: bool,
+}
+
+/// Watchdog of a pipeline, as reported in its status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchdogStatus {
+    pub policy: WatchdogPolicy,
+    /// Automatic restarts since the pipeline last ran without errors
+    pub retries: u32,
+    pub restart_pending: bool,
+    /// Retries ran out; the pipeline is left as it is
+    pub gave_up: bool,
+}
+
+impl Watchdog {
+    /// Watchdog of a pipeline that has posted `errors_seen` errors so far
+    pub fn new(policy: WatchdogPolicy, errors_seen: u32) -> Self {
+        Watchdog {
+            policy,
+            errors_seen,
+            retries: 0,
+            restart_due: None,
+            last_restart: None,
+            gave_up: false,
+        }
+    }
+
+    /// The watchdog moved to a restarted pipeline, which has no errors yet
+    pub fn restarted(mut self) -> Self {
+        self.errors_seen = 0;
+        self
+    }
+
+    /// What to do about a pipeline that has posted `error_count` errors in
+    /// total, at `now`
+    pub fn observe(&mut self, error_count: u32, now: Instant) -> WatchdogAction {
+        let new_errors = error_count > self.errors_seen;
+        self.errors_seen = error_count;
+        match self.policy.on_error {
+            OnError::Ignore => WatchdogAction::None,
+            OnError::Stop if new_errors => WatchdogAction::Stop,
+            OnError::Stop => WatchdogAction::None,
+            OnError::Restart => {
+                if let Some(due) = self.restart_due {
+                    if now < due {
+                        return WatchdogAction::None;
+                    }
+                    self.restart_due = None;
+                    self.retries += 1;
+                    self.last_restart = Some(now);
+                    return WatchdogAction::Restart;
+                }
+                if !new_errors {
+                    if self
+                        .last_restart
+                        .is_some_and(|at| now.duration_since(at) >= WATCHDOG_STABLE_PERIOD)
+                    {
+                        self.retries = 0;
+                        self.last_restart = None;
+                        self.gave_up = false;
+                    }
+                    return WatchdogAction::None;
+                }
+                if self.retries >= self.policy.max_retries() {
+                    if self.gave_up {
+                        return WatchdogAction::None;
+                    }
+                    self.gave_up = true;
+                    return WatchdogAction::GiveUp;
+                }
+                let backoff = self.policy.backoff(self.retries);
+                self.restart_due = Some(now + backoff);
+                WatchdogAction::Scheduled(backoff)
+            }
+        }
+    }
+
+    pub fn status(&self) -> WatchdogStatus {
+        WatchdogStatus {
+            policy: self.policy.clone(),
+            retries: self.retries,
+            restart_pending: self.restart_due.is_some(),
+            gave_up: self.gave_up,
+        }
+    }
+}
+
+/// Something the watchdog did to a pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchdogEvent {
+    pub pipeline_id: String,
+    /// `scheduled`, `restarted`, `stopped`, `gave_up` or `failed`
+    pub action: String,
+    pub message: String,
+}
+
+/// Outcome of [`PipelineManager::restart_pipeline`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestartReport {
+    pub pipeline_id: String,
+    /// Restarts of the pipeline so far, this one included
+    pub restart_count: u32,
+    /// State of the new pipeline
+    pub state: String,
+    /// Position the pipeline resumed at, when it could seek back
+    pub restored_position_ns: Option<u64>,
+    pub elapsed_ms: u64,
+    /// Why the position was not restored
+    pub detail: Option<String>,
+}
+
+/// Coarse health of a pipeline derived from its bus messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineHealth {
+    #[default]
+    Healthy,
+    Degraded,
+    Failed,
+}
+
+impl std::fmt::Display for PipelineHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PipelineHealth::Healthy => "Healthy",
+            PipelineHealth::Degraded => "Degraded",
+            PipelineHealth::Failed => "Failed",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Seek target: nanoseconds, or `[[H:]M:]S[.fraction]` text such as "1:23.5"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum SeekPosition {
+    Nanoseconds(u64),
+    Text(String),
+}
+
+impl SeekPosition {
+    pub fn to_clock_time(&self) -> McpResult<gst::ClockTime> {
+        match self {
+            SeekPosition::Nanoseconds(ns) => Ok(gst::ClockTime::from_nseconds(*ns)),
+            SeekPosition::Text(text) => parse_position(text),
+        }
+    }
+}
+
+/// Outcome of a seek
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeekResult {
+    pub pipeline_id: String,
+    pub requested_ns: u64,
+    /// Position after the seek; key-unit seeks land on a nearby keyframe
+    pub position_ns: Option<u64>,
+    pub duration_ns: Option<u64>,
+}
+
+/// Outcome of a playback rate change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateResult {
+    pub pipeline_id: String,
+    pub requested_rate: f64,
+    /// Whether the seek was taken and the playback segment runs at the rate
+    pub accepted: bool,
+    /// Rate of the playback segment after the seek, when the sinks report it
+    pub rate: Option<f64>,
+    pub start_ns: u64,
+    /// End of the segment; reverse playback runs from here back to the start
+    pub stop_ns: Option<u64>,
+    pub position_ns: Option<u64>,
+    pub duration_ns: Option<u64>,
+}
+
+/// Segment played at `rate`. Forward playback runs from `start` (default:
+/// the current position) to `stop` (default: the end); reverse playback
+/// runs from `stop` (default: the current position) back to `start`
+/// (default: the beginning).
+pub fn rate_segment(
+    rate: f64,
+    start: Option<gst::ClockTime>,
+    stop: Option<gst::ClockTime>,
+    position: Option<gst::ClockTime>,
+) -> McpResult<(gst::ClockTime, Option<gst::ClockTime>)> {
+    if !rate.is_finite() || rate == 0.0 {
+        return Err(GStreamerMcpError::Other(format!(
+            "Invalid rate {}. Use a non-zero rate; negative rates play in reverse",
+            rate
+        )));
+    }
+    let (start, stop) = if rate > 0.0 {
+        (start.or(position).unwrap_or(gst::ClockTime::ZERO), stop)
+    } else {
+        let stop = stop.or(position).ok_or_else(|| {
+            GStreamerMcpError::Other(
+                "Reverse playback needs a stop position when the current position is unknown"
+                    .to_string(),
+            )
+        })?;
+        (start.unwrap_or(gst::ClockTime::ZERO), Some(stop))
+    };
+    if let Some(stop) = stop.filter(|stop| *stop <= start) {
+        return Err(GStreamerMcpError::Other(format!(
+            "Stop {} must be after start {}",
+            stop, start
+        )));
+    }
+    Ok((start, stop))
+}
+
+/// Reject positions past the end of a pipeline with a known duration
+fn check_within(
+    id: &str,
+    position: gst::ClockTime,
+    duration: Option<gst::ClockTime>,
+) -> McpResult<()> {
+    match duration.filter(|d| position > *d) {
+        Some(duration) => Err(GStreamerMcpError::PipelineError(format!(
+            "Position {} is past the end of pipeline '{}' ({})",
+            position, id, duration
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Parse a position given as plain nanoseconds ("83500000000") or as
+/// `[[H:]M:]S[.fraction]` ("1:23.5", "01:02:03.25", "83.5")
+pub fn parse_position(text: &str) -> McpResult<gst::ClockTime> {
+    let text = text.trim();
+    let invalid = || {
+        GStreamerMcpError::Other(format!(
+            "Invalid position '{}'. Use nanoseconds or [[H:]M:]S[.fraction], e.g. '1:23.5'",
+            text
+        ))
+    };
+    if !text.contains([':', '.']) {
+        return text
+            .parse::<u64>()
+            .map(gst::ClockTime::from_nseconds)
+            .map_err(|_| invalid());
+    }
+
+    let fields: Vec<&str> = text.split(':').collect();
+    if fields.len() > 3 {
+        return Err(invalid());
+    }
+    let (seconds, minutes_hours) = fields.split_last().ok_or_else(invalid)?;
+    let seconds: f64 = seconds.parse().map_err(|_| invalid())?;
+    if !seconds.is_finite() || seconds < 0.0 || (!minutes_hours.is_empty() && seconds >= 60.0) {
+        return Err(invalid());
+    }
+    let mut whole = 0u64;
+    for (i, field) in minutes_hours.iter().enumerate() {
+        let value: u64 = field.parse().map_err(|_| invalid())?;
+        // Minutes stay below an hour when hours are given
+        if i == 1 && value >= 60 {
+            return Err(invalid());
+        }
+        whole = whole * 60 + value;
+    }
+    Ok(gst::ClockTime::from_nseconds(
+        whole * 60 * 1_000_000_000 + (seconds * 1e9).round() as u64,
+    ))
+}
+
+/// Element of a pipeline and the factory it was created from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementSummary {
+    pub name: String,
+    pub factory: Option<String>,
+}
+
+/// How a pipeline was brought down by a stop request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopPath {
+    /// EOS was sent and reached the sinks before teardown
+    Drained,
+    /// EOS was sent but did not reach the sinks within the drain timeout
+    DrainTimedOut,
+    /// An error was posted while draining
+    DrainFailed,
+    /// The pipeline was not playing, so there was nothing to drain
+    Immediate,
+    /// Draining was skipped and teardown ran on a watchdog thread
+    Forced,
+}
+
+impl std::fmt::Display for StopPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            StopPath::Drained => "drained",
+            StopPath::DrainTimedOut => "drain timed out",
+            StopPath::DrainFailed => "drain failed",
+            StopPath::Immediate => "immediate",
+            StopPath::Forced => "forced",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopReport {
+    pub pipeline_id: String,
+    pub path: StopPath,
+    pub elapsed_ms: u64,
+    /// False when a forced teardown was still hanging when the watchdog gave up
+    pub teardown_completed: bool,
+    pub detail: Option<String>,
+}
+
+/// What became of an EOS sent to a pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EosOutcome {
+    /// Sent without waiting for it to arrive
+    Sent,
+    /// EOS reached every sink; muxers have written their headers and indexes
+    Completed,
+    /// EOS did not reach the sinks within the timeout
+    TimedOut,
+    /// An error was posted before EOS arrived
+    Failed,
+}
+
+impl std::fmt::Display for EosOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EosOutcome::Sent => "sent",
+            EosOutcome::Completed => "completed",
+            EosOutcome::TimedOut => "timed out",
+            EosOutcome::Failed => "failed",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Result of a `gst_send_eos` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EosReport {
+    pub pipeline_id: String,
+    pub outcome: EosOutcome,
+    pub elapsed_ms: u64,
+    pub detail: Option<String>,
+}
+
+/// EOS and errors of a pipeline from the moment it is made: handed over by
+/// the bus handler, which drains a watched bus, or popped from the bus of a
+/// pipeline that is not watched
+pub enum EndWatch {
+    Drained {
+        drained: Arc<DrainedMessages>,
+        after: u64,
+    },
+    Bus(gst::Bus),
+}
+
+impl EndWatch {
+    /// Also count the EOS or error posted since the pipeline was launched
+    pub fn since_launch(self) -> Self {
+        match self {
+            EndWatch::Drained { drained, .. } => EndWatch::Drained { drained, after: 0 },
+            bus => bus,
+        }
+    }
+
+    /// Wait up to `timeout` for EOS, or an error
+    pub fn wait_for_eos(&self, timeout: Duration) -> (EosOutcome, Option<String>) {
+        let end = match self {
+            EndWatch::Drained { drained, after } => drained.wait_end(*after, timeout),
+            EndWatch::Bus(bus) => bus
+                .timed_pop_filtered(
+                    gst::ClockTime::from_nseconds(timeout.as_nanos() as u64),
+                    &[gst::MessageType::Eos, gst::MessageType::Error],
+                )
+                .map(|msg| match msg.view() {
+                    gst::MessageView::Error(err) => StreamEnd::Error(err.error().to_string()),
+                    _ => StreamEnd::Eos,
+                }),
+        };
+        match end {
+            Some(StreamEnd::Eos) => (EosOutcome::Completed, None),
+            Some(StreamEnd::Error(error)) => (EosOutcome::Failed, Some(error)),
+            None => (
+                EosOutcome::TimedOut,
+                Some(format!("EOS not received within {:?}", timeout)),
+            ),
+        }
+    }
+}
+
+/// The task recording a pipeline's bus messages
+#[derive(Debug)]
+pub struct BusWatch {
+    /// Shuts the task down when sent to or dropped
+    pub shutdown: mpsc::Sender<()>,
+    /// Messages it took off the bus that callers wait on
+    pub drained: Arc<DrainedMessages>,
+}
+
+/// Outcome of waiting for a pipeline to reach a state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateWait {
+    pub pipeline_id: String,
+    pub target: String,
+    pub reached: bool,
+    pub current: String,
+    /// State still being changed to, if any
+    pub pending: Option<String>,
+    /// Result of the last state change: `success`, `async`, `no_preroll` or
+    /// `failure`
+    pub state_return: String,
+    pub elapsed_ms: u64,
+    /// Warnings and errors posted while waiting
+    pub messages: Vec<BusMessage>,
 }
 
 #[derive(Debug)]
 pub struct PipelineInstance {
     pub pipeline: gst::Pipeline,
     pub info: PipelineInfo,
-    pub bus_messages: Vec<BusMessage>,
+    /// Recorded bus messages, the oldest dropped first
+    pub bus_messages: MessageHistory,
+    /// Alert thresholds; health follows them instead of single messages
+    pub budget: Option<BudgetTracker>,
+    /// Level meter attached by `gst_monitor_audio_levels`
+    pub audio_monitor: Option<Arc<Mutex<LevelMonitor>>>,
+    /// Stalled state changes captured by the stall detector, oldest first
+    pub stall_reports: Vec<StallReport>,
+    /// Keeps reported progress monotonic within a segment
+    pub progress: Mutex<ProgressTracker>,
+    /// Probes added by `gst_add_probe`, by pad as `element.pad`
+    pub probes: HashMap<String, StatsProbe>,
+    /// Recovery policy applied when the pipeline posts errors
+    pub watchdog: Option<Watchdog>,
+    /// When the pipeline is stopped and removed, if limited
+    pub deadline: Option<chrono::DateTime<chrono::Utc>>,
+    /// Tool call behind the last launch, seek or state change
+    pub cause: Option<Cause>,
+    /// Repeats a segment, set by `gst_set_loop`
+    pub looper: Option<Looper>,
+    /// Messages dropped by the configured message rules
+    pub suppressed_messages: u64,
+    /// The task recording the bus messages, shut down when dropped with
+    /// the instance
+    pub bus_watch: Option<BusWatch>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,28 +716,123 @@ pub struct BusMessage {
     pub message_type: String,
     pub message: String,
     pub source: Option<String>,
+    /// Tool call whose action preceded the message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cause: Option<Cause>,
+    /// Decoded fields of well-known element messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<ElementMessage>,
+}
+
+impl PipelineInstance {
+    /// Keep a message, tagged with the current cause, in the history
+    fn record_message(&mut self, mut message: BusMessage) {
+        if message.cause.is_none() {
+            message.cause = self.cause.clone();
+        }
+        self.bus_messages.push(message);
+    }
 }
 
 impl Drop for PipelineInstance {
     fn drop(&mut self) {
         // Ensure pipeline is stopped and cleaned up
         let _ = self.pipeline.set_state(gst::State::Null);
+        if let Some(log) = self.bus_messages.log() {
+            log.append(&LogRecord::Removed {
+                timestamp: chrono::Utc::now(),
+            });
+        }
     }
 }
 
+/// Sends messages recorded for a pipeline, with its ID
+pub type BusEventSender = UnboundedSender<(String, BusMessage)>;
+
 pub struct PipelineManager {
     pipelines: Arc<RwLock<HashMap<String, Arc<RwLock<PipelineInstance>>>>>,
+    /// Message filters per pipeline, keyed by subscriber
+    message_filters: Arc<RwLock<HashMap<String, HashMap<String, MessageFilter>>>>,
+    /// Receives error budget breaches of all pipelines
+    budget_alerts: Arc<RwLock<Option<UnboundedSender<BudgetBreach>>>>,
+    /// Receives caps renegotiations of all pipelines
+    caps_alerts: Arc<RwLock<Option<UnboundedSender<CapsChange>>>>,
+    /// Receives the errors, warnings, EOS and state changes recorded for
+    /// all pipelines
+    bus_events: Arc<RwLock<Option<BusEventSender>>>,
+    /// Held while a launch with a dedupe key checks for and creates its
+    /// pipeline
+    dedupe_lock: Mutex<()>,
     max_pipelines: usize,
+    /// Applied to every message before it is recorded and counted
+    message_rules: Vec<MessageRule>,
+    /// Set by `watch_buses`; new pipelines get a bus handler recording
+    /// their messages
+    bus_watcher: RwLock<Option<Weak<PipelineManager>>>,
+    /// Directory each pipeline's history is appended to, if any
+    log_dir: Option<std::path::PathBuf>,
+    /// Registry custom pipeline IDs are reserved in, when shared with other
+    /// instances
+    shared_state: Option<Arc<SharedState>>,
 }
 
 impl PipelineManager {
     pub fn new(max_pipelines: usize) -> Self {
         Self {
             pipelines: Arc::new(RwLock::new(HashMap::new())),
+            message_filters: Arc::new(RwLock::new(HashMap::new())),
+            budget_alerts: Arc::new(RwLock::new(None)),
+            caps_alerts: Arc::new(RwLock::new(None)),
+            bus_events: Arc::new(RwLock::new(None)),
+            dedupe_lock: Mutex::new(()),
             max_pipelines,
+            message_rules: Vec::new(),
+            bus_watcher: RwLock::new(None),
+            log_dir: None,
+            shared_state: None,
+        }
+    }
+
+    /// Reserve custom pipeline IDs in `shared` while the pipelines exist, so
+    /// other instances cannot launch pipelines with the same IDs
+    pub fn with_shared_state(mut self, shared: Option<Arc<SharedState>>) -> Self {
+        self.shared_state = shared;
+        self
+    }
+
+    fn release_id(&self, id: &str) {
+        if let Some(shared) = &self.shared_state {
+            shared.release_id(id);
         }
     }
 
+    /// Append the bus messages of every pipeline, with its launches and
+    /// removal, to a JSON lines file per pipeline in `dir`
+    pub fn with_log_dir(mut self, dir: Option<std::path::PathBuf>) -> Self {
+        self.log_dir = dir;
+        self
+    }
+
+    /// Directory of the pipeline logs, if they are on
+    pub fn log_dir(&self) -> Option<&std::path::Path> {
+        self.log_dir.as_deref()
+    }
+
+    /// Drop or remap the bus messages matching `rules` before they are
+    /// recorded, so known-noisy elements do not degrade pipeline health
+    pub fn with_message_rules(mut self, rules: Vec<MessageRule>) -> Self {
+        self.message_rules = rules;
+        self
+    }
+
+    /// Record the messages of every pipeline created from now on as they
+    /// are posted, with a `BusHandler` task per pipeline. Without it,
+    /// messages are only recorded when a tool collects them. Needs a tokio
+    /// runtime; pipelines created outside of one are not watched.
+    pub fn watch_buses(self: &Arc<Self>) {
+        *self.bus_watcher.write() = Some(Arc::downgrade(self));
+    }
+
     pub fn create_pipeline(
         &self,
         description: &str,
@@ -74,41 +852,299 @@ impl PipelineManager {
             }
         }
 
-        // Parse the pipeline
-        let element = gst::parse::launch(description).map_err(|e| {
-            GStreamerMcpError::PipelineError(format!("Failed to parse pipeline: {}", e))
+        // Generate or use custom ID; only custom IDs can collide with those
+        // of other instances
+        let id = match (custom_id, &self.shared_state) {
+            (Some(id), Some(shared)) => {
+                shared.reserve_id(&id)?;
+                id
+            }
+            (Some(id), None) => id,
+            (None, _) => format!("pipeline-{}", Uuid::new_v4()),
+        };
+        let instance = self.new_instance(description, &id).inspect_err(|_| {
+            self.release_id(&id);
         })?;
 
+        // Store the pipeline
+        let mut pipelines = self.pipelines.write();
+        pipelines.insert(id.clone(), Arc::new(RwLock::new(instance)));
+
+        Ok(id)
+    }
+
+    /// Create a pipeline unless one was already launched with `dedupe_key`.
+    /// Returns the ID of the pipeline and whether it was created; a key in
+    /// use by a pipeline with another description is an error.
+    pub fn create_pipeline_once(
+        &self,
+        description: &str,
+        custom_id: Option<String>,
+        dedupe_key: &str,
+    ) -> McpResult<(String, bool)> {
+        let _guard = self.dedupe_lock.lock();
+        let existing = self.pipelines.read().values().find_map(|instance| {
+            let instance = instance.read();
+            (instance.info.dedupe_key.as_deref() == Some(dedupe_key))
+                .then(|| (instance.info.id.clone(), instance.info.description.clone()))
+        });
+        if let Some((id, existing_description)) = existing {
+            if existing_description != description {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "Dedupe key '{}' belongs to pipeline '{}', launched with another description",
+                    dedupe_key, id
+                )));
+            }
+            return Ok((id, false));
+        }
+
+        let id = self.create_pipeline(description, custom_id)?;
+        if let Some(instance) = self.get_pipeline(&id) {
+            instance.write().info.dedupe_key = Some(dedupe_key.to_string());
+        }
+        Ok((id, true))
+    }
+
+    /// Parse a description into a pipeline stored under `id`, in NULL
+    fn new_instance(&self, description: &str, id: &str) -> McpResult<PipelineInstance> {
+        // Parse the pipeline
+        let element = parse_launch(description, "Failed to parse pipeline")?;
+
         let pipeline = element.downcast::<gst::Pipeline>().map_err(|_| {
             GStreamerMcpError::PipelineError("Failed to cast to Pipeline".to_string())
         })?;
 
-        // Generate or use custom ID
-        let id = custom_id.unwrap_or_else(|| format!("pipeline-{}", Uuid::new_v4()));
+        // Format changes of live inputs are recorded and forwarded as alerts
+        let pipelines = Arc::downgrade(&self.pipelines);
+        let caps_alerts = self.caps_alerts.clone();
+        watch_caps(&pipeline, id, move |change| {
+            let instance = pipelines
+                .upgrade()
+                .and_then(|pipelines| pipelines.read().get(&change.pipeline_id).cloned());
+            if let Some(instance) = instance {
+                instance.write().record_message(BusMessage {
+                    timestamp: change.timestamp,
+                    message_type: "CapsChanged".to_string(),
+                    message: format!(
+                        "{}.{}: {}",
+                        change.element,
+                        change.pad,
+                        change.changes.join(", ")
+                    ),
+                    source: Some(change.element.clone()),
+                    cause: None,
+                    details: None,
+                });
+            }
+            if let Some(alerts) = caps_alerts.read().as_ref() {
+                let _ = alerts.send(change);
+            }
+        });
 
         // Create pipeline info
         let info = PipelineInfo {
-            id: id.clone(),
+            id: id.to_string(),
             description: description.to_string(),
             state: format!("{:?}", gst::State::Null),
             created_at: chrono::Utc::now(),
             last_state_change: chrono::Utc::now(),
             error_count: 0,
             warning_count: 0,
+            health: PipelineHealth::Healthy,
+            labels: BTreeMap::new(),
+            restart_count: 0,
+            metadata: serde_json::Map::new(),
+            dedupe_key: None,
+            owner: None,
         };
 
-        // Create pipeline instance
-        let instance = PipelineInstance {
+        let log = self.log_dir.as_deref().and_then(|dir| {
+            PipelineLog::open(dir, id)
+                .inspect_err(|e| tracing::warn!("Failed to open the log of pipeline {}: {}", id, e))
+                .ok()
+        });
+        if let Some(log) = &log {
+            log.append(&LogRecord::Created {
+                timestamp: info.created_at,
+                description: description.to_string(),
+            });
+        }
+
+        let bus_watch = self.spawn_bus_handler(id, &pipeline);
+        Ok(PipelineInstance {
             pipeline,
             info,
-            bus_messages: Vec::new(),
+            bus_messages: MessageHistory::default().with_log(log),
+            budget: None,
+            audio_monitor: None,
+            stall_reports: Vec::new(),
+            probes: HashMap::new(),
+            progress: Mutex::new(ProgressTracker::default()),
+            watchdog: None,
+            deadline: None,
+            cause: None,
+            looper: None,
+            suppressed_messages: 0,
+            bus_watch,
+        })
+    }
+
+    /// Start a `BusHandler` for a new pipeline, if buses are watched
+    fn spawn_bus_handler(&self, id: &str, pipeline: &gst::Pipeline) -> Option<BusWatch> {
+        let manager = self.bus_watcher.read().as_ref()?.upgrade()?;
+        let runtime = tokio::runtime::Handle::try_current().ok()?;
+        let (shutdown, shutdown_rx) = mpsc::channel(1);
+        let drained = Arc::new(DrainedMessages::default());
+        let id = id.to_string();
+        let pipeline = pipeline.clone();
+        let handler_drained = drained.clone();
+        runtime.spawn(async move {
+            BusHandler::new(manager)
+                .watch_pipeline(id, pipeline, handler_drained, shutdown_rx)
+                .await;
+        });
+        Some(BusWatch { shutdown, drained })
+    }
+
+    /// Watch for the EOS or errors a pipeline posts from now on
+    pub fn end_watch(&self, id: &str) -> McpResult<EndWatch> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let instance = pipeline.read();
+        if let Some(watch) = &instance.bus_watch {
+            return Ok(EndWatch::Drained {
+                drained: watch.drained.clone(),
+                after: watch.drained.ends_seen(),
+            });
+        }
+        instance
+            .pipeline
+            .bus()
+            .map(EndWatch::Bus)
+            .ok_or_else(|| GStreamerMcpError::PipelineError("Pipeline has no bus".to_string()))
+    }
+
+    /// Tear a pipeline down and launch its description again under the same
+    /// ID, e.g. to recover from an error. Labels, messages, stall reports,
+    /// the error budget and the watchdog are kept; probes and audio monitors belong to the old
+    /// elements and are dropped. The pipeline is taken to `target`, by
+    /// default the state it was in, or PLAYING if it was stopped. With
+    /// `restore_position`, a seekable pipeline resumes where it was.
+    pub fn restart_pipeline(
+        &self,
+        id: &str,
+        target: Option<gst::State>,
+        restore_position: bool,
+        timeout: Duration,
+    ) -> McpResult<RestartReport> {
+        let started = Instant::now();
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let (description, previous_state, position) = {
+            let instance = pipeline.read();
+            let (_, current, pending) = instance.pipeline.state(gst::ClockTime::ZERO);
+            let state = if pending == gst::State::VoidPending {
+                current
+            } else {
+                pending
+            };
+            let position = instance
+                .pipeline
+                .query_position::<gst::ClockTime>()
+                .map(|p| p.nseconds())
+                .or_else(|| instance.progress.lock().last_position());
+            (instance.info.description.clone(), state, position)
         };
+        let target = target.unwrap_or(if previous_state < gst::State::Ready {
+            gst::State::Playing
+        } else {
+            previous_state
+        });
 
-        // Store the pipeline
-        let mut pipelines = self.pipelines.write();
-        pipelines.insert(id.clone(), Arc::new(RwLock::new(instance)));
+        // Parse the new pipeline first, so a failure leaves the old one alone
+        let mut replacement = self.new_instance(&description, id)?;
+        let restart_count = {
+            let mut instance = pipeline.write();
+            let _ = instance.pipeline.set_state(gst::State::Null);
+            replacement.info.created_at = instance.info.created_at;
+            replacement.info.labels = std::mem::take(&mut instance.info.labels);
+            replacement.info.metadata = std::mem::take(&mut instance.info.metadata);
+            replacement.info.dedupe_key = instance.info.dedupe_key.take();
+            replacement.info.owner = instance.info.owner.take();
+            replacement.info.restart_count = instance.info.restart_count + 1;
+            replacement.bus_messages = std::mem::take(&mut instance.bus_messages);
+            replacement.stall_reports = std::mem::take(&mut instance.stall_reports);
+            replacement.watchdog = instance.watchdog.take().map(Watchdog::restarted);
+            replacement.deadline = instance.deadline;
+            replacement.cause = instance.cause.take();
+            replacement.budget = instance
+                .budget
+                .as_ref()
+                .map(|tracker| BudgetTracker::new(tracker.status(Instant::now()).budget));
+            // Dropping the old instance releases its elements
+            *instance = replacement;
+            instance.info.restart_count
+        };
 
-        Ok(id)
+        let mut detail = None;
+        let mut restored_position_ns = None;
+        match position.filter(|p| restore_position && *p > 0) {
+            Some(position) => {
+                let handle = self.get_pipeline_handle(id)?;
+                let _ = handle.set_state(gst::State::Paused);
+                let _ = handle.state(gst::ClockTime::from_nseconds(timeout.as_nanos() as u64));
+                let mut seeking = gst::query::Seeking::new(gst::Format::Time);
+                let seekable = handle.query(&mut seeking) && seeking.result().0;
+                if !seekable {
+                    detail = Some("the pipeline cannot seek; it starts over".to_string());
+                } else if handle
+                    .seek_simple(
+                        gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT,
+                        gst::ClockTime::from_nseconds(position),
+                    )
+                    .is_ok()
+                {
+                    let _ = handle.state(gst::ClockTime::from_nseconds(timeout.as_nanos() as u64));
+                    restored_position_ns = Some(position);
+                } else {
+                    detail = Some(format!(
+                        "seeking back to {} failed; it starts over",
+                        gst::ClockTime::from_nseconds(position)
+                    ));
+                }
+            }
+            None if restore_position => {
+                detail = Some("no position was known; it starts over".to_string());
+            }
+            None => {}
+        }
+        let state = self.set_pipeline_state(id, target)?;
+
+        let report = RestartReport {
+            pipeline_id: id.to_string(),
+            restart_count,
+            state: format!("{:?}", state),
+            restored_position_ns,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            detail,
+        };
+        self.add_bus_message(
+            id,
+            BusMessage {
+                timestamp: chrono::Utc::now(),
+                message_type: "Restarted".to_string(),
+                message: match &report.detail {
+                    Some(detail) => format!("Restart {}: {}", restart_count, detail),
+                    None => format!("Restart {}", restart_count),
+                },
+                source: None,
+                cause: None,
+                details: None,
+            },
+        );
+        Ok(report)
     }
 
     pub fn get_pipeline(&self, id: &str) -> Option<Arc<RwLock<PipelineInstance>>> {
@@ -116,10 +1152,21 @@ impl PipelineManager {
         pipelines.get(id).cloned()
     }
 
+    /// Get a reference-counted handle to the underlying GStreamer pipeline
+    pub fn get_pipeline_handle(&self, id: &str) -> McpResult<gst::Pipeline> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let instance = pipeline.read();
+        Ok(instance.pipeline.clone())
+    }
+
     pub fn remove_pipeline(&self, id: &str) -> McpResult<()> {
         let mut pipelines = self.pipelines.write();
         if let Some(_instance) = pipelines.remove(id) {
             // Pipeline cleanup happens in Drop trait
+            self.message_filters.write().remove(id);
+            self.release_id(id);
             Ok(())
         } else {
             Err(GStreamerMcpError::PipelineError(format!(
@@ -136,23 +1183,298 @@ impl PipelineManager {
         self.remove_pipeline(id)
     }
 
-    pub fn list_pipelines(&self) -> Vec<PipelineInfo> {
-        let pipelines = self.pipelines.read();
-        pipelines
-            .values()
-            .map(|instance| {
-                let inst = instance.read();
-                inst.info.clone()
+    /// Elements of a pipeline, including those nested in bins
+    pub fn pipeline_elements(&self, id: &str) -> McpResult<Vec<ElementSummary>> {
+        let pipeline = self.get_pipeline_handle(id)?;
+        let mut elements: Vec<ElementSummary> = pipeline
+            .iterate_recurse()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .map(|element| ElementSummary {
+                name: element.name().to_string(),
+                factory: element.factory().map(|f| f.name().to_string()),
             })
-            .collect()
+            .collect();
+        // Bins iterate the most recently added elements first; report in launch order
+        elements.reverse();
+        Ok(elements)
     }
 
-    pub fn set_pipeline_state(&self, id: &str, state: gst::State) -> McpResult<gst::State> {
-        let pipeline = self.get_pipeline(id).ok_or_else(|| {
-            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
-        })?;
+    /// Take the warnings and errors posted on a pipeline's bus (e.g. during
+    /// preroll) since the last call, record them and return them
+    pub fn collect_pending_problems(&self, id: &str) -> McpResult<Vec<BusMessage>> {
+        let pipeline = self.get_pipeline_handle(id)?;
+        let (drained, cause) = match self.get_pipeline(id) {
+            Some(instance) => {
+                let instance = instance.read();
+                (
+                    instance.bus_watch.as_ref().map(|w| w.drained.clone()),
+                    instance.cause.clone(),
+                )
+            }
+            None => (None, None),
+        };
+        let pending: Vec<gst::Message> = match (&drained, pipeline.bus()) {
+            (Some(drained), _) => drained.take_problems(),
+            (None, Some(bus)) => std::iter::from_fn(|| {
+                bus.pop_filtered(&[gst::MessageType::Warning, gst::MessageType::Error])
+            })
+            .collect(),
+            (None, None) => Vec::new(),
+        };
+        let watched = drained.is_some();
 
-        let mut instance = pipeline.write();
+        let mut messages = Vec::new();
+        for msg in pending {
+            let message = crate::bus_handler::message_to_bus_message(&msg);
+            let recorded = if watched {
+                // The bus handler recorded it when it was posted
+                apply_message_rules(&self.message_rules, message).map(|mut message| {
+                    message.cause = message.cause.or_else(|| cause.clone());
+                    message
+                })
+            } else {
+                self.add_bus_message(id, message)
+            };
+            messages.extend(recorded);
+        }
+        Ok(messages)
+    }
+
+    /// Send EOS to a playing pipeline and wait up to `drain_timeout` for it to
+    /// reach the sinks, then stop and remove the pipeline. Blocks the caller.
+    pub fn stop_pipeline_draining(
+        &self,
+        id: &str,
+        drain_timeout: Duration,
+    ) -> McpResult<StopReport> {
+        let started = Instant::now();
+        let pipeline = self.get_pipeline_handle(id)?;
+
+        let (path, detail) = if pipeline.current_state() != gst::State::Playing {
+            (StopPath::Immediate, None)
+        } else {
+            let watch = self.end_watch(id)?;
+            pipeline.send_event(gst::event::Eos::new());
+
+            let (outcome, detail) = watch.wait_for_eos(drain_timeout);
+            let path = match outcome {
+                EosOutcome::Failed => StopPath::DrainFailed,
+                EosOutcome::TimedOut => StopPath::DrainTimedOut,
+                _ => StopPath::Drained,
+            };
+            (path, detail)
+        };
+
+        let _ = self.set_pipeline_state(id, gst::State::Null);
+        self.remove_pipeline(id)?;
+
+        Ok(StopReport {
+            pipeline_id: id.to_string(),
+            path,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            teardown_completed: true,
+            detail,
+        })
+    }
+
+    /// Send EOS to a paused or playing pipeline, which keeps running. With
+    /// `wait`, block up to that long for EOS to reach the sinks, so muxers
+    /// such as `mp4mux` have finalized their files. The outcome is noted in
+    /// the pipeline's messages.
+    pub fn send_eos(&self, id: &str, wait: Option<Duration>) -> McpResult<EosReport> {
+        let started = Instant::now();
+        let pipeline = self.get_pipeline_handle(id)?;
+        let state = pipeline.current_state();
+        if state < gst::State::Paused {
+            return Err(GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' is {:?}; EOS needs it paused or playing",
+                id, state
+            )));
+        }
+        let watch = self.end_watch(id)?;
+        if !pipeline.send_event(gst::event::Eos::new()) {
+            return Err(GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' did not accept EOS",
+                id
+            )));
+        }
+
+        let (outcome, detail) = match wait {
+            Some(timeout) => watch.wait_for_eos(timeout),
+            None => (EosOutcome::Sent, None),
+        };
+        let report = EosReport {
+            pipeline_id: id.to_string(),
+            outcome,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            detail,
+        };
+        self.add_bus_message(
+            id,
+            BusMessage {
+                timestamp: chrono::Utc::now(),
+                message_type: "Eos".to_string(),
+                message: match &report.detail {
+                    Some(detail) => format!("EOS {}: {}", report.outcome, detail),
+                    None => format!("EOS {}", report.outcome),
+                },
+                source: None,
+                cause: None,
+                details: None,
+            },
+        );
+        Ok(report)
+    }
+
+    /// Wait up to `timeout` for a pipeline to reach `target`. Gives up early
+    /// when the state change fails, an error is posted, or no change toward
+    /// `target` is in progress. Does not change the state itself.
+    pub fn wait_for_state(
+        &self,
+        id: &str,
+        target: gst::State,
+        timeout: Duration,
+    ) -> McpResult<StateWait> {
+        let started = Instant::now();
+        let pipeline = self.get_pipeline_handle(id)?;
+        let mut messages = Vec::new();
+        loop {
+            let remaining = timeout.saturating_sub(started.elapsed());
+            let poll = remaining.min(Duration::from_millis(100));
+            let (result, current, pending) =
+                pipeline.state(gst::ClockTime::from_nseconds(poll.as_nanos() as u64));
+            messages.extend(self.collect_pending_problems(id)?);
+
+            let failed = result.is_err() || messages.iter().any(|m| m.message_type == "Error");
+            let idle = pending == gst::State::VoidPending;
+            if current == target || failed || idle || remaining.is_zero() {
+                return Ok(StateWait {
+                    pipeline_id: id.to_string(),
+                    target: format!("{:?}", target),
+                    reached: current == target,
+                    current: format!("{:?}", current),
+                    pending: (!idle).then(|| format!("{:?}", pending)),
+                    state_return: match result {
+                        Ok(gst::StateChangeSuccess::Success) => "success",
+                        Ok(gst::StateChangeSuccess::Async) => "async",
+                        Ok(gst::StateChangeSuccess::NoPreroll) => "no_preroll",
+                        Err(gst::StateChangeError) => "failure",
+                    }
+                    .to_string(),
+                    elapsed_ms: started.elapsed().as_millis() as u64,
+                    messages,
+                });
+            }
+        }
+    }
+
+    /// Wait up to `timeout` for a pipeline to post a message matching
+    /// `query`. With `include_recorded`, a matching message recorded since
+    /// the last launch, seek or state change ends the wait at once, so EOS
+    /// or an error posted before the call is not missed.
+    pub fn wait_for_message(
+        &self,
+        id: &str,
+        query: &MessageQuery,
+        timeout: Duration,
+        include_recorded: bool,
+    ) -> McpResult<MessageWait> {
+        let started = Instant::now();
+        let pipeline = self.get_pipeline_handle(id)?;
+        let recorded: Vec<BusMessage> = match self.get_pipeline(id) {
+            Some(instance) if include_recorded => {
+                let instance = instance.read();
+                let since = instance.cause.as_ref().map(|cause| cause.at);
+                instance
+                    .bus_messages
+                    .iter()
+                    .filter(|m| since.is_none_or(|since| m.timestamp >= since))
+                    .cloned()
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        let message = message_wait::wait_for_message(&pipeline, query, &recorded, timeout)?;
+        Ok(MessageWait {
+            pipeline_id: id.to_string(),
+            query: query.clone(),
+            matched: message.is_some(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            message,
+        })
+    }
+
+    /// Remove the pipeline immediately and tear it down on a watchdog thread,
+    /// waiting at most `timeout` for the state change to NULL. A hanging
+    /// teardown is abandoned rather than blocking the caller.
+    pub fn force_stop_pipeline(&self, id: &str, timeout: Duration) -> McpResult<StopReport> {
+        let started = Instant::now();
+        let instance = self.pipelines.write().remove(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        self.message_filters.write().remove(id);
+        self.release_id(id);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::Builder::new()
+            .name(format!("gst-force-stop-{}", id))
+            .spawn(move || {
+                let pipeline = instance.read().pipeline.clone();
+                let result = pipeline.set_state(gst::State::Null);
+                // Dropping the instance here keeps its own NULL transition
+                // off the caller's thread as well
+                drop(instance);
+                let _ = tx.send(result.is_ok());
+            })
+            .map_err(|e| {
+                GStreamerMcpError::PipelineError(format!("Failed to spawn teardown thread: {}", e))
+            })?;
+
+        let (teardown_completed, detail) = match rx.recv_timeout(timeout) {
+            Ok(true) => (true, None),
+            Ok(false) => (true, Some("State change to NULL failed".to_string())),
+            Err(_) => (
+                false,
+                Some(format!(
+                    "Teardown still running after {:?}; abandoned on watchdog thread",
+                    timeout
+                )),
+            ),
+        };
+
+        Ok(StopReport {
+            pipeline_id: id.to_string(),
+            path: StopPath::Forced,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            teardown_completed,
+            detail,
+        })
+    }
+
+    pub fn list_pipelines(&self) -> Vec<PipelineInfo> {
+        let pipelines = self.pipelines.read();
+        let mut infos: Vec<PipelineInfo> = pipelines
+            .values()
+            .map(|instance| {
+                let inst = instance.read();
+                inst.info.clone()
+            })
+            .collect();
+        infos.sort_by_key(|info| info.created_at);
+        infos
+    }
+
+    pub fn max_pipelines(&self) -> usize {
+        self.max_pipelines
+    }
+
+    pub fn set_pipeline_state(&self, id: &str, state: gst::State) -> McpResult<gst::State> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+
+        let mut instance = pipeline.write();
 
         // Set the state
         let state_change_result = instance.pipeline.set_state(state);
@@ -182,6 +1504,227 @@ impl PipelineManager {
         }
     }
 
+    /// Take a pipeline to PAUSED and wait up to `timeout` for it to preroll,
+    /// so it goes live without delay when set to PLAYING. Pipelines with live
+    /// sources do not preroll and are refused.
+    pub fn preroll_pipeline(&self, id: &str, timeout: Duration) -> McpResult<()> {
+        let pipeline = self.get_pipeline_handle(id)?;
+        match pipeline.set_state(gst::State::Paused) {
+            Ok(gst::StateChangeSuccess::NoPreroll) => {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "Pipeline '{}' has a live source, which cannot be held prerolled",
+                    id
+                )));
+            }
+            Ok(_) => {}
+            Err(gst::StateChangeError) => {
+                return Err(GStreamerMcpError::PipelineError(
+                    "Failed to change pipeline state to Paused".to_string(),
+                ));
+            }
+        }
+
+        // Wait without holding the instance lock, the bus handler needs it
+        let (result, _, _) =
+            pipeline.state(gst::ClockTime::from_nseconds(timeout.as_nanos() as u64));
+        match result {
+            Ok(gst::StateChangeSuccess::Success) => {
+                if let Some(instance) = self.get_pipeline(id) {
+                    let mut instance = instance.write();
+                    instance.info.state = PREROLLED_STATE.to_string();
+                    instance.info.last_state_change = chrono::Utc::now();
+                }
+                Ok(())
+            }
+            Ok(_) => Err(GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' did not preroll within {} ms",
+                id,
+                timeout.as_millis()
+            ))),
+            Err(gst::StateChangeError) => Err(GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' failed to preroll",
+                id
+            ))),
+        }
+    }
+
+    /// Seek a paused or playing pipeline to `position` and report where it
+    /// ended up. Flushing seeks wait up to `timeout` for the pipeline to
+    /// preroll at the new position before it is queried.
+    pub fn seek(
+        &self,
+        id: &str,
+        position: gst::ClockTime,
+        flags: gst::SeekFlags,
+        timeout: Duration,
+    ) -> McpResult<SeekResult> {
+        let pipeline = self.seekable_pipeline(id)?;
+        let duration = pipeline.query_duration::<gst::ClockTime>();
+        check_within(id, position, duration)?;
+
+        pipeline.seek_simple(flags, position).map_err(|_| {
+            GStreamerMcpError::PipelineError(format!(
+                "Seek to {} failed on pipeline '{}'",
+                position, id
+            ))
+        })?;
+        if flags.contains(gst::SeekFlags::FLUSH) {
+            // Wait without holding the instance lock, the bus handler needs it
+            let _ = pipeline.state(gst::ClockTime::from_nseconds(timeout.as_nanos() as u64));
+        }
+
+        Ok(SeekResult {
+            pipeline_id: id.to_string(),
+            requested_ns: position.nseconds(),
+            position_ns: pipeline
+                .query_position::<gst::ClockTime>()
+                .map(|p| p.nseconds()),
+            duration_ns: duration.map(|d| d.nseconds()),
+        })
+    }
+
+    /// Change the playback rate of a paused or playing pipeline with a seek
+    /// over the segment from `start` to `stop` (see [`rate_segment`]).
+    /// Negative rates play in reverse. A refused seek is reported as not
+    /// accepted rather than as an error.
+    pub fn set_playback_rate(
+        &self,
+        id: &str,
+        rate: f64,
+        start: Option<gst::ClockTime>,
+        stop: Option<gst::ClockTime>,
+        flags: gst::SeekFlags,
+        timeout: Duration,
+    ) -> McpResult<RateResult> {
+        let pipeline = self.seekable_pipeline(id)?;
+        let duration = pipeline.query_duration::<gst::ClockTime>();
+        let position = pipeline.query_position::<gst::ClockTime>();
+        let (start, stop) = rate_segment(rate, start, stop, position)?;
+        check_within(id, start, duration)?;
+        if let Some(stop) = stop {
+            check_within(id, stop, duration)?;
+        }
+
+        let sent = pipeline
+            .seek(
+                rate,
+                flags,
+                gst::SeekType::Set,
+                start,
+                if stop.is_some() {
+                    gst::SeekType::Set
+                } else {
+                    gst::SeekType::None
+                },
+                stop,
+            )
+            .is_ok();
+        if sent && flags.contains(gst::SeekFlags::FLUSH) {
+            // Wait without holding the instance lock, the bus handler needs it
+            let _ = pipeline.state(gst::ClockTime::from_nseconds(timeout.as_nanos() as u64));
+        }
+
+        let mut segment = gst::query::Segment::new(gst::Format::Time);
+        let applied = (sent && pipeline.query(&mut segment)).then(|| segment.result().0);
+        Ok(RateResult {
+            pipeline_id: id.to_string(),
+            requested_rate: rate,
+            accepted: sent && applied.is_none_or(|r| (r - rate).abs() < 1e-6),
+            rate: applied,
+            start_ns: start.nseconds(),
+            stop_ns: stop.map(|s| s.nseconds()),
+            position_ns: pipeline
+                .query_position::<gst::ClockTime>()
+                .map(|p| p.nseconds()),
+            duration_ns: duration.map(|d| d.nseconds()),
+        })
+    }
+
+    /// Add, remove or relink elements of a pipeline without stopping it
+    /// (see [`apply_modification`]). The change is noted in the pipeline's
+    /// messages.
+    pub fn modify_pipeline(
+        &self,
+        id: &str,
+        modification: &PipelineModification,
+        timeout: Duration,
+    ) -> McpResult<ModificationResult> {
+        let pipeline = self.get_pipeline_handle(id)?;
+        let mut result = apply_modification(&pipeline, modification, timeout)?;
+        result.pipeline_id = id.to_string();
+        self.add_bus_message(
+            id,
+            BusMessage {
+                timestamp: chrono::Utc::now(),
+                message_type: "Modified".to_string(),
+                message: result.summary(),
+                source: result.element.clone(),
+                cause: None,
+                details: None,
+            },
+        );
+        Ok(result)
+    }
+
+    /// Send an event to a pipeline or one of its elements (see
+    /// [`send_event`]). The event is noted in the pipeline's messages.
+    pub fn send_event(
+        &self,
+        id: &str,
+        element: Option<&str>,
+        event: &PipelineEvent,
+    ) -> McpResult<EventReport> {
+        let pipeline = self.get_pipeline_handle(id)?;
+        let mut report = send_event(&pipeline, element, event)?;
+        report.pipeline_id = id.to_string();
+        self.add_bus_message(
+            id,
+            BusMessage {
+                timestamp: chrono::Utc::now(),
+                message_type: "Event".to_string(),
+                message: format!(
+                    "{}{} sent to {}, {}",
+                    report.event,
+                    report
+                        .structure
+                        .as_ref()
+                        .map(|s| format!(" {}", s))
+                        .unwrap_or_default(),
+                    report.target,
+                    if report.handled {
+                        "handled"
+                    } else {
+                        "not handled"
+                    }
+                ),
+                source: element.map(|e| e.to_string()),
+                cause: None,
+                details: None,
+            },
+        );
+        Ok(report)
+    }
+
+    /// Handle of a paused or playing pipeline that can seek
+    fn seekable_pipeline(&self, id: &str) -> McpResult<gst::Pipeline> {
+        let pipeline = self.get_pipeline_handle(id)?;
+        let (_, state, _) = pipeline.state(gst::ClockTime::ZERO);
+        if state < gst::State::Paused {
+            return Err(GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' is {:?}; seeking needs it paused or playing",
+                id, state
+            )));
+        }
+        let mut seekable = gst::query::Seeking::new(gst::Format::Time);
+        if pipeline.query(&mut seekable) && !seekable.result().0 {
+            return Err(GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' is not seekable",
+                id
+            )));
+        }
+        Ok(pipeline)
+    }
+
     pub fn get_pipeline_status(&self, id: &str) -> McpResult<PipelineStatus> {
         let pipeline = self.get_pipeline(id).ok_or_else(|| {
             GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
@@ -207,6 +1750,9 @@ impl PipelineManager {
             .map(|t| t.nseconds() as i64)
             .unwrap_or(-1);
 
+        let progress = query_progress(&instance.pipeline, &mut instance.progress.lock());
+        let watchdog = instance.watchdog.as_ref().map(Watchdog::status);
+
         Ok(PipelineStatus {
             id: instance.info.id.clone(),
             description: instance.info.description.clone(),
@@ -220,13 +1766,42 @@ impl PipelineManager {
             duration,
             error_count: instance.info.error_count,
             warning_count: instance.info.warning_count,
+            suppressed_messages: instance.suppressed_messages,
             created_at: instance.info.created_at,
             last_state_change: instance.info.last_state_change,
+            timecode: current_timecode(&instance.pipeline).map(|reading| reading.timecode),
+            prerolled: current_state == gst::State::Paused
+                && instance.info.state == PREROLLED_STATE,
+            error_budget: instance
+                .budget
+                .as_ref()
+                .map(|tracker| tracker.status(Instant::now())),
+            audio_levels: instance
+                .audio_monitor
+                .as_ref()
+                .map(|monitor| monitor.lock().status()),
+            progress,
+            restart_count: instance.info.restart_count,
+            watchdog,
+            metadata: instance.info.metadata.clone(),
+            stop_at: instance.deadline,
+            remaining_ms: instance
+                .deadline
+                .map(|deadline| (deadline - chrono::Utc::now()).num_milliseconds().max(0) as u64),
+            loop_status: instance.looper.as_ref().map(Looper::status),
         })
     }
 
-    pub fn add_bus_message(&self, id: &str, message: BusMessage) {
-        if let Some(pipeline) = self.get_pipeline(id) {
+    /// Record a message of a pipeline and count it towards its health,
+    /// after the message rules. Returns the message as recorded, or None if
+    /// a rule dropped it.
+    pub fn add_bus_message(&self, id: &str, message: BusMessage) -> Option<BusMessage> {
+        let pipeline = self.get_pipeline(id)?;
+        let Some(message) = apply_message_rules(&self.message_rules, message) else {
+            pipeline.write().suppressed_messages += 1;
+            return None;
+        };
+        {
             let mut instance = pipeline.write();
 
             // Update error/warning counts
@@ -236,27 +1811,698 @@ impl PipelineManager {
                 _ => {}
             }
 
-            // Keep last 100 messages
-            if instance.bus_messages.len() >= 100 {
-                instance.bus_messages.remove(0);
+            let mut breach = None;
+            match instance.budget.as_mut() {
+                Some(tracker) => {
+                    breach = tracker
+                        .record(&message, Instant::now())
+                        .map(|(metric, count)| BudgetBreach {
+                            pipeline_id: id.to_string(),
+                            metric,
+                            count,
+                            limit: tracker.limit(metric).unwrap_or_default(),
+                            health: metric.health(),
+                            timestamp: message.timestamp,
+                        });
+                    instance.info.health = tracker.health();
+                }
+                None => match message.message_type.as_str() {
+                    "Error" => instance.info.health = PipelineHealth::Failed,
+                    "Warning" if instance.info.health == PipelineHealth::Healthy => {
+                        instance.info.health = PipelineHealth::Degraded;
+                    }
+                    _ => {}
+                },
+            }
+
+            instance.record_message(message.clone());
+
+            if let Some(breach) = breach {
+                self.report_breach(&mut instance, breach);
+            }
+        }
+        if BusEventKind::from_message_type(&message.message_type).is_some() {
+            if let Some(events) = self.bus_events.read().as_ref() {
+                let mut event = message.clone();
+                event.cause = event.cause.or_else(|| pipeline.read().cause.clone());
+                let _ = events.send((id.to_string(), event));
             }
-            instance.bus_messages.push(message);
         }
+        Some(message)
     }
 
-    pub fn get_bus_messages(&self, id: &str, limit: usize) -> Vec<BusMessage> {
-        if let Some(pipeline) = self.get_pipeline(id) {
+    fn report_breach(&self, instance: &mut PipelineInstance, breach: BudgetBreach) {
+        instance.record_message(BusMessage {
+            timestamp: breach.timestamp,
+            message_type: "ErrorBudget".to_string(),
+            message: format!(
+                "{} {} in the last minute, over the budget of {}; health is now {}",
+                breach.count, breach.metric, breach.limit, breach.health
+            ),
+            source: None,
+            cause: None,
+            details: None,
+        });
+        if let Some(alerts) = self.budget_alerts.read().as_ref() {
+            let _ = alerts.send(breach);
+        }
+    }
+
+    /// Send error budget breaches of every pipeline to `alerts`
+    pub fn set_budget_alerts(&self, alerts: UnboundedSender<BudgetBreach>) {
+        *self.budget_alerts.write() = Some(alerts);
+    }
+
+    /// Send caps renegotiations of every pipeline to `alerts`
+    pub fn set_caps_alerts(&self, alerts: UnboundedSender<CapsChange>) {
+        *self.caps_alerts.write() = Some(alerts);
+    }
+
+    /// Send the errors, warnings, EOS and state changes recorded for every
+    /// pipeline to `events`
+    pub fn set_bus_events(&self, events: BusEventSender) {
+        *self.bus_events.write() = Some(events);
+    }
+
+    /// Set the alert thresholds of a pipeline, or remove them with an empty
+    /// budget. Resets the health of the pipeline and re-arms the alerts.
+    pub fn set_error_budget(&self, id: &str, budget: ErrorBudget) -> McpResult<()> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let mut instance = pipeline.write();
+        instance.budget = (!budget.is_empty()).then(|| BudgetTracker::new(budget));
+        instance.info.health = PipelineHealth::Healthy;
+        Ok(())
+    }
+
+    /// Audio monitor of a pipeline, if one is attached
+    pub fn audio_monitor(&self, id: &str) -> McpResult<Option<Arc<Mutex<LevelMonitor>>>> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let instance = pipeline.read();
+        Ok(instance.audio_monitor.clone())
+    }
+
+    /// Loop a segment of a paused or playing pipeline, replacing any loop
+    /// it has. Without `settings`, the current play is the last. Returns the
+    /// loop status, if the pipeline loops.
+    pub fn set_loop(
+        &self,
+        id: &str,
+        settings: Option<LoopSettings>,
+    ) -> McpResult<Option<LoopStatus>> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let Some(settings) = settings else {
             let instance = pipeline.read();
-            let start = if instance.bus_messages.len() > limit {
-                instance.bus_messages.len() - limit
-            } else {
-                0
+            return Ok(instance.looper.as_ref().map(|looper| {
+                looper.stop();
+                looper.status()
+            }));
+        };
+
+        if settings.count == Some(0) {
+            return Err(GStreamerMcpError::PipelineError(
+                "count must be at least 1; leave it out to loop until turned off".to_string(),
+            ));
+        }
+        let handle = self.seekable_pipeline(id)?;
+        let duration = handle.query_duration::<gst::ClockTime>();
+        check_within(
+            id,
+            gst::ClockTime::from_nseconds(settings.start_ns),
+            duration,
+        )?;
+        if let Some(stop) = settings.stop_ns {
+            if stop <= settings.start_ns {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "The loop must end after it starts ({} ns <= {} ns)",
+                    stop, settings.start_ns
+                )));
+            }
+            check_within(id, gst::ClockTime::from_nseconds(stop), duration)?;
+        }
+
+        // The old loop stops answering before the new one seeks. The seek
+        // is made without holding the pipeline, which streaming threads lock
+        // to record messages.
+        let previous = pipeline.write().looper.take();
+        drop(previous);
+        let looper = Looper::start(&handle, settings).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' refused the seek to the start of the loop",
+                id
+            ))
+        })?;
+        let status = looper.status();
+        pipeline.write().looper = Some(looper);
+        self.add_bus_message(
+            id,
+            BusMessage {
+                timestamp: chrono::Utc::now(),
+                message_type: "Loop".to_string(),
+                message: format!(
+                    "Looping {} to {} {}",
+                    gst::ClockTime::from_nseconds(settings.start_ns),
+                    settings
+                        .stop_ns
+                        .map(|stop| gst::ClockTime::from_nseconds(stop).to_string())
+                        .unwrap_or_else(|| "the end".to_string()),
+                    match settings.count {
+                        Some(count) => format!("{} times", count),
+                        None => "until turned off".to_string(),
+                    }
+                ),
+                source: None,
+                cause: None,
+                details: None,
+            },
+        );
+        Ok(Some(status))
+    }
+
+    pub fn set_audio_monitor(&self, id: &str, monitor: Arc<Mutex<LevelMonitor>>) -> McpResult<()> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        pipeline.write().audio_monitor = Some(monitor);
+        Ok(())
+    }
+
+    /// Collect data statistics on a pad of a pipeline (see
+    /// [`add_stats_probe`]). A probe already on the pad is replaced, which
+    /// starts its statistics over. Returns the pad as `element.pad`.
+    pub fn add_probe(&self, id: &str, pad_spec: &str) -> McpResult<String> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let mut instance = pipeline.write();
+        let probe = add_stats_probe(&instance.pipeline, pad_spec)?;
+        let name = probe.name();
+        if let Some(previous) = instance.probes.insert(name.clone(), probe) {
+            previous.remove();
+        }
+        Ok(name)
+    }
+
+    /// Statistics of the probes of a pipeline, or of the one on `pad_spec`,
+    /// removing the probes with `remove`
+    pub fn probe_stats(
+        &self,
+        id: &str,
+        pad_spec: Option<&str>,
+        remove: bool,
+    ) -> McpResult<Vec<ProbeStats>> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let mut instance = pipeline.write();
+        let names: Vec<String> = match pad_spec {
+            Some(spec) => {
+                let pad = find_pad(&instance.pipeline, spec)?;
+                let name = pad_name(&pad);
+                if !instance.probes.contains_key(&name) {
+                    return Err(GStreamerMcpError::PipelineError(format!(
+                        "No probe on '{}'; add one with gst_add_probe",
+                        name
+                    )));
+                }
+                vec![name]
+            }
+            None => instance.probes.keys().cloned().collect(),
+        };
+
+        let now = Instant::now();
+        let mut stats: Vec<ProbeStats> = names
+            .iter()
+            .filter_map(|name| instance.probes.get(name))
+            .map(|probe| probe.stats.lock().stats(now))
+            .collect();
+        stats.sort_by(|a, b| a.pad.cmp(&b.pad));
+        if remove {
+            for name in &names {
+                if let Some(probe) = instance.probes.remove(name) {
+                    probe.remove();
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Apply `policy` to the errors a pipeline posts from now on
+    pub fn set_watchdog(&self, id: &str, policy: WatchdogPolicy) -> McpResult<()> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let mut instance = pipeline.write();
+        let errors = instance.info.error_count;
+        instance.watchdog = Some(Watchdog::new(policy, errors));
+        Ok(())
+    }
+
+    /// Hand a pipeline to `owner`, replacing its labels when given. With
+    /// `expected_owner`, the pipeline must still belong to it, so two
+    /// takeovers cannot both succeed.
+    pub fn transfer_pipeline(
+        &self,
+        id: &str,
+        owner: Option<String>,
+        expected_owner: Option<&str>,
+        labels: Option<BTreeMap<String, String>>,
+    ) -> McpResult<TransferReport> {
+        if let Some(labels) = &labels {
+            check_labels(labels)?;
+        }
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let report = {
+            let mut instance = pipeline.write();
+            if let Some(expected) = expected_owner {
+                if instance.info.owner.as_deref() != Some(expected) {
+                    return Err(GStreamerMcpError::PipelineError(format!(
+                        "Pipeline '{}' belongs to {}, not '{}'",
+                        id,
+                        instance
+                            .info
+                            .owner
+                            .as_deref()
+                            .map_or("no one".to_string(), |o| format!("'{}'", o)),
+                        expected
+                    )));
+                }
+            }
+            if let Some(labels) = labels {
+                instance.info.labels = labels;
+            }
+            let previous_owner = std::mem::replace(&mut instance.info.owner, owner.clone());
+            TransferReport {
+                pipeline_id: id.to_string(),
+                previous_owner,
+                owner,
+                labels: instance.info.labels.clone(),
+            }
+        };
+        self.add_bus_message(
+            id,
+            BusMessage {
+                timestamp: chrono::Utc::now(),
+                message_type: "Ownership".to_string(),
+                message: format!(
+                    "Handed from {} to {}",
+                    report.previous_owner.as_deref().unwrap_or("no one"),
+                    report.owner.as_deref().unwrap_or("no one")
+                ),
+                source: None,
+                cause: None,
+                details: None,
+            },
+        );
+        Ok(report)
+    }
+
+    /// Clear the owner of every pipeline after its client left. Returns the
+    /// pipelines orphaned.
+    pub fn orphan_pipelines(&self) -> Vec<String> {
+        let ids: Vec<String> = self.pipelines.read().keys().cloned().collect();
+        for id in &ids {
+            let previous = self
+                .get_pipeline(id)
+                .and_then(|pipeline| pipeline.write().info.owner.take());
+            self.add_bus_message(
+                id,
+                BusMessage {
+                    timestamp: chrono::Utc::now(),
+                    message_type: "Orphaned".to_string(),
+                    message: match previous {
+                        Some(owner) => format!("Owner '{}' disconnected", owner),
+                        None => "Client disconnected".to_string(),
+                    },
+                    source: None,
+                    cause: None,
+                    details: None,
+                },
+            );
+        }
+        ids
+    }
+
+    /// Stop and remove the pipelines that reached EOS or failed. Returns the
+    /// pipelines removed.
+    pub fn stop_finished_pipelines(&self) -> Vec<String> {
+        let ids: Vec<String> = self.pipelines.read().keys().cloned().collect();
+        ids.into_iter()
+            .filter(|id| {
+                let _ = self.collect_pending_problems(id);
+                let Some(pipeline) = self.get_pipeline(id) else {
+                    return false;
+                };
+                let (failed, eos) = {
+                    let instance = pipeline.read();
+                    let eos = match &instance.bus_watch {
+                        Some(watch) => watch.drained.take_eos(),
+                        None => instance.pipeline.bus().is_some_and(|bus| {
+                            bus.pop_filtered(&[gst::MessageType::Eos]).is_some()
+                        }),
+                    };
+                    (instance.info.health == PipelineHealth::Failed, eos)
+                };
+                (failed || eos) && self.stop_pipeline(id).is_ok()
+            })
+            .collect()
+    }
+
+    /// Record the tool call behind a launch, seek or state change of a
+    /// pipeline; the messages that follow are tagged with it
+    pub fn set_cause(&self, id: &str, cause: Cause) {
+        if let Some(pipeline) = self.get_pipeline(id) {
+            pipeline.write().cause = Some(cause);
+        }
+    }
+
+    /// Tool call behind the last launch, seek or state change of a pipeline
+    pub fn cause(&self, id: &str) -> Option<Cause> {
+        self.get_pipeline(id)?.read().cause.clone()
+    }
+
+    /// Set the owner of a new pipeline; use `transfer_pipeline` to hand over
+    /// a running one
+    pub fn set_owner(&self, id: &str, owner: Option<String>) -> McpResult<()> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        pipeline.write().info.owner = owner;
+        Ok(())
+    }
+
+    /// Replace the labels of a pipeline
+    pub fn set_labels(&self, id: &str, labels: BTreeMap<String, String>) -> McpResult<()> {
+        check_labels(&labels)?;
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        pipeline.write().info.labels = labels;
+        Ok(())
+    }
+
+    /// Merge `update` into the metadata of a pipeline, or replace it all
+    /// with `replace`. Returns the metadata now stored.
+    pub fn set_metadata(
+        &self,
+        id: &str,
+        update: serde_json::Map<String, serde_json::Value>,
+        replace: bool,
+    ) -> McpResult<serde_json::Map<String, serde_json::Value>> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let mut instance = pipeline.write();
+        let mut metadata = if replace {
+            serde_json::Map::new()
+        } else {
+            instance.info.metadata.clone()
+        };
+        merge_metadata(&mut metadata, update);
+        let size = serde_json::to_vec(&metadata)?.len();
+        if size > MAX_METADATA_BYTES {
+            return Err(GStreamerMcpError::PipelineError(format!(
+                "Metadata of {} bytes exceeds the limit of {} bytes",
+                size, MAX_METADATA_BYTES
+            )));
+        }
+        instance.info.metadata = metadata.clone();
+        Ok(metadata)
+    }
+
+    /// Stop and remove the pipeline at `deadline`
+    pub fn set_deadline(&self, id: &str, deadline: chrono::DateTime<chrono::Utc>) -> McpResult<()> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        pipeline.write().deadline = Some(deadline);
+        Ok(())
+    }
+
+    /// Stop and remove the pipelines whose deadline is before `now`
+    pub fn expire_pipelines(&self, now: chrono::DateTime<chrono::Utc>) -> Vec<ExpiredPipeline> {
+        let expired: Vec<(String, chrono::DateTime<chrono::Utc>)> = self
+            .pipelines
+            .read()
+            .iter()
+            .filter_map(|(id, instance)| {
+                instance
+                    .read()
+                    .deadline
+                    .filter(|deadline| *deadline <= now)
+                    .map(|deadline| (id.clone(), deadline))
+            })
+            .collect();
+
+        expired
+            .into_iter()
+            .map(|(pipeline_id, deadline)| {
+                let error = self
+                    .set_pipeline_state(&pipeline_id, gst::State::Null)
+                    .err()
+                    .map(|e| e.to_string());
+                // Removed even if it did not stop cleanly, so it does not
+                // expire again
+                let _ = self.remove_pipeline(&pipeline_id);
+                ExpiredPipeline {
+                    pipeline_id,
+                    deadline,
+                    error,
+                }
+            })
+            .collect()
+    }
+
+    /// Check the pipelines that have a watchdog for new errors and act on
+    /// them. Blocks while pipelines restart, each for up to `timeout`.
+    pub fn check_watchdogs(&self, timeout: Duration) -> Vec<WatchdogEvent> {
+        let ids: Vec<String> = self
+            .pipelines
+            .read()
+            .iter()
+            .filter(|(_, instance)| instance.read().watchdog.is_some())
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        let mut events = Vec::new();
+        for id in ids {
+            // Errors still on an unwatched bus have not been counted yet
+            let _ = self.collect_pending_problems(&id);
+            let Some(pipeline) = self.get_pipeline(&id) else {
+                continue;
             };
-            instance.bus_messages[start..].to_vec()
+            let action = {
+                let mut instance = pipeline.write();
+                let errors = instance.info.error_count;
+                match instance.watchdog.as_mut() {
+                    Some(watchdog) => watchdog.observe(errors, Instant::now()),
+                    None => continue,
+                }
+            };
+
+            let (action, message) = match action {
+                WatchdogAction::None => continue,
+                WatchdogAction::Scheduled(backoff) => (
+                    "scheduled",
+                    format!("Error detected; restarting in {} ms", backoff.as_millis()),
+                ),
+                WatchdogAction::Restart => match self.restart_pipeline(&id, None, true, timeout) {
+                    Ok(report) => (
+                        "restarted",
+                        format!("Restarted (restart {})", report.restart_count),
+                    ),
+                    Err(e) => ("failed", format!("Restart failed: {}", e)),
+                },
+                WatchdogAction::Stop => match self.stop_pipeline(&id) {
+                    Ok(()) => ("stopped", "Stopped after an error".to_string()),
+                    Err(e) => ("failed", format!("Stop failed: {}", e)),
+                },
+                WatchdogAction::GiveUp => (
+                    "gave_up",
+                    "Error detected with no restarts left; leaving the pipeline as it is"
+                        .to_string(),
+                ),
+            };
+            self.add_bus_message(
+                &id,
+                BusMessage {
+                    timestamp: chrono::Utc::now(),
+                    message_type: "Watchdog".to_string(),
+                    message: message.clone(),
+                    source: None,
+                    cause: None,
+                    details: None,
+                },
+            );
+            events.push(WatchdogEvent {
+                pipeline_id: id,
+                action: action.to_string(),
+                message,
+            });
+        }
+        events
+    }
+
+    /// Keep a stall report with the pipeline and note it in its messages
+    pub fn add_stall_report(&self, id: &str, report: StallReport) {
+        let Some(pipeline) = self.get_pipeline(id) else {
+            return;
+        };
+        let message = BusMessage {
+            timestamp: report.detected_at,
+            message_type: "Stall".to_string(),
+            message: format!(
+                "Stuck changing from {} to {} for {} ms; suspects: {}",
+                report.current_state,
+                report.pending_state,
+                report.stalled_ms,
+                if report.stuck.is_empty() {
+                    "none".to_string()
+                } else {
+                    report.stuck.join(", ")
+                }
+            ),
+            source: None,
+            cause: None,
+            details: None,
+        };
+        {
+            let mut instance = pipeline.write();
+            if instance.stall_reports.len() >= MAX_STALL_REPORTS {
+                instance.stall_reports.remove(0);
+            }
+            instance.stall_reports.push(report);
+        }
+        self.add_bus_message(id, message);
+    }
+
+    /// Stall reports of a pipeline, oldest first
+    pub fn stall_reports(&self, id: &str) -> McpResult<Vec<StallReport>> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let instance = pipeline.read();
+        Ok(instance.stall_reports.clone())
+    }
+
+    /// Register (or replace) the message filter of a subscriber for a pipeline
+    pub fn set_message_filter(
+        &self,
+        id: &str,
+        subscriber: &str,
+        filter: MessageFilter,
+    ) -> McpResult<()> {
+        // Held until the filter is in, so a removal of the pipeline either
+        // comes first and is seen here, or comes after and drops the filter
+        let pipelines = self.pipelines.read();
+        if !pipelines.contains_key(id) {
+            return Err(GStreamerMcpError::PipelineError(format!(
+                "Pipeline '{}' not found",
+                id
+            )));
+        }
+
+        self.message_filters
+            .write()
+            .entry(id.to_string())
+            .or_default()
+            .insert(subscriber.to_string(), filter);
+        Ok(())
+    }
+
+    /// Remove the message filter of a subscriber. Returns whether one existed.
+    pub fn remove_message_filter(&self, id: &str, subscriber: &str) -> bool {
+        let mut filters = self.message_filters.write();
+        let removed = filters
+            .get_mut(id)
+            .map(|subscribers| subscribers.remove(subscriber).is_some())
+            .unwrap_or(false);
+        if filters
+            .get(id)
+            .is_some_and(|subscribers| subscribers.is_empty())
+        {
+            filters.remove(id);
+        }
+        removed
+    }
+
+    pub fn get_message_filter(&self, id: &str, subscriber: &str) -> Option<MessageFilter> {
+        self.message_filters
+            .read()
+            .get(id)
+            .and_then(|subscribers| subscribers.get(subscriber))
+            .cloned()
+    }
+
+    /// List the subscribers with a registered filter for a pipeline
+    pub fn list_message_filters(&self, id: &str) -> Vec<(String, MessageFilter)> {
+        let mut filters: Vec<(String, MessageFilter)> = self
+            .message_filters
+            .read()
+            .get(id)
+            .map(|subscribers| {
+                subscribers
+                    .iter()
+                    .map(|(name, filter)| (name.clone(), filter.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        filters.sort_by(|a, b| a.0.cmp(&b.0));
+        filters
+    }
+
+    /// Get the most recent bus messages that pass the subscriber's filter.
+    /// Without a registered filter all messages are returned.
+    pub fn get_filtered_bus_messages(
+        &self,
+        id: &str,
+        subscriber: &str,
+        limit: usize,
+    ) -> Vec<BusMessage> {
+        let Some(filter) = self.get_message_filter(id, subscriber) else {
+            return self.get_bus_messages(id, limit);
+        };
+
+        if let Some(pipeline) = self.get_pipeline(id) {
+            let instance = pipeline.read();
+            let mut messages: Vec<BusMessage> = instance
+                .bus_messages
+                .iter()
+                .rev()
+                .filter(|m| filter.matches(m))
+                .take(limit)
+                .cloned()
+                .collect();
+            messages.reverse();
+            messages
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn get_bus_messages(&self, id: &str, limit: usize) -> Vec<BusMessage> {
+        if let Some(pipeline) = self.get_pipeline(id) {
+            pipeline.read().bus_messages.latest(limit)
         } else {
             Vec::new()
         }
     }
+
+    /// Page through the recorded bus messages of a pipeline
+    pub fn query_bus_messages(&self, id: &str, query: &HistoryQuery) -> McpResult<HistoryPage> {
+        let pipeline = self.get_pipeline(id).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Pipeline '{}' not found", id))
+        })?;
+        let page = pipeline.read().bus_messages.query(query);
+        Ok(page)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -269,8 +2515,122 @@ pub struct PipelineStatus {
     pub duration: i64,
     pub error_count: u32,
     pub warning_count: u32,
+    /// Messages dropped by the configured message rules
+    #[serde(default)]
+    pub suppressed_messages: u64,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub last_state_change: chrono::DateTime<chrono::Utc>,
+    /// SMPTE timecode of the last frame rendered by a sink, if timecoded
+    #[serde(default)]
+    pub timecode: Option<String>,
+    /// Held in PAUSED by a preroll-only launch, ready to go live
+    #[serde(default)]
+    pub prerolled: bool,
+    /// Alert thresholds and their usage, when set
+    #[serde(default)]
+    pub error_budget: Option<BudgetStatus>,
+    /// Levels and conditions of the audio monitor, when attached
+    #[serde(default)]
+    pub audio_levels: Option<AudioLevelStatus>,
+    /// Segment-aware position for progress bars; unlike `position`, it does
+    /// not go backwards during seeks and accounts for the playback rate
+    #[serde(default)]
+    pub progress: Option<Progress>,
+    /// Times the pipeline was restarted, by hand or by its watchdog
+    #[serde(default)]
+    pub restart_count: u32,
+    #[serde(default)]
+    pub watchdog: Option<WatchdogStatus>,
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+    /// When the pipeline will be stopped, if its run time is limited
+    #[serde(default)]
+    pub stop_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    pub remaining_ms: Option<u64>,
+    /// Segment looped by `gst_set_loop`, with the plays done and left
+    #[serde(default, rename = "loop")]
+    pub loop_status: Option<LoopStatus>,
+}
+
+/// Apply the watchdog policies of the pipelines once a second. What the
+/// watchdogs do is sent to the client as `pipeline_watchdog`
+/// notifications.
+pub fn spawn_watchdog(
+    pipeline_manager: Arc<PipelineManager>,
+    notifier: Notifier,
+    restart_timeout: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(WATCHDOG_INTERVAL).await;
+
+            let manager = pipeline_manager.clone();
+            let Ok(events) =
+                tokio::task::spawn_blocking(move || manager.check_watchdogs(restart_timeout)).await
+            else {
+                continue;
+            };
+            for event in events {
+                tracing::warn!(
+                    "Watchdog of pipeline {}: {}",
+                    event.pipeline_id,
+                    event.message
+                );
+                let level = match event.action.as_str() {
+                    "restarted" => LoggingLevel::Info,
+                    "failed" | "gave_up" => LoggingLevel::Error,
+                    _ => LoggingLevel::Warning,
+                };
+                let mut data = serde_json::to_value(&event).unwrap_or_default();
+                attach_cause(
+                    &mut data,
+                    pipeline_manager.cause(&event.pipeline_id).as_ref(),
+                );
+                notifier.notify("pipeline_watchdog", level, data).await;
+            }
+        }
+    })
+}
+
+/// Stop and remove pipelines once their maximum duration or stop time has
+/// passed, checking every second. Each is reported to the client as a
+/// `pipeline_expired` notification.
+pub fn spawn_deadline_enforcer(
+    pipeline_manager: Arc<PipelineManager>,
+    notifier: Notifier,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(DEADLINE_INTERVAL).await;
+
+            let manager = pipeline_manager.clone();
+            let Ok(expired) =
+                tokio::task::spawn_blocking(move || manager.expire_pipelines(chrono::Utc::now()))
+                    .await
+            else {
+                continue;
+            };
+            for pipeline in expired {
+                let level = match &pipeline.error {
+                    Some(e) => {
+                        tracing::warn!(
+                            "Pipeline {} did not stop cleanly at its deadline: {}",
+                            pipeline.pipeline_id,
+                            e
+                        );
+                        LoggingLevel::Warning
+                    }
+                    None => {
+                        tracing::info!("Pipeline {} stopped at its deadline", pipeline.pipeline_id);
+                        LoggingLevel::Info
+                    }
+                };
+                let data = serde_json::to_value(&pipeline).unwrap_or_default();
+                notifier.notify("pipeline_expired", level, data).await;
+            }
+        }
+    })
 }
 
 pub fn validate_pipeline_description(description: &str) -> McpResult<Vec<String>> {
@@ -278,7 +2638,7 @@ pub fn validate_pipeline_description(description: &str) -> McpResult<Vec<String>
     ensure_gstreamer_initialized()?;
 
     // Try to parse the pipeline
-    match gst::parse::launch(description) {
+    match parse_launch(description, "Invalid pipeline description") {
         Ok(element) => {
             // Extract element names from the pipeline description
             // This is a simple parser that extracts element names
@@ -287,7 +2647,7 @@ pub fn validate_pipeline_description(description: &str) -> McpResult<Vec<String>
 
             for part in parts {
                 // Extract the element name (first word before any properties)
-                if let Some(element_name) = part.trim().split_whitespace().next() {
+                if let Some(element_name) = part.split_whitespace().next() {
                     // Remove any property assignments
                     let clean_name = element_name.split('=').next().unwrap_or(element_name);
                     if !clean_name.is_empty() {
@@ -303,16 +2663,628 @@ pub fn validate_pipeline_description(description: &str) -> McpResult<Vec<String>
 
             Ok(elements)
         }
-        Err(e) => Err(GStreamerMcpError::PipelineError(format!(
-            "Invalid pipeline description: {}",
-            e
+        Err(e) => Err(e),
+    }
+}
+
+/// Tee to branch from: the named one, or the only tee of the pipeline
+fn find_tee(pipeline: &gst::Pipeline, tee_name: Option<&str>) -> McpResult<gst::Element> {
+    let tees: Vec<gst::Element> = pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.factory().is_some_and(|f| f.name() == "tee"))
+        .filter(|e| tee_name.is_none_or(|name| e.name() == name))
+        .collect();
+    match (tees.len(), tee_name) {
+        (1, _) => Ok(tees[0].clone()),
+        (0, Some(name)) => Err(GStreamerMcpError::PipelineError(format!(
+            "No tee named '{}' in pipeline",
+            name
         ))),
+        (0, None) => Err(GStreamerMcpError::PipelineError(
+            "Pipeline has no tee to branch from. Add e.g. 'tee name=t' where the branch should start"
+                .to_string(),
+        )),
+        _ => Err(GStreamerMcpError::PipelineError(
+            "Pipeline has several tees; name the one to branch from".to_string(),
+        )),
     }
 }
 
+/// Branch `description` off a tee of a running pipeline. The description
+/// must end in `fakesink name=sink signal-handoffs=true`; every buffer
+/// reaching it is passed to `on_buffer` from a streaming thread. The branch
+/// is named `<prefix><n>` and its name returned.
+pub fn attach_tee_branch(
+    pipeline: &gst::Pipeline,
+    tee_name: Option<&str>,
+    prefix: &str,
+    description: &str,
+    on_buffer: impl Fn(&str, &gst::Buffer) + Send + Sync + 'static,
+) -> McpResult<String> {
+    let tee = find_tee(pipeline, tee_name)?;
+    let branch_name = (0..)
+        .map(|i| format!("{}{}", prefix, i))
+        .find(|name| pipeline.by_name(name).is_none())
+        .expect("unbounded range");
+    let branch = gst::parse::bin_from_description(description, true)
+        .map_err(|e| GStreamerMcpError::PipelineError(format!("Failed to build branch: {}", e)))?;
+    branch.set_property("name", &branch_name);
+
+    let sink = branch
+        .by_name("sink")
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Branch has no sink".to_string()))?;
+    let name = branch_name.clone();
+    sink.connect("handoff", false, move |values| {
+        let buffer = values.get(1).and_then(|v| v.get::<gst::Buffer>().ok())?;
+        on_buffer(&name, &buffer);
+        None
+    });
+
+    let link_error = |e: &dyn std::fmt::Display| {
+        GStreamerMcpError::PipelineError(format!("Failed to attach {}: {}", branch_name, e))
+    };
+    pipeline.add(&branch).map_err(|e| link_error(&e))?;
+    let tee_pad = tee
+        .request_pad_simple("src_%u")
+        .ok_or_else(|| link_error(&"tee refused a new src pad"))?;
+    let branch_pad = branch
+        .static_pad("sink")
+        .ok_or_else(|| link_error(&"branch has no sink pad"))?;
+    if let Err(e) = tee_pad.link(&branch_pad) {
+        tee.release_request_pad(&tee_pad);
+        let _ = pipeline.remove(&branch);
+        return Err(link_error(&e));
+    }
+    branch
+        .sync_state_with_parent()
+        .map_err(|e| link_error(&e))?;
+
+    Ok(branch_name)
+}
+
+/// Create an element, failing with a hint when its plugin is missing
+pub fn make_element(factory: &str, name: String) -> McpResult<gst::Element> {
+    gst::ElementFactory::make(factory)
+        .name(name)
+        .build()
+        .map_err(|_| GStreamerMcpError::ElementNotFound(format!("{} is not installed", factory)))
+}
+
+/// Parse the string form of a property value, as gst-launch does (enum
+/// nicks, fractions, caps, ...), also accepting human units such as `2Mbps`
+pub fn parse_property_value(
+    element: &gst::Element,
+    name: &str,
+    value: &str,
+) -> McpResult<glib::Value> {
+    let pspec = element.find_property(name).ok_or_else(|| {
+        GStreamerMcpError::PropertyError(format!("{} has no property '{}'", element.name(), name))
+    })?;
+    let converted = crate::units::normalize_value(&pspec, value).map_err(|e| {
+        GStreamerMcpError::PropertyError(format!(
+            "Invalid value for property '{}' of {}: {}",
+            name,
+            element.name(),
+            e
+        ))
+    })?;
+    let value = converted.as_ref().map_or(value, |c| c.normalized.as_str());
+    let parsed = glib::Value::deserialize(value, pspec.value_type()).map_err(|_| {
+        crate::properties::invalid_value_error(
+            &pspec,
+            value,
+            format!(
+                "Invalid value '{}' for property '{}' of {}",
+                value,
+                name,
+                element.name()
+            ),
+        )
+    })?;
+    crate::properties::check_value(&pspec, &parsed).map_err(|e| {
+        GStreamerMcpError::PropertyError(format!(
+            "Invalid value for property '{}' of {}: {}",
+            name,
+            element.name(),
+            e
+        ))
+    })?;
+    Ok(parsed)
+}
+
+/// Set a property from its string form
+pub fn set_property_str(element: &gst::Element, name: &str, value: &str) -> McpResult<()> {
+    let parsed = parse_property_value(element, name, value)?;
+    element.set_property_from_value(name, &parsed);
+    Ok(())
+}
+
+/// Whether an element is a video encoder or a video sink, the points where
+/// video leaves the pipeline as it will be recorded, streamed or shown
+fn is_video_output(element: &gst::Element) -> bool {
+    let Some(factory) = element.factory() else {
+        return false;
+    };
+    let klass = factory.metadata("klass").unwrap_or_default();
+    (klass.contains("Encoder") || klass.contains("Sink")) && klass.contains("Video")
+}
+
+/// Splice the elements built by `make_chain` in front of every linked video
+/// encoder and video sink of a pipeline that is not running yet. The chain
+/// is linked in order; `make_chain` gets the index of the output. Returns
+/// the names of the outputs the chain was inserted before.
+pub fn insert_before_video_outputs(
+    pipeline: &gst::Pipeline,
+    purpose: &str,
+    make_chain: impl Fn(usize) -> McpResult<Vec<gst::Element>>,
+) -> McpResult<Vec<String>> {
+    // Collect first; the pipeline is modified below
+    let targets: Vec<gst::Element> = pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(is_video_output)
+        .collect();
+
+    let mut applied = Vec::new();
+    for (i, target) in targets.iter().enumerate() {
+        // Pads linked later (e.g. behind decodebin) cannot be intercepted
+        let Some(sink_pad) = target.sink_pads().into_iter().next() else {
+            continue;
+        };
+        let Some(upstream) = sink_pad.peer() else {
+            continue;
+        };
+        let Some(bin) = target.parent().and_then(|p| p.downcast::<gst::Bin>().ok()) else {
+            continue;
+        };
+
+        let chain = make_chain(i)?;
+        let (Some(first), Some(last)) = (chain.first(), chain.last()) else {
+            continue;
+        };
+        let link_error = |e: &dyn std::fmt::Display| {
+            GStreamerMcpError::PipelineError(format!(
+                "Failed to insert {} before {}: {}",
+                purpose,
+                target.name(),
+                e
+            ))
+        };
+        bin.add_many(&chain).map_err(|e| link_error(&e))?;
+        upstream.unlink(&sink_pad).map_err(|e| link_error(&e))?;
+        let first_sink = first
+            .static_pad("sink")
+            .ok_or_else(|| link_error(&format!("{} has no sink pad", first.name())))?;
+        let last_src = last
+            .static_pad("src")
+            .ok_or_else(|| link_error(&format!("{} has no src pad", last.name())))?;
+        upstream.link(&first_sink).map_err(|e| link_error(&e))?;
+        gst::Element::link_many(&chain).map_err(|e| link_error(&e))?;
+        last_src.link(&sink_pad).map_err(|e| link_error(&e))?;
+
+        applied.push(target.name().to_string());
+    }
+    Ok(applied)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
     pub is_valid: bool,
     pub elements: Vec<String>,
     pub error: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchdog() {
+        let policy = WatchdogPolicy {
+            on_error: OnError::Restart,
+            max_retries: Some(2),
+            backoff_ms: Some(1000),
+        };
+        assert_eq!(policy.backoff(3), Duration::from_secs(8));
+        assert_eq!(policy.backoff(10), MAX_WATCHDOG_BACKOFF);
+
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut watchdog = Watchdog::new(policy, 1);
+        // Errors from before the policy was set do not count
+        assert_eq!(watchdog.observe(1, at(0)), WatchdogAction::None);
+        assert_eq!(
+            watchdog.observe(2, at(1)),
+            WatchdogAction::Scheduled(Duration::from_secs(1))
+        );
+        assert_eq!(watchdog.observe(3, at(1)), WatchdogAction::None);
+        assert_eq!(watchdog.observe(3, at(2)), WatchdogAction::Restart);
+
+        // The restarted pipeline counts its errors from zero
+        let mut watchdog = watchdog.restarted();
+        assert_eq!(
+            watchdog.observe(1, at(3)),
+            WatchdogAction::Scheduled(Duration::from_secs(2))
+        );
+        assert_eq!(watchdog.observe(1, at(5)), WatchdogAction::Restart);
+        let mut watchdog = watchdog.restarted();
+        assert_eq!(watchdog.observe(1, at(6)), WatchdogAction::GiveUp);
+        assert_eq!(watchdog.observe(2, at(7)), WatchdogAction::None);
+        assert!(watchdog.status().gave_up);
+
+        // A minute without errors gives the retries back
+        assert_eq!(watchdog.observe(2, at(70)), WatchdogAction::None);
+        assert_eq!(watchdog.status().retries, 0);
+        assert_eq!(
+            watchdog.observe(3, at(71)),
+            WatchdogAction::Scheduled(Duration::from_secs(1))
+        );
+
+        let mut stop = Watchdog::new(
+            WatchdogPolicy {
+                on_error: OnError::Stop,
+                ..Default::default()
+            },
+            0,
+        );
+        assert_eq!(stop.observe(0, at(0)), WatchdogAction::None);
+        assert_eq!(stop.observe(1, at(1)), WatchdogAction::Stop);
+        let mut ignore = Watchdog::new(WatchdogPolicy::default(), 0);
+        assert_eq!(ignore.observe(5, at(1)), WatchdogAction::None);
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let ns = |text: &str| parse_position(text).map(|t| t.nseconds()).ok();
+        assert_eq!(ns("83500000000"), Some(83_500_000_000));
+        assert_eq!(ns("1:23.5"), Some(83_500_000_000));
+        assert_eq!(ns("83.5"), Some(83_500_000_000));
+        assert_eq!(ns("01:02:03.25"), Some(3_723_250_000_000));
+        assert_eq!(ns("90:00"), Some(5_400_000_000_000));
+        assert_eq!(ns("1:75"), None);
+        assert_eq!(ns("1:60:00"), None);
+        assert_eq!(ns("-5"), None);
+        assert_eq!(ns("1:2:3:4"), None);
+        assert_eq!(ns("soon"), None);
+    }
+
+    #[test]
+    fn test_rate_segment() {
+        let t = gst::ClockTime::from_seconds;
+        let ns = |segment: (gst::ClockTime, Option<gst::ClockTime>)| {
+            (segment.0.seconds(), segment.1.map(|s| s.seconds()))
+        };
+        let segment =
+            |rate, start, stop, position| rate_segment(rate, start, stop, position).map(ns).ok();
+        assert_eq!(segment(2.0, None, None, Some(t(10))), Some((10, None)));
+        assert_eq!(
+            segment(0.5, Some(t(5)), Some(t(8)), Some(t(10))),
+            Some((5, Some(8)))
+        );
+        assert_eq!(segment(-1.0, None, None, Some(t(10))), Some((0, Some(10))));
+        assert_eq!(
+            segment(-1.0, Some(t(4)), None, Some(t(10))),
+            Some((4, Some(10)))
+        );
+        assert_eq!(segment(-1.0, None, None, None), None);
+        assert_eq!(segment(1.0, Some(t(8)), Some(t(5)), None), None);
+        assert_eq!(segment(0.0, None, None, None), None);
+        assert_eq!(segment(f64::NAN, None, None, None), None);
+    }
+
+    #[test]
+    fn test_pipeline_deadline() {
+        let now = chrono::Utc::now();
+        let at = |secs| now + chrono::Duration::seconds(secs);
+        assert_eq!(pipeline_deadline(now, None, None).unwrap(), None);
+        assert_eq!(
+            pipeline_deadline(now, Some(60), None).unwrap(),
+            Some(at(60))
+        );
+        // The earlier of the two applies
+        assert_eq!(
+            pipeline_deadline(now, Some(60), Some(at(30))).unwrap(),
+            Some(at(30))
+        );
+        assert_eq!(
+            pipeline_deadline(now, Some(10), Some(at(30))).unwrap(),
+            Some(at(10))
+        );
+        assert!(pipeline_deadline(now, None, Some(at(-1))).is_err());
+        assert!(pipeline_deadline(now, Some(u64::MAX), None).is_err());
+    }
+
+    #[test]
+    fn test_merge_metadata() {
+        let mut metadata = serde_json::Map::new();
+        let object = |value: serde_json::Value| value.as_object().unwrap().clone();
+        merge_metadata(
+            &mut metadata,
+            object(serde_json::json!({"request": "r-1", "user": {"name": "ana"}})),
+        );
+        merge_metadata(
+            &mut metadata,
+            object(serde_json::json!({"request": null, "user": {"id": 7}, "retry": 2})),
+        );
+        assert_eq!(
+            serde_json::Value::Object(metadata),
+            serde_json::json!({"user": {"id": 7}, "retry": 2})
+        );
+    }
+
+    #[test]
+    fn test_matches_labels() {
+        let labels: BTreeMap<String, String> = [("camera", "front"), ("purpose", "record")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let selector = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        assert!(matches_labels(&labels, &selector(&[])));
+        assert!(matches_labels(&labels, &selector(&[("camera", "front")])));
+        assert!(matches_labels(
+            &labels,
+            &selector(&[("camera", "front"), ("purpose", "record")])
+        ));
+        assert!(!matches_labels(&labels, &selector(&[("camera", "back")])));
+        assert!(!matches_labels(&labels, &selector(&[("site", "lab")])));
+    }
+
+    #[test]
+    fn test_attach_cause() {
+        let cause = Cause::new(7, "gst_seek");
+        let mut data = serde_json::json!({"pipeline_id": "p1"});
+        attach_cause(&mut data, Some(&cause));
+        assert_eq!(data["cause"]["request_id"], "7");
+        assert_eq!(data["cause"]["tool"], "gst_seek");
+        // Without a cause the data is left alone
+        let mut data = serde_json::json!({"pipeline_id": "p1"});
+        attach_cause(&mut data, None);
+        assert_eq!(data, serde_json::json!({"pipeline_id": "p1"}));
+
+        // Messages without a cause keep their serialized form
+        let message = BusMessage {
+            timestamp: chrono::Utc::now(),
+            message_type: "Eos".to_string(),
+            message: "End of stream".to_string(),
+            source: None,
+            cause: None,
+            details: None,
+        };
+        let json = serde_json::to_value(&message).unwrap();
+        assert!(json.get("cause").is_none());
+        let parsed: BusMessage = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.cause, None);
+    }
+
+    #[test]
+    fn test_stop_pipeline_paths() {
+        let manager = PipelineManager::new(4);
+
+        let id = manager
+            .create_pipeline("videotestsrc is-live=true ! fakesink", None)
+            .unwrap();
+        manager
+            .set_pipeline_state(&id, gst::State::Playing)
+            .unwrap();
+        assert!(
+            manager
+                .wait_for_state(&id, gst::State::Playing, Duration::from_secs(5))
+                .unwrap()
+                .reached
+        );
+        let report = manager
+            .stop_pipeline_draining(&id, Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(report.path, StopPath::Drained);
+        assert!(report.teardown_completed);
+        assert!(manager.get_pipeline(&id).is_none());
+
+        let id = manager
+            .create_pipeline("videotestsrc ! fakesink", None)
+            .unwrap();
+        let report = manager
+            .stop_pipeline_draining(&id, Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(report.path, StopPath::Immediate);
+        assert!(manager
+            .force_stop_pipeline(&id, Duration::from_secs(1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_force_stop_pipeline_timeout() {
+        let manager = PipelineManager::new(4);
+        let id = manager
+            .create_pipeline("videotestsrc name=src ! fakesink", None)
+            .unwrap();
+
+        // Hold the streaming thread, so the teardown waits for it
+        let (blocked_tx, blocked_rx) = std::sync::mpsc::channel();
+        let blocked_tx = Mutex::new(Some(blocked_tx));
+        let pad = manager
+            .get_pipeline_handle(&id)
+            .unwrap()
+            .by_name("src")
+            .unwrap()
+            .static_pad("src")
+            .unwrap();
+        pad.add_probe(gst::PadProbeType::BUFFER, move |_, _| {
+            if let Some(tx) = blocked_tx.lock().take() {
+                let _ = tx.send(());
+                std::thread::sleep(Duration::from_secs(2));
+            }
+            gst::PadProbeReturn::Ok
+        })
+        .unwrap();
+        manager
+            .set_pipeline_state(&id, gst::State::Playing)
+            .unwrap();
+        blocked_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        let report = manager
+            .force_stop_pipeline(&id, Duration::from_millis(100))
+            .unwrap();
+        assert_eq!(report.path, StopPath::Forced);
+        assert!(!report.teardown_completed);
+        assert!(report.detail.unwrap().contains("abandoned"));
+        assert!(report.elapsed_ms < 2000);
+        assert!(manager.get_pipeline(&id).is_none());
+    }
+
+    #[test]
+    fn test_restart_pipeline_position() {
+        let manager = PipelineManager::new(4);
+        let id = manager
+            .create_pipeline("videotestsrc ! fakesink", Some("restarted".to_string()))
+            .unwrap();
+        manager
+            .set_labels(
+                &id,
+                BTreeMap::from([("camera".to_string(), "front".to_string())]),
+            )
+            .unwrap();
+        manager
+            .preroll_pipeline(&id, Duration::from_secs(5))
+            .unwrap();
+        let handle = manager.get_pipeline_handle(&id).unwrap();
+        handle
+            .seek_simple(
+                gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+                gst::ClockTime::from_seconds(3),
+            )
+            .unwrap();
+        handle.state(gst::ClockTime::from_seconds(5)).0.unwrap();
+
+        let report = manager
+            .restart_pipeline(&id, None, true, Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(report.restart_count, 1);
+        assert_eq!(report.state, "Paused");
+        assert_eq!(report.restored_position_ns, Some(3_000_000_000));
+        let position = |manager: &PipelineManager| {
+            manager
+                .get_pipeline_handle(&id)
+                .unwrap()
+                .query_position::<gst::ClockTime>()
+                .unwrap()
+        };
+        assert!(position(&manager) >= gst::ClockTime::from_mseconds(2900));
+        let instance = manager.get_pipeline(&id).unwrap();
+        assert_eq!(instance.read().info.labels["camera"], "front");
+        assert_ne!(
+            manager.get_pipeline_handle(&id).unwrap(),
+            handle,
+            "the pipeline was not rebuilt"
+        );
+
+        // Without restoring, it starts over
+        let report = manager
+            .restart_pipeline(&id, None, false, Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(report.restart_count, 2);
+        assert_eq!(report.restored_position_ns, None);
+        assert!(position(&manager) < gst::ClockTime::from_seconds(1));
+        manager.remove_pipeline(&id).unwrap();
+    }
+
+    #[test]
+    fn test_create_pipeline_once() {
+        let manager = PipelineManager::new(4);
+        let description = "videotestsrc ! fakesink";
+        let (id, created) = manager
+            .create_pipeline_once(description, None, "camera-1")
+            .unwrap();
+        assert!(created);
+        assert_eq!(
+            manager
+                .create_pipeline_once(description, None, "camera-1")
+                .unwrap(),
+            (id.clone(), false)
+        );
+        assert_eq!(manager.list_pipelines().len(), 1);
+        assert!(manager
+            .create_pipeline_once("audiotestsrc ! fakesink", None, "camera-1")
+            .is_err());
+
+        // The key is free again once its pipeline is gone
+        manager.remove_pipeline(&id).unwrap();
+        let (again, created) = manager
+            .create_pipeline_once(description, None, "camera-1")
+            .unwrap();
+        assert!(created);
+        assert_ne!(again, id);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_watched_bus() {
+        let manager = Arc::new(PipelineManager::new(4));
+        manager.watch_buses();
+        tokio::task::spawn_blocking(move || {
+            let id = manager
+                .create_pipeline("videotestsrc num-buffers=10 ! fakesink", None)
+                .unwrap();
+            let watch = manager.end_watch(&id).unwrap();
+            assert!(matches!(watch, EndWatch::Drained { .. }));
+            manager
+                .set_pipeline_state(&id, gst::State::Playing)
+                .unwrap();
+            assert_eq!(
+                watch.wait_for_eos(Duration::from_secs(5)).0,
+                EosOutcome::Completed
+            );
+            // Recorded as posted, before waiters are woken
+            let messages = manager.get_bus_messages(&id, 100);
+            assert!(messages.iter().any(|m| m.message_type == "Eos"));
+            assert!(messages.iter().any(|m| m.message_type == "StateChanged"));
+
+            // A new watch only counts the EOS posted from now on
+            let watch = manager.end_watch(&id).unwrap();
+            assert_eq!(watch.wait_for_eos(Duration::ZERO).0, EosOutcome::TimedOut);
+            assert_eq!(
+                watch.since_launch().wait_for_eos(Duration::ZERO).0,
+                EosOutcome::Completed
+            );
+            manager.remove_pipeline(&id).unwrap();
+
+            // EOS sent to a live pipeline drains through the watched bus
+            let id = manager
+                .create_pipeline("videotestsrc is-live=true ! fakesink", None)
+                .unwrap();
+            manager
+                .set_pipeline_state(&id, gst::State::Playing)
+                .unwrap();
+            assert!(
+                manager
+                    .wait_for_state(&id, gst::State::Playing, Duration::from_secs(5))
+                    .unwrap()
+                    .reached
+            );
+            let report = manager.send_eos(&id, Some(Duration::from_secs(5))).unwrap();
+            assert_eq!(report.outcome, EosOutcome::Completed);
+            let report = manager
+                .stop_pipeline_draining(&id, Duration::from_secs(5))
+                .unwrap();
+            assert!(report.teardown_completed);
+        })
+        .await
+        .unwrap();
+    }
+
+    #[test]
+    fn test_set_property_str_range() {
+        gst::init().unwrap();
+        let src = make_element("videotestsrc", "src".to_string()).unwrap();
+        assert!(parse_property_value(&src, "num-buffers", "-2").is_err());
+        assert!(set_property_str(&src, "timestamp-offset", "-1").is_err());
+        set_property_str(&src, "num-buffers", "10").unwrap();
+        assert_eq!(src.property::<i32>("num-buffers"), 10);
+    }
+}
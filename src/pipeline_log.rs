@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::message_history::HistoryEntry;
+
+/// Bytes read at a time from the end of a log when tailing it
+const TAIL_BLOCK: u64 = 64 * 1024;
+
+/// A line of a pipeline log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum LogRecord {
+    /// The pipeline was launched, or relaunched by a restart
+    Created {
+        timestamp: chrono::DateTime<chrono::Utc>,
+        description: String,
+    },
+    /// A bus message was recorded, state changes included
+    Message(Box<HistoryEntry>),
+    /// The pipeline was stopped and removed
+    Removed {
+        timestamp: chrono::DateTime<chrono::Utc>,
+    },
+}
+
+impl LogRecord {
+    pub fn timestamp(&self) -> chrono::DateTime<chrono::Utc> {
+        match self {
+            LogRecord::Created { timestamp, .. } | LogRecord::Removed { timestamp } => *timestamp,
+            LogRecord::Message(entry) => entry.message.timestamp,
+        }
+    }
+}
+
+/// Name of the log file of a pipeline, with the ID percent-encoded like
+/// [`crate::shared_state::encode_id`] so distinct IDs never share a log
+pub fn log_file_name(pipeline_id: &str) -> String {
+    format!("{}.jsonl", crate::shared_state::encode_id(pipeline_id))
+}
+
+/// Path of the log of a pipeline in `dir`
+pub fn log_path(dir: &Path, pipeline_id: &str) -> PathBuf {
+    dir.join(log_file_name(pipeline_id))
+}
+
+/// JSON lines file the history of a pipeline is appended to, so it
+/// outlives the in-memory message history and the pipeline itself. Lines
+/// are written by a thread of the log's own, so recording a message never
+/// waits for the disk; it stops once every clone of the log is dropped.
+#[derive(Debug, Clone)]
+pub struct PipelineLog {
+    path: PathBuf,
+    lines: mpsc::Sender<String>,
+}
+
+impl PipelineLog {
+    /// Open the log of a pipeline in `dir` for appending, creating both as
+    /// needed
+    pub fn open(dir: &Path, pipeline_id: &str) -> std::io::Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let path = log_path(dir, pipeline_id);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let (lines, received) = mpsc::channel();
+        let writer_path = path.clone();
+        std::thread::Builder::new()
+            .name("pipeline-log".to_string())
+            .spawn(move || write_lines(file, &writer_path, received))?;
+        Ok(Self { path, lines })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Queue a record for writing. Failures are logged rather than
+    /// returned, so a full disk does not fail the pipeline.
+    pub fn append(&self, record: &LogRecord) {
+        match serde_json::to_string(record) {
+            Ok(line) => {
+                let _ = self.lines.send(line);
+            }
+            Err(e) => tracing::warn!(
+                "Failed to write pipeline log {}: {}",
+                self.path.display(),
+                e
+            ),
+        }
+    }
+}
+
+/// Body of the writer thread of a log: writes the lines received, flushing
+/// whenever none are waiting
+fn write_lines(file: File, path: &Path, lines: mpsc::Receiver<String>) {
+    let mut file = BufWriter::new(file);
+    while let Ok(line) = lines.recv() {
+        let result = std::iter::once(line)
+            .chain(lines.try_iter())
+            .try_for_each(|line| writeln!(file, "{}", line))
+            .and_then(|()| file.flush());
+        if let Err(e) = result {
+            tracing::warn!("Failed to write pipeline log {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// The last `count` complete lines of `text`, oldest first
+pub fn last_lines(text: &str, count: usize) -> Vec<&str> {
+    let mut lines: Vec<&str> = text
+        .lines()
+        .rev()
+        .filter(|line| !line.trim().is_empty())
+        .take(count)
+        .collect();
+    lines.reverse();
+    lines
+}
+
+/// The end of a pipeline log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogTail {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// The last records, oldest first
+    pub records: Vec<LogRecord>,
+    /// Lines among the last ones that could not be parsed
+    pub skipped_lines: usize,
+}
+
+/// Read the last `count` records of the log of a pipeline in `dir`, reading
+/// the file backwards so long logs stay cheap to tail
+pub fn tail_log(dir: &Path, pipeline_id: &str, count: usize) -> McpResult<LogTail> {
+    let path = log_path(dir, pipeline_id);
+    let mut file = File::open(&path).map_err(|e| {
+        GStreamerMcpError::PipelineError(format!(
+            "No log for pipeline '{}' at {}: {}",
+            pipeline_id,
+            path.display(),
+            e
+        ))
+    })?;
+    let size_bytes = file.metadata()?.len();
+
+    // Read blocks from the end until they hold enough lines
+    let mut start = size_bytes;
+    let mut bytes = Vec::new();
+    while start > 0 && bytes.iter().filter(|b| **b == b'\n').count() <= count {
+        let block = TAIL_BLOCK.min(start);
+        start -= block;
+        let mut chunk = vec![0; block as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&bytes);
+        bytes = chunk;
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    // Unless the file was read from its start, the first line is partial
+    let text = match text.find('\n') {
+        Some(newline) if start > 0 => &text[newline + 1..],
+        _ => &text[..],
+    };
+
+    let lines = last_lines(text, count);
+    let records: Vec<LogRecord> = lines
+        .iter()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    Ok(LogTail {
+        path,
+        size_bytes,
+        skipped_lines: lines.len() - records.len(),
+        records,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::BusMessage;
+
+    #[test]
+    fn test_pipeline_log() {
+        assert_eq!(log_file_name("cam-1"), "cam-1.jsonl");
+        assert_eq!(
+            log_file_name("../etc/passwd"),
+            "%2E%2E%2Fetc%2Fpasswd.jsonl"
+        );
+        assert_ne!(log_file_name("a/b"), log_file_name("a_b"));
+        assert_eq!(last_lines("a\nb\n\nc\n", 2), vec!["b", "c"]);
+
+        let record = LogRecord::Message(Box::new(HistoryEntry {
+            seq: 7,
+            message: BusMessage {
+                timestamp: chrono::Utc::now(),
+                message_type: "Eos".to_string(),
+                message: "End of stream".to_string(),
+                source: None,
+                cause: None,
+                details: None,
+            },
+        }));
+        let line = serde_json::to_string(&record).unwrap();
+        assert!(line.starts_with(r#"{"event":"message","seq":7"#));
+        match serde_json::from_str::<LogRecord>(&line).unwrap() {
+            LogRecord::Message(entry) => assert_eq!(entry.message.message_type, "Eos"),
+            other => panic!("unexpected record {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::tool_registry::ToolCategory;
+
+/// Access level of a tool caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// Read-only discovery and suggestions
+    Viewer,
+    /// Viewer plus pipeline control
+    Operator,
+    /// Every tool
+    #[default]
+    Admin,
+}
+
+impl Role {
+    /// Tool categories a role may call unless overridden in configuration
+    pub fn default_categories(&self) -> Vec<ToolCategory> {
+        match self {
+            Role::Viewer => vec![ToolCategory::Discovery, ToolCategory::Suggestions],
+            Role::Operator => vec![
+                ToolCategory::Discovery,
+                ToolCategory::Suggestions,
+                ToolCategory::Pipeline,
+                ToolCategory::Seek,
+            ],
+            Role::Admin => vec![
+                ToolCategory::Discovery,
+                ToolCategory::Suggestions,
+                ToolCategory::Pipeline,
+                ToolCategory::Seek,
+                ToolCategory::CodeGeneration,
+                ToolCategory::PluginDevelopment,
+            ],
+        }
+    }
+}
+
+impl std::fmt::Display for Role {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Role::Viewer => "viewer",
+            Role::Operator => "operator",
+            Role::Admin => "admin",
+        };
+        f.write_str(name)
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "viewer" => Ok(Role::Viewer),
+            "operator" => Ok(Role::Operator),
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!(
+                "Invalid role '{}'. Must be one of: viewer, operator, admin",
+                s
+            )),
+        }
+    }
+}
+
+/// Identity of the caller, inserted into the request extensions by
+/// transports that authenticate their clients (e.g. with a bearer token).
+/// Requests without one, such as those arriving over stdio, get the
+/// configured default role.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallerIdentity {
+    pub token: String,
+}
+
+/// Maps caller identities to roles and roles to tool categories
+#[derive(Debug, Clone, Default)]
+pub struct RolePolicy {
+    default_role: Role,
+    tokens: HashMap<String, Role>,
+    categories: HashMap<Role, Vec<ToolCategory>>,
+}
+
+impl RolePolicy {
+    pub fn new(
+        default_role: Role,
+        tokens: HashMap<String, Role>,
+        categories: HashMap<Role, Vec<ToolCategory>>,
+    ) -> Self {
+        Self {
+            default_role,
+            tokens,
+            categories,
+        }
+    }
+
+    /// Role of a caller. Unknown identities get the least privileged role.
+    pub fn resolve(&self, identity: Option<&CallerIdentity>) -> Role {
+        match identity {
+            None => self.default_role,
+            Some(identity) => self
+                .tokens
+                .get(&identity.token)
+                .copied()
+                .unwrap_or(Role::Viewer),
+        }
+    }
+
+    pub fn allows(&self, role: Role, category: &ToolCategory) -> bool {
+        match self.categories.get(&role) {
+            Some(categories) => categories.contains(category),
+            None => role.default_categories().contains(category),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_role() {
+        let policy = RolePolicy::new(
+            Role::Operator,
+            HashMap::from([("secret".to_string(), Role::Admin)]),
+            HashMap::new(),
+        );
+        let identity = |token: &str| CallerIdentity {
+            token: token.to_string(),
+        };
+
+        assert_eq!(policy.resolve(None), Role::Operator);
+        assert_eq!(policy.resolve(Some(&identity("secret"))), Role::Admin);
+        assert_eq!(policy.resolve(Some(&identity("unknown"))), Role::Viewer);
+    }
+
+    #[test]
+    fn test_category_overrides() {
+        let policy = RolePolicy::new(
+            Role::Admin,
+            HashMap::new(),
+            HashMap::from([(Role::Viewer, vec![ToolCategory::Pipeline])]),
+        );
+
+        assert!(policy.allows(Role::Viewer, &ToolCategory::Pipeline));
+        assert!(!policy.allows(Role::Viewer, &ToolCategory::Discovery));
+        assert!(policy.allows(Role::Operator, &ToolCategory::Seek));
+        assert!(!policy.allows(Role::Operator, &ToolCategory::CodeGeneration));
+    }
+}
@@ -0,0 +1,138 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// Room left in the queue over its threshold, the default `max-size-time`
+/// of a queue, so it never blocks the tee while holding the pre-record
+const HEADROOM: gst::ClockTime = gst::ClockTime::SECOND;
+
+/// Pre-record buffer in front of a recording valve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreRecord {
+    pub pipeline_id: String,
+    pub valve: String,
+    /// Queue feeding the valve, which holds the buffer
+    pub queue: String,
+    /// Time the queue holds back; 0 when disarmed
+    pub pre_record_ms: u64,
+    pub max_size_time_ms: u64,
+}
+
+/// `min-threshold-time` and `max-size-time` of a queue holding back
+/// `pre_record`
+pub fn queue_times(pre_record: gst::ClockTime) -> (gst::ClockTime, gst::ClockTime) {
+    (pre_record, pre_record + HEADROOM)
+}
+
+/// The `queue` directly upstream of `valve`
+pub fn feeding_queue(valve: &gst::Element) -> McpResult<gst::Element> {
+    let queue = valve
+        .static_pad("sink")
+        .and_then(|pad| pad.peer())
+        .and_then(|peer| peer.parent_element())
+        .filter(|element| element.find_property("min-threshold-time").is_some());
+    queue.ok_or_else(|| {
+        GStreamerMcpError::PipelineError(format!(
+            "{} is not fed by a queue; put one in front of it, e.g. 't. ! queue ! valve'",
+            valve.name()
+        ))
+    })
+}
+
+/// Time the queue in front of `valve` holds back, if a pre-record buffer is
+/// armed
+pub fn pre_record_time(valve: &gst::Element) -> Option<gst::ClockTime> {
+    let queue = feeding_queue(valve).ok()?;
+    let time = gst::ClockTime::from_nseconds(queue.property::<u64>("min-threshold-time"));
+    (time > gst::ClockTime::ZERO).then_some(time)
+}
+
+/// Make the queue in front of the valve `valve_name` hold back
+/// `pre_record` of data, so that opening the valve records from that long
+/// before. The valve drops what comes out of the queue while it is closed.
+/// A zero `pre_record` restores the queue's default limits.
+pub fn set_prerecord(
+    pipeline: &gst::Pipeline,
+    pipeline_id: &str,
+    valve_name: &str,
+    pre_record: gst::ClockTime,
+) -> McpResult<PreRecord> {
+    let valve = pipeline.by_name(valve_name).ok_or_else(|| {
+        GStreamerMcpError::PipelineError(format!("Element '{}' not found in pipeline", valve_name))
+    })?;
+    if valve.find_property("drop").is_none() {
+        return Err(GStreamerMcpError::PipelineError(format!(
+            "{} is not a valve",
+            valve.name()
+        )));
+    }
+    let queue = feeding_queue(&valve)?;
+
+    if pre_record == gst::ClockTime::ZERO {
+        for name in [
+            "min-threshold-time",
+            "max-size-time",
+            "max-size-buffers",
+            "max-size-bytes",
+        ] {
+            if let Some(pspec) = queue.find_property(name) {
+                queue.set_property_from_value(name, pspec.default_value());
+            }
+        }
+    } else {
+        let (threshold, max_size) = queue_times(pre_record);
+        // Only time limits the queue, whatever the bitrate
+        queue.set_property("max-size-buffers", 0u32);
+        queue.set_property("max-size-bytes", 0u32);
+        queue.set_property("max-size-time", max_size.nseconds());
+        queue.set_property("min-threshold-time", threshold.nseconds());
+    }
+
+    Ok(PreRecord {
+        pipeline_id: pipeline_id.to_string(),
+        valve: valve.name().to_string(),
+        queue: queue.name().to_string(),
+        pre_record_ms: pre_record.mseconds(),
+        max_size_time_ms: queue.property::<u64>("max-size-time") / 1_000_000,
+    })
+}
+
+/// Drop the delta frames leaving `valve` until the next keyframe, so a
+/// recording started on encoded data begins decodable. Upstream encoders
+/// are asked for a keyframe when a frame has to be dropped.
+pub fn keyframe_gate(valve: &gst::Element) {
+    let (Some(src), Some(sink)) = (valve.static_pad("src"), valve.static_pad("sink")) else {
+        return;
+    };
+    let requested = AtomicBool::new(false);
+    src.add_probe(gst::PadProbeType::BUFFER, move |_, info| {
+        let Some(buffer) = info.buffer() else {
+            return gst::PadProbeReturn::Ok;
+        };
+        if !buffer.flags().contains(gst::BufferFlags::DELTA_UNIT) {
+            return gst::PadProbeReturn::Remove;
+        }
+        if !requested.swap(true, Ordering::Relaxed) {
+            let event = gstreamer_video::UpstreamForceKeyUnitEvent::builder()
+                .all_headers(true)
+                .build();
+            sink.push_event(event);
+        }
+        gst::PadProbeReturn::Drop
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_times() {
+        let (threshold, max_size) = queue_times(gst::ClockTime::from_seconds(10));
+        assert_eq!(threshold, gst::ClockTime::from_seconds(10));
+        assert_eq!(max_size, gst::ClockTime::from_seconds(11));
+    }
+}
@@ -0,0 +1,445 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitrateMeasurement {
+    pub pad: String,
+    pub window_ms: u64,
+    pub interval_ms: u64,
+    pub buffers: u64,
+    pub bytes: u64,
+    /// Average bitrate over the whole window in bits per second
+    pub average_bitrate: u64,
+    pub min_bitrate: u64,
+    pub max_bitrate: u64,
+    /// Bitrate of each interval in bits per second
+    pub samples: Vec<u64>,
+}
+
+/// Resolve a pad specification of the form `element.pad` (or just `element`,
+/// meaning its `src` pad) inside a pipeline
+pub fn find_pad(pipeline: &gst::Pipeline, pad_spec: &str) -> McpResult<gst::Pad> {
+    let (element_name, pad_name) = pad_spec.rsplit_once('.').unwrap_or((pad_spec, "src"));
+
+    let element = pipeline.by_name(element_name).ok_or_else(|| {
+        GStreamerMcpError::PipelineError(format!(
+            "Element '{}' not found in pipeline",
+            element_name
+        ))
+    })?;
+
+    element
+        .static_pad(pad_name)
+        .or_else(|| element.pads().into_iter().find(|p| p.name() == pad_name))
+        .ok_or_else(|| {
+            let available: Vec<String> = element
+                .pads()
+                .iter()
+                .map(|p| p.name().to_string())
+                .collect();
+            GStreamerMcpError::PipelineError(format!(
+                "Pad '{}' not found on element '{}'. Available pads: {}",
+                pad_name,
+                element_name,
+                available.join(", ")
+            ))
+        })
+}
+
+/// Data seen by a probe installed with [`add_stats_probe`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeStats {
+    /// Pad as `element.pad`
+    pub pad: String,
+    /// Time since the probe was installed
+    pub elapsed_ms: u64,
+    pub buffers: u64,
+    pub bytes: u64,
+    pub average_buffer_size: u64,
+    /// Average data rate since the probe was installed
+    pub bytes_per_second: u64,
+    pub first_pts_ns: Option<u64>,
+    pub last_pts_ns: Option<u64>,
+    /// Times a buffer's PTS was lower than the one before, as after a seek
+    /// or from a misbehaving element
+    pub pts_backwards: u64,
+    /// Time since the last buffer; None before the first
+    pub idle_ms: Option<u64>,
+    /// Distinct caps seen on the pad, in order, the current ones last
+    pub caps: Vec<String>,
+}
+
+/// Accumulates what flows through a pad
+#[derive(Debug, Clone)]
+pub struct ProbeAccumulator {
+    pad: String,
+    installed: Instant,
+    buffers: u64,
+    bytes: u64,
+    first_pts: Option<u64>,
+    last_pts: Option<u64>,
+    pts_backwards: u64,
+    last_buffer: Option<Instant>,
+    caps: Vec<String>,
+}
+
+impl ProbeAccumulator {
+    pub fn new(pad: &str, installed: Instant) -> Self {
+        ProbeAccumulator {
+            pad: pad.to_string(),
+            installed,
+            buffers: 0,
+            bytes: 0,
+            first_pts: None,
+            last_pts: None,
+            pts_backwards: 0,
+            last_buffer: None,
+            caps: Vec::new(),
+        }
+    }
+
+    /// Count `buffers` buffers of `bytes` in total, the first stamped `pts`
+    pub fn observe_buffers(&mut self, buffers: u64, bytes: u64, pts: Option<u64>, now: Instant) {
+        self.buffers += buffers;
+        self.bytes += bytes;
+        self.last_buffer = Some(now);
+        let Some(pts) = pts else {
+            return;
+        };
+        self.first_pts.get_or_insert(pts);
+        if self.last_pts.is_some_and(|last| pts < last) {
+            self.pts_backwards += 1;
+        }
+        self.last_pts = Some(pts);
+    }
+
+    /// Note caps negotiated on the pad; repeats of the last ones are ignored
+    pub fn observe_caps(&mut self, caps: String) {
+        if self.caps.last() != Some(&caps) {
+            self.caps.retain(|seen| *seen != caps);
+            self.caps.push(caps);
+        }
+    }
+
+    pub fn stats(&self, now: Instant) -> ProbeStats {
+        let elapsed = now.duration_since(self.installed);
+        let secs = elapsed.as_secs_f64();
+        ProbeStats {
+            pad: self.pad.clone(),
+            elapsed_ms: elapsed.as_millis() as u64,
+            buffers: self.buffers,
+            bytes: self.bytes,
+            average_buffer_size: self.bytes.checked_div(self.buffers).unwrap_or(0),
+            bytes_per_second: if secs > 0.0 {
+                (self.bytes as f64 / secs) as u64
+            } else {
+                0
+            },
+            first_pts_ns: self.first_pts,
+            last_pts_ns: self.last_pts,
+            pts_backwards: self.pts_backwards,
+            idle_ms: self
+                .last_buffer
+                .map(|last| now.duration_since(last).as_millis() as u64),
+            caps: self.caps.clone(),
+        }
+    }
+}
+
+/// A probe left on a pad, collecting [`ProbeStats`] until removed
+#[derive(Debug)]
+pub struct StatsProbe {
+    pad: gst::Pad,
+    probe_id: Option<gst::PadProbeId>,
+    pub stats: Arc<Mutex<ProbeAccumulator>>,
+}
+
+impl StatsProbe {
+    /// Pad the probe is on, as `element.pad`
+    pub fn name(&self) -> String {
+        self.stats.lock().pad.clone()
+    }
+
+    pub fn remove(mut self) {
+        if let Some(id) = self.probe_id.take() {
+            self.pad.remove_probe(id);
+        }
+    }
+}
+
+/// Name of a pad as `element.pad`
+pub fn pad_name(pad: &gst::Pad) -> String {
+    let element = pad
+        .parent_element()
+        .map(|e| e.name().to_string())
+        .unwrap_or_default();
+    format!("{}.{}", element, pad.name())
+}
+
+/// Install a probe on `pad_spec` that counts the buffers and bytes passing,
+/// follows their timestamps and records the caps negotiated. It takes a
+/// short lock per buffer and stays until removed.
+pub fn add_stats_probe(pipeline: &gst::Pipeline, pad_spec: &str) -> McpResult<StatsProbe> {
+    let pad = find_pad(pipeline, pad_spec)?;
+    let mut accumulator = ProbeAccumulator::new(&pad_name(&pad), Instant::now());
+    if let Some(caps) = pad.current_caps() {
+        accumulator.observe_caps(caps.to_string());
+    }
+    let stats = Arc::new(Mutex::new(accumulator));
+
+    let probe_id = {
+        let stats = stats.clone();
+        pad.add_probe(
+            gst::PadProbeType::BUFFER
+                | gst::PadProbeType::BUFFER_LIST
+                | gst::PadProbeType::EVENT_DOWNSTREAM,
+            move |_pad, info| {
+                let now = Instant::now();
+                if let Some(buffer) = info.buffer() {
+                    stats.lock().observe_buffers(
+                        1,
+                        buffer.size() as u64,
+                        buffer.pts().map(|pts| pts.nseconds()),
+                        now,
+                    );
+                } else if let Some(list) = info.buffer_list() {
+                    stats.lock().observe_buffers(
+                        list.len() as u64,
+                        list.calculate_size() as u64,
+                        list.get(0)
+                            .and_then(|buffer| buffer.pts())
+                            .map(|pts| pts.nseconds()),
+                        now,
+                    );
+                } else if let Some(gst::EventView::Caps(caps)) =
+                    info.event().map(|event| event.view())
+                {
+                    stats.lock().observe_caps(caps.caps().to_string());
+                }
+                gst::PadProbeReturn::Ok
+            },
+        )
+    }
+    .ok_or_else(|| {
+        GStreamerMcpError::PipelineError(format!("Failed to install probe on '{}'", pad_spec))
+    })?;
+
+    Ok(StatsProbe {
+        pad,
+        probe_id: Some(probe_id),
+        stats,
+    })
+}
+
+fn bits_per_second(bytes: u64, elapsed: Duration) -> u64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        (bytes as f64 * 8.0 / secs) as u64
+    } else {
+        0
+    }
+}
+
+/// Removes a probe when dropped, so a measurement cancelled while it waits
+/// does not leave its probe behind
+struct ProbeGuard {
+    pad: gst::Pad,
+    probe_id: Option<gst::PadProbeId>,
+}
+
+impl Drop for ProbeGuard {
+    fn drop(&mut self) {
+        if let Some(id) = self.probe_id.take() {
+            self.pad.remove_probe(id);
+        }
+    }
+}
+
+/// Interval between bitrate samples: at most the window, at least 10ms
+fn sample_interval(window: Duration, interval: Duration) -> Duration {
+    interval.min(window).max(Duration::from_millis(10))
+}
+
+/// Bitrate of each interval of a measurement, from running byte totals
+#[derive(Debug)]
+struct BitrateSampler {
+    start: Instant,
+    last_bytes: u64,
+    last_tick: Instant,
+    samples: Vec<u64>,
+}
+
+impl BitrateSampler {
+    fn new(start: Instant) -> Self {
+        Self {
+            start,
+            last_bytes: 0,
+            last_tick: start,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Close the interval ending at `now`, with `total_bytes` seen so far
+    fn sample(&mut self, total_bytes: u64, now: Instant) {
+        self.samples.push(bits_per_second(
+            total_bytes.saturating_sub(self.last_bytes),
+            now.saturating_duration_since(self.last_tick),
+        ));
+        self.last_bytes = total_bytes;
+        self.last_tick = now;
+    }
+
+    fn finish(
+        self,
+        pad: &str,
+        window: Duration,
+        interval: Duration,
+        buffers: u64,
+        total_bytes: u64,
+        now: Instant,
+    ) -> BitrateMeasurement {
+        BitrateMeasurement {
+            pad: pad.to_string(),
+            window_ms: window.as_millis() as u64,
+            interval_ms: interval.as_millis() as u64,
+            buffers,
+            bytes: total_bytes,
+            average_bitrate: bits_per_second(
+                total_bytes,
+                now.saturating_duration_since(self.start),
+            ),
+            min_bitrate: self.samples.iter().copied().min().unwrap_or(0),
+            max_bitrate: self.samples.iter().copied().max().unwrap_or(0),
+            samples: self.samples,
+        }
+    }
+}
+
+/// Install a temporary buffer probe on `pad_spec` and measure the data rate
+/// flowing through it over `window`, sampled every `interval`
+pub async fn measure_bitrate(
+    pipeline: &gst::Pipeline,
+    pad_spec: &str,
+    window: Duration,
+    interval: Duration,
+) -> McpResult<BitrateMeasurement> {
+    let pad = find_pad(pipeline, pad_spec)?;
+    let interval = sample_interval(window, interval);
+
+    let bytes = Arc::new(AtomicU64::new(0));
+    let buffers = Arc::new(AtomicU64::new(0));
+
+    let probe_id = {
+        let bytes = bytes.clone();
+        let buffers = buffers.clone();
+        pad.add_probe(
+            gst::PadProbeType::BUFFER | gst::PadProbeType::BUFFER_LIST,
+            move |_pad, info| {
+                if let Some(buffer) = info.buffer() {
+                    bytes.fetch_add(buffer.size() as u64, Ordering::Relaxed);
+                    buffers.fetch_add(1, Ordering::Relaxed);
+                } else if let Some(list) = info.buffer_list() {
+                    bytes.fetch_add(list.calculate_size() as u64, Ordering::Relaxed);
+                    buffers.fetch_add(list.len() as u64, Ordering::Relaxed);
+                }
+                gst::PadProbeReturn::Ok
+            },
+        )
+    }
+    .ok_or_else(|| {
+        GStreamerMcpError::PipelineError(format!("Failed to install probe on '{}'", pad_spec))
+    })?;
+    let guard = ProbeGuard {
+        pad,
+        probe_id: Some(probe_id),
+    };
+
+    let start = Instant::now();
+    let mut sampler = BitrateSampler::new(start);
+    while start.elapsed() < window {
+        let remaining = window.saturating_sub(start.elapsed());
+        tokio::time::sleep(interval.min(remaining)).await;
+        sampler.sample(bytes.load(Ordering::Relaxed), Instant::now());
+    }
+    drop(guard);
+
+    Ok(sampler.finish(
+        pad_spec,
+        window,
+        interval,
+        buffers.load(Ordering::Relaxed),
+        bytes.load(Ordering::Relaxed),
+        Instant::now(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_accumulator() {
+        let start = Instant::now();
+        let mut accumulator = ProbeAccumulator::new("enc.src", start);
+        assert_eq!(accumulator.stats(start).idle_ms, None);
+
+        accumulator.observe_caps("video/x-h264".to_string());
+        accumulator.observe_buffers(1, 1000, Some(0), start + Duration::from_millis(500));
+        accumulator.observe_buffers(2, 3000, Some(40), start + Duration::from_millis(1000));
+        accumulator.observe_buffers(1, 0, None, start + Duration::from_millis(1500));
+        accumulator.observe_buffers(1, 1000, Some(20), start + Duration::from_millis(2000));
+        accumulator.observe_caps("video/x-h264".to_string());
+
+        let stats = accumulator.stats(start + Duration::from_secs(5));
+        assert_eq!(stats.buffers, 5);
+        assert_eq!(stats.bytes, 5000);
+        assert_eq!(stats.average_buffer_size, 1000);
+        assert_eq!(stats.bytes_per_second, 1000);
+        assert_eq!((stats.first_pts_ns, stats.last_pts_ns), (Some(0), Some(20)));
+        assert_eq!(stats.pts_backwards, 1);
+        assert_eq!(stats.idle_ms, Some(3000));
+        assert_eq!(stats.caps, vec!["video/x-h264"]);
+
+        // Renegotiating back to earlier caps moves them last
+        accumulator.observe_caps("video/x-raw".to_string());
+        accumulator.observe_caps("video/x-h264".to_string());
+        assert_eq!(
+            accumulator.stats(start).caps,
+            vec!["video/x-raw", "video/x-h264"]
+        );
+    }
+
+    #[test]
+    fn test_bitrate_sampler() {
+        let ms = Duration::from_millis;
+        assert_eq!(sample_interval(ms(5000), ms(1000)), ms(1000));
+        assert_eq!(sample_interval(ms(500), ms(1000)), ms(500));
+        assert_eq!(sample_interval(ms(5000), ms(1)), ms(10));
+
+        let start = Instant::now();
+        let mut sampler = BitrateSampler::new(start);
+        sampler.sample(125_000, start + ms(1000));
+        sampler.sample(125_000, start + ms(1500));
+        sampler.sample(250_000, start + ms(2000));
+        let measurement =
+            sampler.finish("enc.src", ms(2000), ms(500), 10, 250_000, start + ms(2000));
+        assert_eq!(measurement.samples, vec![1_000_000, 0, 2_000_000]);
+        assert_eq!(measurement.min_bitrate, 0);
+        assert_eq!(measurement.max_bitrate, 2_000_000);
+        assert_eq!(measurement.average_bitrate, 1_000_000);
+        assert_eq!((measurement.buffers, measurement.bytes), (10, 250_000));
+        assert_eq!(measurement.interval_ms, 500);
+
+        // Nothing measured before the first interval closed
+        let measurement = BitrateSampler::new(start).finish("enc.src", ms(0), ms(10), 0, 0, start);
+        assert_eq!(measurement.samples, Vec::<u64>::new());
+        assert_eq!(measurement.average_bitrate, 0);
+    }
+}
@@ -0,0 +1,179 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Part of the stream being played, in stream time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SegmentBounds {
+    pub start_ns: u64,
+    /// Unset for a segment that plays to the end
+    pub stop_ns: Option<u64>,
+    /// Playback rate; negative for reverse playback
+    pub rate: f64,
+}
+
+impl SegmentBounds {
+    /// Position clamped into the segment
+    pub fn clamp(&self, position_ns: u64) -> u64 {
+        let position = position_ns.max(self.start_ns);
+        match self.stop_ns {
+            Some(stop) => position.min(stop.max(self.start_ns)),
+            None => position,
+        }
+    }
+
+    /// Share of the segment played, from 0 to 1, in the direction of
+    /// playback: reverse playback counts down from `stop`. None without a
+    /// known end.
+    pub fn fraction(&self, position_ns: u64, duration_ns: Option<u64>) -> Option<f64> {
+        let stop = self.stop_ns.or(duration_ns)?;
+        let length = stop.checked_sub(self.start_ns).filter(|l| *l > 0)?;
+        let position = self.clamp(position_ns).min(stop);
+        let played = if self.rate < 0.0 {
+            stop - position
+        } else {
+            position - self.start_ns
+        };
+        Some(played as f64 / length as f64)
+    }
+}
+
+/// Playback progress normalized for progress bars
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Progress {
+    /// Stream time of the current position, within the segment
+    pub position_ns: u64,
+    /// Position that only moves in the direction of playback within a
+    /// segment; a seek starts a new segment
+    pub monotonic_position_ns: u64,
+    pub duration_ns: Option<u64>,
+    pub segment: SegmentBounds,
+    /// Share of the segment played, from 0 to 1, when its end is known
+    pub fraction: Option<f64>,
+    /// Wall-clock time left at the current rate, when the end is known
+    pub remaining_ns: Option<u64>,
+}
+
+/// Keeps the progress of a pipeline from going backwards, e.g. while a
+/// sink reports the old position during a seek
+#[derive(Debug, Clone, Default)]
+pub struct ProgressTracker {
+    segment: Option<SegmentBounds>,
+    furthest_ns: u64,
+}
+
+impl ProgressTracker {
+    /// Furthest position observed in the current segment
+    pub fn last_position(&self) -> Option<u64> {
+        self.segment.map(|_| self.furthest_ns)
+    }
+
+    /// Progress at a raw position. A new segment resets the tracker.
+    pub fn observe(
+        &mut self,
+        segment: SegmentBounds,
+        position_ns: u64,
+        duration_ns: Option<u64>,
+    ) -> Progress {
+        let position = segment.clamp(position_ns);
+        if self.segment != Some(segment) {
+            self.segment = Some(segment);
+            self.furthest_ns = position;
+        } else if segment.rate < 0.0 {
+            self.furthest_ns = self.furthest_ns.min(position);
+        } else {
+            self.furthest_ns = self.furthest_ns.max(position);
+        }
+        let monotonic = self.furthest_ns;
+
+        let fraction = segment.fraction(monotonic, duration_ns);
+        let remaining_ns = segment.stop_ns.or(duration_ns).and_then(|stop| {
+            let left = if segment.rate < 0.0 {
+                monotonic.saturating_sub(segment.start_ns)
+            } else {
+                stop.saturating_sub(monotonic)
+            };
+            (segment.rate != 0.0).then(|| (left as f64 / segment.rate.abs()) as u64)
+        });
+        Progress {
+            position_ns: position,
+            monotonic_position_ns: monotonic,
+            duration_ns,
+            segment,
+            fraction,
+            remaining_ns,
+        }
+    }
+}
+
+/// Segment the sinks of a pipeline are playing, in stream time
+pub fn query_segment(pipeline: &gst::Pipeline) -> Option<SegmentBounds> {
+    let mut query = gst::query::Segment::new(gst::Format::Time);
+    if !pipeline.query(&mut query) {
+        return None;
+    }
+    let (rate, start, stop) = query.result();
+    let time = |value: gst::GenericFormattedValue| match value {
+        gst::GenericFormattedValue::Time(time) => time.map(|t| t.nseconds()),
+        _ => None,
+    };
+    Some(SegmentBounds {
+        start_ns: time(start).unwrap_or(0),
+        stop_ns: time(stop),
+        rate,
+    })
+}
+
+/// Progress of a pipeline, or None before it knows its position
+pub fn query_progress(pipeline: &gst::Pipeline, tracker: &mut ProgressTracker) -> Option<Progress> {
+    let position = pipeline.query_position::<gst::ClockTime>()?;
+    let duration = pipeline
+        .query_duration::<gst::ClockTime>()
+        .map(|d| d.nseconds());
+    let segment = query_segment(pipeline).unwrap_or(SegmentBounds {
+        start_ns: 0,
+        stop_ns: None,
+        rate: 1.0,
+    });
+    Some(tracker.observe(segment, position.nseconds(), duration))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const S: u64 = 1_000_000_000;
+
+    #[test]
+    fn test_progress_tracker() {
+        let forward = SegmentBounds {
+            start_ns: 10 * S,
+            stop_ns: None,
+            rate: 2.0,
+        };
+        let mut tracker = ProgressTracker::default();
+        let progress = tracker.observe(forward, 20 * S, Some(110 * S));
+        assert_eq!(progress.fraction, Some(0.1));
+        assert_eq!(progress.remaining_ns, Some(45 * S));
+        // A stale position does not move progress back
+        let progress = tracker.observe(forward, 15 * S, Some(110 * S));
+        assert_eq!(progress.position_ns, 15 * S);
+        assert_eq!(progress.monotonic_position_ns, 20 * S);
+        // Positions before the segment are clamped to its start
+        assert_eq!(tracker.observe(forward, 0, None).position_ns, 10 * S);
+
+        // A seek into reverse playback starts over, counting down
+        let reverse = SegmentBounds {
+            start_ns: 0,
+            stop_ns: Some(60 * S),
+            rate: -1.0,
+        };
+        let progress = tracker.observe(reverse, 45 * S, Some(110 * S));
+        assert_eq!(progress.monotonic_position_ns, 45 * S);
+        assert_eq!(progress.fraction, Some(0.25));
+        assert_eq!(progress.remaining_ns, Some(45 * S));
+        let progress = tracker.observe(reverse, 50 * S, Some(110 * S));
+        assert_eq!(progress.monotonic_position_ns, 45 * S);
+        assert_eq!(tracker.observe(reverse, 30 * S, None).fraction, Some(0.5));
+    }
+}
@@ -0,0 +1,506 @@
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::suggestions::closest_matches;
+use crate::units::{normalize_value, UnitConversion};
+
+/// Separator of the segments of a property path, as in gst-launch's
+/// child-proxy syntax: `playbin::video-sink::sync`
+pub const PATH_SEPARATOR: &str = "::";
+
+/// A property resolved from a property path
+#[derive(Debug, Clone)]
+pub struct PropertyTarget {
+    /// Path with wildcards expanded, e.g. `mix::sink_1::alpha`
+    pub path: String,
+    pub object: glib::Object,
+    pub property: String,
+}
+
+/// Value of a property read through [`get_properties`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropertyValue {
+    pub path: String,
+    pub type_name: String,
+    pub value: String,
+    /// How a value given with units was rewritten when it was set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub converted: Option<UnitConversion>,
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any run of characters
+/// and `?` one character
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    fn matches(pattern: &[char], name: &[char]) -> bool {
+        match pattern.split_first() {
+            None => name.is_empty(),
+            Some(('*', rest)) => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+            Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+            Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches(&pattern, &name)
+}
+
+fn is_pattern(segment: &str) -> bool {
+    segment.contains(['*', '?'])
+}
+
+fn object_name(object: &glib::Object) -> Option<String> {
+    object
+        .downcast_ref::<gst::Object>()
+        .map(|o| o.name().to_string())
+}
+
+/// Named children of a child proxy, such as the elements of a bin or the
+/// sink pads of a compositor
+fn children(object: &glib::Object) -> Vec<(String, glib::Object)> {
+    let Some(proxy) = object.downcast_ref::<gst::ChildProxy>() else {
+        return Vec::new();
+    };
+    (0..proxy.children_count())
+        .filter_map(|i| proxy.child_by_index(i))
+        .filter_map(|child| object_name(&child).map(|name| (name, child)))
+        .collect()
+}
+
+/// A named child of a child proxy, or the object held by an object-valued
+/// property such as playbin's `video-sink`
+fn child(object: &glib::Object, name: &str) -> Option<glib::Object> {
+    if let Some(child) = object
+        .downcast_ref::<gst::ChildProxy>()
+        .and_then(|proxy| proxy.child_by_name(name))
+    {
+        return Some(child);
+    }
+    let pspec = object.find_property(name)?;
+    if !pspec.value_type().is_a(glib::Object::static_type())
+        || !pspec.flags().contains(glib::ParamFlags::READABLE)
+    {
+        return None;
+    }
+    object.property::<Option<glib::Object>>(name)
+}
+
+/// Resolve `element::property`, `element::child::property` and deeper paths
+/// in a pipeline. Intermediate segments are child-proxy children or
+/// object-valued properties. Segments other than the property may contain
+/// `*` and `?` wildcards; objects they match that lack the property are
+/// skipped.
+pub fn resolve_property_path(
+    pipeline: &gst::Pipeline,
+    path: &str,
+) -> McpResult<Vec<PropertyTarget>> {
+    let segments: Vec<&str> = path.split(PATH_SEPARATOR).collect();
+    let Some((property, objects)) = segments.split_last().filter(|(_, o)| !o.is_empty()) else {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "Invalid property path '{}': use element::property or element::child::property",
+            path
+        )));
+    };
+    if is_pattern(property) {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "Wildcards are not allowed in property names ('{}')",
+            property
+        )));
+    }
+
+    let pipeline_object = pipeline.clone().upcast::<glib::Object>();
+    let first = objects[0];
+    let mut current: Vec<(String, glib::Object)> = if is_pattern(first) {
+        pipeline
+            .iterate_recurse()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| glob_match(first, e.name().as_str()))
+            .map(|e| (e.name().to_string(), e.upcast()))
+            .collect()
+    } else if let Some(element) = pipeline.by_name(first) {
+        vec![(first.to_string(), element.upcast())]
+    } else if pipeline.name() == first || pipeline.factory().is_some_and(|f| f.name() == first) {
+        // The pipeline itself, e.g. playbin launched on its own
+        vec![(first.to_string(), pipeline_object)]
+    } else {
+        return Err(GStreamerMcpError::ElementNotFound(format!(
+            "No element named '{}' in the pipeline",
+            first
+        )));
+    };
+
+    for segment in &objects[1..] {
+        current = current
+            .into_iter()
+            .flat_map(|(path, object)| {
+                if is_pattern(segment) {
+                    children(&object)
+                        .into_iter()
+                        .filter(|(name, _)| glob_match(segment, name))
+                        .map(|(name, child)| (format!("{}{}{}", path, PATH_SEPARATOR, name), child))
+                        .collect::<Vec<_>>()
+                } else {
+                    child(&object, segment)
+                        .map(|child| (format!("{}{}{}", path, PATH_SEPARATOR, segment), child))
+                        .into_iter()
+                        .collect()
+                }
+            })
+            .collect();
+    }
+
+    let wildcard = objects.iter().any(|s| is_pattern(s));
+    if !wildcard {
+        if let Some((path, object)) = current.first() {
+            if object.find_property(property).is_none() {
+                return Err(GStreamerMcpError::PropertyError(format!(
+                    "{} has no property '{}'",
+                    path, property
+                )));
+            }
+        }
+    }
+    let targets: Vec<PropertyTarget> = current
+        .into_iter()
+        .filter(|(_, object)| object.find_property(property).is_some())
+        .map(|(path, object)| PropertyTarget {
+            path: format!("{}{}{}", path, PATH_SEPARATOR, property),
+            object,
+            property: property.to_string(),
+        })
+        .collect();
+    if targets.is_empty() {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "Nothing in the pipeline matches '{}'",
+            path
+        )));
+    }
+    Ok(targets)
+}
+
+/// String form of a property value, as gst-launch would accept it
+pub fn format_value(value: &glib::Value) -> String {
+    if let Ok(Some(object)) = value.get::<Option<glib::Object>>() {
+        return object_name(&object).unwrap_or_else(|| object.type_().name().to_string());
+    }
+    value
+        .serialize()
+        .map(|s| s.to_string())
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+/// Nicknames in `value` that are not among `nicks`. Flags values combine
+/// nicknames with `+` or `|`.
+pub fn unknown_nicks<'a>(value: &'a str, nicks: &[String], flags: bool) -> Vec<&'a str> {
+    let names: Vec<&str> = if flags {
+        value.split(['+', '|']).map(str::trim).collect()
+    } else {
+        vec![value.trim()]
+    };
+    names
+        .into_iter()
+        .filter(|name| !name.is_empty() && !nicks.iter().any(|nick| nick == name))
+        .collect()
+}
+
+/// Error for a value that did not parse as the type of `pspec`. For enum
+/// and flags properties it lists the valid nicknames and carries the
+/// closest one to each unknown nickname; otherwise it is `message` alone.
+pub fn invalid_value_error(
+    pspec: &glib::ParamSpec,
+    value: &str,
+    message: String,
+) -> GStreamerMcpError {
+    let (nicks, flags): (Vec<String>, bool) =
+        if let Some(class) = glib::EnumClass::with_type(pspec.value_type()) {
+            let nicks = class.values().iter().map(|v| v.nick().to_string());
+            (nicks.collect(), false)
+        } else if let Some(class) = glib::FlagsClass::with_type(pspec.value_type()) {
+            let nicks = class.values().iter().map(|v| v.nick().to_string());
+            (nicks.collect(), true)
+        } else {
+            return GStreamerMcpError::PropertyError(message);
+        };
+
+    let closest: BTreeMap<String, String> = unknown_nicks(value, &nicks, flags)
+        .into_iter()
+        .filter_map(|name| {
+            let matches =
+                closest_matches(&name.to_lowercase(), nicks.iter().map(String::as_str), 1);
+            matches
+                .into_iter()
+                .next()
+                .map(|nick| (name.to_string(), nick))
+        })
+        .collect();
+    let mut message = format!("{}. Valid values: {}", message, nicks.join(", "));
+    for (name, nick) in &closest {
+        message.push_str(&format!(". {} → {}?", name, nick));
+    }
+    GStreamerMcpError::InvalidEnumValue {
+        message,
+        valid_values: nicks,
+        closest,
+    }
+}
+
+fn within<T: PartialOrd + std::fmt::Display>(value: T, min: T, max: T) -> Result<(), String> {
+    if value < min || value > max {
+        return Err(format!("{} is outside the range {} to {}", value, min, max));
+    }
+    Ok(())
+}
+
+/// Check `value` against the limits of `pspec` before it is set: numbers
+/// must lie within the property's range and enums must be one of its
+/// values. glib panics on a value that fails GObject's own validation, so
+/// anything else it would change is refused too.
+pub fn check_value(pspec: &glib::ParamSpec, value: &glib::Value) -> Result<(), String> {
+    let wrong_type = |e: glib::value::ValueTypeMismatchError| e.to_string();
+    if let Some(p) = pspec.downcast_ref::<glib::ParamSpecDouble>() {
+        within(
+            value.get::<f64>().map_err(wrong_type)?,
+            p.minimum(),
+            p.maximum(),
+        )?;
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecFloat>() {
+        within(
+            value.get::<f32>().map_err(wrong_type)?,
+            p.minimum(),
+            p.maximum(),
+        )?;
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecInt>() {
+        within(
+            value.get::<i32>().map_err(wrong_type)?,
+            p.minimum(),
+            p.maximum(),
+        )?;
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecUInt>() {
+        within(
+            value.get::<u32>().map_err(wrong_type)?,
+            p.minimum(),
+            p.maximum(),
+        )?;
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecInt64>() {
+        within(
+            value.get::<i64>().map_err(wrong_type)?,
+            p.minimum(),
+            p.maximum(),
+        )?;
+    } else if let Some(p) = pspec.downcast_ref::<glib::ParamSpecUInt64>() {
+        within(
+            value.get::<u64>().map_err(wrong_type)?,
+            p.minimum(),
+            p.maximum(),
+        )?;
+    } else if glib::EnumClass::with_type(pspec.value_type()).is_some()
+        && glib::EnumValue::from_value(value).is_none()
+    {
+        return Err(format!(
+            "{} is not a value of {}",
+            format_value(value),
+            pspec.value_type().name()
+        ));
+    }
+
+    let mut copy = value.clone();
+    // SAFETY: both pointers are valid for the call and `copy` is owned here
+    let changed = unsafe {
+        use glib::translate::{ToGlibPtr, ToGlibPtrMut};
+        glib::gobject_ffi::g_param_value_validate(pspec.to_glib_none().0, copy.to_glib_none_mut().0)
+    };
+    if changed != 0 && !pspec.flags().contains(glib::ParamFlags::LAX_VALIDATION) {
+        return Err(format!(
+            "{} is not a valid {}",
+            format_value(value),
+            pspec.value_type().name()
+        ));
+    }
+    Ok(())
+}
+
+/// Readable and writable properties of an object whose value differs from
+/// their default, by name. `name` and `parent` are left out.
+pub fn changed_properties(object: &glib::Object) -> BTreeMap<String, String> {
+    object
+        .list_properties()
+        .iter()
+        .filter(|pspec| {
+            pspec
+                .flags()
+                .contains(glib::ParamFlags::READABLE | glib::ParamFlags::WRITABLE)
+                && !matches!(pspec.name(), "name" | "parent")
+        })
+        .filter_map(|pspec| {
+            let value = format_value(&object.property_value(pspec.name()));
+            (value != format_value(pspec.default_value()))
+                .then(|| (pspec.name().to_string(), value))
+        })
+        .collect()
+}
+
+/// Read the properties a path resolves to
+pub fn get_properties(pipeline: &gst::Pipeline, path: &str) -> McpResult<Vec<PropertyValue>> {
+    resolve_property_path(pipeline, path)?
+        .into_iter()
+        .map(|target| {
+            let pspec = target
+                .object
+                .find_property(&target.property)
+                .expect("resolved properties exist");
+            if !pspec.flags().contains(glib::ParamFlags::READABLE) {
+                return Err(GStreamerMcpError::PropertyError(format!(
+                    "{} is not readable",
+                    target.path
+                )));
+            }
+            Ok(PropertyValue {
+                type_name: pspec.value_type().name().to_string(),
+                value: format_value(&target.object.property_value(&target.property)),
+                path: target.path,
+                converted: None,
+            })
+        })
+        .collect()
+}
+
+/// Set every property a path resolves to from the string form of `value`,
+/// which may use human units such as `2Mbps` or `500ms` (see
+/// [`normalize_value`]). The value is checked against all of them before
+/// any is changed.
+pub fn set_properties(
+    pipeline: &gst::Pipeline,
+    path: &str,
+    value: &str,
+) -> McpResult<Vec<PropertyValue>> {
+    let targets = resolve_property_path(pipeline, path)?;
+    let parsed = targets
+        .iter()
+        .map(|target| {
+            let pspec = target
+                .object
+                .find_property(&target.property)
+                .expect("resolved properties exist");
+            if !pspec.flags().contains(glib::ParamFlags::WRITABLE)
+                || pspec.flags().contains(glib::ParamFlags::CONSTRUCT_ONLY)
+            {
+                return Err(GStreamerMcpError::PropertyError(format!(
+                    "{} is not writable",
+                    target.path
+                )));
+            }
+            let converted = normalize_value(&pspec, value).map_err(|e| {
+                GStreamerMcpError::PropertyError(format!(
+                    "Invalid value for {}: {}",
+                    target.path, e
+                ))
+            })?;
+            let text = converted.as_ref().map_or(value, |c| c.normalized.as_str());
+            let parsed = glib::Value::deserialize(text, pspec.value_type()).map_err(|_| {
+                invalid_value_error(
+                    &pspec,
+                    text,
+                    format!(
+                        "Invalid value '{}' for {} ({})",
+                        text,
+                        target.path,
+                        pspec.value_type().name()
+                    ),
+                )
+            })?;
+            check_value(&pspec, &parsed).map_err(|e| {
+                GStreamerMcpError::PropertyError(format!(
+                    "Invalid value for {}: {}",
+                    target.path, e
+                ))
+            })?;
+            Ok((parsed, converted))
+        })
+        .collect::<McpResult<Vec<_>>>()?;
+
+    Ok(targets
+        .into_iter()
+        .zip(parsed)
+        .map(|(target, (parsed, converted))| {
+            target
+                .object
+                .set_property_from_value(&target.property, &parsed);
+            PropertyValue {
+                type_name: parsed.type_().name().to_string(),
+                value: format_value(&target.object.property_value(&target.property)),
+                path: target.path,
+                converted,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_nicks() {
+        let nicks: Vec<String> = ["cbr", "quant", "pass1"]
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(unknown_nicks("cbrr", &nicks, false), vec!["cbrr"]);
+        assert!(unknown_nicks(" cbr ", &nicks, false).is_empty());
+        assert_eq!(
+            unknown_nicks("cbr+qaunt|pass1", &nicks, true),
+            vec!["qaunt"]
+        );
+        assert_eq!(
+            closest_matches("qaunt", nicks.iter().map(String::as_str), 1),
+            vec!["quant"]
+        );
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("sink_*", "sink_0"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("queue?", "queue1"));
+        assert!(!glob_match("queue?", "queue10"));
+        assert!(!glob_match("sink_*", "src_0"));
+        assert!(glob_match("*sink", "autovideosink"));
+    }
+
+    #[test]
+    fn test_set_properties_out_of_range() {
+        gst::init().unwrap();
+        let pipeline = gst::parse::launch("videotestsrc name=src ! fakesink")
+            .unwrap()
+            .downcast::<gst::Pipeline>()
+            .unwrap();
+        for (path, value) in [
+            ("src::num-buffers", "-2"),
+            ("src::timestamp-offset", "-1"),
+            ("src::blocksize", "0"),
+        ] {
+            assert!(
+                matches!(
+                    set_properties(&pipeline, path, value),
+                    Err(GStreamerMcpError::PropertyError(_))
+                ),
+                "{}={} was accepted",
+                path,
+                value
+            );
+        }
+        assert!(set_properties(&pipeline, "src::pattern", "100").is_err());
+        assert_eq!(
+            set_properties(&pipeline, "src::pattern", "ball").unwrap()[0].value,
+            "ball"
+        );
+        assert_eq!(
+            set_properties(&pipeline, "src::num-buffers", "-1").unwrap()[0].value,
+            "-1"
+        );
+    }
+}
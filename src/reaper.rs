@@ -0,0 +1,202 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use rmcp::model::LoggingLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::jobs::{JobQueue, JobStatus};
+use crate::notifications::Notifier;
+use crate::pipeline::{attach_cause, PipelineHealth, PipelineManager};
+
+/// How often pipelines are checked for being dead
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Why a pipeline counts as dead
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadReason {
+    /// In NULL with no state change pending: stopped, or never started
+    Null,
+    /// Its health is failed after an error
+    Failed,
+}
+
+/// A pipeline removed by the reaper
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReapedPipeline {
+    pub pipeline_id: String,
+    pub description: String,
+    pub reason: DeadReason,
+    /// How long it had been dead
+    pub dead_for_ms: u64,
+}
+
+/// Whether a pipeline in `state`, with `pending` in progress, is dead
+pub fn dead_reason(
+    state: gst::State,
+    pending: gst::State,
+    health: PipelineHealth,
+) -> Option<DeadReason> {
+    if health == PipelineHealth::Failed {
+        Some(DeadReason::Failed)
+    } else if state == gst::State::Null && pending == gst::State::VoidPending {
+        Some(DeadReason::Null)
+    } else {
+        None
+    }
+}
+
+/// Times how long each pipeline has been dead
+#[derive(Debug, Clone, Default)]
+pub struct DeadTracker {
+    since: HashMap<String, Instant>,
+}
+
+impl DeadTracker {
+    /// Note whether a pipeline is dead at `now`. Returns how long it has
+    /// been dead, once that reaches `ttl`.
+    pub fn observe(
+        &mut self,
+        id: &str,
+        dead: bool,
+        now: Instant,
+        ttl: Duration,
+    ) -> Option<Duration> {
+        if !dead {
+            self.since.remove(id);
+            return None;
+        }
+        let since = *self.since.entry(id.to_string()).or_insert(now);
+        let elapsed = now.duration_since(since);
+        (elapsed >= ttl).then_some(elapsed)
+    }
+
+    /// Forget the pipelines for which `keep` is false
+    pub fn retain(&mut self, keep: impl Fn(&str) -> bool) {
+        self.since.retain(|id, _| keep(id));
+    }
+}
+
+/// Remove pipelines that have been in NULL, or failed, for `ttl`, so
+/// pipelines leaked by clients do not use up the pipeline limit. Pipelines
+/// of running jobs are left to the job queue. Each removal is sent to the
+/// client as a `pipeline_reaped` notification.
+pub fn spawn_reaper(
+    pipeline_manager: Arc<PipelineManager>,
+    jobs: Arc<JobQueue>,
+    notifier: Notifier,
+    ttl: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut tracker = DeadTracker::default();
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let running_jobs: Vec<String> = jobs
+                .list(Some(JobStatus::Running))
+                .into_iter()
+                .map(|job| job.id)
+                .collect();
+            let pipelines: Vec<_> = pipeline_manager
+                .list_pipelines()
+                .into_iter()
+                .filter(|info| !running_jobs.contains(&info.id))
+                .collect();
+            tracker.retain(|id| pipelines.iter().any(|info| info.id == id));
+
+            let now = Instant::now();
+            for info in pipelines {
+                let Ok(pipeline) = pipeline_manager.get_pipeline_handle(&info.id) else {
+                    continue;
+                };
+                let reason = dead_reason(
+                    pipeline.current_state(),
+                    pipeline.pending_state(),
+                    info.health,
+                );
+                let Some(dead_for) = tracker.observe(&info.id, reason.is_some(), now, ttl) else {
+                    continue;
+                };
+                let Some(reason) = reason else {
+                    continue;
+                };
+
+                let cause = pipeline_manager.cause(&info.id);
+                let manager = pipeline_manager.clone();
+                let id = info.id.clone();
+                match tokio::task::spawn_blocking(move || manager.stop_pipeline(&id)).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        tracing::warn!("Failed to reap pipeline {}: {}", info.id, e);
+                        continue;
+                    }
+                    Err(_) => continue,
+                }
+                tracing::info!(
+                    "Reaped pipeline {} ({:?} for {} s)",
+                    info.id,
+                    reason,
+                    dead_for.as_secs()
+                );
+                let reaped = ReapedPipeline {
+                    pipeline_id: info.id,
+                    description: info.description,
+                    reason,
+                    dead_for_ms: dead_for.as_millis() as u64,
+                };
+                let mut data = serde_json::to_value(&reaped).unwrap_or_default();
+                attach_cause(&mut data, cause.as_ref());
+                notifier
+                    .notify("pipeline_reaped", LoggingLevel::Warning, data)
+                    .await;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dead_tracker() {
+        assert_eq!(
+            dead_reason(
+                gst::State::Null,
+                gst::State::VoidPending,
+                PipelineHealth::Healthy
+            ),
+            Some(DeadReason::Null)
+        );
+        // Still on its way up from NULL
+        assert_eq!(
+            dead_reason(
+                gst::State::Null,
+                gst::State::Playing,
+                PipelineHealth::Healthy
+            ),
+            None
+        );
+        assert_eq!(
+            dead_reason(
+                gst::State::Playing,
+                gst::State::VoidPending,
+                PipelineHealth::Failed
+            ),
+            Some(DeadReason::Failed)
+        );
+
+        let ttl = Duration::from_secs(60);
+        let start = Instant::now();
+        let mut tracker = DeadTracker::default();
+        assert_eq!(tracker.observe("a", true, start, ttl), None);
+        assert_eq!(tracker.observe("a", true, start + ttl, ttl), Some(ttl));
+        // Coming back to life starts the clock over
+        assert_eq!(tracker.observe("a", false, start + ttl, ttl), None);
+        assert_eq!(tracker.observe("a", true, start + 2 * ttl, ttl), None);
+        tracker.retain(|id| id != "a");
+        assert!(tracker.since.is_empty());
+    }
+}
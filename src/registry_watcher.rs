@@ -0,0 +1,166 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use rmcp::model::LoggingLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::discovery::{ensure_gstreamer_initialized, DiscoveryCache};
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::notifications::Notifier;
+
+/// Fingerprint of every plugin in the registry, keyed by plugin name.
+/// Each value hashes the plugin version together with its feature list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistrySnapshot {
+    plugins: BTreeMap<String, u64>,
+}
+
+/// Plugins that differ between two registry snapshots
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegistryChange {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Plugins whose version or feature list changed
+    pub changed: Vec<String>,
+}
+
+impl RegistryChange {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl RegistrySnapshot {
+    /// Rescan the plugin paths and fingerprint the resulting registry
+    pub fn capture() -> McpResult<Self> {
+        ensure_gstreamer_initialized()?;
+        gst::Registry::update().map_err(|e| {
+            GStreamerMcpError::Other(format!("Failed to update the registry: {}", e))
+        })?;
+
+        let registry = gst::Registry::get();
+        let mut plugins = BTreeMap::new();
+        for plugin in registry.plugins() {
+            let name = plugin.plugin_name().to_string();
+            let mut features: Vec<String> = registry
+                .features_by_plugin(&name)
+                .iter()
+                .map(|f| f.name().to_string())
+                .collect();
+            features.sort();
+
+            let mut hasher = DefaultHasher::new();
+            plugin.version().as_str().hash(&mut hasher);
+            features.hash(&mut hasher);
+            plugins.insert(name, hasher.finish());
+        }
+
+        Ok(Self { plugins })
+    }
+
+    pub fn diff(&self, newer: &RegistrySnapshot) -> RegistryChange {
+        let mut change = RegistryChange::default();
+        for (name, hash) in &newer.plugins {
+            match self.plugins.get(name) {
+                None => change.added.push(name.clone()),
+                Some(old) if old != hash => change.changed.push(name.clone()),
+                Some(_) => {}
+            }
+        }
+        change.removed = self
+            .plugins
+            .keys()
+            .filter(|name| !newer.plugins.contains_key(*name))
+            .cloned()
+            .collect();
+        change
+    }
+}
+
+async fn capture_snapshot() -> McpResult<RegistrySnapshot> {
+    tokio::task::spawn_blocking(RegistrySnapshot::capture)
+        .await
+        .map_err(|e| GStreamerMcpError::Other(format!("Registry scan task failed: {}", e)))?
+}
+
+/// Periodically rescan the registry. When plugins are added, removed or
+/// modified the discovery cache is invalidated and a `registry_changed`
+/// notification is sent to the client.
+pub fn spawn_registry_watcher(
+    cache: Arc<DiscoveryCache>,
+    notifier: Notifier,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut previous = match capture_snapshot().await {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                tracing::warn!("Registry watcher disabled: {}", e);
+                return;
+            }
+        };
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let current = match capture_snapshot().await {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    tracing::warn!("Registry rescan failed: {}", e);
+                    continue;
+                }
+            };
+
+            let change = previous.diff(&current);
+            if change.is_empty() {
+                continue;
+            }
+
+            tracing::info!(
+                "Registry changed: {} added, {} removed, {} changed",
+                change.added.len(),
+                change.removed.len(),
+                change.changed.len()
+            );
+            cache.clear().await;
+            notifier
+                .notify(
+                    "registry_changed",
+                    LoggingLevel::Info,
+                    serde_json::to_value(&change).unwrap_or_default(),
+                )
+                .await;
+            previous = current;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(entries: &[(&str, u64)]) -> RegistrySnapshot {
+        RegistrySnapshot {
+            plugins: entries
+                .iter()
+                .map(|(name, hash)| (name.to_string(), *hash))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let old = snapshot(&[("coreelements", 1), ("videotestsrc", 2), ("x264", 3)]);
+        let new = snapshot(&[("coreelements", 1), ("videotestsrc", 5), ("rsrtp", 4)]);
+
+        let change = old.diff(&new);
+        assert_eq!(change.added, vec!["rsrtp"]);
+        assert_eq!(change.removed, vec!["x264"]);
+        assert_eq!(change.changed, vec!["videotestsrc"]);
+        assert!(old.diff(&old).is_empty());
+    }
+}
@@ -2,8 +2,98 @@ use crate::{config::Configuration, handler::GStreamerHandler, pipeline::Pipeline
 use anyhow::Result;
 use rustyline::error::ReadlineError;
 use rustyline::DefaultEditor;
+use std::io::{IsTerminal, Write};
 use std::sync::Arc;
 
+/// Prompt for the lines after one ending with a backslash
+const CONTINUATION_PROMPT: &str = "...> ";
+
+/// Output longer than this many lines goes through the pager
+const PAGE_LINES: usize = 20;
+
+/// Pager used without $PAGER: quits at once when the output fits on one
+/// screen, keeps colors and leaves the output on the terminal
+const DEFAULT_PAGER: &str = "less -FRX";
+
+/// ANSI styles of the REPL output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Heading,
+    Name,
+    Dim,
+    Good,
+    Bad,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Heading => "1",
+            Style::Name => "36",
+            Style::Dim => "2",
+            Style::Good => "32",
+            Style::Bad => "31",
+        }
+    }
+}
+
+/// Wrap `text` in the escape codes of `style` when `color` is set
+fn paint(text: &str, style: Style, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", style.code(), text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Where the REPL prints results: colored and paged on a terminal, plain
+/// when redirected or with `--no-color` or `NO_COLOR`
+struct Output {
+    color: bool,
+    page: bool,
+}
+
+impl Output {
+    fn new(no_color: bool) -> Self {
+        let terminal = std::io::stdout().is_terminal();
+        Output {
+            color: terminal && !no_color && std::env::var_os("NO_COLOR").is_none(),
+            page: terminal,
+        }
+    }
+
+    fn paint(&self, text: &str, style: Style) -> String {
+        paint(text, style, self.color)
+    }
+
+    /// Print `text`, through $PAGER when it is too long for the screen.
+    /// Falls back to printing when the pager cannot be started.
+    fn show(&self, text: &str) {
+        if self.page && text.lines().count() > PAGE_LINES && self.run_pager(text).is_ok() {
+            return;
+        }
+        print!("{}", text);
+    }
+
+    fn run_pager(&self, text: &str) -> std::io::Result<()> {
+        let pager = std::env::var("PAGER")
+            .ok()
+            .filter(|pager| !pager.trim().is_empty())
+            .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+        let mut command = pager.split_whitespace();
+        let mut child = std::process::Command::new(command.next().unwrap_or_default())
+            .args(command)
+            .stdin(std::process::Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // The pager closes its input when quit early
+            let _ = stdin.write_all(text.as_bytes());
+        }
+        child.wait()?;
+        Ok(())
+    }
+}
+
 /// REPL commands
 enum ReplCommand {
     Help,
@@ -15,12 +105,13 @@ enum ReplCommand {
     Inspect(String),
     Search(String),
     Validate(String),
+    Edit(String),
     Exit,
 }
 
 impl ReplCommand {
     fn parse(input: &str) -> Result<Self> {
-        let parts: Vec<&str> = input.trim().split_whitespace().collect();
+        let parts: Vec<&str> = input.split_whitespace().collect();
         if parts.is_empty() {
             return Ok(Self::Help);
         }
@@ -70,6 +161,12 @@ impl ReplCommand {
                 }
                 Ok(Self::Validate(parts[1..].join(" ")))
             }
+            "edit" | "e" => {
+                if parts.len() != 2 {
+                    anyhow::bail!("Usage: edit <pipeline-id>");
+                }
+                Ok(Self::Edit(parts[1].to_string()))
+            }
             "exit" | "quit" | "q" => Ok(Self::Exit),
             _ => anyhow::bail!(
                 "Unknown command: {}. Type 'help' for available commands.",
@@ -79,8 +176,88 @@ impl ReplCommand {
     }
 }
 
+/// A line ending with an unescaped backslash continues on the next one;
+/// returns it without the backslash
+fn continuation(line: &str) -> Option<&str> {
+    let backslashes = line.len() - line.trim_end_matches('\\').len();
+    (backslashes % 2 == 1).then(|| &line[..line.len() - 1])
+}
+
+/// Join lines into a single command, dropping continuation backslashes
+fn join_lines(text: &str) -> String {
+    text.lines()
+        .map(|line| continuation(line).unwrap_or(line).trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Read a command, following lines that end with a backslash
+fn read_command(rl: &mut DefaultEditor, prompt: &str) -> rustyline::Result<String> {
+    let mut lines = vec![rl.readline(prompt)?];
+    while lines.last().and_then(|line| continuation(line)).is_some() {
+        lines.push(rl.readline(CONTINUATION_PROMPT)?);
+    }
+    Ok(join_lines(&lines.join("\n")))
+}
+
+/// Edit a pipeline description in $VISUAL or $EDITOR, where it may span
+/// several lines, or inline on the prompt without one
+fn edit_description(rl: &mut DefaultEditor, description: &str) -> Result<String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty());
+    let Some(editor) = editor else {
+        return match rl.readline_with_initial("edit> ", (description, "")) {
+            Ok(line) => Ok(join_lines(&line)),
+            Err(ReadlineError::Interrupted) => Ok(description.to_string()),
+            Err(e) => Err(e.into()),
+        };
+    };
+
+    let path =
+        std::env::temp_dir().join(format!("gstreamer-mcp-edit-{}.txt", uuid::Uuid::new_v4()));
+    std::fs::write(&path, format!("{}\n", description))?;
+    // The editor may come with arguments, as in `code --wait`
+    let mut command = editor.split_whitespace();
+    let status = std::process::Command::new(command.next().unwrap_or_default())
+        .args(command)
+        .arg(&path)
+        .status();
+    let text = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+    if !status?.success() {
+        anyhow::bail!("Editor '{}' exited with an error", editor);
+    }
+    Ok(join_lines(&text?))
+}
+
+/// Create a pipeline and set it playing; returns its ID once created
+fn launch_pipeline(
+    pipeline_manager: &PipelineManager,
+    description: &str,
+    custom_id: Option<String>,
+) -> Option<String> {
+    match pipeline_manager.create_pipeline(description, custom_id) {
+        Ok(id) => {
+            // Auto-play the pipeline
+            if let Err(e) = pipeline_manager.set_pipeline_state(&id, gstreamer::State::Playing) {
+                eprintln!("Pipeline created but failed to play: {}", e);
+            } else {
+                println!("Pipeline launched successfully with ID: {}", id);
+            }
+            Some(id)
+        }
+        Err(e) => {
+            eprintln!("Failed to launch pipeline: {}", e);
+            None
+        }
+    }
+}
+
 /// Run the interactive REPL mode
-pub async fn run_repl(config: Configuration) -> Result<()> {
+pub async fn run_repl(config: Configuration, no_color: bool) -> Result<()> {
     println!("GStreamer MCP REPL - Interactive Testing Mode");
     println!("Type 'help' for available commands or 'exit' to quit\n");
 
@@ -99,26 +276,28 @@ pub async fn run_repl(config: Configuration) -> Result<()> {
 
     // Store pipeline IDs for convenience
     let mut last_pipeline_id: Option<String> = None;
+    let output = Output::new(no_color);
 
     loop {
-        let readline = rl.readline(prompt);
+        let readline = read_command(&mut rl, prompt);
         match readline {
             Ok(line) => {
-                let line = line.trim();
                 if line.is_empty() {
                     continue;
                 }
 
                 // Add to history
-                let _ = rl.add_history_entry(line);
+                let _ = rl.add_history_entry(line.as_str());
 
                 // Parse and execute command
-                match ReplCommand::parse(line) {
+                match ReplCommand::parse(&line) {
                     Ok(cmd) => {
                         let result = execute_command(
                             cmd,
                             &handler,
                             &pipeline_manager,
+                            &mut rl,
+                            &output,
                             &mut last_pipeline_id,
                         )
                         .await;
@@ -159,6 +338,8 @@ async fn execute_command(
     cmd: ReplCommand,
     _handler: &GStreamerHandler,
     pipeline_manager: &Arc<PipelineManager>,
+    rl: &mut DefaultEditor,
+    output: &Output,
     last_pipeline_id: &mut Option<String>,
 ) -> Result<bool> {
     match cmd {
@@ -171,32 +352,22 @@ async fn execute_command(
             if pipelines.is_empty() {
                 println!("No active pipelines");
             } else {
-                println!("Active pipelines:");
+                let mut text = format!("{}\n", output.paint("Active pipelines:", Style::Heading));
                 for info in pipelines {
-                    println!(
-                        "  {} - State: {}, Description: {}",
-                        info.id, info.state, info.description
-                    );
+                    text.push_str(&format!(
+                        "  {} - State: {}, Description: {}\n",
+                        output.paint(&info.id, Style::Name),
+                        info.state,
+                        info.description
+                    ));
                 }
+                output.show(&text);
             }
             Ok(false)
         }
         ReplCommand::Launch(description) => {
-            match pipeline_manager.create_pipeline(&description, None) {
-                Ok(id) => {
-                    // Auto-play the pipeline
-                    if let Err(e) =
-                        pipeline_manager.set_pipeline_state(&id, gstreamer::State::Playing)
-                    {
-                        eprintln!("Pipeline created but failed to play: {}", e);
-                    } else {
-                        println!("Pipeline launched successfully with ID: {}", id);
-                    }
-                    *last_pipeline_id = Some(id);
-                }
-                Err(e) => {
-                    eprintln!("Failed to launch pipeline: {}", e);
-                }
+            if let Some(id) = launch_pipeline(pipeline_manager, &description, None) {
+                *last_pipeline_id = Some(id);
             }
             Ok(false)
         }
@@ -268,20 +439,49 @@ async fn execute_command(
         ReplCommand::Inspect(element) => {
             match crate::discovery::inspect_element(&element) {
                 Ok(info) => {
-                    println!("Element: {}", info.name);
-                    println!("Description: {}", info.description);
-                    println!("Classification: {}", info.classification);
-                    println!("\nProperties:");
+                    let mut text = format!(
+                        "{} {}\n",
+                        output.paint("Element:", Style::Heading),
+                        output.paint(&info.name, Style::Name)
+                    );
+                    text.push_str(&format!("Description: {}\n", info.description));
+                    text.push_str(&format!("Classification: {}\n", info.classification));
+                    text.push_str(&format!(
+                        "\n{}\n",
+                        output.paint("Properties:", Style::Heading)
+                    ));
                     for prop in &info.properties {
-                        println!(
-                            "  {} ({}) - {}",
-                            prop.name, prop.type_name, prop.description
-                        );
+                        text.push_str(&format!(
+                            "  {} {} - {}\n",
+                            output.paint(&prop.name, Style::Name),
+                            output.paint(&format!("({})", prop.type_name), Style::Dim),
+                            prop.description
+                        ));
+                        if let Some(default) = &prop.default_value {
+                            text.push_str(&format!(
+                                "      {}\n",
+                                output.paint(&format!("default: {}", default), Style::Dim)
+                            ));
+                        }
                     }
-                    println!("\nPad Templates:");
+                    text.push_str(&format!(
+                        "\n{}\n",
+                        output.paint("Pad Templates:", Style::Heading)
+                    ));
                     for pad in &info.pad_templates {
-                        println!("  {} ({:?}) - Caps: {}", pad.name, pad.direction, pad.caps);
+                        text.push_str(&format!(
+                            "  {} {}\n",
+                            output.paint(&pad.name, Style::Name),
+                            output.paint(
+                                &format!("({}, {})", pad.direction, pad.presence),
+                                Style::Dim
+                            )
+                        ));
+                        for caps in pad.caps.split("; ") {
+                            text.push_str(&format!("      {}\n", caps));
+                        }
                     }
+                    output.show(&text);
                 }
                 Err(e) => {
                     eprintln!("Failed to inspect element: {}", e);
@@ -295,13 +495,19 @@ async fn execute_command(
                     if results.is_empty() {
                         println!("No elements found matching '{}'", query);
                     } else {
-                        println!("Elements matching '{}':", query);
-                        for result in results.iter().take(10) {
-                            println!("  {} - {}", result.name, result.classification);
-                        }
-                        if results.len() > 10 {
-                            println!("  ... and {} more", results.len() - 10);
+                        let mut text = format!(
+                            "{}\n",
+                            output
+                                .paint(&format!("Elements matching '{}':", query), Style::Heading)
+                        );
+                        for result in &results {
+                            text.push_str(&format!(
+                                "  {} - {}\n",
+                                output.paint(&result.name, Style::Name),
+                                result.classification
+                            ));
                         }
+                        output.show(&text);
                     }
                 }
                 Err(e) => {
@@ -313,18 +519,51 @@ async fn execute_command(
         ReplCommand::Validate(description) => {
             match crate::pipeline::validate_pipeline_description(&description) {
                 Ok(elements) => {
-                    println!("Pipeline is valid!");
-                    println!("Elements that would be created:");
+                    let mut text = format!(
+                        "{}\nElements that would be created:\n",
+                        output.paint("Pipeline is valid!", Style::Good)
+                    );
                     for element in &elements {
-                        println!("  - {}", element);
+                        text.push_str(&format!("  - {}\n", output.paint(element, Style::Name)));
                     }
+                    output.show(&text);
                 }
                 Err(e) => {
-                    println!("Pipeline is invalid: {}", e);
+                    println!("{} {}", output.paint("Pipeline is invalid:", Style::Bad), e);
                 }
             }
             Ok(false)
         }
+        ReplCommand::Edit(id) => {
+            let pipeline_id = resolve_pipeline_id(&id, last_pipeline_id)?;
+            let Some(info) = pipeline_manager
+                .list_pipelines()
+                .into_iter()
+                .find(|info| info.id == pipeline_id)
+            else {
+                eprintln!("Pipeline '{}' not found", pipeline_id);
+                return Ok(false);
+            };
+
+            let description = edit_description(rl, &info.description)?;
+            if description.is_empty() || description == info.description {
+                println!("Pipeline {} unchanged", pipeline_id);
+                return Ok(false);
+            }
+            // Keep the running pipeline if the new one cannot be built
+            if let Err(e) = crate::pipeline::validate_pipeline_description(&description) {
+                eprintln!("Pipeline is invalid, keeping the running one: {}", e);
+                return Ok(false);
+            }
+            if let Err(e) = pipeline_manager.stop_pipeline(&pipeline_id) {
+                eprintln!("Failed to stop pipeline: {}", e);
+                return Ok(false);
+            }
+            if let Some(id) = launch_pipeline(pipeline_manager, &description, Some(pipeline_id)) {
+                *last_pipeline_id = Some(id);
+            }
+            Ok(false)
+        }
         ReplCommand::Exit => Ok(true),
     }
 }
@@ -350,10 +589,44 @@ fn print_help() {
     println!("  inspect <element>             - Inspect an element");
     println!("  search <query>                - Search for elements");
     println!("  validate <description>        - Validate pipeline syntax");
+    println!("  edit <pipeline-id>            - Edit a pipeline's description and relaunch it");
     println!("  exit, quit, q                 - Exit REPL");
     println!();
     println!("Tips:");
     println!("  - Use 'last' or '.' as pipeline-id to refer to the last launched pipeline");
     println!("  - Pipeline descriptions use gst-launch syntax");
+    println!("  - End a line with '\\' to continue a command on the next line");
+    println!("  - 'edit' opens $VISUAL or $EDITOR if set, and edits inline otherwise");
+    println!("  - Long output goes through $PAGER (default: less); --no-color or NO_COLOR turn off colors");
     println!("  - Example: launch videotestsrc ! autovideosink");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint() {
+        assert_eq!(
+            paint("videoconvert", Style::Name, true),
+            "\x1b[36mvideoconvert\x1b[0m"
+        );
+        assert_eq!(paint("videoconvert", Style::Name, false), "videoconvert");
+    }
+
+    #[test]
+    fn test_join_lines() {
+        assert_eq!(continuation("videotestsrc !\\"), Some("videotestsrc !"));
+        assert_eq!(continuation("location=C:\\\\"), None);
+        assert_eq!(continuation("fakesink"), None);
+        assert_eq!(
+            join_lines("launch videotestsrc ! \\\n    videoconvert \\\n  ! autovideosink"),
+            "launch videotestsrc ! videoconvert ! autovideosink"
+        );
+        // Lines from an editor join without backslashes
+        assert_eq!(
+            join_lines("filesrc location=a.mp4\n\n! decodebin ! fakesink\n"),
+            "filesrc location=a.mp4 ! decodebin ! fakesink"
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// Element applying a gain matrix from input to output channels
+const MIX_MATRIX_FACTORY: &str = "audiomixmatrix";
+
+/// One input channel feeding one output channel
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+pub struct AudioRoute {
+    #[schemars(description = "Input channel index, starting at 0")]
+    pub input: u32,
+    #[schemars(description = "Output channel index, starting at 0")]
+    pub output: u32,
+    #[schemars(description = "Gain in dB applied on this route (default: 0, unity)")]
+    pub gain_db: Option<f64>,
+}
+
+/// Routing currently applied by an audiomixmatrix element
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioRouting {
+    pub element: String,
+    pub in_channels: u32,
+    pub out_channels: u32,
+    /// Linear gains, one row per output channel and one column per input
+    pub matrix: Vec<Vec<f32>>,
+}
+
+/// Build the gain matrix for `routes`. Outputs without a route are silent;
+/// several routes to one output are summed.
+pub fn routing_matrix(
+    routes: &[AudioRoute],
+    in_channels: u32,
+    out_channels: u32,
+) -> McpResult<Vec<Vec<f32>>> {
+    let mut matrix = vec![vec![0.0f32; in_channels as usize]; out_channels as usize];
+    for route in routes {
+        if route.input >= in_channels || route.output >= out_channels {
+            return Err(GStreamerMcpError::PropertyError(format!(
+                "Route {} → {} is outside the {} input and {} output channels",
+                route.input, route.output, in_channels, out_channels
+            )));
+        }
+        let gain = 10f64.powf(route.gain_db.unwrap_or(0.0) / 20.0) as f32;
+        matrix[route.output as usize][route.input as usize] += gain;
+    }
+    Ok(matrix)
+}
+
+fn find_mix_matrix(
+    pipeline: &gst::Pipeline,
+    element_name: Option<&str>,
+) -> McpResult<gst::Element> {
+    pipeline
+        .iterate_recurse()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.factory().is_some_and(|f| f.name() == MIX_MATRIX_FACTORY))
+        .find(|e| element_name.is_none_or(|name| e.name() == name))
+        .ok_or_else(|| {
+            GStreamerMcpError::PipelineError(match element_name {
+                Some(name) => format!("No audiomixmatrix named '{}' in pipeline", name),
+                None => "Pipeline does not contain an audiomixmatrix. Add e.g. \
+                         'audiomixmatrix name=router in-channels=8 out-channels=2 mode=manual' \
+                         where channels should be routed"
+                    .to_string(),
+            })
+        })
+}
+
+fn read_routing(element: &gst::Element) -> AudioRouting {
+    let matrix = element
+        .property::<gst::Array>("matrix")
+        .iter()
+        .map(|row| {
+            row.get::<gst::Array>()
+                .map(|row| row.iter().filter_map(|g| g.get::<f32>().ok()).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+    AudioRouting {
+        element: element.name().to_string(),
+        in_channels: element.property::<u32>("in-channels"),
+        out_channels: element.property::<u32>("out-channels"),
+        matrix,
+    }
+}
+
+/// Replace the routing of an audiomixmatrix element in a (running)
+/// pipeline. The channel counts are fixed by the element's configuration;
+/// only the gains change.
+pub fn route_audio(
+    pipeline: &gst::Pipeline,
+    element_name: Option<&str>,
+    routes: &[AudioRoute],
+) -> McpResult<AudioRouting> {
+    let element = find_mix_matrix(pipeline, element_name)?;
+    let current = read_routing(&element);
+    if current.in_channels == 0 || current.out_channels == 0 {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "Set in-channels and out-channels on '{}' to route channels manually",
+            current.element
+        )));
+    }
+
+    let matrix = routing_matrix(routes, current.in_channels, current.out_channels)?;
+    let value = gst::Array::new(
+        matrix
+            .iter()
+            .map(|row| gst::Array::new(row.iter().copied())),
+    );
+    element.set_property("matrix", value);
+    Ok(read_routing(&element))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routing_matrix() {
+        let routes = [
+            AudioRoute {
+                input: 0,
+                output: 0,
+                gain_db: None,
+            },
+            AudioRoute {
+                input: 3,
+                output: 1,
+                gain_db: Some(-6.0),
+            },
+            AudioRoute {
+                input: 2,
+                output: 1,
+                gain_db: None,
+            },
+        ];
+        let matrix = routing_matrix(&routes, 4, 2).unwrap();
+        assert_eq!(matrix[0], [1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(matrix[1][2], 1.0);
+        assert!((matrix[1][3] - 0.501187).abs() < 1e-6);
+
+        let outside = AudioRoute {
+            input: 4,
+            output: 0,
+            gain_db: None,
+        };
+        assert!(routing_matrix(&[outside], 4, 2).is_err());
+    }
+}
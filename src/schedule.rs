@@ -0,0 +1,421 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_video::VideoTimeCodeMeta;
+use parking_lot::Mutex;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::pipeline::{parse_property_value, set_property_str};
+use crate::prerecord::{keyframe_gate, pre_record_time};
+use crate::probes::find_pad;
+use crate::timecode::{parse_timecode, Timecode};
+
+/// What to do when the scheduled time is reached
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScheduledAction {
+    /// Make `pad` the active input of an input-selector, e.g. to cut to
+    /// another camera or to a slate
+    SwitchInput { selector: String, pad: String },
+    /// Let data through a valve in front of a recording branch, from the
+    /// next keyframe
+    StartRecording { valve: String },
+    /// Stop data at a valve in front of a recording branch
+    StopRecording { valve: String },
+    /// Set any property, from its gst-launch string form
+    SetProperty {
+        element: String,
+        property: String,
+        value: String,
+    },
+}
+
+impl std::fmt::Display for ScheduledAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScheduledAction::SwitchInput { selector, pad } => {
+                write!(f, "switch {} to {}", selector, pad)
+            }
+            ScheduledAction::StartRecording { valve } => write!(f, "start recording at {}", valve),
+            ScheduledAction::StopRecording { valve } => write!(f, "stop recording at {}", valve),
+            ScheduledAction::SetProperty {
+                element,
+                property,
+                value,
+            } => write!(f, "set {}.{} = {}", element, property, value),
+        }
+    }
+}
+
+/// When a scheduled action runs
+#[derive(Debug, Clone, Copy)]
+pub enum ScheduleTrigger {
+    /// First buffer at or after this running time
+    RunningTime(gst::ClockTime),
+    /// First buffer timecoded at or after this timecode
+    Timecode(Timecode),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleStatus {
+    Pending,
+    Fired,
+    Failed,
+    Cancelled,
+}
+
+impl std::fmt::Display for ScheduleStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ScheduleStatus::Pending => "pending",
+            ScheduleStatus::Fired => "fired",
+            ScheduleStatus::Failed => "failed",
+            ScheduleStatus::Cancelled => "cancelled",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledEntry {
+    pub id: String,
+    pub pipeline_id: String,
+    pub action: ScheduledAction,
+    pub running_time_ms: Option<u64>,
+    pub timecode: Option<String>,
+    /// Pad whose buffers are watched, as `element.pad`
+    pub trigger_pad: String,
+    pub status: ScheduleStatus,
+    /// Running time of the buffer the action ran on
+    pub fired_at_ns: Option<u64>,
+    pub error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+type Execute = Box<dyn Fn() -> McpResult<()> + Send + Sync>;
+
+/// Resolve the elements of an action. Returns how to run it and the pad
+/// watched when none is given: the pad whose next buffer the action
+/// affects.
+fn prepare_action(
+    pipeline: &gst::Pipeline,
+    action: &ScheduledAction,
+) -> McpResult<(Execute, gst::Pad)> {
+    let element = |name: &str| {
+        pipeline.by_name(name).ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Element '{}' not found in pipeline", name))
+        })
+    };
+    let first_pad = |element: &gst::Element| {
+        element
+            .sink_pads()
+            .into_iter()
+            .next()
+            .or_else(|| element.src_pads().into_iter().next())
+            .ok_or_else(|| {
+                GStreamerMcpError::PipelineError(format!("{} has no pads", element.name()))
+            })
+    };
+
+    match action {
+        ScheduledAction::SwitchInput { selector, pad } => {
+            let selector = element(selector)?;
+            if selector.find_property("active-pad").is_none() {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "{} is not an input-selector",
+                    selector.name()
+                )));
+            }
+            let target = selector
+                .sink_pads()
+                .into_iter()
+                .find(|p| p.name() == pad.as_str())
+                .ok_or_else(|| {
+                    GStreamerMcpError::PipelineError(format!(
+                        "{} has no input pad '{}'",
+                        selector.name(),
+                        pad
+                    ))
+                })?;
+            // The active input carries the frame at which to cut
+            let trigger = selector
+                .property::<Option<gst::Pad>>("active-pad")
+                .map_or_else(|| first_pad(&selector), Ok)?;
+            let execute: Execute = Box::new(move || {
+                selector.set_property("active-pad", &target);
+                Ok(())
+            });
+            Ok((execute, trigger))
+        }
+        ScheduledAction::StartRecording { valve } | ScheduledAction::StopRecording { valve } => {
+            let valve = element(valve)?;
+            if valve.find_property("drop").is_none() {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "{} is not a valve",
+                    valve.name()
+                )));
+            }
+            let drop = matches!(action, ScheduledAction::StopRecording { .. });
+            let trigger = first_pad(&valve)?;
+            let execute: Execute = Box::new(move || {
+                if !drop {
+                    keyframe_gate(&valve);
+                }
+                valve.set_property("drop", drop);
+                Ok(())
+            });
+            Ok((execute, trigger))
+        }
+        ScheduledAction::SetProperty {
+            element: name,
+            property,
+            value,
+        } => {
+            let target = element(name)?;
+            // Fail now rather than when the time comes
+            parse_property_value(&target, property, value)?;
+            let trigger = first_pad(&target)?;
+            let (property, value) = (property.clone(), value.clone());
+            let execute: Execute = Box::new(move || set_property_str(&target, &property, &value));
+            Ok((execute, trigger))
+        }
+    }
+}
+
+fn pad_spec(pad: &gst::Pad) -> String {
+    match pad.parent_element() {
+        Some(element) => format!("{}.{}", element.name(), pad.name()),
+        None => pad.name().to_string(),
+    }
+}
+
+/// Running time of a buffer on `pad`, from the pad's current segment
+pub fn running_time(pad: &gst::Pad, buffer: &gst::BufferRef) -> Option<gst::ClockTime> {
+    let event = pad.sticky_event::<gst::event::Segment>(0)?;
+    let segment = event.segment().downcast_ref::<gst::ClockTime>()?.clone();
+    segment.to_running_time(buffer.pts()?)
+}
+
+/// Actions waiting for their time in running pipelines
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    entries: Arc<Mutex<HashMap<String, ScheduledEntry>>>,
+    /// Probes of pending actions
+    probes: Arc<Mutex<HashMap<String, (gst::Pad, gst::PadProbeId)>>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schedule `action` in `pipeline`. The action runs from a pad probe on
+    /// the streaming thread, before the triggering buffer goes on, so it
+    /// takes effect exactly at that frame. `on_done` gets the entry once the
+    /// action ran or failed.
+    pub fn schedule(
+        &self,
+        pipeline: &gst::Pipeline,
+        pipeline_id: &str,
+        action: ScheduledAction,
+        trigger: ScheduleTrigger,
+        pad: Option<&str>,
+        on_done: impl Fn(ScheduledEntry) + Send + Sync + 'static,
+    ) -> McpResult<ScheduledEntry> {
+        let (execute, default_pad) = prepare_action(pipeline, &action)?;
+        let pad = match pad {
+            Some(spec) => find_pad(pipeline, spec)?,
+            None => default_pad,
+        };
+
+        let id = format!("action-{}", uuid::Uuid::new_v4());
+        let entry = ScheduledEntry {
+            id: id.clone(),
+            pipeline_id: pipeline_id.to_string(),
+            action,
+            running_time_ms: match trigger {
+                ScheduleTrigger::RunningTime(time) => Some(time.mseconds()),
+                ScheduleTrigger::Timecode(_) => None,
+            },
+            timecode: match trigger {
+                ScheduleTrigger::Timecode(timecode) => Some(format!(
+                    "{:02}:{:02}:{:02}:{:02}",
+                    timecode.hours, timecode.minutes, timecode.seconds, timecode.frames
+                )),
+                ScheduleTrigger::RunningTime(_) => None,
+            },
+            trigger_pad: pad_spec(&pad),
+            status: ScheduleStatus::Pending,
+            fired_at_ns: None,
+            error: None,
+            created_at: chrono::Utc::now(),
+        };
+        self.entries.lock().insert(id.clone(), entry.clone());
+
+        // Behind a pre-record buffer the valve sees the stream that much
+        // later; open it on the data from that long before the time
+        let trigger = match (&entry.action, trigger) {
+            (ScheduledAction::StartRecording { valve }, ScheduleTrigger::RunningTime(time)) => {
+                let lead = pipeline
+                    .by_name(valve)
+                    .and_then(|valve| pre_record_time(&valve))
+                    .unwrap_or(gst::ClockTime::ZERO);
+                ScheduleTrigger::RunningTime(time.saturating_sub(lead))
+            }
+            (_, trigger) => trigger,
+        };
+
+        let (entries, probes) = (self.entries.clone(), self.probes.clone());
+        let probe_id = {
+            let id = id.clone();
+            pad.add_probe(gst::PadProbeType::BUFFER, move |pad, info| {
+                let Some(buffer) = info.buffer() else {
+                    return gst::PadProbeReturn::Ok;
+                };
+                let reached = match trigger {
+                    ScheduleTrigger::RunningTime(target) => {
+                        running_time(pad, buffer).is_some_and(|time| time >= target)
+                    }
+                    ScheduleTrigger::Timecode(target) => {
+                        buffer.meta::<VideoTimeCodeMeta>().is_some_and(|meta| {
+                            let tc = meta.tc();
+                            (tc.hours(), tc.minutes(), tc.seconds(), tc.frames()) >= target.key()
+                        })
+                    }
+                };
+                if !reached {
+                    return gst::PadProbeReturn::Ok;
+                }
+                let done = {
+                    let mut entries = entries.lock();
+                    // Cancelled meanwhile
+                    let Some(entry) = entries
+                        .get_mut(&id)
+                        .filter(|e| e.status == ScheduleStatus::Pending)
+                    else {
+                        return gst::PadProbeReturn::Remove;
+                    };
+                    entry.fired_at_ns = running_time(pad, buffer).map(|t| t.nseconds());
+                    // A panic must not unwind out of the streaming thread
+                    let result = std::panic::catch_unwind(AssertUnwindSafe(&execute))
+                        .unwrap_or_else(|panic| {
+                            let message = panic
+                                .downcast_ref::<&str>()
+                                .map(|s| s.to_string())
+                                .or_else(|| panic.downcast_ref::<String>().cloned())
+                                .unwrap_or_else(|| "unknown panic".to_string());
+                            Err(GStreamerMcpError::Other(format!(
+                                "Action panicked: {}",
+                                message
+                            )))
+                        });
+                    match result {
+                        Ok(()) => entry.status = ScheduleStatus::Fired,
+                        Err(e) => {
+                            entry.status = ScheduleStatus::Failed;
+                            entry.error = Some(e.to_string());
+                        }
+                    }
+                    entry.clone()
+                };
+                probes.lock().remove(&id);
+                on_done(done);
+                gst::PadProbeReturn::Remove
+            })
+        }
+        .ok_or_else(|| {
+            self.entries.lock().remove(&id);
+            GStreamerMcpError::PipelineError(format!(
+                "Failed to install probe on '{}'",
+                entry.trigger_pad
+            ))
+        })?;
+        self.probes.lock().insert(id, (pad, probe_id));
+
+        Ok(entry)
+    }
+
+    /// Scheduled actions, oldest first, optionally of one pipeline
+    pub fn list(&self, pipeline_id: Option<&str>) -> Vec<ScheduledEntry> {
+        let mut entries: Vec<ScheduledEntry> = self
+            .entries
+            .lock()
+            .values()
+            .filter(|e| pipeline_id.is_none_or(|id| e.pipeline_id == id))
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| e.created_at);
+        entries
+    }
+
+    /// Cancel a pending action
+    pub fn cancel(&self, id: &str) -> McpResult<ScheduledEntry> {
+        let entry = {
+            let mut entries = self.entries.lock();
+            let entry = entries.get_mut(id).ok_or_else(|| {
+                GStreamerMcpError::PipelineError(format!("Scheduled action '{}' not found", id))
+            })?;
+            if entry.status != ScheduleStatus::Pending {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "Scheduled action '{}' is already {}",
+                    id, entry.status
+                )));
+            }
+            entry.status = ScheduleStatus::Cancelled;
+            entry.clone()
+        };
+        if let Some((pad, probe_id)) = self.probes.lock().remove(id) {
+            pad.remove_probe(probe_id);
+        }
+        Ok(entry)
+    }
+}
+
+/// Trigger from a running time in milliseconds or a `HH:MM:SS:FF`
+/// timecode; exactly one must be given
+pub fn parse_trigger(
+    running_time_ms: Option<u64>,
+    timecode: Option<&str>,
+) -> McpResult<ScheduleTrigger> {
+    match (running_time_ms, timecode) {
+        (Some(ms), None) => Ok(ScheduleTrigger::RunningTime(gst::ClockTime::from_mseconds(
+            ms,
+        ))),
+        (None, Some(timecode)) => Ok(ScheduleTrigger::Timecode(parse_timecode(timecode)?)),
+        _ => Err(GStreamerMcpError::Other(
+            "Give either a running time or a timecode to schedule at".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_trigger() {
+        assert!(matches!(
+            parse_trigger(Some(1500), None),
+            Ok(ScheduleTrigger::RunningTime(t)) if t.mseconds() == 1500
+        ));
+        assert!(matches!(
+            parse_trigger(None, Some("01:00:10:00")),
+            Ok(ScheduleTrigger::Timecode(tc)) if tc.key() == (1, 0, 10, 0)
+        ));
+        assert!(parse_trigger(Some(1), Some("01:00:10:00")).is_err());
+        assert!(parse_trigger(None, None).is_err());
+    }
+
+    #[test]
+    fn test_action_format() {
+        let action: ScheduledAction =
+            serde_json::from_str(r#"{"type": "switch_input", "selector": "sel", "pad": "sink_1"}"#)
+                .unwrap();
+        assert_eq!(action.to_string(), "switch sel to sink_1");
+    }
+}
@@ -0,0 +1,293 @@
+//! Pipeline state shared between gstreamer-mcp instances on the same host,
+//! through a directory on a local filesystem. Instances on other hosts
+//! cannot take part: ID reservations need atomic exclusive file creation,
+//! which network filesystems do not guarantee.
+
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::pipeline::{PipelineInfo, PipelineManager};
+
+/// How often each instance republishes its pipelines
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Entries not refreshed for this long belong to instances that have exited
+const STALE_AFTER: Duration = Duration::from_secs(15);
+
+/// Pipelines published by one gstreamer-mcp instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceState {
+    pub instance_id: String,
+    pub pid: u32,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub pipelines: Vec<PipelineInfo>,
+}
+
+/// Claim of a pipeline ID by one instance, held in a lock file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdReservation {
+    pub pipeline_id: String,
+    pub instance_id: String,
+    pub pid: u32,
+    pub reserved_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A pipeline ID as a file name: bytes other than ASCII letters, digits,
+/// `-` and `_` are percent-encoded, so distinct IDs never share a file and
+/// no ID escapes the directory
+pub fn encode_id(id: &str) -> String {
+    let mut name = String::with_capacity(id.len());
+    for byte in id.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    name
+}
+
+/// Pipeline registry shared between instances on the same host through a
+/// directory holding one JSON file per instance. Other instances' pipelines
+/// are visible read-only. Custom pipeline IDs are reserved atomically with
+/// a lock file each in `ids/`, created only if it does not exist yet.
+#[derive(Debug, Clone)]
+pub struct SharedState {
+    dir: PathBuf,
+    instance_id: String,
+}
+
+impl SharedState {
+    pub fn new(dir: impl AsRef<Path>) -> McpResult<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(dir.join("ids"))?;
+        Ok(Self {
+            dir,
+            instance_id: format!("instance-{}", uuid::Uuid::new_v4()),
+        })
+    }
+
+    pub fn instance_id(&self) -> &str {
+        &self.instance_id
+    }
+
+    fn state_path(&self, instance_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", instance_id))
+    }
+
+    fn reservation_path(&self, pipeline_id: &str) -> PathBuf {
+        self.dir
+            .join("ids")
+            .join(format!("{}.lock", encode_id(pipeline_id)))
+    }
+
+    /// Publish this instance's pipelines, replacing the previous entry
+    pub fn publish(&self, pipelines: Vec<PipelineInfo>) -> McpResult<()> {
+        let state = InstanceState {
+            instance_id: self.instance_id.clone(),
+            pid: std::process::id(),
+            updated_at: chrono::Utc::now(),
+            pipelines,
+        };
+
+        // Write then rename so readers never see a partial file
+        let path = self.state_path(&self.instance_id);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec(&state)?)?;
+        std::fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Remove this instance's entry and reservations, e.g. on shutdown
+    pub fn withdraw(&self) {
+        let _ = std::fs::remove_file(self.state_path(&self.instance_id));
+        for reservation in self.reservations() {
+            if reservation.instance_id == self.instance_id {
+                self.release_id(&reservation.pipeline_id);
+            }
+        }
+    }
+
+    fn read_reservation(&self, pipeline_id: &str) -> Option<IdReservation> {
+        let content = std::fs::read(self.reservation_path(pipeline_id)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// All reservations, of every instance
+    pub fn reservations(&self) -> Vec<IdReservation> {
+        let Ok(entries) = std::fs::read_dir(self.dir.join("ids")) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| std::fs::read(entry.ok()?.path()).ok())
+            .filter_map(|content| serde_json::from_slice(&content).ok())
+            .collect()
+    }
+
+    /// Claim `pipeline_id` for this instance. Creating the lock file fails
+    /// if it exists, so of two instances claiming the same ID at once only
+    /// one succeeds. Reservations of instances that stopped publishing are
+    /// taken over.
+    pub fn reserve_id(&self, pipeline_id: &str) -> McpResult<()> {
+        let reservation = IdReservation {
+            pipeline_id: pipeline_id.to_string(),
+            instance_id: self.instance_id.clone(),
+            pid: std::process::id(),
+            reserved_at: chrono::Utc::now(),
+        };
+        let path = self.reservation_path(pipeline_id);
+
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(&serde_json::to_vec(&reservation)?)?;
+                    return Ok(());
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e.into()),
+            }
+
+            // A file being written has no content yet; count it as held
+            let Some(holder) = self.read_reservation(pipeline_id) else {
+                break;
+            };
+            if holder.instance_id == self.instance_id {
+                return Ok(());
+            }
+            let live = self
+                .other_instances()?
+                .iter()
+                .any(|instance| instance.instance_id == holder.instance_id);
+            let age = (chrono::Utc::now() - holder.reserved_at)
+                .to_std()
+                .unwrap_or_default();
+            if live || age <= STALE_AFTER {
+                return Err(GStreamerMcpError::PipelineError(format!(
+                    "Pipeline ID '{}' is already used by {} (pid {})",
+                    pipeline_id, holder.instance_id, holder.pid
+                )));
+            }
+            tracing::info!(
+                "Taking over pipeline ID '{}' from exited instance {}",
+                pipeline_id,
+                holder.instance_id
+            );
+            let _ = std::fs::remove_file(&path);
+        }
+        Err(GStreamerMcpError::PipelineError(format!(
+            "Pipeline ID '{}' is being reserved by another instance",
+            pipeline_id
+        )))
+    }
+
+    /// Give up this instance's claim of `pipeline_id`, if it holds one
+    pub fn release_id(&self, pipeline_id: &str) {
+        if self
+            .read_reservation(pipeline_id)
+            .is_some_and(|holder| holder.instance_id == self.instance_id)
+        {
+            let _ = std::fs::remove_file(self.reservation_path(pipeline_id));
+        }
+    }
+
+    /// State published by the other live instances
+    pub fn other_instances(&self) -> McpResult<Vec<InstanceState>> {
+        let now = chrono::Utc::now();
+        let mut instances = Vec::new();
+
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            // Files may vanish or be replaced while we read; skip those
+            let Ok(content) = std::fs::read(&path) else {
+                continue;
+            };
+            let Ok(state) = serde_json::from_slice::<InstanceState>(&content) else {
+                continue;
+            };
+
+            if state.instance_id == self.instance_id {
+                continue;
+            }
+            let age = (now - state.updated_at).to_std().unwrap_or_default();
+            if age > STALE_AFTER {
+                let _ = std::fs::remove_file(&path);
+                continue;
+            }
+            instances.push(state);
+        }
+
+        instances.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
+        Ok(instances)
+    }
+}
+
+/// Periodically publish the local pipelines so other instances see them
+pub fn spawn_heartbeat(
+    shared: Arc<SharedState>,
+    pipeline_manager: Arc<PipelineManager>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = shared.publish(pipeline_manager.list_pipelines()) {
+                tracing::warn!("Failed to publish shared pipeline state: {}", e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instances_see_each_other() {
+        let dir = std::env::temp_dir().join(format!("gstreamer-mcp-test-{}", uuid::Uuid::new_v4()));
+        let first = SharedState::new(&dir).unwrap();
+        let second = SharedState::new(&dir).unwrap();
+
+        let info = PipelineInfo {
+            id: "ingest".to_string(),
+            description: "videotestsrc ! fakesink".to_string(),
+            state: "Playing".to_string(),
+            created_at: chrono::Utc::now(),
+            last_state_change: chrono::Utc::now(),
+            error_count: 0,
+            warning_count: 0,
+            health: Default::default(),
+            labels: Default::default(),
+            restart_count: 0,
+            metadata: Default::default(),
+            dedupe_key: None,
+            owner: None,
+        };
+        first.publish(vec![info]).unwrap();
+        second.publish(Vec::new()).unwrap();
+
+        let others = second.other_instances().unwrap();
+        assert_eq!(others.len(), 1);
+        assert_eq!(others[0].instance_id, first.instance_id());
+
+        first.reserve_id("ingest").unwrap();
+        assert!(second.reserve_id("ingest").is_err());
+        assert!(first.reserve_id("ingest").is_ok());
+        second.reserve_id("other").unwrap();
+        first.release_id("other");
+        assert!(first.reserve_id("other").is_err());
+        first.release_id("ingest");
+        assert!(second.reserve_id("ingest").is_ok());
+        assert_eq!(encode_id("a/b"), "a%2Fb");
+        assert_ne!(encode_id("a/b"), encode_id("a_b"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
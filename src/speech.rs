@@ -0,0 +1,114 @@
+use gstreamer as gst;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::{quote_location, require_element};
+use crate::pipeline::attach_tee_branch;
+
+/// Speech-to-text elements that output the transcript as text buffers, in
+/// order of preference
+pub const TRANSCRIBERS: &[&str] = &[
+    "whisper",
+    "vosktranscriber",
+    "speechmaticstranscriber",
+    "awstranscriber",
+];
+
+/// Text-to-speech elements reading plain text and producing WAV
+pub const SYNTHESIZERS: &[&str] = &["festival"];
+
+/// A piece of transcript produced by a transcriber branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub pipeline_id: String,
+    pub branch: String,
+    pub text: String,
+    /// Running time of the speech in the pipeline
+    pub pts_ns: Option<u64>,
+    pub duration_ns: Option<u64>,
+}
+
+/// Branch a transcriber off a tee of a running pipeline. Every transcript
+/// buffer it outputs is passed to `on_segment` from a streaming thread.
+/// Returns the name of the added branch.
+pub fn attach_transcriber(
+    pipeline: &gst::Pipeline,
+    pipeline_id: &str,
+    tee_name: Option<&str>,
+    transcriber: Option<&str>,
+    on_segment: impl Fn(TranscriptSegment) + Send + Sync + 'static,
+) -> McpResult<String> {
+    let factory = match transcriber {
+        Some(name) if gst::ElementFactory::find(name).is_some() => name.to_string(),
+        Some(name) => {
+            return Err(GStreamerMcpError::ElementNotFound(format!(
+                "{} is not installed",
+                name
+            )))
+        }
+        None => require_element(TRANSCRIBERS, "speech-to-text element")?.to_string(),
+    };
+
+    // Leaky queue: a slow recognizer must never stall the main pipeline
+    let description = format!(
+        "queue leaky=downstream max-size-time=5000000000 ! audioconvert ! audioresample ! {} ! fakesink name=sink signal-handoffs=true sync=false async=false",
+        factory
+    );
+    let id = pipeline_id.to_string();
+    attach_tee_branch(
+        pipeline,
+        tee_name,
+        "transcriber",
+        &description,
+        move |branch, buffer| {
+            let Ok(map) = buffer.map_readable() else {
+                return;
+            };
+            let text = String::from_utf8_lossy(map.as_slice()).trim().to_string();
+            if !text.is_empty() {
+                on_segment(TranscriptSegment {
+                    pipeline_id: id.clone(),
+                    branch: branch.to_string(),
+                    text,
+                    pts_ns: buffer.pts().map(|t| t.nseconds()),
+                    duration_ns: buffer.duration().map(|t| t.nseconds()),
+                });
+            }
+        },
+    )
+}
+
+/// gst-launch description speaking the text in `text_file`, played on the
+/// default audio device or written to `output` as WAV
+pub fn speech_description(synthesizer: &str, text_file: &Path, output: Option<&Path>) -> String {
+    let sink = match output {
+        Some(path) => format!("wavenc ! filesink location={}", quote_location(path)),
+        None => "autoaudiosink".to_string(),
+    };
+    format!(
+        "filesrc location={} ! {} ! wavparse ! audioconvert ! audioresample ! {}",
+        quote_location(text_file),
+        synthesizer,
+        sink
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speech_description() {
+        assert_eq!(
+            speech_description("festival", Path::new("/tmp/say.txt"), None),
+            "filesrc location=\"/tmp/say.txt\" ! festival ! wavparse ! audioconvert ! audioresample ! autoaudiosink"
+        );
+        assert!(speech_description(
+            "festival",
+            Path::new("/tmp/say.txt"),
+            Some(Path::new("hi.wav"))
+        )
+        .ends_with("wavenc ! filesink location=\"hi.wav\""));
+    }
+}
@@ -0,0 +1,186 @@
+use std::path::Path;
+
+use crate::conversion::Conversions;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::{output_format, quote_location, require_element, MediaInfo};
+
+/// Supported playback rate range for speed changes
+pub const MIN_RATE: f64 = 0.25;
+pub const MAX_RATE: f64 = 4.0;
+
+/// soundtouch element that changes tempo (keeping pitch) or rate
+const PITCH_ELEMENT: &str = "pitch";
+
+/// Plan a speed change of `input` to `rate` and build the pipeline. Video is
+/// retimed with `videorate`, audio with the soundtouch `pitch` element.
+pub fn plan_speed_change(
+    input: &MediaInfo,
+    output: &Path,
+    rate: f64,
+    preserve_pitch: bool,
+    conversions: &Conversions,
+) -> McpResult<String> {
+    if !(MIN_RATE..=MAX_RATE).contains(&rate) {
+        return Err(GStreamerMcpError::Other(format!(
+            "Rate {} is outside the supported range {}-{}",
+            rate, MIN_RATE, MAX_RATE
+        )));
+    }
+    if input.video.is_empty() && input.audio.is_empty() {
+        return Err(GStreamerMcpError::Other(format!(
+            "{} has no audio or video streams",
+            input.path.display()
+        )));
+    }
+
+    let format = output_format(output)?;
+    let video_encoder = match input.video.first() {
+        Some(_) if format.video_encoders.is_empty() => {
+            return Err(GStreamerMcpError::Other(format!(
+                "{} cannot hold video; choose a video container for {}",
+                format.muxer,
+                output.display()
+            )));
+        }
+        Some(_) => Some(require_element(format.video_encoders, "video encoder")?),
+        None => None,
+    };
+    let audio_encoder = match input.audio.first() {
+        Some(_) => {
+            require_element(&[PITCH_ELEMENT], "tempo changer (soundtouch pitch)")?;
+            Some(require_element(format.audio_encoders, "audio encoder")?)
+        }
+        None => None,
+    };
+
+    Ok(speed_description(
+        input,
+        output,
+        format.muxer,
+        rate,
+        preserve_pitch,
+        video_encoder,
+        audio_encoder,
+        conversions,
+    ))
+}
+
+/// gst-launch description playing `input` at `rate` into `output`. Output
+/// timestamps are divided by the rate, so the result lasts
+/// `duration / rate`; the video keeps its frame rate by dropping or
+/// duplicating frames. `conversions` run on the decoded streams first.
+#[allow(clippy::too_many_arguments)]
+pub fn speed_description(
+    input: &MediaInfo,
+    output: &Path,
+    muxer: &str,
+    rate: f64,
+    preserve_pitch: bool,
+    video_encoder: Option<&str>,
+    audio_encoder: Option<&str>,
+    conversions: &Conversions,
+) -> String {
+    let mut parts = vec![
+        format!(
+            "{} name=mux ! filesink location={}",
+            muxer,
+            quote_location(output)
+        ),
+        format!(
+            "filesrc location={} ! decodebin name=d",
+            quote_location(&input.path)
+        ),
+    ];
+
+    if let (Some(video), Some(encoder)) = (input.video.first(), video_encoder) {
+        parts.push(format!(
+            "d. ! video/x-raw ! queue ! {}videoconvert ! videorate rate={} ! video/x-raw,framerate={} ! {} ! queue ! mux.",
+            conversions.video_steps(Some(video)),
+            rate,
+            video.framerate,
+            encoder
+        ));
+    }
+    if let (Some(audio), Some(encoder)) = (input.audio.first(), audio_encoder) {
+        // tempo keeps the pitch; rate speeds up audio like a faster tape
+        let property = if preserve_pitch { "tempo" } else { "rate" };
+        parts.push(format!(
+            "d. ! audio/x-raw ! queue ! {}audioconvert ! {} {}={} ! audioconvert ! audioresample ! {} ! queue ! mux.",
+            conversions.audio_steps(Some(audio)),
+            PITCH_ELEMENT,
+            property,
+            rate,
+            encoder
+        ));
+    }
+
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Tonemap;
+    use crate::media::{AudioStream, VideoStream};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_speed_description() {
+        let input = MediaInfo {
+            path: PathBuf::from("talk.mp4"),
+            container: None,
+            duration_ns: Some(60_000_000_000),
+            seekable: true,
+            video: vec![VideoStream {
+                codec: "video/x-h264".to_string(),
+                caps: "video/x-h264".to_string(),
+                width: 1280,
+                height: 720,
+                framerate: "25/1".to_string(),
+                interlaced: false,
+                color: None,
+            }],
+            audio: vec![AudioStream {
+                codec: "audio/x-opus".to_string(),
+                caps: "audio/x-opus".to_string(),
+                sample_rate: 48000,
+                channels: 2,
+                channel_mask: 0x3,
+                layout: "stereo".to_string(),
+            }],
+        };
+
+        let description = speed_description(
+            &input,
+            Path::new("fast.mkv"),
+            "matroskamux",
+            1.5,
+            true,
+            Some("x264enc"),
+            Some("opusenc"),
+            &Conversions::default(),
+        );
+        assert!(description.contains("videorate rate=1.5 ! video/x-raw,framerate=25/1 ! x264enc"));
+        assert!(description.contains("pitch tempo=1.5 ! audioconvert"));
+
+        let description = speed_description(
+            &input,
+            Path::new("fast.mkv"),
+            "matroskamux",
+            2.0,
+            false,
+            Some("x264enc"),
+            Some("opusenc"),
+            &Conversions {
+                tonemap: Some(Tonemap {
+                    chain: "vapostproc hdr-tone-mapping=true".to_string(),
+                    forced: true,
+                }),
+                downmix: true,
+            },
+        );
+        assert!(description.contains("pitch rate=2 !"));
+        assert!(description
+            .contains("queue ! vapostproc hdr-tone-mapping=true ! videoconvert ! videorate"));
+    }
+}
@@ -0,0 +1,179 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use rmcp::model::LoggingLevel;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::element_states::{element_states, stuck_elements, ElementState};
+use crate::notifications::Notifier;
+use crate::pipeline::{attach_cause, PipelineManager};
+
+/// How often pipelines are checked for state changes that do not finish
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Stall reports kept per pipeline; older ones are dropped
+pub const MAX_STALL_REPORTS: usize = 5;
+
+/// Snapshot of a pipeline whose state change did not complete in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StallReport {
+    pub pipeline_id: String,
+    pub detected_at: chrono::DateTime<chrono::Utc>,
+    pub current_state: String,
+    pub pending_state: String,
+    /// How long the change had been pending when it was captured
+    pub stalled_ms: u64,
+    /// Elements likely holding the change up
+    pub stuck: Vec<String>,
+    pub elements: Vec<ElementState>,
+    /// Graphviz dump of the pipeline, with caps and states
+    pub dot: String,
+}
+
+/// Times the state change in progress on one pipeline
+#[derive(Debug, Clone, Default)]
+pub struct StallTracker {
+    since: Option<Instant>,
+    reported: bool,
+}
+
+impl StallTracker {
+    /// Note whether a state change is pending at `now`. Returns how long
+    /// it has been pending the first time that exceeds `timeout`; a stall
+    /// is reported once until the change completes.
+    pub fn observe(&mut self, pending: bool, now: Instant, timeout: Duration) -> Option<Duration> {
+        if !pending {
+            *self = StallTracker::default();
+            return None;
+        }
+        let since = *self.since.get_or_insert(now);
+        let elapsed = now.duration_since(since);
+        if self.reported || elapsed < timeout {
+            return None;
+        }
+        self.reported = true;
+        Some(elapsed)
+    }
+}
+
+/// Capture the element states and a DOT graph of a stalled pipeline
+pub fn capture_stall(
+    pipeline: &gst::Pipeline,
+    pipeline_id: &str,
+    stalled: Duration,
+) -> StallReport {
+    let (_, current, pending) = pipeline.state(gst::ClockTime::ZERO);
+    let elements = element_states(pipeline);
+    StallReport {
+        pipeline_id: pipeline_id.to_string(),
+        detected_at: chrono::Utc::now(),
+        current_state: format!("{:?}", current),
+        pending_state: format!("{:?}", pending),
+        stalled_ms: stalled.as_millis() as u64,
+        stuck: stuck_elements(&elements),
+        elements,
+        dot: pipeline
+            .debug_to_dot_data(gst::DebugGraphDetails::all())
+            .to_string(),
+    }
+}
+
+/// Watch every pipeline for state changes pending longer than `timeout`.
+/// Each stall is captured into the pipeline's diagnostics and sent to the
+/// client as a `pipeline_stalled` notification.
+pub fn spawn_stall_detector(
+    pipeline_manager: Arc<PipelineManager>,
+    notifier: Notifier,
+    timeout: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut trackers: HashMap<String, StallTracker> = HashMap::new();
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let ids: Vec<String> = pipeline_manager
+                .list_pipelines()
+                .into_iter()
+                .map(|info| info.id)
+                .collect();
+            trackers.retain(|id, _| ids.contains(id));
+
+            for id in ids {
+                let Ok(pipeline) = pipeline_manager.get_pipeline_handle(&id) else {
+                    continue;
+                };
+                let (_, _, pending) = pipeline.state(gst::ClockTime::ZERO);
+                let Some(stalled) = trackers.entry(id.clone()).or_default().observe(
+                    pending != gst::State::VoidPending,
+                    Instant::now(),
+                    timeout,
+                ) else {
+                    continue;
+                };
+
+                let report = capture_stall(&pipeline, &id, stalled);
+                tracing::warn!(
+                    "Pipeline {} stuck changing from {} to {} for {} ms; suspects: {}",
+                    id,
+                    report.current_state,
+                    report.pending_state,
+                    report.stalled_ms,
+                    report.stuck.join(", ")
+                );
+                let mut data = serde_json::json!({
+                    "pipeline_id": report.pipeline_id,
+                    "current_state": report.current_state,
+                    "pending_state": report.pending_state,
+                    "stalled_ms": report.stalled_ms,
+                    "stuck": report.stuck,
+                });
+                attach_cause(&mut data, pipeline_manager.cause(&id).as_ref());
+                pipeline_manager.add_stall_report(&id, report);
+                notifier
+                    .notify("pipeline_stalled", LoggingLevel::Warning, data)
+                    .await;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stall_tracker() {
+        let timeout = Duration::from_secs(10);
+        let start = Instant::now();
+        let mut tracker = StallTracker::default();
+        assert_eq!(tracker.observe(true, start, timeout), None);
+        assert_eq!(
+            tracker.observe(true, start + Duration::from_secs(5), timeout),
+            None
+        );
+        assert_eq!(
+            tracker.observe(true, start + Duration::from_secs(11), timeout),
+            Some(Duration::from_secs(11))
+        );
+        // Reported once per stall
+        assert_eq!(
+            tracker.observe(true, start + Duration::from_secs(20), timeout),
+            None
+        );
+        // A completed change re-arms the tracker
+        assert_eq!(
+            tracker.observe(false, start + Duration::from_secs(21), timeout),
+            None
+        );
+        assert_eq!(
+            tracker.observe(true, start + Duration::from_secs(22), timeout),
+            None
+        );
+        assert_eq!(
+            tracker.observe(true, start + Duration::from_secs(32), timeout),
+            Some(Duration::from_secs(10))
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::discovery::ensure_gstreamer_initialized;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// Number of registry matches proposed for an unknown element
+const MAX_SUGGESTIONS: usize = 3;
+
+/// An element name replaced by `auto_correct`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElementCorrection {
+    pub from: String,
+    pub to: String,
+}
+
+/// Levenshtein distance between two strings
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Candidates close enough to `name` to be a plausible typo, best first
+pub fn closest_matches<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+    max: usize,
+) -> Vec<String> {
+    let threshold = (name.chars().count() / 3).max(2);
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .collect();
+    matches.sort();
+    matches
+        .into_iter()
+        .take(max)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Registry element names closest to an unknown element name
+pub fn suggest_element_names(name: &str) -> Vec<String> {
+    let factories: Vec<String> = gst::Registry::get()
+        .features(gst::ElementFactory::static_type())
+        .iter()
+        .map(|feature| feature.name().to_string())
+        .collect();
+    closest_matches(name, factories.iter().map(String::as_str), MAX_SUGGESTIONS)
+}
+
+/// Parse a pipeline description, prefixing parse errors with `context`. When
+/// the description references elements that are not in the registry, the
+/// error carries the closest registry matches for each.
+pub fn parse_launch(description: &str, context: &str) -> McpResult<gst::Element> {
+    ensure_gstreamer_initialized()?;
+
+    let mut parse_context = gst::ParseContext::new();
+    gst::parse::launch_full(
+        description,
+        Some(&mut parse_context),
+        gst::ParseFlags::empty(),
+    )
+    .map_err(|e| {
+        let missing = parse_context.missing_elements();
+        if missing.is_empty() {
+            return GStreamerMcpError::PipelineError(format!("{}: {}", context, e));
+        }
+
+        let suggestions: BTreeMap<String, Vec<String>> = missing
+            .into_iter()
+            .map(|name| {
+                let matches = suggest_element_names(&name);
+                (name, matches)
+            })
+            .collect();
+        GStreamerMcpError::UnknownElements {
+            message: format!("{}: {}{}", context, e, format_suggestions(&suggestions)),
+            suggestions,
+        }
+    })
+}
+
+fn format_suggestions(suggestions: &BTreeMap<String, Vec<String>>) -> String {
+    let mut output = String::new();
+    for (name, matches) in suggestions {
+        if !matches.is_empty() {
+            output.push_str(&format!(". {} → {}?", name, matches.join(" / ")));
+        }
+    }
+    output
+}
+
+/// Replace every unknown element of a description by its closest registry
+/// match. Elements without a plausible match are left untouched.
+pub fn correct_element_typos(description: &str) -> McpResult<(String, Vec<ElementCorrection>)> {
+    let suggestions = match parse_launch(description, "Failed to parse pipeline") {
+        Ok(_) => return Ok((description.to_string(), Vec::new())),
+        Err(GStreamerMcpError::UnknownElements { suggestions, .. }) => suggestions,
+        Err(e) => return Err(e),
+    };
+
+    let mut corrected = description.to_string();
+    let mut corrections = Vec::new();
+    for (name, matches) in suggestions {
+        if let Some(best) = matches.into_iter().next() {
+            corrected = replace_element_name(&corrected, &name, &best);
+            corrections.push(ElementCorrection {
+                from: name,
+                to: best,
+            });
+        }
+    }
+
+    Ok((corrected, corrections))
+}
+
+/// Replace `from` where it appears as a standalone element token, i.e.
+/// delimited by whitespace, `!` or the ends of the description
+pub fn replace_element_name(description: &str, from: &str, to: &str) -> String {
+    let is_boundary = |c: Option<char>| c.is_none_or(|c| c.is_whitespace() || c == '!');
+
+    let mut output = String::with_capacity(description.len());
+    let mut rest = description;
+    let mut previous: Option<char> = None;
+    while let Some(pos) = rest.find(from) {
+        let before = rest[..pos].chars().last().or(previous);
+        let after = rest[pos + from.len()..].chars().next();
+        output.push_str(&rest[..pos]);
+        if is_boundary(before) && is_boundary(after) {
+            output.push_str(to);
+        } else {
+            output.push_str(from);
+        }
+        previous = from.chars().last();
+        rest = &rest[pos + from.len()..];
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("x264env", "x264enc"), 1);
+        assert_eq!(edit_distance("autovideosnk", "autovideosink"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("queue", "queue"), 0);
+    }
+
+    #[test]
+    fn test_closest_matches() {
+        let candidates = ["x264enc", "x265enc", "vp8enc", "autovideosink"];
+        assert_eq!(
+            closest_matches("x264env", candidates, 3),
+            vec!["x264enc", "x265enc"]
+        );
+        assert!(closest_matches("rtmpsink", candidates, 3).is_empty());
+    }
+
+    #[test]
+    fn test_replace_element_name() {
+        assert_eq!(
+            replace_element_name(
+                "videotestsrc ! x264env bitrate=500 ! fakesink name=x264env_out",
+                "x264env",
+                "x264enc"
+            ),
+            "videotestsrc ! x264enc bitrate=500 ! fakesink name=x264env_out"
+        );
+        assert_eq!(
+            replace_element_name("videotestsrc!autovideosnk", "autovideosnk", "autovideosink"),
+            "videotestsrc!autovideosink"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Time zone timestamps are shown in by text outputs. Structured outputs
+/// always carry ISO-8601 timestamps in UTC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeZoneDisplay {
+    #[default]
+    Utc,
+    /// The time zone of the machine running the server
+    Local,
+}
+
+impl std::str::FromStr for TimeZoneDisplay {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utc" => Ok(TimeZoneDisplay::Utc),
+            "local" => Ok(TimeZoneDisplay::Local),
+            _ => Err(format!(
+                "Invalid time zone '{}'. Must be one of: utc, local",
+                s
+            )),
+        }
+    }
+}
+
+/// How text outputs show timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeDisplay {
+    pub zone: TimeZoneDisplay,
+    /// Follow each timestamp with how long ago it was, e.g. "(12s ago)"
+    pub relative: bool,
+}
+
+impl TimeDisplay {
+    pub fn new(zone: TimeZoneDisplay, relative: bool) -> Self {
+        Self { zone, relative }
+    }
+
+    /// Show `time` as ISO-8601 to the second, relative to now
+    pub fn format(&self, time: DateTime<Utc>) -> String {
+        self.format_at(time, Utc::now())
+    }
+
+    /// Show `time` as ISO-8601 to the second, relative to `now`
+    pub fn format_at(&self, time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+        let absolute = match self.zone {
+            TimeZoneDisplay::Utc => time.to_rfc3339_opts(SecondsFormat::Secs, true),
+            TimeZoneDisplay::Local => time
+                .with_timezone(&Local)
+                .to_rfc3339_opts(SecondsFormat::Secs, false),
+        };
+        if self.relative {
+            format!(
+                "{} ({})",
+                absolute,
+                format_relative((now - time).num_seconds())
+            )
+        } else {
+            absolute
+        }
+    }
+}
+
+/// Show a number of seconds in the past, or in the future if negative, in
+/// its largest whole unit
+pub fn format_relative(seconds: i64) -> String {
+    let amount = seconds.unsigned_abs();
+    let span = match amount {
+        0 => return "just now".to_string(),
+        1..=59 => format!("{}s", amount),
+        60..=3599 => format!("{}m", amount / 60),
+        3600..=86399 => format!("{}h", amount / 3600),
+        _ => format!("{}d", amount / 86400),
+    };
+    if seconds > 0 {
+        format!("{} ago", span)
+    } else {
+        format!("in {}", span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_display() {
+        assert_eq!(format_relative(0), "just now");
+        assert_eq!(format_relative(12), "12s ago");
+        assert_eq!(format_relative(150), "2m ago");
+        assert_eq!(format_relative(7200), "2h ago");
+        assert_eq!(format_relative(3 * 86400), "3d ago");
+        assert_eq!(format_relative(-30), "in 30s");
+
+        let now = DateTime::parse_from_rfc3339("2026-03-01T12:00:30Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let time = now - chrono::Duration::seconds(30);
+        assert_eq!(
+            TimeDisplay::new(TimeZoneDisplay::Utc, true).format_at(time, now),
+            "2026-03-01T12:00:00Z (30s ago)"
+        );
+        assert_eq!(
+            TimeDisplay::new(TimeZoneDisplay::Utc, false).format_at(time, now),
+            "2026-03-01T12:00:00Z"
+        );
+        assert_eq!("Local".parse(), Ok(TimeZoneDisplay::Local));
+        assert!("cet".parse::<TimeZoneDisplay>().is_err());
+    }
+}
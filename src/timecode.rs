@@ -0,0 +1,252 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_video::VideoTimeCodeMeta;
+use parking_lot::Mutex;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::pipeline::{insert_before_video_outputs, make_element, set_property_str};
+use crate::probes::find_pad;
+
+/// Where timecodestamper takes the timecode of each frame from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimecodeSource {
+    /// Count frames from the first timecode
+    #[default]
+    Internal,
+    /// Count frames from 00:00:00:00
+    Zero,
+    /// Continue from the last timecode seen upstream
+    LastKnown,
+    /// Wall clock time of day
+    Rtc,
+}
+
+impl TimecodeSource {
+    fn nick(self) -> &'static str {
+        match self {
+            TimecodeSource::Internal => "internal",
+            TimecodeSource::Zero => "zero",
+            TimecodeSource::LastKnown => "last-known",
+            TimecodeSource::Rtc => "rtc",
+        }
+    }
+}
+
+/// SMPTE timecode stamped on the video of a pipeline
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+pub struct TimecodeOptions {
+    #[schemars(
+        description = "Timecode source: 'internal' (count from first_timecode), 'zero', 'last-known' or 'rtc' (time of day) (default: 'internal')"
+    )]
+    pub source: Option<TimecodeSource>,
+    #[schemars(
+        description = "Timecode of the first frame as 'HH:MM:SS:FF' ('HH:MM:SS;FF' for drop-frame), used by the internal source"
+    )]
+    pub first_timecode: Option<String>,
+    #[schemars(description = "Use drop-frame timecode for 29.97/59.94 fps video (default: false)")]
+    pub drop_frame: Option<bool>,
+    #[schemars(
+        description = "Replace timecodes already present upstream (e.g. from a capture card) (default: false, keep them)"
+    )]
+    pub overwrite: Option<bool>,
+    #[schemars(description = "Burn the timecode into the picture (default: false)")]
+    pub burn_in: Option<bool>,
+}
+
+/// A timecode read from a pipeline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimecodeReading {
+    pub timecode: String,
+    /// Element or pad the frame was seen on
+    pub source: String,
+    pub pts_ns: Option<u64>,
+}
+
+/// Fields of a `HH:MM:SS:FF` timecode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+    pub drop_frame: bool,
+}
+
+impl Timecode {
+    /// Sort key of the timecode within a day
+    pub fn key(&self) -> (u32, u32, u32, u32) {
+        (self.hours, self.minutes, self.seconds, self.frames)
+    }
+}
+
+/// Parse a `HH:MM:SS:FF` timecode; a `;` or `.` before the frames marks
+/// drop-frame
+pub fn parse_timecode(timecode: &str) -> McpResult<Timecode> {
+    let invalid = || {
+        GStreamerMcpError::PropertyError(format!(
+            "Invalid timecode '{}', expected HH:MM:SS:FF",
+            timecode
+        ))
+    };
+    let (time, frames, drop_frame) = match timecode.rfind([':', ';', '.']) {
+        Some(i) => (
+            &timecode[..i],
+            &timecode[i + 1..],
+            &timecode[i..i + 1] != ":",
+        ),
+        None => return Err(invalid()),
+    };
+    let fields: Vec<&str> = time.split(':').chain([frames]).collect();
+    let limits = [24, 60, 60, 120];
+    if fields.len() != limits.len() {
+        return Err(invalid());
+    }
+    let mut values = [0; 4];
+    for ((field, limit), value) in fields.iter().zip(limits).zip(values.iter_mut()) {
+        match field.parse::<u32>() {
+            Ok(parsed) if field.len() == 2 && parsed < limit => *value = parsed,
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(Timecode {
+        hours: values[0],
+        minutes: values[1],
+        seconds: values[2],
+        frames: values[3],
+        drop_frame,
+    })
+}
+
+/// Insert `timecodestamper` (and `timeoverlay` when burning in) in front of
+/// every video encoder and video sink of a pipeline that is not running yet.
+/// Returns the names of the elements that now receive timecoded video.
+pub fn apply_timecode(
+    pipeline: &gst::Pipeline,
+    options: &TimecodeOptions,
+) -> McpResult<Vec<String>> {
+    let drop_frame = match &options.first_timecode {
+        Some(timecode) => {
+            parse_timecode(timecode)?.drop_frame || options.drop_frame.unwrap_or(false)
+        }
+        None => options.drop_frame.unwrap_or(false),
+    };
+    let applied = insert_before_video_outputs(pipeline, "timecode", |i| {
+        let stamper = make_element("timecodestamper", format!("timecode{}", i))?;
+        set_property_str(
+            &stamper,
+            "source",
+            options.source.unwrap_or_default().nick(),
+        )?;
+        let set = if options.overwrite.unwrap_or(false) {
+            "always"
+        } else {
+            "keep"
+        };
+        set_property_str(&stamper, "set", set)?;
+        stamper.set_property("drop-frame", drop_frame);
+        if let Some(timecode) = &options.first_timecode {
+            set_property_str(&stamper, "set-internal-timecode", timecode)?;
+        }
+        let mut chain = vec![stamper];
+        if options.burn_in.unwrap_or(false) {
+            let overlay = make_element("timeoverlay", format!("timecode{}_overlay", i))?;
+            set_property_str(&overlay, "time-mode", "time-code")?;
+            chain.push(overlay);
+        }
+        Ok(chain)
+    })?;
+
+    if applied.is_empty() {
+        return Err(GStreamerMcpError::PipelineError(
+            "Pipeline has no linked video encoder or video sink to timecode".to_string(),
+        ));
+    }
+    Ok(applied)
+}
+
+fn buffer_timecode(buffer: &gst::BufferRef) -> Option<String> {
+    buffer
+        .meta::<VideoTimeCodeMeta>()
+        .map(|meta| meta.tc().to_string())
+}
+
+/// Timecode of the last frame rendered by a sink of the pipeline, read from
+/// the sinks' last sample
+pub fn current_timecode(pipeline: &gst::Pipeline) -> Option<TimecodeReading> {
+    pipeline
+        .iterate_sinks()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|sink| sink.find_property("last-sample").is_some())
+        .find_map(|sink| {
+            let sample = sink.property::<Option<gst::Sample>>("last-sample")?;
+            let buffer = sample.buffer()?;
+            Some(TimecodeReading {
+                timecode: buffer_timecode(buffer)?,
+                source: sink.name().to_string(),
+                pts_ns: buffer.pts().map(|t| t.nseconds()),
+            })
+        })
+}
+
+/// Wait up to `timeout` for a timecoded buffer on `pad_spec` and return its
+/// timecode
+pub async fn read_timecode(
+    pipeline: &gst::Pipeline,
+    pad_spec: &str,
+    timeout: Duration,
+) -> McpResult<Option<TimecodeReading>> {
+    let pad = find_pad(pipeline, pad_spec)?;
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    let tx = Mutex::new(Some(tx));
+    let source = pad_spec.to_string();
+    let probe_id = pad
+        .add_probe(gst::PadProbeType::BUFFER, move |_pad, info| {
+            let reading = info.buffer().and_then(|buffer| {
+                Some(TimecodeReading {
+                    timecode: buffer_timecode(buffer)?,
+                    source: source.clone(),
+                    pts_ns: buffer.pts().map(|t| t.nseconds()),
+                })
+            });
+            let Some(reading) = reading else {
+                return gst::PadProbeReturn::Ok;
+            };
+            if let Some(tx) = tx.lock().take() {
+                let _ = tx.send(reading);
+            }
+            gst::PadProbeReturn::Remove
+        })
+        .ok_or_else(|| {
+            GStreamerMcpError::PipelineError(format!("Failed to install probe on '{}'", pad_spec))
+        })?;
+
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(reading)) => Ok(Some(reading)),
+        _ => {
+            pad.remove_probe(probe_id);
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timecode() {
+        let timecode = parse_timecode("01:02:03:04").unwrap();
+        assert_eq!(timecode.key(), (1, 2, 3, 4));
+        assert!(!timecode.drop_frame);
+        assert!(parse_timecode("10:59:59;29").unwrap().drop_frame);
+        assert!(parse_timecode("24:00:00:00").is_err());
+        assert!(parse_timecode("1:00:00:00").is_err());
+        assert!(parse_timecode("01:00:00").is_err());
+        assert!(parse_timecode("garbage").is_err());
+    }
+}
@@ -1,4 +1,6 @@
+use crate::aravis::ARAVIS_ELEMENTS;
 use crate::cli::OperationalMode;
+use crate::ndi::NDI_ELEMENTS;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
@@ -20,6 +22,8 @@ pub struct ToolMetadata {
     pub category: ToolCategory,
     pub description: String,
     pub modes: Vec<OperationalMode>,
+    /// Elements that must be installed for the tool to be offered
+    pub required_elements: Vec<String>,
 }
 
 impl ToolMetadata {
@@ -34,9 +38,31 @@ impl ToolMetadata {
             category,
             description: description.into(),
             modes,
+            required_elements: Vec::new(),
         }
     }
 
+    /// Only offer the tool when all of `elements` are installed
+    pub fn with_required_elements(mut self, elements: &[&str]) -> Self {
+        self.required_elements = elements.iter().map(|e| e.to_string()).collect();
+        self
+    }
+
+    /// Required elements that are not installed
+    pub fn missing_elements(&self) -> Vec<String> {
+        if self.required_elements.is_empty() {
+            return Vec::new();
+        }
+        if crate::discovery::ensure_gstreamer_initialized().is_err() {
+            return self.required_elements.clone();
+        }
+        self.required_elements
+            .iter()
+            .filter(|name| gstreamer::ElementFactory::find(name).is_none())
+            .cloned()
+            .collect()
+    }
+
     /// Check if this tool is available in the given mode
     pub fn is_available_in_mode(&self, mode: &OperationalMode) -> bool {
         mode == &OperationalMode::All || self.modes.contains(mode)
@@ -59,7 +85,7 @@ impl ToolRegistry {
             ToolMetadata::new(
                 "gst_list_elements",
                 ToolCategory::Discovery,
-                "Lists all available GStreamer elements with optional filtering. Accepts name filter and category filter (both optional). Returns element names, descriptions, plugin sources, and rank values. Use to discover available media processing components.",
+                "Lists all available GStreamer elements with optional filtering. Accepts name, category, plugin, min_rank, has_src_pads/has_sink_pads, and media_type (audio/video/text) filters (all optional). Returns element names, descriptions, plugin sources, and rank values. Use to discover available media processing components.",
                 vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Dev, OperationalMode::Discovery],
             ),
         );
@@ -89,7 +115,7 @@ impl ToolRegistry {
             ToolMetadata::new(
                 "gst_search_elements",
                 ToolCategory::Discovery,
-                "Searches for GStreamer elements by keyword. Accepts search query (required). Returns relevance-ranked results matching element names, descriptions, and classifications. Use to find elements for specific media processing tasks.",
+                "Searches for GStreamer elements by keyword. Accepts search query (required) and search_in (optional: names, descriptions, properties, signals, caps). Returns relevance-ranked results and, for properties/signals/caps, what matched. Use to find elements for specific media processing tasks, or the elements exposing a knob such as 'bitrate'.",
                 vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Dev, OperationalMode::Discovery],
             ),
         );
@@ -100,7 +126,7 @@ impl ToolRegistry {
             ToolMetadata::new(
                 "gst_launch_pipeline",
                 ToolCategory::Pipeline,
-                "Creates and launches a GStreamer pipeline from description. Accepts gst-launch syntax, auto-play flag (default: true), and custom ID (optional). Returns pipeline ID and current state. Use to start media processing pipelines.",
+                "Creates and launches a GStreamer pipeline from description. Accepts gst-launch syntax, auto-play flag (default: true), custom ID (optional), auto_correct to fix element name typos (optional), and preroll_only to hold it prerolled in PAUSED (optional). Returns pipeline ID and current state; unknown elements are reported with the closest registry matches. Use to start media processing pipelines.",
                 vec![OperationalMode::All, OperationalMode::Live],
             ),
         );
@@ -115,6 +141,116 @@ impl ToolRegistry {
             ),
         );
 
+        tools.insert(
+            "gst_seek".to_string(),
+            ToolMetadata::new(
+                "gst_seek",
+                ToolCategory::Pipeline,
+                "Seeks a paused or playing pipeline to a position given in nanoseconds or as '1:23.5' style text. Accepts pipeline ID, position and flush/key_unit/accurate flags. Returns the resulting position. Use to jump within file playback.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_stop_all_pipelines".to_string(),
+            ToolMetadata::new(
+                "gst_stop_all_pipelines",
+                ToolCategory::Pipeline,
+                "Stops and releases every pipeline, or those matching labels or an owner, in parallel. Accepts force flag, timeout_ms, labels, owner, and include_jobs (optional). Returns a stop report per pipeline and the failures. Use to clean up pipelines leaked by earlier sessions that exhaust the pipeline limit.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_set_loop".to_string(),
+            ToolMetadata::new(
+                "gst_set_loop",
+                ToolCategory::Pipeline,
+                "Loops a segment of a paused or playing pipeline, or the whole media, with seeks back to its start on SEGMENT_DONE and EOS. Accepts pipeline ID, start/stop bounds, count, and enabled flag (optional). Returns the loop with the plays completed and remaining. Use for kiosks, test signals, and repeated playback of a clip.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_set_playback_rate".to_string(),
+            ToolMetadata::new(
+                "gst_set_playback_rate",
+                ToolCategory::Pipeline,
+                "Changes the playback rate of a paused or playing pipeline with a seek. Accepts pipeline ID, rate (negative for reverse), start/stop bounds and flush/key_units_only flags (optional). Returns whether the rate was accepted. Use for slow motion, fast-forward, and reverse playback.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_add_element".to_string(),
+            ToolMetadata::new(
+                "gst_add_element",
+                ToolCategory::Pipeline,
+                "Adds an element to a running pipeline. Accepts pipeline ID, element description, and after/before elements (optional). Splices it between linked elements while the stream is blocked. Returns the links made. Use to hot-patch a pipeline, e.g. insert a videoscale or a tee.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_remove_element".to_string(),
+            ToolMetadata::new(
+                "gst_remove_element",
+                ToolCategory::Pipeline,
+                "Removes an element from a running pipeline. Accepts pipeline ID, element name and relink flag (optional). Drains the element and links its neighbours together. Returns the links changed. Use to take a filter or branch out without restarting.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_link_elements".to_string(),
+            ToolMetadata::new(
+                "gst_link_elements",
+                ToolCategory::Pipeline,
+                "Links two elements of a running pipeline. Accepts pipeline ID, src and sink as element or element.pad. Moves an already linked src pad while it is blocked. Returns the links changed. Use to wire up added elements or reroute a stream.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_get_element_states".to_string(),
+            ToolMetadata::new(
+                "gst_get_element_states",
+                ToolCategory::Pipeline,
+                "Lists each element's current and pending state and whether its pads are linked, flushing, blocked, or at EOS. Accepts pipeline ID and include_pads flag (optional). Returns the states and the elements likely stuck. Use when a pipeline won't reach PLAYING.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_list_pipeline_elements".to_string(),
+            ToolMetadata::new(
+                "gst_list_pipeline_elements",
+                ToolCategory::Pipeline,
+                "Lists every element of a running pipeline with its factory, state, pads, negotiated caps, and non-default properties. Accepts pipeline ID and include_pads and include_properties flags (optional). Use to see what a pipeline is made of and what it negotiated.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_get_pipeline_diagnostics".to_string(),
+            ToolMetadata::new(
+                "gst_get_pipeline_diagnostics",
+                ToolCategory::Pipeline,
+                "Returns the stall reports captured when a pipeline's state change stays pending past the stall timeout. Accepts pipeline ID and include_details flag (optional). Returns states, suspect elements, element snapshots, and DOT graphs. Use to debug pipelines stuck in preroll or deadlocked.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_get_pipeline_latency".to_string(),
+            ToolMetadata::new(
+                "gst_get_pipeline_latency",
+                ToolCategory::Pipeline,
+                "Queries the latency of a pipeline and reads its clock. Accepts pipeline ID. Returns min/max latency, whether the pipeline is live, the configured latency, the clock type, base time, and running time. Use to diagnose A/V sync problems and live streaming delay.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
         tools.insert(
             "gst_get_pipeline_status".to_string(),
             ToolMetadata::new(
@@ -125,12 +261,82 @@ impl ToolRegistry {
             ),
         );
 
+        tools.insert(
+            "gst_send_event".to_string(),
+            ToolMetadata::new(
+                "gst_send_event",
+                ToolCategory::Pipeline,
+                "Sends flush, latency, or custom events with a JSON-encoded structure to a pipeline or a named element. Accepts pipeline ID, event, element, and event options (optional). Returns whether the event was handled. Use to drive elements like splitmuxsink that react to custom events.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_wait_for_state".to_string(),
+            ToolMetadata::new(
+                "gst_wait_for_state",
+                ToolCategory::Pipeline,
+                "Waits, up to a timeout, for a pipeline to reach a target state after an asynchronous change. Accepts pipeline ID, state and timeout_ms (optional). Returns the final and pending state and any errors posted meanwhile. Use after gst_set_pipeline_state returns before the pipeline has prerolled.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_wait_for_message".to_string(),
+            ToolMetadata::new(
+                "gst_wait_for_message",
+                ToolCategory::Pipeline,
+                "Blocks, up to a timeout, until a pipeline posts a bus message such as EOS, an error or an element message with a given structure name. Accepts pipeline ID, message_types, structure_name, source, timeout_ms and include_recorded (optional). Returns the message type, source, text and structure fields. Use to await a launched pipeline instead of polling its status.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_send_eos".to_string(),
+            ToolMetadata::new(
+                "gst_send_eos",
+                ToolCategory::Pipeline,
+                "Sends EOS to a paused or playing pipeline and optionally waits for it to reach the sinks. Accepts pipeline ID, wait flag and timeout_ms (optional). Returns whether EOS completed, timed out, or failed. Use to finalize recordings (mp4mux etc.) before stopping.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_restart_pipeline".to_string(),
+            ToolMetadata::new(
+                "gst_restart_pipeline",
+                ToolCategory::Pipeline,
+                "Tears a pipeline down and relaunches its description under the same ID. Accepts pipeline ID, restore_position flag, state and timeout_ms (optional). Returns the restart count, new state, and restored position. Use to recover a pipeline from an error without re-specifying it.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_set_pipeline_metadata".to_string(),
+            ToolMetadata::new(
+                "gst_set_pipeline_metadata",
+                ToolCategory::Pipeline,
+                "Attaches client metadata to a pipeline. Accepts pipeline ID, a JSON object merged into the existing metadata (null values remove keys) and replace flag (optional). Returns the metadata now stored. Use to keep correlation IDs and user context with a pipeline for orchestration.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_transfer_pipeline".to_string(),
+            ToolMetadata::new(
+                "gst_transfer_pipeline",
+                ToolCategory::Pipeline,
+                "Hands a pipeline to another owner. Accepts pipeline ID, new owner, expected current owner and replacement labels (all optional except the pipeline ID). Returns the previous and new owner. Use to let another agent or a human take over supervision of a pipeline.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
         tools.insert(
             "gst_stop_pipeline".to_string(),
             ToolMetadata::new(
                 "gst_stop_pipeline",
                 ToolCategory::Pipeline,
-                "Stops and releases resources for a pipeline. Accepts pipeline ID and force flag (optional). Returns cleanup status. Use to properly terminate pipelines and free resources.",
+                "Stops and releases resources for a pipeline. Accepts pipeline ID, force flag and timeout_ms (optional). By default sends EOS and waits for it to drain; force skips draining and abandons a hanging teardown. Returns cleanup status and which path was taken. Use to properly terminate pipelines and free resources.",
                 vec![OperationalMode::All, OperationalMode::Live],
             ),
         );
@@ -140,7 +346,7 @@ impl ToolRegistry {
             ToolMetadata::new(
                 "gst_list_pipelines",
                 ToolCategory::Pipeline,
-                "Lists all currently active pipelines. Accepts include_details flag (optional). Returns pipeline IDs, descriptions, states, and creation times. Use to manage multiple concurrent pipelines.",
+                "Lists all currently active pipelines. Accepts include_details flag, state filter including 'prerolled', and label filter (optional). Returns pipeline IDs, descriptions, states, health, labels, and creation times as text plus structured JSON. Use to manage multiple concurrent pipelines.",
                 vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
             ),
         );
@@ -150,11 +356,580 @@ impl ToolRegistry {
             ToolMetadata::new(
                 "gst_validate_pipeline",
                 ToolCategory::Pipeline,
-                "Validates pipeline description syntax without launching. Accepts gst-launch syntax description. Returns validation status and list of elements that would be created. Use to verify pipeline correctness before execution.",
+                "Validates pipeline description syntax without launching. Accepts gst-launch syntax description, auto_correct flag (optional) and deep flag (optional) that prerolls the pipeline to report elements auto-plugged inside decodebin, encodebin and similar bins. Returns validation status and list of elements that would be created, or the closest registry matches for unknown elements. Use to verify pipeline correctness before execution.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Dev, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_build_encoding_profile".to_string(),
+            ToolMetadata::new(
+                "gst_build_encoding_profile",
+                ToolCategory::Pipeline,
+                "Builds an encodebin encoding profile from high-level parameters. Accepts container, video_codec, video_profile, width, height, framerate, audio_codec, channels and sample_rate (all optional, at least one codec). Returns the serialized profile, file extension, an example launch description and warnings for streams no installed encoder or muxer can produce. Use when writing encodebin or transcoding pipelines.",
+                vec![OperationalMode::All, OperationalMode::Dev, OperationalMode::Discovery],
+            ),
+        );
+
+        // Adaptive Streaming Tools
+        tools.insert(
+            "gst_get_adaptive_stats".to_string(),
+            ToolMetadata::new(
+                "gst_get_adaptive_stats",
+                ToolCategory::Pipeline,
+                "Reports adaptive streaming (HLS/DASH) statistics for a pipeline. Accepts pipeline ID and demuxer element name (optional). Returns current variant caps, bandwidth estimate, buffer level, and demuxer tuning properties. Use to diagnose quality switching and buffering of streamed inputs.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_set_variant".to_string(),
+            ToolMetadata::new(
+                "gst_set_variant",
+                ToolCategory::Pipeline,
+                "Pins the quality level of adaptive streaming (HLS/DASH) demuxers. Accepts pipeline ID, demuxer element name (optional), and variant bitrate in bits/s (omit or 0 for automatic). Returns the updated demuxer stats. Use to force a specific rendition or restore automatic selection.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_get_property".to_string(),
+            ToolMetadata::new(
+                "gst_get_property",
+                ToolCategory::Pipeline,
+                "Reads element properties of a running pipeline. Accepts pipeline ID and property path (element::property, child-proxy paths like playbin0::video-sink::sync, and * wildcards). Returns the value and type of every matching property. Use to inspect knobs of auto-created internal elements.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_set_property".to_string(),
+            ToolMetadata::new(
+                "gst_set_property",
+                ToolCategory::Pipeline,
+                "Sets element properties of a running pipeline. Accepts pipeline ID, property path (element::property, child-proxy paths like playbin0::video-sink::sync, and * wildcards applied to every match), and value. Returns the new values. Use to tune a live pipeline without relaunching it.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        // Network Tools
+        tools.insert(
+            "gst_list_network_interfaces".to_string(),
+            ToolMetadata::new(
+                "gst_list_network_interfaces",
+                ToolCategory::Discovery,
+                "Lists the network interfaces of the host. Accepts no parameters. Returns interface names, indexes, MAC addresses, IP addresses, and loopback flags. Use to pick the multicast interface for UDP pipelines.",
                 vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Dev, OperationalMode::Discovery],
             ),
         );
 
+        tools.insert(
+            "gst_measure_bitrate".to_string(),
+            ToolMetadata::new(
+                "gst_measure_bitrate",
+                ToolCategory::Pipeline,
+                "Measures the data rate flowing through a pad of a running pipeline. Accepts pipeline ID, pad as 'element.pad', window and interval in milliseconds (optional). Returns buffer/byte counts and average, min, max, and per-interval bitrates. Use to verify encoder output rates and network payload sizes.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_add_probe".to_string(),
+            ToolMetadata::new(
+                "gst_add_probe",
+                ToolCategory::Pipeline,
+                "Installs a lasting probe on a pad of a running pipeline. Accepts pipeline ID and pad as 'element.pad'. Returns the pad probed. Use with gst_get_probe_stats to check that data flows through each branch.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_get_probe_stats".to_string(),
+            ToolMetadata::new(
+                "gst_get_probe_stats",
+                ToolCategory::Pipeline,
+                "Reads the probes added with gst_add_probe. Accepts pipeline ID, pad and remove flag (optional). Returns buffer and byte counts, average buffer size, byte rate, PTS progression, idle time, and caps seen per pad. Use to verify data is flowing through a branch.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        // Bus Message Tools
+        tools.insert(
+            "gst_tap_metadata".to_string(),
+            ToolMetadata::new(
+                "gst_tap_metadata",
+                ToolCategory::Pipeline,
+                "Extracts buffer metadata (regions of interest, timecodes, KLV) from a pad of a running pipeline for a limited time. Accepts pipeline ID, pad, window, and maximum events (optional). Returns per-buffer metadata events. Use to see what analytics elements attach downstream.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_read_timecode".to_string(),
+            ToolMetadata::new(
+                "gst_read_timecode",
+                ToolCategory::Pipeline,
+                "Reads the SMPTE timecode of a running pipeline. Accepts pipeline ID, pad, and timeout (optional). Returns the timecode and where it was read. Use to align edits or broadcast events with the video.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_schedule_action".to_string(),
+            ToolMetadata::new(
+                "gst_schedule_action",
+                ToolCategory::Pipeline,
+                "Schedules a frame-accurate action (switch input, start/stop recording, set property) at a running time or timecode. Accepts pipeline ID, action, time, and trigger pad (optional). Returns the action ID. Use for automated playout-style control.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_list_scheduled_actions".to_string(),
+            ToolMetadata::new(
+                "gst_list_scheduled_actions",
+                ToolCategory::Pipeline,
+                "Lists scheduled actions and their status. Accepts pipeline ID (optional). Returns pending, fired, failed, and cancelled actions. Use to check what will run and what already ran.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_cancel_scheduled_action".to_string(),
+            ToolMetadata::new(
+                "gst_cancel_scheduled_action",
+                ToolCategory::Pipeline,
+                "Cancels a pending scheduled action. Accepts the action ID. Returns the cancelled action. Use when plans change before the scheduled time.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_set_prerecord".to_string(),
+            ToolMetadata::new(
+                "gst_set_prerecord",
+                ToolCategory::Pipeline,
+                "Keeps a rolling pre-record buffer in the queue in front of a recording valve. Accepts pipeline ID, valve name and pre_record_ms (0 disarms). Returns the queue and its limits. Use for DVR or security-camera recording that must include the moments before the trigger.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_animate_property".to_string(),
+            ToolMetadata::new(
+                "gst_animate_property",
+                ToolCategory::Pipeline,
+                "Ramps a numeric property of a running pipeline over time, following the running time of its buffers. Accepts pipeline ID, property path, from (optional), to, duration_ms, interpolation, start_ms and pad (optional). Returns the animation ID. Use for programmed transitions such as audio fades or moving a picture-in-picture.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_cancel_animation".to_string(),
+            ToolMetadata::new(
+                "gst_cancel_animation",
+                ToolCategory::Pipeline,
+                "Cancels a running property animation, leaving the property where it is or at the end value. Accepts the animation ID and finish flag (optional). Returns the animation.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_get_bus_messages".to_string(),
+            ToolMetadata::new(
+                "gst_get_bus_messages",
+                ToolCategory::Pipeline,
+                "Pages through the recorded bus message history of a pipeline. Accepts pipeline ID, message types, minimum severity, source element glob, since/until times, cursor and limit (all optional but the ID). Returns the messages with sequence numbers and the next cursor. Use to read a pipeline's full history, or poll it with the cursor for new messages.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_get_pipeline_log".to_string(),
+            ToolMetadata::new(
+                "gst_get_pipeline_log",
+                ToolCategory::Pipeline,
+                "Tails the on-disk JSON lines log of a pipeline: its launches, bus messages and removal. Accepts pipeline ID and number of lines (optional). Returns the last records. Use for the history of long-running or removed pipelines, beyond what gst_get_bus_messages keeps in memory.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_set_message_filter".to_string(),
+            ToolMetadata::new(
+                "gst_set_message_filter",
+                ToolCategory::Pipeline,
+                "Registers a bus message filter for a pipeline subscriber. Accepts pipeline ID, subscriber name (optional), message types, minimum severity, and source element glob (all optional). Returns the active filters for the pipeline. Use to suppress StreamStatus/Tag chatter and only receive relevant messages.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_subscribe_events".to_string(),
+            ToolMetadata::new(
+                "gst_subscribe_events",
+                ToolCategory::Pipeline,
+                "Subscribes to bus events pushed as 'bus_event' notifications as they happen. Accepts pipeline ID, events (error, warning, eos, state_changed) and subscription ID (all optional). Returns the subscription ID. Use instead of polling gst_get_pipeline_status to react to errors and EOS.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_unsubscribe_events".to_string(),
+            ToolMetadata::new(
+                "gst_unsubscribe_events",
+                ToolCategory::Pipeline,
+                "Removes a bus event subscription. Accepts subscription ID. Returns whether it was removed and the remaining subscriptions.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_remove_message_filter".to_string(),
+            ToolMetadata::new(
+                "gst_remove_message_filter",
+                ToolCategory::Pipeline,
+                "Removes the bus message filter of a pipeline subscriber. Accepts pipeline ID and subscriber name (optional). Returns whether a filter was removed. Use to receive unfiltered messages again.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        // Server Tools
+        tools.insert(
+            "gst_health".to_string(),
+            ToolMetadata::new(
+                "gst_health",
+                ToolCategory::Discovery,
+                "Reports server health and readiness. Accepts no parameters. Returns GStreamer version, discovery cache status (cold/warming/ready), active pipeline count, and operational mode. Use as a first call to check the server is ready.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Dev, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_doctor".to_string(),
+            ToolMetadata::new(
+                "gst_doctor",
+                ToolCategory::Discovery,
+                "Diagnoses the GStreamer installation. Accepts no parameters. Checks initialization, base plugins, audio/video sinks (headless or not), hardware codecs, registry writability and environment misconfigurations. Returns each check and a prioritized fix list. Use as the first call when pipelines fail for unclear reasons.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Dev, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_get_tool_metrics".to_string(),
+            ToolMetadata::new(
+                "gst_get_tool_metrics",
+                ToolCategory::Discovery,
+                "Reports per-tool execution metrics collected since startup. Optional: tool (filter to one tool), reset (clear metrics after reporting). Returns call counts, success/failure counters, slow calls, and average/max/last duration in milliseconds. Use to diagnose slow or failing workflows.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Dev, OperationalMode::Discovery],
+            ),
+        );
+
+        // Job Queue Tools
+        tools.insert(
+            "gst_submit_job".to_string(),
+            ToolMetadata::new(
+                "gst_submit_job",
+                ToolCategory::Pipeline,
+                "Queues a batch pipeline (e.g., a transcode) that runs until EOS. Accepts gst-launch syntax, priority and custom job ID (optional). At most max_concurrent_jobs run at once; the rest wait in priority/FIFO order. Returns the job ID and queue position. Use to submit many transcodes without overwhelming the machine.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_estimate_job".to_string(),
+            ToolMetadata::new(
+                "gst_estimate_job",
+                ToolCategory::Pipeline,
+                "Predicts duration, CPU usage, and output size of a transcode from a short sample encode. Accepts the job's pipeline description and sample length (optional). Returns the estimates and the measured speed. Use before submitting long jobs to warn users how long they will take.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_list_jobs".to_string(),
+            ToolMetadata::new(
+                "gst_list_jobs",
+                ToolCategory::Pipeline,
+                "Lists batch jobs in queue order. Accepts status filter (optional: queued, running, completed, failed, cancelled). Returns job IDs, status, priority, timestamps, and errors. Use to track progress of submitted jobs.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_cancel_job".to_string(),
+            ToolMetadata::new(
+                "gst_cancel_job",
+                ToolCategory::Pipeline,
+                "Cancels a batch job. Accepts job ID. Queued jobs are cancelled immediately; running jobs have their pipeline stopped. Returns the job status. Use to withdraw jobs that are no longer needed.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_list_artifacts".to_string(),
+            ToolMetadata::new(
+                "gst_list_artifacts",
+                ToolCategory::Pipeline,
+                "Lists files produced by managed pipelines and jobs. Accepts pipeline ID filter (optional). Returns paths, sizes, durations, SHA-256 hashes, and creation times. Use to find recordings, thumbnails, and transcodes.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_prune_artifacts".to_string(),
+            ToolMetadata::new(
+                "gst_prune_artifacts",
+                ToolCategory::Pipeline,
+                "Removes artifacts selected by a retention policy (max age, total size, newest N). Accepts pipeline ID filter, delete_files and dry_run flags (optional). Returns the pruned artifacts. Use to reclaim disk space.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_concat_media".to_string(),
+            ToolMetadata::new(
+                "gst_concat_media",
+                ToolCategory::Pipeline,
+                "Joins media files into one output as a batch job. Accepts input paths, output path, reencode, tonemap and downmix flags (optional). Checks codec compatibility up front and remuxes when possible, re-encoding otherwise. Returns the job ID and the chosen mode. Use to stitch recordings or clips together.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_extract_frame_at".to_string(),
+            ToolMetadata::new(
+                "gst_extract_frame_at",
+                ToolCategory::Pipeline,
+                "Exports the frame of a video file at a timestamp as a PNG or JPEG image. Accepts input path, output path, position in nanoseconds and accurate flag (optional). Seeks accurately to the exact frame, or to the preceding keyframe when speed matters more. Returns the frame's timestamp and size. Use to grab thumbnails or inspect a specific frame of a file.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_extract_audio_segment".to_string(),
+            ToolMetadata::new(
+                "gst_extract_audio_segment",
+                ToolCategory::Pipeline,
+                "Extracts a bounded audio segment from a file or a pad of a running pipeline. Accepts input path with start, rate and channels, or pipeline ID and pad; duration and output path (optional). Returns a WAV (or encoded) file as an artifact resource. Use to feed fingerprinting or identification services.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_analyze_gop".to_string(),
+            ToolMetadata::new(
+                "gst_analyze_gop",
+                ToolCategory::Pipeline,
+                "Analyzes the keyframe/GOP structure of a video file by parsing, not decoding, it. Accepts input path. Returns GOP length distribution, keyframe timestamps, B-frame usage, colorimetry/HDR metadata and hints. Use when diagnosing slow seeking or HLS segmenting problems.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_bitrate_profile".to_string(),
+            ToolMetadata::new(
+                "gst_bitrate_profile",
+                ToolCategory::Pipeline,
+                "Scans a media file and returns per-interval bitrate samples split by stream. Accepts input path, interval and target bitrate (optional). Returns averages, peaks, intervals over the target and re-encoding recommendations. Use to spot spikes that break streaming targets.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_verify_file".to_string(),
+            ToolMetadata::new(
+                "gst_verify_file",
+                ToolCategory::Pipeline,
+                "Decodes an entire media file into fakesinks to check it. Accepts input path. Returns decode errors with timestamps, truncation, audio/video duration mismatches and an OK/not OK verdict. Use to answer whether a file is corrupt.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_detect_interlacing".to_string(),
+            ToolMetadata::new(
+                "gst_detect_interlacing",
+                ToolCategory::Pipeline,
+                "Samples the video of a file or URI to tell progressive, interlaced, mixed and telecined content apart. Accepts input path or URI and frame count. Returns scan type, field order and the deinterlace element to insert. Transcode tools deinterlace interlaced inputs automatically.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Discovery],
+            ),
+        );
+
+        tools.insert(
+            "gst_normalize_audio".to_string(),
+            ToolMetadata::new(
+                "gst_normalize_audio",
+                ToolCategory::Pipeline,
+                "Normalizes a file's loudness to a target LUFS (EBU R128). Accepts input path, output path, target LUFS and true-peak limit (optional). Measures, applies gain, and measures the result. Returns before/after loudness and the applied gain. Use to level audio for broadcast or streaming.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_change_speed".to_string(),
+            ToolMetadata::new(
+                "gst_change_speed",
+                ToolCategory::Pipeline,
+                "Writes a sped-up or slowed-down copy of a file as a batch job. Accepts input path, output path, rate (0.25-4.0), preserve_pitch, tonemap and downmix flags (optional). HDR video is tone-mapped to SDR by default; multichannel audio can be downmixed to stereo. Handles audio-only and audio/video inputs. Returns the job ID and expected output duration. Use for time-lapses, slow motion, or faster speech.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_create_abr_ladder".to_string(),
+            ToolMetadata::new(
+                "gst_create_abr_ladder",
+                ToolCategory::Pipeline,
+                "Encodes a file or stream into a multi-bitrate HLS or DASH ladder. Accepts input path or URI, output directory, format, renditions (width, height, kbit/s), audio bitrate, tonemap and downmix flags, and segment duration (optional). Returns the pipeline ID, manifest path, and renditions. Use to package video for adaptive streaming.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_list_ndi_sources".to_string(),
+            ToolMetadata::new(
+                "gst_list_ndi_sources",
+                ToolCategory::Discovery,
+                "Lists NDI sources announced on the network. Accepts wait time in milliseconds (optional). Returns source names and sender addresses. Use to find the ndi-name for receiving NDI video.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Dev, OperationalMode::Discovery],
+            )
+            .with_required_elements(NDI_ELEMENTS),
+        );
+
+        tools.insert(
+            "gst_launch_ndi".to_string(),
+            ToolMetadata::new(
+                "gst_launch_ndi",
+                ToolCategory::Pipeline,
+                "Launches a pipeline that receives or publishes NDI. Accepts direction, NDI name, video/audio chains, sender address, and bandwidth (optional). Returns the pipeline ID and generated description. Use instead of hand-writing ndisrc/ndisink wiring.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            )
+            .with_required_elements(NDI_ELEMENTS),
+        );
+
+        tools.insert(
+            "gst_list_aravis_cameras".to_string(),
+            ToolMetadata::new(
+                "gst_list_aravis_cameras",
+                ToolCategory::Discovery,
+                "Lists GigE Vision / USB3 Vision cameras visible to Aravis. Accepts details flag (optional). Returns camera names, addresses, and optionally pixel formats, sizes, exposure, and gain. Use to find the camera-name and formats for aravissrc.",
+                vec![OperationalMode::All, OperationalMode::Live, OperationalMode::Dev, OperationalMode::Discovery],
+            )
+            .with_required_elements(ARAVIS_ELEMENTS),
+        );
+
+        tools.insert(
+            "gst_launch_aravis".to_string(),
+            ToolMetadata::new(
+                "gst_launch_aravis",
+                ToolCategory::Pipeline,
+                "Launches a pipeline streaming from an industrial camera. Accepts camera name, exposure, gain, auto modes, pixel format, size, frame rate, and downstream chain (optional). Returns the pipeline ID and generated description. Use for machine-vision capture with aravissrc.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            )
+            .with_required_elements(ARAVIS_ELEMENTS),
+        );
+
+        tools.insert(
+            "gst_route_audio".to_string(),
+            ToolMetadata::new(
+                "gst_route_audio",
+                ToolCategory::Pipeline,
+                "Routes audio channels through an audiomixmatrix element of a running pipeline. Accepts pipeline ID, element name (optional), and routes (input channel, output channel, gain in dB). Returns the applied gain matrix. Use for multi-channel production routing without restarting.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_attach_transcriber".to_string(),
+            ToolMetadata::new(
+                "gst_attach_transcriber",
+                ToolCategory::Pipeline,
+                "Attaches a speech-to-text branch to a tee of a running pipeline. Accepts pipeline ID, tee name, and transcriber element (optional). Returns the branch name; transcripts arrive as notifications. Use to follow what is said in a stream.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_attach_detector".to_string(),
+            ToolMetadata::new(
+                "gst_attach_detector",
+                ToolCategory::Pipeline,
+                "Attaches an object detection branch to a tee of a running pipeline. Accepts pipeline ID, tee name, detector, model, labels, and minimum confidence (optional). Returns the branch name; detections arrive as notifications. Use to react to visual content, e.g. start recording when a person appears.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_speak".to_string(),
+            ToolMetadata::new(
+                "gst_speak",
+                ToolCategory::Pipeline,
+                "Speaks text through a text-to-speech element. Accepts text and output WAV path (optional). Returns the pipeline ID. Use to announce events or produce voice-over audio.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_set_error_budget".to_string(),
+            ToolMetadata::new(
+                "gst_set_error_budget",
+                ToolCategory::Pipeline,
+                "Sets per-pipeline alert thresholds (errors, warnings, and buffering events per minute). Accepts pipeline ID and limits (optional). Returns the budget; breaches degrade or fail the pipeline health and send notifications. Use for proactive alerting on long-running pipelines.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_monitor_audio_levels".to_string(),
+            ToolMetadata::new(
+                "gst_monitor_audio_levels",
+                ToolCategory::Pipeline,
+                "Meters the audio of a running pipeline from a tee and warns about sustained clipping or silence. Accepts pipeline ID, tee name and per-pipeline clip/silence levels and durations (optional). Returns the branch and thresholds; alerts arrive as notifications. Use to catch dead or overdriven microphones on live streams.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_switch_encoder".to_string(),
+            ToolMetadata::new(
+                "gst_switch_encoder",
+                ToolCategory::Pipeline,
+                "Migrates a live stream to another encoder without interrupting the output. Accepts pipeline ID, encoder name, replacement encoder description, warm-up, and timeout (optional). Returns the switch timestamp. Use to move encoding to hardware, or off a failing encoder, while streaming.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_start_clock_server".to_string(),
+            ToolMetadata::new(
+                "gst_start_clock_server",
+                ToolCategory::Pipeline,
+                "Publishes this host's clock on the network. Accepts UDP port and bind address (optional). Returns the port and current clock time. Use on one host before syncing pipelines for multi-room audio or video walls.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_sync_pipeline".to_string(),
+            ToolMetadata::new(
+                "gst_sync_pipeline",
+                ToolCategory::Pipeline,
+                "Slaves a pipeline to a network clock with a shared base time and latency, then plays it. Accepts pipeline ID, clock server address and port, base time, and latency (optional). Returns the sync settings. Use on every pipeline that must render in lockstep.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
+        tools.insert(
+            "gst_get_sync_report".to_string(),
+            ToolMetadata::new(
+                "gst_get_sync_report",
+                ToolCategory::Pipeline,
+                "Reports clock lock and drift of synchronized pipelines. Accepts pipeline ID (optional). Returns sync state, drift in ppm, and running time. Use to check that rooms or screens stay aligned.",
+                vec![OperationalMode::All, OperationalMode::Live],
+            ),
+        );
+
         // Future tools (PRP-03, PRP-04, PRP-05, PRP-06) would be added here
         // For now, we're only including the implemented tools
 
@@ -248,7 +1023,7 @@ mod tests {
 
         // All mode should have everything
         let all_tools = registry.get_tools_for_mode(&OperationalMode::All);
-        assert_eq!(all_tools.len(), 10); // We have 10 implemented tools
+        assert_eq!(all_tools.len(), 84); // We have 84 implemented tools
     }
 
     #[test]
@@ -0,0 +1,421 @@
+use gstreamer as gst;
+use gstreamer::glib;
+use gstreamer::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A property value written with a unit, e.g. `2Mbps` or `500ms`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quantity {
+    BitRate { bits_per_second: f64 },
+    Duration { nanoseconds: f64 },
+    Size { bytes: f64 },
+    Frequency { hertz: f64 },
+    Percent(f64),
+    Resolution { width: u32, height: u32 },
+}
+
+impl Quantity {
+    fn kind(&self) -> &'static str {
+        match self {
+            Quantity::BitRate { .. } => "bitrate",
+            Quantity::Duration { .. } => "duration",
+            Quantity::Size { .. } => "size",
+            Quantity::Frequency { .. } => "frequency",
+            Quantity::Percent(_) => "percentage",
+            Quantity::Resolution { .. } => "resolution",
+        }
+    }
+}
+
+/// Unit a property takes, from its name, description and type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropertyUnit {
+    BitsPerSecond,
+    KbitsPerSecond,
+    MbitsPerSecond,
+    BytesPerSecond,
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+    Bytes,
+    Hertz,
+    /// A number, where percentages are divided by 100 for floats
+    Ratio,
+    Fraction,
+    Caps,
+}
+
+impl PropertyUnit {
+    pub fn label(self) -> &'static str {
+        match self {
+            PropertyUnit::BitsPerSecond => "bit/s",
+            PropertyUnit::KbitsPerSecond => "kbit/s",
+            PropertyUnit::MbitsPerSecond => "Mbit/s",
+            PropertyUnit::BytesPerSecond => "bytes/s",
+            PropertyUnit::Nanoseconds => "ns",
+            PropertyUnit::Microseconds => "us",
+            PropertyUnit::Milliseconds => "ms",
+            PropertyUnit::Seconds => "s",
+            PropertyUnit::Bytes => "bytes",
+            PropertyUnit::Hertz => "Hz",
+            PropertyUnit::Ratio => "a plain number",
+            PropertyUnit::Fraction => "fraction",
+            PropertyUnit::Caps => "caps",
+        }
+    }
+}
+
+/// A value rewritten from human units into the form the property takes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitConversion {
+    /// Value as given
+    pub input: String,
+    /// Value as set, e.g. `2000` for `2Mbps` on a property in kbit/s
+    pub normalized: String,
+    /// Unit the property takes
+    pub unit: String,
+}
+
+/// Parse a value written with a unit. Returns None for plain numbers and
+/// anything else without a known unit.
+pub fn parse_quantity(value: &str) -> Option<Quantity> {
+    let value = value.trim();
+    if value.starts_with("0x") || value.starts_with("0X") {
+        // Hexadecimal, e.g. flags
+        return None;
+    }
+    if let Some((width, height)) = value.split_once(['x', 'X']) {
+        if let (Ok(width), Ok(height)) = (width.trim().parse(), height.trim().parse()) {
+            return Some(Quantity::Resolution { width, height });
+        }
+    }
+
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+')))
+        .filter(|i| *i > 0)?;
+    let number: f64 = value[..split].parse().ok()?;
+    let unit = value[split..].trim();
+
+    if unit == "%" {
+        return Some(Quantity::Percent(number));
+    }
+
+    // Rates: a prefix, then b (bits) or B (bytes), then ps or /s
+    let rate = unit
+        .strip_suffix("ps")
+        .or_else(|| unit.strip_suffix("/s"))
+        .or_else(|| unit.strip_suffix("/sec"));
+    if let Some(rate) = rate {
+        let (scale, base) = split_prefix(rate);
+        let bits = match base {
+            "b" | "bit" | "bits" => 1.0,
+            "B" | "byte" | "bytes" => 8.0,
+            _ => return None,
+        };
+        return Some(Quantity::BitRate {
+            bits_per_second: number * scale * bits,
+        });
+    }
+
+    let nanoseconds = match unit {
+        "ns" => Some(1.0),
+        "us" | "µs" => Some(1e3),
+        "ms" => Some(1e6),
+        "s" | "sec" | "secs" => Some(1e9),
+        "min" => Some(60e9),
+        "h" => Some(3600e9),
+        _ => None,
+    };
+    if let Some(scale) = nanoseconds {
+        return Some(Quantity::Duration {
+            nanoseconds: number * scale,
+        });
+    }
+
+    if let Some(frequency) = unit.strip_suffix("Hz").or_else(|| unit.strip_suffix("hz")) {
+        let (scale, base) = split_prefix(frequency);
+        return base.is_empty().then_some(Quantity::Frequency {
+            hertz: number * scale,
+        });
+    }
+
+    let bytes = match unit {
+        "B" => Some(1.0),
+        "KB" | "kB" => Some(1e3),
+        "MB" => Some(1e6),
+        "GB" => Some(1e9),
+        "KiB" => Some(1024.0),
+        "MiB" => Some(1024.0 * 1024.0),
+        "GiB" => Some(1024.0 * 1024.0 * 1024.0),
+        _ => None,
+    };
+    bytes.map(|scale| Quantity::Size {
+        bytes: number * scale,
+    })
+}
+
+/// Split an SI prefix (k, M, G) off a unit
+fn split_prefix(unit: &str) -> (f64, &str) {
+    let scale = match unit.chars().next() {
+        Some('k' | 'K') => 1e3,
+        Some('M' | 'm') => 1e6,
+        Some('G' | 'g') => 1e9,
+        _ => return (1.0, unit),
+    };
+    let rest = &unit[1..];
+    // A lone letter is the unit itself, not a prefix
+    if rest.is_empty() {
+        (1.0, unit)
+    } else {
+        (scale, rest)
+    }
+}
+
+/// Unit of a property, from its name and description. Units written in the
+/// description ("in kbit/sec", "(in ns)") win over the name.
+pub fn property_unit(name: &str, blurb: &str, numeric: bool) -> Option<PropertyUnit> {
+    if !numeric {
+        return None;
+    }
+    let name = name.to_lowercase();
+    let blurb = blurb.to_lowercase();
+    let words: Vec<&str> = blurb
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let has = |candidates: &[&str]| words.iter().any(|w| candidates.contains(w));
+
+    if name.contains("bitrate") || name.contains("bit-rate") || blurb.contains("bitrate") {
+        return Some(if has(&["kbit", "kbps", "kbits", "kb"]) {
+            PropertyUnit::KbitsPerSecond
+        } else if has(&["mbit", "mbps", "mbits"]) {
+            PropertyUnit::MbitsPerSecond
+        } else {
+            PropertyUnit::BitsPerSecond
+        });
+    }
+    if name.contains("byte-rate") || blurb.contains("bytes per second") {
+        return Some(PropertyUnit::BytesPerSecond);
+    }
+    if has(&["ns", "nanoseconds", "nanosecond", "nsec"]) {
+        return Some(PropertyUnit::Nanoseconds);
+    }
+    if has(&["us", "usec", "microseconds", "microsecond"]) {
+        return Some(PropertyUnit::Microseconds);
+    }
+    if has(&["ms", "msec", "msecs", "milliseconds", "millisecond"]) {
+        return Some(PropertyUnit::Milliseconds);
+    }
+    if has(&["seconds", "second", "sec", "secs"]) {
+        return Some(PropertyUnit::Seconds);
+    }
+    if name.contains("bytes") || name == "blocksize" || has(&["bytes"]) {
+        return Some(PropertyUnit::Bytes);
+    }
+    if has(&["hz"]) {
+        return Some(PropertyUnit::Hertz);
+    }
+    // Time properties without a unit follow GStreamer's clock time, in ns
+    let time_like = [
+        "time", "latency", "timeout", "duration", "delay", "interval",
+    ];
+    if time_like.iter().any(|t| name.contains(t)) {
+        return Some(PropertyUnit::Nanoseconds);
+    }
+    Some(PropertyUnit::Ratio)
+}
+
+/// Decimal frame rate or ratio as a fraction, NTSC rates included
+/// (29.97 is 30000/1001)
+pub fn decimal_to_fraction(value: f64) -> Option<(i32, i32)> {
+    if !value.is_finite() {
+        return None;
+    }
+    let ntsc = (value * 1.001).round();
+    if value.fract() != 0.0 && ntsc > 0.0 && (ntsc * 1000.0 / 1001.0 - value).abs() < 0.005 {
+        return Some((ntsc as i32 * 1000, 1001));
+    }
+    gst::Fraction::approximate_f64(value).map(|f| (f.numer(), f.denom()))
+}
+
+/// Rewrite `value` for a property in `unit`, unset for properties that are
+/// not numbers. Returns None when the value is passed on as it is.
+pub fn convert_quantity(
+    value: &str,
+    unit: Option<PropertyUnit>,
+    integer: bool,
+) -> Result<Option<String>, String> {
+    let Some(quantity) = parse_quantity(value) else {
+        // Decimal frame rates and ratios for fractions
+        if unit == Some(PropertyUnit::Fraction) && !value.contains('/') {
+            if let Some((numer, denom)) = value.trim().parse().ok().and_then(decimal_to_fraction) {
+                return Ok(Some(format!("{}/{}", numer, denom)));
+            }
+        }
+        return Ok(None);
+    };
+
+    let mismatch = |unit: PropertyUnit| {
+        Err(format!(
+            "'{}' is a {}, but the property takes {}",
+            value,
+            quantity.kind(),
+            unit.label()
+        ))
+    };
+    // Strings, enums and other values are never rewritten
+    let Some(unit) = unit else {
+        return Ok(None);
+    };
+
+    let number = match (quantity, unit) {
+        (Quantity::Resolution { width, height }, PropertyUnit::Caps) => {
+            return Ok(Some(format!(
+                "video/x-raw,width={},height={}",
+                width, height
+            )));
+        }
+        (Quantity::BitRate { bits_per_second }, _) => match unit {
+            PropertyUnit::BitsPerSecond => bits_per_second,
+            PropertyUnit::KbitsPerSecond => bits_per_second / 1e3,
+            PropertyUnit::MbitsPerSecond => bits_per_second / 1e6,
+            PropertyUnit::BytesPerSecond => bits_per_second / 8.0,
+            _ => return mismatch(unit),
+        },
+        (Quantity::Duration { nanoseconds }, _) => match unit {
+            PropertyUnit::Nanoseconds => nanoseconds,
+            PropertyUnit::Microseconds => nanoseconds / 1e3,
+            PropertyUnit::Milliseconds => nanoseconds / 1e6,
+            PropertyUnit::Seconds => nanoseconds / 1e9,
+            _ => return mismatch(unit),
+        },
+        (Quantity::Size { bytes }, PropertyUnit::Bytes) => bytes,
+        (Quantity::Frequency { hertz }, PropertyUnit::Hertz) => hertz,
+        (Quantity::Percent(percent), _) if integer => percent,
+        (Quantity::Percent(percent), _) => percent / 100.0,
+        _ => return mismatch(unit),
+    };
+
+    Ok(Some(if integer {
+        format!("{}", number.round() as i64)
+    } else {
+        format!("{}", number)
+    }))
+}
+
+/// Rewrite a property value given with human units ("2Mbps", "500ms",
+/// "1920x1080", "29.97") into the form its param spec takes. Returns None
+/// when the value is passed on as it is.
+pub fn normalize_value(
+    pspec: &glib::ParamSpec,
+    value: &str,
+) -> Result<Option<UnitConversion>, String> {
+    let value_type = pspec.value_type();
+    let integer = [
+        glib::Type::I8,
+        glib::Type::U8,
+        glib::Type::I32,
+        glib::Type::U32,
+        glib::Type::I_LONG,
+        glib::Type::U_LONG,
+        glib::Type::I64,
+        glib::Type::U64,
+    ]
+    .contains(&value_type);
+    let float = value_type == glib::Type::F32 || value_type == glib::Type::F64;
+
+    let unit = if value_type == gst::Fraction::static_type() {
+        Some(PropertyUnit::Fraction)
+    } else if value_type == gst::Caps::static_type() {
+        Some(PropertyUnit::Caps)
+    } else {
+        property_unit(pspec.name(), pspec.blurb().unwrap_or(""), integer || float)
+    };
+
+    Ok(
+        convert_quantity(value, unit, integer)?.map(|normalized| UnitConversion {
+            input: value.to_string(),
+            normalized,
+            unit: unit
+                .map(PropertyUnit::label)
+                .unwrap_or_default()
+                .to_string(),
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_conversion() {
+        assert_eq!(
+            parse_quantity("2Mbps"),
+            Some(Quantity::BitRate {
+                bits_per_second: 2e6
+            })
+        );
+        assert_eq!(
+            parse_quantity("1 MB/s"),
+            Some(Quantity::BitRate {
+                bits_per_second: 8e6
+            })
+        );
+        assert_eq!(
+            parse_quantity("500ms"),
+            Some(Quantity::Duration { nanoseconds: 5e8 })
+        );
+        assert_eq!(
+            parse_quantity("1920x1080"),
+            Some(Quantity::Resolution {
+                width: 1920,
+                height: 1080
+            })
+        );
+        assert_eq!(parse_quantity("0.5"), None);
+        assert_eq!(parse_quantity("ultrafast"), None);
+
+        // x264enc: "Bitrate in kbit/sec"
+        let unit = property_unit("bitrate", "Bitrate in kbit/sec", true);
+        assert_eq!(unit, Some(PropertyUnit::KbitsPerSecond));
+        assert_eq!(
+            convert_quantity("2Mbps", unit, true),
+            Ok(Some("2000".to_string()))
+        );
+        // queue: "Max. amount of data in the queue (in ns, 0=disable)"
+        let unit = property_unit(
+            "max-size-time",
+            "Max. amount of data in the queue (in ns, 0=disable)",
+            true,
+        );
+        assert_eq!(
+            convert_quantity("500ms", unit, true),
+            Ok(Some("500000000".to_string()))
+        );
+        // rtpjitterbuffer: "Amount of ms to buffer"
+        let unit = property_unit("latency", "Amount of ms to buffer", true);
+        assert_eq!(
+            convert_quantity("2s", unit, true),
+            Ok(Some("2000".to_string()))
+        );
+        assert!(convert_quantity("500ms", Some(PropertyUnit::KbitsPerSecond), true).is_err());
+        assert_eq!(
+            convert_quantity("0.5", Some(PropertyUnit::Ratio), false),
+            Ok(None)
+        );
+        assert_eq!(
+            convert_quantity("50%", Some(PropertyUnit::Ratio), false),
+            Ok(Some("0.5".to_string()))
+        );
+        assert_eq!(
+            convert_quantity("29.97", Some(PropertyUnit::Fraction), false),
+            Ok(Some("30000/1001".to_string()))
+        );
+        assert_eq!(
+            convert_quantity("1280x720", Some(PropertyUnit::Caps), false),
+            Ok(Some("video/x-raw,width=1280,height=720".to_string()))
+        );
+    }
+}
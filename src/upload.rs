@@ -0,0 +1,269 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::discovery::ensure_gstreamer_initialized;
+use crate::error::{GStreamerMcpError, Result as McpResult};
+
+/// How often a running upload reports progress
+const PROGRESS_INTERVAL_MS: u64 = 500;
+
+/// Storage that finished artifacts can be uploaded to. Credentials live in
+/// the server configuration and are never accepted from tool arguments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum UploadTarget {
+    /// S3-compatible object storage, uploaded with `awss3sink`
+    /// (gst-plugins-rs)
+    S3 {
+        bucket: String,
+        #[serde(default)]
+        region: Option<String>,
+        /// Custom endpoint for S3-compatible services (e.g. MinIO)
+        #[serde(default)]
+        endpoint_uri: Option<String>,
+        #[serde(default)]
+        access_key_id: Option<String>,
+        #[serde(default)]
+        secret_access_key: Option<String>,
+        /// Key prefix prepended to every uploaded file name
+        #[serde(default)]
+        prefix: Option<String>,
+    },
+    /// HTTP endpoint accepting PUT requests, uploaded with
+    /// `souphttpclientsink`
+    Http {
+        /// Base URL; the file name is appended to it
+        url: String,
+        #[serde(default)]
+        username: Option<String>,
+        #[serde(default)]
+        password: Option<String>,
+    },
+}
+
+impl UploadTarget {
+    fn sink_factory(&self) -> &'static str {
+        match self {
+            UploadTarget::S3 { .. } => "awss3sink",
+            UploadTarget::Http { .. } => "souphttpclientsink",
+        }
+    }
+
+    /// Object key or URL path of a file uploaded under `name`
+    fn key(&self, name: &str, prefix: Option<&str>) -> String {
+        let configured = match self {
+            UploadTarget::S3 { prefix, .. } => prefix.as_deref(),
+            UploadTarget::Http { .. } => None,
+        };
+        [configured, prefix, Some(name)]
+            .into_iter()
+            .flatten()
+            .map(|part| part.trim_matches('/'))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Final URL of an uploaded object
+    fn url(&self, key: &str) -> String {
+        match self {
+            UploadTarget::S3 {
+                bucket,
+                endpoint_uri: Some(endpoint),
+                ..
+            } => format!("{}/{}/{}", endpoint.trim_end_matches('/'), bucket, key),
+            UploadTarget::S3 { bucket, .. } => format!("s3://{}/{}", bucket, key),
+            UploadTarget::Http { url, .. } => format!("{}/{}", url.trim_end_matches('/'), key),
+        }
+    }
+
+    fn make_sink(&self, key: &str) -> McpResult<gst::Element> {
+        let factory = self.sink_factory();
+        let sink = gst::ElementFactory::make(factory).build().map_err(|_| {
+            GStreamerMcpError::ElementNotFound(format!(
+                "{} is required to upload to {} targets",
+                factory,
+                match self {
+                    UploadTarget::S3 { .. } => "S3",
+                    UploadTarget::Http { .. } => "HTTP",
+                }
+            ))
+        })?;
+
+        match self {
+            UploadTarget::S3 {
+                bucket,
+                region,
+                endpoint_uri,
+                access_key_id,
+                secret_access_key,
+                ..
+            } => {
+                sink.set_property("bucket", bucket);
+                sink.set_property("key", key);
+                if let Some(region) = region {
+                    sink.set_property("region", region);
+                }
+                if let Some(endpoint) = endpoint_uri {
+                    sink.set_property("endpoint-uri", endpoint);
+                }
+                if let Some(access_key) = access_key_id {
+                    sink.set_property("access-key", access_key);
+                }
+                if let Some(secret) = secret_access_key {
+                    sink.set_property("secret-access-key", secret);
+                }
+            }
+            UploadTarget::Http {
+                username, password, ..
+            } => {
+                sink.set_property("location", self.url(key));
+                if let Some(username) = username {
+                    sink.set_property("user-id", username);
+                }
+                if let Some(password) = password {
+                    sink.set_property("user-pw", password);
+                }
+            }
+        }
+        Ok(sink)
+    }
+}
+
+/// Upload requested for the artifacts of a job
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct UploadRequest {
+    #[schemars(description = "Name of an upload target from the server configuration")]
+    pub target: String,
+    #[schemars(description = "Key or path prefix for the uploaded files (optional)")]
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UploadState {
+    Pending,
+    Uploading,
+    Completed,
+    Failed,
+}
+
+/// Progress of the upload of a job's artifacts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadProgress {
+    pub target: String,
+    pub state: UploadState,
+    pub bytes_total: u64,
+    pub bytes_uploaded: u64,
+    /// URLs of the files uploaded so far
+    pub urls: Vec<String>,
+    pub error: Option<String>,
+}
+
+impl UploadProgress {
+    pub fn new(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+            state: UploadState::Pending,
+            bytes_total: 0,
+            bytes_uploaded: 0,
+            urls: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+/// Stream a file to an upload target and return its final URL.
+/// `on_progress` is called with the number of bytes sent so far. Blocks
+/// until the upload finishes.
+pub fn upload_file(
+    target: &UploadTarget,
+    path: &Path,
+    prefix: Option<&str>,
+    mut on_progress: impl FnMut(u64),
+) -> McpResult<String> {
+    ensure_gstreamer_initialized()?;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| GStreamerMcpError::Other(format!("{} is not a file", path.display())))?;
+    let key = target.key(&name, prefix);
+
+    let src = gst::ElementFactory::make("filesrc")
+        .property("location", path.to_string_lossy().as_ref())
+        .build()
+        .map_err(|e| GStreamerMcpError::PipelineError(e.to_string()))?;
+    let sink = target.make_sink(&key)?;
+
+    let pipeline = gst::Pipeline::new();
+    pipeline
+        .add_many([&src, &sink])
+        .and_then(|_| src.link(&sink))
+        .map_err(|e| GStreamerMcpError::PipelineError(format!("Failed to build upload: {}", e)))?;
+    let bus = pipeline
+        .bus()
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Pipeline has no bus".to_string()))?;
+
+    pipeline.set_state(gst::State::Playing).map_err(|e| {
+        GStreamerMcpError::PipelineError(format!("Failed to start upload: {:?}", e))
+    })?;
+
+    let result = loop {
+        let msg = bus.timed_pop_filtered(
+            gst::ClockTime::from_mseconds(PROGRESS_INTERVAL_MS),
+            &[gst::MessageType::Eos, gst::MessageType::Error],
+        );
+        if let Some(sent) = pipeline.query_position::<gst::format::Bytes>() {
+            on_progress(*sent);
+        }
+        match msg.as_ref().map(|m| m.view()) {
+            Some(gst::MessageView::Error(err)) => {
+                break Err(GStreamerMcpError::PipelineError(format!(
+                    "Upload of {} failed: {}",
+                    path.display(),
+                    err.error()
+                )));
+            }
+            Some(_) => break Ok(target.url(&key)),
+            None => continue,
+        }
+    };
+
+    let _ = pipeline.set_state(gst::State::Null);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_upload_urls() {
+        let s3 = UploadTarget::S3 {
+            bucket: "media".to_string(),
+            region: None,
+            endpoint_uri: None,
+            access_key_id: None,
+            secret_access_key: None,
+            prefix: Some("recordings/".to_string()),
+        };
+        let key = s3.key("out.mp4", Some("/2024-06-01/"));
+        assert_eq!(key, "recordings/2024-06-01/out.mp4");
+        assert_eq!(s3.url(&key), "s3://media/recordings/2024-06-01/out.mp4");
+
+        let http = UploadTarget::Http {
+            url: "https://uploads.example.com/incoming/".to_string(),
+            username: None,
+            password: None,
+        };
+        let key = http.key("thumb.png", None);
+        assert_eq!(
+            http.url(&key),
+            "https://uploads.example.com/incoming/thumb.png"
+        );
+    }
+}
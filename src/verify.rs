@@ -0,0 +1,326 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::media::quote_location;
+use crate::suggestions::parse_launch;
+
+/// Decode issues kept in a report; later ones are only counted
+const MAX_REPORTED_ISSUES: usize = 100;
+
+/// Audio and video ending further apart than this are reported
+const AV_MISMATCH_TOLERANCE_NS: u64 = 500_000_000;
+
+/// Decoded media ending this much before the declared duration means the
+/// file is truncated
+const TRUNCATION_TOLERANCE_NS: u64 = 1_000_000_000;
+
+/// A decode warning or error
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecodeIssue {
+    /// Playback position when the issue was reported
+    pub position_ns: Option<u64>,
+    pub source: String,
+    pub message: String,
+    /// Whether decoding stopped
+    pub fatal: bool,
+}
+
+/// Time span covered by the decoded buffers of a stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamCoverage {
+    pub index: usize,
+    pub media_type: String,
+    pub buffers: u64,
+    pub start_ns: Option<u64>,
+    pub end_ns: Option<u64>,
+}
+
+impl StreamCoverage {
+    fn is(&self, kind: &str) -> bool {
+        self.media_type.starts_with(kind)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerifyReport {
+    pub ok: bool,
+    /// Duration the container declares
+    pub declared_duration_ns: Option<u64>,
+    pub streams: Vec<StreamCoverage>,
+    pub issues: Vec<DecodeIssue>,
+    pub warning_count: u64,
+    pub error_count: u64,
+    pub truncated: bool,
+    /// Video end minus audio end
+    pub av_end_difference_ns: Option<i64>,
+    /// Everything found wrong, in plain words
+    pub problems: Vec<String>,
+}
+
+/// Judge the decode results: truncation, A/V mismatch and decode errors
+pub fn assess(report: &mut VerifyReport) {
+    let end = report.streams.iter().filter_map(|s| s.end_ns).max();
+    if let (Some(declared), Some(end)) = (report.declared_duration_ns, end) {
+        if end + TRUNCATION_TOLERANCE_NS < declared {
+            report.truncated = true;
+            report.problems.push(format!(
+                "Decoding ended at {:.2} s of the declared {:.2} s: the file is truncated",
+                end as f64 / 1e9,
+                declared as f64 / 1e9
+            ));
+        }
+    }
+
+    let stream_end = |kind: &str| {
+        report
+            .streams
+            .iter()
+            .filter(|s| s.is(kind))
+            .filter_map(|s| s.end_ns)
+            .max()
+    };
+    if let (Some(video), Some(audio)) = (stream_end("video/"), stream_end("audio/")) {
+        let difference = video as i64 - audio as i64;
+        report.av_end_difference_ns = Some(difference);
+        if difference.unsigned_abs() > AV_MISMATCH_TOLERANCE_NS {
+            report.problems.push(format!(
+                "Video ends {:.2} s {} audio",
+                difference.unsigned_abs() as f64 / 1e9,
+                if difference > 0 { "after" } else { "before" }
+            ));
+        }
+    }
+
+    for stream in report.streams.iter().filter(|s| s.buffers == 0) {
+        report.problems.push(format!(
+            "Stream {} ({}) decoded no data",
+            stream.index, stream.media_type
+        ));
+    }
+    if report.error_count > 0 {
+        report.problems.push(format!(
+            "{} decode error(s) stopped decoding",
+            report.error_count
+        ));
+    }
+    if report.warning_count > 0 {
+        report.problems.push(format!(
+            "{} decode warning(s), usually corrupt frames",
+            report.warning_count
+        ));
+    }
+    report.ok = report.problems.is_empty();
+}
+
+fn issue(message: &gst::MessageRef, position: Option<gst::ClockTime>) -> Option<DecodeIssue> {
+    let source = message
+        .src()
+        .map(|s| s.name().to_string())
+        .unwrap_or_default();
+    let position_ns = position.map(|p| p.nseconds());
+    match message.view() {
+        gst::MessageView::Error(err) => Some(DecodeIssue {
+            position_ns,
+            source,
+            message: err.error().to_string(),
+            fatal: true,
+        }),
+        gst::MessageView::Warning(warning) => Some(DecodeIssue {
+            position_ns,
+            source,
+            message: warning.error().to_string(),
+            fatal: false,
+        }),
+        _ => None,
+    }
+}
+
+/// Decode a whole file as fast as possible and report decode errors with
+/// their position, truncation and audio/video duration mismatches.
+/// Decoders are told to tolerate any number of corrupt frames, so one bad
+/// frame does not hide the rest of the file.
+pub fn verify_file(input: &Path) -> McpResult<VerifyReport> {
+    let description = format!(
+        "filesrc location={} ! decodebin name=dec",
+        quote_location(input)
+    );
+    let pipeline = parse_launch(&description, "Failed to build verification pipeline")?
+        .downcast::<gst::Pipeline>()
+        .map_err(|_| GStreamerMcpError::PipelineError("Expected a pipeline".to_string()))?;
+    let decodebin = pipeline
+        .by_name("dec")
+        .ok_or_else(|| GStreamerMcpError::PipelineError("decodebin not found".to_string()))?;
+
+    decodebin.connect("deep-element-added", false, |values| {
+        let element = values.get(2).and_then(|v| v.get::<gst::Element>().ok())?;
+        if element.find_property("max-errors").is_some() {
+            element.set_property_from_value("max-errors", &(-1i32).to_value());
+        }
+        None
+    });
+
+    let streams = Arc::new(Mutex::new(Vec::<StreamCoverage>::new()));
+    {
+        let streams = streams.clone();
+        let pipeline_weak = pipeline.downgrade();
+        decodebin.connect_pad_added(move |_, pad| {
+            let Some(pipeline) = pipeline_weak.upgrade() else {
+                return;
+            };
+            let Ok(sink) = gst::ElementFactory::make("fakesink")
+                .property("sync", false)
+                .property("signal-handoffs", true)
+                .build()
+            else {
+                return;
+            };
+            let index = {
+                let mut streams = streams.lock();
+                let caps = pad.current_caps().unwrap_or_else(|| pad.query_caps(None));
+                let index = streams.len();
+                streams.push(StreamCoverage {
+                    index,
+                    media_type: caps
+                        .structure(0)
+                        .map(|s| s.name().to_string())
+                        .unwrap_or_default(),
+                    buffers: 0,
+                    start_ns: None,
+                    end_ns: None,
+                });
+                index
+            };
+            let streams = streams.clone();
+            sink.connect("handoff", false, move |values| {
+                let buffer = values.get(1).and_then(|v| v.get::<gst::Buffer>().ok())?;
+                let mut streams = streams.lock();
+                let stream = &mut streams[index];
+                stream.buffers += 1;
+                if let Some(pts) = buffer.pts() {
+                    let end = pts + buffer.duration().unwrap_or(gst::ClockTime::ZERO);
+                    let start = stream
+                        .start_ns
+                        .map_or(pts.nseconds(), |s| s.min(pts.nseconds()));
+                    stream.start_ns = Some(start);
+                    stream.end_ns = stream.end_ns.max(Some(end.nseconds()));
+                }
+                None
+            });
+            if pipeline.add(&sink).is_ok() {
+                let _ = sink.sync_state_with_parent();
+                if let Some(sink_pad) = sink.static_pad("sink") {
+                    let _ = pad.link(&sink_pad);
+                }
+            }
+        });
+    }
+
+    let bus = pipeline
+        .bus()
+        .ok_or_else(|| GStreamerMcpError::PipelineError("Pipeline has no bus".to_string()))?;
+    pipeline.set_state(gst::State::Playing).map_err(|_| {
+        GStreamerMcpError::PipelineError(format!("Failed to open {}", input.display()))
+    })?;
+
+    let mut report = VerifyReport::default();
+    loop {
+        let Some(message) = bus.timed_pop_filtered(
+            gst::ClockTime::NONE,
+            &[
+                gst::MessageType::Eos,
+                gst::MessageType::Error,
+                gst::MessageType::Warning,
+                gst::MessageType::DurationChanged,
+                gst::MessageType::AsyncDone,
+            ],
+        ) else {
+            break;
+        };
+        match message.view() {
+            gst::MessageView::Eos(_) => break,
+            gst::MessageView::AsyncDone(_) | gst::MessageView::DurationChanged(_) => {
+                if let Some(duration) = pipeline.query_duration::<gst::ClockTime>() {
+                    report.declared_duration_ns = Some(duration.nseconds());
+                }
+                continue;
+            }
+            _ => {}
+        }
+
+        let position = pipeline.query_position::<gst::ClockTime>();
+        let Some(issue) = issue(&message, position) else {
+            continue;
+        };
+        let fatal = issue.fatal;
+        if fatal {
+            report.error_count += 1;
+        } else {
+            report.warning_count += 1;
+        }
+        if report.issues.len() < MAX_REPORTED_ISSUES {
+            report.issues.push(issue);
+        }
+        if fatal {
+            break;
+        }
+    }
+    let _ = pipeline.set_state(gst::State::Null);
+
+    report.streams = streams.lock().clone();
+    assess(&mut report);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(index: usize, media_type: &str, end_s: f64) -> StreamCoverage {
+        StreamCoverage {
+            index,
+            media_type: media_type.to_string(),
+            buffers: 100,
+            start_ns: Some(0),
+            end_ns: Some((end_s * 1e9) as u64),
+        }
+    }
+
+    #[test]
+    fn test_assess_clean_file() {
+        let mut report = VerifyReport {
+            declared_duration_ns: Some(60_000_000_000),
+            streams: vec![
+                stream(0, "video/x-raw", 60.0),
+                stream(1, "audio/x-raw", 59.8),
+            ],
+            ..Default::default()
+        };
+        assess(&mut report);
+        assert!(report.ok);
+        assert!(!report.truncated);
+        assert_eq!(report.av_end_difference_ns, Some(200_000_000));
+    }
+
+    #[test]
+    fn test_assess_truncated_and_mismatched() {
+        let mut report = VerifyReport {
+            declared_duration_ns: Some(60_000_000_000),
+            streams: vec![
+                stream(0, "video/x-raw", 42.0),
+                stream(1, "audio/x-raw", 30.0),
+            ],
+            warning_count: 3,
+            ..Default::default()
+        };
+        assess(&mut report);
+        assert!(!report.ok);
+        assert!(report.truncated);
+        assert_eq!(report.av_end_difference_ns, Some(12_000_000_000));
+        assert_eq!(report.problems.len(), 3);
+    }
+}
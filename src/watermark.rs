@@ -0,0 +1,115 @@
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use rmcp::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::error::{GStreamerMcpError, Result as McpResult};
+use crate::pipeline::{insert_before_video_outputs, make_element};
+
+/// Element drawing the image (gst-plugins-good gdkpixbuf)
+const OVERLAY_FACTORY: &str = "gdkpixbufoverlay";
+
+/// Distance from the frame edges when no margin is given
+const DEFAULT_MARGIN: i32 = 16;
+
+/// Corner of the frame the watermark is drawn in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum WatermarkPosition {
+    TopLeft,
+    #[default]
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl WatermarkPosition {
+    /// `offset-x`/`offset-y` of gdkpixbufoverlay; negative offsets are
+    /// measured from the right and bottom edges
+    fn offsets(self, margin: i32) -> (i32, i32) {
+        match self {
+            WatermarkPosition::TopLeft => (margin, margin),
+            WatermarkPosition::TopRight => (-margin, margin),
+            WatermarkPosition::BottomLeft => (margin, -margin),
+            WatermarkPosition::BottomRight => (-margin, -margin),
+        }
+    }
+}
+
+/// Logo ("bug") drawn over the video of a pipeline
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct WatermarkOptions {
+    #[schemars(description = "Path of the image to overlay (PNG with transparency recommended)")]
+    pub image: String,
+    #[schemars(
+        description = "Corner to draw the image in: 'top-left', 'top-right', 'bottom-left' or 'bottom-right' (default: 'top-right')"
+    )]
+    pub position: Option<WatermarkPosition>,
+    #[schemars(description = "Opacity between 0.0 (invisible) and 1.0 (opaque) (default: 1.0)")]
+    pub opacity: Option<f64>,
+    #[schemars(description = "Distance from the frame edges in pixels (default: 16)")]
+    pub margin: Option<u32>,
+}
+
+/// Insert `videoconvert ! gdkpixbufoverlay ! videoconvert` in front of every
+/// video encoder and video sink of a pipeline that is not running yet.
+/// Returns the names of the elements that were watermarked.
+pub fn apply_watermark(
+    pipeline: &gst::Pipeline,
+    options: &WatermarkOptions,
+) -> McpResult<Vec<String>> {
+    if !Path::new(&options.image).is_file() {
+        return Err(GStreamerMcpError::Other(format!(
+            "Watermark image '{}' does not exist",
+            options.image
+        )));
+    }
+    let opacity = options.opacity.unwrap_or(1.0);
+    if !(0.0..=1.0).contains(&opacity) {
+        return Err(GStreamerMcpError::PropertyError(format!(
+            "Watermark opacity {} must be between 0.0 and 1.0",
+            opacity
+        )));
+    }
+    let margin = options
+        .margin
+        .map_or(Ok(DEFAULT_MARGIN), i32::try_from)
+        .map_err(|_| GStreamerMcpError::PropertyError("Watermark margin is too large".into()))?;
+    let (offset_x, offset_y) = options.position.unwrap_or_default().offsets(margin);
+
+    let applied = insert_before_video_outputs(pipeline, "watermark", |i| {
+        let convert_in = make_element("videoconvert", format!("watermark{}_convert_in", i))?;
+        let overlay = make_element(OVERLAY_FACTORY, format!("watermark{}", i))?;
+        let convert_out = make_element("videoconvert", format!("watermark{}_convert_out", i))?;
+        overlay.set_property("location", &options.image);
+        overlay.set_property("alpha", opacity);
+        overlay.set_property("offset-x", offset_x);
+        overlay.set_property("offset-y", offset_y);
+        Ok(vec![convert_in, overlay, convert_out])
+    })?;
+
+    if applied.is_empty() {
+        return Err(GStreamerMcpError::PipelineError(
+            "Pipeline has no linked video encoder or video sink to watermark".to_string(),
+        ));
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermark_offsets() {
+        assert_eq!(WatermarkPosition::TopLeft.offsets(10), (10, 10));
+        assert_eq!(WatermarkPosition::TopRight.offsets(10), (-10, 10));
+        assert_eq!(WatermarkPosition::BottomLeft.offsets(10), (10, -10));
+        assert_eq!(WatermarkPosition::BottomRight.offsets(10), (-10, -10));
+
+        let options: WatermarkOptions =
+            serde_json::from_str(r#"{"image": "logo.png", "position": "bottom-right"}"#).unwrap();
+        assert_eq!(options.position, Some(WatermarkPosition::BottomRight));
+    }
+}